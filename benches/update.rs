@@ -0,0 +1,48 @@
+// 物理演算の update() をネイティブで計測する（cargo bench --bench update）
+// perf や cargo flamegraph で同じコードを調べたり、physics-bench の wasm 版の数字と比べて WASM の分のコストを見積もる
+// 初期状態は physics-bench と同じ固定シードで作る
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use particle_webgl::native::NativeBenchmark;
+use particle_webgl::scene::SceneKind;
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+const SEED: u64 = 1;
+const PARTICLE_COUNTS: [usize; 4] = [1_000, 10_000, 100_000, 500_000];
+// 最初の数フレームは測らない（生成直後の確保やキャッシュが落ち着くまで。10 フレームではまだ全体が動き回っている）
+const WARMUP_FRAMES: u32 = 10;
+
+fn update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update");
+    for count in PARTICLE_COUNTS {
+        let mut benchmark =
+            NativeBenchmark::with_seed(SceneKind::Particles, WIDTH, HEIGHT, count, SEED).unwrap();
+        benchmark.simulate_frames(WARMUP_FRAMES);
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| benchmark.update())
+        });
+    }
+    group.finish();
+}
+
+// 爆発で全体が速く動いている状態（壁での跳ね返りが多い）
+fn update_after_explosion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_after_explosion");
+    for count in PARTICLE_COUNTS {
+        let mut benchmark =
+            NativeBenchmark::with_seed(SceneKind::Particles, WIDTH, HEIGHT, count, SEED).unwrap();
+        group.throughput(Throughput::Elements(count as u64));
+        group.bench_function(BenchmarkId::from_parameter(count), |b| {
+            b.iter(|| {
+                benchmark.explode(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+                benchmark.update();
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, update, update_after_explosion);
+criterion_main!(benches);
@@ -0,0 +1,213 @@
+// ブラウザと同じシーンと物理演算をネイティブの窓で動かし、ブラウザのレポートと比べる基準の数字を出す
+//
+//   cargo run --release --example native --features native-window -- [シーン名] [パーティクル数] [フレーム数]
+//
+// シーン名は SceneKind::name()（既定は particles）。フレーム数を渡すとその数だけ描いて終わる
+// 1秒ごとに FPS と update()・render() の平均時間を、終わるときに全体の平均を標準出力に書く
+// クリックした位置で爆発させる
+
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+use particle_webgl::native::{FrameTiming, NativeBenchmark, Renderer};
+use particle_webgl::scene::SceneKind;
+use particle_webgl::timing;
+use winit::application::ApplicationHandler;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
+use winit::window::{Window, WindowId};
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+const DEFAULT_PARTICLE_COUNT: usize = 10_000;
+// FPS を書く間隔(ms)
+const REPORT_INTERVAL_MS: f64 = 1000.0;
+
+// update()・render() の時間の合計
+#[derive(Default)]
+struct Totals {
+    frames: u32,
+    update_ms: f64,
+    render_ms: f64,
+}
+
+impl Totals {
+    fn add(&mut self, timing: FrameTiming) {
+        self.frames += 1;
+        self.update_ms += timing.update_ms;
+        self.render_ms += timing.render_ms;
+    }
+
+    fn print(&self, label: &str, elapsed_ms: f64) {
+        if self.frames == 0 {
+            return;
+        }
+        let frames = self.frames as f64;
+        println!(
+            "{}: {:.1} fps, update {:.3} ms, render {:.3} ms ({} frames)",
+            label,
+            frames * 1000.0 / elapsed_ms.max(1e-3),
+            self.update_ms / frames,
+            self.render_ms / frames,
+            self.frames
+        );
+    }
+}
+
+// 塗り終わった RGBA を softbuffer の 0RGB に詰め替えて窓に出す
+struct WindowRenderer {
+    surface: softbuffer::Surface<Rc<Window>, Rc<Window>>,
+}
+
+impl Renderer for WindowRenderer {
+    fn present(&mut self, width: u32, height: u32, rgba: &[u8]) {
+        let (Some(w), Some(h)) = (NonZeroU32::new(width), NonZeroU32::new(height)) else {
+            return;
+        };
+        if let Err(err) = self.surface.resize(w, h) {
+            eprintln!("resize failed: {}", err);
+            return;
+        }
+        let mut buffer = match self.surface.buffer_mut() {
+            Ok(buffer) => buffer,
+            Err(err) => {
+                eprintln!("buffer_mut failed: {}", err);
+                return;
+            }
+        };
+        for (pixel, rgba) in buffer.iter_mut().zip(rgba.chunks_exact(4)) {
+            *pixel = (rgba[0] as u32) << 16 | (rgba[1] as u32) << 8 | rgba[2] as u32;
+        }
+        if let Err(err) = buffer.present() {
+            eprintln!("present failed: {}", err);
+        }
+    }
+}
+
+struct App {
+    benchmark: NativeBenchmark,
+    // 描くフレーム数（None なら窓を閉じるまで）
+    frame_limit: Option<u32>,
+    window: Option<Rc<Window>>,
+    renderer: Option<WindowRenderer>,
+    cursor: PhysicalPosition<f64>,
+    started_ms: f64,
+    total: Totals,
+    interval_started_ms: f64,
+    interval: Totals,
+}
+
+impl App {
+    fn draw_frame(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(renderer) = &mut self.renderer else {
+            return;
+        };
+        let timing = self.benchmark.frame(renderer);
+        self.total.add(timing);
+        self.interval.add(timing);
+
+        let now = timing::now_ms();
+        if now - self.interval_started_ms >= REPORT_INTERVAL_MS {
+            self.interval
+                .print(self.benchmark.kind().name(), now - self.interval_started_ms);
+            self.interval = Totals::default();
+            self.interval_started_ms = now;
+        }
+        if self
+            .frame_limit
+            .is_some_and(|limit| self.total.frames >= limit)
+        {
+            event_loop.exit();
+        }
+    }
+}
+
+impl ApplicationHandler for App {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
+        if self.window.is_some() {
+            return;
+        }
+        // 塗るピクセル数をブラウザの同じ大きさのキャンバスとそろえるため、物理ピクセルで決めて変えさせない
+        let attributes = Window::default_attributes()
+            .with_title(format!(
+                "particle-webgl native: {}",
+                self.benchmark.kind().name()
+            ))
+            .with_inner_size(PhysicalSize::new(WIDTH, HEIGHT))
+            .with_resizable(false);
+        let window = match event_loop.create_window(attributes) {
+            Ok(window) => Rc::new(window),
+            Err(err) => {
+                eprintln!("failed to create window: {}", err);
+                return event_loop.exit();
+            }
+        };
+        let surface = softbuffer::Context::new(window.clone())
+            .and_then(|context| softbuffer::Surface::new(&context, window.clone()));
+        match surface {
+            Ok(surface) => self.renderer = Some(WindowRenderer { surface }),
+            Err(err) => {
+                eprintln!("failed to create surface: {}", err);
+                return event_loop.exit();
+            }
+        }
+        self.window = Some(window);
+        self.started_ms = timing::now_ms();
+        self.interval_started_ms = self.started_ms;
+    }
+
+    fn window_event(&mut self, event_loop: &ActiveEventLoop, _id: WindowId, event: WindowEvent) {
+        match event {
+            WindowEvent::CloseRequested => event_loop.exit(),
+            WindowEvent::RedrawRequested => self.draw_frame(event_loop),
+            WindowEvent::CursorMoved { position, .. } => self.cursor = position,
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } => self
+                .benchmark
+                .explode(self.cursor.x as f32, self.cursor.y as f32),
+            _ => {}
+        }
+    }
+
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        if let Some(window) = &self.window {
+            window.request_redraw();
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let scene_name = args.next().unwrap_or_else(|| String::from("particles"));
+    let kind = SceneKind::ALL
+        .into_iter()
+        .find(|kind| kind.name() == scene_name)
+        .ok_or_else(|| format!("unknown scene: {}", scene_name))?;
+    let particle_count = match args.next() {
+        Some(count) => count.parse()?,
+        None => DEFAULT_PARTICLE_COUNT,
+    };
+    let frame_limit = args.next().map(|frames| frames.parse()).transpose()?;
+
+    let mut app = App {
+        benchmark: NativeBenchmark::new(kind, WIDTH, HEIGHT, particle_count)?,
+        frame_limit,
+        window: None,
+        renderer: None,
+        cursor: PhysicalPosition::new(0.0, 0.0),
+        started_ms: 0.0,
+        total: Totals::default(),
+        interval_started_ms: 0.0,
+        interval: Totals::default(),
+    };
+    let event_loop = EventLoop::new()?;
+    event_loop.set_control_flow(ControlFlow::Poll);
+    event_loop.run_app(&mut app)?;
+
+    app.total.print("total", timing::now_ms() - app.started_ms);
+    Ok(())
+}
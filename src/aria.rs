@@ -0,0 +1,80 @@
+use web_sys::Element;
+
+use crate::error::BenchError;
+use crate::i18n::{self, tr, Text};
+use crate::scene::SceneKind;
+use crate::timing;
+
+// 読み上げを更新する間隔の下限(ms)。短すぎるとスクリーンリーダーが読み終わらないうちに次が来る
+const MIN_INTERVAL_MS: f64 = 2_000.0;
+// 画面には出さず、読み上げにだけ残す（よく使われる visually-hidden の指定）
+const HIDDEN_STYLE: &str = "position:absolute;width:1px;height:1px;margin:-1px;padding:0;\
+    overflow:hidden;clip:rect(0,0,0,0);white-space:nowrap;border:0";
+
+// パーティクル数・FPS・シーンを文章にして書き込む ARIA のライブリージョン
+// 要素は document.body に足し、外したら（drop したら）取り除く
+pub(crate) struct LiveRegion {
+    element: Element,
+    interval_ms: f64,
+    // 前回書き込んだ時刻と、それ以降に描いたフレーム数
+    window_start: f64,
+    frames: u32,
+    // 同じ文章を書き直すと読み上げ直されるので、変わったときだけ書く
+    last_text: String,
+}
+
+impl LiveRegion {
+    pub fn attach(interval_ms: f64) -> Result<LiveRegion, String> {
+        let unavailable = || BenchError::DocumentUnavailable.to_string();
+        let document = web_sys::window()
+            .and_then(|window| window.document())
+            .ok_or_else(unavailable)?;
+        let body = document.body().ok_or_else(unavailable)?;
+        let element = document.create_element("div").map_err(|_| unavailable())?;
+        for (name, value) in [
+            ("role", "status"),
+            ("aria-live", "polite"),
+            ("aria-atomic", "true"),
+            ("style", HIDDEN_STYLE),
+        ] {
+            element
+                .set_attribute(name, value)
+                .map_err(|_| unavailable())?;
+        }
+        body.append_child(&element).map_err(|_| unavailable())?;
+        Ok(LiveRegion {
+            element,
+            interval_ms: interval_ms.max(MIN_INTERVAL_MS),
+            window_start: timing::now_ms(),
+            frames: 0,
+            last_text: String::new(),
+        })
+    }
+
+    // render() のたびに呼ぶ
+    pub fn note_frame(&mut self, particle_count: usize, scene: SceneKind) {
+        self.frames += 1;
+        let now = timing::now_ms();
+        let elapsed = now - self.window_start;
+        if elapsed < self.interval_ms {
+            return;
+        }
+        let fps = (self.frames as f64 * 1000.0 / elapsed).round();
+        self.window_start = now;
+        self.frames = 0;
+        let text = tr(
+            Text::AriaSummary,
+            &[&particle_count, &fps, &i18n::scene_label(scene)],
+        );
+        if text != self.last_text {
+            self.element.set_text_content(Some(&text));
+            self.last_text = text;
+        }
+    }
+}
+
+impl Drop for LiveRegion {
+    fn drop(&mut self) {
+        self.element.remove();
+    }
+}
@@ -0,0 +1,101 @@
+use wasm_bindgen::prelude::*;
+
+use crate::math;
+use crate::simulation::Particle;
+
+// パーティクルを引き寄せる点（strength が負なら押しのける）
+// add_attractor() で置き、update() のたびに半径の中のパーティクルへ速度を加える
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Attractor {
+    // remove_attractor() などで指定する番号
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    // 中心での速度の増分(px/フレーム)。半径まで一定の割合で弱まる
+    pub strength: f32,
+    // この距離(px)より遠いパーティクルは動かさない
+    pub radius: f32,
+}
+
+impl Attractor {
+    fn apply(&self, p: &mut Particle) {
+        let dx = self.x - p.x;
+        let dy = self.y - p.y;
+        let dist = math::sqrt(dx * dx + dy * dy);
+        // 中心にいるパーティクルは向きが決まらないのでそのまま
+        if dist >= self.radius || dist < 1e-3 {
+            return;
+        }
+        let force = self.strength * (1.0 - dist / self.radius);
+        p.vx += dx / dist * force;
+        p.vy += dy / dist * force;
+    }
+}
+
+// 置かれている引力点（番号は置いた順に振り、使い回さない）
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Attractors {
+    list: Vec<Attractor>,
+    next_id: u32,
+}
+
+impl Attractors {
+    // 範囲外の値は丸める（半径は 1px 以上、NaN などは力 0）
+    pub fn add(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        let finite_or = |value: f32, default: f32| if value.is_finite() { value } else { default };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.list.push(Attractor {
+            id,
+            x: finite_or(x, 0.0),
+            y: finite_or(y, 0.0),
+            strength: finite_or(strength, 0.0),
+            radius: finite_or(radius, 1.0).max(1.0),
+        });
+        id
+    }
+
+    // 見つからなければ false
+    pub fn remove(&mut self, id: u32) -> bool {
+        let before = self.list.len();
+        self.list.retain(|attractor| attractor.id != id);
+        self.list.len() != before
+    }
+
+    // マウスで引きずるときなどに位置だけ動かす（見つからなければ false）
+    pub fn move_to(&mut self, id: u32, x: f32, y: f32) -> bool {
+        match self.list.iter_mut().find(|attractor| attractor.id == id) {
+            Some(attractor) if x.is_finite() && y.is_finite() => {
+                attractor.x = x;
+                attractor.y = y;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    pub fn list(&self) -> Vec<Attractor> {
+        self.list.clone()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // すべての引力点の力を1パーティクルに加える
+    pub fn apply(&self, p: &mut Particle) {
+        for attractor in &self.list {
+            attractor.apply(p);
+        }
+    }
+}
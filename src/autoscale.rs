@@ -0,0 +1,158 @@
+use wasm_bindgen::prelude::*;
+
+use crate::stats::{self, Aggregation};
+use crate::timing;
+
+// 1回の判定に使う時間(ms)
+const WINDOW_MS: f64 = 1_000.0;
+// 予算を守れた数と守れなかった数の差がこの割合より小さくなったら収束とみなす
+const CONVERGE_RATIO: f64 = 0.02;
+
+// 目標のフレーム時間（例: 16.6ms）を守れる一番多いパーティクル数を探す
+// 毎フレーム record_frame() に update() + render() の時間を渡すと、1秒ごとにその間の中央値を予算と比べ、
+// 守れていれば数を増やし、守れなければ減らす（境目が分かるまでは2倍・半分、分かってからは二分探索）
+// 収束した後も判定を続け、守れなくなったら探し直す
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct AutoScaler {
+    target_ms: f64,
+    max_count: usize,
+    count: usize,
+    // 今の判定期間のフレーム時間と、その始まりの時刻
+    window: Vec<f64>,
+    window_start: Option<f64>,
+    // 予算を守れた一番多い数と、守れなかった一番少ない数
+    sustained: Option<usize>,
+    failed: Option<usize>,
+    converged: bool,
+    // 判定した回数（秒数）
+    windows: u32,
+    last_median_ms: f64,
+}
+
+#[wasm_bindgen]
+impl AutoScaler {
+    // initial_count から探し始め、max_count（バックエンドを作ったときの数）は超えない
+    #[wasm_bindgen(constructor)]
+    pub fn new(target_ms: f64, initial_count: usize, max_count: usize) -> AutoScaler {
+        let max_count = max_count.max(1);
+        AutoScaler {
+            target_ms: if target_ms.is_finite() && target_ms > 0.0 {
+                target_ms
+            } else {
+                16.6
+            },
+            max_count,
+            count: initial_count.clamp(1, max_count),
+            window: Vec::new(),
+            window_start: None,
+            sustained: None,
+            failed: None,
+            converged: false,
+            windows: 0,
+            last_median_ms: 0.0,
+        }
+    }
+
+    // 1フレーム分の時間(ms)を渡す。数を変えるときは新しい数を返す（set_particle_count() に渡す）
+    pub fn record_frame(&mut self, frame_ms: f64) -> Option<usize> {
+        let now = timing::now_ms();
+        let start = *self.window_start.get_or_insert(now);
+        if frame_ms.is_finite() {
+            self.window.push(frame_ms);
+        }
+        if now - start < WINDOW_MS || self.window.is_empty() {
+            return None;
+        }
+        let median = stats::summarize(&self.window, Aggregation::Median).center;
+        self.window.clear();
+        self.window_start = None;
+        self.windows += 1;
+        self.last_median_ms = median;
+
+        let previous = self.count;
+        self.judge(median <= self.target_ms);
+        (self.count != previous).then_some(self.count)
+    }
+
+    // 今使っている（次の判定で試す）パーティクル数
+    pub fn get_particle_count(&self) -> usize {
+        self.count
+    }
+
+    // 予算を守れた一番多い数（まだなければ 0）
+    pub fn get_sustained_count(&self) -> usize {
+        self.sustained.unwrap_or(0)
+    }
+
+    pub fn is_converged(&self) -> bool {
+        self.converged
+    }
+
+    pub fn get_target_ms(&self) -> f64 {
+        self.target_ms
+    }
+
+    pub fn get_windows(&self) -> u32 {
+        self.windows
+    }
+
+    // 直前の判定期間のフレーム時間の中央値(ms)
+    pub fn get_last_median_ms(&self) -> f64 {
+        self.last_median_ms
+    }
+
+    // 今の数から探し直す
+    pub fn reset(&mut self) {
+        *self = AutoScaler::new(self.target_ms, self.count, self.max_count);
+    }
+}
+
+impl AutoScaler {
+    fn judge(&mut self, within_budget: bool) {
+        if self.converged {
+            if within_budget {
+                return;
+            }
+            // 収束した数でも守れなくなった（他の負荷が増えたなど）ので、下に向かって探し直す
+            self.converged = false;
+            self.sustained = None;
+        }
+        if within_budget {
+            self.sustained = Some(self.sustained.map_or(self.count, |n| n.max(self.count)));
+        } else {
+            self.failed = Some(self.failed.map_or(self.count, |n| n.min(self.count)));
+        }
+
+        self.count = match (self.sustained, self.failed) {
+            // 上限でも守れた
+            (Some(sustained), None) if sustained >= self.max_count => {
+                self.converged = true;
+                sustained
+            }
+            (Some(sustained), None) => (sustained * 2).min(self.max_count),
+            // 1個でも守れない
+            (None, Some(1)) => {
+                self.converged = true;
+                1
+            }
+            (None, Some(failed)) => (failed / 2).max(1),
+            (Some(sustained), Some(failed)) if sustained >= failed => {
+                // 前に守れなかった数を今は守れた（負荷が減った）ので、上を探し直す
+                self.failed = None;
+                (sustained * 2).min(self.max_count)
+            }
+            (Some(sustained), Some(failed)) => {
+                let gap = (sustained as f64 * CONVERGE_RATIO).max(1.0);
+                if (failed - sustained) as f64 <= gap {
+                    self.converged = true;
+                    self.failed = None;
+                    sustained
+                } else {
+                    sustained + (failed - sustained) / 2
+                }
+            }
+            (None, None) => self.count,
+        };
+    }
+}
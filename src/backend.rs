@@ -0,0 +1,1303 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, OffscreenCanvas, WebGl2RenderingContext,
+    WebGlRenderingContext,
+};
+
+use crate::aria::LiveRegion;
+use crate::attractor::Attractor;
+use crate::bounds::{BoundaryMode, OutOfBounds};
+use crate::canvas2d::ParticleSystemCanvas2D;
+use crate::capture::CapturedBuffer;
+use crate::color_pipeline::ColorPipeline;
+use crate::color_theme::ColorMode;
+use crate::context::{CanvasById, CanvasElement, ContextSource, OffscreenCanvasSource};
+use crate::draw_strategy::DrawStrategy;
+use crate::emitter::Emitter;
+use crate::events::{BenchmarkEvent, EventKind};
+use crate::explosion::ExplosionConfig;
+use crate::gpu::GpuCanvasContext;
+use crate::gpu_physics::PhysicsMode;
+use crate::i18n::{tr, Text};
+use crate::init::{BuildOptions, InitTimings};
+use crate::input::{InputAction, InputHandlers};
+use crate::lod::LodMode;
+use crate::memory::DEFAULT_MEMORY_BUDGET;
+use crate::obstacle::Obstacle;
+use crate::panel::OverlayRect;
+use crate::particle_shape::ParticleShape;
+use crate::particle_style::ParticleStyle;
+use crate::physics::SimulationConfig;
+use crate::picking::ParticleInfo;
+use crate::projection::ProjectionMode;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::scene::{Atlas, SceneDescription, SceneKind};
+use crate::tuning::TuningProfile;
+use crate::upload::{BufferLayout, UploadStrategy};
+use crate::webgl2::ParticleSystemWebGl2;
+use crate::webgpu::ParticleSystemWebGpu;
+use crate::workload::{Action, Player, Recorder, Workload};
+use crate::ParticleSystem;
+
+// 描画バックエンドの種類（値が小さいほど高機能）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BackendKind {
+    WebGpu = 0,
+    WebGl2 = 1,
+    WebGl = 2,
+    Canvas2D = 3,
+    // Canvas2D の描画APIを使わず、WASM内で塗って putImageData で転送する
+    Software = 4,
+}
+
+impl BackendKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            BackendKind::WebGpu => "webgpu",
+            BackendKind::WebGl2 => "webgl2",
+            BackendKind::WebGl => "webgl",
+            BackendKind::Canvas2D => "canvas2d",
+            BackendKind::Software => "software",
+        }
+    }
+}
+
+// create_best_backend() に渡す設定
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct BackendConfig {
+    pub particle_count: usize,
+    pub memory_budget: u64,
+    // true ならパーティクルを生成せずに返し、init_chunk() で生成する
+    pub progressive: bool,
+    // true なら各バックエンドを strict モードで作り、WebGLが使えなくても Canvas2D に落とさずエラーにする
+    pub strict: bool,
+    // 重力・跳ね返り・摩擦・点の大きさ（どのバックエンドにも同じ値を渡す）
+    pub simulation: SimulationConfig,
+    // Some なら同じシードのバックエンドはどれも同じ初期状態のパーティクルで始まる
+    pub seed: Option<u64>,
+}
+
+#[wasm_bindgen]
+impl BackendConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(particle_count: usize) -> BackendConfig {
+        BackendConfig {
+            particle_count,
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            progressive: false,
+            strict: false,
+            simulation: SimulationConfig::default(),
+            seed: None,
+        }
+    }
+}
+
+impl BackendConfig {
+    fn options(&self) -> BuildOptions {
+        BuildOptions {
+            memory_budget: self.memory_budget,
+            progressive: self.progressive,
+            strict: self.strict,
+            simulation: self.simulation,
+            seed: self.seed,
+            ..BuildOptions::default()
+        }
+    }
+}
+
+// どれも大きいので箱に入れて、Backend の大きさを一番大きいものに揃えない
+enum Inner {
+    WebGpu(Box<ParticleSystemWebGpu>),
+    WebGl2(Box<ParticleSystemWebGl2>),
+    WebGl(Box<ParticleSystem>),
+    Canvas2D(Box<ParticleSystemCanvas2D>),
+}
+
+// どのバックエンドでも同じように呼べるラッパー
+#[wasm_bindgen]
+pub struct Backend {
+    kind: BackendKind,
+    inner: Inner,
+    particle_count: usize,
+    // Some なら操作を記録中
+    recorder: Option<Recorder>,
+    // Some なら記録した操作を再生中
+    player: Option<Player>,
+    // load_scenario() で読み込んだ、play() で再生する記録
+    scenario: Option<Workload>,
+    // Some なら状態を ARIA のライブリージョンに書き込む
+    live_region: Option<LiveRegion>,
+}
+
+// 選ばれたバックエンドのメソッドをそのまま呼ぶ
+macro_rules! dispatch {
+    ($inner:expr, $system:ident => $call:expr) => {
+        match $inner {
+            Inner::WebGpu($system) => $call,
+            Inner::WebGl2($system) => $call,
+            Inner::WebGl($system) => $call,
+            Inner::Canvas2D($system) => $call,
+        }
+    };
+}
+
+// 高機能な順に試し、最初に作れたバックエンドを返す
+// 飛ばしたバックエンドとその理由、選ばれたバックエンドはイベントとして記録する
+// WebGPU は生成が非同期なので試さない（create_best_backend_async() を使う）
+#[wasm_bindgen]
+pub fn create_best_backend(canvas_id: &str, config: &BackendConfig) -> Result<Backend, JsValue> {
+    create_best_backend_for_canvas(&CanvasById(canvas_id).canvas()?, config)
+}
+
+// WebGPU から順に試す create_best_backend()
+#[wasm_bindgen]
+pub async fn create_best_backend_async(
+    canvas_id: String,
+    config: BackendConfig,
+) -> Result<Backend, JsValue> {
+    create_best_backend_for_canvas_async(CanvasById(&canvas_id).canvas()?, config).await
+}
+
+// フレームワークが持っている canvas 要素に create_best_backend() と同じ順で作る
+// （id が無くても、ドキュメントに追加前でもよい）
+#[wasm_bindgen]
+pub fn create_best_backend_for_canvas(
+    canvas: &HtmlCanvasElement,
+    config: &BackendConfig,
+) -> Result<Backend, JsValue> {
+    let skipped = vec![tr(Text::BackendAsyncOnly, &[&BackendKind::WebGpu.name()])];
+    create_best_webgl_or_canvas2d(&CanvasElement(canvas), config, skipped)
+}
+
+// WebGPU から順に試す create_best_backend_for_canvas()
+#[wasm_bindgen]
+pub async fn create_best_backend_for_canvas_async(
+    canvas: HtmlCanvasElement,
+    config: BackendConfig,
+) -> Result<Backend, JsValue> {
+    create_best_backend_from(&CanvasElement(&canvas), &config).await
+}
+
+// Worker に渡した OffscreenCanvas に create_best_backend() と同じ順で作る
+#[wasm_bindgen]
+pub fn create_best_backend_offscreen(
+    canvas: &OffscreenCanvas,
+    config: &BackendConfig,
+) -> Result<Backend, JsValue> {
+    let skipped = vec![tr(Text::BackendAsyncOnly, &[&BackendKind::WebGpu.name()])];
+    create_best_webgl_or_canvas2d(&OffscreenCanvasSource(canvas), config, skipped)
+}
+
+// WebGPU から順に試す create_best_backend_offscreen()
+#[wasm_bindgen]
+pub async fn create_best_backend_offscreen_async(
+    canvas: OffscreenCanvas,
+    config: BackendConfig,
+) -> Result<Backend, JsValue> {
+    create_best_backend_from(&OffscreenCanvasSource(&canvas), &config).await
+}
+
+async fn create_best_backend_from<S>(source: &S, config: &BackendConfig) -> Result<Backend, JsValue>
+where
+    S: ContextSource<GpuCanvasContext>
+        + ContextSource<WebGl2RenderingContext>
+        + ContextSource<WebGlRenderingContext>
+        + ContextSource<CanvasRenderingContext2d>,
+{
+    match Backend::create_async(BackendKind::WebGpu, source, config).await {
+        Ok(mut backend) => {
+            backend.emit_event(
+                EventKind::BackendSelected,
+                &tr(Text::BackendSelected, &[&BackendKind::WebGpu.name()]),
+            );
+            Ok(backend)
+        }
+        Err(error) => {
+            let skipped = vec![format!("webgpu: {}", describe_error(&error))];
+            create_best_webgl_or_canvas2d(source, config, skipped)
+        }
+    }
+}
+
+fn create_best_webgl_or_canvas2d<S>(
+    source: &S,
+    config: &BackendConfig,
+    mut skipped: Vec<String>,
+) -> Result<Backend, JsValue>
+where
+    S: ContextSource<WebGl2RenderingContext>
+        + ContextSource<WebGlRenderingContext>
+        + ContextSource<CanvasRenderingContext2d>,
+{
+    // WebGL2 に対応していなくても WebGL1 は使えることが多いので、strict でも次を試す
+    let webgl2 = Backend::create(BackendKind::WebGl2, source, config)
+        .map_err(|error| skipped.push(format!("webgl2: {}", describe_error(&error))));
+    let mut backend = match webgl2 {
+        Ok(backend) => backend,
+        Err(()) => match Backend::create(BackendKind::WebGl, source, config) {
+            Ok(backend) => backend,
+            Err(error) if config.strict => return Err(error),
+            Err(error) => {
+                skipped.push(format!("webgl: {}", describe_error(&error)));
+                // WebGLのコンテキストを取得できていた場合、同じキャンバスでは2Dも取得できずにエラーになる
+                Backend::create(BackendKind::Canvas2D, source, config)?
+            }
+        },
+    };
+    let kind = backend.kind;
+    for reason in &skipped {
+        backend.emit_event(EventKind::FallbackUsed, reason);
+    }
+    backend.emit_event(
+        EventKind::BackendSelected,
+        &tr(Text::BackendSelected, &[&kind.name()]),
+    );
+    Ok(backend)
+}
+
+pub(crate) fn describe_error(error: &JsValue) -> String {
+    error.as_string().unwrap_or_else(|| format!("{:?}", error))
+}
+
+impl Backend {
+    // kind のバックエンドを source に作る（WebGPU は create_async() でしか作れない）
+    pub(crate) fn create<S>(
+        kind: BackendKind,
+        source: &S,
+        config: &BackendConfig,
+    ) -> Result<Backend, JsValue>
+    where
+        S: ContextSource<WebGl2RenderingContext>
+            + ContextSource<WebGlRenderingContext>
+            + ContextSource<CanvasRenderingContext2d>,
+    {
+        let (count, options) = (config.particle_count, config.options());
+        let inner = match kind {
+            BackendKind::WebGl2 => Inner::WebGl2(Box::new(ParticleSystemWebGl2::from_source(
+                source, count, options,
+            )?)),
+            BackendKind::WebGl => Inner::WebGl(Box::new(ParticleSystem::from_source(
+                source, count, options,
+            )?)),
+            BackendKind::Canvas2D => Inner::Canvas2D(Box::new(
+                ParticleSystemCanvas2D::from_source(source, count, options)?,
+            )),
+            // 描き方だけが違う Canvas2D のシステムをそのまま使う
+            BackendKind::Software => {
+                let mut system = ParticleSystemCanvas2D::from_source(source, count, options)?;
+                system.enable_software();
+                Inner::Canvas2D(Box::new(system))
+            }
+            BackendKind::WebGpu => return Err(tr(Text::BackendAsyncOnly, &[&kind.name()]).into()),
+        };
+        Ok(Backend::wrap(kind, inner, count))
+    }
+
+    // WebGPU も含めてどのバックエンドでも作れる create()
+    pub(crate) async fn create_async<S>(
+        kind: BackendKind,
+        source: &S,
+        config: &BackendConfig,
+    ) -> Result<Backend, JsValue>
+    where
+        S: ContextSource<GpuCanvasContext>
+            + ContextSource<WebGl2RenderingContext>
+            + ContextSource<WebGlRenderingContext>
+            + ContextSource<CanvasRenderingContext2d>,
+    {
+        if kind != BackendKind::WebGpu {
+            return Backend::create(kind, source, config);
+        }
+        let (count, options) = (config.particle_count, config.options());
+        let system = ParticleSystemWebGpu::from_source(source, count, options).await?;
+        Ok(Backend::wrap(kind, Inner::WebGpu(Box::new(system)), count))
+    }
+
+    // set_target_fps() の上限を超えない回なら描く。描いたら true
+    pub(crate) fn render_capped(&mut self, timestamp: f64) -> bool {
+        let render = dispatch!(&mut self.inner, system => system.cap_frame(timestamp));
+        if render {
+            self.render();
+        }
+        render
+    }
+
+    fn wrap(kind: BackendKind, inner: Inner, particle_count: usize) -> Backend {
+        Backend {
+            kind,
+            inner,
+            particle_count,
+            recorder: None,
+            player: None,
+            scenario: None,
+            live_region: None,
+        }
+    }
+
+    pub(crate) fn emit_event(&mut self, kind: EventKind, message: &str) {
+        dispatch!(&mut self.inner, system => system.emit_event(kind, message))
+    }
+
+    // 出した描画が終わるまで待つ（待てないバックエンドでは false）
+    pub(crate) fn finish_gpu(&self) -> bool {
+        dispatch!(&self.inner, system => system.finish_gpu())
+    }
+
+    // 入力のコールバックが積んだ操作を、記録にも残るようにここから行う
+    fn apply_input(&mut self) {
+        for action in dispatch!(&mut self.inner, system => system.take_input()) {
+            match action {
+                InputAction::Explode { x, y } => self.explode(x, y),
+                InputAction::Stir { x, y, dx, dy } => self.stir(x, y, dx, dy),
+            }
+        }
+    }
+
+    pub(crate) fn input_handlers(&self) -> Option<&InputHandlers> {
+        dispatch!(&self.inner, system => system.input_handlers())
+    }
+
+    // render() で描いたフレームの上に rects を重ねる（重ねられなければ false）
+    pub(crate) fn draw_overlay(&mut self, rects: &[OverlayRect]) -> bool {
+        dispatch!(&mut self.inner, system => system.draw_overlay(rects))
+    }
+
+    pub(crate) fn scene_atlas_mut(&mut self) -> Option<&mut Atlas> {
+        dispatch!(&mut self.inner, system => system.scene_atlas_mut())
+    }
+
+    fn record(&mut self, action: Action) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(action);
+        }
+    }
+
+    // 記録にはキャンバスの大きさに対する割合で残す
+    fn normalize(&self, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = dispatch!(&self.inner, system => system.size());
+        (x / width, y / height)
+    }
+
+    fn denormalize(&self, x: f32, y: f32) -> (f32, f32) {
+        let (width, height) = dispatch!(&self.inner, system => system.size());
+        (x * width, y * height)
+    }
+
+    // 再生中なら、このフレームで行う操作をすべて行う
+    fn replay_frame(&mut self) {
+        let Some(player) = &mut self.player else {
+            return;
+        };
+        let steps = player.advance().to_vec();
+        let finished = player.is_finished();
+        for step in steps {
+            match step.action {
+                Action::Explode { x, y } => {
+                    let (x, y) = self.denormalize(x, y);
+                    self.explode(x, y);
+                }
+                Action::Implode { x, y } => {
+                    let (x, y) = self.denormalize(x, y);
+                    self.implode(x, y);
+                }
+                Action::Stir { x, y, dx, dy } => {
+                    let (x, y) = self.denormalize(x, y);
+                    let (dx, dy) = self.denormalize(dx, dy);
+                    self.stir(x, y, dx, dy);
+                }
+                Action::ScheduleExplosion { x, y, delay_frames } => {
+                    let (x, y) = self.denormalize(x, y);
+                    self.schedule_explosion(x, y, delay_frames);
+                }
+                Action::PointerMove { x, y, dx, dy } => {
+                    let (x, y) = self.denormalize(x, y);
+                    let (dx, dy) = self.denormalize(dx, dy);
+                    self.pointer_move(x, y, dx, dy);
+                }
+                Action::PointerDrag { x, y, strength } => {
+                    let (x, y) = self.denormalize(x, y);
+                    self.pointer_drag(x, y, strength);
+                }
+                // 記録したときに切り替えられたシーンなので、失敗したらそのシーンだけ飛ばす
+                Action::SwitchScene(kind) => {
+                    let _ = self.switch_scene(kind);
+                }
+                Action::SetLoad(load) => self.set_load(load),
+                Action::Reset => self.reset(),
+                Action::AddAttractor {
+                    x,
+                    y,
+                    strength,
+                    radius,
+                    id,
+                } => {
+                    let (x, y) = self.denormalize(x, y);
+                    let actual = self.add_attractor(x, y, strength, radius);
+                    if let Some(player) = &mut self.player {
+                        player.map_attractor(id, actual);
+                    }
+                }
+                Action::RemoveAttractor { id } => {
+                    if let Some(actual) = self.replayed_attractor(id) {
+                        self.remove_attractor(actual);
+                    }
+                }
+                Action::MoveAttractor { id, x, y } => {
+                    if let Some(actual) = self.replayed_attractor(id) {
+                        let (x, y) = self.denormalize(x, y);
+                        self.move_attractor(actual, x, y);
+                    }
+                }
+                Action::ClearAttractors => self.clear_attractors(),
+                Action::AddCircleObstacle { x, y, radius } => {
+                    let (x, y) = self.denormalize(x, y);
+                    self.add_circle_obstacle(x, y, radius);
+                }
+                Action::AddLineObstacle { x0, y0, x1, y1 } => {
+                    let (x0, y0) = self.denormalize(x0, y0);
+                    let (x1, y1) = self.denormalize(x1, y1);
+                    self.add_line_obstacle(x0, y0, x1, y1);
+                }
+                Action::ClearObstacles => self.clear_obstacles(),
+                // 記録したときに変えられた大きさなので、失敗したらその変更だけ飛ばす
+                Action::Resize { width, height } => {
+                    let _ = self.resize(width, height);
+                }
+            }
+        }
+        if finished {
+            self.player = None;
+        }
+    }
+
+    fn replayed_attractor(&self, recorded: u32) -> Option<u32> {
+        self.player.as_ref()?.attractor(recorded)
+    }
+}
+
+#[wasm_bindgen]
+impl Backend {
+    pub fn get_kind(&self) -> BackendKind {
+        self.kind
+    }
+
+    // 止めている間は入力だけを受け付け、記録と再生も進めない
+    pub fn update(&mut self) {
+        if self.is_paused() {
+            self.apply_input();
+            return;
+        }
+        self.replay_frame();
+        self.apply_input();
+        dispatch!(&mut self.inner, system => system.update());
+        if let Some(recorder) = &mut self.recorder {
+            recorder.advance();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        dispatch!(&mut self.inner, system => system.pause())
+    }
+
+    pub fn resume(&mut self) {
+        dispatch!(&mut self.inner, system => system.resume())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        dispatch!(&self.inner, system => system.is_paused())
+    }
+
+    // update() を通すので、記録と再生も n フレーム分進む
+    pub fn step(&mut self, n: u32) {
+        let paused = self.is_paused();
+        self.resume();
+        for _ in 0..n {
+            self.update();
+        }
+        if paused {
+            self.pause();
+        }
+    }
+
+    // ステップごとに update() するので、記録と再生もステップ単位で進む
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        let steps = dispatch!(&mut self.inner, system => system.advance_timestep(dt_ms));
+        for _ in 0..steps {
+            self.update();
+        }
+        dispatch!(&mut self.inner, system => system.interpolate_frame());
+        steps
+    }
+
+    pub fn set_fixed_timestep(&mut self, step_ms: f64) -> f64 {
+        dispatch!(&mut self.inner, system => system.set_fixed_timestep(step_ms))
+    }
+
+    pub fn get_fixed_timestep(&self) -> f64 {
+        dispatch!(&self.inner, system => system.get_fixed_timestep())
+    }
+
+    // update() を通すので、記録と再生も1回ずつ進む
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.update();
+        self.render_capped(timestamp)
+    }
+
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        dispatch!(&mut self.inner, system => system.set_target_fps(fps))
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        dispatch!(&self.inner, system => system.get_target_fps())
+    }
+
+    pub fn get_refresh_rate(&self) -> f64 {
+        dispatch!(&self.inner, system => system.get_refresh_rate())
+    }
+
+    pub fn get_rendered_ticks(&self) -> u32 {
+        dispatch!(&self.inner, system => system.get_rendered_ticks())
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        dispatch!(&self.inner, system => system.get_skipped_ticks())
+    }
+
+    pub fn render(&mut self) {
+        dispatch!(&mut self.inner, system => system.render());
+        if self.live_region.is_some() {
+            let (count, scene) = (self.get_particle_count(), self.get_scene());
+            if let Some(region) = &mut self.live_region {
+                region.note_frame(count, scene);
+            }
+        }
+    }
+
+    // パーティクル数・FPS・シーンを interval_ms ごと（2000ms 以上）に文章にして、
+    // 画面に出ない role="status" の要素に書き込む（スクリーンリーダーが読み上げる）
+    // 呼び直すと要素を作り直す。Worker の中では document が無いのでエラー
+    pub fn enable_aria_summary(&mut self, interval_ms: f64) -> Result<(), JsValue> {
+        self.live_region = None;
+        self.live_region = Some(LiveRegion::attach(interval_ms)?);
+        Ok(())
+    }
+
+    // 書き込みをやめて要素を取り除く
+    pub fn disable_aria_summary(&mut self) {
+        self.live_region = None;
+    }
+
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        dispatch!(&mut self.inner, system => system.simulate_frames(n))
+    }
+
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        dispatch!(&mut self.inner, system => system.warmup(frames))
+    }
+
+    pub fn simulate_until_settled(&mut self, threshold: f32, max_frames: u32) -> f64 {
+        dispatch!(&mut self.inner, system => system.simulate_until_settled(threshold, max_frames))
+    }
+
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        dispatch!(&mut self.inner, system => system.set_settle_threshold(threshold))
+    }
+
+    pub fn is_settled(&self) -> bool {
+        dispatch!(&self.inner, system => system.is_settled())
+    }
+
+    pub fn get_frames_until_settle(&self) -> Option<u32> {
+        dispatch!(&self.inner, system => system.get_frames_until_settle())
+    }
+
+    pub fn get_mean_speed(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_mean_speed())
+    }
+
+    pub fn init_chunk(&mut self, max_particles: usize) -> f32 {
+        dispatch!(&mut self.inner, system => system.init_chunk(max_particles))
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        dispatch!(&self.inner, system => system.is_initialized())
+    }
+
+    pub fn get_init_timings(&self) -> InitTimings {
+        dispatch!(&self.inner, system => system.get_init_timings())
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        dispatch!(&self.inner, system => system.get_frame_count())
+    }
+
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        dispatch!(&mut self.inner, system => system.on_frame(callback))
+    }
+
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        dispatch!(&mut self.inner, system => system.on_benchmark_complete(callback))
+    }
+
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        dispatch!(&mut self.inner, system => system.on_fps_drop(threshold, callback))
+    }
+
+    pub fn clear_observers(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_observers())
+    }
+
+    pub fn annotate(&mut self, label: &str) {
+        dispatch!(&mut self.inner, system => system.annotate(label))
+    }
+
+    pub fn get_timeline_json(&self) -> String {
+        dispatch!(&self.inner, system => system.get_timeline_json())
+    }
+
+    pub fn clear_timeline(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_timeline())
+    }
+
+    pub fn context_lost(&self) -> bool {
+        dispatch!(&self.inner, system => system.context_lost())
+    }
+
+    pub fn get_context_losses(&self) -> u32 {
+        dispatch!(&self.inner, system => system.get_context_losses())
+    }
+
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_scene(kind))?;
+        self.record(Action::SwitchScene(kind));
+        Ok(())
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        dispatch!(&self.inner, system => system.get_scene())
+    }
+
+    pub fn describe_current_scene(&self) -> SceneDescription {
+        dispatch!(&self.inner, system => system.describe_current_scene())
+    }
+
+    pub fn set_load(&mut self, load: f32) {
+        dispatch!(&mut self.inner, system => system.set_load(load));
+        self.record(Action::SetLoad(load));
+    }
+
+    pub fn get_load(&self) -> Option<f32> {
+        dispatch!(&self.inner, system => system.get_load())
+    }
+
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        dispatch!(&mut self.inner, system => system.set_particle_count(count))
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_particle_count())
+    }
+
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, JsValue> {
+        dispatch!(&mut self.inner, system => system.grow_particle_count(count))
+    }
+
+    pub fn get_max_particles(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_max_particles())
+    }
+
+    pub fn export_animation(&self, frames: u32, every_n: u32) -> Result<Vec<u8>, JsValue> {
+        dispatch!(&self.inner, system => system.export_animation(frames, every_n))
+    }
+
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        dispatch!(&self.inner, system => system.capture_frame())
+    }
+
+    pub fn capture_debug_buffers(&self) -> Result<Vec<CapturedBuffer>, JsValue> {
+        dispatch!(&self.inner, system => system.capture_debug_buffers())
+    }
+
+    pub fn get_config_fingerprint(&self) -> String {
+        dispatch!(&self.inner, system => system.get_config_fingerprint())
+    }
+
+    pub fn get_config_summary(&self) -> String {
+        dispatch!(&self.inner, system => system.get_config_summary())
+    }
+
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        dispatch!(&mut self.inner, system => system.poll_events())
+    }
+
+    pub fn set_event_callback(&mut self, callback: js_sys::Function) {
+        dispatch!(&mut self.inner, system => system.set_event_callback(callback))
+    }
+
+    pub fn clear_event_callback(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_event_callback())
+    }
+
+    pub fn subscribe_stats(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        dispatch!(&mut self.inner, system => system.subscribe_stats(callback, interval_ms))
+    }
+
+    pub fn unsubscribe_stats(&mut self) {
+        dispatch!(&mut self.inner, system => system.unsubscribe_stats())
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_channel(&mut self, name: &str) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.start_telemetry_channel(name))
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_socket(&mut self, url: &str) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.start_telemetry_socket(url))
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn stop_telemetry(&mut self) {
+        dispatch!(&mut self.inner, system => system.stop_telemetry())
+    }
+
+    pub fn reset(&mut self) {
+        dispatch!(&mut self.inner, system => system.reset());
+        self.record(Action::Reset);
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        dispatch!(&mut self.inner, system => system.set_seed(seed))
+    }
+
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        dispatch!(&mut self.inner, system => system.query_radius(x, y, radius))
+    }
+
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        dispatch!(&mut self.inner, system => system.nearest_particle(x, y))
+    }
+
+    pub fn get_particle(&mut self, index: u32) -> Option<ParticleInfo> {
+        dispatch!(&mut self.inner, system => system.get_particle(index))
+    }
+
+    pub fn snapshot(&mut self) -> Vec<u8> {
+        dispatch!(&mut self.inner, system => system.snapshot())
+    }
+
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.restore(bytes))
+    }
+
+    pub fn clear_seed(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_seed())
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        dispatch!(&self.inner, system => system.get_seed())
+    }
+
+    pub fn explode(&mut self, click_x: f32, click_y: f32) {
+        dispatch!(&mut self.inner, system => system.explode(click_x, click_y));
+        let (x, y) = self.normalize(click_x, click_y);
+        self.record(Action::Explode { x, y });
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.resize(width, height))?;
+        self.record(Action::Resize { width, height });
+        Ok(())
+    }
+
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        dispatch!(&mut self.inner, system => system.set_pixel_ratio(ratio))
+    }
+
+    pub fn get_pixel_ratio(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_pixel_ratio())
+    }
+
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        dispatch!(&mut self.inner, system => system.set_render_scale(scale))
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_render_scale())
+    }
+
+    pub fn observe_resize(&mut self) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.observe_resize())
+    }
+
+    pub fn unobserve_resize(&mut self) {
+        dispatch!(&mut self.inner, system => system.unobserve_resize())
+    }
+
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.attach_input_handlers())
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        dispatch!(&mut self.inner, system => system.detach_input_handlers())
+    }
+
+    // GPU のリソースを解放して破棄する（以後このインスタンスは使えない）
+    pub fn dispose(self) {
+        dispatch!(self.inner, system => system.dispose())
+    }
+
+    pub fn reset_camera(&mut self) {
+        dispatch!(&mut self.inner, system => system.reset_camera())
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_camera_zoom())
+    }
+
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        dispatch!(&mut self.inner, system => system.strict_benchmark(enabled))
+    }
+
+    pub fn is_strict_benchmark(&self) -> bool {
+        dispatch!(&self.inner, system => system.is_strict_benchmark())
+    }
+
+    pub fn get_quirks(&self) -> Vec<String> {
+        dispatch!(&self.inner, system => system.get_quirks())
+    }
+
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        dispatch!(&mut self.inner, system => system.set_out_of_bounds(policy))
+    }
+
+    pub fn get_out_of_bounds(&self) -> OutOfBounds {
+        dispatch!(&self.inner, system => system.get_out_of_bounds())
+    }
+
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        dispatch!(&mut self.inner, system => system.set_boundary_mode(mode))
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        dispatch!(&self.inner, system => system.get_boundary_mode())
+    }
+
+    pub fn set_particle_color_mode(
+        &mut self,
+        mode: ColorMode,
+        colors: Vec<f32>,
+    ) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_particle_color_mode(mode, colors))
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        dispatch!(&self.inner, system => system.get_particle_color_mode())
+    }
+
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        dispatch!(&mut self.inner, system => system.set_background_color(r, g, b))
+    }
+
+    // [r, g, b]
+    pub fn get_background_color(&self) -> Vec<f32> {
+        dispatch!(&self.inner, system => system.get_background_color())
+    }
+
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_projection_mode(mode))
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        dispatch!(&self.inner, system => system.get_projection_mode())
+    }
+
+    pub fn implode(&mut self, x: f32, y: f32) {
+        dispatch!(&mut self.inner, system => system.implode(x, y));
+        let (x, y) = self.normalize(x, y);
+        self.record(Action::Implode { x, y });
+    }
+
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        dispatch!(&mut self.inner, system => system.stir(x, y, dx, dy));
+        let (x, y) = self.normalize(x, y);
+        let (dx, dy) = self.normalize(dx, dy);
+        self.record(Action::Stir { x, y, dx, dy });
+    }
+
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        dispatch!(&mut self.inner, system => system.pointer_move(x, y, dx, dy));
+        let (x, y) = self.normalize(x, y);
+        let (dx, dy) = self.normalize(dx, dy);
+        self.record(Action::PointerMove { x, y, dx, dy });
+    }
+
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        dispatch!(&mut self.inner, system => system.pointer_drag(x, y, strength));
+        let (x, y) = self.normalize(x, y);
+        self.record(Action::PointerDrag { x, y, strength });
+    }
+
+    pub fn schedule_explosion(&mut self, x: f32, y: f32, delay_frames: u32) {
+        dispatch!(&mut self.inner, system => system.schedule_explosion(x, y, delay_frames));
+        let (x, y) = self.normalize(x, y);
+        self.record(Action::ScheduleExplosion { x, y, delay_frames });
+    }
+
+    pub fn get_pending_explosions(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_pending_explosions())
+    }
+
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        let id = dispatch!(&mut self.inner, system => system.add_attractor(x, y, strength, radius));
+        let (x, y) = self.normalize(x, y);
+        self.record(Action::AddAttractor {
+            x,
+            y,
+            strength,
+            radius,
+            id,
+        });
+        id
+    }
+
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        let removed = dispatch!(&mut self.inner, system => system.remove_attractor(id));
+        if removed {
+            self.record(Action::RemoveAttractor { id });
+        }
+        removed
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        let moved = dispatch!(&mut self.inner, system => system.move_attractor(id, x, y));
+        if moved {
+            let (x, y) = self.normalize(x, y);
+            self.record(Action::MoveAttractor { id, x, y });
+        }
+        moved
+    }
+
+    pub fn clear_attractors(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_attractors());
+        self.record(Action::ClearAttractors);
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        dispatch!(&self.inner, system => system.get_attractors())
+    }
+
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        let added = dispatch!(&mut self.inner, system => system.add_circle_obstacle(x, y, radius));
+        if added {
+            let (x, y) = self.normalize(x, y);
+            self.record(Action::AddCircleObstacle { x, y, radius });
+        }
+        added
+    }
+
+    pub fn add_line_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        let added = dispatch!(&mut self.inner, system => system.add_line_obstacle(x0, y0, x1, y1));
+        if added {
+            let (x0, y0) = self.normalize(x0, y0);
+            let (x1, y1) = self.normalize(x1, y1);
+            self.record(Action::AddLineObstacle { x0, y0, x1, y1 });
+        }
+        added
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_obstacles());
+        self.record(Action::ClearObstacles);
+    }
+
+    pub fn get_obstacles(&self) -> Vec<Obstacle> {
+        dispatch!(&self.inner, system => system.get_obstacles())
+    }
+
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        dispatch!(&mut self.inner, system => system.add_emitter(x, y, rate, spread, speed))
+    }
+
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        dispatch!(&mut self.inner, system => system.remove_emitter(id))
+    }
+
+    pub fn clear_emitters(&mut self) {
+        dispatch!(&mut self.inner, system => system.clear_emitters())
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        dispatch!(&self.inner, system => system.get_emitters())
+    }
+
+    pub fn get_emitted_count(&self) -> f64 {
+        dispatch!(&self.inner, system => system.get_emitted_count())
+    }
+
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        dispatch!(&mut self.inner, system => system.set_particle_lifetime(frames))
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_particle_lifetime())
+    }
+
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        dispatch!(&mut self.inner, system => system.set_explosion_config(config))
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        dispatch!(&self.inner, system => system.get_explosion_config())
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        dispatch!(&mut self.inner, system => system.set_simulation_config(config))
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        dispatch!(&self.inner, system => system.get_simulation_config())
+    }
+
+    pub fn set_gravity(&mut self, gravity: f32) {
+        dispatch!(&mut self.inner, system => system.set_gravity(gravity))
+    }
+
+    pub fn set_bounce(&mut self, bounce: f32) {
+        dispatch!(&mut self.inner, system => system.set_bounce(bounce))
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        dispatch!(&mut self.inner, system => system.set_friction(friction))
+    }
+
+    pub fn set_point_size(&mut self, point_size: f32) {
+        dispatch!(&mut self.inner, system => system.set_point_size(point_size))
+    }
+
+    pub fn set_particle_shape(&mut self, shape: ParticleShape) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_particle_shape(shape))
+    }
+
+    pub fn get_particle_shape(&self) -> ParticleShape {
+        dispatch!(&self.inner, system => system.get_particle_shape())
+    }
+
+    pub fn set_particle_texture(&mut self, image: &JsValue) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_particle_texture(image))
+    }
+
+    pub fn clear_particle_texture(&mut self) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.clear_particle_texture())
+    }
+
+    pub fn set_particle_style(&mut self, style: &ParticleStyle) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_particle_style(style))
+    }
+
+    pub fn get_particle_style(&self) -> ParticleStyle {
+        dispatch!(&self.inner, system => system.get_particle_style())
+    }
+
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_render_mode(mode))
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        dispatch!(&self.inner, system => system.get_render_mode())
+    }
+
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        dispatch!(&mut self.inner, system => system.set_lod(mode, skip))
+    }
+
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        dispatch!(&mut self.inner, system => system.set_adaptive_lod(mode, target_render_ms))
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        dispatch!(&self.inner, system => system.get_lod_mode())
+    }
+
+    pub fn get_lod_skip(&self) -> u32 {
+        dispatch!(&self.inner, system => system.get_lod_skip())
+    }
+
+    pub fn get_visible_particle_count(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_visible_particle_count())
+    }
+
+    pub fn set_draw_strategy(&mut self, strategy: DrawStrategy) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_draw_strategy(strategy))
+    }
+
+    pub fn get_draw_strategy(&self) -> DrawStrategy {
+        dispatch!(&self.inner, system => system.get_draw_strategy())
+    }
+
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_upload_strategy(strategy))
+    }
+
+    pub fn get_upload_strategy(&self) -> UploadStrategy {
+        dispatch!(&self.inner, system => system.get_upload_strategy())
+    }
+
+    // 選んだバックエンドで使える値だけで組を当てる（どのバックエンドでも失敗しない組になっている）
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.apply_tuning_profile(profile))
+    }
+
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        dispatch!(&self.inner, system => system.get_tuning_profile())
+    }
+
+    pub fn set_buffer_layout(&mut self, layout: BufferLayout) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_buffer_layout(layout))
+    }
+
+    pub fn get_buffer_layout(&self) -> BufferLayout {
+        dispatch!(&self.inner, system => system.get_buffer_layout())
+    }
+
+    pub fn set_color_mode(&mut self, mode: ColorPipeline) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_color_mode(mode))
+    }
+
+    pub fn get_color_mode(&self) -> ColorPipeline {
+        dispatch!(&self.inner, system => system.get_color_mode())
+    }
+
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.set_physics_mode(mode))
+    }
+
+    pub fn get_physics_mode(&self) -> PhysicsMode {
+        dispatch!(&self.inner, system => system.get_physics_mode())
+    }
+
+    pub fn switch_to_point_size(&mut self, count: usize, size: f32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_point_size(count, size))
+    }
+
+    pub fn switch_to_texture_upload(&mut self, scale: f32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_texture_upload(scale))
+    }
+
+    pub fn switch_to_readback(&mut self, scale: f32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_readback(scale))
+    }
+
+    pub fn switch_to_clip(&mut self, regions: u32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_clip(regions))
+    }
+
+    pub fn switch_to_compositing(&mut self, mode: CompositeMode) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_compositing(mode))
+    }
+
+    pub fn switch_to_shadows(&mut self, shadow_blur: f32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_shadows(shadow_blur))
+    }
+
+    pub fn switch_to_state_changes(&mut self, change_every: u32) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_to_state_changes(change_every))
+    }
+
+    pub fn get_max_point_size(&self) -> f32 {
+        dispatch!(&self.inner, system => system.get_max_point_size())
+    }
+
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        dispatch!(&mut self.inner, system => system.set_thread_count(threads))
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_thread_count())
+    }
+
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        dispatch!(&mut self.inner, system => system.set_simd_enabled(enabled))
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        dispatch!(&self.inner, system => system.get_simd_enabled())
+    }
+
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        dispatch!(&mut self.inner, system => system.set_collisions_enabled(enabled))
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        dispatch!(&self.inner, system => system.get_collisions_enabled())
+    }
+
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        dispatch!(&mut self.inner, system => system.set_wind(x, y))
+    }
+
+    pub fn get_wind(&self) -> Vec<f32> {
+        dispatch!(&self.inner, system => system.get_wind())
+    }
+
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        dispatch!(&mut self.inner, system => system.set_turbulence(strength, scale))
+    }
+
+    pub fn get_turbulence(&self) -> Option<Vec<f32>> {
+        dispatch!(&self.inner, system => system.get_turbulence())
+    }
+
+    pub fn get_contact_count(&self) -> usize {
+        dispatch!(&self.inner, system => system.get_contact_count())
+    }
+
+    // これ以降の操作（爆発・引力点・大きさの変更・シーン切り替え・負荷の変更・リセット）をフレーム番号つきで記録する
+    pub fn start_recording(&mut self) {
+        self.recorder = Some(Recorder::new(self.particle_count, self.get_scene()));
+    }
+
+    // 記録を終えて返す（記録していなければ None）
+    pub fn stop_recording(&mut self) -> Option<Workload> {
+        self.recorder.take().map(Recorder::finish)
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
+    // 記録したときのシーンに切り替えてリセットし、次の update() から記録どおりに操作する
+    // パーティクル数は揃えないので、同じ条件で比べるなら Workload.get_particle_count() で作ったバックエンドで再生する
+    pub fn play_workload(&mut self, workload: &Workload) -> Result<(), JsValue> {
+        dispatch!(&mut self.inner, system => system.switch_scene(workload.get_scene()))?;
+        dispatch!(&mut self.inner, system => system.reset());
+        self.player = Some(Player::new(workload.clone()));
+        Ok(())
+    }
+
+    // Workload.to_json() の形式の記録を読み込む（play() で再生する）
+    pub fn load_scenario(&mut self, json: &str) -> Result<(), JsValue> {
+        self.scenario = Some(Workload::from_json(json)?);
+        Ok(())
+    }
+
+    // load_scenario() で読み込んだ記録を最初から再生する（何度でも再生し直せる）
+    pub fn play(&mut self) -> Result<(), JsValue> {
+        let workload = self
+            .scenario
+            .clone()
+            .ok_or_else(|| tr(Text::ScenarioNotLoaded, &[]))?;
+        self.play_workload(&workload)
+    }
+
+    pub fn stop_replay(&mut self) {
+        self.player = None;
+    }
+
+    // 再生し終わると false になる
+    pub fn is_replaying(&self) -> bool {
+        self.player.is_some()
+    }
+}
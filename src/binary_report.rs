@@ -0,0 +1,357 @@
+// SuiteReport のバイナリ形式
+// フレームごとの時間を数千個含む結果を送るときにテキストでは大きすぎるので、postcard と同じ考え方の
+// 詰めた形式で書く（数値はリトルエンディアン、整数は LEB128 の可変長）
+//
+// スキーマ（バージョン 2）:
+//   report  = magic:"PWBR" version:u8 seed:varint total_ms:f64 metadata:str count:varint result*count
+//   result  = backend:str scene:str supported:bool error:opt_str trials:varint frames:varint
+//             mean_ms:f64 stddev_ms:f64 aggregation:str outliers:varint min_ms:f64 max_ms:f64
+//             fps:f64 reliable:bool drift:f64 warning:opt_str gc_pauses:varint
+//             render_stalls:varint host_stalls:varint fingerprint:str samples:f32_vec
+//   str     = len:varint UTF-8 のバイト列
+//   opt_str = 0 | 1 str
+//   bool    = 0 | 1
+//   f32_vec = len:varint f32*len
+// フィールドを増やすときは version を上げ、古い版も読めるようにする
+//
+// 1: metadata がない（"{}" として読む）
+
+use crate::i18n::{tr, Text};
+use crate::suite_report::{SuiteReport, SuiteResult};
+
+const MAGIC: &[u8; 4] = b"PWBR";
+const VERSION: u8 = 2;
+
+pub(crate) fn encode(report: &SuiteReport) -> Vec<u8> {
+    let samples: usize = report
+        .results
+        .iter()
+        .map(|result| result.samples.len())
+        .sum();
+    let mut writer = Writer(Vec::with_capacity(
+        64 + report.results.len() * 128 + samples * 4,
+    ));
+    writer.0.extend_from_slice(MAGIC);
+    writer.0.push(VERSION);
+    writer.varint(report.seed);
+    writer.f64(report.total_ms);
+    writer.str(&report.metadata);
+    writer.varint(report.results.len() as u64);
+    for result in &report.results {
+        writer.str(&result.backend);
+        writer.str(&result.scene);
+        writer.bool(result.supported);
+        writer.opt_str(result.error.as_deref());
+        writer.varint(result.trials as u64);
+        writer.varint(result.frames as u64);
+        writer.f64(result.mean_ms);
+        writer.f64(result.stddev_ms);
+        writer.str(&result.aggregation);
+        writer.varint(result.outliers as u64);
+        writer.f64(result.min_ms);
+        writer.f64(result.max_ms);
+        writer.f64(result.fps);
+        writer.bool(result.reliable);
+        writer.f64(result.drift);
+        writer.opt_str(result.warning.as_deref());
+        writer.varint(result.gc_pauses as u64);
+        writer.varint(result.render_stalls as u64);
+        writer.varint(result.host_stalls as u64);
+        writer.str(&result.fingerprint);
+        writer.varint(result.samples.len() as u64);
+        for &sample in &result.samples {
+            writer.0.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+    writer.0
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<SuiteReport, String> {
+    let mut reader = Reader(bytes);
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(invalid());
+    }
+    let version = reader.u8()?;
+    if version == 0 || version > VERSION {
+        return Err(tr(Text::ReportVersionUnsupported, &[&version]));
+    }
+    let seed = reader.varint()?;
+    let total_ms = reader.f64()?;
+    let metadata = if version >= 2 {
+        reader.str()?
+    } else {
+        String::from("{}")
+    };
+    let count = reader.varint()?;
+    let mut results = Vec::new();
+    for _ in 0..count {
+        results.push(SuiteResult {
+            backend: reader.str()?,
+            scene: reader.str()?,
+            supported: reader.bool()?,
+            error: reader.opt_str()?,
+            trials: reader.u32()?,
+            frames: reader.u32()?,
+            mean_ms: reader.f64()?,
+            stddev_ms: reader.f64()?,
+            aggregation: reader.str()?,
+            outliers: reader.u32()?,
+            min_ms: reader.f64()?,
+            max_ms: reader.f64()?,
+            fps: reader.f64()?,
+            reliable: reader.bool()?,
+            drift: reader.f64()?,
+            warning: reader.opt_str()?,
+            gc_pauses: reader.u32()?,
+            render_stalls: reader.u32()?,
+            host_stalls: reader.u32()?,
+            fingerprint: reader.str()?,
+            samples: reader.f32_vec()?,
+        });
+    }
+    if !reader.0.is_empty() {
+        return Err(invalid());
+    }
+    Ok(SuiteReport {
+        results,
+        seed,
+        total_ms,
+        metadata,
+    })
+}
+
+fn invalid() -> String {
+    tr(Text::ReportInvalid, &[])
+}
+
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.0.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.0.push(value as u8);
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn bool(&mut self, value: bool) {
+        self.0.push(value as u8);
+    }
+
+    fn str(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+
+    fn opt_str(&mut self, value: Option<&str>) {
+        match value {
+            Some(value) => {
+                self.0.push(1);
+                self.str(value);
+            }
+            None => self.0.push(0),
+        }
+    }
+}
+
+// 途中で切れていたり値が範囲外だったりしたら ReportInvalid を返す
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err(invalid());
+        }
+        let (head, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid())
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        u32::try_from(self.varint()?).map_err(|_| invalid())
+    }
+
+    fn f64(&mut self) -> Result<f64, String> {
+        let bytes = self.take(8)?;
+        Ok(f64::from_le_bytes(bytes.try_into().map_err(|_| invalid())?))
+    }
+
+    fn bool(&mut self) -> Result<bool, String> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        let len = usize::try_from(self.varint()?).map_err(|_| invalid())?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid())
+    }
+
+    fn opt_str(&mut self) -> Result<Option<String>, String> {
+        match self.u8()? {
+            0 => Ok(None),
+            1 => self.str().map(Some),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn f32_vec(&mut self) -> Result<Vec<f32>, String> {
+        let len = usize::try_from(self.varint()?).map_err(|_| invalid())?;
+        // 長さが壊れていても大きな確保をしないよう、先に残りのバイト数と比べる
+        let bytes = self.take(len.checked_mul(4).ok_or_else(invalid)?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(backend: &str, samples: Vec<f32>) -> SuiteResult {
+        SuiteResult {
+            backend: backend.to_string(),
+            scene: "particles".to_string(),
+            supported: true,
+            error: None,
+            trials: 3,
+            frames: 300,
+            mean_ms: 4.25,
+            stddev_ms: 0.5,
+            aggregation: "median".to_string(),
+            outliers: 2,
+            min_ms: 3.0,
+            max_ms: 12.5,
+            fps: 235.0,
+            reliable: false,
+            drift: 0.125,
+            warning: Some("ドリフト".to_string()),
+            gc_pauses: 1,
+            render_stalls: 200,
+            host_stalls: 0,
+            fingerprint: "abc123".to_string(),
+            samples,
+        }
+    }
+
+    fn report() -> SuiteReport {
+        let mut skipped = result("webgpu", Vec::new());
+        skipped.supported = false;
+        skipped.error = Some("unsupported".to_string());
+        skipped.warning = None;
+        SuiteReport {
+            results: vec![result("webgl", vec![4.0, 4.5, f32::MAX, -0.0]), skipped],
+            seed: 42,
+            total_ms: 1234.5,
+            metadata: r#"{"device":"test"}"#.to_string(),
+        }
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let report = report();
+        let bytes = encode(&report);
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(encode(&decoded), bytes);
+        assert_eq!((decoded.seed, decoded.total_ms), (42, 1234.5));
+        assert_eq!(decoded.metadata, report.metadata);
+        assert_eq!(decoded.results.len(), 2);
+        let (first, second) = (&decoded.results[0], &decoded.results[1]);
+        assert_eq!(first.samples, report.results[0].samples);
+        assert_eq!(first.warning.as_deref(), Some("ドリフト"));
+        assert_eq!((first.render_stalls, first.reliable), (200, false));
+        assert_eq!(second.error.as_deref(), Some("unsupported"));
+        assert!(!second.supported && second.samples.is_empty());
+    }
+
+    #[test]
+    fn reads_version_1_without_metadata() {
+        // seed が1バイトの varint なので、metadata は magic・version・seed・total_ms の直後（14バイト目）から
+        let mut bytes = encode(&report());
+        let metadata_len = 1 + r#"{"device":"test"}"#.len();
+        bytes.drain(14..14 + metadata_len);
+        bytes[4] = 1;
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(decoded.metadata, "{}");
+        assert_eq!(decoded.results.len(), 2);
+        assert_eq!(decoded.results[0].fingerprint, "abc123");
+    }
+
+    #[test]
+    fn rejects_truncated_and_trailing_bytes() {
+        let bytes = encode(&report());
+        for len in 0..bytes.len() {
+            assert!(
+                decode(&bytes[..len]).is_err(),
+                "accepted {} of {} bytes",
+                len,
+                bytes.len()
+            );
+        }
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert_eq!(decode(&longer).unwrap_err(), invalid());
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_newer_versions() {
+        let mut bytes = encode(&report());
+        bytes[0] = b'X';
+        assert_eq!(decode(&bytes).unwrap_err(), invalid());
+
+        let mut bytes = encode(&report());
+        bytes[4] = VERSION + 1;
+        assert_eq!(
+            decode(&bytes).unwrap_err(),
+            tr(Text::ReportVersionUnsupported, &[&(VERSION + 1)])
+        );
+    }
+
+    #[test]
+    fn rejects_oversized_sample_lengths_without_allocating() {
+        let mut writer = Writer(Vec::new());
+        writer.varint(u64::MAX);
+        writer.0.extend_from_slice(&[0; 8]);
+        assert_eq!(Reader(&writer.0).f32_vec().unwrap_err(), invalid());
+
+        let mut writer = Writer(Vec::new());
+        writer.varint(1 << 40);
+        assert_eq!(Reader(&writer.0).f32_vec().unwrap_err(), invalid());
+
+        // 最後の samples の長さだけを大きくする（長さの varint は最後の f32 の4バイトの直前）
+        let mut bytes = encode(&SuiteReport {
+            results: vec![result("webgl", vec![1.0])],
+            ..report()
+        });
+        let len_at = bytes.len() - 4 - 1;
+        assert_eq!(bytes[len_at], 1);
+        bytes[len_at] = 0x7f;
+        assert_eq!(decode(&bytes).unwrap_err(), invalid());
+    }
+}
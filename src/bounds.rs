@@ -0,0 +1,51 @@
+use wasm_bindgen::prelude::*;
+
+// resize() で領域が縮んだときに外に残ったパーティクルの扱い
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum OutOfBounds {
+    // 一番近い縁に寄せる（速度はそのまま）
+    #[default]
+    Clamp = 0,
+    // 取り除く（数が減るので、set_load() で生成し直すまで戻らない）
+    Kill = 1,
+    // 反対側の縁から出てくるよう、領域の大きさで割った余りの位置に移す
+    Wrap = 2,
+}
+
+// update() でパーティクルが壁に届いたときの扱い
+// 跳ね返りは分岐が多く、折り返しと壁なしはほとんど分岐しないので、壁の処理の重さの違いも比べられる
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BoundaryMode {
+    // 跳ね返りの割合で速度を反転し、床では摩擦をかける
+    #[default]
+    Bounce = 0,
+    // 反対側の縁から出てくる（速度はそのまま）
+    Wrap = 1,
+    // 領域の外に出たら消し、次のステップの最初に噴水の位置から生成し直す
+    Respawn = 2,
+    // 壁なし（出ていったパーティクルは戻らない）
+    None = 3,
+}
+
+impl BoundaryMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            BoundaryMode::Bounce => "bounce",
+            BoundaryMode::Wrap => "wrap",
+            BoundaryMode::Respawn => "respawn",
+            BoundaryMode::None => "none",
+        }
+    }
+}
+
+impl OutOfBounds {
+    pub fn name(self) -> &'static str {
+        match self {
+            OutOfBounds::Clamp => "clamp",
+            OutOfBounds::Kill => "kill",
+            OutOfBounds::Wrap => "wrap",
+        }
+    }
+}
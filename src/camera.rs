@@ -0,0 +1,57 @@
+// 拡大率の範囲
+const MIN_ZOOM: f32 = 0.25;
+const MAX_ZOOM: f32 = 8.0;
+
+// 描画するときの視点（シミュレーションの座標はそのままで、描くときだけ平行移動と拡大をする）
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) struct Camera {
+    // 画面の左上に映るシミュレーションの座標
+    pub x: f32,
+    pub y: f32,
+    pub zoom: f32,
+}
+
+impl Default for Camera {
+    fn default() -> Camera {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera {
+    pub fn is_identity(self) -> bool {
+        self == Camera::default()
+    }
+
+    // シミュレーションの座標 → 画面の座標(CSSピクセル)
+    pub fn to_screen(self, x: f32, y: f32) -> (f32, f32) {
+        ((x - self.x) * self.zoom, (y - self.y) * self.zoom)
+    }
+
+    // 画面の座標(CSSピクセル) → シミュレーションの座標
+    pub fn to_world(self, x: f32, y: f32) -> (f32, f32) {
+        (x / self.zoom + self.x, y / self.zoom + self.y)
+    }
+
+    // 画面上で (dx, dy) だけ引きずる
+    pub fn pan(&mut self, dx: f32, dy: f32) {
+        if dx.is_finite() && dy.is_finite() {
+            self.x -= dx / self.zoom;
+            self.y -= dy / self.zoom;
+        }
+    }
+
+    // 画面の (x, y) に映っている点を動かさずに factor 倍に拡大する
+    pub fn zoom_at(&mut self, factor: f32, x: f32, y: f32) {
+        if !(factor.is_finite() && factor > 0.0 && x.is_finite() && y.is_finite()) {
+            return;
+        }
+        let (world_x, world_y) = self.to_world(x, y);
+        self.zoom = (self.zoom * factor).clamp(MIN_ZOOM, MAX_ZOOM);
+        self.x = world_x - x / self.zoom;
+        self.y = world_y - y / self.zoom;
+    }
+}
@@ -1,184 +1,2059 @@
 use wasm_bindgen::prelude::*;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-use rand::Rng;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, OffscreenCanvas};
 use std::f32::consts::PI;
 
-const GRAVITY: f32 = 0.0002;
-const BOUNCE: f32 = 0.85;
-const EXPLOSION_FORCE: f32 = 8.0;
+use crate::attractor::Attractor;
+use crate::backend::BackendKind;
+use crate::emitter::Emitter;
+use crate::bounds::{BoundaryMode, OutOfBounds};
+use crate::capture::{self, CapturedBuffer};
+use crate::clustering::KMeans;
+use crate::color_pipeline::ColorPipeline;
+use crate::color_theme::{ColorMode, ColorTheme};
+use crate::gpu_physics::PhysicsMode;
+use crate::image_source::{self, DecodedImage};
+use crate::events::{BenchmarkEvent, EventBus, EventKind};
+use crate::explosion::ExplosionConfig;
+use crate::export;
+use crate::fingerprint::ConfigFingerprint;
+use crate::i18n::{tr, Text};
+use crate::draw_strategy::{self, DrawStrategy};
+use crate::context::{AcquiredContext, CanvasById, CanvasElement, ContextSource, ExternalContext, OffscreenCanvasSource};
+use crate::init::{progress, BuildOptions, InitProgress, InitTimings};
+use crate::camera::Camera;
+use crate::input::{InputAction, InputHandlers};
+use crate::panel::{self, OverlayRect};
+use crate::memory::check_budget;
+use crate::metrics::{FrameHistogram, Metrics, MetricsCollector};
+use crate::observers::Observers;
+use crate::obstacle::{self, Obstacle};
+use crate::results::{self, ResultFormat, RunInfo};
+use crate::scheduler::SchedulerStats;
+use crate::particle_shape::{self, ParticleShape, SPRITE_SIZE};
+use crate::particle_style::ParticleStyle;
+use crate::physics::SimulationConfig;
+use crate::projection::{ProjectionMode, Projector};
+use crate::quirks::Quirks;
+use crate::resize::CanvasSizing;
+use crate::render_mode::{CompositeMode, RenderMode, TRAIL_FADE};
+use crate::rng;
+use crate::canvas_surface::{atlas_canvas, rgb_css, CanvasSurface, SceneCanvas};
+use crate::scene::{
+    create_blur_scene, create_clip_scene, create_composite_scene, create_life_scene, create_point_size_scene, create_scene, create_readback_scene, create_shadow_scene, create_state_change_scene, create_texture_upload_scene, describe_scene,
+    require_backend, Atlas, BlurMethod, Scene, SceneDescription, SceneKind, Surface,
+};
+use crate::selection;
+use crate::lod::LodMode;
+use crate::picking::ParticleInfo;
+use crate::simulation::{hsl_to_rgb, Particle, ParticleSet, ReadStamps, Simulation};
+use crate::snapshot;
+use crate::software::SoftwareRaster;
+use crate::stats_stream::StatsStream;
+#[cfg(feature = "telemetry")]
+use crate::telemetry::Telemetry;
+use crate::timeline::Timeline;
+use crate::timing;
+use crate::tuning::TuningProfile;
+use crate::upload::{BufferLayout, UploadStrategy};
+use crate::viewport::{self, Viewport};
 
 #[wasm_bindgen]
 pub struct ParticleSystemCanvas2D {
-    particles: Vec<Particle>,
+    sim: Simulation,
     ctx: CanvasRenderingContext2d,
-    width: f32,
-    height: f32,
-    frame_count: u32,
-    particle_count: usize,
-}
-
-struct Particle {
-    x: f32,
-    y: f32,
-    vx: f32,
-    vy: f32,
-    hue: f32,
+    read_stamps: ReadStamps,
+    init: InitProgress,
+    // Particles 以外のシーンが有効なときだけ Some
+    scene: Option<Box<dyn Scene>>,
+    scene_canvas: SceneCanvas,
+    viewport: Option<Viewport>,
+    split_compare: Option<(RenderMode, RenderMode)>,
+    // set_load() で指定した負荷（None ならシーンごとの既定）
+    load: Option<f32>,
+    events: EventBus,
+    strict: bool,
+    // explode() の強さ・範囲・減衰
+    explosion: ExplosionConfig,
+    // 検出したブラウザ固有の問題と回避策
+    quirks: Quirks,
+    // subscribe_stats() の購読
+    stats_stream: StatsStream,
+    // start_telemetry_channel() と start_telemetry_socket() の送り先
+    #[cfg(feature = "telemetry")]
+    telemetry: Telemetry,
+    // on_frame() などで登録されたコールバック
+    observers: Observers,
+    // 直近のフレーム時間と annotate() の印
+    timeline: Timeline,
+    // apply_tuning_profile() で当てた設定の組
+    tuning: Option<TuningProfile>,
+    // get_metrics() の計測
+    metrics: MetricsCollector,
+    // 描画バッファの倍率と ResizeObserver
+    sizing: CanvasSizing,
+    // attach_input_handlers() で登録した入力のコールバック
+    input: Option<InputHandlers>,
+    // パーティクルの形と、Sprite のときに使う色違いのアトラス（最初に Sprite にしたときに作る）
+    shape: ParticleShape,
+    sprite_atlas: Option<OffscreenCanvas>,
+    // set_particle_texture() で渡した画像の大きさ（組み込みの光の玉なら None）
+    particle_texture: Option<(u32, u32)>,
+    // パーティクルの合成方法
+    render_mode: RenderMode,
+    // パーティクルを1個ずつ描くか、色ごとにまとめて描くか
+    draw_strategy: DrawStrategy,
+    // Batched のときに使い回す、まとまりごとの (x, y, 半径)
+    batches: Vec<Vec<(f64, f64, f64)>>,
+    // Some なら描画APIを使わず、WASM内で塗って putImageData で転送する（software バックエンド）
+    software: Option<SoftwareRaster>,
 }
 
 #[wasm_bindgen]
 impl ParticleSystemCanvas2D {
     #[wasm_bindgen(constructor)]
     pub fn new(canvas_id: &str, particle_count: usize) -> Result<ParticleSystemCanvas2D, JsValue> {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let canvas = document
-            .get_element_by_id(canvas_id)
-            .unwrap()
-            .dyn_into::<HtmlCanvasElement>()?;
+        Self::from_canvas(CanvasById(canvas_id).canvas()?, particle_count)
+    }
 
-        let width = canvas.width() as f32;
-        let height = canvas.height() as f32;
+    // フレームワークが持っている canvas 要素から生成（id が無くても、ドキュメントに追加前でもよい）
+    pub fn from_canvas(canvas: HtmlCanvasElement, particle_count: usize) -> Result<ParticleSystemCanvas2D, JsValue> {
+        Self::from_source(&CanvasElement(&canvas), particle_count, BuildOptions::default())
+    }
 
-        let ctx = canvas
-            .get_context("2d")?
-            .unwrap()
-            .dyn_into::<CanvasRenderingContext2d>()?;
+    // メモリ予算を指定して生成（超える場合は確保前にエラーを返す）
+    pub fn with_memory_budget(
+        canvas_id: &str,
+        particle_count: usize,
+        budget_bytes: u64,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let options = BuildOptions {
+            memory_budget: budget_bytes,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
 
-        // パーティクルを生成
-        let particles = create_particles(width, height, particle_count);
+    // 重力・跳ね返り・摩擦・点の大きさを指定して生成する
+    pub fn with_simulation_config(
+        canvas_id: &str,
+        particle_count: usize,
+        config: &SimulationConfig,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let options = BuildOptions {
+            simulation: *config,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
 
-        Ok(ParticleSystemCanvas2D {
-            particles,
-            ctx,
-            width,
-            height,
-            frame_count: 0,
-            particle_count,
-        })
+    // 機能が足りないときに代替経路で描かず、エラーを返すモードで生成する
+    pub fn new_strict(canvas_id: &str, particle_count: usize) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let options = BuildOptions {
+            strict: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
     }
 
-    pub fn update(&mut self) {
-        // Rustで高速物理演算!
-        for p in &mut self.particles {
-            // 重力
-            p.vy += GRAVITY;
+    // パーティクルを生成せずに作り、init_chunk() で少しずつ生成する
+    // 数百万個でもページを固めずに起動できる
+    pub fn new_progressive(canvas_id: &str, particle_count: usize) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let options = BuildOptions {
+            progressive: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
 
-            // 位置更新
-            p.x += p.vx;
-            p.y += p.vy;
+    // キャンバスの一部の領域だけを使って生成する
+    // 同じキャンバスに複数インスタンスを作ると1つのコンテキストを共有して描画する
+    pub fn with_viewport(
+        canvas_id: &str,
+        particle_count: usize,
+        viewport: &Viewport,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let options = BuildOptions {
+            viewport: Some(*viewport),
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
 
-            // 壁で跳ね返る
-            if p.x < 0.0 || p.x > self.width {
-                p.vx *= -BOUNCE;
-                p.x = p.x.clamp(0.0, self.width);
-            }
+    // JS側で用意した2Dコンテキストから生成（Node + node-canvas 用）
+    pub fn with_context(
+        ctx: JsValue,
+        width: u32,
+        height: u32,
+        particle_count: usize,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        let source = ExternalContext {
+            context: ctx,
+            width: width as f32,
+            height: height as f32,
+        };
+        Self::from_source(&source, particle_count, BuildOptions::default())
+    }
+
+    // Worker に渡した OffscreenCanvas から生成（メインスレッドと Worker での描画を比べる用）
+    pub fn from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+        particle_count: usize,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        Self::from_source(&OffscreenCanvasSource(&canvas), particle_count, BuildOptions::default())
+    }
 
-            if p.y < 0.0 {
-                p.vy *= -BOUNCE;
-                p.y = 0.0;
+    pub fn update(&mut self) {
+        self.apply_input();
+        if self.sim.paused {
+            return;
+        }
+        let start = timing::now_ms();
+        if let Some(scene) = &mut self.scene {
+            // パーティクルのシミュレーションは止まっているので、仕掛けた爆発はここで数える
+            for charge in self.sim.charges.tick() {
+                scene.explode(charge.x, charge.y, &charge.explosion);
             }
+            scene.update();
+        } else {
+            self.sim.step();
+        }
+        self.metrics.record_update(start);
+    }
+
+    // update() で物理演算を止める（描画・入力による視点の操作・爆発の受け付けは続ける）
+    pub fn pause(&mut self) {
+        self.sim.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.sim.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sim.paused
+    }
+
+    // 止めていても n ステップだけ update() と同じように進める（描画はしない）
+    pub fn step(&mut self, n: u32) {
+        let paused = std::mem::replace(&mut self.sim.paused, false);
+        for _ in 0..n {
+            self.update();
+        }
+        self.sim.paused = paused;
+    }
+
+    // dt_ms 経過した分だけ固定長のステップで update() し、進めたステップ数を返す
+    // 物理の進み方がリフレッシュレートに依らなくなる。描画は持ち越した端数の分だけ直前のステップとの間を補間する
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        let steps = self.advance_timestep(dt_ms);
+        for _ in 0..steps {
+            self.update();
+        }
+        self.interpolate_frame();
+        steps
+    }
+
+    // 1ステップの長さ(ms)を設定し、実際に使う値を返す（既定は 60Hz の1フレーム）
+    pub fn set_fixed_timestep(&mut self, step_ms: f64) -> f64 {
+        self.sim.timestep.set_step_ms(step_ms)
+    }
+
+    pub fn get_fixed_timestep(&self) -> f64 {
+        self.sim.timestep.step_ms()
+    }
+
+    // requestAnimationFrame の timestamp(ms) を渡して1回分進める
+    // 物理は毎回 update() で進め、描画は set_target_fps() の上限を超えない回だけ行う。描いたら true
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.update();
+        let render = self.cap_frame(timestamp);
+        if render {
+            self.render();
+        }
+        render
+    }
+
+    // 描画の上限(fps)を設定し、実際に使う値を返す（0 以下なら上限なし。既定は上限なし）
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.sim.frame_cap.set_target_fps(fps)
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        self.sim.frame_cap.target_fps()
+    }
+
+    // tick() の間隔から推定した表示のリフレッシュレート(Hz)（まだ分からなければ 0）
+    pub fn get_refresh_rate(&self) -> f64 {
+        self.sim.frame_cap.refresh_rate()
+    }
+
+    // set_target_fps() してから tick() で描いた回数と、上限のために描かなかった回数
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.sim.frame_cap.rendered
+    }
 
-            if p.y > self.height {
-                p.vy *= -BOUNCE;
-                p.y = self.height;
-                p.vx *= 0.98; // 摩擦
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.sim.frame_cap.skipped
+    }
+
+    pub fn render(&mut self) {
+        if let Some((width, height)) = self.sizing.take_pending() {
+            let _ = self.resize(width, height);
+        }
+        if let Some(message) = self.quirks.note_frame() {
+            self.events.emit(EventKind::QuirkApplied, &message);
+        }
+        let frame_count = self.get_frame_count();
+        self.stats_stream.note_frame(frame_count);
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.note_frame() {
+            self.telemetry.send(Some(self.metrics.snapshot()), self.backend_name(), self.get_particle_count());
+        }
+        self.observers.note_frame(frame_count);
+        self.timeline.note_frame(frame_count);
+        let start = timing::now_ms();
+        viewport::begin_canvas(&self.ctx, self.viewport);
+        self.render_contents();
+        self.ctx.restore();
+        self.metrics.record_render(start);
+        self.sim.lod.record_render(timing::now_ms() - start, self.sim.particle_count);
+        // Canvas2D は頂点データを持たないので、色ごとにまとめる位置の配列を数える
+        let batched: usize = self.batches.iter().map(|batch| batch.capacity()).sum();
+        self.metrics.record_buffer_bytes(self.sim.buffer_bytes() + (batched * std::mem::size_of::<(f64, f64, f64)>()) as u64);
+    }
+
+    // 描画せずに n ステップ連続で物理演算し、経過時間(ms)を返す
+    // JS⇔WASMの境界を1回しか跨がないので純粋な物理演算性能を測れる
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..n {
+            self.update();
+        }
+        let elapsed = timing::now_ms() - start;
+        self.observers
+            .note_complete(n, elapsed, false, self.get_frame_count());
+        elapsed
+    }
+
+    // 最初の frames フレームを今すぐ物理演算と描画まで進め、計測から除く（経過時間(ms)を返す）
+    // シェーダーのコンパイルや JIT・バッファの確保を済ませ、get_metrics() が落ち着いた状態の値になる
+    // 呼ばなくても最初の数フレームは除く（warmup(0) なら除かない）
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        self.metrics.begin_warmup(frames);
+        let start = timing::now_ms();
+        for _ in 0..frames {
+            self.update();
+            self.render();
+        }
+        timing::now_ms() - start
+    }
+
+    // 止まるまで（平均速度が threshold px/フレームを下回るまで）最大 max_frames ステップ物理演算し、
+    // 経過時間(ms)を返す。止まるまでのフレーム数は get_frames_until_settle() で取れる
+    pub fn simulate_until_settled(&mut self, threshold: f32, max_frames: u32) -> f64 {
+        self.set_settle_threshold(Some(threshold));
+        let start = timing::now_ms();
+        let mut frames = 0;
+        for _ in 0..max_frames {
+            self.update();
+            frames += 1;
+            if self.is_settled() {
+                break;
             }
+        }
+        let elapsed = timing::now_ms() - start;
+        let (settled, frame_count) = (self.is_settled(), self.get_frame_count());
+        self.observers
+            .note_complete(frames, elapsed, settled, frame_count);
+        elapsed
+    }
+
+    // 平均速度が threshold を下回ったら止まったとみなす終了条件（None で解除）
+    // 固定フレーム数より、跳ねて止まるまでを測るシーンに向いている
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        self.sim.set_settle_threshold(threshold);
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.get_frames_until_settle().is_some()
+    }
+
+    // 終了条件を設定してから止まるまでのフレーム数（まだ止まっていなければ None）
+    pub fn get_frames_until_settle(&self) -> Option<u32> {
+        self.sim.settle.and_then(|settle| settle.frames_until_settle())
+    }
+
+    pub fn get_mean_speed(&self) -> f32 {
+        self.sim.mean_speed()
+    }
+
+    // 未生成のパーティクルを最大 max_particles 個生成し、進捗(0.0~1.0)を返す
+    pub fn init_chunk(&mut self, max_particles: usize) -> f32 {
+        let start = timing::now_ms();
+        self.sim.spawn_pending(max_particles);
+        self.init.timings.particles_ms += timing::now_ms() - start;
+        let (spawned, total) = (self.sim.spawned(), self.sim.particle_count);
+        self.init.report(spawned, total);
+        progress(spawned, total)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.sim.spawned() == self.sim.particle_count
+    }
+
+    // init_chunk() のたびに (progress, spawned, total) で呼ばれる
+    pub fn set_init_progress_callback(&mut self, callback: js_sys::Function) {
+        self.init.set_callback(callback);
+    }
+
+    // 生成開始から全パーティクルが揃った最初のフレームまでの時間(ms)
+    pub fn get_time_to_first_frame(&self) -> f64 {
+        self.init.time_to_first_frame()
+    }
+
+    // コンストラクタ各段階の所要時間
+    pub fn get_init_timings(&self) -> InitTimings {
+        self.init.timings()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        match &self.scene {
+            Some(scene) => scene.frame_count(),
+            None => self.sim.frame_count,
+        }
+    }
 
-            // 色を変化
-            p.hue = (p.hue + 0.3) % 360.0;
+    // コンテキスト・バッファ・プログラムを作り直さずにシーンを切り替える
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        if self.strict {
+            require_backend(kind, self.backend_name())?;
+        }
+        self.events.clear_reported();
+        self.scene = create_scene(kind, self.sim.width, self.sim.height)?;
+        if self.scene.is_none() {
+            self.reset();
         }
+        self.apply_load();
+        Ok(())
+    }
 
-        self.frame_count += 1;
+    // セル数を指定してライフゲームに切り替える（数百万セルまで指定できる）
+    pub fn switch_to_life_grid(&mut self, columns: usize, rows: usize) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_life_scene(
+            columns,
+            rows,
+            self.sim.width,
+            self.sim.height,
+        )?);
+        self.apply_load();
+        Ok(())
     }
 
-    pub fn render(&self) {
-        let ctx = &self.ctx;
+    // 決まった数（負荷 0.5 のとき）の点を size px で描くシーンに切り替える（フィルレートの計測用）
+    // get_max_point_size() より大きい点は上限の大きさで描く
+    pub fn switch_to_point_size(&mut self, count: usize, size: f32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_point_size_scene(
+            count,
+            size,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // キャンバスの一辺の scale 倍の画像を毎フレーム putImageData で送り直すシーンに切り替える（転送帯域の計測用）
+    pub fn switch_to_texture_upload(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_texture_upload_scene(scale, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描いた直後にキャンバスの一辺の scale 倍の範囲を getImageData で読み戻すシーンに切り替える（読み戻しの計測用）
+    pub fn switch_to_readback(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_readback_scene(scale, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 領域の数を決めてクリップのシーンに切り替える（1〜64 に丸める）
+    pub fn switch_to_clip(&mut self, regions: u32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_clip_scene(regions, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 合成方法を1つに決めて重ねた矩形のシーンに切り替える
+    pub fn switch_to_compositing(&mut self, mode: CompositeMode) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_composite_scene(mode, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 影のぼかしの大きさ(px)を決めて光る円のシーンに切り替える（0〜64 に丸める）
+    // shadowBlur はぼかしが大きいほど重くなるので、大きさを変えながら計測できるようにする
+    pub fn switch_to_shadows(&mut self, shadow_blur: f32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_shadow_scene(shadow_blur, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // change_every 個の矩形ごとに fillStyle・globalAlpha・変形・クリップを切り替えるシーンに切り替える
+    // 1 なら毎回切り替え、大きくするほど同じ状態で続けて描く（状態変更のコストの計測用）
+    pub fn switch_to_state_changes(&mut self, change_every: u32) -> Result<(), JsValue> {
+        self.events.clear_reported();
+        self.scene = Some(create_state_change_scene(change_every, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 点のシーンで描ける一番大きい点(px)
+    pub fn get_max_point_size(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    // 任意の画像（width × height のRGBA）を毎フレームぼかすシーンに切り替える
+    // 描画先が method に対応していなければWASMでぼかす
+    // radius は 16px までに丸める
+    pub fn switch_to_blur(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+        method: BlurMethod,
+        radius: u32,
+    ) -> Result<(), JsValue> {
+        if self.strict && method == BlurMethod::Shader {
+            return Err(tr(Text::StrictBlurUnsupported, &[&"shader"]).into());
+        }
+        self.events.clear_reported();
+        self.scene = Some(create_blur_scene(width, height, rgba, method, radius)?);
+        self.apply_load();
+        Ok(())
+    }
 
-        // 画面クリア
-        ctx.set_fill_style(&JsValue::from_str("rgba(17, 17, 17, 1)"));
-        ctx.fill_rect(0.0, 0.0, self.width as f64, self.height as f64);
-
-        // 各パーティクルを描画（Canvas 2D APIで1個ずつ！）
-        for p in &self.particles {
-            let rgb = hsl_to_rgb(p.hue, 1.0, 0.5);
-            let color = format!(
-                "rgba({}, {}, {}, 0.8)",
-                (rgb.0 * 255.0) as u8,
-                (rgb.1 * 255.0) as u8,
-                (rgb.2 * 255.0) as u8
+    // シーンの負荷を 0.0~1.0 で指定する（パーティクル数・格子の大きさ・曲線の数など）
+    // 0.5 がおおむね各シーンの既定。シーンを切り替えても引き継ぐ
+    pub fn set_load(&mut self, load: f32) {
+        if !(0.0..=1.0).contains(&load) {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::LoadClamped, &[&load]),
             );
+        }
+        self.load = Some(load.clamp(0.0, 1.0));
+        self.apply_load();
+    }
 
-            ctx.set_fill_style(&JsValue::from_str(&color));
-            ctx.begin_path();
-            let _ = ctx.arc(p.x as f64, p.y as f64, 2.5, 0.0, 2.0 * PI as f64);
-            ctx.fill();
+    pub fn get_load(&self) -> Option<f32> {
+        self.load
+    }
+
+    // 動いているパーティクルの数をその場で増減する（バッファの作り直しやリセットはしない）
+    // 作成時の数までで、それより多ければ丸めて ParameterClamped を送る。実際の数を返す
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let applied = self.sim.set_particle_count(count);
+        if applied != count {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ParticleCountClamped, &[&count, &applied]),
+            );
         }
+        applied
     }
 
-    pub fn get_frame_count(&self) -> u32 {
-        self.frame_count
+    pub fn get_particle_count(&self) -> usize {
+        self.sim.particle_count
+    }
+
+    // 作成時の数を超えてパーティクル数を増やし、実際の数を返す（メモリ予算を超える数はエラー）
+    // 確保済みの領域を超えた分は伸ばしながら確保するので、メモリの増え方による停止の計測に使う
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, JsValue> {
+        let applied = self.sim.grow_particle_count(count)?;
+        Ok(applied)
+    }
+
+    // set_particle_count() で増やせる上限（作成時の数か、grow_particle_count() で増やした数）
+    pub fn get_max_particles(&self) -> usize {
+        self.sim.max_particles()
+    }
+
+    // 現在のシーン・キャンバスの大きさ・負荷で固定シードの実行をAPNGに書き出す
+    // 描画はWASM内で行うので、どのバックエンドから書き出しても同じアニメーションになる
+    pub fn export_animation(&self, frames: u32, every_n: u32) -> Result<Vec<u8>, JsValue> {
+        export::export_scene_animation(
+            self.get_scene(),
+            self.sim.width as u32,
+            self.sim.height as u32,
+            self.sim.max_particles(),
+            self.load,
+            frames,
+            every_n,
+        )
+    }
+
+    // 最後に描いたフレームを上の行から並べた RGBA で読み戻す（大きさはキャンバスの描画バッファの画素数）
+    // compare_frames() でバックエンド同士や変更前後の見た目を比べる
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        let (width, height) = match self.ctx.canvas() {
+            Some(canvas) => (canvas.width(), canvas.height()),
+            None => self.sizing.backing_size(self.sim.width, self.sim.height),
+        };
+        let image = self.ctx.get_image_data(0.0, 0.0, width as f64, height as f64)?;
+        Ok(image.data().0)
+    }
+
+    // 今のシーンの途中のバッファを PNG で取り出す（パーティクル描画には途中のバッファがないので空）
+    pub fn capture_debug_buffers(&self) -> Result<Vec<CapturedBuffer>, JsValue> {
+        match &self.scene {
+            Some(scene) => capture::capture(scene.debug_buffers()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // 実際に効いている設定（シーン・数・モード・機能・ビルド）のハッシュ
+    // 値が違う計測結果同士は比べられない
+    pub fn get_config_fingerprint(&self) -> String {
+        self.config_fingerprint().hash()
+    }
+
+    // ハッシュの元になった設定の一覧（"key=value;" の並び）
+    pub fn get_config_summary(&self) -> String {
+        self.config_fingerprint().into_text()
+    }
+
+    // 溜まっている警告イベントを取り出す（古い順）
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        self.events.drain()
+    }
+
+    // 警告イベントが起きるたびに callback(event) を呼ぶ（登録中は poll_events() には溜まらない）
+    pub fn set_event_callback(&mut self, callback: js_sys::Function) {
+        self.events.set_callback(callback);
+    }
+
+    pub fn clear_event_callback(&mut self) {
+        self.events.clear_callback();
+    }
+
+    // interval_ms（50ms以上）ごとに callback(StatsDelta) を呼ぶ（毎フレームは呼ばない）
+    pub fn subscribe_stats(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        self.stats_stream.subscribe(callback, interval_ms);
+    }
+
+    pub fn unsubscribe_stats(&mut self) {
+        self.stats_stream.unsubscribe();
+    }
+
+    // 1秒ごとに計測値のまとめ（JSON の文字列）を BroadcastChannel(name) に送る（telemetry フィーチャー）
+    // 同じオリジンの別のタブで new BroadcastChannel(name) を開けば、複数の端末やタブの結果を1か所で見られる
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_channel(&mut self, name: &str) -> Result<(), JsValue> {
+        self.telemetry.start_channel(name)?;
+        Ok(())
+    }
+
+    // 同じまとめを WebSocket で集計サーバーに送る（url は ws:// か wss://）
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_socket(&mut self, url: &str) -> Result<(), JsValue> {
+        self.telemetry.start_socket(url)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn stop_telemetry(&mut self) {
+        self.telemetry.stop();
+    }
+
+    // render() のたびに callback(FrameNotice) を呼ぶ
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        self.observers.on_frame(callback);
+    }
+
+    // simulate_frames()・simulate_until_settled() が終わったら callback(BenchmarkComplete) を呼ぶ
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        self.observers.on_benchmark_complete(callback);
+    }
+
+    // 500ms ごとの FPS が threshold を下回ったら callback(FpsDrop) を呼ぶ（上回るまでは1回だけ）
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        self.observers.on_fps_drop(threshold, callback);
+    }
+
+    // on_frame()・on_benchmark_complete()・on_fps_drop() の登録をすべて外す
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // ホストのページの出来事（クリック・データの読み込み・タブへの復帰など）を今の時刻の印として残す
+    // 印は直近のフレーム時間と一緒に get_timeline_json() と export_results() に書き出される
+    pub fn annotate(&mut self, label: &str) {
+        let frame_count = self.get_frame_count();
+        self.timeline.annotate(label, frame_count);
+    }
+
+    // 直近のフレーム時間と annotate() の印（時刻はどちらも performance.now() の ms）
+    pub fn get_timeline_json(&self) -> String {
+        self.timeline.to_json()
+    }
+
+    pub fn clear_timeline(&mut self) {
+        self.timeline.clear();
+    }
+
+    // 2D のコンテキストは WebGL のように失われて描けなくなることはないので、常に false
+    pub fn context_lost(&self) -> bool {
+        false
+    }
+
+    pub fn get_context_losses(&self) -> u32 {
+        0
+    }
+
+    // update()・render() の所要時間とFPS
+    pub fn get_metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    pub fn get_metrics_json(&self) -> String {
+        self.metrics.snapshot().to_json()
+    }
+
+    // これまでの render() の間隔を bucket_ms 幅でまとめた分布と、パーセンタイル・長いフレームの回数
+    pub fn get_frame_histogram(&self, bucket_ms: f64) -> FrameHistogram {
+        self.metrics.frame_histogram(bucket_ms)
+    }
+
+    pub fn reset_frame_histogram(&mut self) {
+        self.metrics.reset_frame_histogram();
+    }
+
+    // 計測値と実行の情報（バックエンド・パーティクル数・キャンバスの大きさ・シード・ビルドのフィーチャー）を書き出す
+    // user_agent は JS から navigator.userAgent を渡す（set_metadata() の内容も入る）
+    pub fn export_results(&self, format: ResultFormat, user_agent: &str) -> String {
+        let (width, height) = self.size();
+        let info = RunInfo {
+            backend: self.backend_name(),
+            particle_count: self.sim.particle_count,
+            width,
+            height,
+            seed: self.sim.seed,
+            tuning_profile: self.tuning.map(TuningProfile::name),
+            user_agent,
+        };
+        results::export(
+            format,
+            &info,
+            &self.metrics.snapshot(),
+            &self.metrics.frame_histogram(1.0),
+            &self.timeline,
+        )
+    }
+
+    // export_results() を filename のファイルとしてダウンロードさせる
+    pub fn download_results(
+        &self,
+        format: ResultFormat,
+        user_agent: &str,
+        filename: &str,
+    ) -> Result<(), JsValue> {
+        results::download(&self.export_results(format, user_agent), format, filename)
+    }
+
+    // 左半分を mode_a、右半分を mode_b で描いて見た目と負荷を同時に比較する
+    pub fn set_split_compare(&mut self, mode_a: RenderMode, mode_b: RenderMode) {
+        self.split_compare = Some((mode_a, mode_b));
+    }
+
+    pub fn clear_split_compare(&mut self) {
+        self.split_compare = None;
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        self.scene.as_ref().map_or(SceneKind::Particles, |scene| scene.kind())
+    }
+
+    // 現在のシーンの名前・パラメーター・対応バックエンド
+    pub fn describe_current_scene(&self) -> SceneDescription {
+        match &self.scene {
+            Some(scene) => scene.describe(),
+            None => describe_scene(SceneKind::Particles),
+        }
+    }
+
+    // 状態のダブルバッファリング（描画中に次の状態を書けるようにする）
+    pub fn set_double_buffered(&mut self, enabled: bool) {
+        self.sim.set_double_buffered(enabled);
+    }
+
+    pub fn is_double_buffered(&self) -> bool {
+        self.sim.is_double_buffered()
+    }
+
+    // 公開済み状態のシーケンス番号
+    pub fn get_state_sequence(&self) -> u64 {
+        self.sim.sequence()
+    }
+
+    // 直近の render() が描いた状態のシーケンス番号
+    pub fn get_rendered_sequence(&self) -> u64 {
+        self.read_stamps.last_rendered
+    }
+
+    // 読み取り中に状態が書き換わったフレーム数（ダブルバッファなら常に0）
+    pub fn get_torn_frame_count(&self) -> u32 {
+        self.read_stamps.torn_frames
+    }
+
+    // 矩形内のパーティクルを選択して強調表示する。選択数を返す
+    pub fn select_region(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> usize {
+        self.sim.selection = selection::select_region(self.sim.particles(), x0, y0, x1, y1);
+        self.sim.selection.len()
+    }
+
+    // 投げ縄選択。points は [x0, y0, x1, y1, ...] の多角形の頂点列。選択数を返す
+    pub fn select_polygon(&mut self, points: &[f32]) -> usize {
+        self.sim.selection = selection::select_polygon(self.sim.particles(), points);
+        self.sim.selection.len()
+    }
+
+    // 選択中のパーティクルのインデックス
+    pub fn get_selection(&self) -> Vec<u32> {
+        self.sim.selection.clone()
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.sim.selection.clear();
+    }
+
+    // 位置のk-meansクラスタで色分けする（interval_frames ごとに再計算）
+    pub fn set_cluster_coloring(&mut self, k: u32, interval_frames: u32) {
+        self.sim.clustering = Some(KMeans::new(k, interval_frames));
+    }
+
+    pub fn disable_cluster_coloring(&mut self) {
+        self.sim.clustering = None;
+    }
+
+    // 直近のクラスタリングにかかった時間(ms)
+    pub fn get_last_cluster_ms(&self) -> f64 {
+        self.sim.clustering.as_ref().map_or(0.0, |kmeans| kmeans.last_run_ms)
+    }
+
+    // クラスタリングのような重い処理に1フレームで使ってよい時間(ms)。超える分は次のフレームに持ち越す
+    // 0 なら分けずに一度に計算する（既定。周期的なスパイクをそのまま測る）。実際に使う値を返す
+    pub fn set_frame_budget(&mut self, budget_ms: f64) -> f64 {
+        self.sim.scheduler.set_budget_ms(budget_ms)
+    }
+
+    pub fn get_scheduler_stats(&self) -> SchedulerStats {
+        self.sim.scheduler.stats()
+    }
+
+    pub fn reset_scheduler_stats(&mut self) {
+        self.sim.scheduler.reset_stats();
     }
 
     pub fn reset(&mut self) {
-        self.particles = create_particles(self.width, self.height, self.particle_count);
-        self.frame_count = 0;
+        self.sim.charges.clear();
+        if let Some(scene) = &mut self.scene {
+            if scene.reset() {
+                return;
+            }
+            // 一度作れたシーンなので作り直しに失敗することはまずないが、失敗したら今のまま続ける
+            if let Ok(scene) = create_scene(scene.kind(), self.sim.width, self.sim.height) {
+                self.scene = scene;
+                self.apply_load();
+            }
+            return;
+        }
+
+        self.sim.reset();
+    }
+
+    // パーティクルの位置・速度・色相・寿命とフレーム数をバイト列に書き出す
+    // restore() に渡せば、どのバックエンドでも同じ途中の状態から続けられる（爆発の取り消しなどにも使える）
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.sim.snapshot()
+    }
+
+    // snapshot() の状態に戻す（パーティクル数も保存したときの数になる。シーンの状態は含まない）
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.sim.restore(snapshot::decode(bytes)?)?;
+        Ok(())
+    }
+
+    // (x, y) から radius 以内にあるパーティクルの数（座標はシミュレーションの px。カメラで動かした画面の位置とは違う）
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.sim.count_within(x, y, radius)
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス（パーティクルがなければ undefined）
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.sim.nearest(x, y).map(|index| index as u32)
+    }
+
+    // index 番目のパーティクルの位置・速度・色相・寿命（範囲外なら undefined）
+    pub fn get_particle(&self, index: u32) -> Option<ParticleInfo> {
+        self.sim.particle_info(index as usize)
+    }
+
+    // 以後の生成と reset() の乱数を seed から作り、パーティクルを作り直す
+    // 同じシードなら実行やバックエンドが違っても同じ初期状態から始まる
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.seed = Some(seed);
+        rng::with_seed(seed, || self.reset());
+    }
+
+    // 乱数を毎回違うものに戻す（今のパーティクルはそのまま）
+    pub fn clear_seed(&mut self) {
+        self.sim.seed = None;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.sim.seed
     }
 
     // クリックで爆発!
     pub fn explode(&mut self, click_x: f32, click_y: f32) {
-        for p in &mut self.particles {
-            let dx = p.x - click_x;
-            let dy = p.y - click_y;
-            let dist = (dx * dx + dy * dy).sqrt();
+        if let Some(scene) = &mut self.scene {
+            scene.explode(click_x, click_y, &self.explosion);
+            return;
+        }
 
-            // 近いパーティクルほど強く吹き飛ぶ
-            if dist < 200.0 {
-                let force = EXPLOSION_FORCE * (1.0 - dist / 200.0);
-                let angle = dy.atan2(dx);
-                p.vx += angle.cos() * force;
-                p.vy += angle.sin() * force;
-            }
+        self.sim.explode(click_x, click_y, &self.explosion);
+    }
+
+    // 同じ範囲・減衰で中心へ吸い寄せる
+    pub fn implode(&mut self, x: f32, y: f32) {
+        let implosion = self.explosion.inverted();
+        if let Some(scene) = &mut self.scene {
+            scene.explode(x, y, &implosion);
+            return;
         }
+
+        self.sim.explode(x, y, &implosion);
+    }
+
+    // (x, y) の近くのパーティクルを (dx, dy) の向きに押し流す（シーンの描画中は何もしない）
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() {
+            self.sim.stir(x, y, dx, dy);
+        }
+    }
+
+    // (x, y) を (dx, dy)(px) 動いたポインターの勢いを、次の update() で近くのパーティクルに渡す
+    // 押し流しに加えて周りに渦ができる（シーンの描画中は何もしない）
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() {
+            self.sim.pointer.push_move(x, y, dx, dy);
+        }
+    }
+
+    // ドラッグ中のポインターの位置を渡す（動いた量は前に渡した位置から求め、strength 倍にする）
+    // strength が負なら渦の向きが逆になる
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        if self.scene.is_none() {
+            self.sim.pointer.push_drag(x, y, strength);
+        }
+    }
+
+    // delay_frames 回後の update() の最初に explode(x, y) する（0 ならすぐに爆発する）
+    // 仕掛けた時点の設定で爆発し、シーンを切り替えても残る（reset() で取り消す）
+    pub fn schedule_explosion(&mut self, x: f32, y: f32, delay_frames: u32) {
+        if delay_frames == 0 {
+            self.explode(x, y);
+            return;
+        }
+        self.sim.charges.schedule(x, y, delay_frames, self.explosion);
+    }
+
+    // まだ爆発していない schedule_explosion() の数
+    pub fn get_pending_explosions(&self) -> usize {
+        self.sim.charges.len()
     }
-}
 
-// パーティクル生成
-fn create_particles(width: f32, height: f32, particle_count: usize) -> Vec<Particle> {
-    let mut rng = rand::thread_rng();
-    (0..particle_count)
-        .map(|_| {
-            let angle = rng.gen::<f32>() * 2.0 * PI;
-            let speed = rng.gen::<f32>() * 2.0 + 1.0;
-            Particle {
-                x: width / 2.0,
-                y: height / 4.0,
-                vx: angle.cos() * speed,
-                vy: angle.sin() * speed - 3.0,
-                hue: rng.gen::<f32>() * 360.0,
-            }
-        })
-        .collect()
+    // 引力点を置いて番号を返す。update() のたびに半径の中のパーティクルを中心へ引き寄せる
+    // （strength が負なら押しのける。マウスで引きずるときは move_attractor() で動かす）
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        self.sim.attractors.add(x, y, strength, radius)
+    }
+
+    // 見つからなければ false
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        self.sim.attractors.remove(id)
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        self.sim.attractors.move_to(id, x, y)
+    }
+
+    pub fn clear_attractors(&mut self) {
+        self.sim.attractors.clear();
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        self.sim.attractors.list()
+    }
+
+    // 中心 (x, y)・半径 radius の円の障害物を置く。update() のたびに当たったパーティクルを跳ね返す
+    // （跳ね返りの割合は壁と同じ bounce。NaN などや半径が 0 以下なら置かずに false）
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        self.sim.obstacles.add_circle(x, y, radius)
+    }
+
+    // (x0, y0)〜(x1, y1) の線分の障害物を置く（1ステップで横切るほど速いパーティクルも跳ね返す）
+    pub fn add_line_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        self.sim.obstacles.add_line(x0, y0, x1, y1)
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.sim.obstacles.clear();
+    }
+
+    pub fn get_obstacles(&self) -> Vec<Obstacle> {
+        self.sim.obstacles.list()
+    }
+
+    // 毎フレーム rate 個ずつパーティクルを出すエミッターを置いて番号を返す
+    // 出したパーティクルは寿命が減るにつれて薄くなり、尽きると同じ場所を使って出し直される
+    // （最初の1つを置くと、今いるパーティクルも順に尽きてエミッターのものに入れ替わる）
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        self.sim.add_emitter(x, y, rate, spread, speed)
+    }
+
+    // 見つからなければ false
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        self.sim.emitters.remove(id)
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.sim.emitters.clear();
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        self.sim.emitters.list()
+    }
+
+    // エミッターから出したパーティクルの累計
+    pub fn get_emitted_count(&self) -> f64 {
+        self.sim.emitters.emitted as f64
+    }
+
+    // 放出したパーティクルの寿命(フレーム)を設定し、実際に使う値を返す（1フレーム以上）
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        self.sim.emitters.set_lifetime(frames)
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        self.sim.emitters.lifetime()
+    }
+
+    // シミュレーションの領域の大きさを変える（シーン表示中はシーンを新しい大きさで作り直す）
+    // 縮めたときに外に出たパーティクルは set_out_of_bounds() の方法で扱う
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        if let Some(scene) = &self.scene {
+            self.scene = create_scene(scene.kind(), width, height)?;
+        }
+        self.sim.resize(width, height);
+        self.apply_backing_size();
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描画バッファを表示サイズの ratio 倍にする（HiDPI で滲まないように devicePixelRatio を渡す）
+    // シミュレーションの座標は変わらない。範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        let ratio = self.sizing.set_pixel_ratio(ratio);
+        self.apply_backing_size();
+        ratio
+    }
+
+    pub fn get_pixel_ratio(&self) -> f32 {
+        self.sizing.pixel_ratio()
+    }
+
+    // 描画バッファを pixel_ratio のさらに scale 倍にする（2 なら 4 倍の画素を描くスーパーサンプリング、
+    // 0.5 なら 1/4 の画素を描いて拡大表示）。表示の大きさとシミュレーションの座標は変わらない
+    // 範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        let scale = self.sizing.set_render_scale(scale);
+        self.apply_backing_size();
+        scale
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.sizing.render_scale()
+    }
+
+    // ResizeObserver でキャンバスの表示サイズと devicePixelRatio を監視し、変わったら次の render() で resize() する
+    // ページ上の canvas 要素に描いているときだけ使える
+    pub fn observe_resize(&mut self) -> Result<(), JsValue> {
+        if self.viewport.is_some() {
+            return Err(tr(Text::ResizeObserverUnavailable, &[]).into());
+        }
+        let canvas = self.ctx.canvas().map(JsValue::from).ok_or_else(|| tr(Text::ResizeObserverUnavailable, &[]))?;
+        self.sizing.observe(&canvas)
+    }
+
+    pub fn unobserve_resize(&mut self) {
+        self.sizing.unobserve();
+    }
+
+    // キャンバスのポインター・ホイール・タッチのイベントを受け取り、次の update() で
+    // クリック（タップ）は explode()、ドラッグは stir()、ホイール・ピンチ・シフト＋ドラッグは視点の移動にする
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        let canvas = self.ctx.canvas().map(JsValue::from).ok_or_else(|| tr(Text::InputUnavailable, &[]))?;
+        self.input = Some(InputHandlers::attach(&canvas)?);
+        Ok(())
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        self.input = None;
+    }
+
+    // 他のバックエンドと同じ呼び方で破棄する（GPU のリソースはないので、リスナーを外して捨てるだけ）
+    pub fn dispose(self) {}
+
+    // 視点を元に戻す（拡大率 1、平行移動なし）
+    pub fn reset_camera(&mut self) {
+        self.sim.camera = Camera::default();
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        self.sim.camera.zoom
+    }
+
+    // 色相の変化や選択範囲の重ね描きなど見た目だけの毎フレームの処理を止め、
+    // 物理演算と描画だけを計測する（設定のハッシュにも含まれるので通常の計測とは混ざらない）
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        self.sim.cosmetic = !enabled;
+    }
+
+    pub fn is_strict_benchmark(&self) -> bool {
+        !self.sim.cosmetic
+    }
+
+    // 検出して回避策を使った（または記録した）ブラウザ固有の問題の名前
+    pub fn get_quirks(&self) -> Vec<String> {
+        self.quirks.applied().iter().map(|quirk| quirk.name().to_string()).collect()
+    }
+
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.sim.out_of_bounds = policy;
+    }
+
+    pub fn get_out_of_bounds(&self) -> OutOfBounds {
+        self.sim.out_of_bounds
+    }
+
+    // 壁に届いたパーティクルを跳ね返すか・折り返すか・生成し直すか・そのまま出すか
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.sim.boundary = mode;
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        self.sim.boundary
+    }
+
+    // パーティクルに奥行きを持たせて透視投影で描くかを切り替える（既定は Flat）
+    // WebGL がシェーダーで行う投影を、描く前に CPU で1個ずつ行う
+    // ソフトウェア描画（set_software_mode）では奥行きを無視して平面に描く
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) -> Result<(), JsValue> {
+        self.sim.set_projection(mode);
+        Ok(())
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        self.sim.projection()
+    }
+
+    // explode() の半径・強さ・減衰曲線（シーンを切り替えても引き継ぐ）
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        self.explosion = *config;
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        self.explosion
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        self.sim.config = config.clamped();
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        self.sim.config
+    }
+
+    // デモのスライダー用に1項目ずつ変える
+    pub fn set_gravity(&mut self, gravity: f32) {
+        self.set_simulation_config(&SimulationConfig { gravity, ..self.sim.config });
+    }
+
+    pub fn set_bounce(&mut self, bounce: f32) {
+        self.set_simulation_config(&SimulationConfig { bounce, ..self.sim.config });
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        self.set_simulation_config(&SimulationConfig { friction, ..self.sim.config });
+    }
+
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.set_simulation_config(&SimulationConfig { point_size, ..self.sim.config });
+    }
+
+    // パーティクルの形を切り替える（既定は円）
+    // WebGL と同じ見た目で比べるときは、両方に同じ形を指定する
+    pub fn set_particle_shape(&mut self, shape: ParticleShape) -> Result<(), JsValue> {
+        if self.software.is_some() && shape != ParticleShape::Square {
+            return Err(tr(Text::ParticleShapeUnavailable, &[&shape.name(), &"software"]).into());
+        }
+        if shape == ParticleShape::Sprite && self.sprite_atlas.is_none() {
+            let mut cache = None;
+            atlas_canvas(&mut cache, &particle_shape::hue_atlas())
+                .ok_or_else(|| tr(Text::SpriteAtlasFailed, &[]))?;
+            self.sprite_atlas = cache.map(|(_, canvas)| canvas);
+        }
+        self.shape = shape;
+        Ok(())
+    }
+
+    pub fn get_particle_shape(&self) -> ParticleShape {
+        self.shape
+    }
+
+    // パーティクルを image（HTMLImageElement か ImageBitmap）のスプライトで描く（形は Sprite になる）
+    // drawImage で色を掛けられないので、一辺 SPRITE_SIZE に縮めた画像に色相ごとの色を掛けたアトラスを作っておく
+    pub fn set_particle_texture(&mut self, image: &JsValue) -> Result<(), JsValue> {
+        if self.software.is_some() {
+            return Err(tr(Text::ParticleShapeUnavailable, &[&ParticleShape::Sprite.name(), &"software"]).into());
+        }
+        let image = DecodedImage::from_js(image).ok_or_else(|| tr(Text::ParticleTextureInvalid, &[]))?;
+        let pixels = image_source::read_pixels(&image, SPRITE_SIZE, SPRITE_SIZE)?;
+        let mut cache = None;
+        atlas_canvas(&mut cache, &particle_shape::tinted_atlas(&pixels))
+            .ok_or_else(|| tr(Text::SpriteAtlasFailed, &[]))?;
+        self.sprite_atlas = cache.map(|(_, canvas)| canvas);
+        self.particle_texture = Some(image.size());
+        self.shape = ParticleShape::Sprite;
+        Ok(())
+    }
+
+    // 組み込みの光の玉のスプライトに戻す（形は変えない）
+    pub fn clear_particle_texture(&mut self) -> Result<(), JsValue> {
+        if self.particle_texture.take().is_none() {
+            return Ok(());
+        }
+        self.sprite_atlas = None;
+        if self.shape == ParticleShape::Sprite {
+            self.set_particle_shape(ParticleShape::Sprite)?;
+        }
+        Ok(())
+    }
+
+    // パーティクルごとの大きさと不透明度（既定はすべて 1）
+    // 半径に大きさの倍率を掛け、globalAlpha に不透明度を掛けて描く
+    pub fn set_particle_style(&mut self, style: &ParticleStyle) -> Result<(), JsValue> {
+        if self.software.is_some() && !style.sanitized().is_uniform() {
+            return Err(tr(Text::ParticleStyleUnavailable, &[&style.describe(), &"software"]).into());
+        }
+        self.sim.set_style(*style);
+        Ok(())
+    }
+
+    pub fn get_particle_style(&self) -> ParticleStyle {
+        self.sim.style()
+    }
+
+    // パーティクルの合成方法を切り替える（Particles シーンだけに効く）
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> Result<(), JsValue> {
+        if self.software.is_some() && mode == RenderMode::Trails {
+            return Err(tr(Text::RenderModeUnavailable, &[&mode.name(), &"software"]).into());
+        }
+        self.render_mode = mode;
+        Ok(())
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    // 描くパーティクルを skip 個に1個に間引く（物理演算はすべてに行う。mode が Off か skip が 1 なら間引かない）
+    // シーンの表示中は間引かない
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        self.sim.lod.set_fixed(mode, skip);
+    }
+
+    // render() の時間が target_render_ms を超えないように、間引く間隔を AutoScaler と同じ探し方で決める
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        let particle_count = self.sim.particle_count;
+        self.sim.lod.set_adaptive(mode, target_render_ms, particle_count);
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        self.sim.lod.mode()
+    }
+
+    // 直前のフレームで何個に1個描いたか（間引いていなければ 1）
+    pub fn get_lod_skip(&self) -> u32 {
+        self.sim.visible_skip()
+    }
+
+    // 直前のフレームで描いたパーティクル数
+    pub fn get_visible_particle_count(&self) -> usize {
+        self.sim.visible().len()
+    }
+
+    // パーティクルの描き方を切り替える（既定は1個ずつ）
+    // Batched は色相と不透明度を丸めるので色が少し粗くなり、同じ色で重なったところも1回分しか塗られない
+    // Sprite の形は drawImage をまとめられないので常に1個ずつ描く
+    pub fn set_draw_strategy(&mut self, strategy: DrawStrategy) -> Result<(), JsValue> {
+        if self.software.is_some() && strategy != DrawStrategy::Batched {
+            return Err(tr(Text::DrawStrategyUnavailable, &[&strategy.name(), &"software"]).into());
+        }
+        self.draw_strategy = strategy;
+        Ok(())
+    }
+
+    pub fn get_draw_strategy(&self) -> DrawStrategy {
+        self.draw_strategy
+    }
+
+    // 頂点バッファを使わないので、送り方は BufferData（何もしない）だけ
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) -> Result<(), JsValue> {
+        if strategy != UploadStrategy::BufferData {
+            return Err(tr(Text::UploadStrategyUnavailable, &[&strategy.name(), &self.backend_name()]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_upload_strategy(&self) -> UploadStrategy {
+        UploadStrategy::BufferData
+    }
+
+    // 名前の付いた設定の組（合成方法・点の大きさ・描画解像度・精度・送り方・間引き・fps の上限）をまとめて当てる
+    // 組の名前は get_config_fingerprint() に入るので、同じ組で測った結果同士を比べられる
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        let tuning = profile.settings(self.backend_kind());
+        self.set_upload_strategy(tuning.upload_strategy)?;
+        self.set_draw_strategy(tuning.draw_strategy)?;
+        self.set_render_mode(tuning.render_mode)?;
+        self.set_point_size(tuning.point_size);
+        self.set_render_scale(tuning.render_scale);
+        match tuning.adaptive_lod {
+            Some((mode, target_render_ms)) => self.set_adaptive_lod(mode, target_render_ms),
+            None => self.set_lod(LodMode::Off, 1),
+        }
+        self.set_target_fps(tuning.target_fps);
+        self.tuning = Some(profile);
+        Ok(())
+    }
+
+    // 最後に apply_tuning_profile() で当てた組（当てていなければ undefined）
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        self.tuning
+    }
+
+    pub fn set_buffer_layout(&mut self, layout: BufferLayout) -> Result<(), JsValue> {
+        if layout != BufferLayout::Planar {
+            return Err(tr(Text::BufferLayoutUnavailable, &[&layout.name(), &self.backend_name()]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_buffer_layout(&self) -> BufferLayout {
+        BufferLayout::Planar
+    }
+
+    // 塗りの色は CPU で決めるので Cpu だけ
+    pub fn set_color_mode(&mut self, mode: ColorPipeline) -> Result<(), JsValue> {
+        if mode != ColorPipeline::Cpu {
+            return Err(tr(Text::ColorModeUnavailable, &[&mode.name(), &self.backend_name()]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_color_mode(&self) -> ColorPipeline {
+        ColorPipeline::Cpu
+    }
+
+    // パーティクルの塗り方を切り替える（既定は RainbowCycle）
+    // colors は Palette と Monochrome の色を 0〜1 の r, g, b の順に並べたもの（ほかのモードでは空でよい）
+    pub fn set_particle_color_mode(&mut self, mode: ColorMode, colors: Vec<f32>) -> Result<(), JsValue> {
+        self.sim.theme.set_mode(mode, &colors)?;
+        Ok(())
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        self.sim.theme.mode()
+    }
+
+    // 背景色を 0〜1 の RGB で設定する（有限でない値を渡すと既定の灰色に戻す）
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.sim.theme.set_background(r, g, b);
+    }
+
+    // [r, g, b]（既定は rgb(17, 17, 17) で、画素に塗って描くときは GL と同じ灰色）
+    pub fn get_background_color(&self) -> Vec<f32> {
+        match (self.sim.theme.background, &self.software) {
+            (None, None) => vec![17.0 / 255.0; 3],
+            _ => self.sim.theme.background().to_vec(),
+        }
+    }
+
+    // GPU で物理演算できるのは WebGL2 だけなので Cpu だけ
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> Result<(), JsValue> {
+        if mode != PhysicsMode::Cpu {
+            return Err(tr(Text::PhysicsModeUnavailable, &[&mode.name(), &self.backend_name()]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_physics_mode(&self) -> PhysicsMode {
+        PhysicsMode::Cpu
+    }
+
+    // update() と爆発の計算を threads 個に分けて並列に行う（threads フィーチャーが必要）
+    // 実際に使う数を返す。フィーチャーなしでは1、ありでもプールのスレッド数までに丸める
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        let applied = self.sim.set_threads(threads);
+        if applied != threads {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ThreadCountClamped, &[&threads, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.sim.threads()
+    }
+
+    // 物理演算と色の変換を SIMD 版（4個ずつ）にするか切り替える。実際に使うかを返す
+    // simd フィーチャーなしでビルドしたときはスカラー版のまま
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        let applied = self.sim.set_simd(enabled);
+        if applied != enabled {
+            self.events
+                .emit(EventKind::FallbackUsed, &tr(Text::SimdUnavailable, &[]));
+        }
+        applied
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        self.sim.simd()
+    }
+
+    // パーティクル同士の衝突を切り替える（格子で近いものだけ調べるが、壁だけのときよりずっと重い）
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        self.sim.set_collisions(enabled);
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        self.sim.collisions()
+    }
+
+    // 全体に一様に吹く風（毎フレーム速度に足す px/フレーム²。重力と同じ単位）。(0, 0) で止める
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        self.sim.forces.set_wind(x, y);
+    }
+
+    // [x, y]
+    pub fn get_wind(&self) -> Vec<f32> {
+        let (x, y) = self.sim.forces.wind();
+        vec![x, y]
+    }
+
+    // パーティクルごとに curl ノイズの流れを加える（strength が 0 なら止める）
+    // scale は渦のおおよその大きさ(px)。場はゆっくり流れるので同じ場所でも向きが変わっていく
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        self.sim.forces.set_turbulence(strength, scale);
+    }
+
+    // [strength, scale]（止めていれば None）
+    pub fn get_turbulence(&self) -> Option<Vec<f32>> {
+        self.sim
+            .forces
+            .turbulence()
+            .map(|(strength, scale)| vec![strength, scale])
+    }
+
+    // 直近の update() で重なっていたパーティクルの組の数
+    pub fn get_contact_count(&self) -> usize {
+        self.sim.last_contacts()
+    }
+}
+
+impl ParticleSystemCanvas2D {
+    pub(crate) fn emit_event(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    // 1ピクセル読み戻して、溜まっている描画を終わらせる（待てたら true）
+    pub(crate) fn finish_gpu(&self) -> bool {
+        self.ctx.get_image_data(0.0, 0.0, 1.0, 1.0).is_ok()
+    }
+
+    // attach_input_handlers() で積まれた操作を取り出す（視点の操作はここで反映する）
+    pub(crate) fn take_input(&mut self) -> Vec<InputAction> {
+        match &self.input {
+            Some(input) => input.take_actions(&mut self.sim.camera),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn input_handlers(&self) -> Option<&InputHandlers> {
+        self.input.as_ref()
+    }
+
+    // 描いたフレームの上に rects を重ねる（領域分割しているときは描かない）
+    pub(crate) fn draw_overlay(&mut self, rects: &[OverlayRect]) -> bool {
+        if self.viewport.is_some() {
+            return false;
+        }
+        let ratio = self.sizing.backing_ratio() as f64;
+        self.ctx.save();
+        let _ = self.ctx.set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0);
+        panel::draw_2d(&self.ctx, rects);
+        self.ctx.restore();
+        true
+    }
+
+    fn apply_input(&mut self) {
+        for action in self.take_input() {
+            match action {
+                InputAction::Explode { x, y } => self.explode(x, y),
+                InputAction::Stir { x, y, dx, dy } => self.stir(x, y, dx, dy),
+            }
+        }
+    }
+
+    pub(crate) fn advance_timestep(&mut self, dt_ms: f64) -> u32 {
+        self.sim.timestep.advance(dt_ms)
+    }
+
+    pub(crate) fn cap_frame(&mut self, timestamp: f64) -> bool {
+        self.sim.frame_cap.tick(timestamp)
+    }
+
+    // update_with_dt() のステップの後で、描く位置を補間する（シーン表示中は何もしない）
+    pub(crate) fn interpolate_frame(&mut self) {
+        if self.scene.is_none() {
+            self.sim.interpolate();
+        }
+    }
+
+    // 今のシーンがスプライトを描くアトラス（画像の渡し方を切り替えるのに使う）
+    pub(crate) fn scene_atlas_mut(&mut self) -> Option<&mut Atlas> {
+        self.scene.as_mut()?.atlas_mut()
+    }
+
+    // シミュレーション空間の大きさ（記録する座標を割合に直すのに使う）
+    pub(crate) fn size(&self) -> (f32, f32) {
+        (self.sim.width, self.sim.height)
+    }
+
+    // 描き方を SoftwareRaster に替える（software バックエンド）
+    // 画素は正方形の点で1回だけ転送するので、形は Square、描き方は Batched に固定する
+    pub(crate) fn enable_software(&mut self) {
+        self.software = Some(SoftwareRaster::new());
+        self.shape = ParticleShape::Square;
+        self.draw_strategy = DrawStrategy::Batched;
+    }
+
+    fn backend_kind(&self) -> BackendKind {
+        if self.software.is_some() {
+            BackendKind::Software
+        } else {
+            BackendKind::Canvas2D
+        }
+    }
+
+    fn backend_name(&self) -> &'static str {
+        self.backend_kind().name()
+    }
+
+    fn projector(&self) -> Option<Projector> {
+        (self.sim.projection() == ProjectionMode::Perspective)
+            .then(|| Projector::new(self.sim.depth(), self.sim.camera, self.sim.width, self.sim.height))
+    }
+
+    // WASM内で塗ってから1回で転送する（領域分割時は倍率なしで領域の左上に置く）
+    // 選択中のパーティクルの強調は描かない
+    fn render_software(&mut self) {
+        let initialized = self.is_initialized();
+        let Some(software) = &mut self.software else {
+            return;
+        };
+        let (ratio, (x, y)) = match self.viewport {
+            Some(v) => (1.0, (v.x, v.y)),
+            None => (self.sizing.backing_ratio(), (0.0, 0.0)),
+        };
+        let surface = software.surface(self.sim.width, self.sim.height, ratio);
+        if let Some(scene) = &mut self.scene {
+            scene.render(surface);
+        } else {
+            let sequence_begin = self.sim.sequence();
+            surface.clear(self.sim.theme.background());
+            self.sim.prepare_visible();
+            let (particles, size, camera) = (self.sim.visible(), self.sim.config.point_size * 2.0, self.sim.camera);
+            let theme = &self.sim.theme;
+            match self.split_compare {
+                None => software.draw_particles(particles, theme, size, camera, self.render_mode, |_| true),
+                Some((mode_a, mode_b)) => {
+                    let half = self.sim.width / 2.0;
+                    software.draw_particles(particles, theme, size, camera, mode_a, |p| p.x < half);
+                    software.draw_particles(particles, theme, size, camera, mode_b, |p| p.x >= half);
+                }
+            }
+            if !self.sim.obstacles.is_empty() {
+                software.draw_points(&self.sim.obstacles.outline_points(size, camera), size * camera.zoom);
+            }
+            self.read_stamps.record(sequence_begin, self.sim.sequence());
+            self.init.mark_frame(initialized);
+        }
+        let _ = software.present(&self.ctx, x, y);
+    }
+
+    fn config_fingerprint(&self) -> ConfigFingerprint {
+        let mut config = ConfigFingerprint::new(self.backend_name());
+        config.field("strict", self.strict);
+        config.field("width", self.sim.width);
+        config.field("height", self.sim.height);
+        config.field("render_scale", self.sizing.render_scale());
+        config.field("out_of_bounds", self.sim.out_of_bounds.name());
+        config.field("boundary_mode", self.sim.boundary.name());
+        config.field("particle_color_mode", self.sim.theme.mode().name());
+        config.field("projection_mode", self.sim.projection().name());
+        config.field("gravity", self.sim.config.gravity);
+        config.field("bounce", self.sim.config.bounce);
+        config.field("friction", self.sim.config.friction);
+        config.field("point_size", self.sim.config.point_size);
+        config.field("threads", self.sim.threads());
+        config.field("simd", self.sim.simd());
+        config.field("collisions", self.sim.collisions());
+        config.field("attractors", self.sim.attractors.len());
+        config.field("forces", self.sim.forces.describe());
+        config.field("obstacles", self.sim.obstacles.len());
+        config.field("emitters", self.sim.emitters.len());
+        config.field("particle_lifetime", self.sim.emitters.lifetime());
+        config.field("strict_benchmark", !self.sim.cosmetic);
+        config.field("particle_shape", self.shape.name());
+        config.field("particle_texture", particle_shape::describe_texture(self.particle_texture));
+        config.field("particle_style", self.sim.style().describe());
+        config.field("render_mode", self.render_mode.name());
+        config.optional("tuning_profile", self.tuning.map(TuningProfile::name));
+        config.field("draw_strategy", self.draw_strategy.name());
+        config.field("quirks", self.quirks.fingerprint());
+        config.optional("settle_threshold", self.sim.settle.map(|settle| settle.threshold));
+        config.optional("seed", self.sim.seed);
+        config.optional(
+            "viewport",
+            self.viewport
+                .map(|v| format!("{},{},{},{}", v.x, v.y, v.width, v.height)),
+        );
+        config.field("scene", self.get_scene().name());
+        config.optional("load", self.load);
+        config.field("max_particles", self.sim.max_particles());
+        config.field("lod", self.sim.lod.describe());
+        config.field("particles", self.sim.particle_count);
+        config.field("double_buffered", self.sim.is_double_buffered());
+        config.optional(
+            "split_compare",
+            self.split_compare
+                .map(|(a, b)| format!("{}|{}", a.name(), b.name())),
+        );
+        config.optional(
+            "clustering",
+            self.sim.clustering.as_ref().map(|kmeans| {
+                let (k, interval) = kmeans.settings();
+                format!("{}/{}", k, interval)
+            }),
+        );
+        config.optional("frame_budget", self.sim.scheduler.budget_ms());
+        config
+    }
+
+    // 領域分割なしのときだけ、描画バッファを合わせて座標を pixel_ratio 倍する
+    // （描画バッファの大きさを変えると変換も戻るので毎回設定する）
+    fn apply_backing_size(&self) {
+        if let (None, Some(canvas)) = (self.viewport, self.ctx.canvas()) {
+            self.sizing.apply(&canvas, self.sim.width, self.sim.height);
+            let ratio = self.sizing.backing_ratio() as f64;
+            let _ = self.ctx.set_transform(ratio, 0.0, 0.0, ratio, 0.0, 0.0);
+        }
+    }
+
+    fn apply_load(&mut self) {
+        let Some(load) = self.load else {
+            return;
+        };
+        match &mut self.scene {
+            Some(scene) => scene.set_load(load),
+            None => self.sim.set_load(load),
+        }
+    }
+
+    fn render_contents(&mut self) {
+        if self.software.is_some() {
+            self.render_software();
+            return;
+        }
+        if let Some(scene) = &mut self.scene {
+            let mut surface = CanvasSurface {
+                ctx: &self.ctx,
+                width: self.sim.width,
+                height: self.sim.height,
+                resources: &mut self.scene_canvas,
+                events: &mut self.events,
+                pixel_ratio: self.sizing.backing_ratio(),
+            };
+            scene.render(&mut surface);
+            return;
+        }
+
+        self.sim.prepare_visible();
+        let ctx = &self.ctx;
+        let sequence_begin = self.sim.sequence();
+        let radius = self.sim.config.point_size as f64;
+        let projector = self.projector();
+        let painter = ParticlePainter {
+            ctx,
+            quirks: &self.quirks,
+            particles: self.sim.visible(),
+            projector,
+            radius,
+            style: self.sim.style(),
+            theme: &self.sim.theme,
+            frame: self.sim.frame_count,
+            shape: self.shape,
+            sprites: self.sprite_atlas.as_ref(),
+            strategy: self.draw_strategy,
+        };
+        let batches = &mut self.batches;
+
+        // 画面クリア（Trails は背景色を半透明で重ねて前のフレームを薄めるだけ）
+        let fade = match self.render_mode {
+            RenderMode::Trails => TRAIL_FADE,
+            _ => 1.0,
+        };
+        match self.sim.theme.background {
+            Some(rgb) => ctx.set_fill_style_str(&rgb_css(rgb, fade)),
+            None => ctx.set_fill_style_str(&format!("rgba(17, 17, 17, {})", fade)),
+        }
+        ctx.fill_rect(0.0, 0.0, self.sim.width as f64, self.sim.height as f64);
+
+        // 背景はそのままで、パーティクルと選択の強調だけを視点に合わせて描く
+        let camera = self.sim.camera;
+        ctx.save();
+        if !camera.is_identity() {
+            let zoom = camera.zoom as f64;
+            let _ = ctx.transform(zoom, 0.0, 0.0, zoom, -camera.x as f64 * zoom, -camera.y as f64 * zoom);
+        }
+
+        match self.split_compare {
+            None => {
+                let _ = ctx.set_global_composite_operation(self.render_mode.composite_operation());
+                painter.draw(batches, |_| true);
+                let _ = ctx.set_global_composite_operation(RenderMode::Normal.composite_operation());
+            }
+            Some((mode_a, mode_b)) => {
+                // 左右それぞれの半分でクリップし、別の合成方法で描く
+                // （境界をまたぐパーティクルがあるので判定は半径分広めに取る）
+                let half = self.sim.width / 2.0;
+                let full = Viewport::new(0.0, 0.0, self.sim.width, self.sim.height);
+                let (left, right) = full.split_halves();
+                for (mode, region) in [(mode_a, left), (mode_b, right)] {
+                    let is_left = region.x == 0.0;
+                    ctx.save();
+                    ctx.begin_path();
+                    ctx.rect(region.x as f64, region.y as f64, region.width as f64, region.height as f64);
+                    ctx.clip();
+                    let _ = ctx.set_global_composite_operation(mode.composite_operation());
+                    painter.draw(batches, |p| {
+                        if is_left {
+                            p.x < half + radius as f32
+                        } else {
+                            p.x > half - radius as f32
+                        }
+                    });
+                    ctx.restore();
+                }
+            }
+        }
+
+        // 選択中のパーティクルを大きめの白い円で重ね描きする
+        if !self.sim.selection.is_empty() && self.sim.cosmetic {
+            let particles = self.sim.rendered();
+            ctx.set_fill_style_str(&rgb_css(selection::HIGHLIGHT_RGB, 1.0));
+            ctx.begin_path();
+            let radius = radius * selection::HIGHLIGHT_SCALE as f64;
+            for &i in &self.sim.selection {
+                if let Some(p) = particles.get(i as usize) {
+                    let (x, y, radius) = match projector {
+                        Some(projector) => {
+                            let (x, y, scale) = projector.project(p.x, p.y, particles.z[i as usize]);
+                            (x as f64, y as f64, radius * scale as f64)
+                        }
+                        None => (p.x as f64, p.y as f64, radius),
+                    };
+                    ctx.move_to(x + radius, y);
+                    let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI as f64);
+                }
+            }
+            ctx.fill();
+        }
+
+        // 障害物の輪郭をパーティクルと同じ大きさの点を並べて描く
+        if !self.sim.obstacles.is_empty() {
+            ctx.set_fill_style_str(&rgb_css(obstacle::OUTLINE_RGB, 1.0));
+            ctx.begin_path();
+            for point in self.sim.obstacles.outline_points(radius as f32 * 2.0, Camera::default()) {
+                ctx.move_to(point.x as f64 + radius, point.y as f64);
+                let _ = ctx.arc(point.x as f64, point.y as f64, radius, 0.0, 2.0 * PI as f64);
+            }
+            ctx.fill();
+        }
+        ctx.restore();
+
+        self.read_stamps.record(sequence_begin, self.sim.sequence());
+        self.init.mark_frame(self.is_initialized());
+    }
+
+    pub(crate) fn from_source(
+        source: &impl ContextSource<CanvasRenderingContext2d>,
+        particle_count: usize,
+        options: BuildOptions,
+    ) -> Result<ParticleSystemCanvas2D, JsValue> {
+        check_budget(particle_count, options.memory_budget)?;
+        let mut init = InitProgress::start();
+
+        let AcquiredContext {
+            context: ctx,
+            width,
+            height,
+        } = source.acquire()?;
+        let quirks = Quirks::detect_canvas2d(&ctx, particle_fill_style);
+        init.timings.context_ms = init.lap();
+
+        // パーティクルを生成
+        // ビューポート指定時はその領域がシミュレーション空間になる
+        let (width, height) = match options.viewport {
+            Some(v) => (v.width, v.height),
+            None => (width, height),
+        };
+        let mut sim = Simulation::with_capacity(width, height, particle_count)?;
+        sim.seed = options.seed;
+        if !options.progressive {
+            sim.spawn_pending(particle_count);
+        }
+        sim.config = options.simulation.clamped();
+        sim.memory_budget = options.memory_budget;
+        init.timings.particles_ms = init.lap();
+        init.finish_constructor();
+
+        let mut system = ParticleSystemCanvas2D {
+            sim,
+            ctx,
+            read_stamps: ReadStamps::default(),
+            init,
+            scene: None,
+            scene_canvas: SceneCanvas::default(),
+            viewport: options.viewport,
+            split_compare: None,
+            load: None,
+            events: EventBus::default(),
+            strict: options.strict,
+            explosion: ExplosionConfig::default(),
+            quirks,
+            stats_stream: StatsStream::default(),
+            #[cfg(feature = "telemetry")]
+            telemetry: Telemetry::default(),
+            observers: Observers::default(),
+            timeline: Timeline::default(),
+            tuning: None,
+            metrics: MetricsCollector::default(),
+            sizing: CanvasSizing::default(),
+            input: None,
+            shape: ParticleShape::Circle,
+            sprite_atlas: None,
+            particle_texture: None,
+            render_mode: RenderMode::Normal,
+            draw_strategy: DrawStrategy::PerParticle,
+            batches: Vec::new(),
+            software: None,
+        };
+        for message in system.quirks.messages() {
+            system.events.emit(EventKind::QuirkApplied, &message);
+        }
+        Ok(system)
+    }
+}
+
+// パーティクルを描くのに必要なものをまとめたもの
+struct ParticlePainter<'a> {
+    ctx: &'a CanvasRenderingContext2d,
+    quirks: &'a Quirks,
+    particles: &'a ParticleSet,
+    // Perspective なら描く前に CPU で透視投影する
+    projector: Option<Projector>,
+    radius: f64,
+    // パーティクルごとの大きさと不透明度（frame は揺らす位相に使う）
+    style: ParticleStyle,
+    // 塗り方（Sprite は色相ごとのアトラスなので、Palette と Monochrome でも theme.hue_of() の色相の虹色になる）
+    theme: &'a ColorTheme,
+    frame: u32,
+    shape: ParticleShape,
+    sprites: Option<&'a OffscreenCanvas>,
+    strategy: DrawStrategy,
+}
+
+impl ParticlePainter<'_> {
+    // filter を満たすパーティクルを描く（batches は Batched のときの作業領域）
+    fn draw(&self, batches: &mut Vec<Vec<(f64, f64, f64)>>, filter: impl Fn(&Particle) -> bool) {
+        let sprite = self.shape == ParticleShape::Sprite && self.sprites.is_some();
+        if self.strategy == DrawStrategy::Batched && !sprite {
+            draw_particles_batched(self, batches, filter);
+        } else {
+            draw_particles(self, filter);
+        }
+    }
+
+    // i 番目のパーティクルを描く (半径, 不透明度)（不透明度は寿命で薄めた分も掛ける）
+    fn appearance(&self, i: usize, hue: f32, life: f32) -> (f64, f32) {
+        let (size, alpha) = self.style.at(self.particles.size[i], self.particles.alpha[i], hue, self.frame);
+        (self.radius * size as f64, life.clamp(0.0, 1.0) * alpha)
+    }
+
+    // i 番目のパーティクルを描く位置と半径（透視投影のときは奥ほど中心に寄せて小さくする）
+    fn place(&self, i: usize, p: &Particle, radius: f64) -> (f64, f64, f64) {
+        match self.projector {
+            Some(projector) => {
+                let (x, y, scale) = projector.project(p.x, p.y, self.particles.z[i]);
+                (x as f64, y as f64, radius * scale as f64)
+            }
+            None => (p.x as f64, p.y as f64, radius),
+        }
+    }
+}
+
+// 各パーティクルを描画（Canvas 2D APIで1個ずつ！）
+// quirks が SlowFillStyle なら1度刻みで作っておいた文字列を使い回す
+// Sprite はアトラスから色相の近いスプライトを drawImage する（塗りの色は使わない）
+fn draw_particles(painter: &ParticlePainter, filter: impl Fn(&Particle) -> bool) {
+    let (ctx, quirks) = (painter.ctx, painter.quirks);
+    let particles = painter.particles;
+    for (i, (p, &life)) in particles.iter().zip(&particles.life).enumerate().filter(|(_, (p, _))| filter(p)) {
+        // 寿命が残り少ないほど、不透明度が低いほど薄く
+        let (radius, alpha) = painter.appearance(i, p.hue, life);
+        let hue = painter.theme.hue_of(&p);
+        let fading = alpha < 1.0;
+        if fading {
+            ctx.set_global_alpha(alpha as f64);
+        }
+        let (x, y, radius) = painter.place(i, &p, radius);
+        match (painter.shape, painter.sprites) {
+            (ParticleShape::Sprite, Some(atlas)) => {
+                let size = SPRITE_SIZE as f64;
+                let _ = ctx.draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    atlas,
+                    particle_shape::hue_atlas_x(hue) as f64,
+                    0.0,
+                    size,
+                    size,
+                    x - radius,
+                    y - radius,
+                    radius * 2.0,
+                    radius * 2.0,
+                );
+            }
+            (ParticleShape::Square, _) => {
+                set_particle_fill(ctx, quirks, painter.theme, hue);
+                ctx.fill_rect(x - radius, y - radius, radius * 2.0, radius * 2.0);
+            }
+            _ => {
+                set_particle_fill(ctx, quirks, painter.theme, hue);
+                ctx.begin_path();
+                let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI as f64);
+                ctx.fill();
+            }
+        }
+        if fading {
+            ctx.set_global_alpha(1.0);
+        }
+    }
+}
+
+// 色相と不透明度を丸めてまとまりに分け、まとまりごとに1つのパスにして1回で塗る
+// 塗りの色の設定と fill() はまとまりの数だけになる（Square は rect()、それ以外は arc() を重ねる）
+fn draw_particles_batched(
+    painter: &ParticlePainter,
+    batches: &mut Vec<Vec<(f64, f64, f64)>>,
+    filter: impl Fn(&Particle) -> bool,
+) {
+    let (ctx, quirks, shape) = (painter.ctx, painter.quirks, painter.shape);
+    let particles = painter.particles;
+    batches.resize_with(draw_strategy::HUE_BUCKETS * draw_strategy::ALPHA_LEVELS, Vec::new);
+    for batch in batches.iter_mut() {
+        batch.clear();
+    }
+    for (i, (p, &life)) in particles.iter().zip(&particles.life).enumerate().filter(|(_, (p, _))| filter(p)) {
+        let (radius, alpha) = painter.appearance(i, p.hue, life);
+        batches[draw_strategy::bucket(painter.theme.hue_of(&p), alpha)].push(painter.place(i, &p, radius));
+    }
+
+    for (index, batch) in batches.iter().enumerate() {
+        let (hue, alpha) = draw_strategy::bucket_color(index);
+        if batch.is_empty() || alpha <= 0.0 {
+            continue;
+        }
+        set_particle_fill(ctx, quirks, painter.theme, hue);
+        ctx.set_global_alpha(alpha as f64);
+        ctx.begin_path();
+        for &(x, y, radius) in batch {
+            if shape == ParticleShape::Square {
+                ctx.rect(x - radius, y - radius, radius * 2.0, radius * 2.0);
+            } else {
+                // 前の円から線でつながらないように、円の始点に移ってから描く
+                ctx.move_to(x + radius, y);
+                let _ = ctx.arc(x, y, radius, 0.0, 2.0 * PI as f64);
+            }
+        }
+        ctx.fill();
+    }
+    ctx.set_global_alpha(1.0);
+}
+
+// quirks の作り置きは虹色の文字列なので、色相をそのまま塗らない塗り方では毎回作る
+fn set_particle_fill(ctx: &CanvasRenderingContext2d, quirks: &Quirks, theme: &ColorTheme, hue: f32) {
+    if !theme.paints_hue() {
+        let (r, g, b) = theme.hue_rgb(hue);
+        ctx.set_fill_style_str(&rgb_css([r, g, b], 0.8));
+        return;
+    }
+    match quirks.fill_style(hue) {
+        Some(color) => ctx.set_fill_style_str(color),
+        None => ctx.set_fill_style_str(&particle_fill_style(hue)),
+    }
 }
 
-// HSL to RGB変換
-fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
-    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
-    let h_prime = h / 60.0;
-    let x = c * (1.0 - ((h_prime % 2.0) - 1.0).abs());
-
-    let (r1, g1, b1) = if h_prime < 1.0 {
-        (c, x, 0.0)
-    } else if h_prime < 2.0 {
-        (x, c, 0.0)
-    } else if h_prime < 3.0 {
-        (0.0, c, x)
-    } else if h_prime < 4.0 {
-        (0.0, x, c)
-    } else if h_prime < 5.0 {
-        (x, 0.0, c)
-    } else {
-        (c, 0.0, x)
-    };
-
-    let m = l - c / 2.0;
-    (r1 + m, g1 + m, b1 + m)
+fn particle_fill_style(hue: f32) -> String {
+    let rgb = hsl_to_rgb(hue, 1.0, 0.5);
+    format!(
+        "rgba({}, {}, {}, 0.8)",
+        (rgb.0 * 255.0) as u8,
+        (rgb.1 * 255.0) as u8,
+        (rgb.2 * 255.0) as u8
+    )
 }
@@ -0,0 +1,501 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlImageElement, ImageBitmap, ImageData, OffscreenCanvas,
+    OffscreenCanvasRenderingContext2d,
+};
+
+use crate::events::{EventBus, EventKind};
+use crate::image_source::DecodedImage;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::scene::{
+    Atlas, ColorPoint, ColorRect, CubicBezier, Glow, PlacedSprite, Polygon, Sprite, StyledRect,
+    Surface,
+};
+use crate::viewport::Viewport;
+
+// シーン描画用の作業用キャンバス（初回に作って使い回す）
+#[derive(Default)]
+pub(crate) struct SceneCanvas {
+    // ピクセル列を拡大描画するためのキャンバス
+    scratch: Option<(OffscreenCanvas, OffscreenCanvasRenderingContext2d)>,
+    // スプライトのアトラスを転送済みのキャンバスとそのバージョン
+    atlas: Option<(u32, OffscreenCanvas)>,
+}
+
+// Canvas2Dバックエンドの Surface 実装
+pub(crate) struct CanvasSurface<'a> {
+    pub ctx: &'a CanvasRenderingContext2d,
+    pub width: f32,
+    pub height: f32,
+    pub resources: &'a mut SceneCanvas,
+    pub events: &'a mut EventBus,
+    // 描画バッファの倍率（コンテキストの変換にはこの倍率が掛かっている）
+    pub pixel_ratio: f32,
+}
+
+impl Surface for CanvasSurface<'_> {
+    fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn report(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    fn clear(&mut self, rgb: [f32; 3]) {
+        self.ctx.set_fill_style_str(&rgb_css(rgb, 1.0));
+        self.ctx
+            .fill_rect(0.0, 0.0, self.width as f64, self.height as f64);
+    }
+
+    fn read_rgba(&mut self, x: u32, y: u32, width: u32, height: u32, out: &mut Vec<u8>) -> bool {
+        let (canvas_width, canvas_height) = (self.width as u32, self.height as u32);
+        let (x, y) = (x.min(canvas_width), y.min(canvas_height));
+        let (width, height) = (width.min(canvas_width - x), height.min(canvas_height - y));
+        out.clear();
+        if width == 0 || height == 0 {
+            return false;
+        }
+        match self.ctx.get_image_data(x as f64, y as f64, width as f64, height as f64) {
+            Ok(image) => {
+                out.extend_from_slice(&image.data());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    // RGBAのピクセル列を put_image_data で描く
+    // サイズがキャンバスと違う場合は作業用キャンバス経由で拡大する
+    fn blit_rgba(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        let Ok(image) = ImageData::new_with_u8_clamped_array_and_sh(Clamped(pixels), width, height)
+        else {
+            return;
+        };
+
+        // put_image_data は変換を無視するので、倍率が掛かっているときは拡大して描く
+        if width as f32 == self.width && height as f32 == self.height && self.pixel_ratio == 1.0 {
+            let _ = self.ctx.put_image_data(&image, 0.0, 0.0);
+            return;
+        }
+
+        let Some((canvas, scratch_ctx)) =
+            scratch_canvas(&mut self.resources.scratch, width, height)
+        else {
+            let _ = self.ctx.put_image_data(&image, 0.0, 0.0);
+            return;
+        };
+        let _ = scratch_ctx.put_image_data(&image, 0.0, 0.0);
+        self.ctx.set_image_smoothing_enabled(false);
+        let _ = self.ctx.draw_image_with_offscreen_canvas_and_dw_and_dh(
+            canvas,
+            0.0,
+            0.0,
+            self.width as f64,
+            self.height as f64,
+        );
+    }
+
+    // 同じ色が続く間は fill_style を設定し直さない
+    fn fill_rects(&mut self, rects: &[ColorRect]) {
+        let mut current = None;
+        for r in rects {
+            if current != Some(r.rgb) {
+                self.ctx.set_fill_style_str(&rgb_css(r.rgb, 1.0));
+                current = Some(r.rgb);
+            }
+            self.ctx
+                .fill_rect(r.x as f64, r.y as f64, r.width as f64, r.height as f64);
+        }
+    }
+
+    // globalAlpha と globalCompositeOperation を設定して塗り、restore で戻す
+    // ブラウザが知らない合成方法は設定しても無視されるので、読み返して確かめる
+    fn fill_rects_composited(&mut self, rects: &[ColorRect], alpha: f32, mode: CompositeMode) -> bool {
+        let ctx = self.ctx;
+        ctx.save();
+        let _ = ctx.set_global_composite_operation(mode.name());
+        if ctx.global_composite_operation().ok().as_deref() != Some(mode.name()) {
+            ctx.restore();
+            return false;
+        }
+        ctx.set_global_alpha(alpha as f64);
+        self.fill_rects(rects);
+        ctx.restore();
+        true
+    }
+
+    // 同じ状態が続く矩形をまとめ、まとまりごとに状態を設定し直す
+    // クリップは解除できないので、まとまりごとに save / restore で戻す
+    fn fill_rects_styled(&mut self, rects: &[StyledRect]) -> bool {
+        let ctx = self.ctx;
+        let (cx, cy) = (self.width as f64 / 2.0, self.height as f64 / 2.0);
+        for run in rects.chunk_by(|a, b| a.state == b.state) {
+            let state = run[0].state;
+            ctx.save();
+            ctx.set_fill_style_str(&rgb_css(state.rgb, 1.0));
+            ctx.set_global_alpha(state.alpha as f64);
+            let _ = ctx.translate(cx, cy);
+            let _ = ctx.rotate(state.rotation as f64);
+            let _ = ctx.translate(-cx, -cy);
+            if let Some([x, y, width, height]) = state.clip {
+                ctx.begin_path();
+                ctx.rect(x as f64, y as f64, width as f64, height as f64);
+                ctx.clip();
+            }
+            for r in run {
+                ctx.fill_rect(r.x as f64, r.y as f64, r.width as f64, r.height as f64);
+            }
+            ctx.restore();
+        }
+        true
+    }
+
+    // スプライトごとに変換を掛けて drawImage する
+    fn draw_sprites(&mut self, atlas: &Atlas, sprites: &[Sprite]) {
+        let Some(image) = atlas_image(&mut self.resources.atlas, atlas) else {
+            return;
+        };
+        for sprite in sprites {
+            let [a, b, c, d, e, f] = sprite.transform;
+            let [sx, sy, sw, sh] = sprite.source;
+            self.ctx.save();
+            let _ = self
+                .ctx
+                .transform(a as f64, b as f64, c as f64, d as f64, e as f64, f as f64);
+            image.draw(
+                self.ctx,
+                [sx as f64, sy as f64, sw as f64, sh as f64],
+                [0.0, 0.0, 1.0, 1.0],
+            );
+            self.ctx.restore();
+        }
+    }
+
+    // precomputed なら最後に1回だけ restore して、元の変換に戻す
+    fn draw_sprites_placed(&mut self, atlas: &Atlas, sprites: &[PlacedSprite], precomputed: bool) -> bool {
+        let Some(image) = atlas_image(&mut self.resources.atlas, atlas) else {
+            return false;
+        };
+        let ctx = self.ctx;
+        let draw = |sprite: &PlacedSprite| {
+            let [sx, sy, sw, sh] = sprite.source;
+            let half = sprite.size as f64 / 2.0;
+            image.draw(
+                ctx,
+                [sx as f64, sy as f64, sw as f64, sh as f64],
+                [-half, -half, sprite.size as f64, sprite.size as f64],
+            );
+        };
+        if precomputed {
+            ctx.save();
+            let ratio = self.pixel_ratio as f64;
+            for sprite in sprites {
+                let (sin, cos) = (sprite.rotation as f64).sin_cos();
+                let scale = sprite.scale as f64 * ratio;
+                let _ = ctx.set_transform(
+                    cos * scale,
+                    sin * scale,
+                    -sin * scale,
+                    cos * scale,
+                    sprite.x as f64 * ratio,
+                    sprite.y as f64 * ratio,
+                );
+                draw(sprite);
+            }
+            ctx.restore();
+        } else {
+            for sprite in sprites {
+                ctx.save();
+                let _ = ctx.translate(sprite.x as f64, sprite.y as f64);
+                let _ = ctx.rotate(sprite.rotation as f64);
+                let _ = ctx.scale(sprite.scale as f64, sprite.scale as f64);
+                draw(sprite);
+                ctx.restore();
+            }
+        }
+        true
+    }
+
+    // 放射グラデーションは円ごとに作り直す（色も位置も円ごとに違うので使い回せない）
+    // shadowBlur は変換の影響を受けないので、描画バッファの倍率を掛けておく
+    fn fill_glows(&mut self, glows: &[Glow], shadow_blur: Option<f32>) -> bool {
+        let ctx = self.ctx;
+        let circle = |glow: &Glow| {
+            ctx.begin_path();
+            let _ = ctx.arc(
+                glow.x as f64,
+                glow.y as f64,
+                glow.radius as f64,
+                0.0,
+                std::f64::consts::TAU,
+            );
+            ctx.fill();
+        };
+        match shadow_blur {
+            Some(blur) => {
+                ctx.save();
+                ctx.set_shadow_blur((blur * self.pixel_ratio) as f64);
+                for glow in glows {
+                    let color = rgb_css(glow.rgb, 1.0);
+                    ctx.set_shadow_color(&color);
+                    ctx.set_fill_style_str(&color);
+                    circle(glow);
+                }
+                ctx.restore();
+            }
+            None => {
+                for glow in glows {
+                    let (x, y) = (glow.x as f64, glow.y as f64);
+                    let Ok(gradient) = ctx.create_radial_gradient(x, y, 0.0, x, y, glow.radius as f64)
+                    else {
+                        continue;
+                    };
+                    let _ = gradient.add_color_stop(0.0, &rgb_css(glow.rgb, 1.0));
+                    let _ = gradient.add_color_stop(1.0, &rgb_css(glow.rgb, 0.0));
+                    ctx.set_fill_style_canvas_gradient(&gradient);
+                    circle(glow);
+                }
+            }
+        }
+        true
+    }
+
+    // 同じ色が続く曲線は1つのパスにまとめて stroke する
+    fn stroke_beziers(&mut self, curves: &[CubicBezier]) {
+        let ctx = self.ctx;
+        ctx.set_line_width(1.0);
+        for run in curves.chunk_by(|a, b| a.rgb == b.rgb) {
+            ctx.set_stroke_style_str(&rgb_css(run[0].rgb, 1.0));
+            ctx.begin_path();
+            for curve in run {
+                let [p0, p1, p2, p3] = curve.points;
+                ctx.move_to(p0.0 as f64, p0.1 as f64);
+                ctx.bezier_curve_to(
+                    p1.0 as f64,
+                    p1.1 as f64,
+                    p2.0 as f64,
+                    p2.1 as f64,
+                    p3.0 as f64,
+                    p3.1 as f64,
+                );
+            }
+            ctx.stroke();
+        }
+    }
+
+    // 同じ色が続く多角形は1つのパスにまとめて fill する
+    fn fill_polygons(&mut self, polygons: &[Polygon]) {
+        let ctx = self.ctx;
+        for run in polygons.chunk_by(|a, b| a.rgb == b.rgb) {
+            ctx.set_fill_style_str(&rgb_css(run[0].rgb, 1.0));
+            ctx.begin_path();
+            for polygon in run {
+                let Some((first, rest)) = polygon.points.split_first() else {
+                    continue;
+                };
+                ctx.move_to(first.0 as f64, first.1 as f64);
+                for p in rest {
+                    ctx.line_to(p.0 as f64, p.1 as f64);
+                }
+                ctx.close_path();
+            }
+            ctx.fill();
+        }
+    }
+
+    // クリップは解除できないので save して、pop_clip() の restore で戻す
+    fn push_clip(&mut self, regions: &[Polygon]) -> bool {
+        let ctx = self.ctx;
+        ctx.save();
+        ctx.begin_path();
+        for polygon in regions {
+            let Some((first, rest)) = polygon.points.split_first() else {
+                continue;
+            };
+            ctx.move_to(first.0 as f64, first.1 as f64);
+            for p in rest {
+                ctx.line_to(p.0 as f64, p.1 as f64);
+            }
+            ctx.close_path();
+        }
+        ctx.clip();
+        true
+    }
+
+    fn pop_clip(&mut self) {
+        self.ctx.restore();
+    }
+
+    // region でクリップし、キャンバス全体が region に収まるように縮める（pop_viewport() の restore で戻す）
+    fn push_viewport(&mut self, region: Viewport) -> bool {
+        let ctx = self.ctx;
+        ctx.save();
+        ctx.begin_path();
+        ctx.rect(
+            region.x as f64,
+            region.y as f64,
+            region.width as f64,
+            region.height as f64,
+        );
+        ctx.clip();
+        let _ = ctx.translate(region.x as f64, region.y as f64);
+        let _ = ctx.scale(
+            (region.width / self.width) as f64,
+            (region.height / self.height) as f64,
+        );
+        true
+    }
+
+    fn pop_viewport(&mut self) {
+        self.ctx.restore();
+    }
+
+    // 点ごとに fill_rect する（同じ色が続く間は fill_style を設定し直さない）
+    fn draw_points(&mut self, points: &[ColorPoint], size: f32, mode: RenderMode) {
+        let _ = self
+            .ctx
+            .set_global_composite_operation(mode.composite_operation());
+        let half = size as f64 / 2.0;
+        let mut current = None;
+        for p in points {
+            if current != Some(p.rgb) {
+                self.ctx.set_fill_style_str(&rgb_css(p.rgb, 1.0));
+                current = Some(p.rgb);
+            }
+            self.ctx
+                .fill_rect(p.x as f64 - half, p.y as f64 - half, size as f64, size as f64);
+        }
+        let _ = self
+            .ctx
+            .set_global_composite_operation(RenderMode::Normal.composite_operation());
+    }
+
+    // filter はピクセルの直接書き込みには効かないので、作業用キャンバスから描き写すときにかける
+    fn blit_rgba_filtered(&mut self, width: u32, height: u32, pixels: &[u8], radius: f32) -> bool {
+        let Ok(image) = ImageData::new_with_u8_clamped_array_and_sh(Clamped(pixels), width, height)
+        else {
+            return false;
+        };
+        let Some((canvas, scratch_ctx)) =
+            scratch_canvas(&mut self.resources.scratch, width, height)
+        else {
+            return false;
+        };
+        let _ = scratch_ctx.put_image_data(&image, 0.0, 0.0);
+
+        // 拡大後の見た目が他の方法と揃うように半径もキャンバス側の倍率に合わせる
+        let scale = self.width / width as f32;
+        self.ctx.save();
+        self.ctx.set_filter(&format!("blur({}px)", radius * scale));
+        let _ = self.ctx.draw_image_with_offscreen_canvas_and_dw_and_dh(
+            canvas,
+            0.0,
+            0.0,
+            self.width as f64,
+            self.height as f64,
+        );
+        self.ctx.restore();
+        true
+    }
+}
+
+fn scratch_canvas(
+    scratch: &mut Option<(OffscreenCanvas, OffscreenCanvasRenderingContext2d)>,
+    width: u32,
+    height: u32,
+) -> Option<&(OffscreenCanvas, OffscreenCanvasRenderingContext2d)> {
+    if scratch.is_none() {
+        let canvas = OffscreenCanvas::new(width, height).ok()?;
+        let ctx = canvas
+            .get_context("2d")
+            .ok()??
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()
+            .ok()?;
+        *scratch = Some((canvas, ctx));
+    }
+
+    let entry = scratch.as_ref()?;
+    if entry.0.width() != width || entry.0.height() != height {
+        entry.0.set_width(width);
+        entry.0.set_height(height);
+    }
+    Some(entry)
+}
+
+// drawImage に渡すアトラスの画像
+enum AtlasImage<'a> {
+    Canvas(&'a OffscreenCanvas),
+    Bitmap(&'a ImageBitmap),
+    Element(&'a HtmlImageElement),
+}
+
+impl AtlasImage<'_> {
+    // source [x, y, width, height] の範囲を dest に描く
+    fn draw(&self, ctx: &CanvasRenderingContext2d, source: [f64; 4], dest: [f64; 4]) {
+        let [sx, sy, sw, sh] = source;
+        let [dx, dy, dw, dh] = dest;
+        let _ = match self {
+            AtlasImage::Canvas(canvas) => ctx
+                .draw_image_with_offscreen_canvas_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    canvas, sx, sy, sw, sh, dx, dy, dw, dh,
+                ),
+            AtlasImage::Bitmap(bitmap) => ctx
+                .draw_image_with_image_bitmap_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    bitmap, sx, sy, sw, sh, dx, dy, dw, dh,
+                ),
+            AtlasImage::Element(element) => ctx
+                .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
+                    element, sx, sy, sw, sh, dx, dy, dw, dh,
+                ),
+        };
+    }
+}
+
+// アトラスに画像があればそれを、なければピクセル列を転送した作業用キャンバスを使う
+fn atlas_image<'a>(
+    cache: &'a mut Option<(u32, OffscreenCanvas)>,
+    atlas: &'a Atlas,
+) -> Option<AtlasImage<'a>> {
+    match &atlas.image {
+        Some(DecodedImage::Bitmap(bitmap)) => Some(AtlasImage::Bitmap(bitmap)),
+        Some(DecodedImage::Element(element)) => Some(AtlasImage::Element(element)),
+        None => atlas_canvas(cache, atlas).map(AtlasImage::Canvas),
+    }
+}
+
+// アトラスのバージョンが変わったときだけ作業用キャンバスに転送し直す
+pub(crate) fn atlas_canvas<'a>(
+    cache: &'a mut Option<(u32, OffscreenCanvas)>,
+    atlas: &Atlas,
+) -> Option<&'a OffscreenCanvas> {
+    if cache.as_ref().map(|(version, _)| *version) != Some(atlas.version) {
+        let canvas = OffscreenCanvas::new(atlas.width, atlas.height).ok()?;
+        let ctx = canvas
+            .get_context("2d")
+            .ok()??
+            .dyn_into::<OffscreenCanvasRenderingContext2d>()
+            .ok()?;
+        let image = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&atlas.pixels),
+            atlas.width,
+            atlas.height,
+        )
+        .ok()?;
+        ctx.put_image_data(&image, 0.0, 0.0).ok()?;
+        *cache = Some((atlas.version, canvas));
+    }
+    cache.as_ref().map(|(_, canvas)| canvas)
+}
+
+// [0,1] のRGBをCSSの色文字列に変換
+pub(crate) fn rgb_css(rgb: [f32; 3], alpha: f32) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        (rgb[0] * 255.0) as u8,
+        (rgb[1] * 255.0) as u8,
+        (rgb[2] * 255.0) as u8,
+        alpha
+    )
+}
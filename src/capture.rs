@@ -0,0 +1,84 @@
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use crate::i18n::{tr, Text};
+
+// 描画の途中のバッファ（密度バッファ・ぼかしの各パスなど）をデバッグ用に画像で書き出す
+// シーンは Scene::debug_buffers() で手元のバッファを貸すだけで、変換と PNG 化はここで行う
+// 後処理や GPU 上の物理演算のように途中結果が GPU にあるモードは、読み戻したものを同じ形で渡す
+
+// バッファの中身の表し方
+pub enum DebugPixels<'a> {
+    // 1セル1値（0.0〜1.0、範囲外は丸める）をグレースケールで
+    Gray(&'a [f32]),
+    // RGBA 8bit
+    Rgba(&'a [u8]),
+    // 丸める前の RGBA（0.0〜255.0）。畳み込みの途中結果など
+    RgbaF32(&'a [f32]),
+}
+
+pub struct DebugBuffer<'a> {
+    pub name: &'static str,
+    pub width: usize,
+    pub height: usize,
+    pub pixels: DebugPixels<'a>,
+}
+
+// capture_debug_buffers() が返す1枚
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct CapturedBuffer {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    // RGBA の PNG
+    pub png: Vec<u8>,
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn capture(buffers: Vec<DebugBuffer>) -> Result<Vec<CapturedBuffer>, JsValue> {
+    buffers
+        .into_iter()
+        .filter(|buffer| buffer.width > 0 && buffer.height > 0)
+        .map(|buffer| {
+            let rgba = to_rgba8(&buffer.pixels);
+            Ok(CapturedBuffer {
+                name: buffer.name.to_string(),
+                width: buffer.width as u32,
+                height: buffer.height as u32,
+                png: encode_png(buffer.width as u32, buffer.height as u32, &rgba)?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn to_rgba8(pixels: &DebugPixels) -> Vec<u8> {
+    match pixels {
+        DebugPixels::Gray(values) => values
+            .iter()
+            .flat_map(|value| {
+                let v = (value.clamp(0.0, 1.0) * 255.0) as u8;
+                [v, v, v, 255]
+            })
+            .collect(),
+        DebugPixels::Rgba(bytes) => bytes.to_vec(),
+        DebugPixels::RgbaF32(values) => values
+            .iter()
+            .map(|value| value.clamp(0.0, 255.0) as u8)
+            .collect(),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let encode_error = |error: png::EncodingError| tr(Text::CaptureEncodeFailed, &[&error]);
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(encode_error)?;
+    writer.write_image_data(rgba).map_err(encode_error)?;
+    writer.finish().map_err(encode_error)?;
+    Ok(bytes)
+}
@@ -0,0 +1,156 @@
+use crate::scheduler::Slice;
+use crate::simulation::ParticleSet;
+use crate::timing;
+
+// 予算を確かめる間に割り当てるパーティクル数
+const CHUNK: usize = 4096;
+
+// 位置によるk-meansクラスタリングで色分けするモード
+// interval_frames ごとにまとめて計算するので、周期的に重いCPUスパイクが発生する
+// （描画ループ上に分析処理が乗ったときのジャンク研究用）
+// set_frame_budget() で予算を決めたときは CHUNK 個ずつ数フレームに分けて進める
+pub(crate) struct KMeans {
+    k: usize,
+    interval_frames: u32,
+    iterations: u32,
+    centroids: Vec<(f32, f32)>,
+    labels: Vec<u16>,
+    // 途中まで進めた計算（None なら次の is_due() まで何もしない）
+    job: Option<Job>,
+    pub last_run_ms: f64,
+}
+
+// 今の反復で割り当てを済ませたところまでと、その重心の合計
+#[derive(Default)]
+struct Job {
+    iteration: u32,
+    index: usize,
+    sums: Vec<(f32, f32, u32)>,
+    elapsed_ms: f64,
+}
+
+impl KMeans {
+    // 設定の指紋用: (クラスタ数, 再計算の間隔)
+    #[cfg(target_arch = "wasm32")]
+    pub fn settings(&self) -> (usize, u32) {
+        (self.k, self.interval_frames)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn new(k: u32, interval_frames: u32) -> KMeans {
+        KMeans {
+            k: (k as usize).clamp(1, u16::MAX as usize),
+            interval_frames: interval_frames.max(1),
+            iterations: 4,
+            centroids: Vec::new(),
+            labels: Vec::new(),
+            job: None,
+            last_run_ms: 0.0,
+        }
+    }
+
+    pub fn is_due(&self, frame_count: u32) -> bool {
+        frame_count.is_multiple_of(self.interval_frames)
+    }
+
+    // 計算を始める（前の計算が終わっていなければそのまま続ける）
+    pub fn start(&mut self) {
+        if self.job.is_none() {
+            self.job = Some(Job {
+                sums: vec![(0.0, 0.0, 0); self.k],
+                ..Job::default()
+            });
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    // slice の時間がある間クラスタリングを進め、終わったら各パーティクルの色相をクラスタの色相に置き換えて true を返す
+    // フレームを跨ぐあいだにパーティクルは動くが、色分けに使うだけなので気にしない
+    pub fn advance(&mut self, particles: &mut ParticleSet, slice: &Slice) -> bool {
+        let Some(job) = &mut self.job else {
+            return true;
+        };
+        if particles.is_empty() {
+            self.job = None;
+            return true;
+        }
+        let start = timing::now_ms();
+        let (xs, ys) = (&particles.x, &particles.y);
+
+        // 前回の重心から始める（フレーム間で色が入れ替わらないように）
+        if self.centroids.len() != self.k {
+            let stride = (xs.len() / self.k).max(1);
+            self.centroids = (0..self.k)
+                .map(|i| {
+                    let j = (i * stride) % xs.len();
+                    (xs[j], ys[j])
+                })
+                .collect();
+        }
+        // 途中で数が変わっても、その時点の数まで割り当てる
+        self.labels.resize(xs.len(), 0);
+
+        loop {
+            // 割り当てと、重心の合計
+            let end = (job.index + CHUNK).min(xs.len());
+            let range = job.index.min(end)..end;
+            for ((&x, &y), label) in xs[range.clone()]
+                .iter()
+                .zip(&ys[range.clone()])
+                .zip(&mut self.labels[range])
+            {
+                *label = nearest(&self.centroids, x, y) as u16;
+                let s = &mut job.sums[*label as usize];
+                s.0 += x;
+                s.1 += y;
+                s.2 += 1;
+            }
+            job.index = end;
+
+            if job.index >= xs.len() {
+                // 重心の更新（空になったクラスタは前の位置のまま）
+                for (c, s) in self.centroids.iter_mut().zip(job.sums.iter_mut()) {
+                    if s.2 > 0 {
+                        *c = (s.0 / s.2 as f32, s.1 / s.2 as f32);
+                    }
+                    *s = (0.0, 0.0, 0);
+                }
+                job.iteration += 1;
+                job.index = 0;
+                if job.iteration >= self.iterations {
+                    break;
+                }
+            }
+            if !slice.has_time() {
+                job.elapsed_ms += timing::now_ms() - start;
+                return false;
+            }
+        }
+
+        let hue_step = 360.0 / self.k as f32;
+        for (hue, &label) in particles.hue.iter_mut().zip(self.labels.iter()) {
+            *hue = label as f32 * hue_step;
+        }
+
+        // 分けて進めたときは各フレームで使った時間の合計
+        self.last_run_ms = job.elapsed_ms + timing::now_ms() - start;
+        self.job = None;
+        true
+    }
+}
+
+fn nearest(centroids: &[(f32, f32)], x: f32, y: f32) -> usize {
+    let mut best = 0;
+    let mut best_dist = f32::INFINITY;
+    for (i, &(cx, cy)) in centroids.iter().enumerate() {
+        let dist = (cx - x) * (cx - x) + (cy - y) * (cy - y);
+        if dist < best_dist {
+            best_dist = dist;
+            best = i;
+        }
+    }
+    best
+}
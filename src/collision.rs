@@ -0,0 +1,229 @@
+use crate::math;
+use crate::simulation::ParticleSet;
+
+// パーティクル同士の衝突（set_collisions_enabled(true) のとき毎ステップの物理演算の前に行う）
+// 一様な格子に振り分けてから、隣り合うマスの中だけで重なりを調べる
+// 格子はステップごとに数え上げソートで作り直すので、確保した配列は使い回す
+#[derive(Default)]
+pub(crate) struct SpatialHash {
+    cell_size: f32,
+    columns: usize,
+    rows: usize,
+    // cell_start[c]..cell_start[c + 1] が entries の中でマス c に入っているパーティクル
+    cell_start: Vec<u32>,
+    entries: Vec<u32>,
+    cells: Vec<u32>,
+    // 直近のステップで重なっていた組の数
+    pub last_contacts: usize,
+    // 格子が今の位置を表しているときの Simulation::sequence()
+    // resolve() の後はパーティクルが動くので None（問い合わせで作り直す）
+    indexed: Option<u64>,
+}
+
+impl SpatialHash {
+    // 重なっている組を押し離し、近づいている組は中心を結ぶ向きの速度を交換する（質量はすべて同じ）
+    // radius はパーティクルの半径(px)、restitution は残る速度の割合
+    pub fn resolve(
+        &mut self,
+        particles: &mut ParticleSet,
+        width: f32,
+        height: f32,
+        radius: f32,
+        restitution: f32,
+    ) {
+        self.last_contacts = 0;
+        let n = particles.len();
+        if n < 2 {
+            return;
+        }
+        let diameter = radius * 2.0;
+        self.layout(n, width, height, diameter);
+        self.build(particles);
+        self.indexed = None;
+
+        let ParticleSet { x, y, vx, vy, .. } = particles;
+        let min_dist_sq = diameter * diameter;
+        for i in 0..n {
+            let (column, row) = self.cell_of(x[i], y[i]);
+            for neighbor_row in row.saturating_sub(1)..(row + 2).min(self.rows) {
+                for neighbor_column in column.saturating_sub(1)..(column + 2).min(self.columns) {
+                    let cell = neighbor_row * self.columns + neighbor_column;
+                    let range = self.cell_start[cell] as usize..self.cell_start[cell + 1] as usize;
+                    for &j in &self.entries[range] {
+                        // 組ごとに1回だけ
+                        let j = j as usize;
+                        if j <= i {
+                            continue;
+                        }
+                        let dx = x[j] - x[i];
+                        let dy = y[j] - y[i];
+                        let dist_sq = dx * dx + dy * dy;
+                        if dist_sq >= min_dist_sq {
+                            continue;
+                        }
+                        self.last_contacts += 1;
+                        // 同じ位置なら向きが決まらないので横に離す
+                        let dist = math::sqrt(dist_sq);
+                        let (nx, ny) = if dist > 0.0 {
+                            (dx / dist, dy / dist)
+                        } else {
+                            (1.0, 0.0)
+                        };
+
+                        // 重なった分を半分ずつ押し戻す
+                        let push = (diameter - dist) / 2.0;
+                        x[i] -= nx * push;
+                        y[i] -= ny * push;
+                        x[j] += nx * push;
+                        y[j] += ny * push;
+
+                        // 近づいているときだけ跳ね返す
+                        let approach = (vx[i] - vx[j]) * nx + (vy[i] - vy[j]) * ny;
+                        if approach > 0.0 {
+                            let impulse = approach * (1.0 + restitution) / 2.0;
+                            vx[i] -= nx * impulse;
+                            vy[i] -= ny * impulse;
+                            vx[j] += nx * impulse;
+                            vy[j] += ny * impulse;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 問い合わせの前に、格子が sequence の状態のものでなければ作り直す
+    // 同じフレームで何度も問い合わせるときは1回作るだけで済む
+    pub fn index(
+        &mut self,
+        particles: &ParticleSet,
+        width: f32,
+        height: f32,
+        radius: f32,
+        sequence: u64,
+    ) {
+        if self.indexed == Some(sequence) {
+            return;
+        }
+        self.layout(particles.len(), width, height, radius * 2.0);
+        self.build(particles);
+        self.indexed = Some(sequence);
+    }
+
+    // (x, y) から radius 以内（境界を含む）のパーティクルの数。index() の後に呼ぶ
+    pub fn count_within(&self, particles: &ParticleSet, x: f32, y: f32, radius: f32) -> usize {
+        if radius.is_nan() || radius < 0.0 || self.entries.len() != particles.len() {
+            return 0;
+        }
+        let radius_sq = radius * radius;
+        let (min_column, min_row) = self.cell_of(x - radius, y - radius);
+        let (max_column, max_row) = self.cell_of(x + radius, y + radius);
+        let mut count = 0;
+        for row in min_row..=max_row {
+            for column in min_column..=max_column {
+                for i in self.cell(column, row) {
+                    let dx = particles.x[i] - x;
+                    let dy = particles.y[i] - y;
+                    if dx * dx + dy * dy <= radius_sq {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        count
+    }
+
+    // (x, y) に一番近いパーティクル（同じ距離なら小さいインデックス）。index() の後に呼ぶ
+    // (x, y) のマスから1周ずつ広げ、まだ見ていない周にそれより近いものがありえなくなったら止める
+    pub fn nearest(&self, particles: &ParticleSet, x: f32, y: f32) -> Option<usize> {
+        if particles.is_empty() || self.entries.len() != particles.len() {
+            return None;
+        }
+        let (center_column, center_row) = self.cell_of(x, y);
+        let mut best: Option<(usize, f32)> = None;
+        for ring in 0..self.columns.max(self.rows) {
+            // ring 周目のマスにあるパーティクルは (ring - 1) マス分以上離れている
+            if let Some((_, best_sq)) = best {
+                let reach = ring.saturating_sub(1) as f32 * self.cell_size;
+                if best_sq <= reach * reach {
+                    break;
+                }
+            }
+            let min_row = center_row.saturating_sub(ring);
+            let max_row = (center_row + ring).min(self.rows - 1);
+            let min_column = center_column.saturating_sub(ring);
+            let max_column = (center_column + ring).min(self.columns - 1);
+            for row in min_row..=max_row {
+                for column in min_column..=max_column {
+                    // 内側の周は見たので、ちょうど ring 周目のマスだけ
+                    if row.abs_diff(center_row).max(column.abs_diff(center_column)) != ring {
+                        continue;
+                    }
+                    for i in self.cell(column, row) {
+                        let dx = particles.x[i] - x;
+                        let dy = particles.y[i] - y;
+                        let dist_sq = dx * dx + dy * dy;
+                        let closer = best.is_none_or(|(best_i, best_sq)| {
+                            dist_sq < best_sq || (dist_sq == best_sq && i < best_i)
+                        });
+                        if closer {
+                            best = Some((i, dist_sq));
+                        }
+                    }
+                }
+            }
+        }
+        best.map(|(i, _)| i)
+    }
+
+    // マスの大きさは min_cell_size 以上にする（隣のマスまで見れば取りこぼさない）
+    // パーティクルが少ないときにマスが増えすぎないよう、数がパーティクル数程度になる大きさまで広げる
+    fn layout(&mut self, n: usize, width: f32, height: f32, min_cell_size: f32) {
+        let area = width * height / n.max(1) as f32;
+        self.cell_size = min_cell_size.max(math::sqrt(area)).max(1.0);
+        self.columns = ((width / self.cell_size) as usize + 1).max(1);
+        self.rows = ((height / self.cell_size) as usize + 1).max(1);
+    }
+
+    fn cell(&self, column: usize, row: usize) -> impl Iterator<Item = usize> + '_ {
+        let cell = row * self.columns + column;
+        let range = self.cell_start[cell] as usize..self.cell_start[cell + 1] as usize;
+        self.entries[range].iter().map(|&i| i as usize)
+    }
+
+    // 領域の外にいるパーティクルは端のマスに入れる
+    fn cell_of(&self, x: f32, y: f32) -> (usize, usize) {
+        let column = ((x / self.cell_size).max(0.0) as usize).min(self.columns - 1);
+        let row = ((y / self.cell_size).max(0.0) as usize).min(self.rows - 1);
+        (column, row)
+    }
+
+    // 数え上げソートでマスごとに並べる
+    fn build(&mut self, particles: &ParticleSet) {
+        let cell_count = self.columns * self.rows;
+        self.cell_start.clear();
+        self.cell_start.resize(cell_count + 1, 0);
+        self.cells.clear();
+        for (&x, &y) in particles.x.iter().zip(&particles.y) {
+            let (column, row) = self.cell_of(x, y);
+            let cell = row * self.columns + column;
+            self.cells.push(cell as u32);
+            self.cell_start[cell + 1] += 1;
+        }
+        for c in 0..cell_count {
+            self.cell_start[c + 1] += self.cell_start[c];
+        }
+        // cell_start[c] を書き込み位置として使い、終わったら1つずつ後ろにずれた分を戻す
+        self.entries.clear();
+        self.entries.resize(self.cells.len(), 0);
+        for (i, &cell) in self.cells.iter().enumerate() {
+            let slot = &mut self.cell_start[cell as usize];
+            self.entries[*slot as usize] = i as u32;
+            *slot += 1;
+        }
+        for c in (1..=cell_count).rev() {
+            self.cell_start[c] = self.cell_start[c - 1];
+        }
+        self.cell_start[0] = 0;
+    }
+}
@@ -0,0 +1,225 @@
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use web_sys::{WebGlBuffer, WebGlProgram, WebGlRenderingContext};
+
+#[cfg(target_arch = "wasm32")]
+use crate::i18n::{tr, Text};
+#[cfg(target_arch = "wasm32")]
+use crate::simulation::Simulation;
+
+// パーティクルの色をどこで計算するか（WebGL のフレーム時間のうち CPU の色の計算の分を測る用）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorPipeline {
+    // 毎フレーム CPU で色相から RGB に直し、寿命で薄めて送る（既定）
+    Cpu = 0,
+    // 基準の色相と寿命を変わったときだけ送り、RGB への変換は頂点シェーダーで行う
+    // 毎フレーム送るのは全体の色相のずれ（u_hueShift）だけ
+    Gpu = 1,
+}
+
+impl ColorPipeline {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorPipeline::Cpu => "cpu",
+            ColorPipeline::Gpu => "gpu",
+        }
+    }
+}
+
+// Gpu で色を計算するときのプログラムと、基準の色相と寿命のバッファ
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct GpuColors {
+    pub program: WebGlProgram,
+    buffer: WebGlBuffer,
+    // 送ったときの Simulation::color_epoch（変わるまで送り直さない）
+    uploaded_epoch: Option<u64>,
+    // 1個あたり (基準の色相, 寿命) を詰めて送るデータ（使い回す）
+    hue_life: Vec<f32>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GpuColors {
+    pub fn new(gl: &WebGlRenderingContext, program: WebGlProgram) -> Result<GpuColors, JsValue> {
+        let buffer = gl
+            .create_buffer()
+            .ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        Ok(GpuColors {
+            program,
+            buffer,
+            uploaded_epoch: None,
+            hue_life: Vec::new(),
+        })
+    }
+
+    // プログラムはキャッシュのものなので消さない
+    pub fn delete(&self, gl: &WebGlRenderingContext) {
+        gl.delete_buffer(Some(&self.buffer));
+    }
+
+    // 形や精度を変えてプログラムを作り直したとき（送ったデータはそのまま使える）
+    pub fn set_program(&mut self, program: WebGlProgram) {
+        self.program = program;
+    }
+
+    // 描くパーティクル（sim.visible()）の色相か寿命が変わっていれば送り直し、属性と uniform を設定する
+    // （self.program を使っていること）
+    // パーティクルごとの不透明度は寿命に掛けて送る（揺らすときは color_epoch が毎フレーム変わる）
+    // 塗り方は RainbowCycle だけで、背景色は sim.theme のものを毎フレーム設定する
+    pub fn bind(&mut self, gl: &WebGlRenderingContext, sim: &Simulation) {
+        let (particles, style, hue_shift) = (sim.visible(), sim.style(), sim.hue_shift);
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.buffer));
+        if self.uploaded_epoch != Some(sim.color_epoch) {
+            self.hue_life.clear();
+            for (i, (&hue, &life)) in particles.hue.iter().zip(&particles.life).enumerate() {
+                let (_, alpha) =
+                    style.at(particles.size[i], particles.alpha[i], hue, sim.frame_count);
+                self.hue_life.push((hue - hue_shift).rem_euclid(360.0));
+                self.hue_life.push(life.clamp(0.0, 1.0) * alpha);
+            }
+            unsafe {
+                let array = js_sys::Float32Array::view(&self.hue_life);
+                gl.buffer_data_with_array_buffer_view(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    &array,
+                    WebGlRenderingContext::DYNAMIC_DRAW,
+                );
+            }
+            self.uploaded_epoch = Some(sim.color_epoch);
+        }
+
+        let attrib = gl.get_attrib_location(&self.program, "a_hueLife") as u32;
+        gl.vertex_attrib_pointer_with_i32(attrib, 2, WebGlRenderingContext::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(attrib);
+        let shift_location = gl.get_uniform_location(&self.program, "u_hueShift");
+        gl.uniform1f(shift_location.as_ref(), hue_shift);
+        let background_location = gl.get_uniform_location(&self.program, "u_background");
+        let [r, g, b] = sim.theme.background();
+        gl.uniform3f(background_location.as_ref(), r, g, b);
+    }
+}
+
+// Gpu の頂点シェーダー（フラグメントシェーダーは Cpu と同じものを使う）
+// 色は彩度 1.0・明度 0.5 の HSL で、寿命が 1.0 未満なら背景色に近づける（CPU の pack_vertices() と同じ色）
+#[cfg(any(target_arch = "wasm32", test))]
+pub(crate) const GPU_COLOR_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    // x: 基準の色相（度）、y: 残りの寿命（1.0 で薄めない）
+    attribute vec2 a_hueLife;
+    uniform float u_pointSize;
+    uniform float u_hueShift;
+    uniform vec3 u_background;
+    varying vec3 v_color;
+    varying float v_edge;
+
+    vec3 hueToRgb(float hue) {
+        vec3 k = mod(vec3(5.0, 3.0, 1.0) + hue / 60.0, 6.0);
+        return 1.0 - clamp(min(k, 4.0 - k), 0.0, 1.0);
+    }
+
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+        gl_PointSize = u_pointSize;
+        vec3 rgb = hueToRgb(mod(a_hueLife.x + u_hueShift, 360.0));
+        v_color = mix(u_background, rgb, a_hueLife.y);
+        v_edge = 1.0 / max(u_pointSize, 1.0);
+    }
+"#;
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::GPU_COLOR_VERTEX_SHADER;
+    use crate::simd;
+    use crate::simulation::hsl_to_rgb;
+
+    // シェーダー側とずれてよい量（GPU は mediump で計算することもある）
+    const SHADER_TOLERANCE: f32 = 1e-3;
+    // 逆変換で戻したときにずれてよい量
+    const ROUND_TRIP_TOLERANCE: f32 = 1e-3;
+
+    // GPU_COLOR_VERTEX_SHADER の hueToRgb() をそのまま Rust に写したもの（s = 1, l = 0.5）
+    fn shader_hue_to_rgb(hue: f32) -> [f32; 3] {
+        let glsl_mod = |x: f32, y: f32| x - y * (x / y).floor();
+        [5.0, 3.0, 1.0].map(|offset: f32| {
+            let k = glsl_mod(offset + hue / 60.0, 6.0);
+            1.0 - k.min(4.0 - k).clamp(0.0, 1.0)
+        })
+    }
+
+    // RGB（各0.0〜1.0）から (色相[度], 彩度, 明度)。無彩色の色相は 0
+    fn rgb_to_hsl(r: f32, g: f32, b: f32) -> (f32, f32, f32) {
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let c = max - min;
+        if c == 0.0 {
+            return (0.0, 0.0, l);
+        }
+        let s = c / (1.0 - (2.0 * l - 1.0).abs());
+        let h_prime = if max == r {
+            ((g - b) / c).rem_euclid(6.0)
+        } else if max == g {
+            (b - r) / c + 2.0
+        } else {
+            (r - g) / c + 4.0
+        };
+        (h_prime * 60.0, s, l)
+    }
+
+    // 色相の差（360 で折り返す）
+    fn hue_distance(a: f32, b: f32) -> f32 {
+        let d = (a - b).rem_euclid(360.0);
+        d.min(360.0 - d)
+    }
+
+    #[test]
+    fn mirrored_shader_matches_source() {
+        // シェーダーを書き換えたら shader_hue_to_rgb() も合わせる
+        assert!(GPU_COLOR_VERTEX_SHADER
+            .contains("vec3 k = mod(vec3(5.0, 3.0, 1.0) + hue / 60.0, 6.0);"));
+        assert!(GPU_COLOR_VERTEX_SHADER.contains("return 1.0 - clamp(min(k, 4.0 - k), 0.0, 1.0);"));
+    }
+
+    proptest! {
+        #[test]
+        fn output_stays_in_unit_range(h in 0.0f32..360.0, s in 0.0f32..=1.0, l in 0.0f32..=1.0) {
+            let (r, g, b) = hsl_to_rgb(h, s, l);
+            for channel in [r, g, b] {
+                prop_assert!((0.0..=1.0).contains(&channel), "{:?} -> {}", (h, s, l), channel);
+            }
+        }
+
+        #[test]
+        fn round_trips_through_inverse(h in 0.0f32..360.0, s in 0.05f32..=1.0, l in 0.05f32..=0.95) {
+            let (r, g, b) = hsl_to_rgb(h, s, l);
+            let (h2, s2, l2) = rgb_to_hsl(r, g, b);
+            prop_assert!((l - l2).abs() < ROUND_TRIP_TOLERANCE, "l {} -> {}", l, l2);
+            prop_assert!((s - s2).abs() < ROUND_TRIP_TOLERANCE, "s {} -> {}", s, s2);
+            // 彩度が低いと色相の誤差が大きく出るので、色の差（chroma）で重みをつける
+            let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+            prop_assert!(hue_distance(h, h2) * chroma < ROUND_TRIP_TOLERANCE * 360.0, "h {} -> {}", h, h2);
+        }
+
+        #[test]
+        fn matches_shader_conversion(h in 0.0f32..360.0) {
+            let (r, g, b) = hsl_to_rgb(h, 1.0, 0.5);
+            let shader = shader_hue_to_rgb(h);
+            for (cpu, gpu) in [r, g, b].into_iter().zip(shader) {
+                prop_assert!((cpu - gpu).abs() < SHADER_TOLERANCE, "hue {}: {:?} vs {:?}", h, (r, g, b), shader);
+            }
+        }
+
+        #[test]
+        fn simd_batch_matches_scalar(hues in prop::collection::vec(0.0f32..360.0, 0..32)) {
+            let mut colors = Vec::new();
+            simd::hues_to_rgb(&hues, &mut colors);
+            prop_assert_eq!(colors.len(), hues.len() * 3);
+            for (&hue, rgb) in hues.iter().zip(colors.chunks_exact(3)) {
+                let (r, g, b) = hsl_to_rgb(hue, 1.0, 0.5);
+                prop_assert_eq!(rgb, &[r, g, b][..]);
+            }
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::simd;
+use crate::simulation::{hsl_to_rgb, Particle, ParticleSet};
+use crate::BACKGROUND_GRAY;
+
+// VelocityMapped でいちばん速い色（赤）にする速さ(px/フレーム)
+const VELOCITY_FULL_SPEED: f32 = 10.0;
+// VelocityMapped で止まっているパーティクルの色相（青）
+const VELOCITY_SLOW_HUE: f32 = 240.0;
+// Palette に渡せる色の数の上限
+pub(crate) const MAX_PALETTE_COLORS: usize = 256;
+
+// パーティクルの塗り方
+// 寿命による薄め方と不透明度はどれでも同じで、背景色は set_background_color() で別に変える
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ColorMode {
+    // 色相を回す虹色（既定）
+    #[default]
+    RainbowCycle = 0,
+    // 速さで青（止まっている）から赤（VELOCITY_FULL_SPEED 以上）まで塗る
+    // 物理演算を変えたときに、どこが速く動いているかを目で確かめる用
+    VelocityMapped = 1,
+    // 渡した色を色相の順に等分して割り当てる（色相が回ると色も順に入れ替わる）
+    Palette = 2,
+    // 渡した1色で塗る
+    Monochrome = 3,
+}
+
+impl ColorMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorMode::RainbowCycle => "rainbow_cycle",
+            ColorMode::VelocityMapped => "velocity_mapped",
+            ColorMode::Palette => "palette",
+            ColorMode::Monochrome => "monochrome",
+        }
+    }
+}
+
+// パーティクルの塗り方と背景色（バックエンド共通）
+// 色は 0〜1 の RGB
+#[derive(Clone, PartialEq, Debug, Default)]
+pub(crate) struct ColorTheme {
+    mode: ColorMode,
+    // Palette と Monochrome の色（Monochrome は先頭だけ使う）
+    colors: Vec<[f32; 3]>,
+    // None ならバックエンドの既定の背景
+    pub background: Option<[f32; 3]>,
+}
+
+impl ColorTheme {
+    pub fn mode(&self) -> ColorMode {
+        self.mode
+    }
+
+    // colors は r, g, b を並べたもの（RainbowCycle と VelocityMapped では使わない）
+    // Palette と Monochrome で1色もないか、3の倍数でないか、有限でない値があればエラーにして変えない
+    pub fn set_mode(&mut self, mode: ColorMode, colors: &[f32]) -> Result<(), String> {
+        let colors = match mode {
+            ColorMode::RainbowCycle | ColorMode::VelocityMapped => Vec::new(),
+            ColorMode::Palette | ColorMode::Monochrome => {
+                let max = match mode {
+                    ColorMode::Palette => MAX_PALETTE_COLORS,
+                    _ => 1,
+                };
+                let valid = !colors.is_empty()
+                    && colors.len().is_multiple_of(3)
+                    && colors.len() / 3 <= max
+                    && colors.iter().all(|c| c.is_finite());
+                if !valid {
+                    return Err(tr(
+                        Text::ColorModeColors,
+                        &[&mode.name(), &max, &colors.len()],
+                    ));
+                }
+                colors
+                    .chunks_exact(3)
+                    .map(|c| {
+                        [
+                            c[0].clamp(0.0, 1.0),
+                            c[1].clamp(0.0, 1.0),
+                            c[2].clamp(0.0, 1.0),
+                        ]
+                    })
+                    .collect()
+            }
+        };
+        self.mode = mode;
+        self.colors = colors;
+        Ok(())
+    }
+
+    // RGB をそれぞれ 0〜1 に丸めて設定する（有限でなければ既定に戻す）
+    pub fn set_background(&mut self, r: f32, g: f32, b: f32) {
+        self.background = [r, g, b]
+            .iter()
+            .all(|c| c.is_finite())
+            .then(|| [r.clamp(0.0, 1.0), g.clamp(0.0, 1.0), b.clamp(0.0, 1.0)]);
+    }
+
+    // GL と WebGPU とソフトウェアの描画の背景（既定は BACKGROUND_GRAY）
+    pub fn background(&self) -> [f32; 3] {
+        self.background.unwrap_or([BACKGROUND_GRAY; 3])
+    }
+
+    // true なら色は色相そのままの虹色（GPU で色を計算するときはこれしか描けない）
+    pub fn is_rainbow(&self) -> bool {
+        self.mode == ColorMode::RainbowCycle
+    }
+
+    // true なら hue_rgb() は色相そのままの HSL の色（Canvas2D の塗りの文字列を作り置きできる）
+    pub fn paints_hue(&self) -> bool {
+        matches!(
+            self.mode,
+            ColorMode::RainbowCycle | ColorMode::VelocityMapped
+        )
+    }
+
+    // 塗る色を決める色相（VelocityMapped では速さから決め、それ以外はパーティクルの色相）
+    // Canvas2D の色相ごとのまとまりやスプライトもこの色相で選ぶ
+    pub fn hue_of(&self, p: &Particle) -> f32 {
+        match self.mode {
+            ColorMode::VelocityMapped => velocity_hue(p.vx, p.vy),
+            _ => p.hue,
+        }
+    }
+
+    // hue_of() の色相で塗る色
+    pub fn hue_rgb(&self, hue: f32) -> (f32, f32, f32) {
+        match self.mode {
+            ColorMode::RainbowCycle | ColorMode::VelocityMapped => hsl_to_rgb(hue, 1.0, 0.5),
+            ColorMode::Palette => {
+                let index = (hue.rem_euclid(360.0) / 360.0 * self.colors.len() as f32) as usize;
+                let [r, g, b] = self.colors[index.min(self.colors.len() - 1)];
+                (r, g, b)
+            }
+            ColorMode::Monochrome => {
+                let [r, g, b] = self.colors[0];
+                (r, g, b)
+            }
+        }
+    }
+
+    pub fn rgb(&self, p: &Particle) -> (f32, f32, f32) {
+        self.hue_rgb(self.hue_of(p))
+    }
+
+    // 全パーティクルの色を colors に r, g, b の順で詰め直す（simd なら虹色を4個ずつまとめて変換する）
+    pub fn write_colors(&self, particles: &ParticleSet, simd: bool, colors: &mut Vec<f32>) {
+        colors.clear();
+        match self.mode {
+            ColorMode::RainbowCycle if simd => simd::hues_to_rgb(&particles.hue, colors),
+            ColorMode::RainbowCycle => {
+                for &hue in &particles.hue {
+                    let rgb = hsl_to_rgb(hue, 1.0, 0.5);
+                    colors.extend_from_slice(&[rgb.0, rgb.1, rgb.2]);
+                }
+            }
+            _ => {
+                for p in particles {
+                    let rgb = self.rgb(&p);
+                    colors.extend_from_slice(&[rgb.0, rgb.1, rgb.2]);
+                }
+            }
+        }
+    }
+}
+
+// 速さを 0〜VELOCITY_FULL_SPEED で青から赤までの色相にする
+fn velocity_hue(vx: f32, vy: f32) -> f32 {
+    let t = (math::sqrt(vx * vx + vy * vy) / VELOCITY_FULL_SPEED).min(1.0);
+    VELOCITY_SLOW_HUE * (1.0 - t)
+}
@@ -0,0 +1,204 @@
+use wasm_bindgen::prelude::*;
+
+use crate::stats;
+use crate::suite_report::{SuiteReport, SuiteResult};
+
+// 2つの結果の同じシーンの比較
+// 平均の大小だけでなく、フレームごとの時間の分布に差があると言えるかを Mann-Whitney U 検定で調べる
+// （SuiteConfig.keep_samples を有効にして計測した結果が必要）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct Comparison {
+    pub scene: String,
+    pub backend_a: String,
+    pub backend_b: String,
+    pub mean_a_ms: f64,
+    pub mean_b_ms: f64,
+    // b - a のフレーム時間の差の代表値（正なら b の方が遅い）
+    pub median_difference_ms: f64,
+    // フレームごとの時間が残っていなければ None
+    pub p_value: Option<f64>,
+    // p_value が alpha を下回ったときだけ、速い方（"a" か "b"）
+    pub faster: Option<String>,
+}
+
+// 同じバックエンド・シーンの組どうしを比べる（設定や端末を変えた2回の計測の比較）
+#[wasm_bindgen]
+pub fn compare_reports(a: &SuiteReport, b: &SuiteReport, alpha: f64) -> Vec<Comparison> {
+    a.results
+        .iter()
+        .filter_map(|result_a| {
+            let result_b = b.results.iter().find(|result_b| {
+                result_b.backend == result_a.backend && result_b.scene == result_a.scene
+            })?;
+            compare(result_a, result_b, alpha)
+        })
+        .collect()
+}
+
+// 1つの結果の中で、2つのバックエンドを同じシーンどうしで比べる
+#[wasm_bindgen]
+pub fn compare_backends(
+    report: &SuiteReport,
+    backend_a: &str,
+    backend_b: &str,
+    alpha: f64,
+) -> Vec<Comparison> {
+    report
+        .results
+        .iter()
+        .filter(|result_a| result_a.backend == backend_a)
+        .filter_map(|result_a| {
+            let result_b = report.results.iter().find(|result_b| {
+                result_b.backend == backend_b && result_b.scene == result_a.scene
+            })?;
+            compare(result_a, result_b, alpha)
+        })
+        .collect()
+}
+
+// どちらかが計測できていなければ比べない
+fn compare(a: &SuiteResult, b: &SuiteResult, alpha: f64) -> Option<Comparison> {
+    if !a.supported || !b.supported {
+        return None;
+    }
+    let (p_value, median_difference_ms, faster) = if a.samples.is_empty() || b.samples.is_empty() {
+        (None, b.mean_ms - a.mean_ms, None)
+    } else {
+        let samples_a: Vec<f64> = a.samples.iter().map(|&x| x as f64).collect();
+        let samples_b: Vec<f64> = b.samples.iter().map(|&x| x as f64).collect();
+        let test = stats::mann_whitney_u(&samples_a, &samples_b);
+        let faster = (test.p_value < alpha && test.median_difference != 0.0).then(|| {
+            if test.median_difference > 0.0 {
+                "a"
+            } else {
+                "b"
+            }
+            .to_string()
+        });
+        (Some(test.p_value), test.median_difference, faster)
+    };
+    Some(Comparison {
+        scene: a.scene.clone(),
+        backend_a: a.backend.clone(),
+        backend_b: b.backend.clone(),
+        mean_a_ms: a.mean_ms,
+        mean_b_ms: b.mean_ms,
+        median_difference_ms,
+        p_value,
+        faster,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(backend: &str, scene: &str, mean_ms: f64, samples: Vec<f32>) -> SuiteResult {
+        SuiteResult {
+            backend: backend.to_string(),
+            scene: scene.to_string(),
+            supported: true,
+            error: None,
+            trials: 1,
+            frames: samples.len() as u32,
+            mean_ms,
+            stddev_ms: 0.0,
+            aggregation: "mean".to_string(),
+            outliers: 0,
+            min_ms: mean_ms,
+            max_ms: mean_ms,
+            fps: 1000.0 / mean_ms,
+            reliable: true,
+            drift: 0.0,
+            warning: None,
+            gc_pauses: 0,
+            render_stalls: 0,
+            host_stalls: 0,
+            fingerprint: String::new(),
+            samples,
+        }
+    }
+
+    fn report(results: Vec<SuiteResult>) -> SuiteReport {
+        SuiteReport {
+            results,
+            seed: 1,
+            total_ms: 0.0,
+            metadata: String::from("{}"),
+        }
+    }
+
+    // center ms の前後にばらついたフレーム時間
+    fn samples(center: f32) -> Vec<f32> {
+        (0..40).map(|i| center + (i % 7) as f32 * 0.1).collect()
+    }
+
+    #[test]
+    fn compares_matching_pairs_and_picks_the_faster() {
+        let a = report(vec![
+            result("webgl", "particles", 10.3, samples(10.0)),
+            result("webgl", "blur", 5.0, samples(5.0)),
+            result("canvas2d", "particles", 30.0, samples(30.0)),
+        ]);
+        let b = report(vec![
+            result("webgl", "particles", 20.3, samples(20.0)),
+            result("webgl", "blur", 5.0, samples(5.0)),
+        ]);
+        let comparisons = compare_reports(&a, &b, 0.05);
+        // canvas2d は b にないので比べない
+        assert_eq!(comparisons.len(), 2);
+
+        let slower = &comparisons[0];
+        assert_eq!(
+            (slower.scene.as_str(), slower.backend_a.as_str()),
+            ("particles", "webgl")
+        );
+        assert!((slower.median_difference_ms - 10.0).abs() < 1e-4);
+        assert!(slower.p_value.unwrap() < 1e-6);
+        assert_eq!(slower.faster.as_deref(), Some("a"));
+
+        let same = &comparisons[1];
+        assert_eq!(same.median_difference_ms, 0.0);
+        assert!(same.p_value.unwrap() > 0.05);
+        assert_eq!(same.faster, None);
+
+        let reversed = compare_reports(&b, &a, 0.05);
+        assert_eq!(reversed[0].faster.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn skips_unsupported_and_falls_back_to_means_without_samples() {
+        let mut unsupported = result("webgpu", "particles", 0.0, Vec::new());
+        unsupported.supported = false;
+        let a = report(vec![
+            unsupported.clone(),
+            result("webgl", "particles", 8.0, Vec::new()),
+        ]);
+        let b = report(vec![
+            unsupported,
+            result("webgl", "particles", 6.5, samples(6.5)),
+        ]);
+        let comparisons = compare_reports(&a, &b, 0.05);
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].median_difference_ms, -1.5);
+        assert_eq!(
+            (comparisons[0].p_value, comparisons[0].faster.clone()),
+            (None, None)
+        );
+        assert!(compare_reports(&report(Vec::new()), &b, 0.05).is_empty());
+    }
+
+    #[test]
+    fn compare_backends_pairs_scenes_within_one_report() {
+        let report = report(vec![
+            result("webgl", "particles", 10.3, samples(10.0)),
+            result("canvas2d", "particles", 40.3, samples(40.0)),
+            result("canvas2d", "blur", 3.0, samples(3.0)),
+        ]);
+        let comparisons = compare_backends(&report, "webgl", "canvas2d", 0.01);
+        assert_eq!(comparisons.len(), 1);
+        assert_eq!(comparisons[0].backend_b, "canvas2d");
+        assert_eq!(comparisons[0].faster.as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,278 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    CanvasRenderingContext2d, HtmlCanvasElement, OffscreenCanvas, WebGl2RenderingContext,
+    WebGlRenderingContext,
+};
+
+use crate::error::BenchError;
+use crate::gpu::GpuCanvasContext;
+
+// 描画コンテキストの取得方法を抽象化
+// ブラウザのcanvas要素だけでなく、Node上のheadless-glやnode-canvasが作った
+// コンテキストも同じバックエンドに渡せるようにする
+pub trait ContextSource<C> {
+    fn acquire(&self) -> Result<AcquiredContext<C>, JsValue>;
+}
+
+// 取得したコンテキストと描画領域のサイズ
+pub struct AcquiredContext<C> {
+    pub context: C,
+    pub width: f32,
+    pub height: f32,
+}
+
+// ドキュメント内のcanvas要素をidで探す（従来の方法）
+pub struct CanvasById<'a>(pub &'a str);
+
+impl CanvasById<'_> {
+    pub(crate) fn canvas(&self) -> Result<HtmlCanvasElement, BenchError> {
+        let document = web_sys::window()
+            .ok_or(BenchError::WindowUnavailable)?
+            .document()
+            .ok_or(BenchError::DocumentUnavailable)?;
+        document
+            .get_element_by_id(self.0)
+            .ok_or_else(|| BenchError::CanvasNotFound(self.0.to_string()))?
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|_| BenchError::NotACanvas(self.0.to_string()))
+    }
+}
+
+impl ContextSource<WebGlRenderingContext> for CanvasById<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGlRenderingContext>, JsValue> {
+        CanvasElement(&self.canvas()?).acquire()
+    }
+}
+
+impl ContextSource<WebGl2RenderingContext> for CanvasById<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGl2RenderingContext>, JsValue> {
+        CanvasElement(&self.canvas()?).acquire()
+    }
+}
+
+impl ContextSource<GpuCanvasContext> for CanvasById<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<GpuCanvasContext>, JsValue> {
+        CanvasElement(&self.canvas()?).acquire()
+    }
+}
+
+impl ContextSource<CanvasRenderingContext2d> for CanvasById<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<CanvasRenderingContext2d>, JsValue> {
+        CanvasElement(&self.canvas()?).acquire()
+    }
+}
+
+// WebGL のコンテキストに要求する属性（クリップのシーンがステンシルバッファを使う）
+fn gl_attributes() -> JsValue {
+    let attributes = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(&attributes, &"stencil".into(), &true.into());
+    attributes.into()
+}
+
+// 取得済みのcanvas要素（ドキュメントに追加されていなくてもよい）
+pub struct CanvasElement<'a>(pub &'a HtmlCanvasElement);
+
+impl ContextSource<WebGlRenderingContext> for CanvasElement<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGlRenderingContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context_with_context_options("webgl", &gl_attributes())?
+            .ok_or(BenchError::WebGlUnsupported)?
+            .dyn_into::<WebGlRenderingContext>()
+            .map_err(|_| BenchError::WebGlUnsupported)?;
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+impl ContextSource<WebGl2RenderingContext> for CanvasElement<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGl2RenderingContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context_with_context_options("webgl2", &gl_attributes())?
+            .ok_or(BenchError::WebGl2Unsupported)?
+            .dyn_into::<WebGl2RenderingContext>()
+            .map_err(|_| BenchError::WebGl2Unsupported)?;
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+impl ContextSource<GpuCanvasContext> for CanvasElement<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<GpuCanvasContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context("webgpu")?
+            .ok_or(BenchError::WebGpuUnsupported)?
+            .unchecked_into::<GpuCanvasContext>();
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+impl ContextSource<CanvasRenderingContext2d> for CanvasElement<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<CanvasRenderingContext2d>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context("2d")?
+            .ok_or(BenchError::Canvas2DUnavailable)?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .map_err(|_| BenchError::Canvas2DUnavailable)?;
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+// Worker に transferControlToOffscreen() で渡された OffscreenCanvas
+// Worker には document がないので CanvasById は使えない
+pub struct OffscreenCanvasSource<'a>(pub &'a OffscreenCanvas);
+
+impl ContextSource<WebGlRenderingContext> for OffscreenCanvasSource<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGlRenderingContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context_with_context_options("webgl", &gl_attributes())?
+            .ok_or(BenchError::WebGlUnsupported)?
+            .dyn_into::<WebGlRenderingContext>()
+            .map_err(|_| BenchError::WebGlUnsupported)?;
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+impl ContextSource<WebGl2RenderingContext> for OffscreenCanvasSource<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<WebGl2RenderingContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context_with_context_options("webgl2", &gl_attributes())?
+            .ok_or(BenchError::WebGl2Unsupported)?
+            .dyn_into::<WebGl2RenderingContext>()
+            .map_err(|_| BenchError::WebGl2Unsupported)?;
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+impl ContextSource<GpuCanvasContext> for OffscreenCanvasSource<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<GpuCanvasContext>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context("webgpu")?
+            .ok_or(BenchError::WebGpuUnsupported)?
+            .unchecked_into::<GpuCanvasContext>();
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+// OffscreenCanvas の2Dコンテキストは OffscreenCanvasRenderingContext2D という別の型だが、
+// Canvas2D バックエンドが使うメソッドは同じ名前で揃っているので unchecked_into で扱う
+impl ContextSource<CanvasRenderingContext2d> for OffscreenCanvasSource<'_> {
+    fn acquire(&self) -> Result<AcquiredContext<CanvasRenderingContext2d>, JsValue> {
+        let canvas = self.0;
+        let context = canvas
+            .get_context("2d")?
+            .ok_or(BenchError::Canvas2DUnavailable)?
+            .unchecked_into::<CanvasRenderingContext2d>();
+
+        Ok(AcquiredContext {
+            context,
+            width: canvas.width() as f32,
+            height: canvas.height() as f32,
+        })
+    }
+}
+
+// JS側で作られたコンテキストをそのまま使う（headless-gl の createGL() など）
+// headless-gl のオブジェクトは instanceof WebGLRenderingContext を満たさないので
+// dyn_into ではなく unchecked_into で扱う（web-sysのメソッド呼び出しは名前ベース）
+pub struct ExternalContext {
+    pub context: JsValue,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl<C: JsCast> ContextSource<C> for ExternalContext {
+    fn acquire(&self) -> Result<AcquiredContext<C>, JsValue> {
+        if self.context.is_null() || self.context.is_undefined() {
+            return Err(BenchError::ExternalContextMissing.into());
+        }
+
+        Ok(AcquiredContext {
+            context: self.context.clone().unchecked_into::<C>(),
+            width: self.width,
+            height: self.height,
+        })
+    }
+}
+
+// このブラウザで WebGL のコンテキストを作れるか（ページでも Worker でも使える）
+// バックエンドを作る前に確かめておけば、作れないときにページ側で Canvas2D などに切り替えられる
+#[wasm_bindgen]
+pub fn is_webgl_supported() -> bool {
+    probe_context("webgl")
+}
+
+#[wasm_bindgen]
+pub fn is_webgl2_supported() -> bool {
+    probe_context("webgl2")
+}
+
+// 使い捨てのキャンバスで getContext() を試す（ページなら canvas 要素、Worker なら OffscreenCanvas）
+// 同時に持てる WebGL のコンテキストの数には上限があるので、作れたらすぐに手放す
+fn probe_context(kind: &str) -> bool {
+    let context = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document
+            .create_element("canvas")
+            .ok()
+            .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+            .and_then(|canvas| canvas.get_context(kind).ok().flatten()),
+        None => OffscreenCanvas::new(1, 1)
+            .ok()
+            .and_then(|canvas| canvas.get_context(kind).ok().flatten()),
+    };
+    let Some(context) = context else {
+        return false;
+    };
+    let lose_context = context
+        .unchecked_ref::<WebGlRenderingContext>()
+        .get_extension("WEBGL_lose_context")
+        .ok()
+        .flatten();
+    if let Some(extension) = lose_context {
+        if let Ok(lose) = js_sys::Reflect::get(&extension, &"loseContext".into()) {
+            if let Some(lose) = lose.dyn_ref::<js_sys::Function>() {
+                let _ = lose.call0(&extension);
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,78 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{Event, EventTarget};
+
+type Listener = Closure<dyn FnMut(Event)>;
+
+// webglcontextlost / webglcontextrestored のコールバックが書き込む状態
+#[derive(Default)]
+struct LossState {
+    // 戻ったがまだ GL のリソースを作り直していない
+    restored: Cell<bool>,
+    losses: Cell<u32>,
+}
+
+// キャンバスの WebGL コンテキストが失われたり戻ったりするのを見張る
+// モバイルやタブを多く開いたときにブラウザがコンテキストを取り上げるので、
+// 戻ったら描画側がシェーダーとバッファを作り直して続けられるようにする
+pub(crate) struct ContextLossWatch {
+    target: EventTarget,
+    state: Rc<LossState>,
+    listeners: Vec<(&'static str, Listener)>,
+}
+
+impl ContextLossWatch {
+    // canvas（HtmlCanvasElement か OffscreenCanvas）にリスナーを登録する
+    pub fn attach(canvas: &JsValue) -> Result<ContextLossWatch, JsValue> {
+        let mut watch = ContextLossWatch {
+            target: canvas.clone().dyn_into::<EventTarget>()?,
+            state: Rc::new(LossState::default()),
+            listeners: Vec::new(),
+        };
+        // 既定の動作を止めないと、ブラウザはコンテキストを戻さない
+        watch.listen("webglcontextlost", |state, event| {
+            event.prevent_default();
+            state.restored.set(false);
+            state.losses.set(state.losses.get() + 1);
+        })?;
+        watch.listen("webglcontextrestored", |state, _| {
+            state.restored.set(true);
+        })?;
+        Ok(watch)
+    }
+
+    fn listen(
+        &mut self,
+        kind: &'static str,
+        handler: fn(&LossState, &Event),
+    ) -> Result<(), JsValue> {
+        let state = self.state.clone();
+        let callback = Listener::new(move |event: Event| handler(&state, &event));
+        self.target
+            .add_event_listener_with_callback(kind, callback.as_ref().unchecked_ref())?;
+        self.listeners.push((kind, callback));
+        Ok(())
+    }
+
+    // これまでに失われた回数
+    pub fn losses(&self) -> u32 {
+        self.state.losses.get()
+    }
+
+    // 前回から戻っていれば true（リソースを作り直すのは1回だけにする）
+    pub fn take_restored(&self) -> bool {
+        self.state.restored.replace(false)
+    }
+}
+
+impl Drop for ContextLossWatch {
+    fn drop(&mut self) {
+        for (kind, callback) in &self.listeners {
+            let _ = self
+                .target
+                .remove_event_listener_with_callback(kind, callback.as_ref().unchecked_ref());
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use wasm_bindgen::prelude::*;
+
+// パーティクルの描き方（Canvas2D の API 呼び出しの回数の違いを比べる）
+// WebGL と WebGPU は元から全部を1回の描画命令で描くので Batched だけ
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DrawStrategy {
+    // 1個ずつ塗りの色を設定してパスを作って塗る（Canvas2D の既定）
+    PerParticle = 0,
+    // 色相を HUE_BUCKETS 段階に丸めて分け、同じ色のものを1つのパスにまとめて塗る
+    Batched = 1,
+}
+
+impl DrawStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            DrawStrategy::PerParticle => "per_particle",
+            DrawStrategy::Batched => "batched",
+        }
+    }
+}
+
+// Batched で色相を丸める段階の数
+pub(crate) const HUE_BUCKETS: usize = 36;
+// Batched で寿命による薄れ方を丸める段階の数（最後の段階が不透明）
+pub(crate) const ALPHA_LEVELS: usize = 4;
+
+// 色相と不透明度（0.0〜1.0）を丸めたまとまりの番号（0..HUE_BUCKETS * ALPHA_LEVELS）
+pub(crate) fn bucket(hue: f32, alpha: f32) -> usize {
+    let step = 360.0 / HUE_BUCKETS as f32;
+    let hue_index = (hue.rem_euclid(360.0) / step).round() as usize % HUE_BUCKETS;
+    let alpha_index = (alpha.clamp(0.0, 1.0) * (ALPHA_LEVELS - 1) as f32).round() as usize;
+    alpha_index * HUE_BUCKETS + hue_index
+}
+
+// まとまりの番号から、塗る色相と不透明度に戻す
+pub(crate) fn bucket_color(index: usize) -> (f32, f32) {
+    let hue = (index % HUE_BUCKETS) as f32 * 360.0 / HUE_BUCKETS as f32;
+    let alpha = (index / HUE_BUCKETS) as f32 / (ALPHA_LEVELS - 1) as f32;
+    (hue, alpha)
+}
@@ -0,0 +1,51 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::i18n::{tr, Text};
+
+// update() と render() を回すきっかけ（どれで回すかでフレームの間隔と揺れが変わる）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LoopDriver {
+    // requestAnimationFrame（表示の更新に合わせる）
+    AnimationFrame = 0,
+    // setTimeout(0)（表示と関係なく、タイマーの最小間隔で回る）
+    Timeout = 1,
+    // requestIdleCallback（ブラウザの手が空いたときだけ回る。Safari には無い）
+    IdleCallback = 2,
+}
+
+impl LoopDriver {
+    pub(crate) const ALL: [LoopDriver; 3] = [
+        LoopDriver::AnimationFrame,
+        LoopDriver::Timeout,
+        LoopDriver::IdleCallback,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LoopDriver::AnimationFrame => "animation_frame",
+            LoopDriver::Timeout => "timeout",
+            LoopDriver::IdleCallback => "idle_callback",
+        }
+    }
+}
+
+// driver で次に呼ばれるまで待つ（使えないブラウザではエラー）
+pub(crate) async fn next_tick(driver: LoopDriver) -> Result<(), JsValue> {
+    let unavailable = || JsValue::from(tr(Text::LoopDriverUnavailable, &[&driver.name()]));
+    let window = web_sys::window().ok_or_else(unavailable)?;
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        let scheduled = match driver {
+            LoopDriver::AnimationFrame => window.request_animation_frame(&resolve).map(drop),
+            LoopDriver::Timeout => window
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 0)
+                .map(drop),
+            LoopDriver::IdleCallback => window.request_idle_callback(&resolve).map(drop),
+        };
+        if scheduled.is_err() {
+            let _ = reject.call1(&JsValue::NULL, &unavailable());
+        }
+    });
+    JsFuture::from(promise).await.map(drop)
+}
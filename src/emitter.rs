@@ -0,0 +1,146 @@
+use rand::Rng;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+use crate::math;
+use crate::simulation::Particle;
+
+// 放出したパーティクルの既定の寿命(フレーム)
+const DEFAULT_LIFETIME: f32 = 120.0;
+
+// 毎フレーム rate 個ずつパーティクルを出し続ける点（噴水や花火のような連続した効果）
+// 上向きを中心に spread 度の扇形の中へ、おおよそ speed px/フレームで打ち出す
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Emitter {
+    // remove_emitter() で指定する番号
+    pub id: u32,
+    pub x: f32,
+    pub y: f32,
+    // 1フレームに出す数（端数は次のフレームに持ち越す）
+    pub rate: f32,
+    // 打ち出す向きの広がり（度、0〜360）
+    pub spread: f32,
+    pub speed: f32,
+}
+
+impl Emitter {
+    // 1個分の初期状態（速さは ±25% ばらつかせる）
+    pub(crate) fn particle(&self, rng: &mut impl Rng) -> Particle {
+        let angle = -PI / 2.0 + (rng.gen::<f32>() - 0.5) * self.spread.to_radians();
+        let speed = self.speed * (0.75 + rng.gen::<f32>() * 0.5);
+        Particle {
+            x: self.x,
+            y: self.y,
+            vx: math::cos(angle) * speed,
+            vy: math::sin(angle) * speed,
+            hue: rng.gen::<f32>() * 360.0,
+        }
+    }
+}
+
+// 置かれているエミッターと、放出したパーティクルの寿命
+#[derive(Clone, Debug)]
+pub(crate) struct Emitters {
+    list: Vec<Emitter>,
+    // 持ち越している端数（list と同じ並び）
+    carry: Vec<f32>,
+    next_id: u32,
+    lifetime: f32,
+    // 寿命のあるパーティクルが残っている間は true（エミッターをすべて外しても出し直しを続ける）
+    pub active: bool,
+    // エミッターから出したパーティクルの累計（尽きた場所の使い回しと、増やした分の両方）
+    pub emitted: u64,
+}
+
+impl Default for Emitters {
+    fn default() -> Emitters {
+        Emitters {
+            list: Vec::new(),
+            carry: Vec::new(),
+            next_id: 0,
+            lifetime: DEFAULT_LIFETIME,
+            active: false,
+            emitted: 0,
+        }
+    }
+}
+
+impl Emitters {
+    // 範囲外の値は丸める（数と速さは 0 以上、広がりは 0〜360 度）
+    pub fn add(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        let finite_or = |value: f32, default: f32| if value.is_finite() { value } else { default };
+        let id = self.next_id;
+        self.next_id += 1;
+        self.list.push(Emitter {
+            id,
+            x: finite_or(x, 0.0),
+            y: finite_or(y, 0.0),
+            rate: finite_or(rate, 0.0).max(0.0),
+            spread: finite_or(spread, 0.0).clamp(0.0, 360.0),
+            speed: finite_or(speed, 0.0).max(0.0),
+        });
+        self.carry.push(0.0);
+        self.active = true;
+        id
+    }
+
+    // 見つからなければ false
+    pub fn remove(&mut self, id: u32) -> bool {
+        let Some(index) = self.list.iter().position(|emitter| emitter.id == id) else {
+            return false;
+        };
+        self.list.remove(index);
+        self.carry.remove(index);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.list.clear();
+        self.carry.clear();
+    }
+
+    pub fn list(&self) -> Vec<Emitter> {
+        self.list.clone()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // 1フレーム分の寿命の減り（寿命 1.0 を lifetime フレームで使い切る）
+    pub fn decay(&self) -> f32 {
+        1.0 / self.lifetime
+    }
+
+    pub fn lifetime(&self) -> f32 {
+        self.lifetime
+    }
+
+    // 1フレーム以上に丸めて、実際に使う値を返す
+    pub fn set_lifetime(&mut self, frames: f32) -> f32 {
+        self.lifetime = if frames.is_finite() {
+            frames.max(1.0)
+        } else {
+            DEFAULT_LIFETIME
+        };
+        self.lifetime
+    }
+
+    // このフレームに出すパーティクルの数だけエミッターを並べる
+    pub fn tick(&mut self) -> Vec<Emitter> {
+        let mut due = Vec::new();
+        for (emitter, carry) in self.list.iter().zip(&mut self.carry) {
+            *carry += emitter.rate;
+            let count = carry.floor();
+            *carry -= count;
+            due.extend(std::iter::repeat_n(*emitter, count as usize));
+        }
+        due
+    }
+}
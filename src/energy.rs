@@ -0,0 +1,246 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+// Compute Pressure API の CPU の逼迫度（PressureRecord.state）
+// 端末が熱や電力の制限で性能を落としているかの目安で、長い計測のどこで落ちたかを残す
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum PressureState {
+    // API が無いか、まだ通知が来ていない
+    Unknown = 0,
+    Nominal = 1,
+    Fair = 2,
+    Serious = 3,
+    Critical = 4,
+}
+
+impl PressureState {
+    pub(crate) const ALL: [PressureState; 5] = [
+        PressureState::Unknown,
+        PressureState::Nominal,
+        PressureState::Fair,
+        PressureState::Serious,
+        PressureState::Critical,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PressureState::Unknown => "unknown",
+            PressureState::Nominal => "nominal",
+            PressureState::Fair => "fair",
+            PressureState::Serious => "serious",
+            PressureState::Critical => "critical",
+        }
+    }
+
+    fn parse(name: &str) -> PressureState {
+        PressureState::ALL
+            .into_iter()
+            .find(|state| state.name() == name)
+            .unwrap_or(PressureState::Unknown)
+    }
+}
+
+// Battery Status API の1回分の値
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BatteryReading {
+    // 残量（0〜1、多くのブラウザで 1% 刻み）
+    pub level: f64,
+    pub charging: bool,
+    // 空になるまでの推定秒数（充電中や推定できないときは無限大）
+    pub discharging_time_s: f64,
+}
+
+// 計測の間の逼迫度ごとの時間と電池の値を集め、消費したエネルギーを推定する
+// 電池の容量は API から取れないので capacity_wh を渡す（端末ごとに合わせないと絶対値は目安にしかならない）
+#[derive(Default)]
+pub(crate) struct EnergyLog {
+    // PressureState ごとの時間(ms)
+    pressure_ms: [f64; 5],
+    worst: Option<PressureState>,
+    first: Option<BatteryReading>,
+    last: Option<BatteryReading>,
+    charged: bool,
+    // dischargingTime から求めた消費電力(W)の合計と回数
+    power_w: f64,
+    power_samples: u32,
+}
+
+// 推定したエネルギー（どちらの方法でも推定できなければ None）
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct EnergyEstimate {
+    pub joules: f64,
+    pub watts: f64,
+    // "battery_level"（残量の減り）か "discharging_time"（空になるまでの推定時間）
+    pub method: &'static str,
+}
+
+impl EnergyLog {
+    // 前の記録から elapsed_ms の間 state だった
+    pub fn record(
+        &mut self,
+        elapsed_ms: f64,
+        state: PressureState,
+        battery: Option<BatteryReading>,
+        capacity_wh: f64,
+    ) {
+        self.pressure_ms[state as usize] += elapsed_ms.max(0.0);
+        if state != PressureState::Unknown {
+            self.worst = self.worst.max(Some(state));
+        }
+        let Some(battery) = battery else {
+            return;
+        };
+        self.first.get_or_insert(battery);
+        self.last = Some(battery);
+        self.charged |= battery.charging;
+        if !battery.charging
+            && battery.discharging_time_s.is_finite()
+            && battery.discharging_time_s > 0.0
+        {
+            self.power_w += battery.level * capacity_wh * 3_600.0 / battery.discharging_time_s;
+            self.power_samples += 1;
+        }
+    }
+
+    pub fn pressure_ms(&self, state: PressureState) -> f64 {
+        self.pressure_ms[state as usize]
+    }
+
+    pub fn worst(&self) -> PressureState {
+        self.worst.unwrap_or(PressureState::Unknown)
+    }
+
+    pub fn charging(&self) -> Option<bool> {
+        self.last.map(|_| self.charged)
+    }
+
+    pub fn battery_levels(&self) -> Option<(f64, f64)> {
+        Some((self.first?.level, self.last?.level))
+    }
+
+    // 途中で充電されていたら推定しない
+    // 残量が 1 刻み以上減っていればその分を、減っていなければ dischargingTime からの平均の電力を使う
+    pub fn estimate(&self, elapsed_ms: f64, capacity_wh: f64) -> Option<EnergyEstimate> {
+        let seconds = elapsed_ms / 1_000.0;
+        if self.charged || seconds <= 0.0 || capacity_wh.is_nan() || capacity_wh <= 0.0 {
+            return None;
+        }
+        let (first, last) = self.battery_levels()?;
+        let (joules, method) = if first > last {
+            ((first - last) * capacity_wh * 3_600.0, "battery_level")
+        } else if self.power_samples > 0 {
+            (
+                self.power_w / self.power_samples as f64 * seconds,
+                "discharging_time",
+            )
+        } else {
+            return None;
+        };
+        Some(EnergyEstimate {
+            joules,
+            watts: joules / seconds,
+            method,
+        })
+    }
+}
+
+fn get(target: &JsValue, key: &str) -> Option<JsValue> {
+    js_sys::Reflect::get(target, &key.into())
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+// PressureObserver で CPU の逼迫度を見続ける（Chromium 系にしか無いので、無ければ Unknown のまま）
+// 通知はイベントループで届くので、見ている間はフレームごとにブラウザに制御を返すこと
+pub(crate) struct PressureWatch {
+    state: Rc<Cell<PressureState>>,
+    observer: Option<JsValue>,
+    _callback: Option<Closure<dyn FnMut(js_sys::Array)>>,
+}
+
+impl PressureWatch {
+    pub async fn start() -> PressureWatch {
+        let state = Rc::new(Cell::new(PressureState::Unknown));
+        let mut watch = PressureWatch {
+            state: state.clone(),
+            observer: None,
+            _callback: None,
+        };
+        let Some(constructor) = get(&js_sys::global(), "PressureObserver")
+            .and_then(|value| value.dyn_into::<js_sys::Function>().ok())
+        else {
+            return watch;
+        };
+        // 1回の通知にまとめて届いた記録は最後のものが今の状態
+        let callback = Closure::<dyn FnMut(js_sys::Array)>::new(move |records: js_sys::Array| {
+            let latest = records.iter().last();
+            if let Some(name) = latest.and_then(|record| get(&record, "state")?.as_string()) {
+                state.set(PressureState::parse(&name));
+            }
+        });
+        let Ok(observer) =
+            js_sys::Reflect::construct(&constructor, &js_sys::Array::of1(callback.as_ref()))
+        else {
+            return watch;
+        };
+        let observe =
+            get(&observer, "observe").and_then(|value| value.dyn_into::<js_sys::Function>().ok());
+        let Some(Ok(promise)) = observe.map(|observe| observe.call1(&observer, &"cpu".into()))
+        else {
+            return watch;
+        };
+        // 権限ポリシーなどで見られないときは reject される
+        if JsFuture::from(js_sys::Promise::resolve(&promise))
+            .await
+            .is_err()
+        {
+            return watch;
+        }
+        watch.observer = Some(observer);
+        watch._callback = Some(callback);
+        watch
+    }
+
+    pub fn state(&self) -> PressureState {
+        self.state.get()
+    }
+}
+
+impl Drop for PressureWatch {
+    fn drop(&mut self) {
+        let Some(observer) = &self.observer else {
+            return;
+        };
+        if let Some(disconnect) =
+            get(observer, "disconnect").and_then(|value| value.dyn_into::<js_sys::Function>().ok())
+        {
+            let _ = disconnect.call0(observer);
+        }
+    }
+}
+
+// navigator.getBattery() の BatteryManager（無いブラウザや権限ポリシーで拒まれたときは None）
+pub(crate) async fn battery() -> Option<JsValue> {
+    let navigator = get(&js_sys::global(), "navigator")?;
+    let get_battery = get(&navigator, "getBattery")?
+        .dyn_into::<js_sys::Function>()
+        .ok()?;
+    let promise = get_battery.call0(&navigator).ok()?;
+    JsFuture::from(js_sys::Promise::resolve(&promise))
+        .await
+        .ok()
+}
+
+pub(crate) fn read_battery(manager: &JsValue) -> Option<BatteryReading> {
+    Some(BatteryReading {
+        level: get(manager, "level")?.as_f64()?,
+        charging: get(manager, "charging")?.as_bool()?,
+        discharging_time_s: get(manager, "dischargingTime")
+            .and_then(|value| value.as_f64())
+            .unwrap_or(f64::INFINITY),
+    })
+}
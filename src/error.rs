@@ -0,0 +1,46 @@
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+
+// ページ・キャンバス・描画コンテキストを用意できなかったときのエラー
+// 以前は unwrap() で WASM ごと止まっていたので、何が足りないかを翻訳したメッセージで返す
+// JS には他のエラーと同じく文字列として投げる（is_webgl_supported() などで先に確かめられる）
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub(crate) enum BenchError {
+    // Worker や Node では window が無い（OffscreenCanvas か外部のコンテキストを渡す）
+    WindowUnavailable,
+    DocumentUnavailable,
+    // 指定した id の要素が無い・canvas 要素ではない
+    CanvasNotFound(String),
+    NotACanvas(String),
+    WebGlUnsupported,
+    WebGl2Unsupported,
+    WebGpuUnsupported,
+    Canvas2DUnavailable,
+    ExternalContextMissing,
+}
+
+impl fmt::Display for BenchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let message = match self {
+            BenchError::WindowUnavailable => tr(Text::WindowUnavailable, &[]),
+            BenchError::DocumentUnavailable => tr(Text::DocumentUnavailable, &[]),
+            BenchError::CanvasNotFound(id) => tr(Text::CanvasNotFound, &[id]),
+            BenchError::NotACanvas(id) => tr(Text::NotACanvas, &[id]),
+            BenchError::WebGlUnsupported => tr(Text::WebGlUnsupported, &[]),
+            BenchError::WebGl2Unsupported => tr(Text::WebGl2Unsupported, &[]),
+            BenchError::WebGpuUnsupported => tr(Text::WebGpuUnsupported, &[]),
+            BenchError::Canvas2DUnavailable => tr(Text::Canvas2DUnavailable, &[]),
+            BenchError::ExternalContextMissing => tr(Text::ExternalContextMissing, &[]),
+        };
+        f.write_str(&message)
+    }
+}
+
+impl From<BenchError> for JsValue {
+    fn from(error: BenchError) -> JsValue {
+        JsValue::from(error.to_string())
+    }
+}
@@ -0,0 +1,93 @@
+#[cfg(target_arch = "wasm32")]
+use std::collections::{HashSet, VecDeque};
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use crate::timing;
+
+// 溜めておくイベントの上限（古いものから捨てる）
+#[cfg(target_arch = "wasm32")]
+const MAX_QUEUED: usize = 256;
+
+// 計測結果の解釈に影響する出来事の種類
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum EventKind {
+    ContextLost = 0,
+    // 拡張機能がなく遅い経路で描いている
+    ExtensionMissing = 1,
+    // 範囲外の値が丸められた
+    ParameterClamped = 2,
+    // 描画先が対応していない方法の代わりに別の方法で描いた
+    FallbackUsed = 3,
+    // create_best_backend() が選んだバックエンド
+    BackendSelected = 4,
+    // ブラウザ固有の問題を検出し、回避策を使った（または記録した）
+    QuirkApplied = 5,
+    // 失われた WebGL コンテキストが戻り、GL のリソースを作り直した
+    ContextRestored = 6,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct BenchmarkEvent {
+    pub kind: EventKind,
+    pub message: String,
+    // performance.now() の時刻(ms)
+    pub time_ms: f64,
+}
+
+// 警告をJSへ渡すための窓口
+// コールバックが登録されていればすぐに呼び、なければ poll_events() で取り出されるまで溜める
+// 毎フレーム起きる警告で溢れないよう、同じ内容は clear_reported() までに1回だけ通知する
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+pub(crate) struct EventBus {
+    queue: VecDeque<BenchmarkEvent>,
+    callback: Option<js_sys::Function>,
+    reported: HashSet<(EventKind, String)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl EventBus {
+    pub fn emit(&mut self, kind: EventKind, message: &str) {
+        if !self.reported.insert((kind, message.to_string())) {
+            return;
+        }
+        let event = BenchmarkEvent {
+            kind,
+            message: message.to_string(),
+            time_ms: timing::now_ms(),
+        };
+        if let Some(callback) = &self.callback {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(event));
+            return;
+        }
+        if self.queue.len() == MAX_QUEUED {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(event);
+    }
+
+    pub fn drain(&mut self) -> Vec<BenchmarkEvent> {
+        self.queue.drain(..).collect()
+    }
+
+    // 登録時に溜まっていた分もコールバックに渡す
+    pub fn set_callback(&mut self, callback: js_sys::Function) {
+        for event in self.queue.drain(..) {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(event));
+        }
+        self.callback = Some(callback);
+    }
+
+    pub fn clear_callback(&mut self) {
+        self.callback = None;
+    }
+
+    // シーンの切り替えなどで状況が変わったら同じ警告をもう一度出せるようにする
+    pub fn clear_reported(&mut self) {
+        self.reported.clear();
+    }
+}
@@ -0,0 +1,120 @@
+use wasm_bindgen::prelude::*;
+
+use crate::math;
+
+// 中心からの距離に対する力の減り方
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Falloff {
+    // 半径まで一定の割合で弱まる
+    Linear = 0,
+    // 中心付近だけ強く、外側はすぐ弱まる
+    Quadratic = 1,
+    // 釣鐘型（半径の1/3を標準偏差とし、半径の外は0）
+    Gaussian = 2,
+}
+
+// explode() の強さと範囲
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct ExplosionConfig {
+    // この距離(px)より遠いパーティクルは動かさない
+    pub radius: f32,
+    // 中心での速度の増分(px/フレーム)
+    pub force: f32,
+    pub falloff: Falloff,
+}
+
+#[wasm_bindgen]
+impl ExplosionConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new(radius: f32, force: f32, falloff: Falloff) -> ExplosionConfig {
+        ExplosionConfig {
+            radius,
+            force,
+            falloff,
+        }
+    }
+}
+
+impl Default for ExplosionConfig {
+    fn default() -> ExplosionConfig {
+        ExplosionConfig::new(200.0, 8.0, Falloff::Linear)
+    }
+}
+
+impl ExplosionConfig {
+    // 中心から dist 離れたパーティクルに加える力（範囲外は None）
+    pub(crate) fn force_at(&self, dist: f32) -> Option<f32> {
+        if dist >= self.radius {
+            return None;
+        }
+        let t = dist / self.radius;
+        let scale = match self.falloff {
+            Falloff::Linear => 1.0 - t,
+            Falloff::Quadratic => (1.0 - t) * (1.0 - t),
+            Falloff::Gaussian => math::exp(-4.5 * t * t),
+        };
+        Some(self.force * scale)
+    }
+
+    // 同じ範囲・減衰で逆向き（中心へ吸い込む）の力
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn inverted(&self) -> ExplosionConfig {
+        ExplosionConfig {
+            force: -self.force,
+            ..*self
+        }
+    }
+}
+
+// schedule_explosion() で仕掛けた爆発
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Charge {
+    pub x: f32,
+    pub y: f32,
+    // 仕掛けた時点の設定で起爆する
+    pub explosion: ExplosionConfig,
+    frames_left: u32,
+}
+
+// 指定フレーム数の後に起爆する爆発の待ち行列
+#[derive(Default)]
+pub(crate) struct ChargeQueue {
+    pending: Vec<Charge>,
+}
+
+impl ChargeQueue {
+    // delay_frames 回目の更新の最初に起爆する（0 なら呼び出し側ですぐに起爆する）
+    #[cfg(target_arch = "wasm32")]
+    pub fn schedule(&mut self, x: f32, y: f32, delay_frames: u32, explosion: ExplosionConfig) {
+        self.pending.push(Charge {
+            x,
+            y,
+            explosion,
+            frames_left: delay_frames.max(1),
+        });
+    }
+
+    // 1フレーム進め、このフレームで起爆する分を仕掛けた順に返す
+    pub fn tick(&mut self) -> Vec<Charge> {
+        let mut due = Vec::new();
+        self.pending.retain_mut(|charge| {
+            charge.frames_left -= 1;
+            if charge.frames_left == 0 {
+                due.push(*charge);
+            }
+            charge.frames_left > 0
+        });
+        due
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
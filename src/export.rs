@@ -0,0 +1,75 @@
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+use crate::raster::{RasterRun, RasterSurface};
+use crate::rng;
+use crate::scene::SceneKind;
+
+// デモ用アニメーションのシード（同じ引数なら誰がいつ書き出しても同じファイルになる）
+const SEED: u64 = 42;
+// 書き出すコマ数の上限（メモリを使い切らないように）
+const MAX_FRAMES: u32 = 600;
+
+// 固定シードで frames フレーム進めたシーンを every_n フレームごとに描き、APNGにして返す
+// 描画はバックエンドを通さずWASM内で行うので、ブラウザやGPUによらず同じ画像になる
+// コマの間隔は every_n / 60 秒（60fpsで進めた場合と同じ速さで再生される）
+#[wasm_bindgen]
+pub fn export_scene_animation(
+    kind: SceneKind,
+    width: u32,
+    height: u32,
+    particle_count: usize,
+    load: Option<f32>,
+    frames: u32,
+    every_n: u32,
+) -> Result<Vec<u8>, JsValue> {
+    export_animation(kind, width, height, particle_count, load, frames, every_n)
+        .map_err(JsValue::from)
+}
+
+pub(crate) fn export_animation(
+    kind: SceneKind,
+    width: u32,
+    height: u32,
+    particle_count: usize,
+    load: Option<f32>,
+    frames: u32,
+    every_n: u32,
+) -> Result<Vec<u8>, String> {
+    let every_n = every_n.clamp(1, u16::MAX as u32);
+    let count = (frames / every_n).clamp(1, MAX_FRAMES);
+    if width == 0 || height == 0 {
+        return Err(tr(Text::AnimationEmpty, &[&width, &height]));
+    }
+
+    let mut surface = RasterSurface::new(width as f32, height as f32, 1.0);
+    let mut bytes = Vec::new();
+    rng::with_seed(SEED, || -> Result<(), String> {
+        let mut run = RasterRun::new(kind, width as f32, height as f32, particle_count)?;
+        if let Some(load) = load {
+            run.set_load(load.clamp(0.0, 1.0));
+        }
+
+        let encode_error = |error: png::EncodingError| tr(Text::AnimationEncodeFailed, &[&error]);
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(count, 0).map_err(encode_error)?;
+        encoder
+            .set_frame_delay(every_n as u16, 60)
+            .map_err(encode_error)?;
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+
+        let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+        for _ in 0..count {
+            for _ in 0..every_n {
+                run.update();
+            }
+            run.render(&mut surface);
+            surface.to_rgb8(&mut rgb);
+            writer.write_image_data(&rgb).map_err(encode_error)?;
+        }
+        writer.finish().map_err(encode_error)
+    })?;
+    Ok(bytes)
+}
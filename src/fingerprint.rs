@@ -0,0 +1,52 @@
+use std::fmt::Display;
+
+// 実際に効いている設定を "key=value;" の並びに書き出し、その64ビットハッシュを取る
+// 設定の違う結果を誤って比べないよう、計測結果には必ずこの値を添える
+// 同じ設定なら実行・ブラウザ・マシンをまたいで同じ値になるよう、項目の順番は固定で書く
+pub(crate) struct ConfigFingerprint {
+    text: String,
+}
+
+impl ConfigFingerprint {
+    // ビルドの情報から書き始める（版やビルド設定が違えば別の設定とみなす）
+    pub fn new(backend: &str) -> ConfigFingerprint {
+        let mut config = ConfigFingerprint {
+            text: String::new(),
+        };
+        config.field("version", env!("CARGO_PKG_VERSION"));
+        config.field("debug", cfg!(debug_assertions));
+        config.field("deterministic", cfg!(feature = "deterministic"));
+        config.field("backend", backend);
+        config
+    }
+
+    pub fn field(&mut self, key: &str, value: impl Display) {
+        self.text.push_str(&format!("{}={};", key, value));
+    }
+
+    pub fn optional(&mut self, key: &str, value: Option<impl Display>) {
+        match value {
+            Some(value) => self.field(key, value),
+            None => self.field(key, "none"),
+        }
+    }
+
+    // 16桁の16進数（JSの数値で精度を落とさないよう文字列で渡す）
+    pub fn hash(&self) -> String {
+        format!("{:016x}", fnv1a(self.text.as_bytes()))
+    }
+
+    pub fn into_text(self) -> String {
+        self.text
+    }
+}
+
+// FNV-1a 64ビット（標準の Hasher は版によって結果が変わりうるので使わない）
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &byte in bytes {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
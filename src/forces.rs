@@ -0,0 +1,147 @@
+use crate::rng;
+use crate::simulation::Particle;
+
+// 乱流の場が1フレームに流れる量（ノイズの格子1マスを1とした単位）
+const TURBULENCE_DRIFT: f32 = 0.004;
+// 乱流の模様の大きさ(px)の下限
+#[cfg(target_arch = "wasm32")]
+const MIN_TURBULENCE_SCALE: f32 = 1.0;
+
+// 全体に一様に吹く風と、場所ごとに向きの変わる乱流
+// どちらも重力と同じく毎フレーム速度に足す(px/フレーム²)
+#[derive(Clone, Copy, Default, Debug)]
+pub(crate) struct ForceField {
+    wind: (f32, f32),
+    turbulence: Option<Turbulence>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Turbulence {
+    strength: f32,
+    scale: f32,
+}
+
+impl ForceField {
+    // NaN などは 0 にする
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        let finite_or_zero = |value: f32| if value.is_finite() { value } else { 0.0 };
+        self.wind = (finite_or_zero(x), finite_or_zero(y));
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn wind(&self) -> (f32, f32) {
+        self.wind
+    }
+
+    // strength が 0 か NaN などなら止める。scale は渦のおおよその大きさ(px)
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        self.turbulence = (strength.is_finite() && strength != 0.0).then(|| Turbulence {
+            strength,
+            scale: if scale.is_finite() {
+                scale.max(MIN_TURBULENCE_SCALE)
+            } else {
+                MIN_TURBULENCE_SCALE
+            },
+        });
+    }
+
+    // (強さ, 大きさ)（止めていれば None）
+    #[cfg(target_arch = "wasm32")]
+    pub fn turbulence(&self) -> Option<(f32, f32)> {
+        self.turbulence
+            .map(|turbulence| (turbulence.strength, turbulence.scale))
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.wind != (0.0, 0.0) || self.turbulence.is_some()
+    }
+
+    // 設定の指紋用（"0,0"、"1,0/turbulence=0.1x200" など）
+    #[cfg(target_arch = "wasm32")]
+    pub fn describe(&self) -> String {
+        let wind = format!("{},{}", self.wind.0, self.wind.1);
+        match self.turbulence {
+            Some(turbulence) => format!(
+                "{}/turbulence={}x{}",
+                wind, turbulence.strength, turbulence.scale
+            ),
+            None => wind,
+        }
+    }
+
+    // frame 回目のステップの力を1パーティクルに加える
+    pub fn apply(&self, p: &mut Particle, frame: u32) {
+        p.vx += self.wind.0;
+        p.vy += self.wind.1;
+        if let Some(turbulence) = self.turbulence {
+            let drift = frame as f32 * TURBULENCE_DRIFT;
+            let (cx, cy) = curl(p.x / turbulence.scale + drift, p.y / turbulence.scale);
+            p.vx += cx * turbulence.strength;
+            p.vy += cy * turbulence.strength;
+        }
+    }
+}
+
+// ノイズ ψ の回転 (∂ψ/∂y, -∂ψ/∂x)。発散がないので、パーティクルが1か所に溜まらず渦を巻いて流れる
+fn curl(x: f32, y: f32) -> (f32, f32) {
+    let (dx, dy) = noise_gradient(x, y);
+    (dy, -dx)
+}
+
+// 2次元の勾配ノイズ（Perlin）の偏微分 (∂n/∂x, ∂n/∂y) を解析的に求める
+// 差分で求めるより評価が1回で済み、格子の境目でも滑らか
+fn noise_gradient(x: f32, y: f32) -> (f32, f32) {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+
+    let ga = gradient(ix, iy);
+    let gb = gradient(ix + 1, iy);
+    let gc = gradient(ix, iy + 1);
+    let gd = gradient(ix + 1, iy + 1);
+    let a = ga.0 * fx + ga.1 * fy;
+    let b = gb.0 * (fx - 1.0) + gb.1 * fy;
+    let c = gc.0 * fx + gc.1 * (fy - 1.0);
+    let d = gd.0 * (fx - 1.0) + gd.1 * (fy - 1.0);
+
+    let (u, du) = fade(fx);
+    let (v, dv) = fade(fy);
+    let k = a - b - c + d;
+    let dx = ga.0
+        + u * (gb.0 - ga.0)
+        + v * (gc.0 - ga.0)
+        + u * v * (ga.0 - gb.0 - gc.0 + gd.0)
+        + du * (b - a + v * k);
+    let dy = ga.1
+        + u * (gb.1 - ga.1)
+        + v * (gc.1 - ga.1)
+        + u * v * (ga.1 - gb.1 - gc.1 + gd.1)
+        + dv * (c - a + u * k);
+    (dx, dy)
+}
+
+// 5次の補間の重みとその微分
+fn fade(t: f32) -> (f32, f32) {
+    let weight = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let slope = 30.0 * t * t * (t - 1.0) * (t - 1.0);
+    (weight, slope)
+}
+
+// 格子点ごとに決まった勾配（8方向から選ぶ）
+fn gradient(ix: i32, iy: i32) -> (f32, f32) {
+    const DIAGONAL: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    const DIRECTIONS: [(f32, f32); 8] = [
+        (1.0, 0.0),
+        (-1.0, 0.0),
+        (0.0, 1.0),
+        (0.0, -1.0),
+        (DIAGONAL, DIAGONAL),
+        (-DIAGONAL, DIAGONAL),
+        (DIAGONAL, -DIAGONAL),
+        (-DIAGONAL, -DIAGONAL),
+    ];
+    let hash = rng::hash((ix as u32).wrapping_mul(0x9e37_79b9) ^ iy as u32);
+    DIRECTIONS[(hash & 7) as usize]
+}
@@ -0,0 +1,99 @@
+use std::collections::VecDeque;
+
+use crate::stats::{self, Aggregation};
+
+// リフレッシュレートの推定に使う直近の間隔の数と、推定を始める最少の数
+const REFRESH_WINDOW: usize = 120;
+const MIN_REFRESH_SAMPLES: usize = 10;
+// これより長い間隔はタブが裏に回っていたなどとみなして推定に使わない(ms)
+const MAX_TICK_INTERVAL_MS: f64 = 250.0;
+// よくあるリフレッシュレート(Hz)（推定値がこの相対誤差以内ならその値に丸める）
+const COMMON_RATES: [f64; 10] = [
+    30.0, 50.0, 60.0, 75.0, 90.0, 100.0, 120.0, 144.0, 165.0, 240.0,
+];
+const SNAP_TOLERANCE: f64 = 0.05;
+// 上限にできるフレームレートの範囲
+const MIN_TARGET_FPS: f64 = 1.0;
+const MAX_TARGET_FPS: f64 = 1_000.0;
+
+// tick() に渡された requestAnimationFrame の時刻から表示のリフレッシュレートを推定し、
+// 描画を target_fps 以下に間引く（120Hz や 144Hz の表示でも 60Hz の端末と同じ回数だけ描いて比べる用）
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FrameCap {
+    // None なら間引かない
+    target_fps: Option<f64>,
+    last_tick: Option<f64>,
+    intervals: VecDeque<f64>,
+    // 次に描く予定の時刻
+    next_due: f64,
+    pub rendered: u32,
+    pub skipped: u32,
+}
+
+impl FrameCap {
+    pub fn target_fps(&self) -> f64 {
+        self.target_fps.unwrap_or(0.0)
+    }
+
+    // 0 以下か NaN なら上限なし。範囲内に丸めて実際に使う値を返し、描いた数と飛ばした数を数え直す
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.target_fps = (fps > 0.0).then(|| fps.clamp(MIN_TARGET_FPS, MAX_TARGET_FPS));
+        self.next_due = 0.0;
+        self.rendered = 0;
+        self.skipped = 0;
+        self.target_fps()
+    }
+
+    // timestamp(ms) の回で描くなら true
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        if let Some(last) = self.last_tick {
+            let interval = timestamp - last;
+            if interval > 0.0 && interval < MAX_TICK_INTERVAL_MS {
+                if self.intervals.len() == REFRESH_WINDOW {
+                    self.intervals.pop_front();
+                }
+                self.intervals.push_back(interval);
+            }
+        }
+        self.last_tick = Some(timestamp);
+
+        let render = match self.target_fps {
+            None => true,
+            Some(fps) => {
+                let period = 1_000.0 / fps;
+                // 表示の1回分の半分までは早くても描く（揺れで1回おきより多く飛ばさないように）
+                let slack = self.intervals.back().copied().unwrap_or(0.0) / 2.0;
+                if timestamp + slack >= self.next_due {
+                    // 大きく遅れたら（最初の回や裏に回っていた後）そこから数え直す
+                    self.next_due = if timestamp - self.next_due > period {
+                        timestamp + period
+                    } else {
+                        self.next_due + period
+                    };
+                    true
+                } else {
+                    false
+                }
+            }
+        };
+        if render {
+            self.rendered += 1;
+        } else {
+            self.skipped += 1;
+        }
+        render
+    }
+
+    // 推定したリフレッシュレート(Hz)（まだ分からなければ 0）
+    pub fn refresh_rate(&self) -> f64 {
+        if self.intervals.len() < MIN_REFRESH_SAMPLES {
+            return 0.0;
+        }
+        let samples: Vec<f64> = self.intervals.iter().copied().collect();
+        let hz = 1_000.0 / stats::summarize(&samples, Aggregation::Median).center;
+        COMMON_RATES
+            .into_iter()
+            .find(|rate| (hz - rate).abs() <= rate * SNAP_TOLERANCE)
+            .unwrap_or(hz)
+    }
+}
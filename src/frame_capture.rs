@@ -0,0 +1,71 @@
+use wasm_bindgen::prelude::*;
+use web_sys::WebGlRenderingContext;
+
+use crate::i18n::{tr, Text};
+use crate::shader;
+
+// compare_frames() の結果
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameDiff {
+    pub pixels: u32,
+    // 色が1チャンネルでも違ったピクセルの数
+    pub differing_pixels: u32,
+    // チャンネルごとの差の最大（0〜255）
+    pub max_difference: u32,
+    // チャンネルごとの差の絶対値の平均（0〜255）
+    pub mean_difference: f64,
+    // ピーク信号対雑音比(dB)。まったく同じなら Infinity
+    pub psnr: f64,
+}
+
+// capture_frame() で読み戻した2枚の RGBA を比べる
+// 同じシード・同じフレーム数で描いたバックエンド同士の見た目が揃っているかをテストで確かめる用
+// 丸めやアンチエイリアスの差があるので、一致ではなく differing_pixels や psnr のしきい値で判定する
+#[wasm_bindgen]
+pub fn compare_frames(a: &[u8], b: &[u8]) -> Result<FrameDiff, JsValue> {
+    if a.len() != b.len() || !a.len().is_multiple_of(4) {
+        return Err(tr(Text::FrameSizeMismatch, &[&a.len(), &b.len()]).into());
+    }
+    let mut differing_pixels = 0u32;
+    let mut max_difference = 0u32;
+    let mut total = 0u64;
+    let mut squared = 0u64;
+    for (pa, pb) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+        if pa != pb {
+            differing_pixels += 1;
+        }
+        for (&ca, &cb) in pa.iter().zip(pb) {
+            let diff = ca.abs_diff(cb) as u32;
+            max_difference = max_difference.max(diff);
+            total += diff as u64;
+            squared += (diff * diff) as u64;
+        }
+    }
+    let samples = a.len().max(1) as f64;
+    let mse = squared as f64 / samples;
+    Ok(FrameDiff {
+        pixels: (a.len() / 4) as u32,
+        differing_pixels,
+        max_difference,
+        mean_difference: total as f64 / samples,
+        psnr: if mse == 0.0 {
+            f64::INFINITY
+        } else {
+            10.0 * (255.0 * 255.0 / mse).log10()
+        },
+    })
+}
+
+// WebGL の描画バッファを上の行から並べた RGBA で読み戻す（readPixels は左下原点なので上下を入れ替える）
+// ブラウザが画面に出した後は中身が消えていることがあるので、render() と同じタスクの中で呼ぶ
+pub(crate) fn read_gl_frame(gl: &WebGlRenderingContext) -> Result<Vec<u8>, JsValue> {
+    let pixels = shader::read_drawing_buffer(gl)?;
+    let stride = gl.drawing_buffer_width().max(1) as usize * 4;
+    Ok(pixels
+        .chunks_exact(stride)
+        .rev()
+        .flatten()
+        .copied()
+        .collect())
+}
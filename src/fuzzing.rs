@@ -0,0 +1,95 @@
+// cargo fuzz（fuzz/）から呼ぶ入口。信頼できないページから渡されるバイト列を読む処理を、
+// wasm を作らずにネイティブで panic しないか確かめる（cfg(fuzzing) のビルドでだけ公開する）
+//
+// JSON を読むもの（migrate_report()・Workload::from_json()）は、JsValue を使わない中身の関数を呼ぶ
+
+use crate::binary_report;
+use crate::json;
+use crate::report;
+use crate::rng;
+use crate::simulation::Simulation;
+use crate::snapshot;
+use crate::workload::Workload;
+
+// 読み込み先のシミュレーションの大きさとパーティクル数
+const WIDTH: f32 = 320.0;
+const HEIGHT: f32 = 240.0;
+const PARTICLES: usize = 64;
+// 読み込んだ後に進めるステップ数（読めた値で物理演算が壊れないかも見る）
+const STEPS: u32 = 4;
+
+// restore() に渡されたバイト列。読めたら戻して数ステップ進め、書き出した形式が読み直せることも確かめる
+pub fn restore_snapshot(bytes: &[u8]) {
+    let Ok(decoded) = snapshot::decode(bytes) else {
+        return;
+    };
+    let mut sim = simulation();
+    if sim.restore(decoded).is_err() {
+        return;
+    }
+    for _ in 0..STEPS {
+        sim.step_with(|_| {});
+    }
+    let written = sim.snapshot();
+    assert!(snapshot::decode(&written).is_ok());
+}
+
+// GPU の物理演算から読み戻した (x, y, vx, vy) と基準の色相。先頭の1バイトで数をずらし、合わない数も試す
+pub fn load_state(bytes: &[u8]) {
+    let Some((&skew, rest)) = bytes.split_first() else {
+        return;
+    };
+    let values: Vec<f32> = rest
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    let mut sim = simulation();
+    let split = (PARTICLES * 4 + skew as usize % 8).min(values.len());
+    let (state, base_hue) = values.split_at(split);
+    sim.load_state(state, base_hue);
+    for _ in 0..STEPS {
+        sim.step_with(|_| {});
+    }
+}
+
+// SuiteReport::from_binary() に渡されたバイト列。読めたら書き出し直して同じバイト列になることも確かめる
+pub fn decode_binary_report(bytes: &[u8]) {
+    let Ok(report) = binary_report::decode(bytes) else {
+        return;
+    };
+    let written = binary_report::encode(&report);
+    let reread = binary_report::decode(&written).expect("encoded report must decode");
+    assert_eq!(binary_report::encode(&reread), written);
+}
+
+// load_scenario()（Workload.from_json()）に渡された記録の設定。読めたら書き出した JSON も読めることを確かめる
+pub fn config_json(bytes: &[u8]) {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return;
+    };
+    let Ok(workload) = Workload::parse(text) else {
+        return;
+    };
+    assert!(json::parse(&workload.to_json()).is_some());
+}
+
+// migrate_report() に渡された古い結果。変換できたら、変換した結果をもう一度通しても変わらないことも確かめる
+pub fn migrate_report(bytes: &[u8]) {
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return;
+    };
+    let Ok(migrated) = report::migrate(text) else {
+        return;
+    };
+    assert_eq!(report::migrate(&migrated).as_deref(), Ok(migrated.as_str()));
+}
+
+// 同じ入力で同じ結果になるよう、パーティクルは固定シードで生成する
+fn simulation() -> Simulation {
+    rng::with_seed(0, || {
+        let mut sim =
+            Simulation::with_capacity(WIDTH, HEIGHT, PARTICLES).expect("small simulation");
+        sim.spawn_pending(PARTICLES);
+        sim
+    })
+}
@@ -0,0 +1,39 @@
+use web_sys::WebGlRenderingContext;
+
+// 同じコンテキストを使う他のインスタンスやホストのページが変えたままにした状態を、描く前に既定に戻す
+// （有効なままの頂点属性・ブレンド・深度・ステンシル・フレームバッファ・色の書き込みマスク）
+// こちらが使う状態は描くたびに設定し直すので、前のフレームの状態が残っていることは当てにしない
+// WebGL2 のコンテキストも WebGlRenderingContext として渡せば使える
+pub(crate) struct GlState {
+    // 毎フレーム問い合わせると同期待ちになるので最初に一度だけ読む
+    max_vertex_attribs: u32,
+}
+
+impl GlState {
+    pub fn new(gl: &WebGlRenderingContext) -> GlState {
+        let max_vertex_attribs = gl
+            .get_parameter(WebGlRenderingContext::MAX_VERTEX_ATTRIBS)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0) as u32;
+        GlState { max_vertex_attribs }
+    }
+
+    pub fn reset(&self, gl: &WebGlRenderingContext) {
+        // 他のプログラムが有効にした属性が残っていると、バッファのない属性を読もうとして描けない
+        for index in 0..self.max_vertex_attribs {
+            gl.disable_vertex_attrib_array(index);
+        }
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+        for capability in [
+            WebGlRenderingContext::BLEND,
+            WebGlRenderingContext::DEPTH_TEST,
+            WebGlRenderingContext::STENCIL_TEST,
+            WebGlRenderingContext::CULL_FACE,
+        ] {
+            gl.disable(capability);
+        }
+        gl.color_mask(true, true, true, true);
+        gl.active_texture(WebGlRenderingContext::TEXTURE0);
+    }
+}
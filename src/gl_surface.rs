@@ -0,0 +1,988 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{AngleInstancedArrays, WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture};
+
+use crate::events::{EventBus, EventKind};
+use crate::i18n::{tr, Text};
+use crate::image_source::DecodedImage;
+use crate::particle_shape::{white_sprite, SPRITE_SIZE};
+use crate::quirks;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::scene::{Atlas, ColorPoint, ColorRect, CubicBezier, Glow, Polygon, Sprite, Surface};
+use crate::tessellation;
+use crate::viewport::Viewport;
+use crate::shader;
+
+// シーン描画用のGLリソース（テクスチャ付き全画面矩形など）
+// 最初にシーンを切り替えたときに作り、以後のシーン切り替えでは使い回す
+pub(crate) struct SceneGl {
+    texture_program: WebGlProgram,
+    quad_buffer: WebGlBuffer,
+    texture: WebGlTexture,
+    // 頂点色付きの図形（矩形・線など）用
+    color_program: WebGlProgram,
+    position_buffer: WebGlBuffer,
+    color_buffer: WebGlBuffer,
+    // 毎フレーム詰め直す頂点データ（ピクセル座標と RGB）
+    positions: Vec<f32>,
+    colors: Vec<f32>,
+    // 点の大きさを指定して POINTS で描く用と、描ける一番大きい点(px)
+    point_program: WebGlProgram,
+    max_point_size: f32,
+    // スプライト用（ANGLE_instanced_arrays があればインスタンス描画）
+    sprite_program: WebGlProgram,
+    corner_buffer: WebGlBuffer,
+    instance_buffer: WebGlBuffer,
+    instances: Vec<f32>,
+    instancing: Option<AngleInstancedArrays>,
+    atlas_texture: WebGlTexture,
+    // 転送済みのアトラスのバージョン
+    atlas_version: Option<u32>,
+    // ステンシルバッファがあるか（コンテキストの属性で決まるので最初に一度だけ調べる）
+    stencil: bool,
+    // ブレンド式の MIN / MAX を使えるか（WebGL1 は EXT_blend_minmax、WebGL2 は標準）
+    blend_minmax: bool,
+}
+
+impl SceneGl {
+    pub fn new(gl: &WebGlRenderingContext) -> Result<SceneGl, JsValue> {
+        let (texture_program, _) =
+            shader::get_or_create_program(gl, TEXTURE_VERTEX_SHADER, TEXTURE_FRAGMENT_SHADER)?;
+
+        // 画面全体を覆う2枚の三角形
+        let quad_buffer = gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&quad_buffer));
+        let quad: [f32; 12] = [
+            -1.0, -1.0, 1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, 1.0, 1.0,
+        ];
+        unsafe {
+            let quad_array = js_sys::Float32Array::view(&quad);
+            gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &quad_array,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+
+        let texture = create_texture(gl)?;
+
+        let (color_program, _) =
+            shader::get_or_create_program(gl, COLOR_VERTEX_SHADER, COLOR_FRAGMENT_SHADER)?;
+        let position_buffer = gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        let color_buffer = gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        let (point_program, _) =
+            shader::get_or_create_program(gl, POINT_VERTEX_SHADER, COLOR_FRAGMENT_SHADER)?;
+        // 毎フレーム問い合わせると同期待ちになるので最初に一度だけ読む
+        let max_point_size = quirks::max_point_size(gl).unwrap_or(1.0);
+
+        let (sprite_program, _) =
+            shader::get_or_create_program(gl, SPRITE_VERTEX_SHADER, TEXTURE_FRAGMENT_SHADER)?;
+        // 単位正方形の6頂点（全インスタンスで共有）
+        let corner_buffer = gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&corner_buffer));
+        unsafe {
+            let corner_array = js_sys::Float32Array::view(&SPRITE_CORNERS);
+            gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &corner_array,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+        let instance_buffer = gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        let instancing = gl
+            .get_extension("ANGLE_instanced_arrays")
+            .ok()
+            .flatten()
+            .map(|ext| ext.unchecked_into::<AngleInstancedArrays>());
+        let atlas_texture = create_texture(gl)?;
+        let stencil = gl
+            .get_parameter(WebGlRenderingContext::STENCIL_BITS)
+            .ok()
+            .and_then(|bits| bits.as_f64())
+            .is_some_and(|bits| bits > 0.0);
+        let blend_minmax = gl.is_instance_of::<WebGl2RenderingContext>()
+            || gl.get_extension("EXT_blend_minmax").ok().flatten().is_some();
+
+        Ok(SceneGl {
+            texture_program,
+            quad_buffer,
+            texture,
+            color_program,
+            position_buffer,
+            color_buffer,
+            positions: Vec::new(),
+            colors: Vec::new(),
+            point_program,
+            max_point_size,
+            sprite_program,
+            corner_buffer,
+            instance_buffer,
+            instances: Vec::new(),
+            instancing,
+            atlas_texture,
+            atlas_version: None,
+            stencil,
+            blend_minmax,
+        })
+    }
+
+    pub fn has_instancing(&self) -> bool {
+        self.instancing.is_some()
+    }
+
+    // プログラムはキャッシュのものなので消さない
+    pub fn delete(&self, gl: &WebGlRenderingContext) {
+        for buffer in [
+            &self.quad_buffer,
+            &self.position_buffer,
+            &self.color_buffer,
+            &self.corner_buffer,
+            &self.instance_buffer,
+        ] {
+            gl.delete_buffer(Some(buffer));
+        }
+        gl.delete_texture(Some(&self.texture));
+        gl.delete_texture(Some(&self.atlas_texture));
+    }
+}
+
+// VIEWPORT や SCISSOR_BOX のような4つの整数のパラメーター
+fn gl_rect(gl: &WebGlRenderingContext, parameter: u32) -> Option<[i32; 4]> {
+    let value = gl.get_parameter(parameter).ok()?;
+    let array = value.dyn_into::<js_sys::Int32Array>().ok()?;
+    (array.length() == 4).then(|| [0, 1, 2, 3].map(|i| array.get_index(i)))
+}
+
+// 2の累乗でないサイズを使うのでミップマップなし・端はクランプ
+pub(crate) fn create_texture(gl: &WebGlRenderingContext) -> Result<WebGlTexture, JsValue> {
+    let texture = gl.create_texture().ok_or_else(|| tr(Text::TextureCreationFailed, &[]))?;
+    gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&texture));
+    for (param, value) in [
+        (
+            WebGlRenderingContext::TEXTURE_MIN_FILTER,
+            WebGlRenderingContext::NEAREST,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_MAG_FILTER,
+            WebGlRenderingContext::NEAREST,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_WRAP_S,
+            WebGlRenderingContext::CLAMP_TO_EDGE,
+        ),
+        (
+            WebGlRenderingContext::TEXTURE_WRAP_T,
+            WebGlRenderingContext::CLAMP_TO_EDGE,
+        ),
+    ] {
+        gl.tex_parameteri(WebGlRenderingContext::TEXTURE_2D, param, value as i32);
+    }
+    Ok(texture)
+}
+
+// バインド中のテクスチャにアトラスを転送する（画像があればピクセル列の代わりにそれを渡す）
+fn upload_atlas(gl: &WebGlRenderingContext, atlas: &Atlas) {
+    let (target, rgba, pixel_type) = (
+        WebGlRenderingContext::TEXTURE_2D,
+        WebGlRenderingContext::RGBA,
+        WebGlRenderingContext::UNSIGNED_BYTE,
+    );
+    let _ = match &atlas.image {
+        Some(DecodedImage::Bitmap(bitmap)) => gl
+            .tex_image_2d_with_u32_and_u32_and_image_bitmap(target, 0, rgba as i32, rgba, pixel_type, bitmap),
+        Some(DecodedImage::Element(element)) => gl
+            .tex_image_2d_with_u32_and_u32_and_image(target, 0, rgba as i32, rgba, pixel_type, element),
+        None => gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            target,
+            0,
+            rgba as i32,
+            atlas.width as i32,
+            atlas.height as i32,
+            0,
+            rgba,
+            pixel_type,
+            Some(&atlas.pixels),
+        ),
+    };
+}
+
+// 矩形を2つの三角形の頂点にして詰める（色は color で直す）
+fn pack_rects(res: &mut SceneGl, rects: &[ColorRect], color: impl Fn([f32; 3]) -> [f32; 3]) {
+    res.positions.clear();
+    res.colors.clear();
+    for r in rects {
+        let (x0, y0, x1, y1) = (r.x, r.y, r.x + r.width, r.y + r.height);
+        res.positions
+            .extend_from_slice(&[x0, y0, x1, y0, x0, y1, x0, y1, x1, y0, x1, y1]);
+        let rgb = color(r.rgb);
+        for _ in 0..6 {
+            res.colors.extend_from_slice(&rgb);
+        }
+    }
+}
+
+// パーティクルの光の玉のスプライト（点の大きさに合わせて伸び縮みするので線形補間にする）
+pub(crate) fn create_sprite_texture(gl: &WebGlRenderingContext) -> Result<WebGlTexture, JsValue> {
+    let texture = create_linear_texture(gl)?;
+    gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+        WebGlRenderingContext::TEXTURE_2D,
+        0,
+        WebGlRenderingContext::RGBA as i32,
+        SPRITE_SIZE as i32,
+        SPRITE_SIZE as i32,
+        0,
+        WebGlRenderingContext::RGBA,
+        WebGlRenderingContext::UNSIGNED_BYTE,
+        Some(&white_sprite()),
+    )?;
+    Ok(texture)
+}
+
+// set_particle_texture() の画像のスプライト（縮めずにそのままの大きさで転送する）
+// ミップマップを作らず端を伸ばすので、WebGL1 でも2の累乗でない大きさの画像を使える
+pub(crate) fn create_image_texture(gl: &WebGlRenderingContext, image: &DecodedImage) -> Result<WebGlTexture, JsValue> {
+    let texture = create_linear_texture(gl)?;
+    let (target, rgba, pixel_type) = (
+        WebGlRenderingContext::TEXTURE_2D,
+        WebGlRenderingContext::RGBA,
+        WebGlRenderingContext::UNSIGNED_BYTE,
+    );
+    match image {
+        DecodedImage::Bitmap(bitmap) => {
+            gl.tex_image_2d_with_u32_and_u32_and_image_bitmap(target, 0, rgba as i32, rgba, pixel_type, bitmap)?
+        }
+        DecodedImage::Element(element) => {
+            gl.tex_image_2d_with_u32_and_u32_and_image(target, 0, rgba as i32, rgba, pixel_type, element)?
+        }
+    }
+    Ok(texture)
+}
+
+fn create_linear_texture(gl: &WebGlRenderingContext) -> Result<WebGlTexture, JsValue> {
+    let texture = create_texture(gl)?;
+    for param in [
+        WebGlRenderingContext::TEXTURE_MIN_FILTER,
+        WebGlRenderingContext::TEXTURE_MAG_FILTER,
+    ] {
+        gl.tex_parameteri(
+            WebGlRenderingContext::TEXTURE_2D,
+            param,
+            WebGlRenderingContext::LINEAR as i32,
+        );
+    }
+    Ok(texture)
+}
+
+// WebGLバックエンドの Surface 実装
+pub(crate) struct GlSurface<'a> {
+    pub gl: &'a WebGlRenderingContext,
+    pub width: f32,
+    pub height: f32,
+    pub resources: &'a mut SceneGl,
+    pub events: &'a mut EventBus,
+    // push_viewport() の前のビューポートと、有効だったシザー矩形（pop_viewport() で戻す）
+    pub saved_viewport: Option<([i32; 4], Option<[i32; 4]>)>,
+}
+
+impl Surface for GlSurface<'_> {
+    fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    fn report(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    fn clear(&mut self, rgb: [f32; 3]) {
+        self.gl.clear_color(rgb[0], rgb[1], rgb[2], 1.0);
+        self.gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+    }
+
+    // readPixels は左下原点なので、読んだ後に行の順を上下反転する
+    fn read_rgba(&mut self, x: u32, y: u32, width: u32, height: u32, out: &mut Vec<u8>) -> bool {
+        let buffer_width = self.gl.drawing_buffer_width().max(0) as u32;
+        let buffer_height = self.gl.drawing_buffer_height().max(0) as u32;
+        let (x, y) = (x.min(buffer_width), y.min(buffer_height));
+        let (width, height) = (width.min(buffer_width - x), height.min(buffer_height - y));
+        out.clear();
+        out.resize(width as usize * height as usize * 4, 0);
+        if out.is_empty() {
+            return false;
+        }
+        let read = self.gl.read_pixels_with_opt_u8_array(
+            x as i32,
+            (buffer_height - y - height) as i32,
+            width as i32,
+            height as i32,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(out.as_mut_slice()),
+        );
+        let stride = width as usize * 4;
+        for row in 0..height as usize / 2 {
+            let (top, bottom) = out.split_at_mut((height as usize - 1 - row) * stride);
+            top[row * stride..(row + 1) * stride].swap_with_slice(&mut bottom[..stride]);
+        }
+        read.is_ok()
+    }
+
+    // RGBAのピクセル列をテクスチャに転送して画面全体に引き伸ばして描く
+    fn blit_rgba(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        self.draw_texture(&self.resources.texture_program, width, height, pixels, &[]);
+    }
+
+    // 矩形ごとに2枚の三角形に展開して1回の draw call で描く
+    fn fill_rects(&mut self, rects: &[ColorRect]) {
+        pack_rects(self.resources, rects, |rgb| rgb);
+        self.draw_colored(&self.resources.color_program, WebGlRenderingContext::TRIANGLES, &[]);
+    }
+
+    // 不透明度はブレンドの定数か頂点の色に入れ、合成方法ごとのブレンド式で重ねる
+    fn fill_rects_composited(&mut self, rects: &[ColorRect], alpha: f32, mode: CompositeMode) -> bool {
+        let Some(blend) = mode.gl_blend() else {
+            return false;
+        };
+        if blend.needs_minmax() && !self.resources.blend_minmax {
+            return false;
+        }
+        pack_rects(self.resources, rects, |rgb| blend.color(rgb, alpha));
+        let gl = self.gl;
+        gl.enable(WebGlRenderingContext::BLEND);
+        gl.blend_color(0.0, 0.0, 0.0, alpha);
+        gl.blend_equation(blend.equation);
+        gl.blend_func(blend.src, blend.dst);
+        self.draw_colored(&self.resources.color_program, WebGlRenderingContext::TRIANGLES, &[]);
+        gl.blend_equation(WebGlRenderingContext::FUNC_ADD);
+        gl.disable(WebGlRenderingContext::BLEND);
+        true
+    }
+
+    // インスタンスごとに変換と切り出し範囲だけを送り、1回の draw call で描く
+    // 拡張がない環境では同じデータを6頂点ぶん複製して通常の描画にする
+    fn draw_sprites(&mut self, atlas: &Atlas, sprites: &[Sprite]) {
+        if sprites.is_empty() {
+            return;
+        }
+        let gl = self.gl;
+        let res = &mut *self.resources;
+
+        gl.use_program(Some(&res.sprite_program));
+        gl.active_texture(WebGlRenderingContext::TEXTURE0);
+        gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&res.atlas_texture));
+        if res.atlas_version != Some(atlas.version) {
+            upload_atlas(gl, atlas);
+            res.atlas_version = Some(atlas.version);
+        }
+
+        let repeat = if res.instancing.is_some() { 1 } else { 6 };
+        res.instances.clear();
+        res.positions.clear();
+        for sprite in sprites {
+            for _ in 0..repeat {
+                res.instances.extend_from_slice(&sprite.transform);
+                res.instances.extend_from_slice(&sprite.source);
+            }
+            if res.instancing.is_none() {
+                res.positions.extend_from_slice(&SPRITE_CORNERS);
+            }
+        }
+
+        // 頂点ごとの角の座標
+        let corner_attrib = gl.get_attrib_location(&res.sprite_program, "a_corner") as u32;
+        if res.instancing.is_some() {
+            gl.bind_buffer(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                Some(&res.corner_buffer),
+            );
+        } else {
+            gl.bind_buffer(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                Some(&res.position_buffer),
+            );
+            upload_dynamic(gl, &res.positions);
+        }
+        gl.vertex_attrib_pointer_with_i32(
+            corner_attrib,
+            2,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+        gl.enable_vertex_attrib_array(corner_attrib);
+
+        // インスタンスごとのデータ（変換6 + 切り出し範囲4）
+        gl.bind_buffer(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            Some(&res.instance_buffer),
+        );
+        upload_dynamic(gl, &res.instances);
+        let stride = (SPRITE_INSTANCE_FLOATS * 4) as i32;
+        let instance_attribs = [
+            ("a_linear", 4, 0),
+            ("a_translate", 2, 16),
+            ("a_source", 4, 24),
+        ]
+        .map(|(name, size, offset)| {
+            let attrib = gl.get_attrib_location(&res.sprite_program, name) as u32;
+            gl.vertex_attrib_pointer_with_i32(
+                attrib,
+                size,
+                WebGlRenderingContext::FLOAT,
+                false,
+                stride,
+                offset,
+            );
+            gl.enable_vertex_attrib_array(attrib);
+            attrib
+        });
+
+        gl.uniform2f(
+            gl.get_uniform_location(&res.sprite_program, "u_resolution")
+                .as_ref(),
+            self.width,
+            self.height,
+        );
+        gl.uniform2f(
+            gl.get_uniform_location(&res.sprite_program, "u_atlas_size")
+                .as_ref(),
+            atlas.width as f32,
+            atlas.height as f32,
+        );
+        gl.uniform1i(
+            gl.get_uniform_location(&res.sprite_program, "u_texture")
+                .as_ref(),
+            0,
+        );
+
+        gl.enable(WebGlRenderingContext::BLEND);
+        gl.blend_func(
+            WebGlRenderingContext::SRC_ALPHA,
+            WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+        );
+        match &res.instancing {
+            Some(ext) => {
+                for attrib in instance_attribs {
+                    ext.vertex_attrib_divisor_angle(attrib, 1);
+                }
+                ext.draw_arrays_instanced_angle(
+                    WebGlRenderingContext::TRIANGLES,
+                    0,
+                    6,
+                    sprites.len() as i32,
+                );
+                // 除数は他のプログラムの属性にも残るので戻しておく
+                for attrib in instance_attribs {
+                    ext.vertex_attrib_divisor_angle(attrib, 0);
+                }
+            }
+            None => gl.draw_arrays(
+                WebGlRenderingContext::TRIANGLES,
+                0,
+                (sprites.len() * 6) as i32,
+            ),
+        }
+        gl.disable(WebGlRenderingContext::BLEND);
+        for attrib in instance_attribs {
+            gl.disable_vertex_attrib_array(attrib);
+        }
+    }
+
+    // WASMで折れ線に分解して LINES でまとめて描く
+    fn stroke_beziers(&mut self, curves: &[CubicBezier]) {
+        let res = &mut *self.resources;
+        res.positions.clear();
+        res.colors.clear();
+        let mut polyline = Vec::new();
+        for curve in curves {
+            polyline.clear();
+            polyline.push(curve.points[0]);
+            tessellation::flatten_cubic(&curve.points, &mut polyline);
+            for segment in polyline.windows(2) {
+                res.positions.extend_from_slice(&[
+                    segment[0].0,
+                    segment[0].1,
+                    segment[1].0,
+                    segment[1].1,
+                ]);
+                res.colors.extend_from_slice(&curve.rgb);
+                res.colors.extend_from_slice(&curve.rgb);
+            }
+        }
+        self.draw_colored(&self.resources.color_program, WebGlRenderingContext::LINES, &[]);
+    }
+
+    // WASMで三角形に分割して TRIANGLES でまとめて描く
+    fn fill_polygons(&mut self, polygons: &[Polygon]) {
+        let res = &mut *self.resources;
+        res.positions.clear();
+        res.colors.clear();
+        let mut indices = Vec::new();
+        for polygon in polygons {
+            indices.clear();
+            tessellation::earcut(&polygon.points, &mut indices);
+            for &i in &indices {
+                let p = polygon.points[i as usize];
+                res.positions.extend_from_slice(&[p.0, p.1]);
+                res.colors.extend_from_slice(&polygon.rgb);
+            }
+        }
+        self.draw_colored(&self.resources.color_program, WebGlRenderingContext::TRIANGLES, &[]);
+    }
+
+    // 点ごとに1頂点だけ送り、POINTS の gl_PointSize で広げる（頂点の数は大きさによらない）
+    fn draw_points(&mut self, points: &[ColorPoint], size: f32, mode: RenderMode) {
+        let res = &mut *self.resources;
+        res.positions.clear();
+        res.colors.clear();
+        for p in points {
+            res.positions.extend_from_slice(&[p.x, p.y]);
+            res.colors.extend_from_slice(&p.rgb);
+        }
+        let size = size.min(res.max_point_size);
+        mode.apply_gl(self.gl);
+        self.draw_colored(
+            &self.resources.point_program,
+            WebGlRenderingContext::POINTS,
+            &[("u_point_size", size)],
+        );
+        self.gl.disable(WebGlRenderingContext::BLEND);
+    }
+
+    fn max_point_size(&self) -> f32 {
+        self.resources.max_point_size
+    }
+
+    // 領域の多角形を色を書かずにステンシルへ 1 で書き、以後は 1 のところだけ描く
+    fn push_clip(&mut self, regions: &[Polygon]) -> bool {
+        if !self.resources.stencil {
+            return false;
+        }
+        let gl = self.gl;
+        gl.enable(WebGlRenderingContext::STENCIL_TEST);
+        gl.stencil_mask(0xff);
+        gl.clear_stencil(0);
+        gl.clear(WebGlRenderingContext::STENCIL_BUFFER_BIT);
+        gl.stencil_func(WebGlRenderingContext::ALWAYS, 1, 0xff);
+        gl.stencil_op(
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::REPLACE,
+        );
+        gl.color_mask(false, false, false, false);
+        self.fill_polygons(regions);
+        gl.color_mask(true, true, true, true);
+        gl.stencil_func(WebGlRenderingContext::EQUAL, 1, 0xff);
+        gl.stencil_op(
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::KEEP,
+            WebGlRenderingContext::KEEP,
+        );
+        true
+    }
+
+    fn pop_clip(&mut self) {
+        self.gl.disable(WebGlRenderingContext::STENCIL_TEST);
+    }
+
+    // 今のビューポート（分割表示ならその領域）の中での region にビューポートとシザーを合わせる
+    // 描画バッファはキャンバスより大きいことがあるので、今のビューポートとの比で換算する
+    fn push_viewport(&mut self, region: Viewport) -> bool {
+        let gl = self.gl;
+        let Some(current) = gl_rect(gl, WebGlRenderingContext::VIEWPORT) else {
+            return false;
+        };
+        let scissor = if gl.is_enabled(WebGlRenderingContext::SCISSOR_TEST) {
+            gl_rect(gl, WebGlRenderingContext::SCISSOR_BOX)
+        } else {
+            None
+        };
+        self.saved_viewport = Some((current, scissor));
+        let sx = current[2] as f32 / self.width;
+        let sy = current[3] as f32 / self.height;
+        let x = current[0] + (region.x * sx) as i32;
+        let y = current[1] + current[3] - ((region.y + region.height) * sy) as i32;
+        let (w, h) = ((region.width * sx) as i32, (region.height * sy) as i32);
+        gl.enable(WebGlRenderingContext::SCISSOR_TEST);
+        gl.viewport(x, y, w, h);
+        gl.scissor(x, y, w, h);
+        true
+    }
+
+    fn pop_viewport(&mut self) {
+        let gl = self.gl;
+        let Some((viewport, scissor)) = self.saved_viewport.take() else {
+            return;
+        };
+        gl.viewport(viewport[0], viewport[1], viewport[2], viewport[3]);
+        match scissor {
+            Some(s) => gl.scissor(s[0], s[1], s[2], s[3]),
+            None => gl.disable(WebGlRenderingContext::SCISSOR_TEST),
+        }
+    }
+
+    // 円ごとに影まで含む四角形を2枚の三角形に展開し、減衰はフラグメントシェーダーで計算する
+    // 影は Canvas2D と同じく σ = ぼかし / 2 のガウス分布で円の外に広げる
+    fn fill_glows(&mut self, glows: &[Glow], shadow_blur: Option<f32>) -> bool {
+        let gl = self.gl;
+        let Ok((program, _)) =
+            shader::get_or_create_program(gl, GLOW_VERTEX_SHADER, GLOW_FRAGMENT_SHADER)
+        else {
+            return false;
+        };
+        let blur = shadow_blur.unwrap_or(0.0);
+        let mode = if shadow_blur.is_some() { 1.0 } else { 0.0 };
+        let res = &mut *self.resources;
+        res.positions.clear();
+        res.colors.clear();
+        res.instances.clear();
+        for glow in glows {
+            // 影はぼかしの大きさの2倍（2σ の4倍）まで描けば十分に消える
+            let extent = glow.radius + blur * 2.0;
+            for (dx, dy) in GLOW_CORNERS {
+                let (ox, oy) = (dx * extent, dy * extent);
+                res.positions.extend_from_slice(&[glow.x + ox, glow.y + oy]);
+                res.colors.extend_from_slice(&glow.rgb);
+                res.instances.extend_from_slice(&[ox, oy, glow.radius, blur]);
+            }
+        }
+
+        gl.use_program(Some(&program));
+        gl.bind_buffer(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            Some(&res.instance_buffer),
+        );
+        upload_dynamic(gl, &res.instances);
+        let glow_attrib = gl.get_attrib_location(&program, "a_glow") as u32;
+        gl.vertex_attrib_pointer_with_i32(
+            glow_attrib,
+            4,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+        gl.enable_vertex_attrib_array(glow_attrib);
+
+        gl.enable(WebGlRenderingContext::BLEND);
+        gl.blend_func(
+            WebGlRenderingContext::SRC_ALPHA,
+            WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+        );
+        self.draw_colored(
+            &program,
+            WebGlRenderingContext::TRIANGLES,
+            &[("u_shadow", mode)],
+        );
+        gl.disable(WebGlRenderingContext::BLEND);
+        gl.disable_vertex_attrib_array(glow_attrib);
+        true
+    }
+
+    fn fill_shader(&mut self, fragment_source: &str, uniforms: &[(&str, f32)]) -> bool {
+        let gl = self.gl;
+        let program =
+            match shader::get_or_create_program(gl, TEXTURE_VERTEX_SHADER, fragment_source) {
+                Ok((program, _)) => program,
+                Err(_) => return false,
+            };
+
+        gl.use_program(Some(&program));
+        set_uniforms(gl, &program, uniforms);
+        self.draw_quad(&program);
+        true
+    }
+
+    fn blit_rgba_shader_blur(
+        &mut self,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        radius: u32,
+    ) -> bool {
+        let Ok((program, _)) =
+            shader::get_or_create_program(self.gl, TEXTURE_VERTEX_SHADER, BLUR_FRAGMENT_SHADER)
+        else {
+            return false;
+        };
+        let uniforms = [
+            ("u_texel_x", 1.0 / width as f32),
+            ("u_texel_y", 1.0 / height as f32),
+            ("u_radius", radius as f32),
+        ];
+        self.draw_texture(&program, width, height, pixels, &uniforms);
+        true
+    }
+}
+
+impl GlSurface<'_> {
+    // ピクセル列をテクスチャに転送し、program で全画面矩形を描く
+    fn draw_texture(
+        &self,
+        program: &WebGlProgram,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+        uniforms: &[(&str, f32)],
+    ) {
+        let gl = self.gl;
+        let res = &*self.resources;
+
+        gl.use_program(Some(program));
+        gl.active_texture(WebGlRenderingContext::TEXTURE0);
+        gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&res.texture));
+        let _ = gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+            WebGlRenderingContext::TEXTURE_2D,
+            0,
+            WebGlRenderingContext::RGBA as i32,
+            width as i32,
+            height as i32,
+            0,
+            WebGlRenderingContext::RGBA,
+            WebGlRenderingContext::UNSIGNED_BYTE,
+            Some(pixels),
+        );
+
+        let texture_location = gl.get_uniform_location(program, "u_texture");
+        gl.uniform1i(texture_location.as_ref(), 0);
+        set_uniforms(gl, program, uniforms);
+        self.draw_quad(program);
+    }
+
+    // resources.positions / colors に詰めた頂点を program（color_program か point_program）と mode で描く
+    fn draw_colored(&self, program: &WebGlProgram, mode: u32, uniforms: &[(&str, f32)]) {
+        let gl = self.gl;
+        let res = &*self.resources;
+        if res.positions.is_empty() {
+            return;
+        }
+
+        gl.use_program(Some(program));
+        for (buffer, data, name, size) in [
+            (&res.position_buffer, &res.positions, "a_position", 2),
+            (&res.color_buffer, &res.colors, "a_color", 3),
+        ] {
+            gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+            upload_dynamic(gl, data);
+            let attrib = gl.get_attrib_location(program, name) as u32;
+            gl.vertex_attrib_pointer_with_i32(
+                attrib,
+                size,
+                WebGlRenderingContext::FLOAT,
+                false,
+                0,
+                0,
+            );
+            gl.enable_vertex_attrib_array(attrib);
+        }
+
+        let resolution = gl.get_uniform_location(program, "u_resolution");
+        gl.uniform2f(resolution.as_ref(), self.width, self.height);
+        set_uniforms(gl, program, uniforms);
+        gl.draw_arrays(mode, 0, (res.positions.len() / 2) as i32);
+    }
+
+    fn draw_quad(&self, program: &WebGlProgram) {
+        let gl = self.gl;
+        gl.bind_buffer(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            Some(&self.resources.quad_buffer),
+        );
+        let position_attrib = gl.get_attrib_location(program, "a_position") as u32;
+        gl.vertex_attrib_pointer_with_i32(
+            position_attrib,
+            2,
+            WebGlRenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+        gl.enable_vertex_attrib_array(position_attrib);
+        gl.draw_arrays(WebGlRenderingContext::TRIANGLES, 0, 6);
+    }
+}
+
+// バインド中の ARRAY_BUFFER に毎フレーム変わるデータを送る
+fn upload_dynamic(gl: &WebGlRenderingContext, data: &[f32]) {
+    unsafe {
+        let array = js_sys::Float32Array::view(data);
+        gl.buffer_data_with_array_buffer_view(
+            WebGlRenderingContext::ARRAY_BUFFER,
+            &array,
+            WebGlRenderingContext::DYNAMIC_DRAW,
+        );
+    }
+}
+
+fn set_uniforms(gl: &WebGlRenderingContext, program: &WebGlProgram, uniforms: &[(&str, f32)]) {
+    for (name, value) in uniforms {
+        gl.uniform1f(gl.get_uniform_location(program, name).as_ref(), *value);
+    }
+}
+
+// 全画面テクスチャ用の頂点シェーダー（テクスチャの1行目が画面の上端）
+const TEXTURE_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    varying vec2 v_uv;
+
+    void main() {
+        v_uv = vec2(a_position.x * 0.5 + 0.5, 0.5 - a_position.y * 0.5);
+        gl_Position = vec4(a_position, 0.0, 1.0);
+    }
+"#;
+
+const TEXTURE_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_texture;
+    varying vec2 v_uv;
+
+    void main() {
+        gl_FragColor = texture2D(u_texture, v_uv);
+    }
+"#;
+
+// 2次元ガウシアンぼかし（MAX_RADIUS は scene/blur.rs の MAX_SHADER_RADIUS と合わせる）
+const BLUR_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_texture;
+    uniform float u_texel_x;
+    uniform float u_texel_y;
+    uniform float u_radius;
+    varying vec2 v_uv;
+
+    const int MAX_RADIUS = 8;
+
+    void main() {
+        float sigma = max(u_radius / 2.0, 0.5);
+        vec4 sum = vec4(0.0);
+        float total = 0.0;
+        for (int y = -MAX_RADIUS; y <= MAX_RADIUS; y++) {
+            for (int x = -MAX_RADIUS; x <= MAX_RADIUS; x++) {
+                if (abs(float(x)) > u_radius || abs(float(y)) > u_radius) {
+                    continue;
+                }
+                float weight = exp(-float(x * x + y * y) / (2.0 * sigma * sigma));
+                vec2 offset = vec2(float(x) * u_texel_x, float(y) * u_texel_y);
+                sum += texture2D(u_texture, v_uv + offset) * weight;
+                total += weight;
+            }
+        }
+        gl_FragColor = sum / total;
+    }
+"#;
+
+// ピクセル座標（左上原点）の頂点を頂点色で描く
+const COLOR_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute vec3 a_color;
+    uniform vec2 u_resolution;
+    varying vec3 v_color;
+
+    void main() {
+        vec2 clip = a_position / u_resolution * 2.0 - 1.0;
+        gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+        v_color = a_color;
+    }
+"#;
+
+// COLOR_VERTEX_SHADER に点の大きさを足したもの
+const POINT_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute vec3 a_color;
+    uniform vec2 u_resolution;
+    uniform float u_point_size;
+    varying vec3 v_color;
+
+    void main() {
+        vec2 clip = a_position / u_resolution * 2.0 - 1.0;
+        gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+        gl_PointSize = u_point_size;
+        v_color = a_color;
+    }
+"#;
+
+const COLOR_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    varying vec3 v_color;
+
+    void main() {
+        gl_FragColor = vec4(v_color, 1.0);
+    }
+"#;
+
+// 中心を原点にした一辺2の正方形を2枚の三角形で
+const GLOW_CORNERS: [(f32, f32); 6] = [
+    (-1.0, -1.0),
+    (1.0, -1.0),
+    (-1.0, 1.0),
+    (-1.0, 1.0),
+    (1.0, -1.0),
+    (1.0, 1.0),
+];
+
+// COLOR_VERTEX_SHADER に円の中心からの位置・半径・ぼかしを足したもの
+const GLOW_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+    attribute vec3 a_color;
+    attribute vec4 a_glow;
+    uniform vec2 u_resolution;
+    varying vec3 v_color;
+    varying vec4 v_glow;
+
+    void main() {
+        vec2 clip = a_position / u_resolution * 2.0 - 1.0;
+        gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+        v_color = a_color;
+        v_glow = a_glow;
+    }
+"#;
+
+// u_shadow が 0 なら中心から縁へ線形に透明になる放射グラデーション、
+// 1 なら不透明な円と、その外にガウス分布で薄くなる影
+const GLOW_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform float u_shadow;
+    varying vec3 v_color;
+    varying vec4 v_glow;
+
+    void main() {
+        float d = length(v_glow.xy);
+        float radius = v_glow.z;
+        float alpha;
+        if (u_shadow > 0.5) {
+            float sigma = max(v_glow.w / 2.0, 0.001);
+            float outside = max(d - radius, 0.0);
+            alpha = exp(-outside * outside / (2.0 * sigma * sigma));
+        } else {
+            alpha = clamp(1.0 - d / radius, 0.0, 1.0);
+        }
+        if (alpha <= 0.0) {
+            discard;
+        }
+        gl_FragColor = vec4(v_color, alpha);
+    }
+"#;
+
+// 単位正方形を2枚の三角形で
+const SPRITE_CORNERS: [f32; 12] = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0];
+const SPRITE_INSTANCE_FLOATS: usize = 10;
+
+// 単位正方形の角をアフィン変換でピクセル座標に写し、アトラスの切り出し範囲を貼る
+const SPRITE_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_corner;
+    attribute vec4 a_linear;
+    attribute vec2 a_translate;
+    attribute vec4 a_source;
+    uniform vec2 u_resolution;
+    uniform vec2 u_atlas_size;
+    varying vec2 v_uv;
+
+    void main() {
+        vec2 p = vec2(
+            a_linear.x * a_corner.x + a_linear.z * a_corner.y,
+            a_linear.y * a_corner.x + a_linear.w * a_corner.y
+        ) + a_translate;
+        vec2 clip = p / u_resolution * 2.0 - 1.0;
+        gl_Position = vec4(clip.x, -clip.y, 0.0, 1.0);
+        v_uv = (a_source.xy + a_corner * a_source.zw) / u_atlas_size;
+    }
+"#;
@@ -0,0 +1,153 @@
+// WebGPU のうち webgpu.rs で使う分だけのバインディング
+// web-sys の Gpu* 型は web_sys_unstable_apis を有効にしないと使えず、有効にすると
+// put_image_data など安定版の API のシグネチャまで変わってしまうので、必要なものだけ自前で宣言する
+// 記述子（〜Descriptor）は JS のオブジェクトをそのまま組み立てて渡す
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPU)]
+    pub type Gpu;
+
+    #[wasm_bindgen(method, js_name = requestAdapter)]
+    pub fn request_adapter(this: &Gpu) -> js_sys::Promise;
+
+    #[wasm_bindgen(method, js_name = getPreferredCanvasFormat)]
+    pub fn get_preferred_canvas_format(this: &Gpu) -> String;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUAdapter)]
+    pub type GpuAdapter;
+
+    #[wasm_bindgen(method, js_name = requestDevice)]
+    pub fn request_device(this: &GpuAdapter) -> js_sys::Promise;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUDevice)]
+    pub type GpuDevice;
+
+    #[wasm_bindgen(method, getter)]
+    pub fn queue(this: &GpuDevice) -> GpuQueue;
+
+    #[wasm_bindgen(method, catch, js_name = createBuffer)]
+    pub fn create_buffer(
+        this: &GpuDevice,
+        descriptor: &js_sys::Object,
+    ) -> Result<GpuBuffer, JsValue>;
+
+    #[wasm_bindgen(method, js_name = createShaderModule)]
+    pub fn create_shader_module(this: &GpuDevice, descriptor: &js_sys::Object) -> js_sys::Object;
+
+    #[wasm_bindgen(method, catch, js_name = createRenderPipeline)]
+    pub fn create_render_pipeline(
+        this: &GpuDevice,
+        descriptor: &js_sys::Object,
+    ) -> Result<GpuRenderPipeline, JsValue>;
+
+    #[wasm_bindgen(method, js_name = createCommandEncoder)]
+    pub fn create_command_encoder(this: &GpuDevice) -> GpuCommandEncoder;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUQueue)]
+    pub type GpuQueue;
+
+    #[wasm_bindgen(method, catch, js_name = writeBuffer)]
+    pub fn write_buffer(
+        this: &GpuQueue,
+        buffer: &GpuBuffer,
+        offset: f64,
+        data: &[u8],
+    ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn submit(this: &GpuQueue, command_buffers: &js_sys::Array);
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUBuffer)]
+    pub type GpuBuffer;
+
+    #[wasm_bindgen(method)]
+    pub fn destroy(this: &GpuBuffer);
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPURenderPipeline)]
+    pub type GpuRenderPipeline;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUCanvasContext)]
+    pub type GpuCanvasContext;
+
+    #[wasm_bindgen(method, catch)]
+    pub fn configure(
+        this: &GpuCanvasContext,
+        configuration: &js_sys::Object,
+    ) -> Result<(), JsValue>;
+
+    #[wasm_bindgen(method, catch, js_name = getCurrentTexture)]
+    pub fn get_current_texture(this: &GpuCanvasContext) -> Result<GpuTexture, JsValue>;
+
+    // HtmlCanvasElement か OffscreenCanvas
+    #[wasm_bindgen(method, getter)]
+    pub fn canvas(this: &GpuCanvasContext) -> js_sys::Object;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUTexture)]
+    pub type GpuTexture;
+
+    #[wasm_bindgen(method, catch, js_name = createView)]
+    pub fn create_view(this: &GpuTexture) -> Result<JsValue, JsValue>;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPUCommandEncoder)]
+    pub type GpuCommandEncoder;
+
+    #[wasm_bindgen(method, catch, js_name = beginRenderPass)]
+    pub fn begin_render_pass(
+        this: &GpuCommandEncoder,
+        descriptor: &js_sys::Object,
+    ) -> Result<GpuRenderPassEncoder, JsValue>;
+
+    #[wasm_bindgen(method)]
+    pub fn finish(this: &GpuCommandEncoder) -> JsValue;
+
+    #[wasm_bindgen(extends = js_sys::Object, js_name = GPURenderPassEncoder)]
+    pub type GpuRenderPassEncoder;
+
+    #[wasm_bindgen(method, js_name = setPipeline)]
+    pub fn set_pipeline(this: &GpuRenderPassEncoder, pipeline: &GpuRenderPipeline);
+
+    #[wasm_bindgen(method, js_name = setVertexBuffer)]
+    pub fn set_vertex_buffer(this: &GpuRenderPassEncoder, slot: u32, buffer: &GpuBuffer);
+
+    #[wasm_bindgen(method)]
+    pub fn draw(this: &GpuRenderPassEncoder, vertex_count: u32, instance_count: u32);
+
+    #[wasm_bindgen(method)]
+    pub fn end(this: &GpuRenderPassEncoder);
+}
+
+// GPUBufferUsage の値
+pub const BUFFER_USAGE_COPY_DST: u32 = 0x0008;
+pub const BUFFER_USAGE_VERTEX: u32 = 0x0020;
+
+// navigator.gpu（WebGPU に対応していなければ None）
+// Worker の中でも使えるよう window ではなく globalThis から取る
+pub fn gpu() -> Option<Gpu> {
+    let global: JsValue = js_sys::global().into();
+    let navigator = js_sys::Reflect::get(&global, &"navigator".into()).ok()?;
+    let gpu = js_sys::Reflect::get(&navigator, &"gpu".into()).ok()?;
+    if gpu.is_undefined() || gpu.is_null() {
+        return None;
+    }
+    Some(gpu.unchecked_into())
+}
+
+// 記述子のオブジェクトを組み立てる
+pub fn object(fields: &[(&str, JsValue)]) -> js_sys::Object {
+    let object = js_sys::Object::new();
+    for (key, value) in fields {
+        let _ = js_sys::Reflect::set(&object, &JsValue::from_str(key), value);
+    }
+    object
+}
+
+pub fn array(values: impl IntoIterator<Item = JsValue>) -> js_sys::Array {
+    let array = js_sys::Array::new();
+    for value in values {
+        array.push(&value);
+    }
+    array
+}
@@ -0,0 +1,508 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    WebGl2RenderingContext, WebGlBuffer, WebGlProgram, WebGlRenderingContext,
+    WebGlTransformFeedback, WebGlVertexArrayObject,
+};
+
+use crate::bounds::BoundaryMode;
+use crate::camera::Camera;
+use crate::explosion::{ExplosionConfig, Falloff};
+use crate::i18n::{tr, Text};
+use crate::physics::SimulationConfig;
+use crate::shader;
+use crate::simulation::ParticleSet;
+
+// パーティクルの物理演算をどこで行うか
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PhysicsMode {
+    // CPU（WASM）で計算し、毎フレーム位置と色を送る（既定）
+    Cpu = 0,
+    // transform feedback で GPU 上の2つのバッファを交互に読み書きして計算し、CPU には戻さない
+    // 重力・跳ね返り・摩擦と爆発だけを計算する（「GPU がすべてを行う」場合の上限を測る用）
+    Gpu = 1,
+}
+
+impl PhysicsMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            PhysicsMode::Cpu => "cpu",
+            PhysicsMode::Gpu => "gpu",
+        }
+    }
+}
+
+// 1ステップで適用できる爆発の数（UPDATE_VERTEX_SHADER の配列の大きさと合わせる）
+// 超えた分は次のステップに回す
+const MAX_EXPLOSIONS: usize = 8;
+// 1個あたりの状態（位置と速度）の float の数
+const STATE_FLOATS: usize = 4;
+
+// transform feedback で物理演算を行うプログラムとバッファ
+pub(crate) struct GpuPhysics {
+    update_program: WebGlProgram,
+    render_program: WebGlProgram,
+    // 状態（x, y, vx, vy）を交互に読み書きする2つのバッファと、それぞれを読む VAO
+    states: [WebGlBuffer; 2],
+    update_vaos: [WebGlVertexArrayObject; 2],
+    render_vaos: [WebGlVertexArrayObject; 2],
+    hue_buffer: WebGlBuffer,
+    corner_buffer: WebGlBuffer,
+    transform_feedback: WebGlTransformFeedback,
+    // 最新の状態が入っているバッファ
+    current: usize,
+    count: usize,
+    // 送った時点の色相のずれを引いた基準の色相（書き戻すときに使う）
+    base_hue: Vec<f32>,
+    // 送ったときの Simulation::sequence()（CPU 側で作り直されたら送り直す）
+    uploaded_sequence: Option<u64>,
+    // 次の step() で適用する爆発
+    explosions: Vec<(f32, f32, ExplosionConfig)>,
+    // これまでの step() の回数（BoundaryMode::Respawn の乱数をステップごとに変える）
+    steps: u32,
+}
+
+impl GpuPhysics {
+    // fragment_source は描画に使うフラグメントシェーダー（CPU で計算するときと同じもの）
+    pub fn new(gl: &WebGl2RenderingContext, fragment_source: &str) -> Result<GpuPhysics, JsValue> {
+        let update_program = link_feedback_program(gl)?;
+        let (render_program, _) = shader::get_or_create_program(
+            gl.unchecked_ref::<WebGlRenderingContext>(),
+            RENDER_VERTEX_SHADER,
+            fragment_source,
+        )?;
+        let create_buffer = || {
+            gl.create_buffer()
+                .ok_or_else(|| JsValue::from(tr(Text::BufferCreationFailed, &[])))
+        };
+        let create_vao = || {
+            gl.create_vertex_array()
+                .ok_or_else(|| JsValue::from(tr(Text::BufferCreationFailed, &[])))
+        };
+        let states = [create_buffer()?, create_buffer()?];
+        let hue_buffer = create_buffer()?;
+        let corner_buffer = create_buffer()?;
+        let transform_feedback = gl
+            .create_transform_feedback()
+            .ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+
+        // 中心を原点とした一辺1の四角形（TRIANGLE_STRIP）
+        let corners: [f32; 8] = [-0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, 0.5];
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&corner_buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&corners);
+            gl.buffer_data_with_array_buffer_view(
+                WebGl2RenderingContext::ARRAY_BUFFER,
+                &array,
+                WebGl2RenderingContext::STATIC_DRAW,
+            );
+        }
+
+        let update_vaos = [create_vao()?, create_vao()?];
+        let render_vaos = [create_vao()?, create_vao()?];
+        for (index, state) in states.iter().enumerate() {
+            gl.bind_vertex_array(Some(&update_vaos[index]));
+            vertex_attrib(gl, state, 0, STATE_FLOATS, 0);
+
+            // (バッファ, location, 要素数, divisor)
+            gl.bind_vertex_array(Some(&render_vaos[index]));
+            for (buffer, location, size, divisor) in [
+                (&corner_buffer, 0, 2, 0),
+                (state, 1, STATE_FLOATS, 1),
+                (&hue_buffer, 2, 1, 1),
+            ] {
+                vertex_attrib(gl, buffer, location, size, divisor);
+            }
+        }
+        gl.bind_vertex_array(None);
+
+        Ok(GpuPhysics {
+            update_program,
+            render_program,
+            states,
+            update_vaos,
+            render_vaos,
+            hue_buffer,
+            corner_buffer,
+            transform_feedback,
+            current: 0,
+            count: 0,
+            base_hue: Vec::new(),
+            uploaded_sequence: None,
+            explosions: Vec::new(),
+            steps: 0,
+        })
+    }
+
+    // sequence が送ったときから変わっていれば（reset() や個数の変更など）particles を送り直す
+    // GPU で進めた位置はそこで捨てる
+    pub fn sync(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        particles: &ParticleSet,
+        sequence: u64,
+        hue_shift: f32,
+    ) {
+        if self.uploaded_sequence == Some(sequence) {
+            return;
+        }
+        let mut state = Vec::with_capacity(particles.x.len() * STATE_FLOATS);
+        for i in 0..particles.x.len() {
+            state.extend_from_slice(&[
+                particles.x[i],
+                particles.y[i],
+                particles.vx[i],
+                particles.vy[i],
+            ]);
+        }
+        self.base_hue.clear();
+        self.base_hue.extend(
+            particles
+                .hue
+                .iter()
+                .map(|hue| (hue - hue_shift).rem_euclid(360.0)),
+        );
+
+        // 書き込む側のバッファも同じ大きさにしておく
+        for buffer in &self.states {
+            write_buffer(gl, buffer, &state, WebGl2RenderingContext::DYNAMIC_COPY);
+        }
+        write_buffer(
+            gl,
+            &self.hue_buffer,
+            &self.base_hue,
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+        self.current = 0;
+        self.count = particles.x.len();
+        self.uploaded_sequence = Some(sequence);
+    }
+
+    // 次の step() の最初に (x, y) で爆発させる
+    pub fn explode(&mut self, x: f32, y: f32, explosion: &ExplosionConfig) {
+        self.explosions.push((x, y, *explosion));
+    }
+
+    // 1ステップ進める（描画はしない）
+    pub fn step(
+        &mut self,
+        gl: &WebGl2RenderingContext,
+        width: f32,
+        height: f32,
+        config: &SimulationConfig,
+        boundary: BoundaryMode,
+    ) {
+        let program = &self.update_program;
+        gl.use_program(Some(program));
+        let uniform = |name: &str| gl.get_uniform_location(program, name);
+        gl.uniform2f(uniform("u_bounds").as_ref(), width, height);
+        gl.uniform3f(
+            uniform("u_physics").as_ref(),
+            config.gravity,
+            config.bounce,
+            config.friction,
+        );
+        gl.uniform1i(uniform("u_boundary").as_ref(), boundary as i32);
+        gl.uniform1ui(uniform("u_seed").as_ref(), self.steps);
+        self.steps = self.steps.wrapping_add(1);
+
+        let applied = self.explosions.len().min(MAX_EXPLOSIONS);
+        let mut explosions = [0.0; MAX_EXPLOSIONS * 4];
+        let mut falloffs = [0; MAX_EXPLOSIONS];
+        for (i, (x, y, explosion)) in self.explosions.drain(..applied).enumerate() {
+            explosions[i * 4..i * 4 + 4].copy_from_slice(&[
+                x,
+                y,
+                explosion.radius,
+                explosion.force,
+            ]);
+            falloffs[i] = match explosion.falloff {
+                Falloff::Linear => 0,
+                Falloff::Quadratic => 1,
+                Falloff::Gaussian => 2,
+            };
+        }
+        gl.uniform1i(uniform("u_explosionCount").as_ref(), applied as i32);
+        gl.uniform4fv_with_f32_array(uniform("u_explosions").as_ref(), &explosions);
+        gl.uniform1iv_with_i32_array(uniform("u_falloffs").as_ref(), &falloffs);
+
+        let next = 1 - self.current;
+        gl.bind_vertex_array(Some(&self.update_vaos[self.current]));
+        gl.bind_transform_feedback(
+            WebGl2RenderingContext::TRANSFORM_FEEDBACK,
+            Some(&self.transform_feedback),
+        );
+        gl.bind_buffer_base(
+            WebGl2RenderingContext::TRANSFORM_FEEDBACK_BUFFER,
+            0,
+            Some(&self.states[next]),
+        );
+        gl.enable(WebGl2RenderingContext::RASTERIZER_DISCARD);
+        gl.begin_transform_feedback(WebGl2RenderingContext::POINTS);
+        gl.draw_arrays(WebGl2RenderingContext::POINTS, 0, self.count as i32);
+        gl.end_transform_feedback();
+        gl.disable(WebGl2RenderingContext::RASTERIZER_DISCARD);
+        // 次の描画で頂点属性として読めるように外す
+        gl.bind_buffer_base(WebGl2RenderingContext::TRANSFORM_FEEDBACK_BUFFER, 0, None);
+        gl.bind_transform_feedback(WebGl2RenderingContext::TRANSFORM_FEEDBACK, None);
+        gl.bind_vertex_array(None);
+        self.current = next;
+    }
+
+    // 最新の状態を四角形のインスタンスとして描く（size はクリップ座標での四角形の大きさ）
+    pub fn render(
+        &self,
+        gl: &WebGl2RenderingContext,
+        width: f32,
+        height: f32,
+        camera: Camera,
+        size: (f32, f32),
+        hue_shift: f32,
+    ) {
+        let program = &self.render_program;
+        gl.use_program(Some(program));
+        let uniform = |name: &str| gl.get_uniform_location(program, name);
+        gl.uniform2f(uniform("u_bounds").as_ref(), width, height);
+        gl.uniform3f(
+            uniform("u_camera").as_ref(),
+            camera.x,
+            camera.y,
+            camera.zoom,
+        );
+        gl.uniform2f(uniform("u_size").as_ref(), size.0, size.1);
+        gl.uniform1f(uniform("u_hueShift").as_ref(), hue_shift);
+        gl.bind_vertex_array(Some(&self.render_vaos[self.current]));
+        gl.draw_arrays_instanced(
+            WebGl2RenderingContext::TRIANGLE_STRIP,
+            0,
+            4,
+            self.count as i32,
+        );
+        gl.bind_vertex_array(None);
+    }
+
+    // 最新の状態を読み戻し、(x, y, vx, vy) を並べたものと基準の色相を返す（CPU の計算に戻すとき）
+    pub fn read_back(&self, gl: &WebGl2RenderingContext) -> (Vec<f32>, &[f32]) {
+        let array = js_sys::Float32Array::new_with_length((self.count * STATE_FLOATS) as u32);
+        gl.bind_buffer(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            Some(&self.states[self.current]),
+        );
+        gl.get_buffer_sub_data_with_i32_and_array_buffer_view(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            0,
+            &array,
+        );
+        (array.to_vec(), &self.base_hue)
+    }
+
+    pub fn delete(&self, gl: &WebGl2RenderingContext) {
+        for buffer in self
+            .states
+            .iter()
+            .chain([&self.hue_buffer, &self.corner_buffer])
+        {
+            gl.delete_buffer(Some(buffer));
+        }
+        for vao in self.update_vaos.iter().chain(&self.render_vaos) {
+            gl.delete_vertex_array(Some(vao));
+        }
+        gl.delete_transform_feedback(Some(&self.transform_feedback));
+        gl.delete_program(Some(&self.update_program));
+    }
+}
+
+// buffer を location の属性として読む（VAO に記録される）
+fn vertex_attrib(
+    gl: &WebGl2RenderingContext,
+    buffer: &WebGlBuffer,
+    location: u32,
+    size: usize,
+    divisor: u32,
+) {
+    gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+    gl.enable_vertex_attrib_array(location);
+    gl.vertex_attrib_pointer_with_i32(
+        location,
+        size as i32,
+        WebGl2RenderingContext::FLOAT,
+        false,
+        0,
+        0,
+    );
+    gl.vertex_attrib_divisor(location, divisor);
+}
+
+fn write_buffer(gl: &WebGl2RenderingContext, buffer: &WebGlBuffer, data: &[f32], usage: u32) {
+    gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+    unsafe {
+        let array = js_sys::Float32Array::view(data);
+        gl.buffer_data_with_array_buffer_view(WebGl2RenderingContext::ARRAY_BUFFER, &array, usage);
+    }
+}
+
+// v_state を書き出す物理演算のプログラム（varyings はリンクの前に指定するのでキャッシュは使わない）
+fn link_feedback_program(gl: &WebGl2RenderingContext) -> Result<WebGlProgram, JsValue> {
+    let gl1 = gl.unchecked_ref::<WebGlRenderingContext>();
+    let vertex = shader::compile_shader(
+        gl1,
+        WebGlRenderingContext::VERTEX_SHADER,
+        UPDATE_VERTEX_SHADER,
+    )?;
+    let fragment = shader::compile_shader(
+        gl1,
+        WebGlRenderingContext::FRAGMENT_SHADER,
+        UPDATE_FRAGMENT_SHADER,
+    )?;
+    let program = gl
+        .create_program()
+        .ok_or_else(|| tr(Text::ShaderCreationFailed, &[]))?;
+    gl.attach_shader(&program, &vertex);
+    gl.attach_shader(&program, &fragment);
+    let varyings = js_sys::Array::of1(&JsValue::from_str("v_state"));
+    gl.transform_feedback_varyings(
+        &program,
+        &varyings,
+        WebGl2RenderingContext::INTERLEAVED_ATTRIBS,
+    );
+    gl.link_program(&program);
+    gl.delete_shader(Some(&vertex));
+    gl.delete_shader(Some(&fragment));
+    if gl
+        .get_program_parameter(&program, WebGl2RenderingContext::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(gl
+            .get_program_info_log(&program)
+            .unwrap_or_else(|| tr(Text::ProgramLinkFailed, &[]))
+            .into())
+    }
+}
+
+// 1パーティクルを1頂点として、simulation.rs の explode() と integrate() と同じ計算をする
+// Respawn だけは CPU と違い、外に出たステップのうちに噴水の位置へ戻す（色相はそのまま）
+const UPDATE_VERTEX_SHADER: &str = r#"#version 300 es
+    layout(location = 0) in vec4 a_state;
+    uniform vec2 u_bounds;
+    // x: 重力, y: 跳ね返り, z: 摩擦
+    uniform vec3 u_physics;
+    // 0: Bounce, 1: Wrap, 2: Respawn, 3: None（BoundaryMode と同じ番号）
+    uniform int u_boundary;
+    // Respawn で生成し直すときの乱数の種（ステップごとに変わる）
+    uniform uint u_seed;
+    uniform int u_explosionCount;
+    // x, y: 中心, z: 半径, w: 強さ
+    uniform vec4 u_explosions[8];
+    // 0: Linear, 1: Quadratic, 2: Gaussian
+    uniform int u_falloffs[8];
+    out vec4 v_state;
+
+    // rng.rs の hash() と同じ lowbias32
+    uint hash(uint x) {
+        x ^= x >> 16;
+        x *= 0x7feb352du;
+        x ^= x >> 15;
+        x *= 0x846ca68bu;
+        return x ^ (x >> 16);
+    }
+
+    // 0〜1 の乱数
+    float random(uint x) {
+        return float(hash(x) >> 8) / 16777216.0;
+    }
+
+    void main() {
+        vec2 p = a_state.xy;
+        vec2 v = a_state.zw;
+
+        for (int i = 0; i < 8; i++) {
+            if (i >= u_explosionCount) {
+                break;
+            }
+            vec4 e = u_explosions[i];
+            vec2 d = p - e.xy;
+            float dist = length(d);
+            if (dist < e.z) {
+                float t = dist / e.z;
+                float scale = u_falloffs[i] == 0 ? 1.0 - t
+                    : u_falloffs[i] == 1 ? (1.0 - t) * (1.0 - t)
+                    : exp(-4.5 * t * t);
+                vec2 dir = dist > 0.0 ? d / dist : vec2(1.0, 0.0);
+                v += dir * e.w * scale;
+            }
+        }
+
+        v.y += u_physics.x;
+        p += v;
+        bool outsideX = p.x < 0.0 || p.x > u_bounds.x;
+        bool outsideY = p.y < 0.0 || p.y > u_bounds.y;
+        if (u_boundary == 0) {
+            if (outsideX) {
+                v.x *= -u_physics.y;
+                p.x = clamp(p.x, 0.0, u_bounds.x);
+            }
+            if (p.y < 0.0) {
+                v.y *= -u_physics.y;
+                p.y = 0.0;
+            }
+            if (p.y > u_bounds.y) {
+                v.y *= -u_physics.y;
+                p.y = u_bounds.y;
+                v.x *= u_physics.z;
+            }
+        } else if (u_boundary == 1) {
+            if (outsideX) {
+                p.x -= u_bounds.x * floor(p.x / u_bounds.x);
+            }
+            if (outsideY) {
+                p.y -= u_bounds.y * floor(p.y / u_bounds.y);
+            }
+        } else if (u_boundary == 2 && (outsideX || outsideY)) {
+            // simulation.rs の spawn_particle() と同じ分布
+            uint key = hash(uint(gl_VertexID) ^ hash(u_seed));
+            float angle = random(key) * 6.28318530718;
+            float speed = random(key + 1u) * 2.0 + 1.0;
+            p = vec2(u_bounds.x / 2.0, u_bounds.y / 4.0);
+            v = vec2(cos(angle), sin(angle)) * speed - vec2(0.0, 3.0);
+        }
+        v_state = vec4(p, v);
+    }
+"#;
+
+// RASTERIZER_DISCARD で捨てるので何も書かない
+const UPDATE_FRAGMENT_SHADER: &str = r#"#version 300 es
+    precision mediump float;
+    out vec4 out_color;
+
+    void main() {
+        out_color = vec4(0.0);
+    }
+"#;
+
+// 状態のバッファの位置をそのままインスタンスの位置として読み、色相から色を計算する
+const RENDER_VERTEX_SHADER: &str = r#"#version 300 es
+    layout(location = 0) in vec2 a_corner;
+    layout(location = 1) in vec4 a_state;
+    layout(location = 2) in float a_hue;
+    uniform vec2 u_bounds;
+    // x, y: 画面の左上に映る座標, z: 拡大率
+    uniform vec3 u_camera;
+    uniform vec2 u_size;
+    uniform float u_hueShift;
+    out vec3 v_color;
+
+    vec3 hueToRgb(float hue) {
+        vec3 k = mod(vec3(5.0, 3.0, 1.0) + hue / 60.0, 6.0);
+        return 1.0 - clamp(min(k, 4.0 - k), 0.0, 1.0);
+    }
+
+    void main() {
+        vec2 screen = (a_state.xy - u_camera.xy) * u_camera.z;
+        vec2 clip = vec2(screen.x / u_bounds.x * 2.0 - 1.0, 1.0 - screen.y / u_bounds.y * 2.0);
+        gl_Position = vec4(clip + a_corner * u_size, 0.0, 1.0);
+        v_color = hueToRgb(mod(a_hue + u_hueShift, 360.0));
+    }
+"#;
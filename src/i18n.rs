@@ -0,0 +1,464 @@
+use std::cell::Cell;
+use std::fmt::Display;
+
+use wasm_bindgen::prelude::*;
+
+use crate::scene::SceneKind;
+
+// 利用者に見える文字列（エラー・警告・UI用のラベル）の言語
+// コード内のコメントやログ用の識別子（シーン名・イベントの種類など）は翻訳しない
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Locale {
+    En = 0,
+    Ja = 1,
+}
+
+thread_local! {
+    static LOCALE: Cell<Locale> = const { Cell::new(Locale::En) };
+}
+
+// "en" / "ja"（"ja-JP" のような地域付きの指定も受け付ける）
+#[wasm_bindgen]
+pub fn set_locale(code: &str) -> Result<(), JsValue> {
+    let language = code.split(['-', '_']).next().unwrap_or("");
+    let locale = match language.to_ascii_lowercase().as_str() {
+        "en" => Locale::En,
+        "ja" => Locale::Ja,
+        _ => return Err(tr(Text::UnsupportedLocale, &[&code]).into()),
+    };
+    LOCALE.set(locale);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn get_locale() -> String {
+    match LOCALE.get() {
+        Locale::En => "en",
+        Locale::Ja => "ja",
+    }
+    .to_string()
+}
+
+// 翻訳する文字列の一覧
+#[derive(Clone, Copy, Debug)]
+pub enum Text {
+    UnsupportedLocale,
+    // コンテキストとGLリソース
+    WebGlUnsupported,
+    WebGl2Unsupported,
+    WebGpuUnsupported,
+    Canvas2DUnavailable,
+    ExternalContextMissing,
+    DocumentUnavailable,
+    WindowUnavailable,
+    CanvasNotFound,
+    NotACanvas,
+    StorageUnavailable,
+    BufferCreationFailed,
+    TextureCreationFailed,
+    FramebufferCreationFailed,
+    ShaderCreationFailed,
+    ShaderCompileFailed,
+    ProgramLinkFailed,
+    // メモリ
+    MemoryBudgetExceeded,
+    AllocationFailed,
+    // シーン
+    LifeGridEmpty,
+    LifeGridTooLarge,
+    BlurImageSize,
+    InvalidSize,
+    // strict モード
+    StrictMissingExtensions,
+    StrictSceneUnsupported,
+    SceneUnavailable,
+    StrictBlurUnsupported,
+    // 警告イベント
+    ContextLost,
+    ContextRestored,
+    ContextRestoreFailed,
+    InstancingUnavailable,
+    LoadClamped,
+    ThreadCountClamped,
+    ParticleCountClamped,
+    SimdUnavailable,
+    BlurRadiusClamped,
+    BlurFallback,
+    StateChangeFallback,
+    ResizeObserverUnavailable,
+    InputUnavailable,
+    KioskUnavailable,
+    TransformFallback,
+    GlowFallback,
+    ClipFallback,
+    ViewportFallback,
+    CompositeFallback,
+    CompositeModeUnavailable,
+    SpriteAtlasFailed,
+    ParticleShapeUnavailable,
+    RenderModeUnavailable,
+    DrawStrategyUnavailable,
+    UploadStrategyUnavailable,
+    FrameCaptureUnavailable,
+    FrameSizeMismatch,
+    BufferLayoutUnavailable,
+    ColorModeUnavailable,
+    PhysicsModeUnavailable,
+    GpuPhysicsConflict,
+    ColorModeColors,
+    ColorModeConflict,
+    ProjectionModeUnavailable,
+    #[cfg(feature = "telemetry")]
+    TelemetryUnavailable,
+    ImageDecodeFailed,
+    ParticleTextureInvalid,
+    ParticleStyleUnavailable,
+    SceneHasNoAtlas,
+    ParticleGrowthUnavailable,
+    FractalFallback,
+    BackendAsyncOnly,
+    BackendSelected,
+    QuirkPointSizeClamped,
+    QuirkSlowFillStyle,
+    QuirkRafThrottled,
+    // ベンチマークスイート
+    SuiteUnknownCase,
+    SuiteCaseInterrupted,
+    SuiteThermalDrift,
+    RunnerUnknownBackend,
+    RunnerTooFewInstances,
+    LoopDriverUnavailable,
+    // 結果の書き出し
+    ReportInvalid,
+    ReportJsonInvalid,
+    ReportVersionUnsupported,
+    MetadataInvalid,
+    // 操作の記録と再生
+    WorkloadInvalid,
+    WorkloadVersionUnsupported,
+    ScenarioNotLoaded,
+    // 状態の保存と復元
+    SnapshotInvalid,
+    SnapshotVersionUnsupported,
+    // アニメーションの書き出し
+    AnimationEmpty,
+    DownloadUnavailable,
+    AnimationEncodeFailed,
+    CaptureEncodeFailed,
+    // スクリーンリーダー向けの状態の読み上げ
+    AriaSummary,
+}
+
+// {0}, {1}, ... を args で置き換える
+fn template(text: Text, locale: Locale) -> &'static str {
+    use Locale::*;
+    use Text::*;
+    match (text, locale) {
+        (UnsupportedLocale, En) => "Unsupported locale: {0} (use \"en\" or \"ja\")",
+        (UnsupportedLocale, Ja) => {
+            "未対応のロケールです: {0}（\"en\" か \"ja\" を指定してください）"
+        }
+        (WebGlUnsupported, En) => "WebGL is not supported",
+        (WebGlUnsupported, Ja) => "WebGL に対応していません",
+        (WebGl2Unsupported, En) => "WebGL2 is not supported",
+        (WebGl2Unsupported, Ja) => "WebGL2 に対応していません",
+        (WebGpuUnsupported, En) => "WebGPU is not supported",
+        (WebGpuUnsupported, Ja) => "WebGPU に対応していません",
+        (Canvas2DUnavailable, En) => "Canvas 2D context is unavailable",
+        (Canvas2DUnavailable, Ja) => "Canvas 2D コンテキストを取得できません",
+        (ExternalContextMissing, En) => "External context is null or undefined",
+        (ExternalContextMissing, Ja) => "外部コンテキストが null または undefined です",
+        (DocumentUnavailable, En) => "No document to create canvases in",
+        (DocumentUnavailable, Ja) => "キャンバスを作る document がありません",
+        (WindowUnavailable, En) => {
+            "No window (in a worker, pass an OffscreenCanvas or an external context instead)"
+        }
+        (WindowUnavailable, Ja) => {
+            "window がありません（Worker では OffscreenCanvas か外部のコンテキストを渡してください）"
+        }
+        (CanvasNotFound, En) => "No element with id \"{0}\"",
+        (CanvasNotFound, Ja) => "id が \"{0}\" の要素がありません",
+        (NotACanvas, En) => "Element \"{0}\" is not a <canvas>",
+        (NotACanvas, Ja) => "要素 \"{0}\" は <canvas> ではありません",
+        (StorageUnavailable, En) => "localStorage is unavailable, progress cannot be saved",
+        (StorageUnavailable, Ja) => "localStorage を使えないため、進捗を保存できません",
+        (BufferCreationFailed, En) => "Failed to create buffer",
+        (BufferCreationFailed, Ja) => "バッファを作成できません",
+        (TextureCreationFailed, En) => "Failed to create texture",
+        (TextureCreationFailed, Ja) => "テクスチャを作成できません",
+        (FramebufferCreationFailed, En) => "Failed to create framebuffer",
+        (FramebufferCreationFailed, Ja) => "フレームバッファを作成できません",
+        (ShaderCreationFailed, En) => "Unable to create shader object",
+        (ShaderCreationFailed, Ja) => "シェーダーオブジェクトを作成できません",
+        (ShaderCompileFailed, En) => "Unknown error creating shader",
+        (ShaderCompileFailed, Ja) => "シェーダーの作成中に不明なエラーが発生しました",
+        (ProgramLinkFailed, En) => "Unknown error creating program object",
+        (ProgramLinkFailed, Ja) => "プログラムの作成中に不明なエラーが発生しました",
+        (MemoryBudgetExceeded, En) => {
+            "{0} particles need about {1} MiB, which exceeds the memory budget of {2} MiB"
+        }
+        (MemoryBudgetExceeded, Ja) => {
+            "パーティクル {0} 個には約 {1} MiB が必要で、メモリ予算 {2} MiB を超えています"
+        }
+        (AllocationFailed, En) => "Failed to allocate {0} MiB",
+        (AllocationFailed, Ja) => "{0} MiB のメモリを確保できません",
+        (LifeGridEmpty, En) => "Life grid must have at least one column and one row",
+        (LifeGridEmpty, Ja) => "ライフゲームの盤面には少なくとも1列1行が必要です",
+        (LifeGridTooLarge, En) => "Life grid can have at most {0} columns and {0} rows (got {1} x {2})",
+        (LifeGridTooLarge, Ja) => "ライフゲームの盤面は縦横それぞれ {0} セルまでです（{1} x {2} が指定されました）",
+        (BlurImageSize, En) => "Blur image must be {0}x{1} RGBA ({2} bytes), got {3} bytes",
+        (BlurImageSize, Ja) => "ぼかす画像は {0}x{1} の RGBA（{2} バイト）にしてください（{3} バイトが渡されました）",
+        (InvalidSize, En) => "Invalid size {0}x{1} (both must be at least 1)",
+        (InvalidSize, Ja) => "大きさ {0}x{1} は使えません（どちらも1以上にしてください）",
+        (StrictMissingExtensions, En) => "Strict mode: missing WebGL extensions: {0}",
+        (StrictMissingExtensions, Ja) => "strict モード: WebGL 拡張機能がありません: {0}",
+        (StrictSceneUnsupported, En) => "Strict mode: scene {0} has no native {1} path",
+        (StrictSceneUnsupported, Ja) => "strict モード: シーン {0} は {1} 本来の経路で描けません",
+        (SceneUnavailable, En) => "Scene {0} cannot be drawn by the {1} backend",
+        (SceneUnavailable, Ja) => "シーン {0} は {1} バックエンドでは描けません",
+        (StrictBlurUnsupported, En) => "Strict mode: {0} blur is unavailable on this backend",
+        (StrictBlurUnsupported, Ja) => {
+            "strict モード: このバックエンドでは {0} のぼかしを使えません"
+        }
+        (ContextLost, En) => "WebGL context lost",
+        (ContextLost, Ja) => "WebGL コンテキストが失われました",
+        (ContextRestored, En) => "WebGL context restored, GL resources recreated",
+        (ContextRestored, Ja) => "WebGL コンテキストが戻ったので GL のリソースを作り直しました",
+        (ContextRestoreFailed, En) => "WebGL context restored, but recreating GL resources failed: {0}",
+        (ContextRestoreFailed, Ja) => {
+            "WebGL コンテキストが戻りましたが GL のリソースを作り直せません: {0}"
+        }
+        (InstancingUnavailable, En) => {
+            "ANGLE_instanced_arrays unavailable, sprites are drawn without instancing"
+        }
+        (InstancingUnavailable, Ja) => {
+            "ANGLE_instanced_arrays がないため、スプライトをインスタンス描画なしで描きます"
+        }
+        (LoadClamped, En) => "load {0} clamped to 0.0..=1.0",
+        (LoadClamped, Ja) => "負荷 {0} を 0.0~1.0 に丸めました",
+        (ThreadCountClamped, En) => "thread count {0} clamped to {1} (more threads need the threads feature and a thread pool)",
+        (ThreadCountClamped, Ja) => "スレッド数 {0} を {1} に丸めました（増やすには threads フィーチャーとスレッドプールが必要です）",
+        (ParticleCountClamped, En) => "particle count {0} clamped to {1} (the count the backend was created with)",
+        (ParticleCountClamped, Ja) => "パーティクル数 {0} を {1} に丸めました（作成時の数が上限です）",
+        (SimdUnavailable, En) => "SIMD path is not built in (enable the simd feature), using the scalar path",
+        (SimdUnavailable, Ja) => "SIMD 版が組み込まれていないため（simd フィーチャーが必要）、スカラー版で計算します",
+        (BlurRadiusClamped, En) => "blur: shader radius clamped to {0}px",
+        (BlurRadiusClamped, Ja) => "blur: シェーダーのぼかし半径を {0}px に丸めました",
+        (BlurFallback, En) => "blur: {0} is unsupported here, blurring in WASM",
+        (BlurFallback, Ja) => "blur: ここでは {0} を使えないため、WASMでぼかします",
+        (ResizeObserverUnavailable, En) => "observe_resize() needs a canvas element on the page without a split viewport",
+        (ResizeObserverUnavailable, Ja) => "observe_resize() はページ上の canvas 要素に、領域を分割せずに描いているときだけ使えます",
+        (InputUnavailable, En) => "attach_input_handlers() needs a canvas element on the page",
+        (InputUnavailable, Ja) => "attach_input_handlers() はページ上の canvas 要素に描いているときだけ使えます",
+        (KioskUnavailable, En) => "Kiosk mode needs a canvas element with id \"{0}\" on the page",
+        (KioskUnavailable, Ja) => "キオスクモードにはページ上に id が \"{0}\" の canvas 要素が必要です",
+        (StateChangeFallback, En) => "state-changes: this backend cannot switch draw state per rect, drawing with colors only",
+        (SpriteAtlasFailed, En) => "Could not create the particle sprite atlas",
+        (SpriteAtlasFailed, Ja) => "パーティクルのスプライトのアトラスを作れません",
+        (ParticleShapeUnavailable, En) => "Particle shape {0} cannot be drawn by the {1} backend",
+        (ParticleShapeUnavailable, Ja) => "パーティクルの形 {0} は {1} バックエンドでは描けません",
+        (RenderModeUnavailable, En) => "Render mode {0} is not supported by the {1} backend",
+        (RenderModeUnavailable, Ja) => "描画モード {0} は {1} バックエンドでは使えません",
+        (DrawStrategyUnavailable, En) => "Draw strategy {0} is not available on the {1} backend",
+        (DrawStrategyUnavailable, Ja) => "描き方 {0} は {1} バックエンドでは使えません",
+        (UploadStrategyUnavailable, En) => "Upload strategy {0} is not available on the {1} backend",
+        (UploadStrategyUnavailable, Ja) => "頂点データの送り方 {0} は {1} バックエンドでは使えません",
+        (FrameCaptureUnavailable, En) => "Frame capture is not available on the {0} backend",
+        (FrameCaptureUnavailable, Ja) => "{0} バックエンドではフレームを読み戻せません",
+        (FrameSizeMismatch, En) => "Frames to compare must be RGBA data of the same size ({0} and {1} bytes)",
+        (FrameSizeMismatch, Ja) => "比べるフレームは同じ大きさの RGBA である必要があります（{0} バイトと {1} バイト）",
+        (BufferLayoutUnavailable, En) => "Buffer layout {0} is not available on the {1} backend",
+        (BufferLayoutUnavailable, Ja) => "頂点データの並べ方 {0} は {1} バックエンドでは使えません",
+        (ColorModeUnavailable, En) => "Color mode {0} is not available on the {1} backend",
+        (ColorModeUnavailable, Ja) => "色の計算方法 {0} は {1} バックエンドでは使えません",
+        (PhysicsModeUnavailable, En) => "Physics mode {0} is not available on the {1} backend",
+        (PhysicsModeUnavailable, Ja) => "物理演算の方法 {0} は {1} バックエンドでは使えません",
+        (GpuPhysicsConflict, En) => "GPU physics cannot be used while {0} are enabled",
+        (GpuPhysicsConflict, Ja) => "{0} を使っている間は GPU の物理演算にできません",
+        (ColorModeColors, En) => "Particle color mode {0} needs 1 to {1} colors as r, g, b values (got {2} values)",
+        (ColorModeColors, Ja) => "パーティクルの色 {0} には r, g, b を並べた 1〜{1} 色が必要です（{2} 個の値が渡されました）",
+        (ColorModeConflict, En) => "Particle color mode {0} cannot be used with {1}",
+        (ColorModeConflict, Ja) => "パーティクルの色 {0} は {1} と一緒には使えません",
+        (ProjectionModeUnavailable, En) => "Projection mode {0} is not available on the {1} backend",
+        (ProjectionModeUnavailable, Ja) => "投影の方法 {0} は {1} バックエンドでは使えません",
+        #[cfg(feature = "telemetry")]
+        (TelemetryUnavailable, En) => "Could not open telemetry target {0} (use a BroadcastChannel name or a ws:// or wss:// URL)",
+        #[cfg(feature = "telemetry")]
+        (TelemetryUnavailable, Ja) => "計測値の送り先 {0} を開けません（BroadcastChannel の名前か ws:// か wss:// の URL を指定してください）",
+        (ImageDecodeFailed, En) => "Could not create a {0} image from the sprite atlas",
+        (ImageDecodeFailed, Ja) => "スプライトのアトラスから {0} の画像を作れません",
+        (ParticleTextureInvalid, En) => "set_particle_texture() needs an HTMLImageElement or ImageBitmap",
+        (ParticleTextureInvalid, Ja) => "set_particle_texture() には HTMLImageElement か ImageBitmap を渡してください",
+        (ParticleStyleUnavailable, En) => "Particle style {0} cannot be drawn by the {1} backend",
+        (ParticleStyleUnavailable, Ja) => "パーティクルのスタイル {0} は {1} バックエンドでは描けません",
+        (SceneHasNoAtlas, En) => "Scene {0} does not draw sprites from an atlas",
+        (SceneHasNoAtlas, Ja) => "シーン {0} はアトラスのスプライトを描きません",
+        (ParticleGrowthUnavailable, En) => "The {0} backend cannot grow beyond the particle count it was created with",
+        (ParticleGrowthUnavailable, Ja) => "{0} バックエンドは作成時のパーティクル数を超えて増やせません",
+        (CompositeFallback, En) => "compositing: this backend cannot composite with {0}, drawing opaque rectangles",
+        (CompositeFallback, Ja) => "compositing: このバックエンドは {0} で合成できないため、不透明な矩形で描きます",
+        (CompositeModeUnavailable, En) => "Compositing mode {0} cannot be expressed as a blend equation on the {1} backend",
+        (CompositeModeUnavailable, Ja) => "合成方法 {0} は {1} バックエンドのブレンド式では表せません",
+        (ClipFallback, En) => "clip: this backend cannot clip (no stencil buffer), drawing without clipping",
+        (ClipFallback, Ja) => "clip: このバックエンドはクリップできない（ステンシルバッファがない）ため、クリップせずに描きます",
+        (ViewportFallback, En) => "quadrants: this backend cannot switch viewports, drawing scaled-down points without clipping",
+        (ViewportFallback, Ja) => "quadrants: このバックエンドはビューポートを切り替えられないため、縮めた座標の点をクリップせずに描きます",
+        (GlowFallback, En) => "glow: this backend cannot draw gradients or shadows, drawing flat polygons",
+        (GlowFallback, Ja) => "glow: このバックエンドはグラデーションや影を描けないため、単色の多角形で描きます",
+        (TransformFallback, En) => "transforms: this backend has no transform API, drawing with precomputed sprite matrices",
+        (TransformFallback, Ja) => "transforms: このバックエンドには変換APIがないため、計算した行列のスプライトで描きます",
+        (StateChangeFallback, Ja) => "state-changes: このバックエンドは矩形ごとに描画状態を切り替えられないため、色だけで描きます",
+        (FractalFallback, En) => "fractal-gpu: shaders are unavailable, computing on the CPU",
+        (FractalFallback, Ja) => "fractal-gpu: シェーダーを使えないため、CPUで計算します",
+        (BackendAsyncOnly, En) => "{0}: can only be created by create_best_backend_async()",
+        (BackendAsyncOnly, Ja) => "{0}: create_best_backend_async() でしか作れません",
+        (BackendSelected, En) => "selected {0}",
+        (BackendSelected, Ja) => "{0} を選びました",
+        (QuirkPointSizeClamped, En) => "gl_PointSize is limited to {0}px, point sizes are clamped",
+        (QuirkPointSizeClamped, Ja) => "gl_PointSize の上限が {0}px のため、点の大きさを丸めます",
+        (QuirkSlowFillStyle, En) => "fillStyle parsing is slow, reusing cached colors",
+        (QuirkSlowFillStyle, Ja) => "fillStyle の解析が遅いため、色の文字列を使い回します",
+        (QuirkRafThrottled, En) => "frames are {0}ms apart, requestAnimationFrame is throttled",
+        (QuirkRafThrottled, Ja) => {
+            "フレームの間隔が {0}ms あり、requestAnimationFrame が間引かれています"
+        }
+        (SuiteUnknownCase, En) => "Unknown suite case: {0} (use \"backend\" or \"backend:scene\")",
+        (SuiteUnknownCase, Ja) => {
+            "不明な組です: {0}（\"バックエンド\" か \"バックエンド:シーン\" で指定してください）"
+        }
+        (SuiteCaseInterrupted, En) => "the page was reloaded while this case was running",
+        (SuiteCaseInterrupted, Ja) => "この組の計測中にページが読み込み直されました",
+        (SuiteThermalDrift, En) => {
+            "frame time grew by {0}% during the run, the device may be thermally throttled"
+        }
+        (SuiteThermalDrift, Ja) => {
+            "計測中にフレーム時間が {0}% 増えました。端末の発熱で性能が落ちている可能性があります"
+        }
+        (RunnerUnknownBackend, En) => {
+            "Unknown backend: {0} (use \"webgpu\", \"webgl2\", \"webgl\", \"canvas2d\" or \"software\")"
+        }
+        (RunnerUnknownBackend, Ja) => {
+            "不明なバックエンドです: {0}（\"webgpu\"・\"webgl2\"・\"webgl\"・\"canvas2d\"・\"software\" のいずれかを指定してください）"
+        }
+        (RunnerTooFewInstances, En) => {
+            "Contention needs at least two backends in contention_backends, got {0}"
+        }
+        (RunnerTooFewInstances, Ja) => {
+            "同時実行の計測には contention_backends に2つ以上のバックエンドが必要です（指定は {0} 個）"
+        }
+        (LoopDriverUnavailable, En) => "Loop driver {0} is not available in this browser",
+        (LoopDriverUnavailable, Ja) => "ループの回し方 {0} はこのブラウザでは使えません",
+        (ReportInvalid, En) => "Invalid binary report",
+        (ReportInvalid, Ja) => "バイナリ形式の結果として読めません",
+        (ReportJsonInvalid, En) => "Not a benchmark report in JSON",
+        (ReportJsonInvalid, Ja) => "JSON 形式の結果として読めません",
+        (ReportVersionUnsupported, En) => {
+            "Unsupported report version: {0} (written by a newer version?)"
+        }
+        (ReportVersionUnsupported, Ja) => {
+            "未対応の結果のバージョンです: {0}（新しい版で書き出したものかもしれません）"
+        }
+        (MetadataInvalid, En) => "Metadata must be a JSON object",
+        (MetadataInvalid, Ja) => "メタデータは JSON のオブジェクトで指定してください",
+        (WorkloadInvalid, En) => "Not a recorded workload: {0}",
+        (WorkloadInvalid, Ja) => "操作の記録として読めません: {0}",
+        (WorkloadVersionUnsupported, En) => {
+            "Unsupported workload version: {0} (recorded by a newer version?)"
+        }
+        (WorkloadVersionUnsupported, Ja) => {
+            "未対応の記録のバージョンです: {0}（新しい版で記録したものかもしれません）"
+        }
+        (ScenarioNotLoaded, En) => "No scenario to play (call load_scenario() first)",
+        (ScenarioNotLoaded, Ja) => "再生する記録がありません（先に load_scenario() を呼んでください）",
+        (SnapshotInvalid, En) => "Not a particle snapshot",
+        (SnapshotInvalid, Ja) => "パーティクルの状態として読めません",
+        (SnapshotVersionUnsupported, En) => {
+            "Unsupported snapshot version: {0} (saved by a newer version?)"
+        }
+        (SnapshotVersionUnsupported, Ja) => {
+            "未対応の状態のバージョンです: {0}（新しい版で保存したものかもしれません）"
+        }
+        (AnimationEmpty, En) => "Cannot export a {0}x{1} animation",
+        (AnimationEmpty, Ja) => "{0}x{1} のアニメーションは書き出せません",
+        (DownloadUnavailable, En) => "download_results() can only be used on a page (not in a worker)",
+        (DownloadUnavailable, Ja) => "download_results() はページ上でだけ使えます（Worker では使えません）",
+        (AnimationEncodeFailed, En) => "Failed to encode the animation: {0}",
+        (AnimationEncodeFailed, Ja) => "アニメーションをエンコードできません: {0}",
+        (CaptureEncodeFailed, En) => "Failed to encode the captured buffer: {0}",
+        (CaptureEncodeFailed, Ja) => "取り出したバッファを画像にできません: {0}",
+        (AriaSummary, En) => "{0} particles, {1} fps, scene: {2}",
+        (AriaSummary, Ja) => "パーティクル {0} 個、{1} fps、シーン: {2}",
+    }
+}
+
+pub(crate) fn tr(text: Text, args: &[&dyn Display]) -> String {
+    let mut result = template(text, LOCALE.get()).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        result = result.replace(&format!("{{{}}}", i), &arg.to_string());
+    }
+    result
+}
+
+// UIに表示するシーン名（識別子の SceneKind::name() とは別）
+pub(crate) fn scene_label(kind: SceneKind) -> &'static str {
+    let (en, ja) = match kind {
+        SceneKind::Particles => ("Particles", "パーティクル"),
+        SceneKind::Clear => ("Clear", "画面クリア"),
+        SceneKind::Density => ("Density", "密度バッファ"),
+        SceneKind::Physarum => ("Physarum", "粘菌"),
+        SceneKind::ReactionDiffusion => ("Reaction-diffusion", "反応拡散"),
+        SceneKind::Life => ("Game of Life", "ライフゲーム"),
+        SceneKind::Fractal => ("Fractal (CPU)", "フラクタル (CPU)"),
+        SceneKind::FractalGpu => ("Fractal (GPU)", "フラクタル (GPU)"),
+        SceneKind::Blur => ("Blur", "ぼかし"),
+        SceneKind::Raycaster => ("Raycaster", "レイキャスト"),
+        SceneKind::Skeleton => ("Skeletal sprites", "スケルトン"),
+        SceneKind::Tilemap => ("Tilemap", "タイルマップ"),
+        SceneKind::Bezier => ("Bezier curves", "ベジェ曲線"),
+        SceneKind::Polygons => ("Concave polygons", "凹多角形"),
+        SceneKind::PointSize => ("Point size (fill rate)", "点の大きさ (フィルレート)"),
+        SceneKind::Overdraw => ("Overdraw", "重ね塗り"),
+        SceneKind::OverdrawSpread => ("Overdraw (spread)", "重ね塗り (分散)"),
+        SceneKind::DrawCalls => ("Many small draws", "小さな描画の繰り返し"),
+        SceneKind::DrawCallsBatched => ("Batched draw", "まとめた描画"),
+        SceneKind::TextureUpload => ("Texture upload", "テクスチャ転送"),
+        SceneKind::Readback => ("Readback", "読み戻し"),
+        SceneKind::StateChanges => ("State changes", "状態変更"),
+        SceneKind::Transforms => ("Save/restore transforms", "save/restore での変換"),
+        SceneKind::TransformsPrecomputed => ("Precomputed transforms", "計算済みの変換"),
+        SceneKind::RadialGradients => ("Radial gradients", "放射グラデーション"),
+        SceneKind::Shadows => ("Shadow blur", "影のぼかし"),
+        SceneKind::Clip => ("Clipping", "クリップ"),
+        SceneKind::Compositing => ("Compositing modes", "合成方法"),
+        SceneKind::Vector => ("Vector paths", "ベクターパス"),
+        SceneKind::Quadrants => ("Scissored quadrants", "4分割の描き比べ"),
+    };
+    localized(en, ja)
+}
+
+// UIに表示するパラメーター名
+pub(crate) fn parameter_label(name: &str) -> &'static str {
+    let (en, ja) = match name {
+        "load" => ("Load", "負荷"),
+        "columns" => ("Columns", "列数"),
+        "rows" => ("Rows", "行数"),
+        "method" => ("Method", "方式"),
+        "radius" => ("Radius", "半径"),
+        "clip_regions" => ("Clip regions", "クリップする領域の数"),
+        "shadow_blur" => ("Shadow blur (px)", "影のぼかし(px)"),
+        "change_every" => (
+            "State change every N rects",
+            "状態を切り替える間隔（矩形の数）",
+        ),
+        _ => ("", ""),
+    };
+    localized(en, ja)
+}
+
+fn localized(en: &'static str, ja: &'static str) -> &'static str {
+    match LOCALE.get() {
+        Locale::En => en,
+        Locale::Ja => ja,
+    }
+}
@@ -0,0 +1,134 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    Blob, HtmlImageElement, ImageBitmap, ImageData, OffscreenCanvas,
+    OffscreenCanvasRenderingContext2d, Url,
+};
+
+use crate::i18n::{tr, Text};
+use crate::scene::Atlas;
+
+// スプライトのアトラスを描画先に渡すときの画像の形
+// ImageBitmap と HTMLImageElement はブラウザごとにデコードと転送の経路が違うので、
+// 同じ絵を渡し方だけ変えて転送と描画のコストを比べる
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ImageSource {
+    // RGBA のピクセル列のまま（WebGL は配列で texImage2D、Canvas2D は putImageData したキャンバス）
+    Pixels = 0,
+    // createImageBitmap() で作った ImageBitmap
+    ImageBitmap = 1,
+    // PNG の Blob の URL から読み込んだ <img>
+    ImageElement = 2,
+}
+
+impl ImageSource {
+    pub const ALL: [ImageSource; 3] = [
+        ImageSource::Pixels,
+        ImageSource::ImageBitmap,
+        ImageSource::ImageElement,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ImageSource::Pixels => "pixels",
+            ImageSource::ImageBitmap => "image_bitmap",
+            ImageSource::ImageElement => "image_element",
+        }
+    }
+}
+
+// アトラスをデコードした画像（Atlas::set_image で渡す）
+#[derive(Clone, Debug)]
+pub(crate) enum DecodedImage {
+    Bitmap(ImageBitmap),
+    Element(HtmlImageElement),
+}
+
+impl DecodedImage {
+    // JS から渡された ImageBitmap か HTMLImageElement（どちらでもなければ None）
+    pub(crate) fn from_js(value: &JsValue) -> Option<DecodedImage> {
+        if let Some(bitmap) = value.dyn_ref::<ImageBitmap>() {
+            return Some(DecodedImage::Bitmap(bitmap.clone()));
+        }
+        value
+            .dyn_ref::<HtmlImageElement>()
+            .map(|element| DecodedImage::Element(element.clone()))
+    }
+
+    // (幅, 高さ)（<img> は表示の大きさではなく元の画像の大きさ）
+    pub(crate) fn size(&self) -> (u32, u32) {
+        match self {
+            DecodedImage::Bitmap(bitmap) => (bitmap.width(), bitmap.height()),
+            DecodedImage::Element(element) => (element.natural_width(), element.natural_height()),
+        }
+    }
+}
+
+// image を width × height に伸び縮みさせて RGBA のピクセル列（1行目が上端）で読む
+// 別オリジンの画像はキャンバスが汚染されて読めないのでエラーになる
+pub(crate) fn read_pixels(
+    image: &DecodedImage,
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, JsValue> {
+    let canvas = OffscreenCanvas::new(width, height)?;
+    let ctx = canvas
+        .get_context("2d")?
+        .ok_or_else(|| JsValue::from(tr(Text::SpriteAtlasFailed, &[])))?
+        .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+    let (w, h) = (width as f64, height as f64);
+    match image {
+        DecodedImage::Bitmap(bitmap) => {
+            ctx.draw_image_with_image_bitmap_and_dw_and_dh(bitmap, 0.0, 0.0, w, h)?
+        }
+        DecodedImage::Element(element) => {
+            ctx.draw_image_with_html_image_element_and_dw_and_dh(element, 0.0, 0.0, w, h)?
+        }
+    }
+    Ok(ctx.get_image_data(0.0, 0.0, w, h)?.data().0)
+}
+
+// atlas のピクセル列から source の形の画像を作る（Pixels なら None）
+pub(crate) async fn decode(
+    atlas: &Atlas,
+    source: ImageSource,
+) -> Result<Option<DecodedImage>, JsValue> {
+    if source == ImageSource::Pixels {
+        return Ok(None);
+    }
+    let failed = || JsValue::from(tr(Text::ImageDecodeFailed, &[&source.name()]));
+    let image_data = ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(&atlas.pixels),
+        atlas.width,
+        atlas.height,
+    )?;
+    match source {
+        ImageSource::ImageBitmap => {
+            let window = web_sys::window().ok_or_else(failed)?;
+            let promise = window.create_image_bitmap_with_image_data(&image_data)?;
+            let bitmap = JsFuture::from(promise).await?;
+            Ok(Some(DecodedImage::Bitmap(bitmap.dyn_into()?)))
+        }
+        _ => {
+            // 一度 PNG にして、<img> にデコードさせる
+            let canvas = OffscreenCanvas::new(atlas.width, atlas.height)?;
+            let ctx = canvas
+                .get_context("2d")?
+                .ok_or_else(failed)?
+                .dyn_into::<OffscreenCanvasRenderingContext2d>()?;
+            ctx.put_image_data(&image_data, 0.0, 0.0)?;
+            let blob: Blob = JsFuture::from(canvas.convert_to_blob()?)
+                .await?
+                .dyn_into()?;
+            let url = Url::create_object_url_with_blob(&blob)?;
+            let element = HtmlImageElement::new()?;
+            element.set_src(&url);
+            let decoded = JsFuture::from(element.decode()).await;
+            Url::revoke_object_url(&url)?;
+            decoded?;
+            Ok(Some(DecodedImage::Element(element)))
+        }
+    }
+}
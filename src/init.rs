@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+
+use crate::memory::DEFAULT_MEMORY_BUDGET;
+use crate::physics::SimulationConfig;
+use crate::timing;
+use crate::viewport::Viewport;
+
+// コンストラクタの共通オプション
+#[derive(Clone, Copy)]
+pub(crate) struct BuildOptions {
+    pub memory_budget: u64,
+    // true ならパーティクルを生成せずに返し、init_chunk() で生成する
+    pub progressive: bool,
+    // 共有コンテキスト内の描画領域（None ならキャンバス全体）
+    pub viewport: Option<Viewport>,
+    // true なら機能が足りないときに代替経路で描かずエラーにする
+    pub strict: bool,
+    // 重力・跳ね返り・摩擦・点の大きさ
+    pub simulation: SimulationConfig,
+    // Some ならパーティクルの初期状態をこのシードから作る
+    pub seed: Option<u64>,
+}
+
+impl Default for BuildOptions {
+    fn default() -> BuildOptions {
+        BuildOptions {
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            progressive: false,
+            viewport: None,
+            strict: false,
+            simulation: SimulationConfig::default(),
+            seed: None,
+        }
+    }
+}
+
+// コンストラクタの各段階にかかった時間(ms)
+// 起動コストもWASM vs JSの比較対象なので段階ごとに記録する
+#[wasm_bindgen]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct InitTimings {
+    pub context_ms: f64,
+    pub shader_ms: f64,
+    pub buffers_ms: f64,
+    // 段階的初期化では init_chunk() の時間も加算される
+    pub particles_ms: f64,
+    pub constructor_ms: f64,
+    // シェーダープログラムをキャッシュから再利用できた数
+    pub shader_cache_hits: u32,
+    // まだ完成フレームを描いていなければ NaN
+    pub time_to_first_frame_ms: f64,
+}
+
+// 初期化の進捗と、生成開始から最初の完成フレームまでの時間
+pub(crate) struct InitProgress {
+    started_at: f64,
+    lap_started_at: f64,
+    callback: Option<js_sys::Function>,
+    time_to_first_frame: Option<f64>,
+    pub timings: InitTimings,
+}
+
+impl InitProgress {
+    pub fn start() -> InitProgress {
+        let now = timing::now_ms();
+        InitProgress {
+            started_at: now,
+            lap_started_at: now,
+            callback: None,
+            time_to_first_frame: None,
+            timings: InitTimings::default(),
+        }
+    }
+
+    // 前回の lap() からの経過時間(ms)
+    pub fn lap(&mut self) -> f64 {
+        let now = timing::now_ms();
+        let elapsed = now - self.lap_started_at;
+        self.lap_started_at = now;
+        elapsed
+    }
+
+    pub fn finish_constructor(&mut self) {
+        self.timings.constructor_ms = timing::now_ms() - self.started_at;
+    }
+
+    pub fn timings(&self) -> InitTimings {
+        InitTimings {
+            time_to_first_frame_ms: self.time_to_first_frame(),
+            ..self.timings
+        }
+    }
+
+    pub fn set_callback(&mut self, callback: js_sys::Function) {
+        self.callback = Some(callback);
+    }
+
+    // コールバックには (進捗 0.0~1.0, 生成済み数, 目標数) を渡す
+    pub fn report(&self, spawned: usize, total: usize) {
+        if let Some(callback) = &self.callback {
+            let _ = callback.call3(
+                &JsValue::NULL,
+                &JsValue::from_f64(progress(spawned, total) as f64),
+                &JsValue::from_f64(spawned as f64),
+                &JsValue::from_f64(total as f64),
+            );
+        }
+    }
+
+    // 全パーティクルが揃った状態で最初に描画した時刻を記録する
+    pub fn mark_frame(&mut self, complete: bool) {
+        if complete && self.time_to_first_frame.is_none() {
+            self.time_to_first_frame = Some(timing::now_ms() - self.started_at);
+        }
+    }
+
+    // まだ完成フレームを描いていなければ NaN
+    pub fn time_to_first_frame(&self) -> f64 {
+        self.time_to_first_frame.unwrap_or(f64::NAN)
+    }
+}
+
+pub(crate) fn progress(spawned: usize, total: usize) -> f32 {
+    if total == 0 {
+        1.0
+    } else {
+        spawned as f32 / total as f32
+    }
+}
@@ -0,0 +1,328 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    AddEventListenerOptions, Event, HtmlCanvasElement, MouseEvent, PointerEvent, TouchEvent,
+    TouchList, WheelEvent,
+};
+
+use crate::camera::Camera;
+use crate::i18n::{tr, Text};
+
+// ホイールを 100px 回したときの拡大率と、行単位で回ったときの1行の高さ(px)
+const WHEEL_ZOOM_STEP: f32 = 1.1;
+const WHEEL_LINE_PX: f64 = 40.0;
+// 押してから離すまでにこれ以上動かしたらクリックではなくドラッグとみなす(px)
+const CLICK_SLOP: f32 = 4.0;
+
+// 入力から決まった、シミュレーションに対する操作（座標はシミュレーションの座標）
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum InputAction {
+    Explode { x: f32, y: f32 },
+    Stir { x: f32, y: f32, dx: f32, dy: f32 },
+}
+
+// イベントのコールバックが積んでおくもの（座標は画面の座標）
+#[derive(Clone, Copy, Debug)]
+enum Gesture {
+    Explode { x: f32, y: f32 },
+    Stir { x: f32, y: f32, dx: f32, dy: f32 },
+    Pan { dx: f32, dy: f32 },
+    Zoom { factor: f32, x: f32, y: f32 },
+}
+
+// 画面に重ねた UI（コントロールパネル）に向けたポインターの状態（座標は画面の座標）
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct UiPointer {
+    pub x: f32,
+    pub y: f32,
+    // UI の領域で押してから離すまで true
+    pub down: bool,
+    // 前に take_ui_pointer() してから UI の領域で押した
+    pub pressed: bool,
+}
+
+// 押しているポインター
+struct Drag {
+    id: i32,
+    last: (f32, f32),
+    // 押してから動かした距離の合計(px)
+    moved: f32,
+    // シフトを押しながら・中ボタン・右ボタンならかき混ぜずに視点を動かす
+    pan: bool,
+    // UI の領域で押した（シミュレーションには何もしない）
+    ui: bool,
+}
+
+// 2本指でつまんでいる間の、直前の指の間隔と中点
+struct Pinch {
+    distance: f32,
+    center: (f32, f32),
+}
+
+#[derive(Default)]
+struct PointerState {
+    gestures: Vec<Gesture>,
+    drag: Option<Drag>,
+    pinch: Option<Pinch>,
+    // UI の領域 (x, y, 幅, 高さ)。この中で押した操作はシミュレーションに渡さない
+    ui_region: Option<(f32, f32, f32, f32)>,
+    ui: UiPointer,
+}
+
+type Listener = Closure<dyn FnMut(Event)>;
+
+// キャンバスに登録した入力のイベントのコールバック（外すと登録を取り消す）
+// 主ボタンのクリック（タップ）で爆発、ドラッグでかき混ぜ、シフト＋ドラッグ・中ボタン・右ボタンのドラッグで視点の移動、
+// ホイールと2本指のピンチで拡大縮小する
+// コールバックは操作を積んでおくだけで、実際に動かすのは次の update()
+pub(crate) struct InputHandlers {
+    canvas: HtmlCanvasElement,
+    state: Rc<RefCell<PointerState>>,
+    listeners: Vec<(&'static str, Listener)>,
+}
+
+impl InputHandlers {
+    // canvas（HtmlCanvasElement でなければエラー）にコールバックを登録する
+    pub fn attach(canvas: &JsValue) -> Result<InputHandlers, JsValue> {
+        let canvas = canvas
+            .dyn_ref::<HtmlCanvasElement>()
+            .ok_or_else(|| tr(Text::InputUnavailable, &[]))?
+            .clone();
+        let state = Rc::new(RefCell::new(PointerState::default()));
+        let mut handlers = InputHandlers {
+            canvas,
+            state,
+            listeners: Vec::new(),
+        };
+        handlers.listen("pointerdown", on_pointer_down)?;
+        handlers.listen("pointermove", on_pointer_move)?;
+        handlers.listen("pointerup", on_pointer_up)?;
+        handlers.listen("pointercancel", on_pointer_up)?;
+        handlers.listen("wheel", on_wheel)?;
+        handlers.listen("touchstart", on_touch)?;
+        handlers.listen("touchmove", on_touch)?;
+        handlers.listen("touchend", on_touch)?;
+        handlers.listen("touchcancel", on_touch)?;
+        handlers.listen("contextmenu", |_, _, event| event.prevent_default())?;
+        // ブラウザのスクロールやピンチで pointercancel されないようにする
+        let _ = handlers.canvas.style().set_property("touch-action", "none");
+        Ok(handlers)
+    }
+
+    fn listen(
+        &mut self,
+        kind: &'static str,
+        handler: fn(&HtmlCanvasElement, &mut PointerState, &Event),
+    ) -> Result<(), JsValue> {
+        let (canvas, state) = (self.canvas.clone(), self.state.clone());
+        let callback = Listener::new(move |event: Event| {
+            handler(&canvas, &mut state.borrow_mut(), &event);
+        });
+        // ホイールとタッチのページのスクロールを止められるように passive にしない
+        let options = AddEventListenerOptions::new();
+        options.set_passive(false);
+        self.canvas
+            .add_event_listener_with_callback_and_add_event_listener_options(
+                kind,
+                callback.as_ref().unchecked_ref(),
+                &options,
+            )?;
+        self.listeners.push((kind, callback));
+        Ok(())
+    }
+
+    // 積まれた操作を取り出す。視点の操作は camera に反映し、残りをシミュレーションの座標にして返す
+    pub fn take_actions(&self, camera: &mut Camera) -> Vec<InputAction> {
+        let gestures = std::mem::take(&mut self.state.borrow_mut().gestures);
+        let mut actions = Vec::new();
+        for gesture in gestures {
+            match gesture {
+                Gesture::Explode { x, y } => {
+                    let (x, y) = camera.to_world(x, y);
+                    actions.push(InputAction::Explode { x, y });
+                }
+                Gesture::Stir { x, y, dx, dy } => {
+                    let (x, y) = camera.to_world(x, y);
+                    let (dx, dy) = (dx / camera.zoom, dy / camera.zoom);
+                    actions.push(InputAction::Stir { x, y, dx, dy });
+                }
+                Gesture::Pan { dx, dy } => camera.pan(dx, dy),
+                Gesture::Zoom { factor, x, y } => camera.zoom_at(factor, x, y),
+            }
+        }
+        actions
+    }
+
+    // 画面に重ねる UI の領域を設定する（None なら UI なし）
+    pub fn set_ui_region(&self, region: Option<(f32, f32, f32, f32)>) {
+        let mut state = self.state.borrow_mut();
+        state.ui_region = region;
+        if region.is_none() {
+            state.ui = UiPointer::default();
+        }
+    }
+
+    // UI に向けたポインターの状態を取り出す（押したことは1回だけ返す）
+    pub fn take_ui_pointer(&self) -> UiPointer {
+        let mut state = self.state.borrow_mut();
+        let pointer = state.ui;
+        state.ui.pressed = false;
+        pointer
+    }
+}
+
+impl Drop for InputHandlers {
+    fn drop(&mut self) {
+        for (kind, callback) in &self.listeners {
+            let _ = self
+                .canvas
+                .remove_event_listener_with_callback(kind, callback.as_ref().unchecked_ref());
+        }
+        let _ = self.canvas.style().remove_property("touch-action");
+    }
+}
+
+fn pointer_position(event: &MouseEvent) -> (f32, f32) {
+    (event.offset_x() as f32, event.offset_y() as f32)
+}
+
+fn on_pointer_down(canvas: &HtmlCanvasElement, state: &mut PointerState, event: &Event) {
+    let Some(event) = event.dyn_ref::<PointerEvent>() else {
+        return;
+    };
+    // ピンチ中や、もう別のポインターで押しているときは無視する
+    if state.pinch.is_some() || state.drag.is_some() {
+        return;
+    }
+    let (x, y) = pointer_position(event);
+    let ui = state.ui_region.is_some_and(|(left, top, width, height)| {
+        x >= left && x < left + width && y >= top && y < top + height
+    });
+    let pan = match event.button() {
+        0 => !ui && event.shift_key(),
+        1 | 2 if !ui => true,
+        _ => return,
+    };
+    if ui {
+        state.ui = UiPointer {
+            x,
+            y,
+            down: true,
+            pressed: true,
+        };
+    }
+    // キャンバスの外に出ても pointerup まで受け取る
+    let _ = canvas.set_pointer_capture(event.pointer_id());
+    state.drag = Some(Drag {
+        id: event.pointer_id(),
+        last: (x, y),
+        moved: 0.0,
+        pan,
+        ui,
+    });
+}
+
+fn on_pointer_move(_: &HtmlCanvasElement, state: &mut PointerState, event: &Event) {
+    let Some(event) = event.dyn_ref::<PointerEvent>() else {
+        return;
+    };
+    // UI はボタンの上にあるかどうかも見るので、押していなくても位置を追う
+    let (x, y) = pointer_position(event);
+    (state.ui.x, state.ui.y) = (x, y);
+    let Some(drag) = state
+        .drag
+        .as_mut()
+        .filter(|drag| drag.id == event.pointer_id() && !drag.ui)
+    else {
+        return;
+    };
+    let (dx, dy) = (x - drag.last.0, y - drag.last.1);
+    drag.last = (x, y);
+    drag.moved += (dx * dx + dy * dy).sqrt();
+    if drag.pan {
+        state.gestures.push(Gesture::Pan { dx, dy });
+    } else if drag.moved > CLICK_SLOP {
+        state.gestures.push(Gesture::Stir { x, y, dx, dy });
+    }
+}
+
+fn on_pointer_up(_: &HtmlCanvasElement, state: &mut PointerState, event: &Event) {
+    let Some(event) = event.dyn_ref::<PointerEvent>() else {
+        return;
+    };
+    let Some(drag) = state.drag.take_if(|drag| drag.id == event.pointer_id()) else {
+        return;
+    };
+    if drag.ui {
+        state.ui.down = false;
+        return;
+    }
+    // pointercancel（スクロールなどに取られた）のときは爆発させない
+    if event.type_() == "pointerup" && !drag.pan && drag.moved <= CLICK_SLOP {
+        let (x, y) = pointer_position(event);
+        state.gestures.push(Gesture::Explode { x, y });
+    }
+}
+
+fn on_wheel(_: &HtmlCanvasElement, state: &mut PointerState, event: &Event) {
+    let Some(event) = event.dyn_ref::<WheelEvent>() else {
+        return;
+    };
+    event.prevent_default();
+    let delta = match event.delta_mode() {
+        WheelEvent::DOM_DELTA_PIXEL => event.delta_y(),
+        _ => event.delta_y() * WHEEL_LINE_PX,
+    };
+    // 下に回すと縮小する
+    let factor = WHEEL_ZOOM_STEP.powf(-(delta as f32) / 100.0);
+    let (x, y) = pointer_position(event);
+    state.gestures.push(Gesture::Zoom { factor, x, y });
+}
+
+// 2本指のタッチだけを扱う（1本指はポインターのイベントで扱う）
+fn on_touch(canvas: &HtmlCanvasElement, state: &mut PointerState, event: &Event) {
+    let Some(event) = event.dyn_ref::<TouchEvent>() else {
+        return;
+    };
+    let touches = event.touches();
+    let Some(pinch) = pinch_of(canvas, &touches) else {
+        state.pinch = None;
+        return;
+    };
+    event.prevent_default();
+    // 2本目の指が触れたら、1本目で始めたかき混ぜはやめる（爆発もさせない）
+    state.drag = None;
+    if let Some(last) = &state.pinch {
+        if last.distance > 0.0 {
+            state.gestures.push(Gesture::Zoom {
+                factor: pinch.distance / last.distance,
+                x: pinch.center.0,
+                y: pinch.center.1,
+            });
+        }
+        state.gestures.push(Gesture::Pan {
+            dx: pinch.center.0 - last.center.0,
+            dy: pinch.center.1 - last.center.1,
+        });
+    }
+    state.pinch = Some(pinch);
+}
+
+// 最初の2本の指の間隔と中点（キャンバスの左上からの座標）。2本未満なら None
+fn pinch_of(canvas: &HtmlCanvasElement, touches: &TouchList) -> Option<Pinch> {
+    let (first, second) = (touches.get(0)?, touches.get(1)?);
+    let rect = canvas.get_bounding_client_rect();
+    let position = |touch: &web_sys::Touch| {
+        (
+            (touch.client_x() as f64 - rect.left()) as f32,
+            (touch.client_y() as f64 - rect.top()) as f32,
+        )
+    };
+    let (a, b) = (position(&first), position(&second));
+    Some(Pinch {
+        distance: ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt(),
+        center: ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0),
+    })
+}
@@ -0,0 +1,104 @@
+use crate::math;
+use crate::simulation::Particle;
+
+// ポインターの力が届く範囲(px)
+const POINTER_RADIUS: f32 = 100.0;
+// ポインターが動いた量(px)のうち、中心のパーティクルの速度に移す割合
+const POINTER_MOMENTUM: f32 = 0.25;
+// 動いた量(px)のうち、ポインターの周りに巻く渦の速さにする割合
+const POINTER_SWIRL: f32 = 0.15;
+// 次の update() までにためておく数（止めている間に動かし続けても増え続けないように）
+const MAX_PENDING: usize = 64;
+
+// pointer_move() と pointer_drag() で受け取ったポインターの動き
+// 次の update() の最初に1回だけ、通り道の近くのパーティクルへ勢いを渡す
+// 中心から押し出す explode() と違い、動かした向きと速さに応じて流れと渦ができる
+#[derive(Clone, Default, Debug)]
+pub(crate) struct PointerForces {
+    // 直前に受け取った位置（pointer_drag() の動いた量を求める）
+    last: Option<(f32, f32)>,
+    pending: Vec<Impulse>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Impulse {
+    x: f32,
+    y: f32,
+    dx: f32,
+    dy: f32,
+    strength: f32,
+}
+
+impl PointerForces {
+    // (x, y) を (dx, dy)(px) 動いたところとして勢いを渡す（NaN などは無視する）
+    pub fn push_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.push(x, y, dx, dy, 1.0);
+    }
+
+    // 前に受け取った位置から (x, y) まで動いたとして strength 倍の勢いを渡す
+    // 最初の1回は動いた量が分からないので位置だけ覚える
+    pub fn push_drag(&mut self, x: f32, y: f32, strength: f32) {
+        match self.last {
+            Some((last_x, last_y)) => self.push(x, y, x - last_x, y - last_y, strength),
+            None if x.is_finite() && y.is_finite() => self.last = Some((x, y)),
+            None => {}
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.last = None;
+        self.pending.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    // ためた動きを取り出す
+    pub fn take(&mut self) -> Vec<Impulse> {
+        std::mem::take(&mut self.pending)
+    }
+
+    fn push(&mut self, x: f32, y: f32, dx: f32, dy: f32, strength: f32) {
+        if ![x, y, dx, dy, strength]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return;
+        }
+        self.last = Some((x, y));
+        if self.pending.len() < MAX_PENDING && (dx, dy) != (0.0, 0.0) && strength != 0.0 {
+            self.pending.push(Impulse {
+                x,
+                y,
+                dx,
+                dy,
+                strength,
+            });
+        }
+    }
+}
+
+impl Impulse {
+    // 中心ほど強く（離れるほど2乗で弱まる）、動いた向きへの押し流しとポインターの周りの渦を足す
+    // 渦は strength が正なら画面で時計回り、負なら反時計回りで、速さは動いた距離に比例する
+    pub fn apply(&self, p: &mut Particle) {
+        let (ox, oy) = (p.x - self.x, p.y - self.y);
+        let dist = math::sqrt(ox * ox + oy * oy);
+        if dist >= POINTER_RADIUS {
+            return;
+        }
+        let falloff = (1.0 - dist / POINTER_RADIUS) * (1.0 - dist / POINTER_RADIUS);
+        let push = POINTER_MOMENTUM * falloff * self.strength.abs();
+        p.vx += self.dx * push;
+        p.vy += self.dy * push;
+        // 中心にいるパーティクルは渦の向きが決まらないので押し流すだけ
+        if dist < 1e-3 {
+            return;
+        }
+        let speed = math::sqrt(self.dx * self.dx + self.dy * self.dy);
+        let swirl = POINTER_SWIRL * falloff * self.strength * speed / dist;
+        p.vx -= oy * swirl;
+        p.vy += ox * swirl;
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::memory;
+use crate::stats::{self, Aggregation};
+use crate::timing;
+
+// フレーム時間の急増（カクつき）の原因の推定
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JankKind {
+    // 直前にJSのヒープが減った（GCが走った）か、WASMのメモリが増えた（memory.grow のコピー）
+    GcPause = 0,
+    // update() + render() 自体が遅かった（WASMの処理や描画コマンドの発行）
+    RenderStall = 1,
+    // フレームの処理は普段どおりで、フレームとフレームの間が空いた（ブラウザや他のJS）
+    HostStall = 2,
+}
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JankReport {
+    pub frames: u32,
+    pub spikes: u32,
+    pub gc_pauses: u32,
+    pub render_stalls: u32,
+    pub host_stalls: u32,
+}
+
+// 直近何フレームの中央値を普段の値とするか
+const WINDOW: usize = 60;
+// 普段の何倍、かつ何ms以上長ければ急増とみなすか
+const SPIKE_RATIO: f64 = 2.0;
+const SPIKE_MIN_MS: f64 = 4.0;
+// JSのヒープがこれ以上減っていたらGCが走ったとみなす
+const HEAP_DROP_BYTES: f64 = 1024.0 * 1024.0;
+
+// begin_frame() と end_frame() で update() + render() を挟んで毎フレーム呼ぶ
+// フレームの処理時間・前のフレームからの間隔・メモリの変化から、急増したフレームの原因を推定する
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct JankClassifier {
+    report: JankReport,
+    works: VecDeque<f64>,
+    intervals: VecDeque<f64>,
+    // begin_frame() の時刻と、その時点のメモリ
+    frame_start: Option<f64>,
+    interval_ms: Option<f64>,
+    wasm_bytes: u64,
+    heap_bytes: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl JankClassifier {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JankClassifier {
+        JankClassifier::default()
+    }
+
+    pub fn begin_frame(&mut self) {
+        let now = timing::now_ms();
+        self.interval_ms = self.frame_start.replace(now).map(|last| now - last);
+        // GCやメモリの増加はフレームの間にも起きるので、前のフレームの終わりからの変化を見る
+        if self.report.frames == 0 {
+            self.wasm_bytes = memory::wasm_memory_bytes();
+            self.heap_bytes = memory::js_heap_bytes();
+        }
+    }
+
+    // 急増したフレームならその原因を返す
+    pub fn end_frame(&mut self) -> Option<JankKind> {
+        let start = self.frame_start?;
+        let work = timing::now_ms() - start;
+        self.report.frames += 1;
+
+        let (wasm_bytes, heap_bytes) = (memory::wasm_memory_bytes(), memory::js_heap_bytes());
+        let memory_event = wasm_bytes > self.wasm_bytes
+            || matches!((self.heap_bytes, heap_bytes), (Some(before), Some(after)) if before - after > HEAP_DROP_BYTES);
+        self.wasm_bytes = wasm_bytes;
+        self.heap_bytes = heap_bytes;
+
+        let work_spike = is_spike(work, &self.works);
+        let interval_spike = self
+            .interval_ms
+            .is_some_and(|interval| is_spike(interval, &self.intervals));
+        push_window(&mut self.works, work);
+        if let Some(interval) = self.interval_ms {
+            push_window(&mut self.intervals, interval);
+        }
+
+        if !work_spike && !interval_spike {
+            return None;
+        }
+        let kind = if memory_event {
+            JankKind::GcPause
+        } else if work_spike {
+            JankKind::RenderStall
+        } else {
+            JankKind::HostStall
+        };
+        self.report.spikes += 1;
+        match kind {
+            JankKind::GcPause => self.report.gc_pauses += 1,
+            JankKind::RenderStall => self.report.render_stalls += 1,
+            JankKind::HostStall => self.report.host_stalls += 1,
+        }
+        Some(kind)
+    }
+
+    pub fn get_report(&self) -> JankReport {
+        self.report
+    }
+
+    pub fn reset(&mut self) {
+        *self = JankClassifier::default();
+    }
+}
+
+// 普段の値が分かるまで（WINDOW の半分に満たないうち）は判定しない
+fn is_spike(value: f64, window: &VecDeque<f64>) -> bool {
+    if window.len() < WINDOW / 2 {
+        return false;
+    }
+    let samples: Vec<f64> = window.iter().copied().collect();
+    let usual = stats::summarize(&samples, Aggregation::Median).center;
+    value > usual * SPIKE_RATIO && value - usual > SPIKE_MIN_MS
+}
+
+fn push_window(window: &mut VecDeque<f64>, value: f64) {
+    if window.len() == WINDOW {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
@@ -0,0 +1,439 @@
+// 結果を JSON で書き出すための最小限の組み立てと、読み込む JSON（記録の設定・古い結果）の解析
+// 出力するのは数値・文字列・真偽値とその入れ子だけなので、serde は使わずに済ませる
+// 読み込みもブラウザの JSON.parse を使わずにここで行い、ネイティブの fuzz でも同じ処理を通す
+
+// 入れ子の深さの上限（深すぎる入力で再帰が溢れないように）
+const MAX_DEPTH: usize = 128;
+
+pub(crate) struct JsonObject {
+    text: String,
+}
+
+impl JsonObject {
+    pub fn new() -> JsonObject {
+        JsonObject {
+            text: String::from("{"),
+        }
+    }
+
+    pub fn number(self, key: &str, value: f64) -> JsonObject {
+        self.raw(key, &number(value))
+    }
+
+    pub fn string(self, key: &str, value: &str) -> JsonObject {
+        self.raw(key, &escape(value))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn optional_string(self, key: &str, value: Option<&str>) -> JsonObject {
+        match value {
+            Some(value) => self.string(key, value),
+            None => self.raw(key, "null"),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn boolean(self, key: &str, value: bool) -> JsonObject {
+        self.raw(key, if value { "true" } else { "false" })
+    }
+
+    // value は JSON として正しいこと（入れ子のオブジェクトや配列）
+    pub fn raw(mut self, key: &str, value: &str) -> JsonObject {
+        if self.text.len() > 1 {
+            self.text.push(',');
+        }
+        self.text.push_str(&escape(key));
+        self.text.push(':');
+        self.text.push_str(value);
+        self
+    }
+
+    pub fn finish(mut self) -> String {
+        self.text.push('}');
+        self.text
+    }
+}
+
+// items はそれぞれ JSON として正しいこと
+pub(crate) fn array(items: impl IntoIterator<Item = String>) -> String {
+    let items: Vec<String> = items.into_iter().collect();
+    format!("[{}]", items.join(","))
+}
+
+// 読み込んだ JSON の値
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    // キーは書かれた順のまま持つ（同じキーが重なったら JSON.parse と同じく後の値を使う）
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    // オブジェクトでなければ None
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    // オブジェクトでなければ何もしない
+    pub fn set(&mut self, key: &str, value: Value) {
+        if let Value::Object(entries) = self {
+            match entries.iter_mut().find(|(name, _)| name == key) {
+                Some((_, current)) => *current = value,
+                None => entries.push((key.to_string(), value)),
+            }
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        match self {
+            Value::Null => "null".to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Number(value) => number(*value),
+            Value::String(value) => escape(value),
+            Value::Array(items) => array(items.iter().map(Value::to_json)),
+            Value::Object(entries) => entries
+                .iter()
+                .fold(JsonObject::new(), |object, (key, value)| {
+                    object.raw(key, &value.to_json())
+                })
+                .finish(),
+        }
+    }
+}
+
+// JSON として読めなければ None（前後の空白のほかに余計な文字があっても None）
+pub(crate) fn parse(text: &str) -> Option<Value> {
+    let mut parser = Parser { text, pos: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    (parser.pos == text.len()).then_some(value)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    // 次に読むバイトの位置
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn value(&mut self, depth: usize) -> Option<Value> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.skip_whitespace();
+        match self.peek()? {
+            b'{' => self.object(depth),
+            b'[' => self.array(depth),
+            b'"' => self.string().map(Value::String),
+            b'-' | b'0'..=b'9' => self.number().map(Value::Number),
+            _ => {
+                for (word, value) in [
+                    ("null", Value::Null),
+                    ("true", Value::Bool(true)),
+                    ("false", Value::Bool(false)),
+                ] {
+                    if self.text[self.pos..].starts_with(word) {
+                        self.pos += word.len();
+                        return Some(value);
+                    }
+                }
+                None
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<Value> {
+        self.pos += 1;
+        let mut object = Value::Object(Vec::new());
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Some(object);
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek()? != b'"' {
+                return None;
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.eat(b':') {
+                return None;
+            }
+            let value = self.value(depth + 1)?;
+            object.set(&key, value);
+            self.skip_whitespace();
+            if self.eat(b'}') {
+                return Some(object);
+            }
+            if !self.eat(b',') {
+                return None;
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<Value> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b']') {
+            return Some(Value::Array(items));
+        }
+        loop {
+            items.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            if self.eat(b']') {
+                return Some(Value::Array(items));
+            }
+            if !self.eat(b',') {
+                return None;
+            }
+        }
+    }
+
+    // 先頭の '"' から閉じる '"' まで
+    fn string(&mut self) -> Option<String> {
+        self.pos += 1;
+        let mut value = String::new();
+        loop {
+            let start = self.pos;
+            while !matches!(self.peek()?, b'"' | b'\\' | 0..=0x1f) {
+                self.pos += 1;
+            }
+            value.push_str(&self.text[start..self.pos]);
+            match self.bump()? {
+                b'"' => return Some(value),
+                b'\\' => {}
+                _ => return None,
+            }
+            let escaped = match self.bump()? {
+                b'"' => '"',
+                b'\\' => '\\',
+                b'/' => '/',
+                b'b' => '\u{8}',
+                b'f' => '\u{c}',
+                b'n' => '\n',
+                b'r' => '\r',
+                b't' => '\t',
+                b'u' => self.unicode_escape()?,
+                _ => return None,
+            };
+            value.push(escaped);
+        }
+    }
+
+    // \u の後の4桁。サロゲートペアはまとめて1文字にし、対にならないものは U+FFFD にする
+    fn unicode_escape(&mut self) -> Option<char> {
+        let high = self.hex4()?;
+        if !(0xd800..0xdc00).contains(&high) {
+            return Some(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+        }
+        let rest = &self.text[self.pos..];
+        if rest.starts_with("\\u") {
+            let saved = self.pos;
+            self.pos += 2;
+            let low = self.hex4()?;
+            if (0xdc00..0xe000).contains(&low) {
+                return char::from_u32(0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00));
+            }
+            self.pos = saved;
+        }
+        Some(char::REPLACEMENT_CHARACTER)
+    }
+
+    fn hex4(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.pos..self.pos + 4)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.pos += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    // -?(0|[1-9][0-9]*)(.[0-9]+)?([eE][+-]?[0-9]+)?（大きすぎる値は JSON.parse と同じく無限大になる）
+    fn number(&mut self) -> Option<f64> {
+        let start = self.pos;
+        self.eat(b'-');
+        if !self.eat(b'0') && self.digits() == 0 {
+            return None;
+        }
+        if self.eat(b'.') && self.digits() == 0 {
+            return None;
+        }
+        if self.eat(b'e') || self.eat(b'E') {
+            if !self.eat(b'+') {
+                self.eat(b'-');
+            }
+            if self.digits() == 0 {
+                return None;
+            }
+        }
+        self.text[start..self.pos].parse().ok()
+    }
+
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t' | b'\n' | b'\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        let matched = self.peek() == Some(byte);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+}
+
+// NaN や無限大は JSON で表せないので null にする
+fn number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_values_and_escapes() {
+        let value =
+            parse(r#" {"a": [1, -2.5e1, true, null], "b": {"c": "x\"\u00e9\ud83d\ude00"}} "#)
+                .expect("valid JSON");
+        assert_eq!(
+            value.get("a").and_then(Value::as_array),
+            Some(
+                &[
+                    Value::Number(1.0),
+                    Value::Number(-25.0),
+                    Value::Bool(true),
+                    Value::Null
+                ][..]
+            )
+        );
+        assert_eq!(
+            value
+                .get("b")
+                .and_then(|b| b.get("c"))
+                .and_then(Value::as_str),
+            Some("x\"é😀")
+        );
+    }
+
+    #[test]
+    fn later_duplicate_keys_win_in_the_first_position() {
+        let value = parse(r#"{"a": 1, "b": 2, "a": 3}"#).expect("valid JSON");
+        assert_eq!(value.to_json(), r#"{"a":3,"b":2}"#);
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        for text in [
+            "",
+            "{",
+            "[1,]",
+            "{\"a\" 1}",
+            "01",
+            "1.",
+            "-",
+            "1e",
+            "\"\\x\"",
+            "\"\u{1}\"",
+            "\"\\u12\"",
+            "nul",
+            "{} {}",
+        ] {
+            assert_eq!(parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let deep = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&deep(MAX_DEPTH)).is_some());
+        assert!(parse(&deep(100_000)).is_none());
+    }
+
+    #[test]
+    fn lone_surrogates_become_replacement_characters() {
+        let value = parse(r#""\ud800\u0041\udc00""#).expect("valid JSON");
+        assert_eq!(value.as_str(), Some("\u{fffd}A\u{fffd}"));
+    }
+
+    #[test]
+    fn written_values_parse_back_unchanged() {
+        let text = r#"{"n":-0.125,"big":1e300,"s":"tab\tquote\"","list":[{},[]],"none":null}"#;
+        let value = parse(text).expect("valid JSON");
+        assert_eq!(parse(&value.to_json()), Some(value));
+    }
+}
@@ -0,0 +1,492 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{Event, HtmlCanvasElement, KeyboardEvent, Window};
+
+use crate::backend::{create_best_backend, describe_error, Backend, BackendConfig, BackendKind};
+use crate::context::CanvasElement;
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::input::InputHandlers;
+use crate::panel::ControlPanel;
+use crate::scene::SceneKind;
+
+// 1〜9 のキーで切り替えるパーティクル数
+const PRESETS: [usize; 9] = [
+    1_000, 2_000, 5_000, 10_000, 20_000, 50_000, 100_000, 200_000, 500_000,
+];
+// + と - で何倍にするかと、減らすときの下限
+const COUNT_STEP: usize = 2;
+const MIN_COUNT: usize = 100;
+// B で順に切り替えるバックエンド（WebGPU は作るのが非同期なので入れない）
+const CYCLE: [BackendKind; 4] = [
+    BackendKind::WebGl2,
+    BackendKind::WebGl,
+    BackendKind::Canvas2D,
+    BackendKind::Software,
+];
+// パネルのスライダーで動かせる範囲（パーティクル数は対数で動かす）
+const MAX_COUNT: usize = 500_000;
+const MAX_GRAVITY: f32 = 0.002;
+const MIN_POINT_SIZE: f32 = 0.5;
+const MAX_POINT_SIZE: f32 = 10.0;
+// アトラクトモードの切り替えの前後でフェードする時間(秒)と、A キーで始めるときの1つあたりの時間(秒)
+const FADE_SECONDS: f64 = 0.6;
+const DEFAULT_ATTRACT_SECONDS: f64 = 10.0;
+// アトラクトモードで Particles シーンのときに順に使うプリセット
+const ATTRACT_PRESETS: [usize; 4] = [2, 4, 6, 8];
+
+// キーに割り当てた操作
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Shortcut {
+    TogglePause,
+    Reset,
+    Preset(usize),
+    MoreParticles,
+    FewerParticles,
+    CycleBackend,
+    TogglePanel,
+    Step,
+    ToggleAttract,
+}
+
+impl Shortcut {
+    fn from_key(key: &str) -> Option<Shortcut> {
+        let shortcut = match key {
+            " " => Shortcut::TogglePause,
+            "r" | "R" => Shortcut::Reset,
+            "+" | "=" => Shortcut::MoreParticles,
+            "-" | "_" => Shortcut::FewerParticles,
+            "b" | "B" => Shortcut::CycleBackend,
+            "p" | "P" => Shortcut::TogglePanel,
+            "." => Shortcut::Step,
+            "a" | "A" => Shortcut::ToggleAttract,
+            _ => {
+                let digit = key
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|digit| (1..=9).contains(digit))?;
+                Shortcut::Preset(digit - 1)
+            }
+        };
+        Some(shortcut)
+    }
+}
+
+// アトラクトモードで順に見せるもの
+#[derive(Clone, Copy, Debug)]
+enum AttractItem {
+    Preset(usize),
+    Scene(SceneKind),
+}
+
+// プリセットとシーンを seconds ずつ順に見せ、切り替えの前後はキャンバスの不透明度を下げてフェードでつなぐ
+struct AttractMode {
+    seconds: f64,
+    items: Vec<AttractItem>,
+    next: usize,
+    // 今のものを見せ始めた timestamp(ms)
+    shown_at: Option<f64>,
+}
+
+impl AttractMode {
+    fn new(seconds: f64) -> AttractMode {
+        let presets = ATTRACT_PRESETS.into_iter().map(AttractItem::Preset);
+        let scenes = SceneKind::ALL
+            .into_iter()
+            .filter(|&kind| kind != SceneKind::Particles)
+            .map(AttractItem::Scene);
+        AttractMode {
+            // フェードの分より短いと見せる時間がなくなる
+            seconds: seconds.max(FADE_SECONDS * 2.0),
+            items: presets.chain(scenes).collect(),
+            next: 0,
+            shown_at: None,
+        }
+    }
+
+    // timestamp で次のものに切り替えるならそれを返す
+    fn due(&mut self, timestamp: f64) -> Option<AttractItem> {
+        if self
+            .shown_at
+            .is_some_and(|shown_at| timestamp - shown_at < self.seconds * 1_000.0)
+        {
+            return None;
+        }
+        let item = self.items[self.next];
+        self.next = (self.next + 1) % self.items.len();
+        self.shown_at = Some(timestamp);
+        Some(item)
+    }
+
+    // 切り替えの前後で 0 まで下げるキャンバスの不透明度
+    fn opacity(&self, timestamp: f64) -> f64 {
+        let Some(shown_at) = self.shown_at else {
+            return 0.0;
+        };
+        let elapsed = (timestamp - shown_at) / 1_000.0;
+        let remaining = self.seconds - elapsed;
+        (elapsed.min(remaining) / FADE_SECONDS).clamp(0.0, 1.0)
+    }
+}
+
+type Listener = Closure<dyn FnMut(Event)>;
+
+// window に登録した keydown のコールバック（外すと登録を取り消す）
+// コールバックは操作を積んでおくだけで、実際に行うのは次の tick()
+struct KeyboardShortcuts {
+    window: Window,
+    pending: Rc<RefCell<Vec<Shortcut>>>,
+    listener: Listener,
+}
+
+impl KeyboardShortcuts {
+    fn attach(window: Window) -> Result<KeyboardShortcuts, JsValue> {
+        let pending = Rc::new(RefCell::new(Vec::new()));
+        let queue = pending.clone();
+        let listener = Listener::new(move |event: Event| {
+            let Some(event) = event.dyn_ref::<KeyboardEvent>() else {
+                return;
+            };
+            // ブラウザのショートカットと入力欄での入力は邪魔しない
+            if event.ctrl_key() || event.meta_key() || event.alt_key() || is_editable(event) {
+                return;
+            }
+            if let Some(shortcut) = Shortcut::from_key(&event.key()) {
+                // スペースでページがスクロールしないようにする
+                event.prevent_default();
+                queue.borrow_mut().push(shortcut);
+            }
+        });
+        window.add_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref())?;
+        Ok(KeyboardShortcuts {
+            window,
+            pending,
+            listener,
+        })
+    }
+
+    fn take(&self) -> Vec<Shortcut> {
+        std::mem::take(&mut self.pending.borrow_mut())
+    }
+}
+
+impl Drop for KeyboardShortcuts {
+    fn drop(&mut self) {
+        let _ = self
+            .window
+            .remove_event_listener_with_callback("keydown", self.listener.as_ref().unchecked_ref());
+    }
+}
+
+fn is_editable(event: &KeyboardEvent) -> bool {
+    let Some(element) = event
+        .target()
+        .and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok())
+    else {
+        return false;
+    };
+    element.is_content_editable()
+        || matches!(element.tag_name().as_str(), "INPUT" | "TEXTAREA" | "SELECT")
+}
+
+// JS を書かずにキーボードだけで操作できるベンチマークページ用に、キャンバスとバックエンドをまとめて持つ
+// スペース: 一時停止と再開、.: 止めたまま1ステップ進める、R: 初期状態に戻す、1〜9: パーティクル数のプリセット、
+// + と -: パーティクル数を2倍・半分にする、B: バックエンドを順に切り替える、P: パネルの表示と非表示、
+// A: アトラクトモードの開始と終了（アトラクトモード中は他のキーを押すと終わる）
+// 左上にパーティクル数・重力・点の大きさのスライダーと、シーンとバックエンドを切り替えるボタンのパネルを描く
+// クリックやドラッグでの操作（attach_input_handlers()）も有効にする（パネルの上で押した分はパネルが使う）
+#[wasm_bindgen]
+pub struct Kiosk {
+    canvas_id: String,
+    backend: Backend,
+    shortcuts: KeyboardShortcuts,
+    panel: ControlPanel,
+    panel_visible: bool,
+    attract: Option<AttractMode>,
+}
+
+#[wasm_bindgen]
+impl Kiosk {
+    // canvas_id のキャンバスに create_best_backend() と同じ順でバックエンドを作る
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: &str, particle_count: usize) -> Result<Kiosk, JsValue> {
+        let window = web_sys::window().ok_or_else(|| tr(Text::KioskUnavailable, &[&canvas_id]))?;
+        canvas(canvas_id)?;
+        let mut backend = create_best_backend(canvas_id, &BackendConfig::new(particle_count))?;
+        let _ = backend.attach_input_handlers();
+        Ok(Kiosk {
+            canvas_id: canvas_id.to_string(),
+            backend,
+            shortcuts: KeyboardShortcuts::attach(window)?,
+            panel: ControlPanel::default(),
+            panel_visible: true,
+            attract: None,
+        })
+    }
+
+    // requestAnimationFrame の timestamp(ms) を渡して1回分進める
+    // 押されたキーとパネルの操作を行ってから進めて描く（一時停止中も描画だけは続ける）
+    // パネルは描いたフレームの上に重ねる
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        for shortcut in self.shortcuts.take() {
+            if self.attract.is_some() && shortcut != Shortcut::ToggleAttract {
+                self.stop_attract_mode();
+            }
+            self.apply(shortcut);
+        }
+        self.advance_attract(timestamp);
+        let show_panel = self.panel_visible && self.attract.is_none();
+        if show_panel {
+            self.layout_panel();
+        }
+        let rendered = self.backend.tick(timestamp);
+        let region = show_panel.then(|| {
+            let rects = self.panel.finish();
+            if rendered {
+                self.backend.draw_overlay(rects);
+            }
+            self.panel.region()
+        });
+        if let Some(handlers) = self.backend.input_handlers() {
+            handlers.set_ui_region(region);
+        }
+        rendered
+    }
+
+    // プリセットとシーンを seconds_per_preset 秒ずつ自動で切り替え続ける（展示用）
+    // 切り替えの前後はキャンバスをフェードアウト・フェードインし、その間はパネルを隠す
+    pub fn start_attract_mode(&mut self, seconds_per_preset: f64) {
+        let seconds = if seconds_per_preset.is_finite() {
+            seconds_per_preset
+        } else {
+            DEFAULT_ATTRACT_SECONDS
+        };
+        self.attract = Some(AttractMode::new(seconds));
+        self.backend.resume();
+    }
+
+    // 見せていたものはそのままにして、キャンバスの不透明度を戻す
+    pub fn stop_attract_mode(&mut self) {
+        if self.attract.take().is_some() {
+            if let Ok(canvas) = canvas(&self.canvas_id) {
+                let _ = canvas.style().remove_property("opacity");
+            }
+        }
+    }
+
+    pub fn is_attract_mode(&self) -> bool {
+        self.attract.is_some()
+    }
+
+    pub fn set_panel_visible(&mut self, visible: bool) {
+        self.panel_visible = visible;
+    }
+
+    pub fn is_panel_visible(&self) -> bool {
+        self.panel_visible
+    }
+
+    // 次のシーンに切り替える（今のバックエンドで使えないシーンは飛ばす）
+    pub fn next_scene(&mut self) -> SceneKind {
+        let scenes = SceneKind::ALL;
+        let current = self.backend.get_scene();
+        let start = scenes.iter().position(|&kind| kind == current).unwrap_or(0);
+        for offset in 1..=scenes.len() {
+            if self
+                .backend
+                .switch_scene(scenes[(start + offset) % scenes.len()])
+                .is_ok()
+            {
+                break;
+            }
+        }
+        self.backend.get_scene()
+    }
+
+    pub fn toggle_pause(&mut self) -> bool {
+        if self.backend.is_paused() {
+            self.backend.resume();
+        } else {
+            self.backend.pause();
+        }
+        self.backend.is_paused()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.backend.is_paused()
+    }
+
+    // preset（0〜8）番目のプリセットのパーティクル数にし、実際の数を返す
+    pub fn select_preset(&mut self, preset: usize) -> usize {
+        let count = PRESETS[preset.min(PRESETS.len() - 1)];
+        self.set_particle_count(count)
+    }
+
+    // 作成時の数を超えるときは増やしながら確保する（メモリ予算を超えたら今の上限まで）
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let count = count.max(MIN_COUNT);
+        if count > self.backend.get_max_particles() {
+            if let Ok(applied) = self.backend.grow_particle_count(count) {
+                return applied;
+            }
+        }
+        self.backend.set_particle_count(count)
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.backend.get_particle_count()
+    }
+
+    // 同じ id の新しいキャンバスに置き換えて、次のバックエンドで作り直す
+    // （一度コンテキストを取ったキャンバスでは別の種類のコンテキストを取れないため）
+    // パーティクル数・物理の設定・一時停止は引き継ぐ。作れなければその次を試し、どれも作れなければ今のまま
+    pub fn cycle_backend(&mut self) -> Result<BackendKind, JsValue> {
+        let old = canvas(&self.canvas_id)?;
+        let mut config = BackendConfig::new(self.backend.get_particle_count());
+        config.simulation = self.backend.get_simulation_config();
+        let start = CYCLE
+            .iter()
+            .position(|&kind| kind == self.backend.get_kind())
+            .map_or(0, |index| index + 1);
+        let mut last_error = JsValue::UNDEFINED;
+        for offset in 0..CYCLE.len() {
+            let kind = CYCLE[(start + offset) % CYCLE.len()];
+            let fresh = old.clone_node()?.dyn_into::<HtmlCanvasElement>()?;
+            match Backend::create(kind, &CanvasElement(&fresh), &config) {
+                Ok(mut backend) => {
+                    old.replace_with_with_node_1(&fresh)?;
+                    let _ = backend.attach_input_handlers();
+                    if self.backend.is_paused() {
+                        backend.pause();
+                    }
+                    backend.emit_event(
+                        EventKind::BackendSelected,
+                        &tr(Text::BackendSelected, &[&kind.name()]),
+                    );
+                    self.backend = backend;
+                    return Ok(kind);
+                }
+                Err(error) => last_error = describe_error(&error).into(),
+            }
+        }
+        Err(last_error)
+    }
+
+    pub fn get_kind(&self) -> BackendKind {
+        self.backend.get_kind()
+    }
+}
+
+impl Kiosk {
+    // 切り替える時刻になっていれば次のものにし、キャンバスの不透明度を合わせる
+    fn advance_attract(&mut self, timestamp: f64) {
+        let Some(attract) = &mut self.attract else {
+            return;
+        };
+        let item = attract.due(timestamp);
+        let opacity = attract.opacity(timestamp);
+        let shown = match item {
+            Some(AttractItem::Preset(preset)) => {
+                let _ = self.backend.switch_scene(SceneKind::Particles);
+                self.select_preset(preset);
+                true
+            }
+            Some(AttractItem::Scene(kind)) => self.backend.switch_scene(kind).is_ok(),
+            None => true,
+        };
+        // このバックエンドで使えないシーンなら、次の tick() で次のものにする
+        if let (false, Some(attract)) = (shown, &mut self.attract) {
+            attract.shown_at = None;
+        }
+        if let Ok(canvas) = canvas(&self.canvas_id) {
+            let _ = canvas
+                .style()
+                .set_property("opacity", &format!("{:.3}", opacity));
+        }
+    }
+
+    // パネルを並べ、動かされたスライダーと押されたボタンの操作を行う
+    fn layout_panel(&mut self) {
+        let pointer = self
+            .backend
+            .input_handlers()
+            .map(InputHandlers::take_ui_pointer)
+            .unwrap_or_default();
+        self.panel.begin(pointer);
+
+        let count = self.backend.get_particle_count();
+        let range = (MAX_COUNT as f32 / MIN_COUNT as f32).ln();
+        let t = (count.max(MIN_COUNT) as f32 / MIN_COUNT as f32).ln() / range;
+        if let Some(t) = self.panel.slider(0, &format!("COUNT {}", count), t) {
+            let count = MIN_COUNT as f32 * (t * range).exp();
+            // 100 個単位に丸める
+            self.set_particle_count((count / 100.0).round() as usize * 100);
+        }
+
+        let config = self.backend.get_simulation_config();
+        let label = format!("GRAVITY {:.4}", config.gravity);
+        if let Some(t) = self.panel.slider(1, &label, config.gravity / MAX_GRAVITY) {
+            self.backend.set_gravity(t * MAX_GRAVITY);
+        }
+        let label = format!("SIZE {:.1}", config.point_size);
+        let t = (config.point_size - MIN_POINT_SIZE) / (MAX_POINT_SIZE - MIN_POINT_SIZE);
+        if let Some(t) = self.panel.slider(2, &label, t) {
+            self.backend
+                .set_point_size(MIN_POINT_SIZE + t * (MAX_POINT_SIZE - MIN_POINT_SIZE));
+        }
+
+        if self.panel.button(self.backend.get_scene().name()) {
+            self.next_scene();
+        }
+        if self.panel.button(self.backend.get_kind().name()) {
+            self.apply(Shortcut::CycleBackend);
+        }
+    }
+
+    fn apply(&mut self, shortcut: Shortcut) {
+        match shortcut {
+            Shortcut::TogglePause => {
+                self.toggle_pause();
+            }
+            Shortcut::Reset => self.backend.reset(),
+            Shortcut::Preset(preset) => {
+                self.select_preset(preset);
+            }
+            Shortcut::MoreParticles => {
+                let count = self.backend.get_particle_count();
+                self.set_particle_count(count.saturating_mul(COUNT_STEP));
+            }
+            Shortcut::FewerParticles => {
+                let count = self.backend.get_particle_count();
+                self.set_particle_count(count / COUNT_STEP);
+            }
+            // 作れなかった理由はイベントに残す
+            Shortcut::CycleBackend => {
+                if let Err(error) = self.cycle_backend() {
+                    self.backend
+                        .emit_event(EventKind::FallbackUsed, &describe_error(&error));
+                }
+            }
+            Shortcut::TogglePanel => self.panel_visible = !self.panel_visible,
+            Shortcut::Step => self.backend.step(1),
+            Shortcut::ToggleAttract => {
+                if self.attract.is_some() {
+                    self.stop_attract_mode();
+                } else {
+                    self.start_attract_mode(DEFAULT_ATTRACT_SECONDS);
+                }
+            }
+        }
+    }
+}
+
+// ページ上の canvas_id のキャンバス
+fn canvas(canvas_id: &str) -> Result<HtmlCanvasElement, JsValue> {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.get_element_by_id(canvas_id))
+        .and_then(|element| element.dyn_into::<HtmlCanvasElement>().ok())
+        .ok_or_else(|| tr(Text::KioskUnavailable, &[&canvas_id]).into())
+}
@@ -0,0 +1,105 @@
+use wasm_bindgen::prelude::*;
+
+use crate::autoscale::AutoScaler;
+use crate::rng;
+use crate::simulation::ParticleSet;
+
+// 描画が重いときにパーティクルを間引いて描く方法（物理演算はすべてのパーティクルで続ける）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LodMode {
+    // 間引かない
+    #[default]
+    Off = 0,
+    // skip 個おきに描く（インデックスが skip の倍数のもの）
+    Strided = 1,
+    // パーティクルごとに決まった乱数で約 1/skip を選ぶ（生成順による偏りが出ない）
+    // 選ぶものはフレームをまたいで同じなので、ちらつかない
+    Stochastic = 2,
+}
+
+impl LodMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            LodMode::Off => "off",
+            LodMode::Strided => "strided",
+            LodMode::Stochastic => "stochastic",
+        }
+    }
+
+    fn keeps(self, index: usize, skip: u32) -> bool {
+        match self {
+            LodMode::Off => true,
+            LodMode::Strided => index.is_multiple_of(skip as usize),
+            LodMode::Stochastic => rng::hash(index as u32).is_multiple_of(skip),
+        }
+    }
+}
+
+// 間引きの設定と、間引いた後の描くパーティクル
+#[derive(Clone, Default)]
+pub(crate) struct LevelOfDetail {
+    mode: LodMode,
+    skip: u32,
+    // Some なら描画時間を見て skip を決める（描くパーティクル数を AutoScaler で探す）
+    scaler: Option<AutoScaler>,
+    visible: ParticleSet,
+}
+
+impl LevelOfDetail {
+    pub fn mode(&self) -> LodMode {
+        self.mode
+    }
+
+    // 何個に1個描くか（間引かないときは 1）
+    pub fn skip(&self) -> u32 {
+        match self.mode {
+            LodMode::Off => 1,
+            _ => self.skip.max(1),
+        }
+    }
+
+    // 設定の指紋用（"off"、"strided/4"、"stochastic/auto" など）
+    pub fn describe(&self) -> String {
+        match (self.mode, &self.scaler) {
+            (LodMode::Off, _) => String::from("off"),
+            (mode, Some(_)) => format!("{}/auto", mode.name()),
+            (mode, None) => format!("{}/{}", mode.name(), self.skip()),
+        }
+    }
+
+    // skip 個に1個描く（1 なら間引かない）
+    pub fn set_fixed(&mut self, mode: LodMode, skip: u32) {
+        self.mode = mode;
+        self.skip = skip.max(1);
+        self.scaler = None;
+    }
+
+    // render() の時間が target_ms を超えないように skip を決める（初めは間引かない）
+    pub fn set_adaptive(&mut self, mode: LodMode, target_ms: f64, particle_count: usize) {
+        self.mode = mode;
+        self.skip = 1;
+        self.scaler = (mode != LodMode::Off)
+            .then(|| AutoScaler::new(target_ms, particle_count, particle_count));
+    }
+
+    // 1フレーム分の render() の時間(ms)を渡す（自動で決めていないときは何もしない）
+    pub fn record_render(&mut self, render_ms: f64, particle_count: usize) {
+        if let Some(scaler) = &mut self.scaler {
+            if let Some(visible) = scaler.record_frame(render_ms) {
+                self.skip = particle_count.div_ceil(visible.max(1)).max(1) as u32;
+            }
+        }
+    }
+
+    // particles から描くものだけを集める
+    pub fn collect(&mut self, particles: &ParticleSet) {
+        let (mode, skip) = (self.mode, self.skip());
+        self.visible
+            .copy_filtered(particles, |index| mode.keeps(index, skip));
+    }
+
+    pub fn visible(&self) -> &ParticleSet {
+        &self.visible
+    }
+}
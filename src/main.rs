@@ -1,3 +1,75 @@
-fn main() {
-    println!("Hello, world!");
+// 物理演算だけを N フレーム動かして時間を出す CLI（描画もブラウザも使わない）
+// 同じコードを wasmtime や wasmer で動かし、ブラウザの simulate_frames() やネイティブの数字と比べる
+//
+//   cargo build --release --bin physics-bench --target wasm32-wasip1
+//   wasmtime target/wasm32-wasip1/release/physics-bench.wasm [シーン名] [パーティクル数] [フレーム数]
+//
+// ネイティブでは cargo run --release --bin physics-bench -- [シーン名] [パーティクル数] [フレーム数]
+// 初期状態は固定シードで作るので、どの実行環境でも同じ計算になる
+
+use particle_webgl::native::NativeBenchmark;
+use particle_webgl::scene::SceneKind;
+use particle_webgl::stats;
+use particle_webgl::timing;
+
+const WIDTH: u32 = 1280;
+const HEIGHT: u32 = 720;
+const DEFAULT_PARTICLE_COUNT: usize = 10_000;
+const DEFAULT_FRAMES: u32 = 300;
+// 計測に含めない最初のフレーム数（JIT の段階的なコンパイルやキャッシュが落ち着くまで）
+const WARMUP_FRAMES: u32 = 10;
+// ブラウザの set_seed() に同じ値を渡すと同じ初期状態になる
+const SEED: u64 = 1;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = std::env::args().skip(1);
+    let scene_name = args.next().unwrap_or_else(|| String::from("particles"));
+    let kind = SceneKind::ALL
+        .into_iter()
+        .find(|kind| kind.name() == scene_name)
+        .ok_or_else(|| format!("unknown scene: {}", scene_name))?;
+    let particle_count = match args.next() {
+        Some(count) => count.parse()?,
+        None => DEFAULT_PARTICLE_COUNT,
+    };
+    let frames = match args.next() {
+        Some(frames) => frames.parse()?,
+        None => DEFAULT_FRAMES,
+    };
+
+    let mut benchmark = NativeBenchmark::with_seed(kind, WIDTH, HEIGHT, particle_count, SEED)?;
+    benchmark.simulate_frames(WARMUP_FRAMES);
+    let mut samples = Vec::with_capacity(frames as usize);
+    for _ in 0..frames {
+        let start = timing::now_ms();
+        benchmark.update();
+        samples.push(timing::now_ms() - start);
+    }
+
+    println!(
+        "runtime: {}-{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    );
+    println!(
+        "scene: {}, particles: {}, frames: {} (+{} warmup)",
+        kind.name(),
+        particle_count,
+        frames,
+        WARMUP_FRAMES
+    );
+    if samples.is_empty() {
+        return Ok(());
+    }
+    let total: f64 = samples.iter().sum();
+    println!(
+        "update: total {:.3} ms, mean {:.3} ms, p50 {:.3} ms, p95 {:.3} ms, p99 {:.3} ms, max {:.3} ms",
+        total,
+        total / samples.len() as f64,
+        stats::percentile(&samples, 0.5),
+        stats::percentile(&samples, 0.95),
+        stats::percentile(&samples, 0.99),
+        stats::percentile(&samples, 1.0)
+    );
+    Ok(())
 }
@@ -0,0 +1,69 @@
+use wasm_bindgen::prelude::*;
+
+// 物理演算で使う数学関数
+// `deterministic` feature を有効にすると libm のソフトウェア実装に切り替わり、
+// 同じシードならブラウザやCPUが違ってもビット単位で同じシミュレーションになる
+
+#[cfg(feature = "deterministic")]
+mod imp {
+    #[inline]
+    pub fn sin(x: f32) -> f32 {
+        libm::sinf(x)
+    }
+
+    #[inline]
+    pub fn cos(x: f32) -> f32 {
+        libm::cosf(x)
+    }
+
+    #[inline]
+    pub fn atan2(y: f32, x: f32) -> f32 {
+        libm::atan2f(y, x)
+    }
+
+    #[inline]
+    pub fn sqrt(x: f32) -> f32 {
+        libm::sqrtf(x)
+    }
+
+    #[inline]
+    pub fn exp(x: f32) -> f32 {
+        libm::expf(x)
+    }
+}
+
+#[cfg(not(feature = "deterministic"))]
+mod imp {
+    #[inline]
+    pub fn sin(x: f32) -> f32 {
+        x.sin()
+    }
+
+    #[inline]
+    pub fn cos(x: f32) -> f32 {
+        x.cos()
+    }
+
+    #[inline]
+    pub fn atan2(y: f32, x: f32) -> f32 {
+        y.atan2(x)
+    }
+
+    #[inline]
+    pub fn sqrt(x: f32) -> f32 {
+        x.sqrt()
+    }
+
+    #[inline]
+    pub fn exp(x: f32) -> f32 {
+        x.exp()
+    }
+}
+
+pub use imp::{atan2, cos, exp, sin, sqrt};
+
+// ビルド時の設定をJSから確認できるようにする
+#[wasm_bindgen]
+pub fn is_deterministic_build() -> bool {
+    cfg!(feature = "deterministic")
+}
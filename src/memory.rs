@@ -0,0 +1,160 @@
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+use crate::simulation::Particle;
+
+// 1パーティクルあたりの状態のバイト数
+const PARTICLE_STATE_BYTES: u64 = std::mem::size_of::<Particle>() as u64;
+// 1パーティクルあたりの頂点データ（位置 vec2 + 色 vec3）
+const VERTEX_BYTES: u64 = ((2 + 3) * std::mem::size_of::<f32>()) as u64;
+
+// 既定のメモリ予算（1 GiB）。wasm32 のリニアメモリ上限 4 GiB より十分小さくしておく
+pub const DEFAULT_MEMORY_BUDGET: u64 = 1024 * 1024 * 1024;
+
+// 指定したパーティクル数で必要になるWASM側メモリの見積もり（バイト）
+// ダブルバッファを有効にした場合の最大値を返す
+#[wasm_bindgen]
+pub fn estimate_memory(particle_count: usize) -> u64 {
+    (particle_count as u64).saturating_mul(PARTICLE_STATE_BYTES * 2 + VERTEX_BYTES)
+}
+
+// 予算を超える場合は生成前に分かりやすいエラーを返す
+// （実行中に memory.grow が失敗してインスタンスごと落ちるのを防ぐ）
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+pub(crate) fn check_budget(particle_count: usize, budget: u64) -> Result<(), String> {
+    let required = estimate_memory(particle_count);
+    if required > budget {
+        return Err(tr(
+            Text::MemoryBudgetExceeded,
+            &[
+                &particle_count,
+                &format!("{:.1}", mib(required)),
+                &format!("{:.1}", mib(budget)),
+            ],
+        ));
+    }
+    Ok(())
+}
+
+// 確保に失敗した場合もパニックせずにエラーとして返す
+pub(crate) fn try_vec<T>(capacity: usize) -> Result<Vec<T>, String> {
+    let mut v = Vec::new();
+    v.try_reserve_exact(capacity).map_err(|_| {
+        let bytes = (capacity * std::mem::size_of::<T>()) as u64;
+        tr(Text::AllocationFailed, &[&format!("{:.1}", mib(bytes))])
+    })?;
+    Ok(v)
+}
+
+fn mib(bytes: u64) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0)
+}
+
+// 実行中のメモリの使用量（カクつきの原因の推定に使う）
+#[cfg(all(target_arch = "wasm32", not(target_os = "wasi")))]
+mod usage {
+    use wasm_bindgen::JsCast;
+
+    // WASMのリニアメモリの大きさ（memory.grow で増える）
+    pub fn wasm_memory_bytes() -> u64 {
+        wasm_bindgen::memory()
+            .unchecked_into::<js_sys::WebAssembly::Memory>()
+            .buffer()
+            .unchecked_into::<js_sys::ArrayBuffer>()
+            .byte_length() as u64
+    }
+
+    // JSのヒープの使用量（performance.memory は Chromium 系にしかないので、なければ None）
+    pub fn js_heap_bytes() -> Option<f64> {
+        let get = |target: &wasm_bindgen::JsValue, key: &str| {
+            js_sys::Reflect::get(target, &key.into())
+                .ok()
+                .filter(|value| !value.is_undefined() && !value.is_null())
+        };
+        let global: wasm_bindgen::JsValue = js_sys::global().into();
+        let performance = get(&global, "performance")?;
+        let memory = get(&performance, "memory")?;
+        get(&memory, "usedJSHeapSize")?.as_f64()
+    }
+}
+
+// WASI には JS がないので、リニアメモリの大きさだけをページ数から求める
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+mod usage {
+    pub fn wasm_memory_bytes() -> u64 {
+        core::arch::wasm32::memory_size(0) as u64 * 65536
+    }
+
+    pub fn js_heap_bytes() -> Option<f64> {
+        None
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod usage {
+    #[cfg(target_arch = "wasm32")]
+    pub fn wasm_memory_bytes() -> u64 {
+        0
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn js_heap_bytes() -> Option<f64> {
+        None
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use usage::{js_heap_bytes, wasm_memory_bytes};
+
+// ヒープの確保の回数を数えるアロケーター（alloc-tracking フィーチャー）
+// 確保そのものは System に任せ、alloc と realloc のたびに1増やすだけ
+#[cfg(feature = "alloc-tracking")]
+mod tracking {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+    struct CountingAllocator;
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.alloc(layout)
+        }
+
+        unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.alloc_zeroed(layout)
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            System.realloc(ptr, layout, new_size)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    // 起動してからの確保の回数
+    #[cfg(target_arch = "wasm32")]
+    pub fn allocation_count() -> Option<u64> {
+        Some(ALLOCATIONS.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(not(feature = "alloc-tracking"))]
+mod tracking {
+    #[cfg(target_arch = "wasm32")]
+    pub fn allocation_count() -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub(crate) use tracking::allocation_count;
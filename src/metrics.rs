@@ -0,0 +1,343 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+
+use crate::json;
+use crate::memory;
+use crate::report;
+use crate::timing;
+
+// get_metrics() が返す、直近のフレームの計測値
+// JS側で計ると境界を跨ぐ呼び出しやタイマー自体の時間が混ざるので、WASMの中で計る
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Metrics {
+    // これまでに render() したフレーム数
+    pub frames: u32,
+    // 直近 WINDOW フレームの update()・render()・バッファ転送の平均時間(ms)
+    // バッファ転送は render() の内数（Canvas2D は転送がないので 0）
+    pub update_ms: f64,
+    pub render_ms: f64,
+    pub upload_ms: f64,
+    // 直近1秒の render() の回数から求めたFPS
+    pub fps: f64,
+    // WebGL のコンテキストが失われた回数（0 でなければ途中で描画が止まっていた）
+    pub context_losses: u32,
+    // 計測から除いた最初のフレーム数（シェーダーのコンパイル・JIT・バッファの確保が集中する）
+    pub warmup_frames: u32,
+    // WASM のリニアメモリの大きさ(バイト)（memory.grow で増えるだけで減らない。ネイティブでは 0）
+    pub wasm_memory_bytes: f64,
+    // パーティクルの状態と頂点データの配列が確保しているバイト数（GPU 側のバッファは含まない）
+    pub particle_buffer_bytes: f64,
+    // 直近 WINDOW フレームの1フレームあたりのヒープの確保の回数（alloc-tracking フィーチャーがなければ NaN）
+    pub allocations_per_frame: f64,
+}
+
+#[wasm_bindgen]
+impl Metrics {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("frames", self.frames as f64)
+            .number("update_ms", self.update_ms)
+            .number("render_ms", self.render_ms)
+            .number("upload_ms", self.upload_ms)
+            .number("fps", self.fps)
+            .number("context_losses", self.context_losses as f64)
+            .number("warmup_frames", self.warmup_frames as f64)
+            .number("wasm_memory_bytes", self.wasm_memory_bytes)
+            .number("particle_buffer_bytes", self.particle_buffer_bytes)
+            .number("allocations_per_frame", self.allocations_per_frame)
+            .finish()
+    }
+}
+
+// get_frame_histogram() が返す、これまでのフレーム時間（render() の開始の間隔）の分布
+// 平均のFPSでは同じでも、ときどき長いフレームが混ざるかどうかで負荷の下での違いが分かる
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug, Default)]
+pub struct FrameHistogram {
+    // 1つのビンの幅(ms)。i 番目のビンは [i * bucket_ms, (i + 1) * bucket_ms) の回数
+    pub bucket_ms: f64,
+    pub counts: Vec<u32>,
+    pub frames: u32,
+    pub mean_ms: f64,
+    // パーセンタイルはビンの幅 RESOLUTION_MS の精度
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub longest_ms: f64,
+    // 60Hz の1フレーム（16.6ms）と2フレーム（33.3ms）を超えた回数
+    pub over_16ms: u32,
+    pub over_33ms: u32,
+}
+
+#[wasm_bindgen]
+impl FrameHistogram {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("bucket_ms", self.bucket_ms)
+            .raw(
+                "counts",
+                &json::array(self.counts.iter().map(|count| count.to_string())),
+            )
+            .number("frames", self.frames as f64)
+            .number("mean_ms", self.mean_ms)
+            .number("p50_ms", self.p50_ms)
+            .number("p95_ms", self.p95_ms)
+            .number("p99_ms", self.p99_ms)
+            .number("longest_ms", self.longest_ms)
+            .number("over_16ms", self.over_16ms as f64)
+            .number("over_33ms", self.over_33ms as f64)
+            .finish()
+    }
+}
+
+// 平均を取るフレーム数
+const WINDOW: usize = 60;
+// FPS を数える期間(ms)
+const FPS_WINDOW_MS: f64 = 1000.0;
+// フレーム時間を数える細かいビンの幅(ms)と、ビンで数える上限(ms)（超えた分は最後のビンにまとめる）
+const RESOLUTION_MS: f64 = 0.1;
+const MAX_FRAME_MS: f64 = 1000.0;
+// カクつきとして数えるしきい値(ms)
+const JANK_MS: f64 = 1000.0 / 60.0;
+const SEVERE_JANK_MS: f64 = 2000.0 / 60.0;
+// 何も指定しなくても計測から除く最初のフレーム数
+const DEFAULT_WARMUP_FRAMES: u32 = 3;
+
+// フレーム時間を RESOLUTION_MS のビンで数え続ける（フレーム数によらず大きさが一定）
+// 好きな幅のヒストグラムとパーセンタイルは、このビンをまとめ直して求める
+#[derive(Default)]
+struct FrameTimes {
+    bins: Vec<u32>,
+    frames: u32,
+    total_ms: f64,
+    longest_ms: f64,
+    over_16ms: u32,
+    over_33ms: u32,
+}
+
+impl FrameTimes {
+    fn record(&mut self, frame_ms: f64) {
+        if self.bins.is_empty() {
+            self.bins = vec![0; (MAX_FRAME_MS / RESOLUTION_MS) as usize + 1];
+        }
+        let bin = ((frame_ms / RESOLUTION_MS) as usize).min(self.bins.len() - 1);
+        self.bins[bin] += 1;
+        self.frames += 1;
+        self.total_ms += frame_ms;
+        self.longest_ms = self.longest_ms.max(frame_ms);
+        self.over_16ms += (frame_ms > JANK_MS) as u32;
+        self.over_33ms += (frame_ms > SEVERE_JANK_MS) as u32;
+    }
+
+    // 回数の累計が frames の p 倍に届くビンの中央（上限を超えたビンなら最長のフレーム）
+    fn percentile(&self, p: f64) -> f64 {
+        let target = (p * self.frames as f64).ceil().max(1.0) as u32;
+        let mut seen = 0;
+        for (bin, &count) in self.bins.iter().enumerate() {
+            seen += count;
+            if seen >= target {
+                if bin == self.bins.len() - 1 {
+                    return self.longest_ms;
+                }
+                return ((bin as f64 + 0.5) * RESOLUTION_MS).min(self.longest_ms);
+            }
+        }
+        0.0
+    }
+
+    fn histogram(&self, bucket_ms: f64) -> FrameHistogram {
+        let bucket_ms = if bucket_ms.is_finite() {
+            bucket_ms.max(RESOLUTION_MS)
+        } else {
+            1.0
+        };
+        let bucket_of = |ms: f64| (ms / bucket_ms) as usize;
+        let mut counts = vec![
+            0;
+            if self.frames > 0 {
+                bucket_of(self.longest_ms) + 1
+            } else {
+                0
+            }
+        ];
+        for (bin, &count) in self.bins.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let ms = if bin == self.bins.len() - 1 {
+                self.longest_ms
+            } else {
+                (bin as f64 + 0.5) * RESOLUTION_MS
+            };
+            let bucket = bucket_of(ms).min(counts.len() - 1);
+            counts[bucket] += count;
+        }
+        FrameHistogram {
+            bucket_ms,
+            counts,
+            frames: self.frames,
+            mean_ms: if self.frames > 0 {
+                self.total_ms / self.frames as f64
+            } else {
+                0.0
+            },
+            p50_ms: self.percentile(0.5),
+            p95_ms: self.percentile(0.95),
+            p99_ms: self.percentile(0.99),
+            longest_ms: self.longest_ms,
+            over_16ms: self.over_16ms,
+            over_33ms: self.over_33ms,
+        }
+    }
+}
+
+pub(crate) struct MetricsCollector {
+    frames: u32,
+    update: VecDeque<f64>,
+    render: VecDeque<f64>,
+    upload: VecDeque<f64>,
+    // このフレームのバッファ転送の合計（render の記録と一緒に確定する）
+    pending_upload: f64,
+    // 直近 FPS_WINDOW_MS の render() の開始時刻
+    render_starts: VecDeque<f64>,
+    // render() の開始の間隔（reset_frame_histogram() まで数え続ける）
+    frame_times: FrameTimes,
+    last_render_start: Option<f64>,
+    context_losses: u32,
+    // あと何フレームを計測から除くかと、これまでに除いたフレーム数
+    warmup_remaining: u32,
+    warmup_frames: u32,
+    // 直前の render() の後に数えたパーティクルの配列のバイト数
+    particle_buffer_bytes: u64,
+    // 1フレームごとの確保の回数と、前の render() の終わりまでの確保の回数の累計
+    allocations: VecDeque<f64>,
+    last_allocations: Option<u64>,
+}
+
+impl Default for MetricsCollector {
+    fn default() -> MetricsCollector {
+        MetricsCollector {
+            frames: 0,
+            update: VecDeque::new(),
+            render: VecDeque::new(),
+            upload: VecDeque::new(),
+            pending_upload: 0.0,
+            render_starts: VecDeque::new(),
+            frame_times: FrameTimes::default(),
+            last_render_start: None,
+            context_losses: 0,
+            warmup_remaining: DEFAULT_WARMUP_FRAMES,
+            warmup_frames: 0,
+            particle_buffer_bytes: 0,
+            allocations: VecDeque::new(),
+            last_allocations: None,
+        }
+    }
+}
+
+impl MetricsCollector {
+    pub fn record_context_losses(&mut self, losses: u32) {
+        self.context_losses = losses;
+    }
+
+    // 次の frames フレームを計測から除く（warmup() で描く前に呼ぶ）
+    pub fn begin_warmup(&mut self, frames: u32) {
+        self.warmup_remaining = frames;
+    }
+
+    pub fn record_update(&mut self, start: f64) {
+        if self.warmup_remaining > 0 {
+            return;
+        }
+        push_window(&mut self.update, timing::now_ms() - start);
+    }
+
+    pub fn record_upload(&mut self, start: f64) {
+        self.pending_upload += timing::now_ms() - start;
+    }
+
+    // render() の後に、パーティクルの状態と頂点データの配列が確保しているバイト数を渡す
+    pub fn record_buffer_bytes(&mut self, bytes: u64) {
+        self.particle_buffer_bytes = bytes;
+    }
+
+    pub fn record_render(&mut self, start: f64) {
+        self.frames += 1;
+        // 確保の回数は計測から除くフレームも含めて区切り、除いたフレームの分は数えない
+        let allocations = memory::allocation_count();
+        let previous = std::mem::replace(&mut self.last_allocations, allocations);
+        if self.warmup_remaining > 0 {
+            // 除いたフレームとの間隔も数えないよう、次のフレームから数え始める
+            self.warmup_remaining -= 1;
+            self.warmup_frames += 1;
+            self.pending_upload = 0.0;
+            self.last_render_start = None;
+            return;
+        }
+        push_window(&mut self.render, timing::now_ms() - start);
+        push_window(&mut self.upload, std::mem::take(&mut self.pending_upload));
+        if let (Some(previous), Some(allocations)) = (previous, allocations) {
+            push_window(&mut self.allocations, (allocations - previous) as f64);
+        }
+        if let Some(last) = self.last_render_start.replace(start) {
+            self.frame_times.record(start - last);
+        }
+
+        self.render_starts.push_back(start);
+        while self
+            .render_starts
+            .front()
+            .is_some_and(|&first| start - first > FPS_WINDOW_MS)
+        {
+            self.render_starts.pop_front();
+        }
+    }
+
+    pub fn snapshot(&self) -> Metrics {
+        let fps = match (self.render_starts.front(), self.render_starts.back()) {
+            (Some(first), Some(last)) if last > first => {
+                (self.render_starts.len() - 1) as f64 * 1000.0 / (last - first)
+            }
+            _ => 0.0,
+        };
+        Metrics {
+            frames: self.frames,
+            update_ms: mean(&self.update),
+            render_ms: mean(&self.render),
+            upload_ms: mean(&self.upload),
+            fps,
+            context_losses: self.context_losses,
+            warmup_frames: self.warmup_frames,
+            wasm_memory_bytes: memory::wasm_memory_bytes() as f64,
+            particle_buffer_bytes: self.particle_buffer_bytes as f64,
+            allocations_per_frame: match memory::allocation_count() {
+                Some(_) => mean(&self.allocations),
+                None => f64::NAN,
+            },
+        }
+    }
+
+    // bucket_ms 幅のビンにまとめたフレーム時間の分布
+    pub fn frame_histogram(&self, bucket_ms: f64) -> FrameHistogram {
+        self.frame_times.histogram(bucket_ms)
+    }
+
+    // 数え直す（次の render() からの間隔を数える）
+    pub fn reset_frame_histogram(&mut self) {
+        self.frame_times = FrameTimes::default();
+        self.last_render_start = None;
+    }
+}
+
+fn push_window(window: &mut VecDeque<f64>, value: f64) {
+    if window.len() == WINDOW {
+        window.pop_front();
+    }
+    window.push_back(value);
+}
+
+fn mean(window: &VecDeque<f64>) -> f64 {
+    if window.is_empty() {
+        return 0.0;
+    }
+    window.iter().sum::<f64>() / window.len() as f64
+}
@@ -0,0 +1,187 @@
+// ブラウザなしで同じシーンと物理演算を動かす Rust の API（wasm_bindgen を通さない）
+// ネイティブのアプリに組み込み、wgpu や softbuffer の窓に描いて、ブラウザと同じコードで数字を比べる
+//
+// 描き方は2通り:
+// - render(): WASM 版の software バックエンドと同じラスタライザで RGBA に塗り、Renderer に渡す
+// - render_to(): scene::Surface を自分で実装した描画先（GPU など）にシーンを直接描く
+
+use crate::explosion::ExplosionConfig;
+use crate::raster::{RasterRun, RasterSurface};
+use crate::render_mode::RenderMode;
+use crate::rng;
+use crate::scene::{ColorPoint, SceneKind, Surface};
+use crate::simulation::hsl_to_rgb;
+use crate::timing;
+use crate::BACKGROUND_GRAY;
+
+// 塗り終わった1フレームを表示する先（softbuffer の窓、wgpu のテクスチャ、画像ファイルなど）
+pub trait Renderer {
+    // rgba は width × height の不透明な RGBA（1行目が上端）
+    fn present(&mut self, width: u32, height: u32, rgba: &[u8]);
+}
+
+// 1フレームにかかった時間(ms)
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameTiming {
+    pub update_ms: f64,
+    pub render_ms: f64,
+}
+
+// パーティクルのシーンの今の状態（成分ごとの配列、インデックスは共通）
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleView<'a> {
+    pub x: &'a [f32],
+    pub y: &'a [f32],
+    // px/フレーム
+    pub vx: &'a [f32],
+    pub vy: &'a [f32],
+    // 度（0〜360）
+    pub hue: &'a [f32],
+    // 残りの寿命（寿命のないパーティクルは無限大）
+    pub life: &'a [f32],
+}
+
+// 1つのシーンを進めて描く（ブラウザのバックエンドの update() と render() にあたる）
+pub struct NativeBenchmark {
+    kind: SceneKind,
+    run: RasterRun,
+    surface: RasterSurface,
+    rgba: Vec<u8>,
+    points: Vec<ColorPoint>,
+    explosion: ExplosionConfig,
+}
+
+impl NativeBenchmark {
+    // particle_count は Particles のときだけ使う
+    pub fn new(
+        kind: SceneKind,
+        width: u32,
+        height: u32,
+        particle_count: usize,
+    ) -> Result<NativeBenchmark, String> {
+        let run = RasterRun::new(kind, width as f32, height as f32, particle_count)?;
+        Ok(NativeBenchmark::with_run(kind, width, height, run))
+    }
+
+    // 生成の乱数を seed から作る（同じ seed ならブラウザの set_seed() と同じ初期状態になる）
+    pub fn with_seed(
+        kind: SceneKind,
+        width: u32,
+        height: u32,
+        particle_count: usize,
+        seed: u64,
+    ) -> Result<NativeBenchmark, String> {
+        let run = rng::with_seed(seed, || {
+            RasterRun::new(kind, width as f32, height as f32, particle_count)
+        })?;
+        Ok(NativeBenchmark::with_run(kind, width, height, run))
+    }
+
+    fn with_run(kind: SceneKind, width: u32, height: u32, run: RasterRun) -> NativeBenchmark {
+        NativeBenchmark {
+            kind,
+            run,
+            surface: RasterSurface::new(width as f32, height as f32, 1.0),
+            rgba: Vec::new(),
+            points: Vec::new(),
+            explosion: ExplosionConfig::default(),
+        }
+    }
+
+    pub fn kind(&self) -> SceneKind {
+        self.kind
+    }
+
+    // 負荷 0.0〜1.0（ブラウザの set_load() と同じ割り当て）
+    pub fn set_load(&mut self, load: f32) {
+        self.run.set_load(load.clamp(0.0, 1.0));
+    }
+
+    pub fn update(&mut self) {
+        self.run.update();
+    }
+
+    // クリックと同じ操作（対応しないシーンでは何もしない）
+    pub fn explode(&mut self, x: f32, y: f32) {
+        match &mut self.run {
+            RasterRun::Scene(scene) => scene.explode(x, y, &self.explosion),
+            RasterRun::Particles(sim) => sim.explode(x, y, &self.explosion),
+        }
+    }
+
+    // ラスタライザで塗って renderer に渡す
+    pub fn render(&mut self, renderer: &mut impl Renderer) {
+        self.run.render(&mut self.surface);
+        self.surface.to_rgba8(&mut self.rgba);
+        let (width, height) = self.surface.resolution();
+        renderer.present(width, height, &self.rgba);
+    }
+
+    // 自分で実装した描画先に描く（パーティクルはバックエンドと同じ色・大きさの点で描く）
+    pub fn render_to(&mut self, surface: &mut dyn Surface) {
+        match &mut self.run {
+            RasterRun::Scene(scene) => scene.render(surface),
+            RasterRun::Particles(sim) => {
+                surface.clear([BACKGROUND_GRAY; 3]);
+                let particles = sim.particles();
+                self.points.clear();
+                for (p, &life) in particles.iter().zip(&particles.life) {
+                    let (r, g, b) = hsl_to_rgb(p.hue, 1.0, 0.5);
+                    let alpha = life.clamp(0.0, 1.0);
+                    let fade = |c: f32| BACKGROUND_GRAY + (c - BACKGROUND_GRAY) * alpha;
+                    self.points.push(ColorPoint {
+                        x: p.x,
+                        y: p.y,
+                        rgb: [fade(r), fade(g), fade(b)],
+                    });
+                }
+                let size = sim.config.point_diameter();
+                surface.draw_points(&self.points, size, RenderMode::Normal);
+            }
+        }
+    }
+
+    // update() と render() を1回ずつ行い、それぞれの時間を返す
+    pub fn frame(&mut self, renderer: &mut impl Renderer) -> FrameTiming {
+        let start = timing::now_ms();
+        self.update();
+        let updated = timing::now_ms();
+        self.render(renderer);
+        FrameTiming {
+            update_ms: updated - start,
+            render_ms: timing::now_ms() - updated,
+        }
+    }
+
+    // 描かずに n ステップ進め、経過時間(ms)を返す（ブラウザの simulate_frames() と同じ）
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..n {
+            self.update();
+        }
+        timing::now_ms() - start
+    }
+
+    pub fn frame_count(&self) -> u32 {
+        match &self.run {
+            RasterRun::Scene(scene) => scene.frame_count(),
+            RasterRun::Particles(sim) => sim.frame_count,
+        }
+    }
+
+    // パーティクルのシーンでなければ None
+    pub fn particles(&self) -> Option<ParticleView<'_>> {
+        let RasterRun::Particles(sim) = &self.run else {
+            return None;
+        };
+        let particles = sim.particles();
+        Some(ParticleView {
+            x: &particles.x,
+            y: &particles.y,
+            vx: &particles.vx,
+            vy: &particles.vy,
+            hue: &particles.hue,
+            life: &particles.life,
+        })
+    }
+}
@@ -0,0 +1,126 @@
+use wasm_bindgen::prelude::*;
+
+use crate::timing;
+
+// FPS の低下を判定する区間(ms)。1フレームの引っかかりでは通知しない
+const FPS_WINDOW_MS: f64 = 500.0;
+
+// on_frame() のコールバックに渡す、描いたフレームの情報
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameNotice {
+    pub frame_count: u32,
+    // 前の render() からの間隔(ms)（最初のフレームは 0）
+    pub frame_ms: f64,
+}
+
+// on_fps_drop() のコールバックに渡す、しきい値を下回った区間の FPS
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct FpsDrop {
+    pub fps: f64,
+    pub threshold: f64,
+    pub frame_count: u32,
+}
+
+// on_benchmark_complete() のコールバックに渡す、simulate_frames()・simulate_until_settled() の結果
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct BenchmarkComplete {
+    // 進めたステップ数と経過時間(ms)
+    pub frames: u32,
+    pub elapsed_ms: f64,
+    // simulate_until_settled() で止まったか（simulate_frames() では常に false）
+    pub settled: bool,
+    pub frame_count: u32,
+}
+
+// JS から登録したコールバックを呼ぶ窓口（どのバックエンドも同じものを持つ）
+// 毎フレーム get_frame_count() を見に行かなくても、ダッシュボードが値を受け取れる
+#[derive(Default)]
+pub(crate) struct Observers {
+    frame: Option<js_sys::Function>,
+    complete: Option<js_sys::Function>,
+    fps_drop: Option<(f64, js_sys::Function)>,
+    last_frame: Option<f64>,
+    // FPS を数えている区間の始まりとフレーム数
+    window_start: Option<f64>,
+    window_frames: u32,
+    // しきい値を下回ったと通知済みか（上回るまで次は通知しない）
+    dropped: bool,
+}
+
+impl Observers {
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        self.frame = Some(callback);
+    }
+
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        self.complete = Some(callback);
+    }
+
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        self.fps_drop = Some((threshold, callback));
+        self.window_start = None;
+        self.dropped = false;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Observers::default();
+    }
+
+    // render() の最初に呼ぶ
+    pub fn note_frame(&mut self, frame_count: u32) {
+        if self.frame.is_none() && self.fps_drop.is_none() {
+            return;
+        }
+        let now = timing::now_ms();
+        let frame_ms = self.last_frame.replace(now).map_or(0.0, |last| now - last);
+        if let Some(callback) = &self.frame {
+            let notice = FrameNotice {
+                frame_count,
+                frame_ms,
+            };
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(notice));
+        }
+
+        let Some((threshold, callback)) = &self.fps_drop else {
+            return;
+        };
+        let start = *self.window_start.get_or_insert(now);
+        self.window_frames += 1;
+        let elapsed = now - start;
+        if elapsed < FPS_WINDOW_MS {
+            return;
+        }
+        // 区間の最初のフレームは前の区間との境目なので数えない
+        let fps = (self.window_frames - 1) as f64 * 1000.0 / elapsed;
+        self.window_start = Some(now);
+        self.window_frames = 1;
+        if fps >= *threshold {
+            self.dropped = false;
+            return;
+        }
+        if !self.dropped {
+            self.dropped = true;
+            let drop = FpsDrop {
+                fps,
+                threshold: *threshold,
+                frame_count,
+            };
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(drop));
+        }
+    }
+
+    pub fn note_complete(&self, frames: u32, elapsed_ms: f64, settled: bool, frame_count: u32) {
+        if let Some(callback) = &self.complete {
+            let complete = BenchmarkComplete {
+                frames,
+                elapsed_ms,
+                settled,
+                frame_count,
+            };
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(complete));
+        }
+    }
+}
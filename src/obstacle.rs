@@ -0,0 +1,265 @@
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use crate::camera::Camera;
+use crate::math;
+#[cfg(target_arch = "wasm32")]
+use crate::scene::ColorPoint;
+use crate::simulation::Particle;
+
+// 障害物の輪郭を描く色
+pub const OUTLINE_RGB: [f32; 3] = [0.75, 0.75, 0.75];
+// 1つの障害物の輪郭に並べる点の上限（とても長い線分で頂点データが膨らまないように）
+#[cfg(target_arch = "wasm32")]
+const MAX_OUTLINE_POINTS: usize = 4096;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ObstacleKind {
+    Circle = 0,
+    Line = 1,
+}
+
+// パーティクルが跳ね返る動かない障害物
+// 円は (x0, y0) が中心で radius が半径、線分は (x0, y0)〜(x1, y1)（radius は 0）
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    pub x0: f32,
+    pub y0: f32,
+    pub x1: f32,
+    pub y1: f32,
+    pub radius: f32,
+}
+
+impl Obstacle {
+    // 中に入った（線分は margin より近づいたか横切った）パーティクルを外に出し、
+    // 近づく向きの速度を bounce の割合で跳ね返す
+    fn apply(&self, p: &mut Particle, margin: f32, bounce: f32) {
+        match self.kind {
+            ObstacleKind::Circle => self.apply_circle(p, margin, bounce),
+            ObstacleKind::Line => self.apply_line(p, margin, bounce),
+        }
+    }
+
+    fn apply_circle(&self, p: &mut Particle, margin: f32, bounce: f32) {
+        let reach = self.radius + margin;
+        let (dx, dy) = (p.x - self.x0, p.y - self.y0);
+        let dist_sq = dx * dx + dy * dy;
+        if dist_sq >= reach * reach {
+            return;
+        }
+        let dist = math::sqrt(dist_sq);
+        // 中心にいるパーティクルは向きが決まらないので、来た方向へ戻す
+        let normal = if dist > 1e-6 {
+            (dx / dist, dy / dist)
+        } else {
+            direction(-p.vx, -p.vy).unwrap_or((0.0, -1.0))
+        };
+        p.x = self.x0 + normal.0 * reach;
+        p.y = self.y0 + normal.1 * reach;
+        reflect(p, normal, bounce);
+    }
+
+    fn apply_line(&self, p: &mut Particle, margin: f32, bounce: f32) {
+        let (tx, ty) = (self.x1 - self.x0, self.y1 - self.y0);
+        let length_sq = tx * tx + ty * ty;
+        if length_sq < 1e-12 {
+            // 長さのない線分は点（半径 0 の円）として扱う
+            return self.apply_circle(p, margin, bounce);
+        }
+        let length = math::sqrt(length_sq);
+        let line_normal = (-ty / length, tx / length);
+        // 線分のどちら側にいるか（line_normal の向きが正）
+        let side = |x: f32, y: f32| (x - self.x0) * line_normal.0 + (y - self.y0) * line_normal.1;
+
+        // 1ステップで線分を横切ったら、横切った点で前にいた側へ戻す
+        let (prev_x, prev_y) = (p.x - p.vx, p.y - p.vy);
+        let (before, after) = (side(prev_x, prev_y), side(p.x, p.y));
+        if before * after < 0.0 {
+            let f = before / (before - after);
+            let (hit_x, hit_y) = (prev_x + p.vx * f, prev_y + p.vy * f);
+            let u = ((hit_x - self.x0) * tx + (hit_y - self.y0) * ty) / length_sq;
+            if (0.0..=1.0).contains(&u) {
+                let sign = before.signum();
+                let normal = (line_normal.0 * sign, line_normal.1 * sign);
+                p.x = hit_x + normal.0 * margin;
+                p.y = hit_y + normal.1 * margin;
+                reflect(p, normal, bounce);
+                return;
+            }
+        }
+
+        // 横切っていなければ、線分の一番近い点から margin 以内のものを押し出す
+        let u = (((p.x - self.x0) * tx + (p.y - self.y0) * ty) / length_sq).clamp(0.0, 1.0);
+        let (near_x, near_y) = (self.x0 + tx * u, self.y0 + ty * u);
+        let (dx, dy) = (p.x - near_x, p.y - near_y);
+        let dist_sq = dx * dx + dy * dy;
+        if dist_sq >= margin * margin {
+            return;
+        }
+        let dist = math::sqrt(dist_sq);
+        let normal = if dist > 1e-6 {
+            (dx / dist, dy / dist)
+        } else {
+            let sign = if before < 0.0 { -1.0 } else { 1.0 };
+            (line_normal.0 * sign, line_normal.1 * sign)
+        };
+        p.x = near_x + normal.0 * margin;
+        p.y = near_y + normal.1 * margin;
+        reflect(p, normal, bounce);
+    }
+
+    // 輪郭に spacing(px) おきに並べた点を f に渡す
+    #[cfg(target_arch = "wasm32")]
+    fn outline(&self, spacing: f32, mut f: impl FnMut(f32, f32)) {
+        match self.kind {
+            ObstacleKind::Circle => {
+                let circumference = 2.0 * std::f32::consts::PI * self.radius;
+                let count =
+                    ((circumference / spacing).ceil() as usize).clamp(8, MAX_OUTLINE_POINTS);
+                for i in 0..count {
+                    let angle = i as f32 / count as f32 * 2.0 * std::f32::consts::PI;
+                    f(
+                        self.x0 + math::cos(angle) * self.radius,
+                        self.y0 + math::sin(angle) * self.radius,
+                    );
+                }
+            }
+            ObstacleKind::Line => {
+                let (tx, ty) = (self.x1 - self.x0, self.y1 - self.y0);
+                let length = math::sqrt(tx * tx + ty * ty);
+                let count = ((length / spacing).ceil() as usize).min(MAX_OUTLINE_POINTS - 1);
+                for i in 0..=count {
+                    let u = if count == 0 {
+                        0.0
+                    } else {
+                        i as f32 / count as f32
+                    };
+                    f(self.x0 + tx * u, self.y0 + ty * u);
+                }
+            }
+        }
+    }
+}
+
+// 長さ 1 にした向き（長さがなければ None）
+fn direction(x: f32, y: f32) -> Option<(f32, f32)> {
+    let length = math::sqrt(x * x + y * y);
+    (length > 1e-6).then(|| (x / length, y / length))
+}
+
+// 法線 normal へ近づく向きの速度だけを bounce の割合で跳ね返す（離れていく速度はそのまま）
+fn reflect(p: &mut Particle, normal: (f32, f32), bounce: f32) {
+    let approach = p.vx * normal.0 + p.vy * normal.1;
+    if approach < 0.0 {
+        p.vx -= (1.0 + bounce) * approach * normal.0;
+        p.vy -= (1.0 + bounce) * approach * normal.1;
+    }
+}
+
+// 置かれている障害物（置いた順に当たりを調べる）
+#[derive(Clone, Default, Debug)]
+pub(crate) struct Obstacles {
+    list: Vec<Obstacle>,
+}
+
+impl Obstacles {
+    // NaN などを含むものと半径が 0 以下の円は置かない（置いたら true）
+    #[cfg(target_arch = "wasm32")]
+    pub fn add_circle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        if !(x.is_finite() && y.is_finite() && radius.is_finite() && radius > 0.0) {
+            return false;
+        }
+        self.list.push(Obstacle {
+            kind: ObstacleKind::Circle,
+            x0: x,
+            y0: y,
+            x1: x,
+            y1: y,
+            radius,
+        });
+        true
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn add_line(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        if ![x0, y0, x1, y1].iter().all(|v| v.is_finite()) {
+            return false;
+        }
+        self.list.push(Obstacle {
+            kind: ObstacleKind::Line,
+            x0,
+            y0,
+            x1,
+            y1,
+            radius: 0.0,
+        });
+        true
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn clear(&mut self) {
+        self.list.clear();
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn list(&self) -> Vec<Obstacle> {
+        self.list.clone()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    // 位置を更新した後の1パーティクルをすべての障害物の外に出す
+    // margin はパーティクルの半径(px)、bounce は壁と同じ跳ね返りの割合
+    pub fn apply(&self, p: &mut Particle, margin: f32, bounce: f32) {
+        for obstacle in &self.list {
+            obstacle.apply(p, margin, bounce);
+        }
+    }
+
+    // 輪郭の点を camera から見た正規化座標と色にして positions・colors の後ろに足す（WebGL・WebGPU 用）
+    #[cfg(target_arch = "wasm32")]
+    pub fn pack_outline(
+        &self,
+        spacing: f32,
+        (width, height): (f32, f32),
+        camera: Camera,
+        positions: &mut Vec<f32>,
+        colors: &mut Vec<f32>,
+    ) {
+        for obstacle in &self.list {
+            obstacle.outline(spacing, |x, y| {
+                let (x, y) = camera.to_screen(x, y);
+                positions.push((x / width) * 2.0 - 1.0);
+                positions.push(1.0 - (y / height) * 2.0);
+                colors.extend_from_slice(&OUTLINE_RGB);
+            });
+        }
+    }
+
+    // 輪郭の点を camera から見たキャンバス座標で返す（Canvas2D・ソフトウェア描画用）
+    #[cfg(target_arch = "wasm32")]
+    pub fn outline_points(&self, spacing: f32, camera: Camera) -> Vec<ColorPoint> {
+        let mut points = Vec::new();
+        for obstacle in &self.list {
+            obstacle.outline(spacing, |x, y| {
+                let (x, y) = camera.to_screen(x, y);
+                points.push(ColorPoint {
+                    x,
+                    y,
+                    rgb: OUTLINE_RGB,
+                });
+            });
+        }
+        points
+    }
+}
@@ -0,0 +1,245 @@
+use web_sys::{CanvasRenderingContext2d, WebGlRenderingContext};
+
+use crate::input::UiPointer;
+
+// 描いたフレームの上に重ねる単色の矩形（座標は CSS ピクセル、左上原点）
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OverlayRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub rgb: [f32; 3],
+}
+
+// パネルの位置と大きさ(px)
+const PANEL_X: f32 = 8.0;
+const PANEL_Y: f32 = 8.0;
+const PANEL_WIDTH: f32 = 200.0;
+const PADDING: f32 = 6.0;
+// スライダーは文字の行とつまみの溝の2段、ボタンは1段
+const SLIDER_HEIGHT: f32 = 30.0;
+const BUTTON_HEIGHT: f32 = 20.0;
+const TRACK_HEIGHT: f32 = 8.0;
+const ROW_GAP: f32 = 4.0;
+// 3x5 のフォントの1ドットの大きさ(px)と、1文字分の送り幅（ドット数）
+const FONT_SCALE: f32 = 2.0;
+const GLYPH_ADVANCE: f32 = 4.0;
+
+const BACKGROUND_RGB: [f32; 3] = [0.08, 0.08, 0.1];
+const TRACK_RGB: [f32; 3] = [0.25, 0.25, 0.3];
+const FILL_RGB: [f32; 3] = [0.3, 0.65, 1.0];
+const BUTTON_RGB: [f32; 3] = [0.2, 0.2, 0.26];
+const HOVER_RGB: [f32; 3] = [0.3, 0.3, 0.38];
+const TEXT_RGB: [f32; 3] = [0.9, 0.9, 0.9];
+
+// キャンバスに直接描く即時モードのコントロールパネル
+// 毎フレーム begin() してからスライダーとボタンを並べ、finish() で描く矩形を受け取る
+// 状態として持つのはつまんでいるスライダーだけで、値は呼ぶ側が毎回渡す
+#[derive(Default)]
+pub(crate) struct ControlPanel {
+    rects: Vec<OverlayRect>,
+    pointer: UiPointer,
+    // 押したまま動かしているスライダー
+    active: Option<u32>,
+    // 次のウィジェットを置く y
+    cursor: f32,
+    // 前のフレームで並べ終えたときの高さ（入力の層に渡す領域）
+    height: f32,
+}
+
+impl ControlPanel {
+    // 画面の上でパネルが占める領域 (x, y, 幅, 高さ)
+    pub fn region(&self) -> (f32, f32, f32, f32) {
+        (PANEL_X, PANEL_Y, PANEL_WIDTH, self.height)
+    }
+
+    pub fn begin(&mut self, pointer: UiPointer) {
+        if !pointer.down {
+            self.active = None;
+        }
+        self.pointer = pointer;
+        self.rects.clear();
+        // 背景は並べ終えてから高さを決める
+        self.rects.push(OverlayRect {
+            x: PANEL_X,
+            y: PANEL_Y,
+            width: PANEL_WIDTH,
+            height: 0.0,
+            rgb: BACKGROUND_RGB,
+        });
+        self.cursor = PANEL_Y + PADDING;
+    }
+
+    // label と 0.0〜1.0 の位置 t のスライダー。つまんで動かしたら新しい t を返す
+    pub fn slider(&mut self, id: u32, label: &str, t: f32) -> Option<f32> {
+        let (x, y) = (PANEL_X + PADDING, self.cursor);
+        let width = PANEL_WIDTH - PADDING * 2.0;
+        self.text(x, y, label);
+        if self.take_press(x, y, width, SLIDER_HEIGHT) {
+            self.active = Some(id);
+        }
+        let track_y = y + SLIDER_HEIGHT - TRACK_HEIGHT;
+        let changed = (self.active == Some(id))
+            .then(|| ((self.pointer.x - x) / width).clamp(0.0, 1.0))
+            .filter(|&value| value != t);
+        let t = changed.unwrap_or(t).clamp(0.0, 1.0);
+        self.rect(x, track_y, width, TRACK_HEIGHT, TRACK_RGB);
+        self.rect(x, track_y, width * t, TRACK_HEIGHT, FILL_RGB);
+        self.cursor += SLIDER_HEIGHT + ROW_GAP;
+        changed
+    }
+
+    // 押されたフレームだけ true
+    pub fn button(&mut self, label: &str) -> bool {
+        let (x, y) = (PANEL_X + PADDING, self.cursor);
+        let width = PANEL_WIDTH - PADDING * 2.0;
+        let pressed = self.take_press(x, y, width, BUTTON_HEIGHT);
+        let hover = self.contains(x, y, width, BUTTON_HEIGHT);
+        let rgb = if hover { HOVER_RGB } else { BUTTON_RGB };
+        self.rect(x, y, width, BUTTON_HEIGHT, rgb);
+        let text_y = y + (BUTTON_HEIGHT - 5.0 * FONT_SCALE) / 2.0;
+        self.text(x + PADDING, text_y, label);
+        self.cursor += BUTTON_HEIGHT + ROW_GAP;
+        pressed
+    }
+
+    pub fn finish(&mut self) -> &[OverlayRect] {
+        self.height = self.cursor - ROW_GAP + PADDING - PANEL_Y;
+        self.rects[0].height = self.height;
+        &self.rects
+    }
+
+    fn contains(&self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let pointer = self.pointer;
+        pointer.x >= x && pointer.x < x + width && pointer.y >= y && pointer.y < y + height
+    }
+
+    // この範囲で押していたら、その押下を使う（重なった他のウィジェットには渡さない）
+    fn take_press(&mut self, x: f32, y: f32, width: f32, height: f32) -> bool {
+        let pressed = self.pointer.pressed && self.contains(x, y, width, height);
+        if pressed {
+            self.pointer.pressed = false;
+        }
+        pressed
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, rgb: [f32; 3]) {
+        if width > 0.0 && height > 0.0 {
+            self.rects.push(OverlayRect {
+                x,
+                y,
+                width,
+                height,
+                rgb,
+            });
+        }
+    }
+
+    // 大文字・数字と . - / だけを描く（それ以外は空白）。横に続くドットは1つの矩形にまとめる
+    fn text(&mut self, x: f32, y: f32, text: &str) {
+        for (index, c) in text.chars().enumerate() {
+            let left = x + index as f32 * GLYPH_ADVANCE * FONT_SCALE;
+            for (row, bits) in glyph(c.to_ascii_uppercase()).into_iter().enumerate() {
+                let top = y + row as f32 * FONT_SCALE;
+                let mut column = 0;
+                while column < 3 {
+                    if bits & (0b100 >> column) == 0 {
+                        column += 1;
+                        continue;
+                    }
+                    let start = column;
+                    while column < 3 && bits & (0b100 >> column) != 0 {
+                        column += 1;
+                    }
+                    let run = (column - start) as f32;
+                    self.rect(
+                        left + start as f32 * FONT_SCALE,
+                        top,
+                        run * FONT_SCALE,
+                        FONT_SCALE,
+                        TEXT_RGB,
+                    );
+                }
+            }
+        }
+    }
+}
+
+// 3x5 のフォント（上の行から、左のドットが 0b100）
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b010, 0b010, 0b010],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        'A' => [0b010, 0b101, 0b111, 0b101, 0b101],
+        'B' => [0b110, 0b101, 0b110, 0b101, 0b110],
+        'C' => [0b011, 0b100, 0b100, 0b100, 0b011],
+        'D' => [0b110, 0b101, 0b101, 0b101, 0b110],
+        'E' => [0b111, 0b100, 0b110, 0b100, 0b111],
+        'F' => [0b111, 0b100, 0b110, 0b100, 0b100],
+        'G' => [0b011, 0b100, 0b101, 0b101, 0b011],
+        'H' => [0b101, 0b101, 0b111, 0b101, 0b101],
+        'I' => [0b111, 0b010, 0b010, 0b010, 0b111],
+        'J' => [0b001, 0b001, 0b001, 0b101, 0b010],
+        'K' => [0b101, 0b101, 0b110, 0b101, 0b101],
+        'L' => [0b100, 0b100, 0b100, 0b100, 0b111],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'N' => [0b110, 0b101, 0b101, 0b101, 0b101],
+        'O' => [0b010, 0b101, 0b101, 0b101, 0b010],
+        'P' => [0b110, 0b101, 0b110, 0b100, 0b100],
+        'Q' => [0b010, 0b101, 0b101, 0b110, 0b011],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        'S' => [0b011, 0b100, 0b010, 0b001, 0b110],
+        'T' => [0b111, 0b010, 0b010, 0b010, 0b010],
+        'U' => [0b101, 0b101, 0b101, 0b101, 0b111],
+        'V' => [0b101, 0b101, 0b101, 0b101, 0b010],
+        'W' => [0b101, 0b101, 0b111, 0b111, 0b101],
+        'X' => [0b101, 0b101, 0b010, 0b101, 0b101],
+        'Y' => [0b101, 0b101, 0b010, 0b010, 0b010],
+        'Z' => [0b111, 0b001, 0b010, 0b100, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        '/' => [0b001, 0b001, 0b010, 0b100, 0b100],
+        _ => [0; 5],
+    }
+}
+
+// シザーを掛けた clear で塗る（シェーダーを使わないので、どの状態のプログラムにも触らない）
+// ratio は CSS ピクセルに対する描画バッファの倍率
+pub(crate) fn draw_gl(gl: &WebGlRenderingContext, rects: &[OverlayRect], ratio: f32) {
+    let buffer_height = gl.drawing_buffer_height();
+    gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+    gl.enable(WebGlRenderingContext::SCISSOR_TEST);
+    for rect in rects {
+        let x = (rect.x * ratio).round() as i32;
+        let width = (rect.width * ratio).round() as i32;
+        let height = (rect.height * ratio).round() as i32;
+        let y = buffer_height - (rect.y * ratio).round() as i32 - height;
+        gl.scissor(x, y, width, height);
+        gl.clear_color(rect.rgb[0], rect.rgb[1], rect.rgb[2], 1.0);
+        gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+    }
+    gl.disable(WebGlRenderingContext::SCISSOR_TEST);
+}
+
+// ctx の変換はそのまま使う（CSS ピクセルで描ける変換にしておくこと）
+pub(crate) fn draw_2d(ctx: &CanvasRenderingContext2d, rects: &[OverlayRect]) {
+    for rect in rects {
+        let [r, g, b] = rect.rgb.map(|channel| (channel * 255.0).round() as u8);
+        ctx.set_fill_style_str(&format!("rgb({}, {}, {})", r, g, b));
+        ctx.fill_rect(
+            rect.x as f64,
+            rect.y as f64,
+            rect.width as f64,
+            rect.height as f64,
+        );
+    }
+}
@@ -0,0 +1,100 @@
+use wasm_bindgen::prelude::*;
+
+use crate::scene::Atlas;
+use crate::simulation::hsl_to_rgb;
+
+// パーティクルの形
+// WebGL は gl_PointSize の四角形、Canvas2D は arc() の円がそれぞれの既定なので、
+// 見た目（と塗るピクセル数）を揃えて比べるときは両方に同じ形を指定する
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParticleShape {
+    // 塗りつぶした正方形（WebGL は点そのまま、Canvas2D は fill_rect）
+    Square = 0,
+    // 縁を1ピクセルぼかした円（WebGL はフラグメントシェーダーで円の外を捨てる）
+    Circle = 1,
+    // 中心ほど明るい光の玉のスプライト（WebGL はテクスチャ、Canvas2D は色違いのアトラスから drawImage）
+    // set_particle_texture() で渡した画像に替えられる
+    Sprite = 2,
+}
+
+impl ParticleShape {
+    pub fn name(self) -> &'static str {
+        match self {
+            ParticleShape::Square => "square",
+            ParticleShape::Circle => "circle",
+            ParticleShape::Sprite => "sprite",
+        }
+    }
+}
+
+// スプライトの一辺(px)
+pub(crate) const SPRITE_SIZE: u32 = 32;
+// Canvas2D のアトラスに並べる色違いの数（色相はこの刻みに丸めて描く）
+const SPRITE_HUES: u32 = 36;
+// パーティクルの不透明度（Canvas2D の塗りの色と揃える）
+const PARTICLE_ALPHA: f32 = 0.8;
+
+// 中心からの距離（縁が 1.0）での光の玉の不透明度
+fn sprite_alpha(distance: f32) -> f32 {
+    let t = (1.0 - distance).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+// WebGL 用: 白い光の玉1つ（色はシェーダーで頂点の色を掛け、不透明度もシェーダーで掛ける）
+pub(crate) fn white_sprite() -> Vec<u8> {
+    let half = SPRITE_SIZE as f32 / 2.0;
+    let mut pixels = Vec::with_capacity((SPRITE_SIZE * SPRITE_SIZE * 4) as usize);
+    for y in 0..SPRITE_SIZE {
+        for x in 0..SPRITE_SIZE {
+            let dx = (x as f32 + 0.5 - half) / half;
+            let dy = (y as f32 + 0.5 - half) / half;
+            let a = sprite_alpha((dx * dx + dy * dy).sqrt());
+            pixels.extend_from_slice(&[255, 255, 255, (a * 255.0) as u8]);
+        }
+    }
+    pixels
+}
+
+// Canvas2D 用: 色相を SPRITE_HUES 等分した色違いの光の玉を横に並べたアトラス
+pub(crate) fn hue_atlas() -> Atlas {
+    tinted_atlas(&white_sprite())
+}
+
+// Canvas2D 用: sprite（一辺 SPRITE_SIZE の RGBA）に色相を SPRITE_HUES 等分した色を掛けて横に並べたアトラス
+// WebGL のシェーダーと同じく、色は画素の色との積、不透明度は画素の不透明度に PARTICLE_ALPHA を掛けたもの
+pub(crate) fn tinted_atlas(sprite: &[u8]) -> Atlas {
+    let width = SPRITE_SIZE * SPRITE_HUES;
+    let mut pixels = vec![0u8; (width * SPRITE_SIZE * 4) as usize];
+    for index in 0..SPRITE_HUES {
+        let (r, g, b) = hsl_to_rgb(index as f32 * 360.0 / SPRITE_HUES as f32, 1.0, 0.5);
+        let tint = [r, g, b, PARTICLE_ALPHA];
+        for (row, source) in sprite
+            .chunks_exact((SPRITE_SIZE * 4) as usize)
+            .take(SPRITE_SIZE as usize)
+            .enumerate()
+        {
+            let start = ((row as u32 * width + index * SPRITE_SIZE) * 4) as usize;
+            let dest = &mut pixels[start..start + source.len()];
+            for (i, (d, &s)) in dest.iter_mut().zip(source).enumerate() {
+                *d = (s as f32 * tint[i % 4]) as u8;
+            }
+        }
+    }
+    Atlas::new(width, SPRITE_SIZE, pixels)
+}
+
+// 設定の指紋用（組み込みの光の玉なら "builtin"、set_particle_texture() の画像なら "64x64" など）
+pub(crate) fn describe_texture(size: Option<(u32, u32)>) -> String {
+    match size {
+        Some((width, height)) => format!("{}x{}", width, height),
+        None => String::from("builtin"),
+    }
+}
+
+// hue_atlas() の中で hue に一番近い色のスプライトの左端(px)
+pub(crate) fn hue_atlas_x(hue: f32) -> f32 {
+    let step = 360.0 / SPRITE_HUES as f32;
+    let index = (hue.rem_euclid(360.0) / step).round() as u32 % SPRITE_HUES;
+    (index * SPRITE_SIZE) as f32
+}
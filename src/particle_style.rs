@@ -0,0 +1,175 @@
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+#[cfg(target_arch = "wasm32")]
+use crate::math;
+use crate::rng;
+use crate::simulation::ParticleSet;
+
+// 大きさの倍率の上限（点が画面を覆うほど大きくならないように）
+#[cfg(target_arch = "wasm32")]
+const MAX_SIZE: f32 = 16.0;
+// 揺らすときの1フレームあたりの位相の進み(ラジアン)
+#[cfg(target_arch = "wasm32")]
+const PULSE_SPEED: f32 = 0.08;
+
+// パーティクルごとの大きさと不透明度
+// 生まれたときに [min, max] から一様に選び、pulse が 0 より大きければ描くたびに揺らす
+// 既定はすべて 1（従来どおりのそろった点）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ParticleStyle {
+    // 点の大きさ（set_point_size()）に掛ける倍率
+    pub min_size: f32,
+    pub max_size: f32,
+    // 不透明度（0〜1、寿命で薄めるのにさらに掛ける）
+    pub min_alpha: f32,
+    pub max_alpha: f32,
+    // 揺らす幅（0 で揺らさない、1 で大きさが 0〜2 倍になる）
+    // 位相は色相から決めるので、パーティクルごとにずれて揺れる
+    pub pulse: f32,
+}
+
+#[wasm_bindgen]
+impl ParticleStyle {
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        min_size: f32,
+        max_size: f32,
+        min_alpha: f32,
+        max_alpha: f32,
+        pulse: f32,
+    ) -> ParticleStyle {
+        ParticleStyle {
+            min_size,
+            max_size,
+            min_alpha,
+            max_alpha,
+            pulse,
+        }
+    }
+}
+
+impl Default for ParticleStyle {
+    fn default() -> ParticleStyle {
+        ParticleStyle::new(1.0, 1.0, 1.0, 1.0, 0.0)
+    }
+}
+
+impl ParticleStyle {
+    // 範囲に収め、min と max が逆なら入れ替えたもの（NaN などは既定の値にする）
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn sanitized(&self) -> ParticleStyle {
+        let clamp = |value: f32, default: f32, max: f32| {
+            if value.is_finite() {
+                value.clamp(0.0, max)
+            } else {
+                default
+            }
+        };
+        let ordered = |a: f32, b: f32| (a.min(b), a.max(b));
+        let (min_size, max_size) = ordered(
+            clamp(self.min_size, 1.0, MAX_SIZE),
+            clamp(self.max_size, 1.0, MAX_SIZE),
+        );
+        let (min_alpha, max_alpha) = ordered(
+            clamp(self.min_alpha, 1.0, 1.0),
+            clamp(self.max_alpha, 1.0, 1.0),
+        );
+        ParticleStyle {
+            min_size,
+            max_size,
+            min_alpha,
+            max_alpha,
+            pulse: clamp(self.pulse, 0.0, 1.0),
+        }
+    }
+
+    // 既定のそろった点のままか
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn is_uniform(&self) -> bool {
+        *self == ParticleStyle::default()
+    }
+
+    // 点ごとに大きさが変わるか（WebGL は頂点ごとの大きさを送るシェーダーに替える）
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn varies_size(&self) -> bool {
+        self.min_size != 1.0 || self.max_size != 1.0 || self.pulse > 0.0
+    }
+
+    pub(crate) fn is_animated(&self) -> bool {
+        self.pulse > 0.0
+    }
+
+    // 設定の指紋用（"uniform"、"size=0.5-2/alpha=0.3-1/pulse=0.5" など）
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn describe(&self) -> String {
+        if self.is_uniform() {
+            return String::from("uniform");
+        }
+        format!(
+            "size={}-{}/alpha={}-{}/pulse={}",
+            self.min_size, self.max_size, self.min_alpha, self.max_alpha, self.pulse
+        )
+    }
+
+    // set の range のパーティクルに大きさと不透明度を選ぶ
+    // 乱数の列を進めないように生まれたときの速度と色相のハッシュから選ぶので、
+    // シードを決めた実行で位置や色は変わらない
+    pub(crate) fn assign(&self, set: &mut ParticleSet, range: Range<usize>) {
+        for i in range {
+            let key = set.vx[i].to_bits()
+                ^ set.vy[i].to_bits().rotate_left(11)
+                ^ set.hue[i].to_bits().rotate_left(22);
+            let hash = rng::hash(key);
+            let u = (hash >> 16) as f32 / u16::MAX as f32;
+            let v = (hash & 0xffff) as f32 / u16::MAX as f32;
+            set.size[i] = self.min_size + (self.max_size - self.min_size) * u;
+            set.alpha[i] = self.min_alpha + (self.max_alpha - self.min_alpha) * v;
+        }
+    }
+
+    // frame 回目のステップの後に描く (大きさの倍率, 不透明度)
+    // 不透明度は選んだ値を超えないように、大きさと同じ位相で 0 の側へだけ揺らす
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn at(&self, size: f32, alpha: f32, hue: f32, frame: u32) -> (f32, f32) {
+        if self.pulse <= 0.0 {
+            return (size, alpha);
+        }
+        let wave = self.pulse * math::sin(frame as f32 * PULSE_SPEED + hue.to_radians());
+        (
+            size * (1.0 + wave),
+            alpha * (1.0 + wave) / (1.0 + self.pulse),
+        )
+    }
+
+    // 各パーティクルの描く大きさの倍率を sizes に詰め直す（WebGL の a_size 用）
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn pack_sizes(&self, particles: &ParticleSet, frame: u32, sizes: &mut Vec<f32>) {
+        sizes.clear();
+        for ((&size, &alpha), &hue) in particles
+            .size
+            .iter()
+            .zip(&particles.alpha)
+            .zip(&particles.hue)
+        {
+            sizes.push(self.at(size, alpha, hue, frame).0);
+        }
+    }
+}
+
+// 頂点シェーダーの点の大きさに a_size の倍率を掛けたもの
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn with_point_sizes(source: &str) -> String {
+    source
+        .replace(
+            "uniform float u_pointSize;",
+            "uniform float u_pointSize;\n    attribute float a_size;",
+        )
+        .replace(
+            "gl_PointSize = u_pointSize;",
+            "gl_PointSize = u_pointSize * a_size;",
+        )
+        .replace("max(u_pointSize, 1.0)", "max(u_pointSize * a_size, 1.0)")
+}
@@ -0,0 +1,57 @@
+use wasm_bindgen::prelude::*;
+
+// パーティクルの物理演算と大きさの設定（どのバックエンドでも同じ意味）
+// 爆発の強さと範囲は ExplosionConfig で別に設定する
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SimulationConfig {
+    // 毎フレーム下向きに加える速度(px/フレーム)
+    pub gravity: f32,
+    // 壁で跳ね返ったときに残る速度の割合（0.0〜1.0）
+    pub bounce: f32,
+    // 床で跳ね返ったときに残る横方向の速度の割合（0.0〜1.0）
+    pub friction: f32,
+    // パーティクルの半径(px)。WebGL の点の大きさはこの2倍の直径で描く
+    pub point_size: f32,
+}
+
+#[wasm_bindgen]
+impl SimulationConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SimulationConfig {
+        SimulationConfig {
+            gravity: 0.0002,
+            bounce: 0.85,
+            friction: 0.98,
+            point_size: 2.5,
+        }
+    }
+}
+
+impl Default for SimulationConfig {
+    fn default() -> SimulationConfig {
+        SimulationConfig::new()
+    }
+}
+
+// 点が見えなくならない最小の半径(px)
+const MIN_POINT_SIZE: f32 = 0.5;
+
+impl SimulationConfig {
+    // 範囲外の値を丸めたもの（跳ね返りで加速したり、点が消えたりしないように）
+    pub(crate) fn clamped(self) -> SimulationConfig {
+        let finite_or = |value: f32, default: f32| if value.is_finite() { value } else { default };
+        let defaults = SimulationConfig::default();
+        SimulationConfig {
+            gravity: finite_or(self.gravity, defaults.gravity),
+            bounce: finite_or(self.bounce, defaults.bounce).clamp(0.0, 1.0),
+            friction: finite_or(self.friction, defaults.friction).clamp(0.0, 1.0),
+            point_size: finite_or(self.point_size, defaults.point_size).max(MIN_POINT_SIZE),
+        }
+    }
+
+    // WebGL の gl_PointSize と WebGL2/WebGPU の四角形の一辺(px)
+    pub(crate) fn point_diameter(&self) -> f32 {
+        self.point_size * 2.0
+    }
+}
@@ -0,0 +1,90 @@
+use wasm_bindgen::prelude::*;
+
+use crate::report;
+use crate::simulation::ParticleSet;
+
+// get_particle() が返す1パーティクル分の値（ツールチップやテストで物理演算を確かめる用）
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct ParticleInfo {
+    pub index: u32,
+    pub x: f32,
+    pub y: f32,
+    // px/フレーム
+    pub vx: f32,
+    pub vy: f32,
+    // 度（0〜360）
+    pub hue: f32,
+    // 残りの寿命（寿命のないパーティクルは無限大）
+    pub life: f32,
+}
+
+#[wasm_bindgen]
+impl ParticleInfo {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("index", self.index as f64)
+            .number("x", self.x as f64)
+            .number("y", self.y as f64)
+            .number("vx", self.vx as f64)
+            .number("vy", self.vy as f64)
+            .number("hue", self.hue as f64)
+            .number("life", self.life as f64)
+            .finish()
+    }
+}
+
+pub(crate) fn particle_info(particles: &ParticleSet, index: usize) -> Option<ParticleInfo> {
+    let p = particles.get(index)?;
+    Some(ParticleInfo {
+        index: index as u32,
+        x: p.x,
+        y: p.y,
+        vx: p.vx,
+        vy: p.vy,
+        hue: p.hue,
+        life: particles.life[index],
+    })
+}
+
+// 以下は格子がないとき（衝突を使っていないとき）の総当たり
+// x の差だけで外れと分かるものは y を読まずに飛ばす
+
+// (x, y) から radius 以内（境界を含む）のパーティクルの数
+pub(crate) fn count_within(particles: &ParticleSet, x: f32, y: f32, radius: f32) -> usize {
+    if radius.is_nan() || radius < 0.0 {
+        return 0;
+    }
+    let radius_sq = radius * radius;
+    particles
+        .x
+        .iter()
+        .zip(&particles.y)
+        .filter(|&(&px, &py)| {
+            let dx = px - x;
+            if dx.abs() > radius {
+                return false;
+            }
+            let dy = py - y;
+            dx * dx + dy * dy <= radius_sq
+        })
+        .count()
+}
+
+// (x, y) に一番近いパーティクルのインデックス（同じ距離なら小さいほう）
+pub(crate) fn nearest(particles: &ParticleSet, x: f32, y: f32) -> Option<usize> {
+    let mut best: Option<(usize, f32)> = None;
+    for (i, (&px, &py)) in particles.x.iter().zip(&particles.y).enumerate() {
+        let dx = px - x;
+        let dx_sq = dx * dx;
+        if best.is_some_and(|(_, best_sq)| dx_sq >= best_sq) {
+            continue;
+        }
+        let dy = py - y;
+        let dist_sq = dx_sq + dy * dy;
+        if best.is_none_or(|(_, best_sq)| dist_sq < best_sq) {
+            best = Some((i, dist_sq));
+        }
+    }
+    best.map(|(i, _)| i)
+}
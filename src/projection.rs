@@ -0,0 +1,163 @@
+use std::ops::Range;
+
+use wasm_bindgen::prelude::*;
+
+use crate::bounds::BoundaryMode;
+#[cfg(target_arch = "wasm32")]
+use crate::camera::Camera;
+use crate::rng;
+use crate::simulation::ParticleSet;
+
+// いちばん奥（z = depth）のパーティクルを画面に写したときの大きさの倍率（手前の z = 0 は 1）
+#[cfg(target_arch = "wasm32")]
+const FAR_SCALE: f32 = 0.5;
+// 生まれたときの奥行き方向の速さの上限(px/フレーム)
+const MAX_DEPTH_SPEED: f32 = 2.0;
+
+// パーティクルを平面で動かすか、奥行きも持たせて透視投影で描くか
+// Perspective では z と vz も積分し、奥にあるほど画面の中心に寄って小さく描く
+// 奥行きの範囲は 0（画面の面）〜 領域の幅と高さの大きいほうで、重力は z にはかからない
+// 描く順は奥行きで並べ替えない（どのバックエンドも番号順）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ProjectionMode {
+    // 従来どおりの 2D（既定）
+    #[default]
+    Flat = 0,
+    Perspective = 1,
+}
+
+impl ProjectionMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProjectionMode::Flat => "flat",
+            ProjectionMode::Perspective => "perspective",
+        }
+    }
+
+    // 生まれた range のパーティクルを奥行きの中ほどに置き、奥行き方向の速度を与える（Flat では 0）
+    // 大きさと同じく位置と色相から決めるので、乱数の列は 2D のときと変わらない
+    pub(crate) fn assign(self, set: &mut ParticleSet, range: Range<usize>, depth: f32) {
+        for i in range {
+            let (u, _) = unit_pair(set, i);
+            (set.z[i], set.vz[i]) = match self {
+                ProjectionMode::Flat => (0.0, 0.0),
+                ProjectionMode::Perspective => (depth / 2.0, (u * 2.0 - 1.0) * MAX_DEPTH_SPEED),
+            };
+        }
+    }
+
+    // 途中で切り替えたとき、すでにいるパーティクルを奥行き全体にばらまく（Flat では 0 に戻す）
+    #[cfg(any(target_arch = "wasm32", test, fuzzing))]
+    pub(crate) fn scatter(self, set: &mut ParticleSet, depth: f32) {
+        self.assign(set, 0..set.len(), depth);
+        if self == ProjectionMode::Perspective {
+            for i in 0..set.len() {
+                let (_, v) = unit_pair(set, i);
+                set.z[i] = v * depth;
+            }
+        }
+    }
+}
+
+// パーティクルの状態から決まる 0〜1 の値2つ
+fn unit_pair(set: &ParticleSet, i: usize) -> (f32, f32) {
+    let key = set.x[i].to_bits().rotate_left(7)
+        ^ set.vy[i].to_bits()
+        ^ set.hue[i].to_bits().rotate_left(19);
+    let hash = rng::hash(key);
+    (
+        (hash >> 16) as f32 / u16::MAX as f32,
+        (hash & 0xffff) as f32 / u16::MAX as f32,
+    )
+}
+
+// 奥行きの範囲(px)
+pub(crate) fn depth_extent(width: f32, height: f32) -> f32 {
+    width.max(height)
+}
+
+// 奥行きの位置を進め、手前（0）と奥（depth）の壁で跳ね返す
+// Wrap では反対側から出てくる。Respawn と None でも奥行きだけは跳ね返す（奥へ行きすぎると写せないので）
+pub(crate) fn integrate_depth(
+    set: &mut ParticleSet,
+    depth: f32,
+    bounce: f32,
+    boundary: BoundaryMode,
+) {
+    for (z, vz) in set.z.iter_mut().zip(&mut set.vz) {
+        *z += *vz;
+        if *z < 0.0 || *z > depth {
+            match boundary {
+                BoundaryMode::Wrap => *z -= depth * (*z / depth).floor(),
+                _ => {
+                    *vz *= -bounce;
+                    *z = z.clamp(0.0, depth);
+                }
+            }
+        }
+    }
+}
+
+// 奥行き z のパーティクルを写したときの大きさの倍率（WebGL の gl_Position.w の逆数と同じ）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn depth_scale(z: f32, depth: f32) -> f32 {
+    1.0 / (1.0 + (1.0 / FAR_SCALE - 1.0) * z / depth)
+}
+
+// CPU で透視投影する（Canvas2D で WebGL のシェーダーと同じ位置と大きさに描く用）
+// Canvas2D は視点の変換を ctx に任せるので、写した後もワールド座標のまま
+#[cfg(target_arch = "wasm32")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Projector {
+    depth: f32,
+    // 視点から見た画面の中心のワールド座標（奥ほどここに寄る）
+    center: (f32, f32),
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Projector {
+    pub fn new(depth: f32, camera: Camera, width: f32, height: f32) -> Projector {
+        Projector {
+            depth,
+            center: camera.to_world(width / 2.0, height / 2.0),
+        }
+    }
+
+    // 奥行き z の (x, y) を写す (x, y, 大きさの倍率)
+    pub fn project(&self, x: f32, y: f32, z: f32) -> (f32, f32, f32) {
+        let scale = depth_scale(z, self.depth);
+        let (cx, cy) = self.center;
+        (cx + (x - cx) * scale, cy + (y - cy) * scale, scale)
+    }
+}
+
+// WebGL の u_projection（列優先）
+// a_position の正規化座標と 0〜1 の奥行き a_depth から、奥ほど w が大きい（中心に寄って小さい）位置にする
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn perspective_matrix() -> [f32; 16] {
+    let k = 1.0 / FAR_SCALE - 1.0;
+    [
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, k, //
+        0.0, 0.0, 0.0, 1.0,
+    ]
+}
+
+// 頂点シェーダーを a_depth と u_projection で透視投影するものにする
+// 点の大きさも w で割って奥ほど小さくする（with_point_sizes() の後に使う）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn with_perspective(source: &str) -> String {
+    source
+        .replace(
+            "attribute vec2 a_position;",
+            "attribute vec2 a_position;\n    attribute float a_depth;\n    uniform mat4 u_projection;",
+        )
+        .replace(
+            "gl_Position = vec4(a_position, 0.0, 1.0);",
+            "gl_Position = u_projection * vec4(a_position, a_depth, 1.0);",
+        )
+        .replace("gl_PointSize = u_pointSize", "gl_PointSize = u_pointSize / gl_Position.w")
+        .replace("max(u_pointSize", "max(u_pointSize / gl_Position.w")
+}
@@ -0,0 +1,164 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, WebGlRenderingContext};
+
+use crate::i18n::{tr, Text};
+use crate::timing;
+
+// ブラウザごとの既知の問題（実行時に検出して回避し、集計結果の外れ値を説明できるよう記録する）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Quirk {
+    // gl_PointSize の上限が描きたい点の大きさより小さい（上限に丸めて描く）
+    PointSizeClamped = 0,
+    // 毎回違う fillStyle の文字列の解析が遅い（色を1度刻みの文字列に固定して使い回す）
+    SlowFillStyle = 1,
+    // requestAnimationFrame が間引かれている（バックグラウンドのタブなど。回避はできないので記録だけ）
+    RafThrottled = 2,
+}
+
+impl Quirk {
+    pub fn name(self) -> &'static str {
+        match self {
+            Quirk::PointSizeClamped => "point-size-clamped",
+            Quirk::SlowFillStyle => "slow-fill-style",
+            Quirk::RafThrottled => "raf-throttled",
+        }
+    }
+
+    // 生成時に決まり、描画の経路を変えるもの（設定のハッシュに含める）
+    fn is_static(self) -> bool {
+        !matches!(self, Quirk::RafThrottled)
+    }
+}
+
+// render() の間隔がこれより空いたら間引かれているとみなす(ms)
+const THROTTLED_INTERVAL_MS: f64 = 200.0;
+// fillStyle の解析にかかる時間を比べる回数
+const FILL_STYLE_SAMPLES: u32 = 2_000;
+// 違う文字列の設定が同じ文字列の何倍より遅ければ回避するか
+const SLOW_FILL_STYLE_RATIO: f64 = 4.0;
+
+#[derive(Default)]
+pub(crate) struct Quirks {
+    // 検出した順
+    applied: Vec<Quirk>,
+    max_point_size: Option<f32>,
+    // SlowFillStyle のとき色相(度)ごとの fillStyle
+    fill_styles: Vec<String>,
+    last_frame_ms: Option<f64>,
+}
+
+impl Quirks {
+    // largest_point_size: このバックエンドが描く一番大きい点(px)
+    pub fn detect_webgl(gl: &WebGlRenderingContext, largest_point_size: f32) -> Quirks {
+        let mut quirks = Quirks::default();
+        if let Some(max) = max_point_size(gl) {
+            if max < largest_point_size {
+                quirks.max_point_size = Some(max);
+                quirks.applied.push(Quirk::PointSizeClamped);
+            }
+        }
+        quirks
+    }
+
+    // color: 色相(度)ごとの fillStyle を作る関数
+    pub fn detect_canvas2d(
+        ctx: &CanvasRenderingContext2d,
+        color: impl Fn(f32) -> String,
+    ) -> Quirks {
+        let mut quirks = Quirks::default();
+        let styles: Vec<String> = (0..360).map(|hue| color(hue as f32)).collect();
+
+        // 同じ文字列はブラウザが解析結果を使い回せるので、違う文字列との差が解析の分になる
+        let start = timing::now_ms();
+        for _ in 0..FILL_STYLE_SAMPLES {
+            ctx.set_fill_style_str(&styles[0]);
+        }
+        let same = timing::now_ms() - start;
+        let start = timing::now_ms();
+        for i in 0..FILL_STYLE_SAMPLES {
+            ctx.set_fill_style_str(&styles[i as usize % styles.len()]);
+        }
+        let distinct = timing::now_ms() - start;
+
+        if distinct > same * SLOW_FILL_STYLE_RATIO && distinct > 1.0 {
+            quirks.fill_styles = styles;
+            quirks.applied.push(Quirk::SlowFillStyle);
+        }
+        quirks
+    }
+
+    pub fn applied(&self) -> &[Quirk] {
+        &self.applied
+    }
+
+    // gl_PointSize に渡す大きさ
+    pub fn point_size(&self, size: f32) -> f32 {
+        self.max_point_size.map_or(size, |max| size.min(max))
+    }
+
+    // SlowFillStyle のとき、色相に近い使い回し用の fillStyle
+    pub fn fill_style(&self, hue: f32) -> Option<&str> {
+        if self.fill_styles.is_empty() {
+            return None;
+        }
+        let index = (hue.round() as usize) % self.fill_styles.len();
+        Some(&self.fill_styles[index])
+    }
+
+    // render() のたびに呼び、間引きを初めて検出したら説明を返す
+    pub fn note_frame(&mut self) -> Option<String> {
+        let now = timing::now_ms();
+        let interval = self.last_frame_ms.replace(now).map(|last| now - last)?;
+        if interval < THROTTLED_INTERVAL_MS || self.applied.contains(&Quirk::RafThrottled) {
+            return None;
+        }
+        self.applied.push(Quirk::RafThrottled);
+        Some(tr(Text::QuirkRafThrottled, &[&interval.round()]))
+    }
+
+    // 生成時に検出したものの説明（イベントとして通知する）
+    pub fn messages(&self) -> Vec<String> {
+        let mut messages = Vec::new();
+        for quirk in &self.applied {
+            match quirk {
+                Quirk::PointSizeClamped => messages.push(tr(
+                    Text::QuirkPointSizeClamped,
+                    &[&self.max_point_size.unwrap_or_default()],
+                )),
+                Quirk::SlowFillStyle => messages.push(tr(Text::QuirkSlowFillStyle, &[])),
+                Quirk::RafThrottled => {}
+            }
+        }
+        messages
+    }
+
+    // 設定のハッシュに書く値（"a,b" または "none"）
+    pub fn fingerprint(&self) -> String {
+        let names: Vec<&str> = self
+            .applied
+            .iter()
+            .filter(|quirk| quirk.is_static())
+            .map(|quirk| quirk.name())
+            .collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(",")
+        }
+    }
+}
+
+// gl_PointSize で描ける一番大きい点(px)
+pub(crate) fn max_point_size(gl: &WebGlRenderingContext) -> Option<f32> {
+    let range = gl
+        .get_parameter(WebGlRenderingContext::ALIASED_POINT_SIZE_RANGE)
+        .ok()?
+        .dyn_into::<js_sys::Float32Array>()
+        .ok()?
+        .to_vec();
+    match range.as_slice() {
+        &[_, max] => Some(max),
+        _ => None,
+    }
+}
@@ -0,0 +1,312 @@
+use crate::render_mode::RenderMode;
+use crate::scene::{
+    create_scene, Atlas, ColorPoint, ColorRect, CubicBezier, Polygon, Scene, SceneKind, Sprite,
+    Surface,
+};
+use crate::simulation::Simulation;
+use crate::tessellation;
+use crate::BACKGROUND_GRAY;
+
+// WASM内だけで描く Surface（ブラウザに依存しないので、同じシードなら常に同じ画像になる）
+// 各画素の中心だけを見る単純なラスタライザ（アンチエイリアスなし）
+// scale > 1 ならキャンバスの scale×scale px を1画素にまとめた縮小画像になる
+pub(crate) struct RasterSurface {
+    canvas_width: f32,
+    canvas_height: f32,
+    scale: f32,
+    width: usize,
+    height: usize,
+    pixels: Vec<[f32; 3]>,
+    points: Vec<(f32, f32)>,
+    indices: Vec<u32>,
+}
+
+impl RasterSurface {
+    pub fn new(canvas_width: f32, canvas_height: f32, scale: f32) -> RasterSurface {
+        let width = ((canvas_width / scale).ceil() as usize).max(1);
+        let height = ((canvas_height / scale).ceil() as usize).max(1);
+        RasterSurface {
+            canvas_width,
+            canvas_height,
+            scale,
+            width,
+            height,
+            pixels: vec![[0.0; 3]; width * height],
+            points: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    // 1行ずつ上から並んだ画素
+    pub fn pixels(&self) -> &[[f32; 3]] {
+        &self.pixels
+    }
+
+    // 8ビットのRGB（1行目が上端）
+    pub fn to_rgb8(&self, out: &mut Vec<u8>) {
+        out.clear();
+        for rgb in &self.pixels {
+            out.extend(rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+        }
+    }
+
+    // 8ビットの不透明なRGBA（ImageData にそのまま渡せる並び）
+    pub fn to_rgba8(&self, out: &mut Vec<u8>) {
+        out.clear();
+        for rgb in &self.pixels {
+            out.extend(rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+            out.push(255);
+        }
+    }
+
+    // 画素の数（横, 縦）
+    pub fn resolution(&self) -> (u32, u32) {
+        (self.width as u32, self.height as u32)
+    }
+
+    // 画素 (x, y) の中心のキャンバス座標
+    fn center(&self, x: usize, y: usize) -> (f32, f32) {
+        ((x as f32 + 0.5) * self.scale, (y as f32 + 0.5) * self.scale)
+    }
+
+    // キャンバス座標の範囲に中心が入る画素の範囲
+    fn cell_range(&self, min: f32, max: f32, cells: usize) -> std::ops::Range<usize> {
+        let first = (min / self.scale - 0.5).ceil().max(0.0) as usize;
+        let end = ((max / self.scale - 0.5).floor() + 1.0).clamp(0.0, cells as f32) as usize;
+        first..end.max(first)
+    }
+
+    pub fn plot(&mut self, x: f32, y: f32, rgb: [f32; 3]) {
+        if x < 0.0 || y < 0.0 || x >= self.canvas_width || y >= self.canvas_height {
+            return;
+        }
+        let (px, py) = ((x / self.scale) as usize, (y / self.scale) as usize);
+        self.pixels[py.min(self.height - 1) * self.width + px.min(self.width - 1)] = rgb;
+    }
+
+    fn fill_triangle(&mut self, a: (f32, f32), b: (f32, f32), c: (f32, f32), rgb: [f32; 3]) {
+        let cross = |p: (f32, f32), q: (f32, f32), r: (f32, f32)| {
+            (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+        };
+        let area = cross(a, b, c);
+        if area == 0.0 {
+            return;
+        }
+        let xs = self.cell_range(a.0.min(b.0).min(c.0), a.0.max(b.0).max(c.0), self.width);
+        let ys = self.cell_range(a.1.min(b.1).min(c.1), a.1.max(b.1).max(c.1), self.height);
+        for y in ys {
+            for x in xs.clone() {
+                let p = self.center(x, y);
+                // 向きに関係なく判定できるよう面積の符号を掛ける
+                if cross(a, b, p) * area >= 0.0
+                    && cross(b, c, p) * area >= 0.0
+                    && cross(c, a, p) * area >= 0.0
+                {
+                    self.pixels[y * self.width + x] = rgb;
+                }
+            }
+        }
+    }
+}
+
+impl Surface for RasterSurface {
+    fn size(&self) -> (f32, f32) {
+        (self.canvas_width, self.canvas_height)
+    }
+
+    fn clear(&mut self, rgb: [f32; 3]) {
+        self.pixels.fill(rgb);
+    }
+
+    fn blit_rgba(&mut self, width: u32, height: u32, pixels: &[u8]) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (cx, cy) = self.center(x, y);
+                let sx = ((cx / self.canvas_width * width as f32) as usize).min(width as usize - 1);
+                let sy =
+                    ((cy / self.canvas_height * height as f32) as usize).min(height as usize - 1);
+                let i = (sy * width as usize + sx) * 4;
+                self.pixels[y * self.width + x] = [
+                    pixels[i] as f32 / 255.0,
+                    pixels[i + 1] as f32 / 255.0,
+                    pixels[i + 2] as f32 / 255.0,
+                ];
+            }
+        }
+    }
+
+    // 格子が粗いときは各ピクセルを含むマスの色を返す
+    fn read_rgba(&mut self, x: u32, y: u32, width: u32, height: u32, out: &mut Vec<u8>) -> bool {
+        let (canvas_width, canvas_height) = (self.canvas_width as u32, self.canvas_height as u32);
+        let (x, y) = (x.min(canvas_width), y.min(canvas_height));
+        let (width, height) = (width.min(canvas_width - x), height.min(canvas_height - y));
+        out.clear();
+        for py in y..y + height {
+            let cy = ((py as f32 / self.scale) as usize).min(self.height - 1);
+            for px in x..x + width {
+                let cx = ((px as f32 / self.scale) as usize).min(self.width - 1);
+                let rgb = self.pixels[cy * self.width + cx];
+                out.extend(rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8));
+                out.push(255);
+            }
+        }
+        !out.is_empty()
+    }
+
+    fn fill_rects(&mut self, rects: &[ColorRect]) {
+        for rect in rects {
+            let xs = self.cell_range(rect.x, rect.x + rect.width, self.width);
+            for y in self.cell_range(rect.y, rect.y + rect.height, self.height) {
+                let row = y * self.width;
+                self.pixels[row + xs.start..row + xs.end].fill(rect.rgb);
+            }
+        }
+    }
+
+    fn draw_sprites(&mut self, atlas: &Atlas, sprites: &[Sprite]) {
+        for sprite in sprites {
+            let [a, b, c, d, e, f] = sprite.transform;
+            let det = a * d - b * c;
+            if det == 0.0 {
+                continue;
+            }
+            let corners = [
+                (e, f),
+                (a + e, b + f),
+                (c + e, d + f),
+                (a + c + e, b + d + f),
+            ];
+            let (min_x, max_x) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+                (lo.min(p.0), hi.max(p.0))
+            });
+            let (min_y, max_y) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| {
+                (lo.min(p.1), hi.max(p.1))
+            });
+
+            let [sx, sy, sw, sh] = sprite.source;
+            for y in self.cell_range(min_y, max_y, self.height) {
+                for x in self.cell_range(min_x, max_x, self.width) {
+                    // 画素の中心を単位正方形の座標に戻してアトラスから拾う
+                    let (px, py) = self.center(x, y);
+                    let u = (d * (px - e) - c * (py - f)) / det;
+                    let v = (a * (py - f) - b * (px - e)) / det;
+                    if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                        continue;
+                    }
+                    let ax = ((sx + u * sw) as usize).min(atlas.width as usize - 1);
+                    let ay = ((sy + v * sh) as usize).min(atlas.height as usize - 1);
+                    let i = (ay * atlas.width as usize + ax) * 4;
+                    if atlas.pixels[i + 3] >= 128 {
+                        self.pixels[y * self.width + x] = [
+                            atlas.pixels[i] as f32 / 255.0,
+                            atlas.pixels[i + 1] as f32 / 255.0,
+                            atlas.pixels[i + 2] as f32 / 255.0,
+                        ];
+                    }
+                }
+            }
+        }
+    }
+
+    fn stroke_beziers(&mut self, curves: &[CubicBezier]) {
+        let mut points = std::mem::take(&mut self.points);
+        for curve in curves {
+            points.clear();
+            points.push(curve.points[0]);
+            tessellation::flatten_cubic(&curve.points, &mut points);
+            for segment in points.windows(2) {
+                let ((x0, y0), (x1, y1)) = (segment[0], segment[1]);
+                let length = (x1 - x0).abs().max((y1 - y0).abs());
+                let steps = (length / self.scale).ceil().max(1.0) as u32;
+                for i in 0..=steps {
+                    let t = i as f32 / steps as f32;
+                    self.plot(x0 + (x1 - x0) * t, y0 + (y1 - y0) * t, curve.rgb);
+                }
+            }
+        }
+        self.points = points;
+    }
+
+    fn fill_polygons(&mut self, polygons: &[Polygon]) {
+        let mut indices = std::mem::take(&mut self.indices);
+        for polygon in polygons {
+            indices.clear();
+            tessellation::earcut(&polygon.points, &mut indices);
+            for triangle in indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|k| polygon.points[triangle[k] as usize]);
+                self.fill_triangle(a, b, c, polygon.rgb);
+            }
+        }
+        self.indices = indices;
+    }
+
+    // 加算合成は色ごとに 1.0 で飽和させる
+    fn draw_points(&mut self, points: &[ColorPoint], size: f32, mode: RenderMode) {
+        for p in points {
+            let half = size / 2.0;
+            let xs = self.cell_range(p.x - half, p.x + half, self.width);
+            for y in self.cell_range(p.y - half, p.y + half, self.height) {
+                let row = y * self.width;
+                for pixel in &mut self.pixels[row + xs.start..row + xs.end] {
+                    *pixel = match mode {
+                        RenderMode::Normal | RenderMode::Trails => p.rgb,
+                        RenderMode::Additive => [0, 1, 2].map(|i| (pixel[i] + p.rgb[i]).min(1.0)),
+                    };
+                }
+            }
+        }
+    }
+}
+
+// バックエンドなしで進めて描けるシーン
+// Particles は Scene ではないので、シミュレーションを持ってバックエンドと同じ色の点として描く
+pub(crate) enum RasterRun {
+    Scene(Box<dyn Scene>),
+    Particles(Box<Simulation>),
+}
+
+impl RasterRun {
+    // particle_count は Particles のときだけ使う
+    pub fn new(
+        kind: SceneKind,
+        width: f32,
+        height: f32,
+        particle_count: usize,
+    ) -> Result<RasterRun, String> {
+        Ok(match create_scene(kind, width, height)? {
+            Some(scene) => RasterRun::Scene(scene),
+            None => RasterRun::Particles(Box::new(Simulation::new(width, height, particle_count)?)),
+        })
+    }
+
+    pub fn set_load(&mut self, load: f32) {
+        match self {
+            RasterRun::Scene(scene) => scene.set_load(load),
+            RasterRun::Particles(sim) => sim.set_load(load),
+        }
+    }
+
+    pub fn update(&mut self) {
+        match self {
+            RasterRun::Scene(scene) => scene.update(),
+            RasterRun::Particles(sim) => sim.step(),
+        }
+    }
+
+    pub fn render(&mut self, surface: &mut RasterSurface) {
+        match self {
+            RasterRun::Scene(scene) => scene.render(surface),
+            RasterRun::Particles(sim) => {
+                surface.clear([BACKGROUND_GRAY; 3]);
+                let particles = sim.particles();
+                for (p, &life) in particles.iter().zip(&particles.life) {
+                    let (r, g, b) = crate::simulation::hsl_to_rgb(p.hue, 1.0, 0.5);
+                    // GPU のバックエンドと同じく背景に寿命の割合で重ねた色にする
+                    let alpha = life.clamp(0.0, 1.0);
+                    let fade = |c: f32| BACKGROUND_GRAY + (c - BACKGROUND_GRAY) * alpha;
+                    surface.plot(p.x, p.y, [fade(r), fade(g), fade(b)]);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+
+use crate::backend::Backend;
+use crate::driver::LoopDriver;
+use crate::i18n::{tr, Text};
+
+// 次のフレームでも自分自身を登録し直すコールバック
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+// requestAnimationFrame のループを WASM の中で回す（JS から毎フレーム tick() を呼ばずに済む）
+// 計測に JS とのやりとりの時間が混ざらず、埋め込む側は run() と stop() を呼ぶだけでよい
+// バックエンドは受け取って持つので、設定を変えるときは into_backend() で取り戻す
+#[wasm_bindgen]
+pub struct RenderLoop {
+    backend: Rc<RefCell<Backend>>,
+    running: Rc<Cell<bool>>,
+    // true なら経過時間の分だけ固定長のステップで進め、遅れたフレームの分の描画を飛ばす
+    frame_skip: Rc<Cell<bool>>,
+    // 前の回の timestamp(ms)（run() し直したら数え直す）
+    last_timestamp: Rc<Cell<Option<f64>>>,
+    // 登録済みの requestAnimationFrame の ID（stop() で取り消す）
+    request: Rc<Cell<Option<i32>>>,
+    callback: FrameCallback,
+}
+
+#[wasm_bindgen]
+impl RenderLoop {
+    #[wasm_bindgen(constructor)]
+    pub fn new(backend: Backend) -> RenderLoop {
+        RenderLoop {
+            backend: Rc::new(RefCell::new(backend)),
+            running: Rc::new(Cell::new(false)),
+            frame_skip: Rc::new(Cell::new(false)),
+            last_timestamp: Rc::new(Cell::new(None)),
+            request: Rc::new(Cell::new(None)),
+            callback: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    // ループを始める（回っている間に呼ぶと上限だけを変える）
+    // 毎回 Backend::tick() で物理を進め、target_fps を超える回の描画は飛ばす（None なら毎回描く）
+    pub fn run(&mut self, target_fps: Option<u32>) -> Result<(), JsValue> {
+        let window = web_sys::window().ok_or_else(|| {
+            tr(
+                Text::LoopDriverUnavailable,
+                &[&LoopDriver::AnimationFrame.name()],
+            )
+        })?;
+        self.backend
+            .borrow_mut()
+            .set_target_fps(target_fps.map_or(0.0, f64::from));
+        if self.running.replace(true) {
+            return Ok(());
+        }
+        self.last_timestamp.set(None);
+        if self.callback.borrow().is_none() {
+            let (backend, frame_skip) = (self.backend.clone(), self.frame_skip.clone());
+            let (running, request) = (self.running.clone(), self.request.clone());
+            let (next, frame_window) = (self.callback.clone(), window.clone());
+            let last_timestamp = self.last_timestamp.clone();
+            *self.callback.borrow_mut() = Some(Closure::new(move |timestamp: f64| {
+                request.set(None);
+                if !running.get() {
+                    return;
+                }
+                let dt_ms = last_timestamp
+                    .replace(Some(timestamp))
+                    .map_or(0.0, |last| timestamp - last);
+                // tick() の中のイベントのコールバックから stop() されたら、次は登録しない
+                if let Ok(mut backend) = backend.try_borrow_mut() {
+                    if frame_skip.get() {
+                        backend.update_with_dt(dt_ms);
+                        backend.render_capped(timestamp);
+                    } else {
+                        backend.tick(timestamp);
+                    }
+                }
+                if running.get() {
+                    if let Some(callback) = next.borrow().as_ref() {
+                        request.set(schedule(&frame_window, callback));
+                    }
+                }
+            }));
+        }
+        if let Some(callback) = self.callback.borrow().as_ref() {
+            self.request.set(schedule(&window, callback));
+        }
+        Ok(())
+    }
+
+    // 次のフレームを取り消して止める（バックエンドの状態はそのまま）
+    pub fn stop(&mut self) {
+        self.running.set(false);
+        if let (Some(id), Some(window)) = (self.request.take(), web_sys::window()) {
+            let _ = window.cancel_animation_frame(id);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.get()
+    }
+
+    // 物理を requestAnimationFrame の間隔ではなく経過時間で進めるか（既定は false）
+    // 遅い端末でも1秒に進む量が変わらず、追いつけないフレームの描画は飛ぶ（ステップの長さは set_fixed_timestep()）
+    pub fn set_frame_skip(&mut self, enabled: bool) {
+        self.frame_skip.set(enabled);
+    }
+
+    pub fn is_frame_skip(&self) -> bool {
+        self.frame_skip.get()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        self.backend.borrow().get_frame_count()
+    }
+
+    // run() してから描いた回数と、上限を超えて飛ばした回数
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.backend.borrow().get_rendered_ticks()
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.backend.borrow().get_skipped_ticks()
+    }
+
+    // 止めてバックエンドを返す（この RenderLoop は使えなくなる）
+    pub fn into_backend(mut self) -> Option<Backend> {
+        self.stop();
+        self.callback.borrow_mut().take();
+        let backend = self.backend.clone();
+        drop(self);
+        Rc::try_unwrap(backend).ok().map(RefCell::into_inner)
+    }
+}
+
+impl Drop for RenderLoop {
+    fn drop(&mut self) {
+        self.stop();
+        // コールバックが自分自身を参照しているので、外さないとバックエンドごと残る
+        if let Ok(mut callback) = self.callback.try_borrow_mut() {
+            callback.take();
+        }
+    }
+}
+
+fn schedule(window: &web_sys::Window, callback: &Closure<dyn FnMut(f64)>) -> Option<i32> {
+    window
+        .request_animation_frame(callback.as_ref().unchecked_ref())
+        .ok()
+}
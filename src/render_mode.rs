@@ -0,0 +1,206 @@
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use web_sys::WebGlRenderingContext;
+
+// パーティクルの合成方法
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    // 通常のアルファブレンド
+    Normal = 0,
+    // 加算合成（重なるほど明るくなる）
+    Additive = 1,
+    // 画面を消さずに前のフレームを背景色で薄め、その上にアルファブレンドで描く（残像）
+    Trails = 2,
+}
+
+// Trails で前のフレームに重ねる背景色の不透明度（小さいほど残像が長い）
+pub(crate) const TRAIL_FADE: f32 = 0.15;
+
+impl RenderMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::Normal => "normal",
+            RenderMode::Additive => "additive",
+            RenderMode::Trails => "trails",
+        }
+    }
+
+    // Canvas2D の globalCompositeOperation
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn composite_operation(self) -> &'static str {
+        match self {
+            RenderMode::Normal | RenderMode::Trails => "source-over",
+            RenderMode::Additive => "lighter",
+        }
+    }
+
+    // WebGL のブレンド設定
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn apply_gl(self, gl: &WebGlRenderingContext) {
+        gl.enable(WebGlRenderingContext::BLEND);
+        match self {
+            RenderMode::Normal | RenderMode::Trails => gl.blend_func(
+                WebGlRenderingContext::SRC_ALPHA,
+                WebGlRenderingContext::ONE_MINUS_SRC_ALPHA,
+            ),
+            RenderMode::Additive => {
+                gl.blend_func(WebGlRenderingContext::SRC_ALPHA, WebGlRenderingContext::ONE)
+            }
+        }
+    }
+}
+
+// EXT_blend_minmax（WebGL2 では標準）の式
+#[cfg(target_arch = "wasm32")]
+const MIN_EXT: u32 = 0x8007;
+#[cfg(target_arch = "wasm32")]
+const MAX_EXT: u32 = 0x8008;
+
+// compositing シーンで比べる合成方法（名前は Canvas2D の globalCompositeOperation）
+// WebGL はブレンド式で同じ結果にする。ブレンド式で表せないもの（overlay など）は描けない
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompositeMode {
+    SourceOver = 0,
+    Lighter = 1,
+    Multiply = 2,
+    Screen = 3,
+    Darken = 4,
+    Lighten = 5,
+    Xor = 6,
+    DestinationOver = 7,
+    SourceAtop = 8,
+    Overlay = 9,
+    Difference = 10,
+    Luminosity = 11,
+}
+
+// WebGL のブレンド式と、式に合わせた頂点の色の直し方
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct GlBlend {
+    pub equation: u32,
+    pub src: u32,
+    pub dst: u32,
+    color: BlendColor,
+}
+
+#[cfg(target_arch = "wasm32")]
+enum BlendColor {
+    // そのまま（不透明度は定数の CONSTANT_ALPHA で掛ける）
+    Plain,
+    // 不透明度を掛けておく
+    Premultiplied,
+    // 不透明度の分だけ白から色に近づける（乗算で alpha を表す）
+    TowardWhite,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl GlBlend {
+    pub fn color(&self, rgb: [f32; 3], alpha: f32) -> [f32; 3] {
+        match self.color {
+            BlendColor::Plain => rgb,
+            BlendColor::Premultiplied => rgb.map(|c| c * alpha),
+            BlendColor::TowardWhite => rgb.map(|c| 1.0 - alpha + alpha * c),
+        }
+    }
+
+    // EXT_blend_minmax が必要か
+    pub fn needs_minmax(&self) -> bool {
+        self.equation == MIN_EXT || self.equation == MAX_EXT
+    }
+}
+
+impl CompositeMode {
+    pub const ALL: [CompositeMode; 12] = [
+        CompositeMode::SourceOver,
+        CompositeMode::Lighter,
+        CompositeMode::Multiply,
+        CompositeMode::Screen,
+        CompositeMode::Darken,
+        CompositeMode::Lighten,
+        CompositeMode::Xor,
+        CompositeMode::DestinationOver,
+        CompositeMode::SourceAtop,
+        CompositeMode::Overlay,
+        CompositeMode::Difference,
+        CompositeMode::Luminosity,
+    ];
+
+    // Canvas2D の globalCompositeOperation と同じ
+    pub fn name(self) -> &'static str {
+        match self {
+            CompositeMode::SourceOver => "source-over",
+            CompositeMode::Lighter => "lighter",
+            CompositeMode::Multiply => "multiply",
+            CompositeMode::Screen => "screen",
+            CompositeMode::Darken => "darken",
+            CompositeMode::Lighten => "lighten",
+            CompositeMode::Xor => "xor",
+            CompositeMode::DestinationOver => "destination-over",
+            CompositeMode::SourceAtop => "source-atop",
+            CompositeMode::Overlay => "overlay",
+            CompositeMode::Difference => "difference",
+            CompositeMode::Luminosity => "luminosity",
+        }
+    }
+
+    // 不透明な背景に重ねたときに Canvas2D と同じ結果になる WebGL のブレンド
+    // darken / lighten の min / max は不透明度を掛けられないので、不透明で重ねたのと同じになる
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn gl_blend(self) -> Option<GlBlend> {
+        use WebGlRenderingContext as Gl;
+        let (equation, src, dst, color) = match self {
+            CompositeMode::SourceOver => (
+                Gl::FUNC_ADD,
+                Gl::CONSTANT_ALPHA,
+                Gl::ONE_MINUS_CONSTANT_ALPHA,
+                BlendColor::Plain,
+            ),
+            CompositeMode::Lighter => {
+                (Gl::FUNC_ADD, Gl::CONSTANT_ALPHA, Gl::ONE, BlendColor::Plain)
+            }
+            CompositeMode::Multiply => (
+                Gl::FUNC_ADD,
+                Gl::ZERO,
+                Gl::SRC_COLOR,
+                BlendColor::TowardWhite,
+            ),
+            CompositeMode::Screen => (
+                Gl::FUNC_ADD,
+                Gl::ONE,
+                Gl::ONE_MINUS_SRC_COLOR,
+                BlendColor::Premultiplied,
+            ),
+            CompositeMode::Darken => (MIN_EXT, Gl::ONE, Gl::ONE, BlendColor::Plain),
+            CompositeMode::Lighten => (MAX_EXT, Gl::ONE, Gl::ONE, BlendColor::Plain),
+            CompositeMode::Xor => (
+                Gl::FUNC_ADD,
+                Gl::ONE_MINUS_DST_ALPHA,
+                Gl::ONE_MINUS_CONSTANT_ALPHA,
+                BlendColor::Premultiplied,
+            ),
+            CompositeMode::DestinationOver => (
+                Gl::FUNC_ADD,
+                Gl::ONE_MINUS_DST_ALPHA,
+                Gl::ONE,
+                BlendColor::Premultiplied,
+            ),
+            CompositeMode::SourceAtop => (
+                Gl::FUNC_ADD,
+                Gl::DST_ALPHA,
+                Gl::ONE_MINUS_CONSTANT_ALPHA,
+                BlendColor::Premultiplied,
+            ),
+            CompositeMode::Overlay | CompositeMode::Difference | CompositeMode::Luminosity => {
+                return None
+            }
+        };
+        Some(GlBlend {
+            equation,
+            src,
+            dst,
+            color,
+        })
+    }
+}
@@ -0,0 +1,138 @@
+// JSON で書き出す結果（get_metrics_json()・RunnerReport::to_json()）の形式のバージョン
+// 項目を増やしたり意味を変えたりしたら REPORT_VERSION を上げ、MIGRATIONS に変換を足す
+// 古い結果は migrate_report() で最新の形式に揃えてから比べる
+// （SuiteReport のバイナリ形式は binary_report.rs の VERSION で別に管理する）
+//
+// 1: バージョンを埋め込む前の形式（"version" がない）
+// 2: 先頭に "version" を持つ
+// 3: set_metadata() で付けた "metadata" を持つ
+// 4: get_metrics_json() が wasm_memory_bytes・particle_buffer_bytes・allocations_per_frame を持つ
+
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+use crate::json::{self, JsonObject, Value};
+
+pub const REPORT_VERSION: u32 = 4;
+
+type Migration = fn(&mut Value);
+
+// MIGRATIONS[i] はバージョン i + 1 の結果を i + 2 に書き換える
+const MIGRATIONS: [Migration; 3] = [v1_to_v2, v2_to_v3, v3_to_v4];
+
+thread_local! {
+    // set_metadata() で付けた JSON オブジェクト
+    static METADATA: RefCell<String> = RefCell::new(String::from("{}"));
+}
+
+#[wasm_bindgen]
+pub fn get_report_version() -> u32 {
+    REPORT_VERSION
+}
+
+// 端末名や冷却の状態、ブラウザのフラグなど、任意のキーと値を JSON オブジェクトで付ける
+// これ以降に書き出すすべての結果（JSON・SuiteReport）に "metadata" として入る
+#[wasm_bindgen]
+pub fn set_metadata(json: &str) -> Result<(), JsValue> {
+    let metadata = js_sys::JSON::parse(json)
+        .ok()
+        .filter(|value| value.is_object() && !js_sys::Array::is_array(value))
+        .ok_or_else(|| tr(Text::MetadataInvalid, &[]))?;
+    let text = js_sys::JSON::stringify(&metadata)?
+        .as_string()
+        .ok_or_else(|| tr(Text::MetadataInvalid, &[]))?;
+    METADATA.with_borrow_mut(|current| *current = text);
+    Ok(())
+}
+
+#[wasm_bindgen]
+pub fn get_metadata() -> String {
+    metadata()
+}
+
+#[wasm_bindgen]
+pub fn clear_metadata() {
+    METADATA.with_borrow_mut(|current| *current = String::from("{}"));
+}
+
+pub(crate) fn metadata() -> String {
+    METADATA.with_borrow(Clone::clone)
+}
+
+// 古い形式の JSON を最新の形式に変換する（最新ならそのまま返す）
+#[wasm_bindgen]
+pub fn migrate_report(old_json: &str) -> Result<String, JsValue> {
+    Ok(migrate(old_json)?)
+}
+
+// migrate_report() の中身（JsValue を使わないので、ネイティブの fuzz からも呼べる）
+pub(crate) fn migrate(old_json: &str) -> Result<String, String> {
+    let mut report = json::parse(old_json)
+        .filter(|value| matches!(value, Value::Object(_)))
+        .ok_or_else(|| tr(Text::ReportJsonInvalid, &[]))?;
+    let version = match report.get("version").and_then(Value::as_f64) {
+        Some(version) if version >= 1.0 && version.fract() == 0.0 => version,
+        Some(_) => return Err(tr(Text::ReportJsonInvalid, &[])),
+        None => 1.0,
+    };
+    if version > REPORT_VERSION as f64 {
+        return Err(tr(Text::ReportVersionUnsupported, &[&version]));
+    }
+    for migrate in &MIGRATIONS[version as usize - 1..] {
+        migrate(&mut report);
+    }
+    Ok(report.to_json())
+}
+
+// 書き出す JSON は必ずこれから組み立てる
+pub(crate) fn versioned() -> JsonObject {
+    JsonObject::new()
+        .number("version", REPORT_VERSION as f64)
+        .raw("metadata", &metadata())
+}
+
+fn v1_to_v2(report: &mut Value) {
+    report.set("version", Value::Number(2.0));
+}
+
+fn v2_to_v3(report: &mut Value) {
+    report.set("version", Value::Number(3.0));
+    if report.get("metadata").is_none() {
+        report.set("metadata", Value::Object(Vec::new()));
+    }
+}
+
+// 古い計測値にはメモリの項目がないが、測っていない値を埋めると比べるときに紛らわしいので足さない
+fn v3_to_v4(report: &mut Value) {
+    report.set("version", Value::Number(4.0));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_unversioned_reports_to_the_latest_version() {
+        let migrated = migrate(r#"{"fps": 60, "metadata": null}"#).expect("version 1 report");
+        assert_eq!(migrated, r#"{"fps":60,"metadata":null,"version":4}"#);
+        let migrated = migrate(r#"{"version": 2, "fps": 60}"#).expect("version 2 report");
+        assert_eq!(migrated, r#"{"version":4,"fps":60,"metadata":{}}"#);
+    }
+
+    #[test]
+    fn rejects_invalid_and_newer_reports() {
+        for text in ["[]", "1", "{", r#"{"version": 1.5}"#, r#"{"version": 0}"#] {
+            assert_eq!(
+                migrate(text),
+                Err(tr(Text::ReportJsonInvalid, &[])),
+                "{text:?}"
+            );
+        }
+        assert_eq!(
+            migrate(r#"{"version": 5}"#),
+            Err(tr(Text::ReportVersionUnsupported, &[&5]))
+        );
+    }
+}
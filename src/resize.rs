@@ -0,0 +1,164 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas, ResizeObserver, ResizeObserverEntry};
+
+use crate::i18n::{tr, Text};
+
+// 描画バッファの倍率の範囲（極端な値で巨大なバッファを確保しないように）
+const MIN_PIXEL_RATIO: f32 = 0.25;
+const MAX_PIXEL_RATIO: f32 = 4.0;
+// set_render_scale() の範囲
+const MIN_RENDER_SCALE: f32 = 0.25;
+const MAX_RENDER_SCALE: f32 = 4.0;
+
+// ページの devicePixelRatio（Worker など window がなければ 1.0）
+#[wasm_bindgen]
+pub fn device_pixel_ratio() -> f32 {
+    web_sys::window().map_or(1.0, |window| window.device_pixel_ratio() as f32)
+}
+
+// キャンバスの描画バッファの大きさの管理
+// シミュレーションと描画の座標はCSSピクセルのままで、描画バッファだけを pixel_ratio × render_scale 倍にする
+pub(crate) struct CanvasSizing {
+    pixel_ratio: f32,
+    // 表示の解像度に対する描く解像度の倍率（1 より大きければスーパーサンプリング、小さければ拡大表示）
+    // pixel_ratio と違い、observe_resize() で devicePixelRatio が変わっても保つ
+    render_scale: f32,
+    watcher: Option<ResizeWatcher>,
+}
+
+impl Default for CanvasSizing {
+    fn default() -> CanvasSizing {
+        CanvasSizing {
+            pixel_ratio: 1.0,
+            render_scale: 1.0,
+            watcher: None,
+        }
+    }
+}
+
+impl CanvasSizing {
+    pub fn pixel_ratio(&self) -> f32 {
+        self.pixel_ratio
+    }
+
+    // 範囲外の値は丸め、NaN などは 1.0 にする
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        self.pixel_ratio = if ratio.is_finite() {
+            ratio.clamp(MIN_PIXEL_RATIO, MAX_PIXEL_RATIO)
+        } else {
+            1.0
+        };
+        self.pixel_ratio
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    // 範囲外の値は丸め、NaN などは 1.0 にする
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        self.render_scale = if scale.is_finite() {
+            scale.clamp(MIN_RENDER_SCALE, MAX_RENDER_SCALE)
+        } else {
+            1.0
+        };
+        self.render_scale
+    }
+
+    // CSSピクセル1つあたりの描画バッファの画素数（点の大きさや Canvas2D の変換に使う）
+    pub fn backing_ratio(&self) -> f32 {
+        self.pixel_ratio * self.render_scale
+    }
+
+    // (width, height) のCSSピクセルに対する描画バッファの大きさ
+    pub fn backing_size(&self, width: f32, height: f32) -> (u32, u32) {
+        let ratio = self.backing_ratio();
+        (
+            ((width * ratio).round() as u32).max(1),
+            ((height * ratio).round() as u32).max(1),
+        )
+    }
+
+    // canvas（HtmlCanvasElement か OffscreenCanvas）の描画バッファを (width, height) × backing_ratio() にする
+    // 大きさが同じなら何もしない（設定し直すと Canvas2D の状態や WebGL の中身が消える）
+    pub fn apply(&self, canvas: &JsValue, width: f32, height: f32) {
+        let (backing_width, backing_height) = self.backing_size(width, height);
+        if let Some(canvas) = canvas.dyn_ref::<HtmlCanvasElement>() {
+            if (canvas.width(), canvas.height()) == (backing_width, backing_height) {
+                return;
+            }
+            canvas.set_width(backing_width);
+            canvas.set_height(backing_height);
+            return;
+        }
+        if let Some(canvas) = canvas.dyn_ref::<OffscreenCanvas>() {
+            if (canvas.width(), canvas.height()) == (backing_width, backing_height) {
+                return;
+            }
+            canvas.set_width(backing_width);
+            canvas.set_height(backing_height);
+        }
+    }
+
+    // ページ上の canvas の表示サイズと devicePixelRatio の変化を ResizeObserver で監視する
+    pub fn observe(&mut self, canvas: &JsValue) -> Result<(), JsValue> {
+        let canvas = canvas
+            .dyn_ref::<HtmlCanvasElement>()
+            .ok_or_else(|| tr(Text::ResizeObserverUnavailable, &[]))?;
+        self.watcher = Some(ResizeWatcher::new(canvas)?);
+        Ok(())
+    }
+
+    pub fn unobserve(&mut self) {
+        self.watcher = None;
+    }
+
+    // 前回から表示サイズが変わっていれば、その大きさ（CSSピクセル）を返し pixel_ratio も合わせる
+    pub fn take_pending(&mut self) -> Option<(f32, f32)> {
+        let (width, height, ratio) = self.watcher.as_ref()?.pending.take()?;
+        self.set_pixel_ratio(ratio);
+        Some((width, height))
+    }
+}
+
+// ResizeObserver とそのコールバック（外すと監視をやめる）
+// コールバックは最後の大きさを覚えておくだけで、実際の resize() は次の render() で行う
+struct ResizeWatcher {
+    observer: ResizeObserver,
+    pending: Rc<Cell<Option<(f32, f32, f32)>>>,
+    _callback: Closure<dyn FnMut(js_sys::Array)>,
+}
+
+impl ResizeWatcher {
+    fn new(canvas: &HtmlCanvasElement) -> Result<ResizeWatcher, JsValue> {
+        let pending = Rc::new(Cell::new(None));
+        let sink = pending.clone();
+        let callback = Closure::<dyn FnMut(js_sys::Array)>::new(move |entries: js_sys::Array| {
+            let Ok(entry) = entries.get(0).dyn_into::<ResizeObserverEntry>() else {
+                return;
+            };
+            let rect = entry.content_rect();
+            let (width, height) = (rect.width() as f32, rect.height() as f32);
+            // 非表示（display: none など）の間は大きさ 0 になるので無視する
+            if width >= 1.0 && height >= 1.0 {
+                sink.set(Some((width, height, device_pixel_ratio())));
+            }
+        });
+        let observer = ResizeObserver::new(callback.as_ref().unchecked_ref())?;
+        observer.observe(canvas);
+        Ok(ResizeWatcher {
+            observer,
+            pending,
+            _callback: callback,
+        })
+    }
+}
+
+impl Drop for ResizeWatcher {
+    fn drop(&mut self) {
+        self.observer.disconnect();
+    }
+}
@@ -0,0 +1,183 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+use crate::i18n::{tr, Text};
+use crate::json;
+use crate::metrics::{FrameHistogram, Metrics};
+use crate::report;
+use crate::timeline::Timeline;
+
+// export_results() の書き出し形式
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResultFormat {
+    Json = 0,
+    // 見出しの行と値の行の2行（表計算ソフトで何回分も並べて比べる用）
+    Csv = 1,
+}
+
+impl ResultFormat {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResultFormat::Json => "json",
+            ResultFormat::Csv => "csv",
+        }
+    }
+
+    fn mime_type(self) -> &'static str {
+        match self {
+            ResultFormat::Json => "application/json",
+            ResultFormat::Csv => "text/csv",
+        }
+    }
+}
+
+// 結果と一緒に書き出す実行の情報
+pub(crate) struct RunInfo<'a> {
+    pub backend: &'a str,
+    pub particle_count: usize,
+    pub width: f32,
+    pub height: f32,
+    pub seed: Option<u64>,
+    // apply_tuning_profile() で当てた設定の組の名前（同じ組で測った結果同士を並べる用）
+    pub tuning_profile: Option<&'a str>,
+    // WASM からは navigator を読まずに JS から渡してもらう（Worker でも同じように書き出せるように）
+    pub user_agent: &'a str,
+}
+
+// 有効にしてビルドしたフィーチャー
+fn build_features() -> Vec<&'static str> {
+    [
+        ("deterministic", cfg!(feature = "deterministic")),
+        ("threads", cfg!(feature = "threads")),
+        ("simd", cfg!(feature = "simd")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+// 書き出す値（CSV の列の順番）
+fn fields(metrics: &Metrics, histogram: &FrameHistogram) -> [(&'static str, f64); 15] {
+    [
+        ("frames", metrics.frames as f64),
+        ("fps", metrics.fps),
+        ("update_ms", metrics.update_ms),
+        ("render_ms", metrics.render_ms),
+        ("upload_ms", metrics.upload_ms),
+        ("frame_mean_ms", histogram.mean_ms),
+        ("frame_p50_ms", histogram.p50_ms),
+        ("frame_p95_ms", histogram.p95_ms),
+        ("frame_p99_ms", histogram.p99_ms),
+        ("frame_longest_ms", histogram.longest_ms),
+        ("frames_over_16ms", histogram.over_16ms as f64),
+        ("frames_over_33ms", histogram.over_33ms as f64),
+        ("histogram_frames", histogram.frames as f64),
+        ("context_losses", metrics.context_losses as f64),
+        ("warmup_frames", metrics.warmup_frames as f64),
+    ]
+}
+
+pub(crate) fn export(
+    format: ResultFormat,
+    info: &RunInfo,
+    metrics: &Metrics,
+    histogram: &FrameHistogram,
+    timeline: &Timeline,
+) -> String {
+    let features = build_features();
+    match format {
+        ResultFormat::Json => {
+            let mut object = report::versioned()
+                .string("backend", info.backend)
+                .number("particle_count", info.particle_count as f64)
+                .number("width", info.width as f64)
+                .number("height", info.height as f64)
+                .optional_string("seed", info.seed.map(|seed| seed.to_string()).as_deref())
+                .optional_string("tuning_profile", info.tuning_profile)
+                .string("user_agent", info.user_agent)
+                .raw(
+                    "features",
+                    &json::array(features.iter().map(|feature| format!("\"{}\"", feature))),
+                );
+            for (key, value) in fields(metrics, histogram) {
+                object = object.number(key, value);
+            }
+            // 長いフレームと annotate() の印を同じ時刻で並べられるように、直近のフレーム時間も入れる
+            object
+                .raw("annotations", &timeline.annotations_json())
+                .raw("frame_timeline", &timeline.frames_json())
+                .finish()
+        }
+        ResultFormat::Csv => {
+            let mut header = vec![
+                "report_version",
+                "backend",
+                "particle_count",
+                "width",
+                "height",
+                "seed",
+                "tuning_profile",
+                "user_agent",
+                "features",
+                "metadata",
+                "annotations",
+            ];
+            let mut row = vec![
+                report::REPORT_VERSION.to_string(),
+                info.backend.to_string(),
+                info.particle_count.to_string(),
+                info.width.to_string(),
+                info.height.to_string(),
+                info.seed.map(|seed| seed.to_string()).unwrap_or_default(),
+                info.tuning_profile.unwrap_or_default().to_string(),
+                info.user_agent.to_string(),
+                features.join(" "),
+                report::metadata(),
+                timeline.annotations_summary(),
+            ];
+            for (key, value) in fields(metrics, histogram) {
+                header.push(key);
+                row.push(if value.is_finite() {
+                    value.to_string()
+                } else {
+                    String::new()
+                });
+            }
+            let header: Vec<String> = header.into_iter().map(csv_field).collect();
+            let row: Vec<String> = row.into_iter().map(csv_field).collect();
+            format!("{}\n{}\n", header.join(","), row.join(","))
+        }
+    }
+}
+
+// カンマ・引用符・改行を含む値だけ引用符で囲む
+fn csv_field(value: impl AsRef<str>) -> String {
+    let value = value.as_ref();
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// text を filename としてダウンロードさせる（ページの上でしか使えない）
+pub(crate) fn download(text: &str, format: ResultFormat, filename: &str) -> Result<(), JsValue> {
+    let unavailable = || JsValue::from(tr(Text::DownloadUnavailable, &[]));
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(unavailable)?;
+    let options = BlobPropertyBag::new();
+    options.set_type(format.mime_type());
+    let parts = js_sys::Array::of1(&JsValue::from_str(text));
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let anchor = document
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()
+        .map_err(|_| unavailable())?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    Url::revoke_object_url(&url)
+}
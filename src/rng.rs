@@ -0,0 +1,38 @@
+use std::cell::Cell;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// シーンやパーティクルの生成に使う乱数
+// 通常は毎回 OS の乱数で初期化し、with_seed() の中だけ固定シードから順に派生させる
+thread_local! {
+    // (シード, これまでに作った乱数生成器の数)
+    static FIXED: Cell<Option<(u64, u64)>> = const { Cell::new(None) };
+}
+
+pub(crate) fn rng() -> StdRng {
+    match FIXED.get() {
+        Some((seed, count)) => {
+            FIXED.set(Some((seed, count + 1)));
+            StdRng::seed_from_u64(seed.wrapping_add(count.wrapping_mul(0x9e37_79b9_7f4a_7c15)))
+        }
+        None => StdRng::from_rng(rand::thread_rng()).expect("thread_rng never fails"),
+    }
+}
+
+// f の中で作られる乱数生成器をすべて seed から決定的に作る
+pub(crate) fn with_seed<T>(seed: u64, f: impl FnOnce() -> T) -> T {
+    let previous = FIXED.replace(Some((seed, 0)));
+    let result = f();
+    FIXED.set(previous);
+    result
+}
+
+// 整数から作る決まった乱数（lowbias32）。パーティクルの番号や格子点ごとに、状態を持たずに値を決める
+pub(crate) fn hash(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7feb_352d);
+    x ^= x >> 15;
+    x = x.wrapping_mul(0x846c_a68b);
+    x ^ (x >> 16)
+}
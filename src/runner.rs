@@ -0,0 +1,2469 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::autoscale::AutoScaler;
+use crate::backend::{describe_error, Backend, BackendConfig, BackendKind};
+use crate::context::{CanvasElement, OffscreenCanvasSource};
+use crate::driver::{self, LoopDriver};
+use crate::energy::{self, EnergyLog, PressureState, PressureWatch};
+use crate::error::BenchError;
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::image_source::{self, ImageSource};
+use crate::json::{self, JsonObject};
+use crate::memory;
+use crate::render_mode::CompositeMode;
+use crate::report;
+use crate::rng;
+use crate::scene::{self, SceneKind};
+use crate::stats::{self, Aggregation};
+use crate::suite::{create_canvas, yield_to_browser, BACKENDS};
+use crate::timing;
+use crate::workload::Workload;
+
+// BenchmarkRunner::run() に渡す設定
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct RunnerConfig {
+    pub width: u32,
+    pub height: u32,
+    pub warmup_frames: u32,
+    pub measure_frames: u32,
+    // 計測するパーティクル数（少ない順に計測する）
+    pub particle_counts: Vec<u32>,
+    // 計測するバックエンドの名前（空ならすべて）
+    pub backends: Vec<String>,
+    pub seed: u64,
+    // auto_scale() で守るフレーム時間(ms)
+    pub target_frame_ms: f64,
+    // memory_growth() で1秒ごとに増やすパーティクル数と、増やし続ける秒数
+    pub growth_per_second: u32,
+    pub growth_seconds: u32,
+    // churn() で1秒（60フレーム）あたりに生まれて消えるパーティクル数（少ない順に計測する）
+    pub churn_rates: Vec<u32>,
+    // contention() で同じページに並べて同時に動かすバックエンド（同じ名前を繰り返してもよい）と負荷
+    pub contention_backends: Vec<String>,
+    pub contention_loads: Vec<f32>,
+    // frame_cap() で描画を間引く上限(fps)
+    pub target_fps: f64,
+    // throughput() で1つの組をできるだけ多く描き続ける時間(ms)
+    pub throughput_ms: f64,
+    // energy() で1つのバックエンドを描き続ける時間(ms)と、エネルギーの推定に使う電池の容量(Wh)
+    pub energy_ms: f64,
+    pub battery_capacity_wh: f64,
+}
+
+#[wasm_bindgen]
+impl RunnerConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> RunnerConfig {
+        RunnerConfig {
+            width: 800,
+            height: 600,
+            warmup_frames: 30,
+            measure_frames: 120,
+            particle_counts: vec![1_000, 10_000, 100_000],
+            backends: Vec::new(),
+            seed: 42,
+            target_frame_ms: 16.6,
+            growth_per_second: 1_000,
+            growth_seconds: 120,
+            churn_rates: vec![10_000, 30_000, 60_000],
+            contention_backends: vec!["webgl".to_string(), "canvas2d".to_string()],
+            contention_loads: vec![0.25, 0.5, 1.0],
+            target_fps: 60.0,
+            throughput_ms: 2_000.0,
+            energy_ms: 60_000.0,
+            battery_capacity_wh: 50.0,
+        }
+    }
+}
+
+impl Default for RunnerConfig {
+    fn default() -> RunnerConfig {
+        RunnerConfig::new()
+    }
+}
+
+// バックエンド × パーティクル数 1組の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct RunnerResult {
+    pub backend: String,
+    pub particle_count: u32,
+    // false なら計測していない（理由は error）
+    pub supported: bool,
+    pub error: Option<String>,
+    pub frames: u32,
+    // update() + render() の1フレームあたりの時間(ms)
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl RunnerResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("particle_count", self.particle_count as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("frames", self.frames as f64)
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .number("p95_ms", self.p95_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct RunnerReport {
+    // バックエンド（高機能な順）ごとに、パーティクル数の少ない順
+    pub results: Vec<RunnerResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl RunnerReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(RunnerResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// 点の大きさ1つ分の結果（fill_rate() の曲線の1点）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct FillRateResult {
+    pub backend: String,
+    pub particle_count: u32,
+    // 描いた点の一辺(px)。supported が false なら 0
+    pub point_size: f32,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    // 1秒あたりに塗ったピクセル数（百万単位、中央値から求める）
+    pub megapixels_per_second: f64,
+}
+
+impl FillRateResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("particle_count", self.particle_count as f64)
+            .number("point_size", self.point_size as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .number("megapixels_per_second", self.megapixels_per_second)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct FillRateReport {
+    // バックエンド（高機能な順）× パーティクル数ごとに、点の小さい順
+    pub results: Vec<FillRateResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl FillRateReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(FillRateResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// 画像の大きさ1つ分の結果（upload_bandwidth() の曲線の1点）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct UploadResult {
+    pub backend: String,
+    // 毎フレーム送った画像の大きさ(px)。supported が false なら 0
+    pub texture_width: u32,
+    pub texture_height: u32,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    // 1秒あたりに送ったバイト数（百万単位、RGBA の 4 バイト/px、中央値から求める）
+    pub megabytes_per_second: f64,
+}
+
+impl UploadResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("texture_width", self.texture_width as f64)
+            .number("texture_height", self.texture_height as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .number("megabytes_per_second", self.megabytes_per_second)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct UploadReport {
+    // バックエンド（高機能な順）ごとに、画像の小さい順
+    pub results: Vec<UploadResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl UploadReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(UploadResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// 読み戻す範囲の大きさ1つ分の結果（readback() の曲線の1点）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ReadbackResult {
+    pub backend: String,
+    // 毎フレーム読み戻した範囲の大きさ(px)。supported が false なら 0
+    pub rect_width: u32,
+    pub rect_height: u32,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    // 1秒あたりに読み戻したバイト数（百万単位、RGBA の 4 バイト/px、中央値から求める）
+    pub megabytes_per_second: f64,
+}
+
+impl ReadbackResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("rect_width", self.rect_width as f64)
+            .number("rect_height", self.rect_height as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .number("megabytes_per_second", self.megabytes_per_second)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ReadbackReport {
+    // バックエンド（高機能な順）ごとに、範囲の小さい順
+    pub results: Vec<ReadbackResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ReadbackReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ReadbackResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// 合成方法1つ分の結果（compositing() の表の1行）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct CompositeResult {
+    pub backend: String,
+    // globalCompositeOperation の名前
+    pub mode: String,
+    pub supported: bool,
+    pub error: Option<String>,
+    // true なら合成方法を使えず不透明で重ねたので、時間は比べられない
+    pub fallback: bool,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+}
+
+impl CompositeResult {
+    fn unsupported(kind: BackendKind, mode: CompositeMode, error: &JsValue) -> CompositeResult {
+        CompositeResult {
+            backend: kind.name().to_string(),
+            mode: mode.name().to_string(),
+            supported: false,
+            error: Some(describe_error(error)),
+            fallback: false,
+            mean_ms: 0.0,
+            median_ms: 0.0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .string("mode", &self.mode)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .boolean("fallback", self.fallback)
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct CompositeReport {
+    // バックエンド（高機能な順）ごとに、CompositeMode::ALL の順
+    pub results: Vec<CompositeResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl CompositeReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(CompositeResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// シーンと画像の渡し方の組1つ分の結果（image_sources() の表の1行）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ImageSourceResult {
+    pub backend: String,
+    pub scene: String,
+    pub source: String,
+    pub supported: bool,
+    pub error: Option<String>,
+    // アトラスから画像を作るまでの時間(ms)（Pixels は 0）
+    pub decode_ms: f64,
+    // 画像を渡し直した直後のフレームの時間の中央値(ms)。median_ms との差が転送の分
+    pub upload_frame_ms: f64,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+}
+
+impl ImageSourceResult {
+    fn unsupported(
+        kind: BackendKind,
+        scene: SceneKind,
+        source: ImageSource,
+        error: &JsValue,
+    ) -> ImageSourceResult {
+        ImageSourceResult {
+            backend: kind.name().to_string(),
+            scene: scene.name().to_string(),
+            source: source.name().to_string(),
+            supported: false,
+            error: Some(describe_error(error)),
+            decode_ms: 0.0,
+            upload_frame_ms: 0.0,
+            mean_ms: 0.0,
+            median_ms: 0.0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .string("scene", &self.scene)
+            .string("source", &self.source)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("decode_ms", self.decode_ms)
+            .number("upload_frame_ms", self.upload_frame_ms)
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ImageSourceReport {
+    // バックエンド（高機能な順）ごとに、シーンごとに、ImageSource::ALL の順
+    pub results: Vec<ImageSourceResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ImageSourceReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ImageSourceResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// image_sources() で計測するアトラスからスプライトを描くシーン
+const SPRITE_SCENES: [SceneKind; 3] = [
+    SceneKind::Tilemap,
+    SceneKind::Skeleton,
+    SceneKind::Transforms,
+];
+// 画像を渡し直して転送込みのフレームを計測する回数
+const UPLOAD_SAMPLES: u32 = 10;
+
+// パーティクル数を変えながら各バックエンドを計測する（requestAnimationFrame を使わないのでCIでも動く）
+// 1バックエンド分の auto_scale() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct AutoScaleResult {
+    pub backend: String,
+    pub supported: bool,
+    pub error: Option<String>,
+    // 目標のフレーム時間を守れた一番多いパーティクル数
+    pub sustained_count: u32,
+    // false なら上限の秒数までに収束しなかった（sustained_count はそれまでの最大）
+    pub converged: bool,
+    pub seconds: u32,
+    // 最後の1秒のフレーム時間の中央値(ms)
+    pub median_ms: f64,
+}
+
+impl AutoScaleResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("sustained_count", self.sustained_count as f64)
+            .boolean("converged", self.converged)
+            .number("seconds", self.seconds as f64)
+            .number("median_ms", self.median_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct AutoScaleReport {
+    // バックエンド（高機能な順）ごと
+    pub results: Vec<AutoScaleResult>,
+    pub target_frame_ms: f64,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl AutoScaleReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("target_frame_ms", self.target_frame_ms)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(AutoScaleResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// memory_growth() の1秒分の記録
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct GrowthSample {
+    // 始めてからの秒数（1から）
+    pub second: u32,
+    // その1秒の終わりのパーティクル数
+    pub particle_count: u32,
+    pub frames: u32,
+    pub median_ms: f64,
+    pub max_ms: f64,
+    // その1秒の終わりのWASMのリニアメモリの大きさ（64KiB のページ数）
+    pub wasm_pages: u32,
+    // WASMのメモリが増えた（memory.grow が起きた）フレームの数と、その中で一番長かったフレーム(ms)
+    pub grow_frames: u32,
+    pub grow_max_ms: f64,
+}
+
+impl GrowthSample {
+    fn to_json(self) -> String {
+        JsonObject::new()
+            .number("second", self.second as f64)
+            .number("particle_count", self.particle_count as f64)
+            .number("frames", self.frames as f64)
+            .number("median_ms", self.median_ms)
+            .number("max_ms", self.max_ms)
+            .number("wasm_pages", self.wasm_pages as f64)
+            .number("grow_frames", self.grow_frames as f64)
+            .number("grow_max_ms", self.grow_max_ms)
+            .finish()
+    }
+}
+
+// 1バックエンド分の memory_growth() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct GrowthResult {
+    pub backend: String,
+    pub supported: bool,
+    // supported が true でも、途中で増やせなくなった（メモリ予算を超えたなど）ときはその理由
+    pub error: Option<String>,
+    pub samples: Vec<GrowthSample>,
+}
+
+impl GrowthResult {
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .raw(
+                "samples",
+                &json::array(self.samples.iter().map(|sample| sample.to_json())),
+            )
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct GrowthReport {
+    // バックエンド（高機能な順）ごと
+    pub results: Vec<GrowthResult>,
+    pub growth_per_second: u32,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl GrowthReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("growth_per_second", self.growth_per_second as f64)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(GrowthResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// バックエンド × 入れ替わる数 1組の churn() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ChurnResult {
+    pub backend: String,
+    pub churn_per_second: u32,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub frames: u32,
+    pub mean_ms: f64,
+    pub median_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    // 1フレームに出す予定の数と、実際に出した数（尽きた場所が足りないと少なくなる）
+    pub target_per_frame: f64,
+    pub spawned_per_frame: f64,
+    // 計測中に増えたWASMのメモリのページ数（使い回しが効いていれば 0）
+    pub wasm_pages_grown: u32,
+}
+
+impl ChurnResult {
+    fn unsupported(kind: BackendKind, churn_per_second: u32, error: &JsValue) -> ChurnResult {
+        ChurnResult {
+            backend: kind.name().to_string(),
+            churn_per_second,
+            supported: false,
+            error: Some(describe_error(error)),
+            frames: 0,
+            mean_ms: 0.0,
+            median_ms: 0.0,
+            p95_ms: 0.0,
+            max_ms: 0.0,
+            target_per_frame: 0.0,
+            spawned_per_frame: 0.0,
+            wasm_pages_grown: 0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("churn_per_second", self.churn_per_second as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("frames", self.frames as f64)
+            .number("mean_ms", self.mean_ms)
+            .number("median_ms", self.median_ms)
+            .number("p95_ms", self.p95_ms)
+            .number("max_ms", self.max_ms)
+            .number("target_per_frame", self.target_per_frame)
+            .number("spawned_per_frame", self.spawned_per_frame)
+            .number("wasm_pages_grown", self.wasm_pages_grown as f64)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ChurnReport {
+    // バックエンド（高機能な順）ごとに、入れ替わる数の少ない順
+    pub results: Vec<ChurnResult>,
+    pub lifetime_frames: f32,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ChurnReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("lifetime_frames", self.lifetime_frames as f64)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ChurnResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// contention() の1インスタンス × 負荷 1組の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ContentionResult {
+    pub backend: String,
+    // contention_backends の中の位置（同じバックエンドを並べたときの区別）
+    pub instance: u32,
+    pub load: f32,
+    pub supported: bool,
+    pub error: Option<String>,
+    // 1つだけ動かしたときと、全部を同時に動かしたときの update() + render() の時間(ms)
+    pub solo_median_ms: f64,
+    pub shared_median_ms: f64,
+    // shared_median_ms / solo_median_ms（1 より大きいほど他のインスタンスに邪魔されている）
+    pub slowdown: f64,
+    // 同時に動かしたときの、全インスタンス分を合わせた1フレームの時間(ms)
+    pub combined_median_ms: f64,
+}
+
+impl ContentionResult {
+    fn unsupported(
+        kind: BackendKind,
+        instance: u32,
+        load: f32,
+        error: &JsValue,
+    ) -> ContentionResult {
+        ContentionResult {
+            backend: kind.name().to_string(),
+            instance,
+            load,
+            supported: false,
+            error: Some(describe_error(error)),
+            solo_median_ms: 0.0,
+            shared_median_ms: 0.0,
+            slowdown: 0.0,
+            combined_median_ms: 0.0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("instance", self.instance as f64)
+            .number("load", self.load as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("solo_median_ms", self.solo_median_ms)
+            .number("shared_median_ms", self.shared_median_ms)
+            .number("slowdown", self.slowdown)
+            .number("combined_median_ms", self.combined_median_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ContentionReport {
+    // 負荷の小さい順に、インスタンスの並び順
+    pub results: Vec<ContentionResult>,
+    pub particle_count: u32,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ContentionReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("particle_count", self.particle_count as f64)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ContentionResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// バックエンド × ループの回し方 1組の loop_drivers() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct LoopDriverResult {
+    pub backend: String,
+    pub driver: String,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub frames: u32,
+    // 実際に回った1秒あたりの回数
+    pub cadence_hz: f64,
+    // 前の回から次の回までの間隔(ms)
+    pub interval_median_ms: f64,
+    pub interval_p95_ms: f64,
+    pub interval_max_ms: f64,
+    // 間隔の標準偏差(ms)（揺れの大きさ）
+    pub jitter_ms: f64,
+    // 1回あたりの update() + render() の時間(ms)
+    pub work_median_ms: f64,
+}
+
+impl LoopDriverResult {
+    fn unsupported(kind: BackendKind, driver: LoopDriver, error: &JsValue) -> LoopDriverResult {
+        LoopDriverResult {
+            backend: kind.name().to_string(),
+            driver: driver.name().to_string(),
+            supported: false,
+            error: Some(describe_error(error)),
+            frames: 0,
+            cadence_hz: 0.0,
+            interval_median_ms: 0.0,
+            interval_p95_ms: 0.0,
+            interval_max_ms: 0.0,
+            jitter_ms: 0.0,
+            work_median_ms: 0.0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .string("driver", &self.driver)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("frames", self.frames as f64)
+            .number("cadence_hz", self.cadence_hz)
+            .number("interval_median_ms", self.interval_median_ms)
+            .number("interval_p95_ms", self.interval_p95_ms)
+            .number("interval_max_ms", self.interval_max_ms)
+            .number("jitter_ms", self.jitter_ms)
+            .number("work_median_ms", self.work_median_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct LoopDriverReport {
+    // バックエンド（高機能な順）ごとに、LoopDriver の番号順
+    pub results: Vec<LoopDriverResult>,
+    pub particle_count: u32,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl LoopDriverReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("particle_count", self.particle_count as f64)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(LoopDriverResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// バックエンド × 上限 1組の frame_cap() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct FrameCapResult {
+    pub backend: String,
+    // 0 なら上限なし
+    pub target_fps: f64,
+    pub supported: bool,
+    pub error: Option<String>,
+    // 推定した表示のリフレッシュレート(Hz)
+    pub refresh_hz: f64,
+    pub ticks: u32,
+    // 1秒あたりの tick() の回数（物理の回数）と描いた回数
+    pub tick_hz: f64,
+    pub rendered_fps: f64,
+    pub skipped: u32,
+    // 1回の tick() の時間(ms)（描かなかった回も含む）
+    pub tick_median_ms: f64,
+    pub tick_p95_ms: f64,
+}
+
+impl FrameCapResult {
+    fn unsupported(kind: BackendKind, target_fps: f64, error: &JsValue) -> FrameCapResult {
+        FrameCapResult {
+            backend: kind.name().to_string(),
+            target_fps,
+            supported: false,
+            error: Some(describe_error(error)),
+            refresh_hz: 0.0,
+            ticks: 0,
+            tick_hz: 0.0,
+            rendered_fps: 0.0,
+            skipped: 0,
+            tick_median_ms: 0.0,
+            tick_p95_ms: 0.0,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("target_fps", self.target_fps)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("refresh_hz", self.refresh_hz)
+            .number("ticks", self.ticks as f64)
+            .number("tick_hz", self.tick_hz)
+            .number("rendered_fps", self.rendered_fps)
+            .number("skipped", self.skipped as f64)
+            .number("tick_median_ms", self.tick_median_ms)
+            .number("tick_p95_ms", self.tick_p95_ms)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct FrameCapReport {
+    // バックエンド（高機能な順）ごとに、上限なし・上限ありの順
+    pub results: Vec<FrameCapResult>,
+    pub particle_count: u32,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl FrameCapReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("particle_count", self.particle_count as f64)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(FrameCapResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// バックエンド × パーティクル数 1組の throughput() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ThroughputResult {
+    pub backend: String,
+    pub particle_count: u32,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub frames: u32,
+    // 最後に描画が終わるのを待った時間も含めた、実際にかかった時間(ms)
+    pub elapsed_ms: f64,
+    // 1秒あたりに描けたフレーム数と、1フレームあたりの時間(ms)
+    pub fps: f64,
+    pub frame_ms: f64,
+    // 最後に描画が終わるのを待てたか（false なら GPU に送り終えるまでの数で、GPU の描画は終わっていないことがある）
+    pub gpu_synced: bool,
+}
+
+impl ThroughputResult {
+    fn unsupported(kind: BackendKind, particle_count: u32, error: &JsValue) -> ThroughputResult {
+        ThroughputResult {
+            backend: kind.name().to_string(),
+            particle_count,
+            supported: false,
+            error: Some(describe_error(error)),
+            frames: 0,
+            elapsed_ms: 0.0,
+            fps: 0.0,
+            frame_ms: 0.0,
+            gpu_synced: false,
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .number("particle_count", self.particle_count as f64)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("frames", self.frames as f64)
+            .number("elapsed_ms", self.elapsed_ms)
+            .number("fps", self.fps)
+            .number("frame_ms", self.frame_ms)
+            .boolean("gpu_synced", self.gpu_synced)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ThroughputReport {
+    // バックエンド（高機能な順）ごとに、パーティクル数の少ない順
+    pub results: Vec<ThroughputResult>,
+    pub window_ms: f64,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ThroughputReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("window_ms", self.window_ms)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ThroughputResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// バックエンド1つ分の energy() の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct EnergyResult {
+    pub backend: String,
+    pub supported: bool,
+    pub error: Option<String>,
+    pub frames: u32,
+    pub elapsed_ms: f64,
+    pub fps: f64,
+    // CPU の逼迫度ごとの時間(ms)（Compute Pressure API が無ければすべて unknown_ms）
+    pub nominal_ms: f64,
+    pub fair_ms: f64,
+    pub serious_ms: f64,
+    pub critical_ms: f64,
+    pub unknown_ms: f64,
+    // 計測中で一番高かった逼迫度の名前
+    pub worst_pressure: String,
+    // 電池の残量（Battery Status API が無ければ None）と、途中で充電されていたか
+    pub battery_start: Option<f64>,
+    pub battery_end: Option<f64>,
+    pub charging: Option<bool>,
+    // 推定した消費エネルギー(J)・平均の消費電力(W)・1ジュールあたりのフレーム数と推定の方法
+    // 充電中や電池の値が取れないときは None（画面や他のアプリの分も含む端末全体の値）
+    pub joules: Option<f64>,
+    pub watts: Option<f64>,
+    pub frames_per_joule: Option<f64>,
+    pub estimate_method: Option<String>,
+}
+
+impl EnergyResult {
+    fn unsupported(kind: BackendKind, error: &JsValue) -> EnergyResult {
+        EnergyResult::new(
+            kind,
+            0,
+            0.0,
+            &EnergyLog::default(),
+            0.0,
+            Some(describe_error(error)),
+        )
+    }
+
+    fn new(
+        kind: BackendKind,
+        frames: u32,
+        elapsed_ms: f64,
+        log: &EnergyLog,
+        capacity_wh: f64,
+        error: Option<String>,
+    ) -> EnergyResult {
+        let estimate = log.estimate(elapsed_ms, capacity_wh);
+        let levels = log.battery_levels();
+        EnergyResult {
+            backend: kind.name().to_string(),
+            supported: error.is_none(),
+            error,
+            frames,
+            elapsed_ms,
+            fps: if elapsed_ms > 0.0 {
+                frames as f64 * 1_000.0 / elapsed_ms
+            } else {
+                0.0
+            },
+            nominal_ms: log.pressure_ms(PressureState::Nominal),
+            fair_ms: log.pressure_ms(PressureState::Fair),
+            serious_ms: log.pressure_ms(PressureState::Serious),
+            critical_ms: log.pressure_ms(PressureState::Critical),
+            unknown_ms: log.pressure_ms(PressureState::Unknown),
+            worst_pressure: log.worst().name().to_string(),
+            battery_start: levels.map(|(start, _)| start),
+            battery_end: levels.map(|(_, end)| end),
+            charging: log.charging(),
+            joules: estimate.map(|estimate| estimate.joules),
+            watts: estimate.map(|estimate| estimate.watts),
+            frames_per_joule: estimate.map(|estimate| frames as f64 / estimate.joules),
+            estimate_method: estimate.map(|estimate| estimate.method.to_string()),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let number = |value: Option<f64>| value.unwrap_or(f64::NAN);
+        let charging = match self.charging {
+            Some(true) => "true",
+            Some(false) => "false",
+            None => "null",
+        };
+        JsonObject::new()
+            .string("backend", &self.backend)
+            .boolean("supported", self.supported)
+            .optional_string("error", self.error.as_deref())
+            .number("frames", self.frames as f64)
+            .number("elapsed_ms", self.elapsed_ms)
+            .number("fps", self.fps)
+            .number("nominal_ms", self.nominal_ms)
+            .number("fair_ms", self.fair_ms)
+            .number("serious_ms", self.serious_ms)
+            .number("critical_ms", self.critical_ms)
+            .number("unknown_ms", self.unknown_ms)
+            .string("worst_pressure", &self.worst_pressure)
+            .number("battery_start", number(self.battery_start))
+            .number("battery_end", number(self.battery_end))
+            .raw("charging", charging)
+            .number("joules", number(self.joules))
+            .number("watts", number(self.watts))
+            .number("frames_per_joule", number(self.frames_per_joule))
+            .optional_string("estimate_method", self.estimate_method.as_deref())
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct EnergyReport {
+    // バックエンド（高機能な順）ごと
+    pub results: Vec<EnergyResult>,
+    pub particle_count: u32,
+    pub battery_capacity_wh: f64,
+    pub seed: u64,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl EnergyReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("particle_count", self.particle_count as f64)
+            .number("battery_capacity_wh", self.battery_capacity_wh)
+            .number("seed", self.seed as f64)
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(EnergyResult::to_json)),
+            )
+            .finish()
+    }
+}
+
+// churn() のパーティクルの寿命（フレーム）と、1秒とみなすフレーム数
+const CHURN_LIFETIME_FRAMES: f32 = 60.0;
+const CHURN_FRAMES_PER_SECOND: f64 = 60.0;
+
+// WASMのメモリのページの大きさ（バイト）
+const WASM_PAGE_BYTES: u64 = 64 * 1024;
+
+// 1バックエンドで数を探す秒数の上限
+const MAX_AUTO_SCALE_SECONDS: u32 = 30;
+
+#[wasm_bindgen]
+pub struct BenchmarkRunner;
+
+#[wasm_bindgen]
+impl BenchmarkRunner {
+    // 計測が終わると RunnerReport で解決する Promise を返す
+    pub fn run(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { run_all(config, None).await.map(JsValue::from) })
+    }
+
+    // 記録した操作を各バックエンドで再生して計測する
+    // パーティクル数は記録したときの数、計測するフレーム数は記録の長さになる
+    // （config の particle_counts と measure_frames は使わない）
+    pub fn replay(config: &RunnerConfig, workload: &Workload) -> js_sys::Promise {
+        let config = config.clone();
+        let workload = workload.clone();
+        future_to_promise(async move { run_all(config, Some(workload)).await.map(JsValue::from) })
+    }
+
+    // 点の数を固定して、点の大きさを 1px からバックエンドの上限まで2倍ずつ変えて計測する
+    // 頂点と CPU のコストは変わらないので、フレーム時間の増え方がラスタライズのコストになる
+    // 結果は FillRateReport で解決する（点のシーンを描けないバックエンドは supported が false）
+    pub fn fill_rate(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { fill_rate_all(config).await.map(JsValue::from) })
+    }
+
+    // キャンバスの 1/8 から同じ大きさまでの画像を毎フレーム送り直して計測する（転送帯域の曲線）
+    // 結果は UploadReport で解決する（config の particle_counts は使わない）
+    pub fn upload_bandwidth(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { upload_all(config).await.map(JsValue::from) })
+    }
+
+    // 描いた直後に 1px からキャンバス全体までの範囲を読み戻して計測する（読み戻しのコストの曲線）
+    // 結果は ReadbackReport で解決する（config の particle_counts は使わない）
+    pub fn readback(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { readback_all(config).await.map(JsValue::from) })
+    }
+
+    // particle_counts の最小から始めて、growth_per_second 個/秒でパーティクルを growth_seconds 秒間増やし続ける
+    // 作成時の領域を超えて伸ばしながら確保するので、一定の数の計測では出ない memory.grow や
+    // バッファの取り直しによる停止が、1秒ごとのフレーム時間とWASMのメモリのページ数に現れる
+    // 結果は GrowthReport で解決する（webgpu は作成時の数を超えて増やせないので supported が false）
+    pub fn memory_growth(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { memory_growth_all(config).await.map(JsValue::from) })
+    }
+
+    // churn_rates 個/秒のパーティクルを生んでは消し続けて計測する（入れ替わりのコストの計測用）
+    // エミッターは尽きたパーティクルの場所を使い回す（プール）ので、数が一定のまま確保も詰め直しも起きない
+    // 寿命は CHURN_LIFETIME_FRAMES なので、生きている数はおおよそ1秒分（= 作成時の数）になる
+    // 結果は ChurnReport で解決する（config の particle_counts は使わない）
+    pub fn churn(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { churn_all(config).await.map(JsValue::from) })
+    }
+
+    // contention_backends のインスタンスを同じページに並べ、contention_loads の負荷ごとに
+    // 1つずつ動かしたときと、毎フレーム全部を順に動かしたときのフレーム時間を比べる
+    // いくつもの可視化が同時に動くダッシュボードのようなページで、互いにどれだけ遅くし合うかの計測用
+    // パーティクル数は particle_counts の最大で作り、負荷で減らす
+    // 結果は ContentionReport で解決する（1つでも作れなければ、その負荷の結果はすべて supported が false）
+    pub fn contention(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { contention_all(config).await.map(JsValue::from) })
+    }
+
+    // 各バックエンドで、update() と render() を LoopDriver のそれぞれで回して、
+    // 実際に回った間隔とその揺れを比べる（他の計測と違い、1フレームごとにブラウザに制御を返す）
+    // パーティクル数は particle_counts の最大
+    // 結果は LoopDriverReport で解決する（requestIdleCallback の無いブラウザでは supported が false）
+    pub fn loop_drivers(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { loop_drivers_all(config).await.map(JsValue::from) })
+    }
+
+    // 各バックエンドを requestAnimationFrame で tick() し、上限なしと target_fps で描画を間引いたときを比べる
+    // 120Hz や 144Hz の表示では上限なしだと描く回数が増えるので、上限ありの値が 60Hz の端末と比べられる数になる
+    // パーティクル数は particle_counts の最大
+    // 結果は FrameCapReport で解決する
+    pub fn frame_cap(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { frame_cap_all(config).await.map(JsValue::from) })
+    }
+
+    // ページに出さない OffscreenCanvas に、throughput_ms の間 update() と render() を休みなく繰り返し、
+    // 表示のリフレッシュレートに縛られない1秒あたりのフレーム数を比べる（垂直同期を切ったときに相当）
+    // 最後に描画が終わるまで待った時間も含める（WebGPU は待てないので gpu_synced が false）
+    // 結果は ThroughputReport で解決する
+    pub fn throughput(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { throughput_all(config).await.map(JsValue::from) })
+    }
+
+    // 各バックエンドを requestAnimationFrame で energy_ms の間描き続け、CPU の逼迫度（Compute Pressure API）の
+    // 移り変わりと電池の減り（Battery Status API）を記録して、1ジュールあたりのフレーム数を推定する
+    // モバイルでは FPS が同じでも電池の持ちと熱による性能の低下が違うので、その比較用
+    // 電池で動かし、画面の明るさや他のアプリを揃えて比べること（充電中は推定しない）
+    // パーティクル数は particle_counts の最大
+    // 結果は EnergyReport で解決する（API の無いブラウザでは逼迫度は unknown、推定は None）
+    pub fn energy(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { energy_all(config).await.map(JsValue::from) })
+    }
+
+    // スプライトを描くシーンで、アトラスをピクセル列・ImageBitmap・HTMLImageElement で渡して計測する
+    // 画像を作る時間、渡し直した直後（転送込み）のフレーム時間、その後のフレーム時間を比べる
+    // 結果は ImageSourceReport で解決する（config の particle_counts は使わない）
+    pub fn image_sources(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { image_sources_all(config).await.map(JsValue::from) })
+    }
+
+    // 同じ半透明の矩形の重なりを合成方法だけ変えて描き、合成方法ごとのフレーム時間を計測する
+    // 結果は CompositeReport で解決する（config の particle_counts は使わない）
+    pub fn compositing(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { compositing_all(config).await.map(JsValue::from) })
+    }
+
+    // 各バックエンドで target_frame_ms を守れる一番多いパーティクル数を AutoScaler で探す
+    // particle_counts の最小から探し始め、最大を上限にする（作るときに最大の数で確保する）
+    // 結果は AutoScaleReport で解決する（1バックエンドあたり最長30秒）
+    pub fn auto_scale(config: &RunnerConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { auto_scale_all(config).await.map(JsValue::from) })
+    }
+}
+
+async fn run_all(
+    config: RunnerConfig,
+    workload: Option<Workload>,
+) -> Result<RunnerReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let mut counts = match &workload {
+        Some(workload) => vec![workload.get_particle_count() as u32],
+        None => config.particle_counts.clone(),
+    };
+    counts.sort_unstable();
+    counts.dedup();
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for &count in &counts {
+            let result = match run_case(kind, count, &config, workload.as_ref()).await {
+                Ok(samples) => RunnerResult {
+                    backend: kind.name().to_string(),
+                    particle_count: count,
+                    supported: true,
+                    error: None,
+                    frames: samples.len() as u32,
+                    mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+                    median_ms: stats::summarize(&samples, Aggregation::Median).center,
+                    p95_ms: stats::percentile(&samples, 0.95),
+                },
+                Err(error) => RunnerResult {
+                    backend: kind.name().to_string(),
+                    particle_count: count,
+                    supported: false,
+                    error: Some(describe_error(&error)),
+                    frames: 0,
+                    mean_ms: 0.0,
+                    median_ms: 0.0,
+                    p95_ms: 0.0,
+                },
+            };
+            results.push(result);
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(RunnerReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn fill_rate_all(config: RunnerConfig) -> Result<FillRateReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let mut counts = config.particle_counts.clone();
+    counts.sort_unstable();
+    counts.dedup();
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for &count in &counts {
+            match fill_rate_case(kind, count, &config).await {
+                Ok(curve) => {
+                    for (point_size, samples) in curve {
+                        let median_ms = stats::summarize(&samples, Aggregation::Median).center;
+                        let pixels = count as f64 * (point_size as f64).powi(2);
+                        results.push(FillRateResult {
+                            backend: kind.name().to_string(),
+                            particle_count: count,
+                            point_size,
+                            supported: true,
+                            error: None,
+                            mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+                            median_ms,
+                            megapixels_per_second: if median_ms > 0.0 {
+                                pixels / median_ms / 1_000.0
+                            } else {
+                                0.0
+                            },
+                        });
+                    }
+                }
+                Err(error) => results.push(FillRateResult {
+                    backend: kind.name().to_string(),
+                    particle_count: count,
+                    point_size: 0.0,
+                    supported: false,
+                    error: Some(describe_error(&error)),
+                    mean_ms: 0.0,
+                    median_ms: 0.0,
+                    megapixels_per_second: 0.0,
+                }),
+            }
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(FillRateReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn readback_all(config: RunnerConfig) -> Result<ReadbackReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let curve = scale_case(kind, &config, &scene::READBACK_SCALES, |backend, scale| {
+            backend.switch_to_readback(scale)
+        });
+        match curve.await {
+            Ok(curve) => {
+                for (scale, samples) in curve {
+                    let median_ms = stats::summarize(&samples, Aggregation::Median).center;
+                    let (rect_width, rect_height) =
+                        scene::readback_size(config.width as f32, config.height as f32, scale);
+                    let bytes = rect_width as f64 * rect_height as f64 * 4.0;
+                    results.push(ReadbackResult {
+                        backend: kind.name().to_string(),
+                        rect_width,
+                        rect_height,
+                        supported: true,
+                        error: None,
+                        mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+                        median_ms,
+                        megabytes_per_second: if median_ms > 0.0 {
+                            bytes / median_ms / 1_000.0
+                        } else {
+                            0.0
+                        },
+                    });
+                }
+            }
+            Err(error) => results.push(ReadbackResult {
+                backend: kind.name().to_string(),
+                rect_width: 0,
+                rect_height: 0,
+                supported: false,
+                error: Some(describe_error(&error)),
+                mean_ms: 0.0,
+                median_ms: 0.0,
+                megabytes_per_second: 0.0,
+            }),
+        }
+        yield_to_browser().await;
+    }
+
+    Ok(ReadbackReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn upload_all(config: RunnerConfig) -> Result<UploadReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let curve = scale_case(kind, &config, &scene::UPLOAD_SCALES, |backend, scale| {
+            backend.switch_to_texture_upload(scale)
+        });
+        match curve.await {
+            Ok(curve) => {
+                for (scale, samples) in curve {
+                    let median_ms = stats::summarize(&samples, Aggregation::Median).center;
+                    // シーンと同じ丸め方
+                    let texture_width = ((config.width as f32 * scale).round() as u32).max(1);
+                    let texture_height = ((config.height as f32 * scale).round() as u32).max(1);
+                    let bytes = texture_width as f64 * texture_height as f64 * 4.0;
+                    results.push(UploadResult {
+                        backend: kind.name().to_string(),
+                        texture_width,
+                        texture_height,
+                        supported: true,
+                        error: None,
+                        mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+                        median_ms,
+                        megabytes_per_second: if median_ms > 0.0 {
+                            bytes / median_ms / 1_000.0
+                        } else {
+                            0.0
+                        },
+                    });
+                }
+            }
+            Err(error) => results.push(UploadResult {
+                backend: kind.name().to_string(),
+                texture_width: 0,
+                texture_height: 0,
+                supported: false,
+                error: Some(describe_error(&error)),
+                mean_ms: 0.0,
+                median_ms: 0.0,
+                megabytes_per_second: 0.0,
+            }),
+        }
+        yield_to_browser().await;
+    }
+
+    Ok(UploadReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn compositing_all(config: RunnerConfig) -> Result<CompositeReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        match compositing_case(kind, &config).await {
+            Ok(cases) => {
+                for (mode, case) in cases {
+                    results.push(match case {
+                        Ok((samples, fallback)) => CompositeResult {
+                            backend: kind.name().to_string(),
+                            mode: mode.name().to_string(),
+                            supported: true,
+                            error: None,
+                            fallback,
+                            mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+                            median_ms: stats::summarize(&samples, Aggregation::Median).center,
+                        },
+                        Err(error) => CompositeResult::unsupported(kind, mode, &error),
+                    });
+                }
+            }
+            Err(error) => results.extend(
+                CompositeMode::ALL
+                    .iter()
+                    .map(|&mode| CompositeResult::unsupported(kind, mode, &error)),
+            ),
+        }
+        yield_to_browser().await;
+    }
+
+    Ok(CompositeReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn image_sources_all(config: RunnerConfig) -> Result<ImageSourceReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let canvas = create_canvas(config.width, config.height)?;
+        // パーティクルは描かないので最小限にする
+        let backend_config = BackendConfig::new(1);
+        let mut backend =
+            match Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await {
+                Ok(backend) => backend,
+                Err(error) => {
+                    for scene in SPRITE_SCENES {
+                        results.extend(ImageSource::ALL.iter().map(|&source| {
+                            ImageSourceResult::unsupported(kind, scene, source, &error)
+                        }));
+                    }
+                    continue;
+                }
+            };
+        backend.strict_benchmark(true);
+
+        for scene in SPRITE_SCENES {
+            for source in ImageSource::ALL {
+                let result = match image_source_case(&mut backend, scene, source, &config).await {
+                    Ok(result) => result,
+                    Err(error) => ImageSourceResult::unsupported(kind, scene, source, &error),
+                };
+                results.push(result);
+                yield_to_browser().await;
+            }
+        }
+    }
+
+    Ok(ImageSourceReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn memory_growth_all(config: RunnerConfig) -> Result<GrowthReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let result = match memory_growth_case(kind, &config).await {
+            Ok((samples, error)) => GrowthResult {
+                backend: kind.name().to_string(),
+                supported: true,
+                error: error.as_ref().map(describe_error),
+                samples,
+            },
+            Err(error) => GrowthResult {
+                backend: kind.name().to_string(),
+                supported: false,
+                error: Some(describe_error(&error)),
+                samples: Vec::new(),
+            },
+        };
+        results.push(result);
+        yield_to_browser().await;
+    }
+
+    Ok(GrowthReport {
+        results,
+        growth_per_second: config.growth_per_second,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn churn_all(config: RunnerConfig) -> Result<ChurnReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let mut rates = config.churn_rates.clone();
+    rates.sort_unstable();
+    rates.dedup();
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for &rate in &rates {
+            let result = churn_case(kind, rate, &config)
+                .await
+                .unwrap_or_else(|error| ChurnResult::unsupported(kind, rate, &error));
+            results.push(result);
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(ChurnReport {
+        results,
+        lifetime_frames: CHURN_LIFETIME_FRAMES,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn contention_all(config: RunnerConfig) -> Result<ContentionReport, JsValue> {
+    let start = timing::now_ms();
+    let kinds = config
+        .contention_backends
+        .iter()
+        .map(|name| parse_backend(name))
+        .collect::<Result<Vec<_>, _>>()?;
+    if kinds.len() < 2 {
+        return Err(tr(Text::RunnerTooFewInstances, &[&kinds.len()]).into());
+    }
+    let mut loads = config.contention_loads.clone();
+    loads.sort_by(f32::total_cmp);
+    loads.dedup();
+    let count = config.particle_counts.iter().copied().max().unwrap_or(1);
+
+    let mut results = Vec::new();
+    for &load in &loads {
+        match contention_case(&kinds, load, count as usize, &config).await {
+            Ok(case) => results.extend(case),
+            Err(error) => {
+                results.extend(
+                    kinds.iter().enumerate().map(|(i, &kind)| {
+                        ContentionResult::unsupported(kind, i as u32, load, &error)
+                    }),
+                );
+            }
+        }
+        yield_to_browser().await;
+    }
+
+    Ok(ContentionReport {
+        results,
+        particle_count: count,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn loop_drivers_all(config: RunnerConfig) -> Result<LoopDriverReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let count = config.particle_counts.iter().copied().max().unwrap_or(1);
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for driver in LoopDriver::ALL {
+            let result = loop_driver_case(kind, driver, count as usize, &config)
+                .await
+                .unwrap_or_else(|error| LoopDriverResult::unsupported(kind, driver, &error));
+            results.push(result);
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(LoopDriverReport {
+        results,
+        particle_count: count,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn frame_cap_all(config: RunnerConfig) -> Result<FrameCapReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let count = config.particle_counts.iter().copied().max().unwrap_or(1);
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for target_fps in [0.0, config.target_fps] {
+            let result = frame_cap_case(kind, target_fps, count as usize, &config)
+                .await
+                .unwrap_or_else(|error| FrameCapResult::unsupported(kind, target_fps, &error));
+            results.push(result);
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(FrameCapReport {
+        results,
+        particle_count: count,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn throughput_all(config: RunnerConfig) -> Result<ThroughputReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let mut counts = config.particle_counts.clone();
+    counts.sort_unstable();
+    counts.dedup();
+
+    let mut results = Vec::new();
+    for kind in backends {
+        for &count in &counts {
+            let result = throughput_case(kind, count, &config)
+                .await
+                .unwrap_or_else(|error| ThroughputResult::unsupported(kind, count, &error));
+            results.push(result);
+            yield_to_browser().await;
+        }
+    }
+
+    Ok(ThroughputReport {
+        results,
+        window_ms: config.throughput_ms,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn energy_all(config: RunnerConfig) -> Result<EnergyReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+    let count = config.particle_counts.iter().copied().max().unwrap_or(1);
+    let battery = energy::battery().await;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let result = energy_case(kind, count as usize, battery.as_ref(), &config)
+            .await
+            .unwrap_or_else(|error| EnergyResult::unsupported(kind, &error));
+        results.push(result);
+        yield_to_browser().await;
+    }
+
+    Ok(EnergyReport {
+        results,
+        particle_count: count,
+        battery_capacity_wh: config.battery_capacity_wh,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+async fn auto_scale_all(config: RunnerConfig) -> Result<AutoScaleReport, JsValue> {
+    let start = timing::now_ms();
+    let backends = select_backends(&config.backends)?;
+
+    let mut results = Vec::new();
+    for kind in backends {
+        let result = match auto_scale_case(kind, &config).await {
+            Ok(scaler) => AutoScaleResult {
+                backend: kind.name().to_string(),
+                supported: true,
+                error: None,
+                sustained_count: scaler.get_sustained_count() as u32,
+                converged: scaler.is_converged(),
+                seconds: scaler.get_windows(),
+                median_ms: scaler.get_last_median_ms(),
+            },
+            Err(error) => AutoScaleResult {
+                backend: kind.name().to_string(),
+                supported: false,
+                error: Some(describe_error(&error)),
+                sustained_count: 0,
+                converged: false,
+                seconds: 0,
+                median_ms: 0.0,
+            },
+        };
+        results.push(result);
+        yield_to_browser().await;
+    }
+
+    Ok(AutoScaleReport {
+        results,
+        target_frame_ms: config.target_frame_ms,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+    })
+}
+
+fn parse_backend(name: &str) -> Result<BackendKind, JsValue> {
+    BACKENDS
+        .into_iter()
+        .find(|kind| kind.name() == name)
+        .ok_or_else(|| tr(Text::RunnerUnknownBackend, &[&name]).into())
+}
+
+fn select_backends(names: &[String]) -> Result<Vec<BackendKind>, JsValue> {
+    if names.is_empty() {
+        return Ok(BACKENDS.to_vec());
+    }
+    let mut selected = Vec::new();
+    for name in names {
+        let kind = parse_backend(name)?;
+        if !selected.contains(&kind) {
+            selected.push(kind);
+        }
+    }
+    // 指定の順によらず高機能な順に並べる
+    selected.sort_by_key(|kind| BACKENDS.iter().position(|other| other == kind));
+    Ok(selected)
+}
+
+// フレームごとの update() + render() の時間(ms)を返す（GPU が描き終わるまでを含む）
+// workload があれば、ウォームアップの後に記録どおりに操作しながら記録の長さだけ計測する
+async fn run_case(
+    kind: BackendKind,
+    count: u32,
+    config: &RunnerConfig,
+    workload: Option<&Workload>,
+) -> Result<Vec<f64>, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(count as usize);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    rng::with_seed(config.seed, || {
+        backend.switch_scene(SceneKind::Particles)?;
+        for _ in 0..config.warmup_frames {
+            backend.update();
+            backend.render();
+        }
+        backend.finish_gpu();
+        let frames = match workload {
+            Some(workload) => {
+                backend.play_workload(workload)?;
+                workload.get_frames()
+            }
+            None => config.measure_frames,
+        };
+        let mut samples = Vec::with_capacity(frames.max(1) as usize);
+        for _ in 0..frames.max(1) {
+            let start = timing::now_ms();
+            backend.update();
+            backend.render();
+            backend.finish_gpu();
+            samples.push(timing::now_ms() - start);
+        }
+        Ok(samples)
+    })
+}
+
+// 収束するか上限の秒数になるまで、1秒ごとにパーティクル数を変えながら計測する
+async fn auto_scale_case(kind: BackendKind, config: &RunnerConfig) -> Result<AutoScaler, JsValue> {
+    let max_count = config.particle_counts.iter().copied().max().unwrap_or(1);
+    let initial_count = config.particle_counts.iter().copied().min().unwrap_or(1);
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(max_count as usize);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    let mut scaler = AutoScaler::new(
+        config.target_frame_ms,
+        initial_count as usize,
+        backend.get_max_particles(),
+    );
+    rng::with_seed(config.seed, || {
+        backend.switch_scene(SceneKind::Particles)?;
+        backend.set_particle_count(scaler.get_particle_count());
+        for _ in 0..config.warmup_frames {
+            backend.update();
+            backend.render();
+        }
+        Ok::<_, JsValue>(())
+    })?;
+
+    while !scaler.is_converged() && scaler.get_windows() < MAX_AUTO_SCALE_SECONDS {
+        let windows = scaler.get_windows();
+        // 1秒分のフレームを続けて計測し、判定のたびにブラウザに戻す
+        rng::with_seed(config.seed.wrapping_add(windows as u64), || {
+            while scaler.get_windows() == windows {
+                let start = timing::now_ms();
+                backend.update();
+                backend.render();
+                if let Some(count) = scaler.record_frame(timing::now_ms() - start) {
+                    backend.set_particle_count(count);
+                }
+            }
+        });
+        yield_to_browser().await;
+    }
+    Ok(scaler)
+}
+
+// 点の大きさごとのフレーム時間(ms)を返す
+async fn fill_rate_case(
+    kind: BackendKind,
+    count: u32,
+    config: &RunnerConfig,
+) -> Result<Vec<(f32, Vec<f64>)>, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(count as usize);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    // 画面より大きい点は塗る量が増えないので、画面の短い辺までにする
+    let max_size = backend
+        .get_max_point_size()
+        .min(config.width.min(config.height) as f32);
+    let mut curve = Vec::new();
+    for size in scene::sweep_sizes(max_size) {
+        let samples = rng::with_seed(config.seed, || {
+            backend.switch_to_point_size(count as usize, size)?;
+            Ok::<_, JsValue>(measure_scene(&mut backend, config))
+        })?;
+        curve.push((size, samples));
+        yield_to_browser().await;
+    }
+    Ok(curve)
+}
+
+// 合成方法ごとに、フレーム時間(ms)と代わりの描き方になったかを返す
+// 使えない合成方法はその合成方法だけ Err になる
+async fn compositing_case(
+    kind: BackendKind,
+    config: &RunnerConfig,
+) -> Result<Vec<(CompositeMode, Result<(Vec<f64>, bool), JsValue>)>, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    // パーティクルは描かないので最小限にする
+    let backend_config = BackendConfig::new(1);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    // 作ったときの警告は合成方法のものではないので捨てる
+    backend.poll_events();
+    let mut cases = Vec::new();
+    for mode in CompositeMode::ALL {
+        let case = rng::with_seed(config.seed, || {
+            backend.switch_to_compositing(mode)?;
+            let samples = measure_scene(&mut backend, config);
+            let fallback = backend
+                .poll_events()
+                .iter()
+                .any(|event| event.kind == EventKind::FallbackUsed);
+            Ok((samples, fallback))
+        });
+        cases.push((mode, case));
+        yield_to_browser().await;
+    }
+    Ok(cases)
+}
+
+// 1秒ごとの記録と、途中で増やせなくなったときのエラーを返す
+// 増やすのはフレームの中で行い、取り直しの時間もそのフレームの時間に含める
+async fn memory_growth_case(
+    kind: BackendKind,
+    config: &RunnerConfig,
+) -> Result<(Vec<GrowthSample>, Option<JsValue>), JsValue> {
+    let initial_count = config.particle_counts.iter().copied().min().unwrap_or(1) as usize;
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(initial_count);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+    // 作成時の数を超えて増やせないバックエンドは計測しない
+    backend.grow_particle_count(initial_count + 1)?;
+    backend.set_particle_count(initial_count);
+
+    rng::with_seed(config.seed, || {
+        backend.switch_scene(SceneKind::Particles)?;
+        for _ in 0..config.warmup_frames {
+            backend.update();
+            backend.render();
+        }
+        Ok::<_, JsValue>(())
+    })?;
+
+    let growth_start = timing::now_ms();
+    let mut samples = Vec::new();
+    for second in 1..=config.growth_seconds {
+        let window_end = growth_start + second as f64 * 1_000.0;
+        let mut frames = Vec::new();
+        let (mut grow_frames, mut grow_max_ms) = (0, 0.0f64);
+        let result = rng::with_seed(config.seed.wrapping_add(second as u64), || {
+            while timing::now_ms() < window_end {
+                let frame_start = timing::now_ms();
+                let elapsed = (frame_start - growth_start) / 1_000.0;
+                let target = initial_count + (config.growth_per_second as f64 * elapsed) as usize;
+                let bytes = memory::wasm_memory_bytes();
+                if target > backend.get_particle_count() {
+                    backend.grow_particle_count(target)?;
+                }
+                backend.update();
+                backend.render();
+                let frame_ms = timing::now_ms() - frame_start;
+                if memory::wasm_memory_bytes() > bytes {
+                    grow_frames += 1;
+                    grow_max_ms = grow_max_ms.max(frame_ms);
+                }
+                frames.push(frame_ms);
+            }
+            Ok::<_, JsValue>(())
+        });
+        if !frames.is_empty() {
+            samples.push(GrowthSample {
+                second,
+                particle_count: backend.get_particle_count() as u32,
+                frames: frames.len() as u32,
+                median_ms: stats::summarize(&frames, Aggregation::Median).center,
+                max_ms: frames.iter().copied().fold(0.0, f64::max),
+                wasm_pages: (memory::wasm_memory_bytes() / WASM_PAGE_BYTES) as u32,
+                grow_frames,
+                grow_max_ms,
+            });
+        }
+        if let Err(error) = result {
+            return Ok((samples, Some(error)));
+        }
+        yield_to_browser().await;
+    }
+    Ok((samples, None))
+}
+
+// 1秒分（rate 個）の領域で作り、中央のエミッターから毎フレーム rate / 60 個出す
+// 最初のパーティクルは add_emitter() でばらばらの寿命になるので、寿命1回分待ってから計測する
+async fn churn_case(
+    kind: BackendKind,
+    rate: u32,
+    config: &RunnerConfig,
+) -> Result<ChurnResult, JsValue> {
+    let count = rate.max(1) as usize;
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(count);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+    let target_per_frame = rate as f64 / CHURN_FRAMES_PER_SECOND;
+
+    let (samples, spawned, pages_grown) = rng::with_seed(config.seed, || {
+        backend.switch_scene(SceneKind::Particles)?;
+        backend.set_particle_lifetime(CHURN_LIFETIME_FRAMES);
+        let (width, height) = (config.width as f32, config.height as f32);
+        backend.add_emitter(
+            width / 2.0,
+            height / 2.0,
+            target_per_frame as f32,
+            360.0,
+            4.0,
+        );
+        for _ in 0..config.warmup_frames.max(CHURN_LIFETIME_FRAMES as u32) {
+            backend.update();
+            backend.render();
+        }
+        let emitted = backend.get_emitted_count();
+        let bytes = memory::wasm_memory_bytes();
+        let samples: Vec<f64> = (0..config.measure_frames.max(1))
+            .map(|_| {
+                let start = timing::now_ms();
+                backend.update();
+                backend.render();
+                timing::now_ms() - start
+            })
+            .collect();
+        let pages_grown = (memory::wasm_memory_bytes() - bytes) / WASM_PAGE_BYTES;
+        Ok::<_, JsValue>((samples, backend.get_emitted_count() - emitted, pages_grown))
+    })?;
+
+    Ok(ChurnResult {
+        backend: kind.name().to_string(),
+        churn_per_second: rate,
+        supported: true,
+        error: None,
+        frames: samples.len() as u32,
+        mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+        median_ms: stats::summarize(&samples, Aggregation::Median).center,
+        p95_ms: stats::percentile(&samples, 0.95),
+        max_ms: samples.iter().copied().fold(0.0, f64::max),
+        target_per_frame,
+        spawned_per_frame: spawned / samples.len() as f64,
+        wasm_pages_grown: pages_grown as u32,
+    })
+}
+
+// driver で呼ばれるたびに1フレーム進め、呼ばれた間隔と1フレームの処理時間を記録する
+async fn loop_driver_case(
+    kind: BackendKind,
+    driver: LoopDriver,
+    count: usize,
+    config: &RunnerConfig,
+) -> Result<LoopDriverResult, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let mut backend_config = BackendConfig::new(count);
+    backend_config.seed = Some(config.seed);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    for _ in 0..config.warmup_frames {
+        driver::next_tick(driver).await?;
+        backend.update();
+        backend.render();
+    }
+    let frames = config.measure_frames.max(1) as usize;
+    let mut intervals = Vec::with_capacity(frames);
+    let mut work = Vec::with_capacity(frames);
+    let mut last = timing::now_ms();
+    for _ in 0..frames {
+        driver::next_tick(driver).await?;
+        let now = timing::now_ms();
+        intervals.push(now - last);
+        last = now;
+        backend.update();
+        backend.render();
+        work.push(timing::now_ms() - now);
+    }
+    let elapsed: f64 = intervals.iter().sum();
+
+    Ok(LoopDriverResult {
+        backend: kind.name().to_string(),
+        driver: driver.name().to_string(),
+        supported: true,
+        error: None,
+        frames: frames as u32,
+        cadence_hz: if elapsed > 0.0 {
+            frames as f64 * 1_000.0 / elapsed
+        } else {
+            0.0
+        },
+        interval_median_ms: stats::summarize(&intervals, Aggregation::Median).center,
+        interval_p95_ms: stats::percentile(&intervals, 0.95),
+        interval_max_ms: intervals.iter().copied().fold(0.0, f64::max),
+        jitter_ms: stats::stddev(&intervals),
+        work_median_ms: stats::summarize(&work, Aggregation::Median).center,
+    })
+}
+
+// requestAnimationFrame ごとに tick() し、描いた回数と1回の時間を記録する
+async fn frame_cap_case(
+    kind: BackendKind,
+    target_fps: f64,
+    count: usize,
+    config: &RunnerConfig,
+) -> Result<FrameCapResult, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let mut backend_config = BackendConfig::new(count);
+    backend_config.seed = Some(config.seed);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    // 上限なしで回しながらリフレッシュレートを推定し、計測の前に数え直す
+    for _ in 0..config.warmup_frames {
+        driver::next_tick(LoopDriver::AnimationFrame).await?;
+        backend.tick(timing::now_ms());
+    }
+    let target_fps = backend.set_target_fps(target_fps);
+    let ticks = config.measure_frames.max(1);
+    let mut samples = Vec::with_capacity(ticks as usize);
+    let measure_start = timing::now_ms();
+    for _ in 0..ticks {
+        driver::next_tick(LoopDriver::AnimationFrame).await?;
+        let now = timing::now_ms();
+        backend.tick(now);
+        samples.push(timing::now_ms() - now);
+    }
+    let seconds = (timing::now_ms() - measure_start) / 1_000.0;
+    let per_second = |n: u32| {
+        if seconds > 0.0 {
+            n as f64 / seconds
+        } else {
+            0.0
+        }
+    };
+
+    Ok(FrameCapResult {
+        backend: kind.name().to_string(),
+        target_fps,
+        supported: true,
+        error: None,
+        refresh_hz: backend.get_refresh_rate(),
+        ticks,
+        tick_hz: per_second(ticks),
+        rendered_fps: per_second(backend.get_rendered_ticks()),
+        skipped: backend.get_skipped_ticks(),
+        tick_median_ms: stats::summarize(&samples, Aggregation::Median).center,
+        tick_p95_ms: stats::percentile(&samples, 0.95),
+    })
+}
+
+// requestAnimationFrame ごとに描き、フレームの間の逼迫度と電池の値を記録する
+// 逼迫度の通知はフレームの間に届くので、前のフレームからの時間をその時点の状態に数える
+async fn energy_case(
+    kind: BackendKind,
+    count: usize,
+    battery: Option<&JsValue>,
+    config: &RunnerConfig,
+) -> Result<EnergyResult, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let mut backend_config = BackendConfig::new(count);
+    backend_config.seed = Some(config.seed);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+    let pressure = PressureWatch::start().await;
+
+    for _ in 0..config.warmup_frames {
+        driver::next_tick(LoopDriver::AnimationFrame).await?;
+        backend.update();
+        backend.render();
+    }
+
+    let capacity_wh = config.battery_capacity_wh;
+    let mut log = EnergyLog::default();
+    let measure_start = timing::now_ms();
+    let mut last = measure_start;
+    let mut frames = 0;
+    log.record(
+        0.0,
+        pressure.state(),
+        battery.and_then(energy::read_battery),
+        capacity_wh,
+    );
+    while last - measure_start < config.energy_ms.max(1.0) {
+        driver::next_tick(LoopDriver::AnimationFrame).await?;
+        backend.update();
+        backend.render();
+        frames += 1;
+        let now = timing::now_ms();
+        log.record(
+            now - last,
+            pressure.state(),
+            battery.and_then(energy::read_battery),
+            capacity_wh,
+        );
+        last = now;
+    }
+
+    Ok(EnergyResult::new(
+        kind,
+        frames,
+        last - measure_start,
+        &log,
+        capacity_wh,
+        None,
+    ))
+}
+
+// throughput_ms の間できるだけ多く描き、最後に描画が終わるのを待ってから数える
+async fn throughput_case(
+    kind: BackendKind,
+    count: u32,
+    config: &RunnerConfig,
+) -> Result<ThroughputResult, JsValue> {
+    let canvas = web_sys::OffscreenCanvas::new(config.width, config.height)?;
+    let mut backend_config = BackendConfig::new(count as usize);
+    backend_config.seed = Some(config.seed);
+    let mut backend =
+        Backend::create_async(kind, &OffscreenCanvasSource(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    for _ in 0..config.warmup_frames {
+        backend.update();
+        backend.render();
+    }
+    backend.finish_gpu();
+
+    let window_ms = config.throughput_ms.max(1.0);
+    let measure_start = timing::now_ms();
+    let mut frames = 0;
+    while timing::now_ms() - measure_start < window_ms {
+        backend.update();
+        backend.render();
+        frames += 1;
+    }
+    let gpu_synced = backend.finish_gpu();
+    let elapsed_ms = timing::now_ms() - measure_start;
+
+    Ok(ThroughputResult {
+        backend: kind.name().to_string(),
+        particle_count: count,
+        supported: true,
+        error: None,
+        frames,
+        elapsed_ms,
+        fps: frames as f64 * 1_000.0 / elapsed_ms,
+        frame_ms: elapsed_ms / frames as f64,
+        gpu_synced,
+    })
+}
+
+// 全インスタンスを作ってページに並べ、1つずつ計測してから全部を同時に計測する
+// 計測が終わったら（途中で作れなかったときも）キャンバスはページから外す
+async fn contention_case(
+    kinds: &[BackendKind],
+    load: f32,
+    count: usize,
+    config: &RunnerConfig,
+) -> Result<Vec<ContentionResult>, JsValue> {
+    let body = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.body())
+        .ok_or(BenchError::DocumentUnavailable)?;
+    let mut canvases = Vec::new();
+    let mut instances = Vec::new();
+    let created = async {
+        for &kind in kinds {
+            let canvas = create_canvas(config.width, config.height)?;
+            body.append_child(&canvas)?;
+            canvases.push(canvas.clone());
+            let source = CanvasElement(&canvas);
+            let mut backend =
+                Backend::create_async(kind, &source, &BackendConfig::new(count)).await?;
+            backend.strict_benchmark(true);
+            rng::with_seed(config.seed, || {
+                backend.switch_scene(SceneKind::Particles)?;
+                backend.set_load(load);
+                Ok::<_, JsValue>(())
+            })?;
+            instances.push(backend);
+        }
+        Ok::<_, JsValue>(())
+    }
+    .await;
+    let result = created.map(|()| measure_contention(&mut instances, config));
+    for canvas in &canvases {
+        canvas.remove();
+    }
+
+    let (solo, shared, combined) = result?;
+    let combined_median_ms = stats::summarize(&combined, Aggregation::Median).center;
+    Ok(kinds
+        .iter()
+        .zip(solo.iter().zip(&shared))
+        .enumerate()
+        .map(|(i, (&kind, (solo, shared)))| {
+            let solo_median_ms = stats::summarize(solo, Aggregation::Median).center;
+            let shared_median_ms = stats::summarize(shared, Aggregation::Median).center;
+            ContentionResult {
+                backend: kind.name().to_string(),
+                instance: i as u32,
+                load,
+                supported: true,
+                error: None,
+                solo_median_ms,
+                shared_median_ms,
+                slowdown: if solo_median_ms > 0.0 {
+                    shared_median_ms / solo_median_ms
+                } else {
+                    0.0
+                },
+                combined_median_ms,
+            }
+        })
+        .collect())
+}
+
+// インスタンスごとの単独と同時のフレーム時間、同時のときの1フレーム全体の時間を返す
+fn measure_contention(
+    instances: &mut [Backend],
+    config: &RunnerConfig,
+) -> (Vec<Vec<f64>>, Vec<Vec<f64>>, Vec<f64>) {
+    let solo = instances
+        .iter_mut()
+        .map(|backend| rng::with_seed(config.seed, || measure_scene(backend, config)))
+        .collect();
+
+    let frames = config.measure_frames.max(1) as usize;
+    let mut shared = vec![Vec::with_capacity(frames); instances.len()];
+    let mut combined = Vec::with_capacity(frames);
+    rng::with_seed(config.seed, || {
+        for frame in 0..config.warmup_frames as usize + frames {
+            let frame_start = timing::now_ms();
+            for (backend, samples) in instances.iter_mut().zip(&mut shared) {
+                let start = timing::now_ms();
+                backend.update();
+                backend.render();
+                if frame >= config.warmup_frames as usize {
+                    samples.push(timing::now_ms() - start);
+                }
+            }
+            if frame >= config.warmup_frames as usize {
+                combined.push(timing::now_ms() - frame_start);
+            }
+        }
+    });
+    (solo, shared, combined)
+}
+
+// scene に切り替えてアトラスを source の形で渡し、フレーム時間を計測する
+async fn image_source_case(
+    backend: &mut Backend,
+    scene: SceneKind,
+    source: ImageSource,
+    config: &RunnerConfig,
+) -> Result<ImageSourceResult, JsValue> {
+    rng::with_seed(config.seed, || backend.switch_scene(scene))?;
+    let atlas = backend
+        .scene_atlas_mut()
+        .ok_or_else(|| tr(Text::SceneHasNoAtlas, &[&scene.name()]))?;
+    let decode_start = timing::now_ms();
+    let image = image_source::decode(atlas, source).await?;
+    let decode_ms = timing::now_ms() - decode_start;
+
+    let (samples, upload_samples) = rng::with_seed(config.seed, || {
+        if let Some(atlas) = backend.scene_atlas_mut() {
+            atlas.set_image(image.clone());
+        }
+        let samples = measure_scene(backend, config);
+        // 渡し直すとバージョンが変わるので、次の描画で転送し直す
+        let upload_samples: Vec<f64> = (0..UPLOAD_SAMPLES)
+            .map(|_| {
+                if let Some(atlas) = backend.scene_atlas_mut() {
+                    atlas.set_image(image.clone());
+                }
+                let start = timing::now_ms();
+                backend.update();
+                backend.render();
+                timing::now_ms() - start
+            })
+            .collect();
+        (samples, upload_samples)
+    });
+
+    Ok(ImageSourceResult {
+        backend: backend.get_kind().name().to_string(),
+        scene: scene.name().to_string(),
+        source: source.name().to_string(),
+        supported: true,
+        error: None,
+        decode_ms,
+        upload_frame_ms: stats::summarize(&upload_samples, Aggregation::Median).center,
+        mean_ms: stats::summarize(&samples, Aggregation::Mean).center,
+        median_ms: stats::summarize(&samples, Aggregation::Median).center,
+    })
+}
+
+// 大きさ（キャンバスの一辺に対する割合）ごとに switch でシーンを切り替え、フレーム時間(ms)を返す
+async fn scale_case(
+    kind: BackendKind,
+    config: &RunnerConfig,
+    scales: &[f32],
+    switch: impl Fn(&mut Backend, f32) -> Result<(), JsValue>,
+) -> Result<Vec<(f32, Vec<f64>)>, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    // パーティクルは描かないので最小限にする
+    let backend_config = BackendConfig::new(1);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(true);
+
+    let mut curve = Vec::new();
+    for &scale in scales {
+        let samples = rng::with_seed(config.seed, || {
+            switch(&mut backend, scale)?;
+            Ok::<_, JsValue>(measure_scene(&mut backend, config))
+        })?;
+        curve.push((scale, samples));
+        yield_to_browser().await;
+    }
+    Ok(curve)
+}
+
+// 切り替えたシーンをウォームアップしてから、フレームごとの update() + render() の時間(ms)を返す
+// GPU のバックエンドは描き終わるまで待ってから測る（命令を出した時間だけでは塗る量の違いが出ない）
+fn measure_scene(backend: &mut Backend, config: &RunnerConfig) -> Vec<f64> {
+    for _ in 0..config.warmup_frames {
+        backend.update();
+        backend.render();
+    }
+    backend.finish_gpu();
+    let mut samples = Vec::with_capacity(config.measure_frames.max(1) as usize);
+    for _ in 0..config.measure_frames.max(1) {
+        let start = timing::now_ms();
+        backend.update();
+        backend.render();
+        backend.finish_gpu();
+        samples.push(timing::now_ms() - start);
+    }
+    samples
+}
@@ -0,0 +1,109 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{CubicBezier, Scene, SceneKind, Surface};
+use crate::math;
+
+// 負荷 1.0 のときの曲線の数（既定はその半分）
+const MAX_CURVES: usize = 4_000;
+// 制御点が揺れる幅(px)
+const WOBBLE: f32 = 80.0;
+const PALETTE_SIZE: u32 = 12;
+
+struct Strand {
+    start: (f32, f32),
+    end: (f32, f32),
+    phase: f32,
+    speed: f32,
+    rgb: [f32; 3],
+}
+
+// 揺れ動く3次ベジェ曲線を大量に描く
+// WebGLはWASMで折れ線に分解して線で、Canvas2Dは bezierCurveTo で描く
+pub(crate) struct BezierScene {
+    width: f32,
+    height: f32,
+    strands: Vec<Strand>,
+    curves: Vec<CubicBezier>,
+    frame_count: u32,
+}
+
+impl BezierScene {
+    pub fn new(width: f32, height: f32) -> BezierScene {
+        let mut scene = BezierScene {
+            width,
+            height,
+            strands: Vec::new(),
+            curves: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for BezierScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Bezier
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.02, 0.02, 0.05]);
+
+        let t = self.frame_count as f32;
+        self.curves.clear();
+        for s in &self.strands {
+            let a = s.phase + t * s.speed;
+            let (sx, sy) = s.start;
+            let (ex, ey) = s.end;
+            self.curves.push(CubicBezier {
+                points: [
+                    s.start,
+                    (
+                        sx + (ex - sx) / 3.0 + math::cos(a) * WOBBLE,
+                        sy + (ey - sy) / 3.0 + math::sin(a * 1.3) * WOBBLE,
+                    ),
+                    (
+                        sx + (ex - sx) * 2.0 / 3.0 + math::sin(a * 0.7) * WOBBLE,
+                        sy + (ey - sy) * 2.0 / 3.0 + math::cos(a * 1.1) * WOBBLE,
+                    ),
+                    s.end,
+                ],
+                rgb: s.rgb,
+            });
+        }
+        surface.stroke_beziers(&self.curves);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_CURVES as f32 * load).round() as usize;
+        let (w, h) = (self.width, self.height);
+        let mut rng = crate::rng::rng();
+        self.strands.truncate(count);
+        while self.strands.len() < count {
+            // 色は数色に絞る（Canvas2D で同じ色の曲線をまとめて描けるように）
+            let hue = rng.gen_range(0..PALETTE_SIZE) as f32 * 360.0 / PALETTE_SIZE as f32;
+            let (r, g, b) = crate::simulation::hsl_to_rgb(hue, 0.7, 0.6);
+            self.strands.push(Strand {
+                start: (rng.gen::<f32>() * w, rng.gen::<f32>() * h),
+                end: (rng.gen::<f32>() * w, rng.gen::<f32>() * h),
+                phase: rng.gen::<f32>() * 2.0 * PI,
+                speed: 0.01 + rng.gen::<f32>() * 0.03,
+                rgb: [r, g, b],
+            });
+        }
+        self.strands.sort_by(|a, b| {
+            a.rgb
+                .partial_cmp(&b.rgb)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
@@ -0,0 +1,212 @@
+use wasm_bindgen::prelude::*;
+
+use super::{Scene, SceneKind, Surface};
+use crate::capture::{DebugBuffer, DebugPixels};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+
+// 既定の画像を使うときのぼかし半径(px)
+pub(crate) const DEFAULT_RADIUS: u32 = 8;
+// シェーダー版が扱える最大半径（GLSL ES 1.0 はループ回数が定数）
+pub(crate) const MAX_SHADER_RADIUS: u32 = 8;
+// 負荷 1.0 のときのぼかし半径
+pub(crate) const MAX_RADIUS: u32 = 16;
+
+// ぼかしを誰が計算するか
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlurMethod {
+    // WASMで分離可能ガウシアンを計算して転送する
+    Wasm = 0,
+    // Canvas2D の filter = "blur(...)" に任せる
+    CanvasFilter = 1,
+    // WebGLのフラグメントシェーダーでぼかす
+    Shader = 2,
+}
+
+// 同じ画像を毎フレームぼかして描くフィルタのベンチマーク
+// 描画先が指定の方法に対応していなければWASM版で描く
+pub(crate) struct BlurScene {
+    method: BlurMethod,
+    radius: u32,
+    width: usize,
+    height: usize,
+    image: Vec<u8>,
+    kernel: Vec<f32>,
+    // 横方向に畳み込んだ途中結果
+    scratch: Vec<f32>,
+    output: Vec<u8>,
+    frame_count: u32,
+}
+
+impl BlurScene {
+    // image は width × height のRGBA（radius は MAX_RADIUS までに丸める）
+    pub fn new(
+        width: u32,
+        height: u32,
+        image: Vec<u8>,
+        method: BlurMethod,
+        radius: u32,
+    ) -> Result<BlurScene, String> {
+        let (width, height) = (width as usize, height as usize);
+        let expected = width.checked_mul(height).and_then(|pixels| pixels.checked_mul(4));
+        if width == 0 || height == 0 || expected != Some(image.len()) {
+            return Err(tr(
+                Text::BlurImageSize,
+                &[&width, &height, &(width as u64 * height as u64 * 4), &image.len()],
+            ));
+        }
+        let radius = radius.min(MAX_RADIUS);
+        Ok(BlurScene {
+            method,
+            radius,
+            width,
+            height,
+            kernel: gaussian_kernel(radius),
+            scratch: vec![0.0; image.len()],
+            output: vec![0; image.len()],
+            image,
+            frame_count: 0,
+        })
+    }
+
+    // 画像が渡されていないときの確認用パターン（格子とグラデーション）
+    pub fn with_test_pattern(canvas_width: f32, canvas_height: f32) -> BlurScene {
+        let width = ((canvas_width / 2.0) as usize).max(1);
+        let height = ((canvas_height / 2.0) as usize).max(1);
+        let mut image = vec![0; width * height * 4];
+        for (i, px) in image.chunks_exact_mut(4).enumerate() {
+            let (x, y) = (i % width, i / width);
+            let on = (x / 16 + y / 16) % 2 == 0;
+            px[0] = (x * 255 / width) as u8;
+            px[1] = if on { 230 } else { 40 };
+            px[2] = (y * 255 / height) as u8;
+            px[3] = 255;
+        }
+        BlurScene::new(
+            width as u32,
+            height as u32,
+            image,
+            BlurMethod::Wasm,
+            DEFAULT_RADIUS,
+        )
+        .expect("test pattern has a valid size")
+    }
+
+    // 横→縦の2パスで畳み込む（端は端のピクセルを延長する）
+    fn blur_wasm(&mut self) {
+        let (w, h) = (self.width, self.height);
+        let r = self.radius as isize;
+
+        for y in 0..h {
+            let row = y * w;
+            for x in 0..w {
+                let mut sum = [0.0f32; 4];
+                for (k, weight) in self.kernel.iter().enumerate() {
+                    let sx = (x as isize + k as isize - r).clamp(0, w as isize - 1) as usize;
+                    let src = &self.image[(row + sx) * 4..(row + sx) * 4 + 4];
+                    for c in 0..4 {
+                        sum[c] += src[c] as f32 * weight;
+                    }
+                }
+                self.scratch[(row + x) * 4..(row + x) * 4 + 4].copy_from_slice(&sum);
+            }
+        }
+
+        for y in 0..h {
+            for x in 0..w {
+                let mut sum = [0.0f32; 4];
+                for (k, weight) in self.kernel.iter().enumerate() {
+                    let sy = (y as isize + k as isize - r).clamp(0, h as isize - 1) as usize;
+                    let src = &self.scratch[(sy * w + x) * 4..(sy * w + x) * 4 + 4];
+                    for c in 0..4 {
+                        sum[c] += src[c] * weight;
+                    }
+                }
+                let dst = &mut self.output[(y * w + x) * 4..(y * w + x) * 4 + 4];
+                for c in 0..4 {
+                    dst[c] = sum[c].clamp(0.0, 255.0) as u8;
+                }
+            }
+        }
+    }
+}
+
+// 合計が1になるガウシアンの重み（σ = 半径/2）
+fn gaussian_kernel(radius: u32) -> Vec<f32> {
+    let r = radius as i32;
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let mut kernel: Vec<f32> = (-r..=r)
+        .map(|i| math::exp(-((i * i) as f32) / (2.0 * sigma * sigma)))
+        .collect();
+    let total: f32 = kernel.iter().sum();
+    for weight in &mut kernel {
+        *weight /= total;
+    }
+    kernel
+}
+
+impl Scene for BlurScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Blur
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        let (w, h) = (self.width as u32, self.height as u32);
+        let drawn = match self.method {
+            BlurMethod::Wasm => false,
+            BlurMethod::CanvasFilter => {
+                surface.blit_rgba_filtered(w, h, &self.image, self.radius as f32)
+            }
+            BlurMethod::Shader => {
+                if self.radius > MAX_SHADER_RADIUS {
+                    surface.report(
+                        EventKind::ParameterClamped,
+                        &tr(Text::BlurRadiusClamped, &[&MAX_SHADER_RADIUS]),
+                    );
+                }
+                surface.blit_rgba_shader_blur(w, h, &self.image, self.radius.min(MAX_SHADER_RADIUS))
+            }
+        };
+        if !drawn && self.method != BlurMethod::Wasm {
+            surface.report(
+                EventKind::FallbackUsed,
+                &tr(Text::BlurFallback, &[&format!("{:?}", self.method)]),
+            );
+        }
+        if !drawn {
+            self.blur_wasm();
+            surface.blit_rgba(w, h, &self.output);
+        }
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 半径 0~16px（シェーダー版は8pxで頭打ち）
+    fn set_load(&mut self, load: f32) {
+        self.radius = ((MAX_RADIUS as f32 * load).round() as u32).min(MAX_RADIUS);
+        self.kernel = gaussian_kernel(self.radius);
+    }
+
+    // 横・縦のパスは WASM 版で描いたときだけ中身がある
+    fn debug_buffers(&self) -> Vec<DebugBuffer<'_>> {
+        let buffer = |name, pixels| DebugBuffer {
+            name,
+            width: self.width,
+            height: self.height,
+            pixels,
+        };
+        vec![
+            buffer("source", DebugPixels::Rgba(&self.image)),
+            buffer("blur-horizontal", DebugPixels::RgbaF32(&self.scratch)),
+            buffer("blur-vertical", DebugPixels::Rgba(&self.output)),
+        ]
+    }
+}
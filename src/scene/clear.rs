@@ -0,0 +1,42 @@
+use super::{Scene, SceneKind, Surface};
+
+// 何も描かずに画面クリアだけ行う基準シーン
+pub(crate) struct ClearScene {
+    // 1フレームあたりのクリア回数（フィルレートの負荷を調整する）
+    clears: u32,
+    frame_count: u32,
+}
+
+impl ClearScene {
+    pub fn new(_width: f32, _height: f32) -> ClearScene {
+        ClearScene {
+            clears: 1,
+            frame_count: 0,
+        }
+    }
+}
+
+impl Scene for ClearScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Clear
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for _ in 0..self.clears {
+            surface.clear([0.1, 0.1, 0.1]);
+        }
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 0.0 で1回、1.0 で16回
+    fn set_load(&mut self, load: f32) {
+        self.clears = 1 + (load * 15.0).round() as u32;
+    }
+}
@@ -0,0 +1,143 @@
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use super::{ColorPoint, Polygon, Scene, SceneKind, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::render_mode::RenderMode;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 1.0 のときの点の数（既定はその半分）
+const MAX_POINTS: usize = 40_000;
+const POINT_SIZE: f32 = 3.0;
+pub(crate) const DEFAULT_CLIP_REGIONS: u32 = 6;
+pub(crate) const MAX_CLIP_REGIONS: u32 = 64;
+// 星形の先端の数
+const STAR_POINTS: usize = 5;
+
+struct Dot {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    rgb: [f32; 3],
+}
+
+// 回りながら動く星形の領域の中だけにパーティクルを描く（クリップのコストの計測用）
+// Canvas2D は毎フレーム星形のパスで clip() し、WebGL は星形をステンシルバッファに書いてから
+// ステンシルテスト付きで点を描く。領域の数を増やすほどクリップの形が複雑になる
+pub(crate) struct ClipScene {
+    width: f32,
+    height: f32,
+    regions: u32,
+    dots: Vec<Dot>,
+    points: Vec<ColorPoint>,
+    shapes: Vec<Polygon>,
+    frame_count: u32,
+}
+
+impl ClipScene {
+    pub fn new(width: f32, height: f32, regions: u32) -> ClipScene {
+        let mut scene = ClipScene {
+            width,
+            height,
+            regions: regions.clamp(1, MAX_CLIP_REGIONS),
+            dots: Vec::new(),
+            points: Vec::new(),
+            shapes: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    // i 番目の星形（円周上を回りながら自転し、大きさも脈打つ）
+    fn shape(&self, i: u32) -> Polygon {
+        let t = self.frame_count as f32 * 0.01;
+        let offset = i as f32 / self.regions as f32 * TAU;
+        let orbit = self.width.min(self.height) * 0.3;
+        let cx = self.width / 2.0 + math::cos(t + offset) * orbit;
+        let cy = self.height / 2.0 + math::sin(t * 1.3 + offset) * orbit;
+        let outer = self.width.min(self.height) * (0.12 + 0.04 * math::sin(t * 3.0 + offset));
+        let spin = t * 2.0 + offset;
+        let points = (0..STAR_POINTS * 2)
+            .map(|k| {
+                let radius = if k % 2 == 0 { outer } else { outer * 0.45 };
+                let angle = spin + k as f32 / (STAR_POINTS * 2) as f32 * TAU;
+                (
+                    cx + math::cos(angle) * radius,
+                    cy + math::sin(angle) * radius,
+                )
+            })
+            .collect();
+        Polygon {
+            points,
+            rgb: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+impl Scene for ClipScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Clip
+    }
+
+    fn update(&mut self) {
+        let (width, height) = (self.width, self.height);
+        for dot in &mut self.dots {
+            dot.x += dot.vx;
+            dot.y += dot.vy;
+            if dot.x < 0.0 || dot.x > width {
+                dot.vx = -dot.vx;
+            }
+            if dot.y < 0.0 || dot.y > height {
+                dot.vy = -dot.vy;
+            }
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.05, 0.05, 0.05]);
+        self.shapes = (0..self.regions).map(|i| self.shape(i)).collect();
+        self.points.clear();
+        self.points.extend(self.dots.iter().map(|dot| ColorPoint {
+            x: dot.x,
+            y: dot.y,
+            rgb: dot.rgb,
+        }));
+
+        let clipped = surface.push_clip(&self.shapes);
+        if !clipped {
+            surface.report(EventKind::FallbackUsed, &tr(Text::ClipFallback, &[]));
+        }
+        surface.draw_points(&self.points, POINT_SIZE, RenderMode::Normal);
+        if clipped {
+            surface.pop_clip();
+        }
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 点の数を負荷に比例させる（足りない分だけ足し、多い分は末尾から減らす）
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_POINTS as f32 * load).round() as usize;
+        let mut rng = crate::rng::rng();
+        while self.dots.len() < count {
+            let angle = rng.gen::<f32>() * TAU;
+            let speed = 0.5 + rng.gen::<f32>() * 2.0;
+            let (r, g, b) = hsl_to_rgb(rng.gen::<f32>() * 360.0, 0.8, 0.6);
+            self.dots.push(Dot {
+                x: rng.gen::<f32>() * self.width,
+                y: rng.gen::<f32>() * self.height,
+                vx: math::cos(angle) * speed,
+                vy: math::sin(angle) * speed,
+                rgb: [r, g, b],
+            });
+        }
+        self.dots.truncate(count);
+    }
+}
@@ -0,0 +1,127 @@
+use rand::Rng;
+
+use super::{ColorRect, Scene, SceneKind, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::render_mode::CompositeMode;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 1.0 のときに重ねる層の数（既定はその半分）
+const MAX_LAYERS: usize = 16;
+// 1層の格子の列と行の数
+const COLUMNS: usize = 8;
+const ROWS: usize = 6;
+// 重ねる矩形の不透明度
+const LAYER_ALPHA: f32 = 0.6;
+// 掃引で1つの合成方法を描き続けるフレーム数
+const FRAMES_PER_MODE: u32 = 60;
+// 背景の縦縞の数
+const STRIPES: usize = 12;
+
+// 止まった半透明の矩形を何層も重ねて、合成方法だけを変えて描く（合成方法のコストの計測用）
+// 絵は毎フレーム同じなので、フレーム時間の違いが合成方法の違いになる
+// mode が None なら CompositeMode::ALL を FRAMES_PER_MODE フレームずつ順に描く
+pub(crate) struct CompositeScene {
+    width: f32,
+    height: f32,
+    mode: Option<CompositeMode>,
+    background: Vec<ColorRect>,
+    layers: Vec<ColorRect>,
+    frame_count: u32,
+}
+
+impl CompositeScene {
+    pub fn new(width: f32, height: f32, mode: Option<CompositeMode>) -> CompositeScene {
+        let stripe = width / STRIPES as f32;
+        let background = (0..STRIPES)
+            .map(|i| {
+                let (r, g, b) = hsl_to_rgb(i as f32 * 360.0 / STRIPES as f32, 0.5, 0.35);
+                ColorRect {
+                    x: i as f32 * stripe,
+                    y: 0.0,
+                    width: stripe,
+                    height,
+                    rgb: [r, g, b],
+                }
+            })
+            .collect();
+        let mut scene = CompositeScene {
+            width,
+            height,
+            mode,
+            background,
+            layers: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    fn current_mode(&self) -> CompositeMode {
+        self.mode.unwrap_or_else(|| {
+            let all = CompositeMode::ALL;
+            all[(self.frame_count / FRAMES_PER_MODE) as usize % all.len()]
+        })
+    }
+}
+
+impl Scene for CompositeScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Compositing
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.1, 0.1, 0.1]);
+        surface.fill_rects(&self.background);
+        let mode = self.current_mode();
+        if surface.fill_rects_composited(&self.layers, LAYER_ALPHA, mode) {
+            return;
+        }
+        // 合成方法を使えないので不透明で重ねる
+        surface.report(
+            EventKind::FallbackUsed,
+            &tr(Text::CompositeFallback, &[&mode.name()]),
+        );
+        surface.fill_rects(&self.layers);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 層の数を負荷に比例させる（位置と色は作り直す）
+    fn set_load(&mut self, load: f32) {
+        let count = ((MAX_LAYERS as f32 * load).round() as usize).max(1);
+        let (cell_width, cell_height) = (self.width / COLUMNS as f32, self.height / ROWS as f32);
+        let mut rng = crate::rng::rng();
+        self.layers.clear();
+        for _ in 0..count {
+            // 層ごとに格子を少しずらして、隣の矩形とも重なるようにする
+            let (dx, dy) = (
+                rng.gen::<f32>() * cell_width * 0.5,
+                rng.gen::<f32>() * cell_height * 0.5,
+            );
+            for row in 0..ROWS {
+                for column in 0..COLUMNS {
+                    let (r, g, b) = hsl_to_rgb(rng.gen::<f32>() * 360.0, 0.9, 0.6);
+                    self.layers.push(ColorRect {
+                        x: column as f32 * cell_width + dx - cell_width * 0.25,
+                        y: row as f32 * cell_height + dy - cell_height * 0.25,
+                        width: cell_width,
+                        height: cell_height,
+                        rgb: [r, g, b],
+                    });
+                }
+            }
+        }
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
@@ -0,0 +1,98 @@
+use super::{Scene, SceneKind, Surface};
+use crate::capture::{DebugBuffer, DebugPixels};
+use crate::explosion::ExplosionConfig;
+use crate::simulation::Simulation;
+
+// 負荷 1.0 のときのパーティクル数（既定はその半分）
+const MAX_PARTICLES: usize = 40_000;
+// 1セルあたりのピクセル数（縦横）
+const CELL_SIZE: f32 = 2.0;
+const DEPOSIT: f32 = 0.15;
+const DECAY: f32 = 0.96;
+
+// パーティクルが通った跡を密度バッファに積もらせ、毎フレーム減衰させるシーン
+// 大きなバッファへの毎フレームの書き込みとテクスチャ/ImageData転送の負荷を測る
+pub(crate) struct DensityScene {
+    sim: Simulation,
+    grid_width: usize,
+    grid_height: usize,
+    density: Vec<f32>,
+    pixels: Vec<u8>,
+}
+
+impl DensityScene {
+    pub fn new(width: f32, height: f32) -> Result<DensityScene, String> {
+        let grid_width = ((width / CELL_SIZE) as usize).max(1);
+        let grid_height = ((height / CELL_SIZE) as usize).max(1);
+        let mut sim = Simulation::with_capacity(width, height, MAX_PARTICLES)?;
+        sim.set_load(0.5);
+        Ok(DensityScene {
+            sim,
+            grid_width,
+            grid_height,
+            density: vec![0.0; grid_width * grid_height],
+            pixels: vec![0; grid_width * grid_height * 4],
+        })
+    }
+}
+
+impl Scene for DensityScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Density
+    }
+
+    fn update(&mut self) {
+        self.sim.step();
+
+        for d in &mut self.density {
+            *d *= DECAY;
+        }
+
+        let max_x = self.grid_width - 1;
+        let max_y = self.grid_height - 1;
+        for p in self.sim.particles() {
+            let cx = ((p.x / CELL_SIZE) as usize).min(max_x);
+            let cy = ((p.y / CELL_SIZE) as usize).min(max_y);
+            let cell = &mut self.density[cy * self.grid_width + cx];
+            *cell = (*cell + DEPOSIT).min(1.0);
+        }
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for (d, px) in self.density.iter().zip(self.pixels.chunks_exact_mut(4)) {
+            let [r, g, b] = heat(*d);
+            px[0] = (r * 255.0) as u8;
+            px[1] = (g * 255.0) as u8;
+            px[2] = (b * 255.0) as u8;
+            px[3] = 255;
+        }
+        surface.blit_rgba(self.grid_width as u32, self.grid_height as u32, &self.pixels);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.sim.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        self.sim.set_load(load);
+    }
+
+    fn explode(&mut self, x: f32, y: f32, explosion: &ExplosionConfig) {
+        self.sim.explode(x, y, explosion);
+    }
+
+    fn debug_buffers(&self) -> Vec<DebugBuffer<'_>> {
+        vec![DebugBuffer {
+            name: "density",
+            width: self.grid_width,
+            height: self.grid_height,
+            pixels: DebugPixels::Gray(&self.density),
+        }]
+    }
+}
+
+// 密度(0~1)を黒→赤→黄→白のヒートマップ色に変換
+pub(crate) fn heat(t: f32) -> [f32; 3] {
+    let t = t.clamp(0.0, 1.0) * 3.0;
+    [t.min(1.0), (t - 1.0).clamp(0.0, 1.0), (t - 2.0).clamp(0.0, 1.0)]
+}
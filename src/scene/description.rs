@@ -0,0 +1,138 @@
+use wasm_bindgen::prelude::*;
+
+use super::{blur, clip, glow, life, state_changes, SceneKind};
+use crate::i18n;
+#[cfg(target_arch = "wasm32")]
+use crate::i18n::{tr, Text};
+
+// 調整できるパラメーター1つ分（UIがスライダーなどを自動生成できるように範囲と既定値を持つ）
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct SceneParameter {
+    // 値を渡すAPIの引数名（load は set_load、columns/rows は switch_to_life_grid など）
+    pub name: String,
+    // UIに表示する名前（set_locale() の言語）
+    pub label: String,
+    pub min: f64,
+    pub max: f64,
+    pub default: f64,
+    // 1 なら整数のみ
+    pub step: f64,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct SceneDescription {
+    pub kind: SceneKind,
+    pub name: String,
+    pub label: String,
+    pub parameters: Vec<SceneParameter>,
+    // 本来の描画経路で動くバックエンド（"webgpu" / "webgl" / "webgl2" / "canvas2d" / "software"）
+    pub backends: Vec<String>,
+}
+
+fn parameter(name: &str, min: f64, max: f64, default: f64, step: f64) -> SceneParameter {
+    SceneParameter {
+        name: name.to_string(),
+        label: i18n::parameter_label(name).to_string(),
+        min,
+        max,
+        default,
+        step,
+    }
+}
+
+pub(crate) fn describe(kind: SceneKind) -> SceneDescription {
+    // すべてのシーンが set_load() に対応している
+    let mut parameters = vec![parameter("load", 0.0, 1.0, 0.5, 0.01)];
+    match kind {
+        SceneKind::Life => {
+            // 既定は1ピクセル1セル（キャンバスの大きさで決まるので 0 で表す）
+            parameters.push(parameter("columns", 1.0, life::MAX_GRID_SIZE as f64, 0.0, 1.0));
+            parameters.push(parameter("rows", 1.0, life::MAX_GRID_SIZE as f64, 0.0, 1.0));
+        }
+        SceneKind::Blur => {
+            parameters.push(parameter("method", 0.0, 2.0, 0.0, 1.0));
+            parameters.push(parameter(
+                "radius",
+                0.0,
+                blur::MAX_RADIUS as f64,
+                blur::DEFAULT_RADIUS as f64,
+                1.0,
+            ));
+        }
+        SceneKind::Clip => {
+            parameters.push(parameter(
+                "clip_regions",
+                1.0,
+                clip::MAX_CLIP_REGIONS as f64,
+                clip::DEFAULT_CLIP_REGIONS as f64,
+                1.0,
+            ));
+        }
+        SceneKind::Shadows => {
+            parameters.push(parameter(
+                "shadow_blur",
+                0.0,
+                glow::MAX_SHADOW_BLUR as f64,
+                glow::DEFAULT_SHADOW_BLUR as f64,
+                1.0,
+            ));
+        }
+        SceneKind::StateChanges => {
+            parameters.push(parameter(
+                "change_every",
+                1.0,
+                state_changes::MAX_CHANGE_EVERY as f64,
+                state_changes::DEFAULT_CHANGE_EVERY as f64,
+                1.0,
+            ));
+        }
+        _ => {}
+    }
+
+    // シェーダー版は Canvas2D ではCPU版で代わりに描くので対応に数えない
+    let backends: &[&str] = match kind {
+        SceneKind::FractalGpu => &["webgl"],
+        // WebGL では状態を切り替えずに色だけで描く
+        SceneKind::StateChanges => &["canvas2d"],
+        SceneKind::Transforms | SceneKind::TransformsPrecomputed => &["canvas2d"],
+        // software はぼかし・グラデーション・クリップ・合成方法・ビューポートの切り替えを描けず、代わりの描き方になる
+        SceneKind::RadialGradients
+        | SceneKind::Shadows
+        | SceneKind::Clip
+        | SceneKind::Compositing
+        | SceneKind::Quadrants => &["webgl", "canvas2d"],
+        // 他のシーンは WebGL2 でも WebGL1 互換の API で描く
+        SceneKind::Particles => &["webgpu", "webgl", "webgl2", "canvas2d", "software"],
+        _ => &["webgl", "canvas2d", "software"],
+    };
+
+    SceneDescription {
+        kind,
+        name: kind.name().to_string(),
+        label: i18n::scene_label(kind).to_string(),
+        parameters,
+        backends: backends.iter().map(|b| b.to_string()).collect(),
+    }
+}
+
+// strict モード用: シーンがそのバックエンドの本来の経路で描けなければエラー
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn require_backend(kind: SceneKind, backend: &str) -> Result<(), String> {
+    if describe(kind).backends.iter().any(|b| b == backend) {
+        return Ok(());
+    }
+    Err(tr(Text::StrictSceneUnsupported, &[&kind.name(), &backend]))
+}
+
+// すべてのシーンの説明（SceneKind の値の順）
+#[wasm_bindgen]
+pub fn describe_scenes() -> Vec<SceneDescription> {
+    SceneKind::ALL.iter().map(|&kind| describe(kind)).collect()
+}
+
+#[wasm_bindgen]
+pub fn describe_scene(kind: SceneKind) -> SceneDescription {
+    describe(kind)
+}
@@ -0,0 +1,104 @@
+use super::{ColorRect, Scene, SceneKind, Surface};
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 1.0 のときの矩形の数（既定はその半分）
+const MAX_RECTS: usize = 8_000;
+const RECT_SIZE: f32 = 4.0;
+const PALETTE_SIZE: usize = 8;
+
+// 重ならない小さな矩形を格子に並べ、毎フレーム色を1つずつずらして描く
+// batched なら同じ色の矩形を並べて1回の fill_rects で、そうでなければ矩形ごとに fill_rects を呼ぶ
+// WebGL は呼ぶたびにプログラム・バッファ・属性を設定し直して描画命令を出し、
+// Canvas2D は矩形ごとに fillStyle を設定し直すので、2つの差が1回の描画命令のコストになる
+pub(crate) struct DrawCallScene {
+    width: f32,
+    height: f32,
+    batched: bool,
+    // 矩形の位置と、パレットの何番目の色から始めるか
+    cells: Vec<(f32, f32, usize)>,
+    palette: [[f32; 3]; PALETTE_SIZE],
+    rects: Vec<ColorRect>,
+    frame_count: u32,
+}
+
+impl DrawCallScene {
+    pub fn new(width: f32, height: f32, batched: bool) -> DrawCallScene {
+        let palette = std::array::from_fn(|i| {
+            let (r, g, b) = hsl_to_rgb(i as f32 * 360.0 / PALETTE_SIZE as f32, 0.7, 0.55);
+            [r, g, b]
+        });
+        let mut scene = DrawCallScene {
+            width,
+            height,
+            batched,
+            cells: Vec::new(),
+            palette,
+            rects: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for DrawCallScene {
+    fn kind(&self) -> SceneKind {
+        if self.batched {
+            SceneKind::DrawCallsBatched
+        } else {
+            SceneKind::DrawCalls
+        }
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.05, 0.05, 0.05]);
+        let shift = self.frame_count as usize;
+        self.rects.clear();
+        self.rects
+            .extend(self.cells.iter().map(|&(x, y, color)| ColorRect {
+                x,
+                y,
+                width: RECT_SIZE,
+                height: RECT_SIZE,
+                rgb: self.palette[(color + shift) % PALETTE_SIZE],
+            }));
+        if self.batched {
+            surface.fill_rects(&self.rects);
+        } else {
+            for rect in &self.rects {
+                surface.fill_rects(std::slice::from_ref(rect));
+            }
+        }
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 矩形の数を負荷に比例させる（格子に収まらない分は描かない）
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_RECTS as f32 * load).round() as usize;
+        let pitch = RECT_SIZE * 2.0;
+        let columns = ((self.width / pitch) as usize).max(1);
+        let rows = ((self.height / pitch) as usize).max(1);
+        self.cells = (0..count.min(columns * rows))
+            .map(|i| {
+                let (column, row) = (i % columns, i / columns);
+                (column as f32 * pitch, row as f32 * pitch, i % PALETTE_SIZE)
+            })
+            .collect();
+        if self.batched {
+            // 同じ色が続くように並べておく（重ならないので順番が変わっても同じ絵になる）
+            self.cells.sort_by_key(|&(_, _, color)| color);
+        }
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
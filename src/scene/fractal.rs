@@ -0,0 +1,224 @@
+use super::{Scene, SceneKind, Surface};
+use crate::events::EventKind;
+use crate::explosion::ExplosionConfig;
+use crate::i18n::{tr, Text};
+use crate::math;
+
+// 反復回数の範囲（負荷 0.0~1.0 に対応、既定は 128）
+const MIN_ITERATIONS: u32 = 16;
+const MAX_ITERATIONS: u32 = 240;
+const DEFAULT_ITERATIONS: u32 = 128;
+// ズームしていく先（Seahorse valley）
+const ZOOM_TARGET: (f64, f64) = (-0.743_643_887, 0.131_825_904);
+const INITIAL_SCALE: f64 = 1.5;
+const ZOOM_SPEED: f64 = 0.99;
+// f32 の GPU 版でも破綻しない深さで最初に戻す
+const MIN_SCALE: f64 = 1e-5;
+
+// Mandelbrot/Julia 集合のズーム
+// CPU版は全ピクセルをWASMで計算して転送し、GPU版は同じ式をフラグメントシェーダーで計算する
+// （GPUで計算できない描画先ではCPU版にフォールバックする）
+pub(crate) struct FractalScene {
+    gpu: bool,
+    width: usize,
+    height: usize,
+    scale: f64,
+    // Some なら Julia 集合（定数 c）
+    julia: Option<(f64, f64)>,
+    iterations: u32,
+    pixels: Vec<u8>,
+    frame_count: u32,
+}
+
+impl FractalScene {
+    pub fn new(width: f32, height: f32, gpu: bool) -> FractalScene {
+        let width = (width as usize).max(1);
+        let height = (height as usize).max(1);
+        FractalScene {
+            gpu,
+            width,
+            height,
+            scale: INITIAL_SCALE,
+            julia: None,
+            iterations: DEFAULT_ITERATIONS,
+            pixels: Vec::new(),
+            frame_count: 0,
+        }
+    }
+
+    // 画面の中心。Julia 集合は原点を中心に表示する
+    fn center(&self) -> (f64, f64) {
+        match self.julia {
+            Some(_) => (0.0, 0.0),
+            None => ZOOM_TARGET,
+        }
+    }
+
+    // ピクセル座標 → 複素平面
+    fn to_complex(&self, x: f64, y: f64) -> (f64, f64) {
+        let (cx, cy) = self.center();
+        let aspect = self.width as f64 / self.height as f64;
+        (
+            cx + (x / self.width as f64 - 0.5) * 2.0 * self.scale * aspect,
+            cy - (y / self.height as f64 - 0.5) * 2.0 * self.scale,
+        )
+    }
+
+    fn render_cpu(&mut self, surface: &mut dyn Surface) {
+        self.pixels.resize(self.width * self.height * 4, 0);
+        let mut pixels = std::mem::take(&mut self.pixels);
+        let iterations = self.iterations;
+        for (i, px) in pixels.chunks_exact_mut(4).enumerate() {
+            let (x, y) = self.to_complex((i % self.width) as f64, (i / self.width) as f64);
+            let (zx, zy, cx, cy) = match self.julia {
+                Some((cx, cy)) => (x, y, cx, cy),
+                None => (0.0, 0.0, x, y),
+            };
+            let [r, g, b] = palette(escape_time(zx, zy, cx, cy, iterations), iterations);
+            px[0] = (r * 255.0) as u8;
+            px[1] = (g * 255.0) as u8;
+            px[2] = (b * 255.0) as u8;
+            px[3] = 255;
+        }
+        surface.blit_rgba(self.width as u32, self.height as u32, &pixels);
+        self.pixels = pixels;
+    }
+}
+
+// 発散するまでの反復回数（発散しなければ max_iterations）
+fn escape_time(mut zx: f64, mut zy: f64, cx: f64, cy: f64, max_iterations: u32) -> u32 {
+    for i in 0..max_iterations {
+        let (x2, y2) = (zx * zx, zy * zy);
+        if x2 + y2 > 4.0 {
+            return i;
+        }
+        zy = 2.0 * zx * zy + cy;
+        zx = x2 - y2 + cx;
+    }
+    max_iterations
+}
+
+// 反復回数を色に変換（シェーダーと同じ式）
+fn palette(iterations: u32, max_iterations: u32) -> [f32; 3] {
+    if iterations >= max_iterations {
+        return [0.0, 0.0, 0.0];
+    }
+    let t = iterations as f32 / max_iterations as f32;
+    let tau = std::f32::consts::TAU;
+    [
+        0.5 + 0.5 * math::cos(tau * t),
+        0.5 + 0.5 * math::cos(tau * (t + 0.33)),
+        0.5 + 0.5 * math::cos(tau * (t + 0.67)),
+    ]
+}
+
+impl Scene for FractalScene {
+    fn kind(&self) -> SceneKind {
+        if self.gpu {
+            SceneKind::FractalGpu
+        } else {
+            SceneKind::Fractal
+        }
+    }
+
+    fn update(&mut self) {
+        self.scale *= ZOOM_SPEED;
+        if self.scale < MIN_SCALE {
+            self.scale = INITIAL_SCALE;
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        if self.gpu {
+            let (cx, cy) = self.center();
+            let (julia_x, julia_y) = self.julia.unwrap_or((0.0, 0.0));
+            let uniforms = [
+                ("u_center_x", cx as f32),
+                ("u_center_y", cy as f32),
+                ("u_scale", self.scale as f32),
+                ("u_aspect", (self.width as f64 / self.height as f64) as f32),
+                ("u_julia", if self.julia.is_some() { 1.0 } else { 0.0 }),
+                ("u_c_x", julia_x as f32),
+                ("u_c_y", julia_y as f32),
+                ("u_max_iterations", self.iterations as f32),
+            ];
+            if surface.fill_shader(FRACTAL_FRAGMENT_SHADER, &uniforms) {
+                return;
+            }
+            surface.report(
+                EventKind::FallbackUsed,
+                &tr(Text::FractalFallback, &[]),
+            );
+        }
+        self.render_cpu(surface);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        self.iterations =
+            MIN_ITERATIONS + ((MAX_ITERATIONS - MIN_ITERATIONS) as f32 * load).round() as u32;
+    }
+
+    // クリックした点を定数 c とする Julia 集合に切り替える（Julia 表示中なら Mandelbrot に戻す）
+    fn explode(&mut self, x: f32, y: f32, _explosion: &ExplosionConfig) {
+        self.julia = match self.julia {
+            Some(_) => None,
+            None => Some(self.to_complex(x as f64, y as f64)),
+        };
+        self.scale = INITIAL_SCALE;
+    }
+}
+
+// CPU版と同じ計算をするフラグメントシェーダー（WebGL1 はループ回数が定数である必要がある）
+const FRACTAL_FRAGMENT_SHADER: &str = r#"
+    #ifdef GL_FRAGMENT_PRECISION_HIGH
+    precision highp float;
+    #else
+    precision mediump float;
+    #endif
+    uniform float u_center_x;
+    uniform float u_center_y;
+    uniform float u_scale;
+    uniform float u_aspect;
+    uniform float u_julia;
+    uniform float u_c_x;
+    uniform float u_c_y;
+    uniform float u_max_iterations;
+    varying vec2 v_uv;
+
+    // ループ回数の上限は定数。実際の回数は u_max_iterations で打ち切る
+    const int MAX_ITERATIONS = 240;
+
+    void main() {
+        vec2 p = vec2(
+            u_center_x + (v_uv.x - 0.5) * 2.0 * u_scale * u_aspect,
+            u_center_y - (v_uv.y - 0.5) * 2.0 * u_scale
+        );
+        vec2 z = mix(vec2(0.0), p, u_julia);
+        vec2 c = mix(p, vec2(u_c_x, u_c_y), u_julia);
+
+        float escaped = u_max_iterations;
+        for (int i = 0; i < MAX_ITERATIONS; i++) {
+            if (float(i) >= u_max_iterations) {
+                break;
+            }
+            if (dot(z, z) > 4.0) {
+                escaped = float(i);
+                break;
+            }
+            z = vec2(z.x * z.x - z.y * z.y, 2.0 * z.x * z.y) + c;
+        }
+
+        if (escaped >= u_max_iterations) {
+            gl_FragColor = vec4(0.0, 0.0, 0.0, 1.0);
+        } else {
+            float t = escaped / u_max_iterations;
+            vec3 rgb = 0.5 + 0.5 * cos(6.2831853 * (t + vec3(0.0, 0.33, 0.67)));
+            gl_FragColor = vec4(rgb, 1.0);
+        }
+    }
+"#;
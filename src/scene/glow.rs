@@ -0,0 +1,136 @@
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use super::{Glow, Polygon, Scene, SceneKind, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 1.0 のときの数（既定はその半分）
+const MAX_ORBS: usize = 2_000;
+const MIN_RADIUS: f32 = 6.0;
+const MAX_RADIUS: f32 = 24.0;
+pub(crate) const DEFAULT_SHADOW_BLUR: f32 = 16.0;
+pub(crate) const MAX_SHADOW_BLUR: f32 = 64.0;
+// 代わりに描く多角形の頂点数
+const FALLBACK_SIDES: usize = 12;
+
+struct Orb {
+    // 楕円軌道の中心・半径・位相・角速度
+    cx: f32,
+    cy: f32,
+    rx: f32,
+    ry: f32,
+    phase: f32,
+    speed: f32,
+    radius: f32,
+    hue: f32,
+}
+
+// ぼんやり光る円を大量に動かす（Canvas2D でよく重いと言われる描き方のコストの計測用）
+// shadow_blur が None なら円ごとに放射グラデーション（createRadialGradient）を作って塗り、
+// Some なら単色の円を shadowBlur 付きで塗る。WebGL はどちらも同じ見た目をシェーダーで描く
+pub(crate) struct GlowScene {
+    width: f32,
+    height: f32,
+    shadow_blur: Option<f32>,
+    orbs: Vec<Orb>,
+    glows: Vec<Glow>,
+    frame_count: u32,
+}
+
+impl GlowScene {
+    pub fn new(width: f32, height: f32, shadow_blur: Option<f32>) -> GlowScene {
+        let mut scene = GlowScene {
+            width,
+            height,
+            shadow_blur: shadow_blur.map(|blur| blur.clamp(0.0, MAX_SHADOW_BLUR)),
+            orbs: Vec::new(),
+            glows: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for GlowScene {
+    fn kind(&self) -> SceneKind {
+        if self.shadow_blur.is_some() {
+            SceneKind::Shadows
+        } else {
+            SceneKind::RadialGradients
+        }
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.02, 0.02, 0.05]);
+        let t = self.frame_count as f32;
+        self.glows.clear();
+        self.glows.extend(self.orbs.iter().map(|orb| {
+            let angle = orb.phase + orb.speed * t;
+            let (r, g, b) = hsl_to_rgb(orb.hue, 0.9, 0.6);
+            Glow {
+                x: orb.cx + math::cos(angle) * orb.rx,
+                y: orb.cy + math::sin(angle) * orb.ry,
+                radius: orb.radius,
+                rgb: [r, g, b],
+            }
+        }));
+        if surface.fill_glows(&self.glows, self.shadow_blur) {
+            return;
+        }
+        // ぼかしもグラデーションもなしの単色の多角形で描く
+        surface.report(EventKind::FallbackUsed, &tr(Text::GlowFallback, &[]));
+        let polygons: Vec<Polygon> = self
+            .glows
+            .iter()
+            .map(|glow| Polygon {
+                points: (0..FALLBACK_SIDES)
+                    .map(|i| {
+                        let angle = i as f32 / FALLBACK_SIDES as f32 * TAU;
+                        (
+                            glow.x + math::cos(angle) * glow.radius,
+                            glow.y + math::sin(angle) * glow.radius,
+                        )
+                    })
+                    .collect(),
+                rgb: glow.rgb,
+            })
+            .collect();
+        surface.fill_polygons(&polygons);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 円の数を負荷に比例させる（足りない分だけ足し、多い分は末尾から減らす）
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_ORBS as f32 * load).round() as usize;
+        let mut rng = crate::rng::rng();
+        while self.orbs.len() < count {
+            self.orbs.push(Orb {
+                cx: rng.gen::<f32>() * self.width,
+                cy: rng.gen::<f32>() * self.height,
+                rx: rng.gen::<f32>() * self.width * 0.2,
+                ry: rng.gen::<f32>() * self.height * 0.2,
+                phase: rng.gen::<f32>() * TAU,
+                speed: (rng.gen::<f32>() - 0.5) * 0.04,
+                radius: MIN_RADIUS + rng.gen::<f32>() * (MAX_RADIUS - MIN_RADIUS),
+                hue: rng.gen::<f32>() * 360.0,
+            });
+        }
+        self.orbs.truncate(count);
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
@@ -0,0 +1,214 @@
+use rand::Rng;
+
+use super::{Scene, SceneKind, Surface};
+use crate::explosion::ExplosionConfig;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::memory;
+
+const ALIVE_RGBA: [u8; 4] = [120, 255, 140, 255];
+const DEAD_RGBA: [u8; 4] = [8, 12, 16, 255];
+// 初期状態で生きているセルの割合
+const INITIAL_DENSITY: f64 = 0.25;
+// 縦横それぞれのセル数の上限（wasm32 の usize でもピクセルのバイト数が溢れない大きさ）
+pub(crate) const MAX_GRID_SIZE: usize = 8192;
+
+// ライフゲーム（B3/S23）の巨大グリッド
+// 1行を u64 のワード列にビットで詰め、64セルずつビット演算で次世代を求める
+// 数百万セルの更新と、同じ数のピクセルのテクスチャ転送を測る
+pub(crate) struct LifeScene {
+    columns: usize,
+    rows: usize,
+    // 1行あたりのワード数
+    words: usize,
+    // 最終ワードの使わないビットを落とすマスク
+    last_mask: u64,
+    cells: Vec<u64>,
+    next: Vec<u64>,
+    pixels: Vec<u8>,
+    canvas_width: f32,
+    canvas_height: f32,
+    // 負荷 0.5 に相当するグリッドの大きさ（生成時に指定された大きさ）
+    base_size: (usize, usize),
+    frame_count: u32,
+}
+
+impl LifeScene {
+    pub fn new(
+        columns: usize,
+        rows: usize,
+        canvas_width: f32,
+        canvas_height: f32,
+    ) -> Result<LifeScene, String> {
+        if columns == 0 || rows == 0 {
+            return Err(tr(Text::LifeGridEmpty, &[]));
+        }
+        let too_large = || tr(Text::LifeGridTooLarge, &[&MAX_GRID_SIZE, &columns, &rows]);
+        if columns > MAX_GRID_SIZE || rows > MAX_GRID_SIZE {
+            return Err(too_large());
+        }
+        let words = columns.div_ceil(64);
+        let last_mask = match columns % 64 {
+            0 => u64::MAX,
+            bits => (1u64 << bits) - 1,
+        };
+        let cell_words = words.checked_mul(rows).ok_or_else(too_large)?;
+        let pixel_bytes = columns
+            .checked_mul(rows)
+            .and_then(|cells| cells.checked_mul(4))
+            .ok_or_else(too_large)?;
+
+        let mut cells = memory::try_vec(cell_words)?;
+        cells.resize(cell_words, 0);
+        let mut next = memory::try_vec(cell_words)?;
+        next.resize(cell_words, 0);
+        let mut pixels = memory::try_vec(pixel_bytes)?;
+        pixels.resize(pixel_bytes, 0);
+
+        let mut scene = LifeScene {
+            columns,
+            rows,
+            words,
+            last_mask,
+            cells,
+            next,
+            pixels,
+            canvas_width,
+            canvas_height,
+            base_size: (columns, rows),
+            frame_count: 0,
+        };
+        scene.randomize();
+        Ok(scene)
+    }
+
+    fn randomize(&mut self) {
+        let mut rng = crate::rng::rng();
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            let mut bits = 0u64;
+            for bit in 0..64 {
+                if rng.gen_bool(INITIAL_DENSITY) {
+                    bits |= 1 << bit;
+                }
+            }
+            *cell = if i % self.words == self.words - 1 {
+                bits & self.last_mask
+            } else {
+                bits
+            };
+        }
+    }
+}
+
+// 行 row の i 番目のワードについて (左隣, 自分, 右隣) のセルを同じビット位置に揃える
+// ビット b はワード内の列 b に対応する（グリッドの端の外は死んだセル扱い）
+fn shifted(row: &[u64], i: usize) -> (u64, u64, u64) {
+    let word = row[i];
+    let prev = if i > 0 { row[i - 1] } else { 0 };
+    let next = row.get(i + 1).copied().unwrap_or(0);
+    ((word << 1) | (prev >> 63), word, (word >> 1) | (next << 63))
+}
+
+// 近傍数をビットごとの3ビットカウンタ (s0, s1, s2) に加算する（8は0に回り込むが結果は変わらない）
+fn add(counter: &mut (u64, u64, u64), x: u64) {
+    let carry0 = counter.0 & x;
+    counter.0 ^= x;
+    let carry1 = counter.1 & carry0;
+    counter.1 ^= carry0;
+    counter.2 ^= carry1;
+}
+
+impl Scene for LifeScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Life
+    }
+
+    fn update(&mut self) {
+        let words = self.words;
+        let empty = vec![0u64; words];
+        let row = |y: usize| &self.cells[y * words..(y + 1) * words];
+        for y in 0..self.rows {
+            let above = if y > 0 { row(y - 1) } else { &empty };
+            let current = row(y);
+            let below = if y + 1 < self.rows {
+                row(y + 1)
+            } else {
+                &empty
+            };
+
+            for i in 0..words {
+                let (al, a, ar) = shifted(above, i);
+                let (cl, alive, cr) = shifted(current, i);
+                let (bl, b, br) = shifted(below, i);
+
+                let mut counter = (0, 0, 0);
+                for x in [al, a, ar, cl, cr, bl, b, br] {
+                    add(&mut counter, x);
+                }
+                let (s0, s1, s2) = counter;
+                // 近傍3なら誕生、生存中で近傍2なら維持
+                let mut bits = s1 & !s2 & (s0 | alive);
+                if i == words - 1 {
+                    bits &= self.last_mask;
+                }
+                self.next[y * words + i] = bits;
+            }
+        }
+        std::mem::swap(&mut self.cells, &mut self.next);
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for y in 0..self.rows {
+            let row = &self.cells[y * self.words..(y + 1) * self.words];
+            let pixels = &mut self.pixels[y * self.columns * 4..(y + 1) * self.columns * 4];
+            for (x, px) in pixels.chunks_exact_mut(4).enumerate() {
+                let alive = row[x / 64] >> (x % 64) & 1 == 1;
+                px.copy_from_slice(if alive { &ALIVE_RGBA } else { &DEAD_RGBA });
+            }
+        }
+        surface.blit_rgba(self.columns as u32, self.rows as u32, &self.pixels);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // セル数が負荷に比例するように縦横を拡縮する（作り直すので状態は初期化される）
+    fn set_load(&mut self, load: f32) {
+        let scale = math::sqrt(2.0 * load);
+        let columns = ((self.base_size.0 as f32 * scale).round() as usize).clamp(1, MAX_GRID_SIZE);
+        let rows = ((self.base_size.1 as f32 * scale).round() as usize).clamp(1, MAX_GRID_SIZE);
+        if (columns, rows) == (self.columns, self.rows) {
+            return;
+        }
+        // 確保できなければ今のグリッドのまま続ける
+        if let Ok(mut scene) = LifeScene::new(columns, rows, self.canvas_width, self.canvas_height)
+        {
+            scene.base_size = self.base_size;
+            *self = scene;
+        }
+    }
+
+    fn reset(&mut self) -> bool {
+        self.randomize();
+        self.frame_count = 0;
+        true
+    }
+
+    // クリック位置の周りのセルをランダムに生き返らせる
+    fn explode(&mut self, x: f32, y: f32, _explosion: &ExplosionConfig) {
+        let cx = (x / self.canvas_width * self.columns as f32) as isize;
+        let cy = (y / self.canvas_height * self.rows as f32) as isize;
+        let radius = (self.columns.max(self.rows) / 50).max(4) as isize;
+        let mut rng = crate::rng::rng();
+        for gy in (cy - radius).max(0)..(cy + radius).min(self.rows as isize) {
+            for gx in (cx - radius).max(0)..(cx + radius).min(self.columns as isize) {
+                if rng.gen_bool(0.5) {
+                    let (gx, gy) = (gx as usize, gy as usize);
+                    self.cells[gy * self.words + gx / 64] |= 1 << (gx % 64);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,594 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use wasm_bindgen::prelude::*;
+
+use crate::capture::DebugBuffer;
+use crate::events::EventKind;
+use crate::explosion::ExplosionConfig;
+#[cfg(target_arch = "wasm32")]
+use crate::image_source::DecodedImage;
+use crate::math;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::viewport::Viewport;
+
+mod bezier;
+mod blur;
+mod clip;
+mod clear;
+mod compositing;
+mod density;
+mod description;
+mod draw_calls;
+mod fractal;
+mod glow;
+mod life;
+mod overdraw;
+mod physarum;
+mod point_size;
+mod polygons;
+mod quadrants;
+mod raycaster;
+mod readback;
+mod reaction_diffusion;
+mod skeleton;
+mod state_changes;
+mod texture_upload;
+mod transforms;
+mod tilemap;
+mod vector;
+
+pub use blur::BlurMethod;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use point_size::sweep_sizes;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use readback::{readback_size, READBACK_SCALES};
+#[cfg(target_arch = "wasm32")]
+pub(crate) use texture_upload::UPLOAD_SCALES;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use description::require_backend;
+pub use description::{describe_scene, describe_scenes, SceneDescription, SceneParameter};
+
+// 切り替え可能なベンチマークシーン
+// Particles はバックエンドごとに最適化された既存の描画経路を使い、
+// それ以外のシーンは Scene トレイト経由で Surface に描く
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SceneKind {
+    Particles = 0,
+    // 画面クリアのみ（フレームごとの固定コストの基準値）
+    Clear = 1,
+    // 密度バッファへの蓄積と減衰（テクスチャ転送）
+    Density = 2,
+    // 粘菌エージェント（感知・移動・堆積・拡散）
+    Physarum = 3,
+    // Gray-Scott 反応拡散グリッド（ステンシル計算）
+    ReactionDiffusion = 4,
+    // ビット詰めのライフゲーム（既定は1ピクセル1セル）
+    Life = 5,
+    // Mandelbrot/Julia をWASMで1ピクセルずつ計算
+    Fractal = 6,
+    // 同じフラクタルをフラグメントシェーダーで計算（Canvas2DではCPU版になる）
+    FractalGpu = 7,
+    // 画像を毎フレームぼかす（WASM / Canvas2D filter / シェーダーの比較）
+    Blur = 8,
+    // タイルマップのレイキャスト（列ごとの縦の帯）
+    Raycaster = 9,
+    // 2ボーンのスプライトアニメーション（階層変換 + インスタンス描画）
+    Skeleton = 10,
+    // 無限スクロールのタイルマップ（アトラスからのタイル描画とカリング）
+    Tilemap = 11,
+    // 揺れる3次ベジェ曲線（WASMでの分割 vs bezierCurveTo）
+    Bezier = 12,
+    // 変形する凹多角形の塗りつぶし（WASMの耳切り分割 vs ネイティブ fill）
+    Polygons = 13,
+    // 止まった点の大きさを 1px から上限まで変えて描く（フィルレートの計測）
+    PointSize = 14,
+    // 加算合成の点を狭い範囲に集めて何重にも塗る（重ね塗りの負荷）
+    Overdraw = 15,
+    // Overdraw と同じ点を画面全体に散らしたもの（重ね塗りなしの比較用）
+    OverdrawSpread = 16,
+    // 小さな矩形を1つずつ別の描画命令で描く（描画命令のコスト）
+    DrawCalls = 17,
+    // DrawCalls と同じ矩形を1回の描画命令でまとめて描く（比較用）
+    DrawCallsBatched = 18,
+    // 画像を毎フレーム送り直して描く。大きさをキャンバスの 1/8 から同じ大きさまで変える（転送帯域）
+    TextureUpload = 19,
+    // 描いた直後に画面中央を読み戻す。範囲を 1px からキャンバス全体まで変える（読み戻しのコスト）
+    Readback = 20,
+    // 小さな矩形を描くたびに色・アルファ・変形・クリップを切り替える（Canvas2D の状態変更のコスト）
+    StateChanges = 21,
+    // 回転・拡大するスプライトを1個ごとに save / translate / rotate / restore して描く（Canvas2D の変換APIのコスト）
+    Transforms = 22,
+    // Transforms と同じ絵を、WASM で計算した行列の setTransform だけで描く（比較用）
+    TransformsPrecomputed = 23,
+    // 光る円を1つずつ放射グラデーションで塗る（WebGL はシェーダーで同じ見た目）
+    RadialGradients = 24,
+    // 単色の円に shadowBlur の影を付けて塗る（Canvas2D で特に重い描き方）
+    Shadows = 25,
+    // 動く星形の領域でクリップしてパーティクルを描く（Canvas2D は clip()、WebGL はステンシルバッファ）
+    Clip = 26,
+    // 止まった半透明の矩形を何層も重ね、合成方法（globalCompositeOperation / ブレンド式）を順に変えて描く
+    Compositing = 27,
+    // 文字の輪郭のような閉じたベジェ曲線のパスを毎フレーム変形し、折れ線に分解して塗り、輪郭も線で描く
+    Vector = 28,
+    // 同じパーティクルを4分割した領域に合成方法を変えて描く（ビューポートとシザーの切り替えのコスト）
+    Quadrants = 29,
+}
+
+impl SceneKind {
+    pub const ALL: [SceneKind; 30] = [
+        SceneKind::Particles,
+        SceneKind::Clear,
+        SceneKind::Density,
+        SceneKind::Physarum,
+        SceneKind::ReactionDiffusion,
+        SceneKind::Life,
+        SceneKind::Fractal,
+        SceneKind::FractalGpu,
+        SceneKind::Blur,
+        SceneKind::Raycaster,
+        SceneKind::Skeleton,
+        SceneKind::Tilemap,
+        SceneKind::Bezier,
+        SceneKind::Polygons,
+        SceneKind::PointSize,
+        SceneKind::Overdraw,
+        SceneKind::OverdrawSpread,
+        SceneKind::DrawCalls,
+        SceneKind::DrawCallsBatched,
+        SceneKind::TextureUpload,
+        SceneKind::Readback,
+        SceneKind::StateChanges,
+        SceneKind::Transforms,
+        SceneKind::TransformsPrecomputed,
+        SceneKind::RadialGradients,
+        SceneKind::Shadows,
+        SceneKind::Clip,
+        SceneKind::Compositing,
+        SceneKind::Vector,
+        SceneKind::Quadrants,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SceneKind::Particles => "particles",
+            SceneKind::Clear => "clear",
+            SceneKind::Density => "density",
+            SceneKind::Physarum => "physarum",
+            SceneKind::ReactionDiffusion => "reaction-diffusion",
+            SceneKind::Life => "life",
+            SceneKind::Fractal => "fractal",
+            SceneKind::FractalGpu => "fractal-gpu",
+            SceneKind::Blur => "blur",
+            SceneKind::Raycaster => "raycaster",
+            SceneKind::Skeleton => "skeleton",
+            SceneKind::Tilemap => "tilemap",
+            SceneKind::Bezier => "bezier",
+            SceneKind::Polygons => "polygons",
+            SceneKind::PointSize => "point-size",
+            SceneKind::Overdraw => "overdraw",
+            SceneKind::OverdrawSpread => "overdraw-spread",
+            SceneKind::DrawCalls => "draw-calls",
+            SceneKind::DrawCallsBatched => "draw-calls-batched",
+            SceneKind::TextureUpload => "texture-upload",
+            SceneKind::Readback => "readback",
+            SceneKind::StateChanges => "state-changes",
+            SceneKind::Transforms => "transforms",
+            SceneKind::TransformsPrecomputed => "transforms-precomputed",
+            SceneKind::RadialGradients => "radial-gradients",
+            SceneKind::Shadows => "shadows",
+            SceneKind::Clip => "clip",
+            SceneKind::Compositing => "compositing",
+            SceneKind::Vector => "vector",
+            SceneKind::Quadrants => "quadrants",
+        }
+    }
+
+    // name() の逆
+    pub(crate) fn from_name(name: &str) -> Option<SceneKind> {
+        SceneKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+// 単色の矩形（キャンバスのピクセル座標）
+#[derive(Clone, Copy, Debug)]
+pub struct ColorRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub rgb: [f32; 3],
+}
+
+// fill_rects_styled で矩形ごとに指定する描画状態
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DrawState {
+    pub rgb: [f32; 3],
+    pub alpha: f32,
+    // キャンバスの中心を軸にした回転（ラジアン）
+    pub rotation: f32,
+    // Some なら [x, y, 幅, 高さ] の中だけに描く（回転の前のキャンバスの座標）
+    pub clip: Option<[f32; 4]>,
+}
+
+// 描画状態つきの矩形
+#[derive(Clone, Copy, Debug)]
+pub struct StyledRect {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub state: DrawState,
+}
+
+// スプライト用のテクスチャアトラス
+// 描画先は version が変わったときだけ転送し直す
+pub struct Atlas {
+    pub version: u32,
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+    // Some なら pixels の代わりにこの画像（同じ絵）を描画先に渡す
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) image: Option<DecodedImage>,
+}
+
+static NEXT_ATLAS_VERSION: AtomicU32 = AtomicU32::new(1);
+
+impl Atlas {
+    // pixels は width × height のRGBA
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Atlas {
+        Atlas {
+            version: NEXT_ATLAS_VERSION.fetch_add(1, Ordering::Relaxed),
+            width,
+            height,
+            pixels,
+            #[cfg(target_arch = "wasm32")]
+            image: None,
+        }
+    }
+
+    // 描画先に渡す画像を差し替える（同じ画像でもバージョンが変わるので、次の描画で転送し直す）
+    #[cfg(target_arch = "wasm32")]
+    pub(crate) fn set_image(&mut self, image: Option<DecodedImage>) {
+        self.image = image;
+        self.version = NEXT_ATLAS_VERSION.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// アトラスの一部をアフィン変換して描くスプライト
+#[derive(Clone, Copy, Debug)]
+pub struct Sprite {
+    // 単位正方形 (0,0)-(1,1) をキャンバスのピクセル座標に写す変換
+    // [a, b, c, d, e, f] の並びは Canvas2D の transform() と同じ
+    pub transform: [f32; 6],
+    // アトラス上の切り出し範囲 [x, y, width, height]（ピクセル）
+    pub source: [f32; 4],
+}
+
+// 中心・回転・拡大率で置くスプライト（draw_sprites_placed で描く）
+#[derive(Clone, Copy, Debug)]
+pub struct PlacedSprite {
+    // 中心のキャンバスのピクセル座標
+    pub x: f32,
+    pub y: f32,
+    // ラジアン
+    pub rotation: f32,
+    pub scale: f32,
+    // 拡大する前の一辺(px)
+    pub size: f32,
+    // アトラス上の切り出し範囲 [x, y, width, height]（ピクセル）
+    pub source: [f32; 4],
+}
+
+impl PlacedSprite {
+    // 同じ置き方の Sprite の変換（単位正方形を中心に合わせてから回転・拡大する）
+    pub fn transform(&self) -> [f32; 6] {
+        let (sin, cos) = (math::sin(self.rotation), math::cos(self.rotation));
+        let side = self.size * self.scale;
+        let half = side / 2.0;
+        [
+            cos * side,
+            sin * side,
+            -sin * side,
+            cos * side,
+            self.x - cos * half + sin * half,
+            self.y - sin * half - cos * half,
+        ]
+    }
+}
+
+// ぼんやり光る円（中心と半径はキャンバスのピクセル座標）
+#[derive(Clone, Copy, Debug)]
+pub struct Glow {
+    pub x: f32,
+    pub y: f32,
+    pub radius: f32,
+    pub rgb: [f32; 3],
+}
+
+// 3次ベジェ曲線（始点・制御点2つ・終点、キャンバスのピクセル座標）
+#[derive(Clone, Copy, Debug)]
+pub struct CubicBezier {
+    pub points: [(f32, f32); 4],
+    pub rgb: [f32; 3],
+}
+
+// 大きさをそろえて描く正方形の点（中心のキャンバスのピクセル座標）
+#[derive(Clone, Copy, Debug)]
+pub struct ColorPoint {
+    pub x: f32,
+    pub y: f32,
+    pub rgb: [f32; 3],
+}
+
+// 塗りつぶす単純多角形（穴なし、キャンバスのピクセル座標）
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    pub points: Vec<(f32, f32)>,
+    pub rgb: [f32; 3],
+}
+
+// バックエンドが提供する描画先
+// 座標はすべてキャンバスのピクセル座標（左上原点）
+pub trait Surface {
+    fn size(&self) -> (f32, f32);
+    fn clear(&mut self, rgb: [f32; 3]);
+    // RGBAのピクセル列（width × height、1行目が上端）を描画先全体に引き伸ばして描く
+    fn blit_rgba(&mut self, width: u32, height: u32, pixels: &[u8]);
+    fn fill_rects(&mut self, rects: &[ColorRect]);
+    fn draw_sprites(&mut self, atlas: &Atlas, sprites: &[Sprite]);
+    // 太さ1pxで描く
+    fn stroke_beziers(&mut self, curves: &[CubicBezier]);
+    fn fill_polygons(&mut self, polygons: &[Polygon]);
+    // 一辺 size px の正方形の点を mode で重ねて描く
+    fn draw_points(&mut self, points: &[ColorPoint], size: f32, mode: RenderMode);
+    // (x, y) から width × height の範囲をRGBA（1行目が上端）で out に読み戻す
+    // 範囲は描画先の中に丸める。読み戻せなければ false
+    fn read_rgba(&mut self, x: u32, y: u32, width: u32, height: u32, out: &mut Vec<u8>) -> bool;
+
+    // 矩形ごとの状態（色・アルファ・回転・クリップ）で描く。状態は前の矩形と違うときだけ設定し直す
+    // 状態を切り替えながら描けない描画先は何もせず false を返す
+    fn fill_rects_styled(&mut self, _rects: &[StyledRect]) -> bool {
+        false
+    }
+
+    // 中心・回転・拡大率でスプライトを描く（Canvas2D の変換APIのコストの計測用）
+    // precomputed でなければ1個ごとに save / translate / rotate / scale / restore し、
+    // precomputed なら計算した行列を setTransform で設定する。変換APIのない描画先は何もせず false を返す
+    fn draw_sprites_placed(&mut self, _atlas: &Atlas, _sprites: &[PlacedSprite], _precomputed: bool) -> bool {
+        false
+    }
+
+    // shadow_blur が None なら中心から縁へ透明になる放射グラデーションで、
+    // Some なら単色の円にその大きさ(px)の影のぼかしを付けて描く（対応しなければ何もせず false）
+    fn fill_glows(&mut self, _glows: &[Glow], _shadow_blur: Option<f32>) -> bool {
+        false
+    }
+
+    // 矩形を alpha の不透明度で mode の合成方法で重ねて描く（合成方法のコストの計測用）
+    // その合成方法を使えない描画先は何もせず false を返す
+    fn fill_rects_composited(&mut self, _rects: &[ColorRect], _alpha: f32, _mode: CompositeMode) -> bool {
+        false
+    }
+
+    // 以後の描画を regions（重なりは和）の中だけに制限する。pop_clip() で元に戻す
+    // クリップできない描画先は何もせず false を返す（そのときは pop_clip() を呼ばない）
+    fn push_clip(&mut self, _regions: &[Polygon]) -> bool {
+        false
+    }
+
+    fn pop_clip(&mut self) {}
+
+    // 以後の描画を region の中に縮めて描き、はみ出した分は切る（座標はキャンバス全体のまま渡す）
+    // WebGL はビューポートとシザー、Canvas2D はクリップと変換で切り替える。pop_viewport() で元に戻す
+    // 切り替えられない描画先は何もせず false を返す（そのときは pop_viewport() を呼ばない）
+    fn push_viewport(&mut self, _region: Viewport) -> bool {
+        false
+    }
+
+    fn pop_viewport(&mut self) {}
+
+    // draw_points で描ける一番大きい点(px)。上限がなければ無限大
+    fn max_point_size(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    // 描画先全体をフラグメントシェーダーで塗る（v_uv は左上原点の 0~1）
+    // シェーダーを実行できない描画先は何もせず false を返す
+    fn fill_shader(&mut self, _fragment_source: &str, _uniforms: &[(&str, f32)]) -> bool {
+        false
+    }
+
+    // blit_rgba と同じだが Canvas2D の filter でぼかして描く（対応しなければ false）
+    fn blit_rgba_filtered(&mut self, _width: u32, _height: u32, _pixels: &[u8], _radius: f32) -> bool {
+        false
+    }
+
+    // blit_rgba と同じだがシェーダーでぼかして描く（対応しなければ false）
+    fn blit_rgba_shader_blur(&mut self, _width: u32, _height: u32, _pixels: &[u8], _radius: u32) -> bool {
+        false
+    }
+
+    // 代替経路で描いたことなどをバックエンドのイベントとしてJSに伝える
+    fn report(&mut self, _kind: EventKind, _message: &str) {}
+}
+
+pub trait Scene {
+    fn kind(&self) -> SceneKind;
+    fn update(&mut self);
+    fn render(&mut self, surface: &mut dyn Surface);
+    fn frame_count(&self) -> u32;
+
+    // 名前・調整できるパラメーター・対応バックエンド
+    fn describe(&self) -> SceneDescription {
+        description::describe(self.kind())
+    }
+
+    // 負荷 0.0~1.0 をシーンごとの自然なスケール軸（数・グリッドの大きさ・反復回数など）に割り当てる
+    // 既定の状態はおおむね 0.5 に相当する
+    fn set_load(&mut self, load: f32);
+
+    // クリック操作（対応しないシーンは無視する）
+    // explosion はパーティクルを吹き飛ばすシーンだけが使う
+    fn explode(&mut self, _x: f32, _y: f32, _explosion: &ExplosionConfig) {}
+
+    // その場で初期状態に戻せるシーンは true を返す（false ならバックエンドが作り直す）
+    fn reset(&mut self) -> bool {
+        false
+    }
+
+    // デバッグ用に書き出せる途中のバッファ（capture_debug_buffers() で画像になる）
+    fn debug_buffers(&self) -> Vec<DebugBuffer<'_>> {
+        Vec::new()
+    }
+
+    // アトラスからスプライトを描くシーンはそのアトラス（画像の渡し方を切り替えるのに使う）
+    fn atlas_mut(&mut self) -> Option<&mut Atlas> {
+        None
+    }
+}
+
+// シーンを生成する。Particles はバックエンドが直接扱うので None
+pub(crate) fn create_scene(
+    kind: SceneKind,
+    width: f32,
+    height: f32,
+) -> Result<Option<Box<dyn Scene>>, String> {
+    let scene: Box<dyn Scene> = match kind {
+        SceneKind::Particles => return Ok(None),
+        SceneKind::Clear => Box::new(clear::ClearScene::new(width, height)),
+        SceneKind::Density => Box::new(density::DensityScene::new(width, height)?),
+        SceneKind::Physarum => Box::new(physarum::PhysarumScene::new(width, height)),
+        SceneKind::ReactionDiffusion => Box::new(
+            reaction_diffusion::ReactionDiffusionScene::new(width, height)?,
+        ),
+        // 1ピクセル1セル（上限より大きいキャンバスでは縮小表示）
+        SceneKind::Life => Box::new(life::LifeScene::new(
+            (width as usize).min(life::MAX_GRID_SIZE),
+            (height as usize).min(life::MAX_GRID_SIZE),
+            width,
+            height,
+        )?),
+        SceneKind::Fractal => Box::new(fractal::FractalScene::new(width, height, false)),
+        SceneKind::FractalGpu => Box::new(fractal::FractalScene::new(width, height, true)),
+        SceneKind::Blur => Box::new(blur::BlurScene::with_test_pattern(width, height)),
+        SceneKind::Raycaster => Box::new(raycaster::RaycasterScene::new(width, height)),
+        SceneKind::Skeleton => Box::new(skeleton::SkeletonScene::new(width, height)),
+        SceneKind::Tilemap => Box::new(tilemap::TilemapScene::new(width, height)),
+        SceneKind::Bezier => Box::new(bezier::BezierScene::new(width, height)),
+        SceneKind::Polygons => Box::new(polygons::PolygonScene::new(width, height)),
+        SceneKind::PointSize => Box::new(point_size::PointSizeScene::new(
+            width,
+            height,
+            point_size::DEFAULT_POINTS,
+            None,
+        )),
+        SceneKind::Overdraw => Box::new(overdraw::OverdrawScene::new(width, height, true)),
+        SceneKind::OverdrawSpread => {
+            Box::new(overdraw::OverdrawScene::new(width, height, false))
+        }
+        SceneKind::DrawCalls => Box::new(draw_calls::DrawCallScene::new(width, height, false)),
+        SceneKind::DrawCallsBatched => {
+            Box::new(draw_calls::DrawCallScene::new(width, height, true))
+        }
+        SceneKind::TextureUpload => {
+            Box::new(texture_upload::TextureUploadScene::new(width, height, None))
+        }
+        SceneKind::Readback => Box::new(readback::ReadbackScene::new(width, height, None)),
+        SceneKind::StateChanges => Box::new(state_changes::StateChangeScene::new(
+            width,
+            height,
+            state_changes::DEFAULT_CHANGE_EVERY,
+        )),
+        SceneKind::Transforms => Box::new(transforms::TransformScene::new(width, height, false)),
+        SceneKind::TransformsPrecomputed => {
+            Box::new(transforms::TransformScene::new(width, height, true))
+        }
+        SceneKind::RadialGradients => Box::new(glow::GlowScene::new(width, height, None)),
+        SceneKind::Clip => Box::new(clip::ClipScene::new(
+            width,
+            height,
+            clip::DEFAULT_CLIP_REGIONS,
+        )),
+        SceneKind::Shadows => Box::new(glow::GlowScene::new(
+            width,
+            height,
+            Some(glow::DEFAULT_SHADOW_BLUR),
+        )),
+        SceneKind::Compositing => Box::new(compositing::CompositeScene::new(width, height, None)),
+        SceneKind::Vector => Box::new(vector::VectorScene::new(width, height)),
+        SceneKind::Quadrants => Box::new(quadrants::QuadrantScene::new(width, height)),
+    };
+    Ok(Some(scene))
+}
+
+// セル数を指定してライフゲームのシーンを作る（キャンバスより大きいグリッドは縮小表示）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_life_scene(
+    columns: usize,
+    rows: usize,
+    width: f32,
+    height: f32,
+) -> Result<Box<dyn Scene>, String> {
+    Ok(Box::new(life::LifeScene::new(columns, rows, width, height)?))
+}
+
+// 任意の画像（width × height のRGBA）をぼかすシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_blur_scene(
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+    method: BlurMethod,
+    radius: u32,
+) -> Result<Box<dyn Scene>, String> {
+    Ok(Box::new(blur::BlurScene::new(width, height, rgba, method, radius)?))
+}
+
+// 点の数（負荷 0.5 のとき）と大きさを決めて点を描くシーンを作る（描画先の上限で丸める）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_point_size_scene(
+    count: usize,
+    size: f32,
+    width: f32,
+    height: f32,
+) -> Box<dyn Scene> {
+    Box::new(point_size::PointSizeScene::new(width, height, count, Some(size)))
+}
+
+// キャンバスの一辺に対する割合を決めて画像を送り直すシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_texture_upload_scene(scale: f32, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(texture_upload::TextureUploadScene::new(width, height, Some(scale)))
+}
+
+// クリップする領域の数を決めてクリップのシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_clip_scene(regions: u32, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(clip::ClipScene::new(width, height, regions))
+}
+
+// 合成方法を1つに決めて重ねた矩形のシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_composite_scene(mode: CompositeMode, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(compositing::CompositeScene::new(width, height, Some(mode)))
+}
+
+// 影のぼかしの大きさ(px)を決めて光る円のシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_shadow_scene(shadow_blur: f32, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(glow::GlowScene::new(width, height, Some(shadow_blur)))
+}
+
+// change_every 個の矩形ごとに描画状態を切り替えるシーンを作る（1 で毎回）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_state_change_scene(change_every: u32, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(state_changes::StateChangeScene::new(width, height, change_every))
+}
+
+// キャンバスの一辺に対する割合を決めて読み戻すシーンを作る
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn create_readback_scene(scale: f32, width: f32, height: f32) -> Box<dyn Scene> {
+    Box::new(readback::ReadbackScene::new(width, height, Some(scale)))
+}
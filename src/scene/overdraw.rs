@@ -0,0 +1,97 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{ColorPoint, Scene, SceneKind, Surface};
+use crate::math;
+use crate::render_mode::RenderMode;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 0.5 のときの点の数
+const DEFAULT_POINTS: usize = 10_000;
+const POINT_SIZE: f32 = 8.0;
+// 集めるときの円の半径（画面の短い辺に対する割合）
+const CLUSTER_RADIUS: f32 = 0.05;
+// 加算で白く飛ばないように暗めの色にする
+const BRIGHTNESS: f32 = 0.15;
+
+// 加算合成の点を描く。concentrated なら中央の小さな円に集めて同じピクセルを何重にも塗り、
+// そうでなければ同じ点を画面全体に散らす
+// 数・大きさ・色・合成方法は同じなので、2つの差が重ね塗りへの弱さになる
+pub(crate) struct OverdrawScene {
+    width: f32,
+    height: f32,
+    concentrated: bool,
+    points: Vec<ColorPoint>,
+    frame_count: u32,
+}
+
+impl OverdrawScene {
+    pub fn new(width: f32, height: f32, concentrated: bool) -> OverdrawScene {
+        let mut scene = OverdrawScene {
+            width,
+            height,
+            concentrated,
+            points: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for OverdrawScene {
+    fn kind(&self) -> SceneKind {
+        if self.concentrated {
+            SceneKind::Overdraw
+        } else {
+            SceneKind::OverdrawSpread
+        }
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.0, 0.0, 0.0]);
+        surface.draw_points(&self.points, POINT_SIZE, RenderMode::Additive);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 点の数を負荷に比例させる（どちらのシーンでも同じ乱数の順で作る）
+    fn set_load(&mut self, load: f32) {
+        let count = ((DEFAULT_POINTS as f32 * 2.0 * load).round() as usize).max(1);
+        let radius = self.width.min(self.height) * CLUSTER_RADIUS;
+        let mut rng = crate::rng::rng();
+        self.points = (0..count)
+            .map(|_| {
+                let (u, v, hue) = (rng.gen::<f32>(), rng.gen::<f32>(), rng.gen::<f32>());
+                let (x, y) = if self.concentrated {
+                    // 円の中に一様に
+                    let r = radius * math::sqrt(u);
+                    let angle = v * 2.0 * PI;
+                    (
+                        self.width / 2.0 + r * math::cos(angle),
+                        self.height / 2.0 + r * math::sin(angle),
+                    )
+                } else {
+                    (u * self.width, v * self.height)
+                };
+                let (r, g, b) = hsl_to_rgb(hue * 360.0, 1.0, 0.5);
+                ColorPoint {
+                    x,
+                    y,
+                    rgb: [r * BRIGHTNESS, g * BRIGHTNESS, b * BRIGHTNESS],
+                }
+            })
+            .collect();
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
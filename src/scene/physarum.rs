@@ -0,0 +1,209 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{Scene, SceneKind, Surface};
+use crate::capture::{DebugBuffer, DebugPixels};
+use crate::explosion::ExplosionConfig;
+use crate::math;
+
+// 負荷 1.0 のときのエージェント数（既定はその半分）
+const MAX_AGENTS: usize = 100_000;
+const CELL_SIZE: f32 = 2.0;
+// 感知センサーの角度と距離（セル単位）
+const SENSOR_ANGLE: f32 = PI / 4.0;
+const SENSOR_DISTANCE: f32 = 9.0;
+const TURN_ANGLE: f32 = PI / 8.0;
+const STEP: f32 = 1.0;
+const DEPOSIT: f32 = 0.3;
+const DECAY: f32 = 0.9;
+
+struct Agent {
+    x: f32,
+    y: f32,
+    angle: f32,
+}
+
+// 粘菌（Physarum）シミュレーション
+// 感知→回転→移動→堆積→拡散 をエージェントごと・セルごとに毎フレーム行う
+// CPU側のグリッド処理とテクスチャ転送の両方に負荷がかかる
+pub(crate) struct PhysarumScene {
+    agents: Vec<Agent>,
+    grid_width: usize,
+    grid_height: usize,
+    trail: Vec<f32>,
+    scratch: Vec<f32>,
+    pixels: Vec<u8>,
+    frame_count: u32,
+}
+
+impl PhysarumScene {
+    pub fn new(width: f32, height: f32) -> PhysarumScene {
+        let grid_width = ((width / CELL_SIZE) as usize).max(1);
+        let grid_height = ((height / CELL_SIZE) as usize).max(1);
+        let cells = grid_width * grid_height;
+
+        let mut scene = PhysarumScene {
+            agents: Vec::new(),
+            grid_width,
+            grid_height,
+            trail: vec![0.0; cells],
+            scratch: vec![0.0; cells],
+            pixels: vec![0; cells * 4],
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    // 中央の円内に外向きで配置する
+    fn spawn_agents(&mut self, count: usize) {
+        let mut rng = crate::rng::rng();
+        let (w, h) = (self.grid_width as f32, self.grid_height as f32);
+        let radius = w.min(h) * 0.3;
+        self.agents.extend((0..count).map(|_| {
+            let angle = rng.gen::<f32>() * 2.0 * PI;
+            let r = radius * math::sqrt(rng.gen::<f32>());
+            Agent {
+                x: w / 2.0 + math::cos(angle) * r,
+                y: h / 2.0 + math::sin(angle) * r,
+                angle,
+            }
+        }));
+    }
+
+    fn sense(&self, agent: &Agent, offset: f32) -> f32 {
+        let angle = agent.angle + offset;
+        let x = (agent.x + math::cos(angle) * SENSOR_DISTANCE) as isize;
+        let y = (agent.y + math::sin(angle) * SENSOR_DISTANCE) as isize;
+        if x < 0 || y < 0 || x >= self.grid_width as isize || y >= self.grid_height as isize {
+            return 0.0;
+        }
+        self.trail[y as usize * self.grid_width + x as usize]
+    }
+
+    // 3x3の平均で拡散してから減衰させる
+    fn diffuse(&mut self) {
+        let (w, h) = (self.grid_width, self.grid_height);
+        for y in 0..h {
+            let y0 = y.saturating_sub(1);
+            let y1 = (y + 1).min(h - 1);
+            for x in 0..w {
+                let x0 = x.saturating_sub(1);
+                let x1 = (x + 1).min(w - 1);
+                let mut sum = 0.0;
+                for yy in [y0, y, y1] {
+                    let row = yy * w;
+                    sum += self.trail[row + x0] + self.trail[row + x] + self.trail[row + x1];
+                }
+                self.scratch[y * w + x] = sum / 9.0 * DECAY;
+            }
+        }
+        std::mem::swap(&mut self.trail, &mut self.scratch);
+    }
+}
+
+impl Scene for PhysarumScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Physarum
+    }
+
+    fn update(&mut self) {
+        let mut rng = crate::rng::rng();
+        let (w, h) = (self.grid_width as f32, self.grid_height as f32);
+
+        for i in 0..self.agents.len() {
+            // 感知と回転
+            let agent = &self.agents[i];
+            let forward = self.sense(agent, 0.0);
+            let left = self.sense(agent, SENSOR_ANGLE);
+            let right = self.sense(agent, -SENSOR_ANGLE);
+            let turn = if forward >= left && forward >= right {
+                0.0
+            } else if forward < left && forward < right {
+                if rng.gen::<bool>() {
+                    TURN_ANGLE
+                } else {
+                    -TURN_ANGLE
+                }
+            } else if left > right {
+                TURN_ANGLE
+            } else {
+                -TURN_ANGLE
+            };
+
+            // 移動（壁に当たったらランダムな向きに変える）
+            let agent = &mut self.agents[i];
+            agent.angle += turn;
+            let nx = agent.x + math::cos(agent.angle) * STEP;
+            let ny = agent.y + math::sin(agent.angle) * STEP;
+            if nx < 0.0 || ny < 0.0 || nx >= w || ny >= h {
+                agent.angle = rng.gen::<f32>() * 2.0 * PI;
+            } else {
+                agent.x = nx;
+                agent.y = ny;
+            }
+
+            // 堆積
+            let cell = agent.y as usize * self.grid_width + agent.x as usize;
+            self.trail[cell] = (self.trail[cell] + DEPOSIT).min(1.0);
+        }
+
+        self.diffuse();
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for (t, px) in self.trail.iter().zip(self.pixels.chunks_exact_mut(4)) {
+            let v = t.clamp(0.0, 1.0);
+            px[0] = (v * 120.0) as u8;
+            px[1] = (v * 255.0) as u8;
+            px[2] = (v * 200.0) as u8;
+            px[3] = 255;
+        }
+        surface.blit_rgba(
+            self.grid_width as u32,
+            self.grid_height as u32,
+            &self.pixels,
+        );
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_AGENTS as f32 * load).round() as usize;
+        if count < self.agents.len() {
+            self.agents.truncate(count);
+        } else {
+            self.spawn_agents(count - self.agents.len());
+        }
+    }
+
+    // クリック位置に濃いエサを置いてエージェントを引き寄せる
+    fn explode(&mut self, x: f32, y: f32, _explosion: &ExplosionConfig) {
+        let cx = (x / CELL_SIZE) as isize;
+        let cy = (y / CELL_SIZE) as isize;
+        for dy in -6..=6 {
+            for dx in -6..=6 {
+                let (gx, gy) = (cx + dx, cy + dy);
+                if gx >= 0
+                    && gy >= 0
+                    && (gx as usize) < self.grid_width
+                    && (gy as usize) < self.grid_height
+                {
+                    self.trail[gy as usize * self.grid_width + gx as usize] = 1.0;
+                }
+            }
+        }
+    }
+
+    fn debug_buffers(&self) -> Vec<DebugBuffer<'_>> {
+        vec![DebugBuffer {
+            name: "trail",
+            width: self.grid_width,
+            height: self.grid_height,
+            pixels: DebugPixels::Gray(&self.trail),
+        }]
+    }
+}
@@ -0,0 +1,108 @@
+use rand::Rng;
+
+use super::{ColorPoint, Scene, SceneKind, Surface};
+use crate::render_mode::RenderMode;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 0.5 のときの点の数
+pub(crate) const DEFAULT_POINTS: usize = 10_000;
+// 掃引で1つの大きさを描き続けるフレーム数
+const FRAMES_PER_SIZE: u32 = 60;
+
+// 決まった数の止まった点を、大きさだけ変えて描く（フィルレートの計測用）
+// 点の位置も数も変えないので頂点と CPU のコストは一定で、塗るピクセルの数だけが変わる
+// size が None なら 1px から描画先の上限まで掃引し、上限の次は 1px に戻る
+pub(crate) struct PointSizeScene {
+    width: f32,
+    height: f32,
+    // 負荷 0.5 のときの数（負荷に比例して増減する）
+    base_count: usize,
+    points: Vec<ColorPoint>,
+    size: Option<f32>,
+    // 描画先が描ける一番大きい点(px)。render で描画先から受け取る
+    max_size: f32,
+    frame_count: u32,
+}
+
+impl PointSizeScene {
+    pub fn new(width: f32, height: f32, count: usize, size: Option<f32>) -> PointSizeScene {
+        let mut scene = PointSizeScene {
+            width,
+            height,
+            base_count: count,
+            points: Vec::new(),
+            size,
+            max_size: width.min(height),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    fn current_size(&self) -> f32 {
+        match self.size {
+            Some(size) => size.clamp(1.0, self.max_size),
+            None => {
+                let sizes = sweep_sizes(self.max_size);
+                sizes[(self.frame_count / FRAMES_PER_SIZE) as usize % sizes.len()]
+            }
+        }
+    }
+}
+
+impl Scene for PointSizeScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::PointSize
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        // 画面より大きい点は塗る量が増えないので、画面の短い辺までにする
+        self.max_size = surface
+            .max_point_size()
+            .min(self.width.min(self.height))
+            .max(1.0);
+        surface.clear([0.1, 0.1, 0.1]);
+        surface.draw_points(&self.points, self.current_size(), RenderMode::Normal);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 点の数を負荷に比例させる（位置と色は作り直す）
+    fn set_load(&mut self, load: f32) {
+        let count = ((self.base_count as f32 * 2.0 * load).round() as usize).max(1);
+        let mut rng = crate::rng::rng();
+        self.points = (0..count)
+            .map(|_| {
+                let rgb = hsl_to_rgb(rng.gen::<f32>() * 360.0, 1.0, 0.5);
+                ColorPoint {
+                    x: rng.gen::<f32>() * self.width,
+                    y: rng.gen::<f32>() * self.height,
+                    rgb: [rgb.0, rgb.1, rgb.2],
+                }
+            })
+            .collect();
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
+
+// 掃引する大きさ（1px から2倍ずつ、最後は max_size）
+pub(crate) fn sweep_sizes(max_size: f32) -> Vec<f32> {
+    let mut sizes = Vec::new();
+    let mut size = 1.0;
+    while size < max_size {
+        sizes.push(size);
+        size *= 2.0;
+    }
+    sizes.push(max_size.max(1.0));
+    sizes
+}
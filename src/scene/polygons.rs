@@ -0,0 +1,116 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{Polygon, Scene, SceneKind, Surface};
+use crate::math;
+
+// 負荷 1.0 のときの数（既定はその半分）
+const MAX_POLYGONS: usize = 1_000;
+// 星形の頂点数（外側と内側を交互に置く）
+const POINTS: usize = 24;
+const RADIUS: f32 = 30.0;
+const PALETTE_SIZE: u32 = 8;
+
+struct Star {
+    x: f32,
+    y: f32,
+    rotation: f32,
+    spin: f32,
+    phase: f32,
+}
+
+// 形が変わり続ける凹多角形（星形）を塗りつぶす
+// WebGLは毎フレームWASMで耳切り分割してから三角形で、Canvas2Dはネイティブの fill で描く
+pub(crate) struct PolygonScene {
+    width: f32,
+    height: f32,
+    stars: Vec<Star>,
+    polygons: Vec<Polygon>,
+    frame_count: u32,
+}
+
+impl PolygonScene {
+    pub fn new(width: f32, height: f32) -> PolygonScene {
+        let mut scene = PolygonScene {
+            width,
+            height,
+            stars: Vec::new(),
+            polygons: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for PolygonScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Polygons
+    }
+
+    fn update(&mut self) {
+        for star in &mut self.stars {
+            star.rotation += star.spin;
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.03, 0.03, 0.06]);
+
+        let t = self.frame_count as f32 * 0.05;
+        for (star, polygon) in self.stars.iter().zip(&mut self.polygons) {
+            polygon.points.clear();
+            for i in 0..POINTS {
+                let angle = star.rotation + i as f32 * 2.0 * PI / POINTS as f32;
+                // 内側の頂点の半径が揺れるので凹み具合が毎フレーム変わる
+                let radius = if i % 2 == 0 {
+                    RADIUS
+                } else {
+                    RADIUS * (0.35 + 0.25 * math::sin(t + star.phase + i as f32))
+                };
+                polygon.points.push((
+                    star.x + math::cos(angle) * radius,
+                    star.y + math::sin(angle) * radius,
+                ));
+            }
+        }
+        surface.fill_polygons(&self.polygons);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_POLYGONS as f32 * load).round() as usize;
+        let (width, height) = (self.width, self.height);
+        let mut rng = crate::rng::rng();
+        let stars = &mut self.stars;
+        let polygons = &mut self.polygons;
+        stars.truncate(count);
+        polygons.truncate(count);
+        while stars.len() < count {
+            stars.push(Star {
+                x: rng.gen::<f32>() * width,
+                y: rng.gen::<f32>() * height,
+                rotation: rng.gen::<f32>() * 2.0 * PI,
+                spin: (rng.gen::<f32>() - 0.5) * 0.05,
+                phase: rng.gen::<f32>() * 2.0 * PI,
+            });
+            let hue = rng.gen_range(0..PALETTE_SIZE) as f32 * 360.0 / PALETTE_SIZE as f32;
+            let (r, g, b) = crate::simulation::hsl_to_rgb(hue, 0.6, 0.55);
+            polygons.push(Polygon {
+                points: Vec::with_capacity(POINTS),
+                rgb: [r, g, b],
+            });
+        }
+
+        // Canvas2D で同じ色をまとめて塗れるように色順に並べる（星との対応は順番だけ）
+        polygons.sort_by(|a, b| {
+            a.rgb
+                .partial_cmp(&b.rgb)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use super::{ColorPoint, ColorRect, Scene, SceneKind, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::render_mode::{CompositeMode, RenderMode, TRAIL_FADE};
+use crate::simulation::hsl_to_rgb;
+use crate::viewport::Viewport;
+
+// 負荷 1.0 のときの点の数（既定はその半分）。4回描くので Clip の半分にしておく
+const MAX_POINTS: usize = 20_000;
+const BACKGROUND: [f32; 3] = [0.05, 0.05, 0.05];
+// 左上・右上・左下・右下の順に、合成方法と点の大きさ(px)
+const QUADRANTS: [(RenderMode, f32); 4] = [
+    (RenderMode::Normal, 2.0),
+    (RenderMode::Additive, 2.0),
+    (RenderMode::Trails, 2.0),
+    (RenderMode::Normal, 6.0),
+];
+
+struct Dot {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    rgb: [f32; 3],
+}
+
+// 同じパーティクルを画面を4分割した領域に1回ずつ、合成方法を変えて描く
+// 領域ごとにビューポートとシザー（Canvas2D はクリップと変換）を切り替えるので、その切り替えのコストが測れ、
+// 合成方法の見た目の違いも一目で比べられる。切り替えられない描画先では点の座標を縮めて描く
+pub(crate) struct QuadrantScene {
+    width: f32,
+    height: f32,
+    dots: Vec<Dot>,
+    points: Vec<ColorPoint>,
+    frame_count: u32,
+}
+
+impl QuadrantScene {
+    pub fn new(width: f32, height: f32) -> QuadrantScene {
+        let mut scene = QuadrantScene {
+            width,
+            height,
+            dots: Vec::new(),
+            points: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    fn quadrant(&self, index: usize) -> Viewport {
+        Viewport::grid_cell(self.width, self.height, 2, 2, index as u32)
+    }
+
+    // 領域の背景を塗る。Trails は前のフレームを背景色で薄めるだけにする（できなければ消す）
+    fn clear_region(surface: &mut dyn Surface, rect: ColorRect, mode: RenderMode) {
+        let faded = mode == RenderMode::Trails
+            && surface.fill_rects_composited(&[rect], TRAIL_FADE, CompositeMode::SourceOver);
+        if !faded {
+            surface.fill_rects(&[rect]);
+        }
+    }
+}
+
+impl Scene for QuadrantScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Quadrants
+    }
+
+    fn update(&mut self) {
+        let (width, height) = (self.width, self.height);
+        for dot in &mut self.dots {
+            dot.x += dot.vx;
+            dot.y += dot.vy;
+            if dot.x < 0.0 || dot.x > width {
+                dot.vx = -dot.vx;
+            }
+            if dot.y < 0.0 || dot.y > height {
+                dot.vy = -dot.vy;
+            }
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for (index, (mode, size)) in QUADRANTS.into_iter().enumerate() {
+            let region = self.quadrant(index);
+            self.points.clear();
+            if surface.push_viewport(region) {
+                // 座標はキャンバス全体のまま描けば領域に縮まる
+                self.points.extend(self.dots.iter().map(|dot| ColorPoint {
+                    x: dot.x,
+                    y: dot.y,
+                    rgb: dot.rgb,
+                }));
+                let full = ColorRect {
+                    x: 0.0,
+                    y: 0.0,
+                    width: self.width,
+                    height: self.height,
+                    rgb: BACKGROUND,
+                };
+                Self::clear_region(surface, full, mode);
+                surface.draw_points(&self.points, size, mode);
+                surface.pop_viewport();
+                continue;
+            }
+            if index == 0 {
+                surface.report(EventKind::FallbackUsed, &tr(Text::ViewportFallback, &[]));
+            }
+            let (sx, sy) = (region.width / self.width, region.height / self.height);
+            self.points.extend(self.dots.iter().map(|dot| ColorPoint {
+                x: region.x + dot.x * sx,
+                y: region.y + dot.y * sy,
+                rgb: dot.rgb,
+            }));
+            let rect = ColorRect {
+                x: region.x,
+                y: region.y,
+                width: region.width,
+                height: region.height,
+                rgb: BACKGROUND,
+            };
+            Self::clear_region(surface, rect, mode);
+            surface.draw_points(&self.points, size, mode);
+        }
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 点の数を負荷に比例させる（足りない分だけ足し、多い分は末尾から減らす）
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_POINTS as f32 * load).round() as usize;
+        let mut rng = crate::rng::rng();
+        while self.dots.len() < count {
+            let angle = rng.gen::<f32>() * TAU;
+            let speed = 0.5 + rng.gen::<f32>() * 2.0;
+            let (r, g, b) = hsl_to_rgb(rng.gen::<f32>() * 360.0, 0.8, 0.6);
+            self.dots.push(Dot {
+                x: rng.gen::<f32>() * self.width,
+                y: rng.gen::<f32>() * self.height,
+                vx: math::cos(angle) * speed,
+                vy: math::sin(angle) * speed,
+                rgb: [r, g, b],
+            });
+        }
+        self.dots.truncate(count);
+    }
+}
@@ -0,0 +1,189 @@
+use super::{ColorRect, Scene, SceneKind, Surface};
+use crate::math;
+
+// 1列あたりのピクセル幅の既定値
+const DEFAULT_COLUMN_WIDTH: f32 = 2.0;
+// 視野角の半分の tan（約66度の視野）
+const PLANE_LENGTH: f32 = 0.66;
+const TURN_SPEED: f32 = 0.01;
+const CEILING_RGB: [f32; 3] = [0.15, 0.15, 0.2];
+const FLOOR_RGB: [f32; 3] = [0.3, 0.27, 0.22];
+
+// 0 は通路、1~4 は壁の種類
+const MAP: [&[u8; 16]; 16] = [
+    b"1111111111111111",
+    b"1000000000000001",
+    b"1022000000033001",
+    b"1020000000003001",
+    b"1000004440000001",
+    b"1000004040000001",
+    b"1000000000000001",
+    b"1033000000000221",
+    b"1030000000000021",
+    b"1000000440000001",
+    b"1000000440000001",
+    b"1000000000000001",
+    b"1002200000330001",
+    b"1000200000030001",
+    b"1000000000000001",
+    b"1111111111111111",
+];
+
+// Wolfenstein風のレイキャスター
+// 画面の列ごとにタイルマップをDDAで走査して壁までの距離を求め、縦の帯として描く
+pub(crate) struct RaycasterScene {
+    width: f32,
+    height: f32,
+    angle: f32,
+    column_width: f32,
+    rects: Vec<ColorRect>,
+    frame_count: u32,
+}
+
+impl RaycasterScene {
+    pub fn new(width: f32, height: f32) -> RaycasterScene {
+        RaycasterScene {
+            width,
+            height,
+            angle: 0.0,
+            column_width: DEFAULT_COLUMN_WIDTH,
+            rects: Vec::new(),
+            frame_count: 0,
+        }
+    }
+
+    // マップ中央の周りを回りながら進行方向を向くカメラ
+    fn camera(&self) -> ((f32, f32), (f32, f32)) {
+        let center = MAP.len() as f32 / 2.0;
+        let position = (
+            center + math::cos(self.angle) * 4.5,
+            center + math::sin(self.angle) * 4.5,
+        );
+        let heading = self.angle + std::f32::consts::FRAC_PI_2;
+        (position, (math::cos(heading), math::sin(heading)))
+    }
+}
+
+// DDAで壁に当たるまで進み、(垂直距離, 壁の種類, y方向の面か) を返す
+fn cast(position: (f32, f32), ray: (f32, f32)) -> (f32, u8, bool) {
+    let (mut map_x, mut map_y) = (position.0 as i32, position.1 as i32);
+    let delta_x = if ray.0 == 0.0 {
+        f32::MAX
+    } else {
+        (1.0 / ray.0).abs()
+    };
+    let delta_y = if ray.1 == 0.0 {
+        f32::MAX
+    } else {
+        (1.0 / ray.1).abs()
+    };
+    let (step_x, mut side_x) = if ray.0 < 0.0 {
+        (-1, (position.0 - map_x as f32) * delta_x)
+    } else {
+        (1, (map_x as f32 + 1.0 - position.0) * delta_x)
+    };
+    let (step_y, mut side_y) = if ray.1 < 0.0 {
+        (-1, (position.1 - map_y as f32) * delta_y)
+    } else {
+        (1, (map_y as f32 + 1.0 - position.1) * delta_y)
+    };
+
+    loop {
+        let y_side = if side_x < side_y {
+            side_x += delta_x;
+            map_x += step_x;
+            false
+        } else {
+            side_y += delta_y;
+            map_y += step_y;
+            true
+        };
+        // マップの外周はすべて壁なので必ずどこかで止まる
+        let tile = MAP[map_y as usize][map_x as usize];
+        if tile != b'0' {
+            let distance = if y_side {
+                side_y - delta_y
+            } else {
+                side_x - delta_x
+            };
+            return (distance.max(1e-4), tile - b'0', y_side);
+        }
+    }
+}
+
+fn wall_rgb(tile: u8) -> [f32; 3] {
+    match tile {
+        1 => [0.7, 0.7, 0.7],
+        2 => [0.8, 0.2, 0.2],
+        3 => [0.2, 0.6, 0.9],
+        _ => [0.9, 0.8, 0.2],
+    }
+}
+
+impl Scene for RaycasterScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Raycaster
+    }
+
+    fn update(&mut self) {
+        self.angle += TURN_SPEED;
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        let (w, h) = (self.width, self.height);
+        let (position, dir) = self.camera();
+        // 視線に垂直なカメラ平面
+        let plane = (-dir.1 * PLANE_LENGTH, dir.0 * PLANE_LENGTH);
+
+        self.rects.clear();
+        self.rects.push(ColorRect {
+            x: 0.0,
+            y: 0.0,
+            width: w,
+            height: h / 2.0,
+            rgb: CEILING_RGB,
+        });
+        self.rects.push(ColorRect {
+            x: 0.0,
+            y: h / 2.0,
+            width: w,
+            height: h / 2.0,
+            rgb: FLOOR_RGB,
+        });
+
+        let columns = (w / self.column_width).ceil() as usize;
+        for column in 0..columns {
+            let camera_x = 2.0 * column as f32 / columns as f32 - 1.0;
+            let ray = (dir.0 + plane.0 * camera_x, dir.1 + plane.1 * camera_x);
+            let (distance, tile, y_side) = cast(position, ray);
+
+            let line_height = (h / distance).min(h);
+            let mut rgb = wall_rgb(tile);
+            // y方向の面と遠くの壁を暗くして奥行きを出す
+            let shade = if y_side { 0.7 } else { 1.0 } / (1.0 + distance * 0.1);
+            for c in &mut rgb {
+                *c *= shade;
+            }
+            self.rects.push(ColorRect {
+                x: column as f32 * self.column_width,
+                y: (h - line_height) / 2.0,
+                width: self.column_width,
+                height: line_height,
+                rgb,
+            });
+        }
+
+        surface.fill_rects(&self.rects);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 列数（=レイの本数）を負荷に比例させる。1.0 で1ピクセル1列
+    fn set_load(&mut self, load: f32) {
+        let columns = (self.width * load).round().max(8.0);
+        self.column_width = self.width / columns;
+    }
+}
@@ -0,0 +1,166 @@
+use super::{Scene, SceneKind, Surface};
+use crate::capture::{DebugBuffer, DebugPixels};
+use crate::explosion::ExplosionConfig;
+use crate::simulation::Simulation;
+
+// 1セルあたりのピクセル数（縦横）
+const CELL_SIZE: f32 = 2.0;
+// Gray-Scott のパラメータ（珊瑚状のパターンになる組み合わせ）
+const DIFFUSION_U: f32 = 0.16;
+const DIFFUSION_V: f32 = 0.08;
+const FEED: f32 = 0.060;
+const KILL: f32 = 0.062;
+// 1フレームあたりの反応拡散ステップ数（負荷 1.0 のとき）
+const MAX_STEPS_PER_FRAME: usize = 8;
+// パターンを乱す種まき用のパーティクル数
+const PARTICLE_COUNT: usize = 2_000;
+const SEED_AMOUNT: f32 = 0.05;
+
+// Gray-Scott 反応拡散グリッド
+// 5点ステンシルの計算は典型的なWASMベンチマークのカーネルで、
+// パーティクル系とは違う「メモリ帯域+浮動小数点演算」の負荷を測れる
+pub(crate) struct ReactionDiffusionScene {
+    sim: Simulation,
+    grid_width: usize,
+    grid_height: usize,
+    u: Vec<f32>,
+    v: Vec<f32>,
+    next_u: Vec<f32>,
+    next_v: Vec<f32>,
+    pixels: Vec<u8>,
+    steps_per_frame: usize,
+}
+
+impl ReactionDiffusionScene {
+    pub fn new(width: f32, height: f32) -> Result<ReactionDiffusionScene, String> {
+        let grid_width = ((width / CELL_SIZE) as usize).max(3);
+        let grid_height = ((height / CELL_SIZE) as usize).max(3);
+        let cells = grid_width * grid_height;
+        let mut scene = ReactionDiffusionScene {
+            sim: Simulation::new(width, height, PARTICLE_COUNT)?,
+            grid_width,
+            grid_height,
+            u: vec![1.0; cells],
+            v: vec![0.0; cells],
+            next_u: vec![0.0; cells],
+            next_v: vec![0.0; cells],
+            pixels: vec![0; cells * 4],
+            steps_per_frame: MAX_STEPS_PER_FRAME / 2,
+        };
+        // 中央に V の種を置く
+        let (cx, cy) = (grid_width / 2, grid_height / 2);
+        scene.seed(cx, cy, 10);
+        Ok(scene)
+    }
+
+    // (cx, cy) を中心とした正方形に V を注入する
+    fn seed(&mut self, cx: usize, cy: usize, radius: usize) {
+        let (w, h) = (self.grid_width, self.grid_height);
+        for y in cy.saturating_sub(radius)..(cy + radius).min(h) {
+            for x in cx.saturating_sub(radius)..(cx + radius).min(w) {
+                self.u[y * w + x] = 0.5;
+                self.v[y * w + x] = 0.25;
+            }
+        }
+    }
+
+    // 1ステップ分の反応と拡散（端は反対側に回り込む）
+    fn step(&mut self) {
+        let (w, h) = (self.grid_width, self.grid_height);
+        for y in 0..h {
+            let up = if y == 0 { h - 1 } else { y - 1 } * w;
+            let down = if y == h - 1 { 0 } else { y + 1 } * w;
+            let row = y * w;
+            for x in 0..w {
+                let left = if x == 0 { w - 1 } else { x - 1 };
+                let right = if x == w - 1 { 0 } else { x + 1 };
+                let i = row + x;
+                let u = self.u[i];
+                let v = self.v[i];
+                let lap_u =
+                    self.u[row + left] + self.u[row + right] + self.u[up + x] + self.u[down + x]
+                        - 4.0 * u;
+                let lap_v =
+                    self.v[row + left] + self.v[row + right] + self.v[up + x] + self.v[down + x]
+                        - 4.0 * v;
+                let uvv = u * v * v;
+                self.next_u[i] = (u + DIFFUSION_U * lap_u - uvv + FEED * (1.0 - u)).clamp(0.0, 1.0);
+                self.next_v[i] =
+                    (v + DIFFUSION_V * lap_v + uvv - (FEED + KILL) * v).clamp(0.0, 1.0);
+            }
+        }
+        std::mem::swap(&mut self.u, &mut self.next_u);
+        std::mem::swap(&mut self.v, &mut self.next_v);
+    }
+}
+
+impl Scene for ReactionDiffusionScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::ReactionDiffusion
+    }
+
+    fn update(&mut self) {
+        // パーティクルが通ったセルに V を少しずつ足してパターンをかき乱す
+        self.sim.step();
+        let max_x = self.grid_width - 1;
+        let max_y = self.grid_height - 1;
+        for p in self.sim.particles() {
+            let cx = ((p.x / CELL_SIZE) as usize).min(max_x);
+            let cy = ((p.y / CELL_SIZE) as usize).min(max_y);
+            let cell = &mut self.v[cy * self.grid_width + cx];
+            *cell = (*cell + SEED_AMOUNT).min(1.0);
+        }
+
+        for _ in 0..self.steps_per_frame {
+            self.step();
+        }
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        for ((u, v), px) in self
+            .u
+            .iter()
+            .zip(&self.v)
+            .zip(self.pixels.chunks_exact_mut(4))
+        {
+            let t = (u - v).clamp(0.0, 1.0);
+            px[0] = (t * 230.0) as u8;
+            px[1] = (t * 240.0) as u8;
+            px[2] = (80.0 + t * 175.0) as u8;
+            px[3] = 255;
+        }
+        surface.blit_rgba(
+            self.grid_width as u32,
+            self.grid_height as u32,
+            &self.pixels,
+        );
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.sim.frame_count
+    }
+
+    // 0.0 でも1ステップは進める
+    fn set_load(&mut self, load: f32) {
+        self.steps_per_frame = ((MAX_STEPS_PER_FRAME as f32 * load).round() as usize).max(1);
+    }
+
+    // クリック位置に V の種を置く
+    fn explode(&mut self, x: f32, y: f32, explosion: &ExplosionConfig) {
+        let cx = ((x / CELL_SIZE) as usize).min(self.grid_width - 1);
+        let cy = ((y / CELL_SIZE) as usize).min(self.grid_height - 1);
+        self.seed(cx, cy, 6);
+        self.sim.explode(x, y, explosion);
+    }
+
+    // 2つの物質の濃度をそれぞれ1枚ずつ
+    fn debug_buffers(&self) -> Vec<DebugBuffer<'_>> {
+        let buffer = |name, values| DebugBuffer {
+            name,
+            width: self.grid_width,
+            height: self.grid_height,
+            pixels: DebugPixels::Gray(values),
+        };
+        vec![buffer("u", &self.u), buffer("v", &self.v)]
+    }
+}
@@ -0,0 +1,139 @@
+use super::{ColorRect, Scene, SceneKind, Surface};
+use crate::math;
+use crate::simulation::hsl_to_rgb;
+
+// 掃引する範囲の大きさ（キャンバスの一辺に対する割合。0.0 は 1px で色の取得を想定）
+pub(crate) const READBACK_SCALES: [f32; 5] = [0.0, 0.125, 0.25, 0.5, 1.0];
+// 掃引で1つの大きさを読み続けるフレーム数
+const FRAMES_PER_SCALE: u32 = 60;
+// 読み戻す前に描く矩形の数（描き終わるのを待つ分も読み戻しのコストに含める）
+const RECTS: usize = 64;
+
+// 毎フレーム動く矩形を描いてから、画面中央の範囲を読み戻す（WebGL は readPixels、Canvas2D は getImageData）
+// 読んだ範囲は枠で示し、左上には読んだ中央のピクセルの色を塗る
+// scale が None なら 1px からキャンバス全体まで掃引する
+pub(crate) struct ReadbackScene {
+    width: f32,
+    height: f32,
+    scale: Option<f32>,
+    // 負荷 0.5 で 1.0（負荷に比例して範囲の一辺を伸び縮みさせる）
+    load_scale: f32,
+    pixels: Vec<u8>,
+    rects: Vec<ColorRect>,
+    frame_count: u32,
+}
+
+impl ReadbackScene {
+    pub fn new(width: f32, height: f32, scale: Option<f32>) -> ReadbackScene {
+        let mut scene = ReadbackScene {
+            width,
+            height,
+            scale,
+            load_scale: 1.0,
+            pixels: Vec::new(),
+            rects: Vec::with_capacity(RECTS),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    // 読み戻す範囲（中央寄せ、1px 以上でキャンバスを超えない）
+    fn region(&self) -> (u32, u32, u32, u32) {
+        let scale = match self.scale {
+            Some(scale) => scale,
+            None => {
+                let step = (self.frame_count / FRAMES_PER_SCALE) as usize;
+                READBACK_SCALES[step % READBACK_SCALES.len()]
+            }
+        } * self.load_scale;
+        let (width, height) = readback_size(self.width, self.height, scale);
+        let x = (self.width as u32).saturating_sub(width) / 2;
+        let y = (self.height as u32).saturating_sub(height) / 2;
+        (x, y, width, height)
+    }
+}
+
+impl Scene for ReadbackScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Readback
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.1, 0.1, 0.1]);
+        // 円周上を回る矩形
+        let t = self.frame_count as f32 * 0.02;
+        let size = self.width.min(self.height) / 8.0;
+        self.rects.clear();
+        self.rects.extend((0..RECTS).map(|i| {
+            let angle = t + i as f32 / RECTS as f32 * std::f32::consts::TAU;
+            let (r, g, b) = hsl_to_rgb(i as f32 * 360.0 / RECTS as f32, 0.8, 0.5);
+            ColorRect {
+                x: self.width / 2.0 + math::cos(angle) * self.width * 0.35 - size / 2.0,
+                y: self.height / 2.0 + math::sin(angle) * self.height * 0.35 - size / 2.0,
+                width: size,
+                height: size,
+                rgb: [r, g, b],
+            }
+        }));
+        surface.fill_rects(&self.rects);
+
+        let (x, y, width, height) = self.region();
+        if !surface.read_rgba(x, y, width, height, &mut self.pixels) {
+            return;
+        }
+
+        // 読んだ範囲の枠と、中央のピクセルの色（読み戻しの後に描くので計測する範囲には入らない）
+        let center = ((height / 2 * width + width / 2) * 4) as usize;
+        let picked = match self.pixels.get(center..center + 3) {
+            Some(rgb) => [rgb[0], rgb[1], rgb[2]].map(|c| c as f32 / 255.0),
+            None => [0.0; 3],
+        };
+        let (x, y, width, height) = (x as f32, y as f32, width as f32, height as f32);
+        let white = [1.0, 1.0, 1.0];
+        let border = |x, y, width, height| ColorRect {
+            x,
+            y,
+            width,
+            height,
+            rgb: white,
+        };
+        surface.fill_rects(&[
+            border(x, y, width, 1.0),
+            border(x, y + height - 1.0, width, 1.0),
+            border(x, y, 1.0, height),
+            border(x + width - 1.0, y, 1.0, height),
+            ColorRect {
+                x: 8.0,
+                y: 8.0,
+                width: 24.0,
+                height: 24.0,
+                rgb: picked,
+            },
+        ]);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 範囲の一辺を負荷に比例させる（負荷 1.0 で2倍、キャンバスを超える分は丸める）
+    fn set_load(&mut self, load: f32) {
+        self.load_scale = load * 2.0;
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
+
+// キャンバスの一辺の scale 倍の範囲の大きさ（1px 以上でキャンバスを超えない）
+pub(crate) fn readback_size(width: f32, height: f32, scale: f32) -> (u32, u32) {
+    let side = |length: f32| ((length * scale).round() as u32).clamp(1, length.max(1.0) as u32);
+    (side(width), side(height))
+}
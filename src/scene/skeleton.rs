@@ -0,0 +1,168 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{Atlas, Scene, SceneKind, Sprite, Surface};
+use crate::math;
+
+// 負荷 1.0 のときの数（既定はその半分）
+const MAX_CREATURES: usize = 6_000;
+const SPEED: f32 = 0.8;
+// アトラス上の切り出し範囲 [x, y, width, height]
+const BODY_SOURCE: [f32; 4] = [0.0, 0.0, 16.0, 16.0];
+const BONE_SOURCE: [f32; 4] = [16.0, 4.0, 32.0, 8.0];
+const UPPER_LENGTH: f32 = 24.0;
+const LOWER_LENGTH: f32 = 18.0;
+
+// 2Dアフィン変換 [a, b, c, d, e, f]（x' = a x + c y + e, y' = b x + d y + f）
+type Affine = [f32; 6];
+
+fn multiply(m: &Affine, n: &Affine) -> Affine {
+    [
+        m[0] * n[0] + m[2] * n[1],
+        m[1] * n[0] + m[3] * n[1],
+        m[0] * n[2] + m[2] * n[3],
+        m[1] * n[2] + m[3] * n[3],
+        m[0] * n[4] + m[2] * n[5] + m[4],
+        m[1] * n[4] + m[3] * n[5] + m[5],
+    ]
+}
+
+fn translate(x: f32, y: f32) -> Affine {
+    [1.0, 0.0, 0.0, 1.0, x, y]
+}
+
+fn rotate(angle: f32) -> Affine {
+    let (s, c) = (math::sin(angle), math::cos(angle));
+    [c, s, -s, c, 0.0, 0.0]
+}
+
+// 親の座標系で (x, y) から幅 width、高さ height の矩形に単位正方形を写す
+fn place(parent: &Affine, x: f32, y: f32, width: f32, height: f32) -> Affine {
+    multiply(parent, &[width, 0.0, 0.0, height, x, y])
+}
+
+struct Creature {
+    x: f32,
+    y: f32,
+    heading: f32,
+    phase: f32,
+}
+
+// 胴体と2本のボーン（上腕→前腕）を持つスプライトを大量に動かす
+// 階層の変換行列はWASMで計算し、WebGLはインスタンス描画、Canvas2Dは変換付き drawImage で描く
+pub(crate) struct SkeletonScene {
+    width: f32,
+    height: f32,
+    creatures: Vec<Creature>,
+    atlas: Atlas,
+    sprites: Vec<Sprite>,
+    frame_count: u32,
+}
+
+impl SkeletonScene {
+    pub fn new(width: f32, height: f32) -> SkeletonScene {
+        let mut scene = SkeletonScene {
+            width,
+            height,
+            creatures: Vec::new(),
+            atlas: create_atlas(),
+            sprites: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+// 48x16 のアトラス: 左に胴体の円、右にボーンのカプセル
+fn create_atlas() -> Atlas {
+    let (width, height) = (48usize, 16usize);
+    let mut pixels = vec![0u8; width * height * 4];
+    for y in 0..height {
+        for x in 0..width {
+            let (fx, fy) = (x as f32 + 0.5, y as f32 + 0.5);
+            let rgba = if x < 16 {
+                let (dx, dy) = (fx - 8.0, fy - 8.0);
+                (dx * dx + dy * dy <= 49.0).then_some([255, 160, 60, 255])
+            } else {
+                // 両端が丸いカプセル
+                let cx = fx.clamp(20.0, 44.0);
+                let (dx, dy) = (fx - cx, fy - 8.0);
+                (dx * dx + dy * dy <= 16.0).then_some([230, 230, 210, 255])
+            };
+            if let Some(rgba) = rgba {
+                pixels[(y * width + x) * 4..(y * width + x) * 4 + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+    Atlas::new(width as u32, height as u32, pixels)
+}
+
+impl Scene for SkeletonScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Skeleton
+    }
+
+    fn update(&mut self) {
+        let (w, h) = (self.width, self.height);
+        for c in &mut self.creatures {
+            c.heading += 0.01 * math::sin(c.phase + self.frame_count as f32 * 0.02);
+            c.x = (c.x + math::cos(c.heading) * SPEED).rem_euclid(w);
+            c.y = (c.y + math::sin(c.heading) * SPEED).rem_euclid(h);
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.05, 0.07, 0.1]);
+
+        let t = self.frame_count as f32 * 0.1;
+        self.sprites.clear();
+        for c in &self.creatures {
+            // ルート → 上腕 → 前腕 の順に親の変換を掛けていく
+            let root = multiply(&translate(c.x, c.y), &rotate(c.heading));
+            let upper = multiply(&root, &rotate(math::sin(t + c.phase) * 0.8));
+            let lower = multiply(
+                &multiply(&upper, &translate(UPPER_LENGTH - 2.0, 0.0)),
+                &rotate(math::sin(t * 1.7 + c.phase) * 1.2),
+            );
+
+            self.sprites.push(Sprite {
+                transform: place(&upper, 0.0, -4.0, UPPER_LENGTH, 8.0),
+                source: BONE_SOURCE,
+            });
+            self.sprites.push(Sprite {
+                transform: place(&lower, 0.0, -3.0, LOWER_LENGTH, 6.0),
+                source: BONE_SOURCE,
+            });
+            self.sprites.push(Sprite {
+                transform: place(&root, -8.0, -8.0, 16.0, 16.0),
+                source: BODY_SOURCE,
+            });
+        }
+        surface.draw_sprites(&self.atlas, &self.sprites);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn atlas_mut(&mut self) -> Option<&mut Atlas> {
+        Some(&mut self.atlas)
+    }
+
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_CREATURES as f32 * load).round() as usize;
+        let (w, h) = (self.width, self.height);
+        let mut rng = crate::rng::rng();
+        self.creatures.truncate(count);
+        while self.creatures.len() < count {
+            self.creatures.push(Creature {
+                x: rng.gen::<f32>() * w,
+                y: rng.gen::<f32>() * h,
+                heading: rng.gen::<f32>() * 2.0 * PI,
+                phase: rng.gen::<f32>() * 2.0 * PI,
+            });
+        }
+    }
+}
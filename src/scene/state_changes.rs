@@ -0,0 +1,131 @@
+use super::{ColorRect, DrawState, Scene, SceneKind, StyledRect, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 0.5 のときの矩形の数
+const DEFAULT_RECTS: usize = 2_000;
+const RECT_SIZE: f32 = 6.0;
+pub(crate) const DEFAULT_CHANGE_EVERY: u32 = 1;
+// change_every の上限（これより大きくしても1フレームの矩形の数を超えるので変わらない）
+pub(crate) const MAX_CHANGE_EVERY: u32 = 4_096;
+
+// 格子に並べた小さな矩形を、change_every 個ごとに色・アルファ・回転・クリップを変えて描く
+// Canvas2D は状態が変わるたびに fillStyle・globalAlpha・setTransform・clip を設定し直すので、
+// change_every を変えたときのフレーム時間の差が状態変更のコストになる（描く矩形は同じ）
+// 状態つきで描けない描画先では色だけの fill_rects で描く
+pub(crate) struct StateChangeScene {
+    width: f32,
+    height: f32,
+    change_every: u32,
+    cells: Vec<(f32, f32)>,
+    rects: Vec<StyledRect>,
+    frame_count: u32,
+}
+
+impl StateChangeScene {
+    pub fn new(width: f32, height: f32, change_every: u32) -> StateChangeScene {
+        let mut scene = StateChangeScene {
+            width,
+            height,
+            change_every: change_every.clamp(1, MAX_CHANGE_EVERY),
+            cells: Vec::new(),
+            rects: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    // group 番目の状態（フレームごとに少しずつ動かす）
+    fn state(&self, group: usize) -> DrawState {
+        let phase = self.frame_count as f32 * 0.02 + group as f32;
+        let (r, g, b) = hsl_to_rgb(
+            ((group * 37) as u32 + self.frame_count) as f32 % 360.0,
+            0.8,
+            0.55,
+        );
+        // 1つおきにクリップする（余白の幅も変える）
+        let clip = (group % 2 == 1).then(|| {
+            let margin = (group % 7) as f32 * 4.0;
+            [
+                margin,
+                margin,
+                self.width - margin * 2.0,
+                self.height - margin * 2.0,
+            ]
+        });
+        DrawState {
+            rgb: [r, g, b],
+            alpha: 0.4 + (group % 7) as f32 * 0.1,
+            rotation: math::sin(phase) * 0.02,
+            clip,
+        }
+    }
+}
+
+impl Scene for StateChangeScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::StateChanges
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.05, 0.05, 0.05]);
+        let change_every = self.change_every as usize;
+        let mut rects = std::mem::take(&mut self.rects);
+        rects.clear();
+        let mut state = self.state(0);
+        for (i, &(x, y)) in self.cells.iter().enumerate() {
+            if i % change_every == 0 {
+                state = self.state(i / change_every);
+            }
+            rects.push(StyledRect {
+                x,
+                y,
+                width: RECT_SIZE,
+                height: RECT_SIZE,
+                state,
+            });
+        }
+        if !surface.fill_rects_styled(&rects) {
+            surface.report(EventKind::FallbackUsed, &tr(Text::StateChangeFallback, &[]));
+            let plain: Vec<ColorRect> = rects
+                .iter()
+                .map(|rect| ColorRect {
+                    x: rect.x,
+                    y: rect.y,
+                    width: rect.width,
+                    height: rect.height,
+                    rgb: rect.state.rgb,
+                })
+                .collect();
+            surface.fill_rects(&plain);
+        }
+        self.rects = rects;
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 矩形の数を負荷に比例させる（格子に収まらない分は描かない）
+    fn set_load(&mut self, load: f32) {
+        let count = (DEFAULT_RECTS as f32 * 2.0 * load).round() as usize;
+        let pitch = RECT_SIZE * 1.5;
+        let columns = ((self.width / pitch) as usize).max(1);
+        let rows = ((self.height / pitch) as usize).max(1);
+        self.cells = (0..count.min(columns * rows))
+            .map(|i| ((i % columns) as f32 * pitch, (i / columns) as f32 * pitch))
+            .collect();
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
@@ -0,0 +1,111 @@
+use super::{Scene, SceneKind, Surface};
+use crate::simulation::hsl_to_rgb;
+
+// 掃引する大きさ（キャンバスの一辺に対する割合）
+pub(crate) const UPLOAD_SCALES: [f32; 4] = [0.125, 0.25, 0.5, 1.0];
+// 掃引で1つの大きさを描き続けるフレーム数
+const FRAMES_PER_SCALE: u32 = 60;
+
+// 画像（RGBA）を毎フレーム描画先に送り直して全体に引き伸ばす（転送帯域の計測用）
+// WebGL はテクスチャの texImage2D、Canvas2D は putImageData になる
+// 中身は毎フレーム1行だけ書き換える（送る量は大きさだけで決まり、WASM側の計算はほぼない）
+// scale が None ならキャンバスの 1/8 から同じ大きさまで掃引する
+pub(crate) struct TextureUploadScene {
+    width: f32,
+    height: f32,
+    scale: Option<f32>,
+    // 負荷 0.5 で 1.0（負荷に比例して画像の一辺を伸び縮みさせる）
+    load_scale: f32,
+    texture_width: u32,
+    texture_height: u32,
+    pixels: Vec<u8>,
+    frame_count: u32,
+}
+
+impl TextureUploadScene {
+    pub fn new(width: f32, height: f32, scale: Option<f32>) -> TextureUploadScene {
+        let mut scene = TextureUploadScene {
+            width,
+            height,
+            scale,
+            load_scale: 1.0,
+            texture_width: 0,
+            texture_height: 0,
+            pixels: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+
+    fn current_scale(&self) -> f32 {
+        match self.scale {
+            Some(scale) => scale,
+            None => {
+                let step = (self.frame_count / FRAMES_PER_SCALE) as usize;
+                UPLOAD_SCALES[step % UPLOAD_SCALES.len()]
+            }
+        }
+    }
+
+    // 大きさが変わったときだけ模様を作り直す
+    fn resize_texture(&mut self) {
+        let scale = self.current_scale() * self.load_scale;
+        let texture_width = ((self.width * scale).round() as u32).max(1);
+        let texture_height = ((self.height * scale).round() as u32).max(1);
+        if (texture_width, texture_height) == (self.texture_width, self.texture_height) {
+            return;
+        }
+        self.texture_width = texture_width;
+        self.texture_height = texture_height;
+        self.pixels.clear();
+        self.pixels
+            .reserve(texture_width as usize * texture_height as usize * 4);
+        // 横方向のグラデーションに市松模様を重ねる（引き伸ばしても大きさが分かるように）
+        for y in 0..texture_height {
+            for x in 0..texture_width {
+                let checker = if (x / 16 + y / 16) % 2 == 0 { 255 } else { 64 };
+                let r = (x * 255 / texture_width) as u8;
+                let g = (y * 255 / texture_height) as u8;
+                self.pixels.extend_from_slice(&[r, g, checker, 255]);
+            }
+        }
+    }
+}
+
+impl Scene for TextureUploadScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::TextureUpload
+    }
+
+    fn update(&mut self) {
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        self.resize_texture();
+        // 走査線のように1行ずつ色を塗り替える（毎フレーム中身が変わるようにする）
+        let row = (self.frame_count % self.texture_height) as usize;
+        let (r, g, b) = hsl_to_rgb((self.frame_count % 360) as f32, 1.0, 0.5);
+        let stride = self.texture_width as usize * 4;
+        for pixel in self.pixels[row * stride..(row + 1) * stride].chunks_exact_mut(4) {
+            pixel.copy_from_slice(&[(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255]);
+        }
+        surface.blit_rgba(self.texture_width, self.texture_height, &self.pixels);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    // 画像の一辺を負荷に比例させる（負荷 1.0 でキャンバスの2倍）
+    fn set_load(&mut self, load: f32) {
+        self.load_scale = load * 2.0;
+        self.resize_texture();
+    }
+
+    fn reset(&mut self) -> bool {
+        self.frame_count = 0;
+        true
+    }
+}
@@ -0,0 +1,151 @@
+use super::{Atlas, Scene, SceneKind, Sprite, Surface};
+use crate::math;
+
+// アトラス上のタイルの大きさ(px)
+const TILE_SIZE: f32 = 16.0;
+// アトラス上のタイルの並び（横 ATLAS_COLUMNS 個 × 縦2段）
+const ATLAS_COLUMNS: usize = 4;
+const GROUND_TILES: u32 = 4;
+const DECORATION_TILES: u32 = 4;
+// 装飾タイルを置く割合（1/n）
+const DECORATION_RARITY: u32 = 6;
+const SCROLL_SPEED: f32 = 1.5;
+
+// 無限にスクロールするタイルマップ
+// タイルはワールド座標のハッシュから決まるので保持せず、画面に入る範囲だけを毎フレーム並べる
+pub(crate) struct TilemapScene {
+    width: f32,
+    height: f32,
+    camera: (f32, f32),
+    // 画面上のタイルの大きさ（小さいほど1フレームのタイル数が増える）
+    tile_size: f32,
+    atlas: Atlas,
+    sprites: Vec<Sprite>,
+    frame_count: u32,
+}
+
+impl TilemapScene {
+    pub fn new(width: f32, height: f32) -> TilemapScene {
+        TilemapScene {
+            width,
+            height,
+            camera: (0.0, 0.0),
+            tile_size: TILE_SIZE,
+            atlas: create_atlas(),
+            sprites: Vec::new(),
+            frame_count: 0,
+        }
+    }
+}
+
+// タイル座標から決まる疑似乱数
+fn tile_hash(x: i32, y: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
+
+fn tile_source(index: u32) -> [f32; 4] {
+    let index = index as usize;
+    [
+        (index % ATLAS_COLUMNS) as f32 * TILE_SIZE,
+        (index / ATLAS_COLUMNS) as f32 * TILE_SIZE,
+        TILE_SIZE,
+        TILE_SIZE,
+    ]
+}
+
+// 1段目は地面（草・砂・水・石）、2段目は透過付きの装飾
+fn create_atlas() -> Atlas {
+    let size = TILE_SIZE as usize;
+    let (width, height) = (size * ATLAS_COLUMNS, size * 2);
+    let mut pixels = vec![0u8; width * height * 4];
+    let ground = [
+        [70, 150, 60],
+        [210, 190, 120],
+        [50, 100, 200],
+        [120, 120, 130],
+    ];
+    let decoration = [[230, 80, 80], [240, 220, 70], [30, 90, 30], [200, 200, 200]];
+
+    for y in 0..height {
+        for x in 0..width {
+            let (tx, ty) = (x % size, y % size);
+            let index = x / size;
+            let rgba = if y < size {
+                // 地面はタイル内でわずかに明暗を付けて境界を見えるようにする
+                let [r, g, b] = ground[index];
+                let shade = if (tx + ty) % 7 == 0 { 20 } else { 0 };
+                Some([r + shade, g + shade, b + shade, 255])
+            } else {
+                let (dx, dy) = (tx as i32 - 8, ty as i32 - 8);
+                let [r, g, b] = decoration[index];
+                (dx * dx + dy * dy <= 16 + index as i32 * 6).then_some([r, g, b, 255])
+            };
+            if let Some(rgba) = rgba {
+                pixels[(y * width + x) * 4..(y * width + x) * 4 + 4].copy_from_slice(&rgba);
+            }
+        }
+    }
+    Atlas::new(width as u32, height as u32, pixels)
+}
+
+impl Scene for TilemapScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Tilemap
+    }
+
+    fn update(&mut self) {
+        // ゆっくり向きを変えながら斜めにスクロールする
+        let angle = self.frame_count as f32 * 0.003;
+        self.camera.0 += math::cos(angle) * SCROLL_SPEED;
+        self.camera.1 += (0.5 + 0.5 * math::sin(angle)) * SCROLL_SPEED;
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        // 画面と重なるタイルだけを列挙する（カリング）
+        let size = self.tile_size;
+        let first_x = (self.camera.0 / size).floor() as i32;
+        let first_y = (self.camera.1 / size).floor() as i32;
+        let columns = (self.width / size).ceil() as i32 + 1;
+        let rows = (self.height / size).ceil() as i32 + 1;
+
+        self.sprites.clear();
+        for layer in 0..2 {
+            for ty in first_y..first_y + rows {
+                for tx in first_x..first_x + columns {
+                    let hash = tile_hash(tx, ty);
+                    let index = if layer == 0 {
+                        hash % GROUND_TILES
+                    } else if (hash >> 8).is_multiple_of(DECORATION_RARITY) {
+                        GROUND_TILES + (hash >> 16) % DECORATION_TILES
+                    } else {
+                        continue;
+                    };
+                    let x = tx as f32 * size - self.camera.0;
+                    let y = ty as f32 * size - self.camera.1;
+                    self.sprites.push(Sprite {
+                        transform: [size, 0.0, 0.0, size, x.floor(), y.floor()],
+                        source: tile_source(index),
+                    });
+                }
+            }
+        }
+        surface.draw_sprites(&self.atlas, &self.sprites);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn atlas_mut(&mut self) -> Option<&mut Atlas> {
+        Some(&mut self.atlas)
+    }
+
+    // 0.0 で 64px、0.5 で 16px、1.0 で 4px（タイル数は 256 倍まで増える）
+    fn set_load(&mut self, load: f32) {
+        self.tile_size = 64.0 / (2.0f32).powf(load * 4.0);
+    }
+}
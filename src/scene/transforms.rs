@@ -0,0 +1,170 @@
+use rand::Rng;
+use std::f32::consts::TAU;
+
+use super::{Atlas, PlacedSprite, Scene, SceneKind, Sprite, Surface};
+use crate::events::EventKind;
+use crate::i18n::{tr, Text};
+use crate::math;
+use crate::simulation::hsl_to_rgb;
+
+// 負荷 1.0 のときの数（既定はその半分）
+const MAX_OBJECTS: usize = 10_000;
+const SPRITE_SIZE: f32 = 16.0;
+// アトラスに並べる色違いの矢印の数
+const VARIANTS: usize = 4;
+
+struct Object {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    angle: f32,
+    spin: f32,
+    // 拡大率を揺らす位相
+    phase: f32,
+    variant: usize,
+}
+
+// 回転・拡大しながら動く矢印のスプライトを大量に描く（変換APIのコストの計測用）
+// precomputed でなければ1個ごとに save / translate / rotate / scale / restore し、
+// precomputed なら WASM で計算した行列を setTransform で1回だけ設定する
+// Canvas2D 以外は行列を計算して draw_sprites で描く（2つは同じ絵になる）
+pub(crate) struct TransformScene {
+    width: f32,
+    height: f32,
+    precomputed: bool,
+    objects: Vec<Object>,
+    atlas: Atlas,
+    placed: Vec<PlacedSprite>,
+    sprites: Vec<Sprite>,
+    frame_count: u32,
+}
+
+impl TransformScene {
+    pub fn new(width: f32, height: f32, precomputed: bool) -> TransformScene {
+        let mut scene = TransformScene {
+            width,
+            height,
+            precomputed,
+            objects: Vec::new(),
+            atlas: create_atlas(),
+            placed: Vec::new(),
+            sprites: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+// 64x16 のアトラス: 右向きの矢印を色違いで4つ並べる
+fn create_atlas() -> Atlas {
+    let size = SPRITE_SIZE as usize;
+    let (width, height) = (size * VARIANTS, size);
+    let mut pixels = vec![0u8; width * height * 4];
+    for variant in 0..VARIANTS {
+        let (r, g, b) = hsl_to_rgb(variant as f32 * 360.0 / VARIANTS as f32, 0.8, 0.6);
+        let rgba = [(r * 255.0) as u8, (g * 255.0) as u8, (b * 255.0) as u8, 255];
+        for y in 0..size {
+            for x in 0..size {
+                // 左半分は軸、右半分は先が細くなる三角形
+                let dy = (y as f32 + 0.5 - size as f32 / 2.0).abs();
+                let inside = if x < size / 2 {
+                    dy < 2.0
+                } else {
+                    dy < (size - x) as f32 * 0.9
+                };
+                if inside {
+                    let i = (y * width + variant * size + x) * 4;
+                    pixels[i..i + 4].copy_from_slice(&rgba);
+                }
+            }
+        }
+    }
+    Atlas::new(width as u32, height as u32, pixels)
+}
+
+impl Scene for TransformScene {
+    fn kind(&self) -> SceneKind {
+        if self.precomputed {
+            SceneKind::TransformsPrecomputed
+        } else {
+            SceneKind::Transforms
+        }
+    }
+
+    fn update(&mut self) {
+        let (width, height) = (self.width, self.height);
+        for object in &mut self.objects {
+            object.x += object.vx;
+            object.y += object.vy;
+            if object.x < 0.0 || object.x > width {
+                object.vx = -object.vx;
+            }
+            if object.y < 0.0 || object.y > height {
+                object.vy = -object.vy;
+            }
+            object.angle = (object.angle + object.spin) % TAU;
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.05, 0.05, 0.05]);
+        let t = self.frame_count as f32 * 0.05;
+        self.placed.clear();
+        self.placed
+            .extend(self.objects.iter().map(|object| PlacedSprite {
+                x: object.x,
+                y: object.y,
+                rotation: object.angle,
+                scale: 1.0 + math::sin(t + object.phase) * 0.5,
+                size: SPRITE_SIZE,
+                source: [
+                    object.variant as f32 * SPRITE_SIZE,
+                    0.0,
+                    SPRITE_SIZE,
+                    SPRITE_SIZE,
+                ],
+            }));
+        if surface.draw_sprites_placed(&self.atlas, &self.placed, self.precomputed) {
+            return;
+        }
+        surface.report(EventKind::FallbackUsed, &tr(Text::TransformFallback, &[]));
+        self.sprites.clear();
+        self.sprites.extend(self.placed.iter().map(|sprite| Sprite {
+            transform: sprite.transform(),
+            source: sprite.source,
+        }));
+        surface.draw_sprites(&self.atlas, &self.sprites);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn atlas_mut(&mut self) -> Option<&mut Atlas> {
+        Some(&mut self.atlas)
+    }
+
+    // スプライトの数を負荷に比例させる（足りない分だけ足し、多い分は末尾から減らす）
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_OBJECTS as f32 * load).round() as usize;
+        let mut rng = crate::rng::rng();
+        while self.objects.len() < count {
+            let angle = rng.gen::<f32>() * TAU;
+            let speed = 0.5 + rng.gen::<f32>() * 1.5;
+            self.objects.push(Object {
+                x: rng.gen::<f32>() * self.width,
+                y: rng.gen::<f32>() * self.height,
+                vx: math::cos(angle) * speed,
+                vy: math::sin(angle) * speed,
+                angle,
+                spin: (rng.gen::<f32>() - 0.5) * 0.1,
+                phase: rng.gen::<f32>() * TAU,
+                variant: rng.gen_range(0..VARIANTS),
+            });
+        }
+        self.objects.truncate(count);
+    }
+}
@@ -0,0 +1,172 @@
+use rand::Rng;
+use std::f32::consts::PI;
+
+use super::{CubicBezier, Polygon, Scene, SceneKind, Surface};
+use crate::math;
+
+// 負荷 1.0 のときの図形の数（既定はその半分）
+const MAX_GLYPHS: usize = 3_000;
+// 1つの輪郭を作る3次ベジェ曲線の数
+const SEGMENTS: usize = 6;
+const RADIUS: f32 = 18.0;
+// 折れ線に分解するときの1本の線分のおおよその長さ(px)
+const FLATTEN_TOLERANCE: f32 = 2.0;
+// 1本の曲線を分ける数の上限（拡大しても頂点数が膨らみすぎないように）
+const MAX_STEPS: usize = 16;
+const PALETTE_SIZE: u32 = 8;
+
+struct Glyph {
+    x: f32,
+    y: f32,
+    rotation: f32,
+    spin: f32,
+    phase: f32,
+    // 頂点ごとの半径の比（字形ごとの凹凸）
+    shape: [f32; SEGMENTS],
+}
+
+// 文字の輪郭のような、3次ベジェ曲線でできた閉じたパスを大量に描く
+// 毎フレーム制御点を動かし、WASM で曲線を折れ線に分解して塗りつぶしてから、輪郭を曲線のまま線で描く
+// 塗りは WebGL が耳切り分割した三角形、Canvas2D が fill。輪郭は WebGL が折れ線、Canvas2D が bezierCurveTo
+pub(crate) struct VectorScene {
+    width: f32,
+    height: f32,
+    glyphs: Vec<Glyph>,
+    polygons: Vec<Polygon>,
+    outlines: Vec<CubicBezier>,
+    frame_count: u32,
+}
+
+impl VectorScene {
+    pub fn new(width: f32, height: f32) -> VectorScene {
+        let mut scene = VectorScene {
+            width,
+            height,
+            glyphs: Vec::new(),
+            polygons: Vec::new(),
+            outlines: Vec::new(),
+            frame_count: 0,
+        };
+        scene.set_load(0.5);
+        scene
+    }
+}
+
+impl Scene for VectorScene {
+    fn kind(&self) -> SceneKind {
+        SceneKind::Vector
+    }
+
+    fn update(&mut self) {
+        for glyph in &mut self.glyphs {
+            glyph.rotation += glyph.spin;
+        }
+        self.frame_count += 1;
+    }
+
+    fn render(&mut self, surface: &mut dyn Surface) {
+        surface.clear([0.04, 0.04, 0.05]);
+
+        let t = self.frame_count as f32 * 0.04;
+        self.outlines.clear();
+        for (glyph, polygon) in self.glyphs.iter().zip(&mut self.polygons) {
+            let scale = 1.0 + 0.3 * math::sin(t + glyph.phase);
+            let outline_rgb = polygon.rgb.map(|c| c * 0.4);
+            polygon.points.clear();
+            for segment in glyph_segments(glyph, t, scale) {
+                flatten(&segment, &mut polygon.points);
+                self.outlines.push(CubicBezier {
+                    points: segment,
+                    rgb: outline_rgb,
+                });
+            }
+        }
+        surface.fill_polygons(&self.polygons);
+        surface.stroke_beziers(&self.outlines);
+    }
+
+    fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    fn set_load(&mut self, load: f32) {
+        let count = (MAX_GLYPHS as f32 * load).round() as usize;
+        let (width, height) = (self.width, self.height);
+        let mut rng = crate::rng::rng();
+        let glyphs = &mut self.glyphs;
+        let polygons = &mut self.polygons;
+        glyphs.truncate(count);
+        polygons.truncate(count);
+        while glyphs.len() < count {
+            glyphs.push(Glyph {
+                x: rng.gen::<f32>() * width,
+                y: rng.gen::<f32>() * height,
+                rotation: rng.gen::<f32>() * 2.0 * PI,
+                spin: (rng.gen::<f32>() - 0.5) * 0.04,
+                phase: rng.gen::<f32>() * 2.0 * PI,
+                shape: std::array::from_fn(|_| 0.45 + rng.gen::<f32>() * 0.55),
+            });
+            let hue = rng.gen_range(0..PALETTE_SIZE) as f32 * 360.0 / PALETTE_SIZE as f32;
+            let (r, g, b) = crate::simulation::hsl_to_rgb(hue, 0.55, 0.6);
+            polygons.push(Polygon {
+                points: Vec::with_capacity(SEGMENTS * MAX_STEPS),
+                rgb: [r, g, b],
+            });
+        }
+
+        // Canvas2D で同じ色をまとめて塗れるように色順に並べる（図形との対応は順番だけ）
+        polygons.sort_by(|a, b| {
+            a.rgb
+                .partial_cmp(&b.rgb)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+    }
+}
+
+// 今の時刻の輪郭を作る曲線（終点が次の曲線の始点になる閉じたパス）
+fn glyph_segments(glyph: &Glyph, t: f32, scale: f32) -> [[(f32, f32); 4]; SEGMENTS] {
+    let step = 2.0 * PI / SEGMENTS as f32;
+    let anchor = |i: usize| {
+        let angle = glyph.rotation + i as f32 * step;
+        let radius = RADIUS * scale * glyph.shape[i % SEGMENTS];
+        (
+            glyph.x + math::cos(angle) * radius,
+            glyph.y + math::sin(angle) * radius,
+        )
+    };
+    std::array::from_fn(|i| {
+        let (start, end) = (anchor(i), anchor(i + 1));
+        // 制御点を弦の外側へ押し出す量が揺れるので、膨らみとくびれが毎フレーム変わる
+        let bulge = RADIUS * scale * 0.3 * math::sin(t * 1.7 + glyph.phase + i as f32);
+        let mid_angle = glyph.rotation + (i as f32 + 0.5) * step;
+        let (nx, ny) = (math::cos(mid_angle) * bulge, math::sin(mid_angle) * bulge);
+        let lerp = |f: f32| {
+            (
+                start.0 + (end.0 - start.0) * f,
+                start.1 + (end.1 - start.1) * f,
+            )
+        };
+        let (c1, c2) = (lerp(1.0 / 3.0), lerp(2.0 / 3.0));
+        [start, (c1.0 + nx, c1.1 + ny), (c2.0 + nx, c2.1 + ny), end]
+    })
+}
+
+// 曲線を折れ線にして out の後ろに足す（終点は次の曲線の始点と重なるので足さない）
+// 分ける数は制御点を結んだ長さ（曲線の長さの上限）から決める
+fn flatten(points: &[(f32, f32); 4], out: &mut Vec<(f32, f32)>) {
+    let hull: f32 = points
+        .windows(2)
+        .map(|w| math::sqrt((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)))
+        .sum();
+    let steps = ((hull / FLATTEN_TOLERANCE).ceil() as usize).clamp(1, MAX_STEPS);
+    let [p0, p1, p2, p3] = *points;
+    for i in 0..steps {
+        let u = i as f32 / steps as f32;
+        let v = 1.0 - u;
+        let (a, b, c, d) = (v * v * v, 3.0 * v * v * u, 3.0 * v * u * u, u * u * u);
+        out.push((
+            a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+            a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use wasm_bindgen::prelude::*;
+
+use crate::report;
+use crate::timing;
+
+// 1フレームの予算の上限(ms)
+#[cfg(target_arch = "wasm32")]
+const MAX_BUDGET_MS: f64 = 100.0;
+
+// get_scheduler_stats() が返す、分けて進めた重い処理の記録
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SchedulerStats {
+    // 1フレームの予算(ms)。0 なら分けずに一度に全部進める
+    pub budget_ms: f64,
+    // 処理を進めたフレーム数と、進めた回数
+    pub frames: u32,
+    pub slices: u32,
+    // 終わった処理の数と、次のフレームに持ち越している処理の数
+    pub completed: u32,
+    pub pending: u32,
+    // 使った時間の合計と、1フレームで使った最長の時間(ms)
+    pub total_ms: f64,
+    pub longest_frame_ms: f64,
+    // 予算を超えたフレーム数（1回分の区切りより短い予算だと超える）
+    pub over_budget: u32,
+}
+
+#[wasm_bindgen]
+impl SchedulerStats {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("budget_ms", self.budget_ms)
+            .number("frames", self.frames as f64)
+            .number("slices", self.slices as f64)
+            .number("completed", self.completed as f64)
+            .number("pending", self.pending as f64)
+            .number("total_ms", self.total_ms)
+            .number("longest_frame_ms", self.longest_frame_ms)
+            .number("over_budget", self.over_budget as f64)
+            .finish()
+    }
+}
+
+// 処理に渡す、今のフレームの残り時間
+pub(crate) struct Slice {
+    deadline: f64,
+}
+
+impl Slice {
+    // まだ続けてよければ true（区切りのよいところで調べる）
+    pub fn has_time(&self) -> bool {
+        timing::now_ms() < self.deadline
+    }
+}
+
+// ときどき走る重い処理（クラスタリングなど）を、1フレームの予算に収まる分ずつ進める
+// 各処理は区切りごとに Slice::has_time() を見て、時間がなければ途中の状態を持ったまま戻る
+// （1回に少なくとも1区切りは進めるので、予算が短すぎても終わらなくなることはない）
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FrameScheduler {
+    // None なら分けない（以前と同じく、その処理のフレームでまとめて計算する）
+    budget_ms: Option<f64>,
+    // 今のフレームの締め切りと、使った時間
+    deadline: f64,
+    spent_ms: f64,
+    // 今のフレームで処理を進めたか・予算を超えたか
+    worked: bool,
+    over: bool,
+    stats: SchedulerStats,
+}
+
+impl FrameScheduler {
+    #[cfg(target_arch = "wasm32")]
+    pub fn budget_ms(&self) -> Option<f64> {
+        self.budget_ms
+    }
+
+    // 0 以下か NaN なら分けない。範囲内に丸めて実際に使う値を返す
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_budget_ms(&mut self, budget_ms: f64) -> f64 {
+        self.budget_ms = (budget_ms > 0.0).then(|| budget_ms.min(MAX_BUDGET_MS));
+        self.budget_ms.unwrap_or(0.0)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn stats(&self) -> SchedulerStats {
+        SchedulerStats {
+            budget_ms: self.budget_ms.unwrap_or(0.0),
+            ..self.stats
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn reset_stats(&mut self) {
+        self.stats = SchedulerStats {
+            pending: self.stats.pending,
+            ..SchedulerStats::default()
+        };
+    }
+
+    // 1ステップの初めに呼ぶ
+    pub fn begin_frame(&mut self) {
+        let now = timing::now_ms();
+        self.deadline = self.budget_ms.map_or(f64::INFINITY, |budget| now + budget);
+        self.spent_ms = 0.0;
+        self.worked = false;
+        self.over = false;
+        self.stats.pending = 0;
+    }
+
+    // work を今のフレームの残りの予算で進める。work は終わったら true を返す
+    pub fn run(&mut self, work: impl FnOnce(&Slice) -> bool) -> bool {
+        let start = timing::now_ms();
+        let done = work(&Slice {
+            deadline: self.deadline,
+        });
+        let elapsed = timing::now_ms() - start;
+
+        let stats = &mut self.stats;
+        if !self.worked {
+            self.worked = true;
+            stats.frames += 1;
+        }
+        stats.slices += 1;
+        stats.total_ms += elapsed;
+        if done {
+            stats.completed += 1;
+        } else {
+            stats.pending += 1;
+        }
+        self.spent_ms += elapsed;
+        stats.longest_frame_ms = stats.longest_frame_ms.max(self.spent_ms);
+        let over = self.budget_ms.is_some_and(|budget| self.spent_ms > budget);
+        if over && !self.over {
+            self.over = true;
+            stats.over_budget += 1;
+        }
+        done
+    }
+}
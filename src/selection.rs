@@ -0,0 +1,59 @@
+use crate::simulation::ParticleSet;
+
+// 矩形範囲選択（角の順序は問わない）。選ばれたパーティクルのインデックスを返す
+pub(crate) fn select_region(particles: &ParticleSet, x0: f32, y0: f32, x1: f32, y1: f32) -> Vec<u32> {
+    let (min_x, max_x) = (x0.min(x1), x0.max(x1));
+    let (min_y, max_y) = (y0.min(y1), y0.max(y1));
+    particles
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.x >= min_x && p.x <= max_x && p.y >= min_y && p.y <= max_y)
+        .map(|(i, _)| i as u32)
+        .collect()
+}
+
+// 多角形（投げ縄）選択。points は [x0, y0, x1, y1, ...] の頂点列で、最後の辺は自動で閉じる
+// 外接矩形で先に弾いてから交差数判定（crossing number）を行う
+pub(crate) fn select_polygon(particles: &ParticleSet, points: &[f32]) -> Vec<u32> {
+    let vertices: Vec<(f32, f32)> = points.chunks_exact(2).map(|c| (c[0], c[1])).collect();
+    if vertices.len() < 3 {
+        return Vec::new();
+    }
+
+    let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+    let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+    for &(x, y) in &vertices {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+
+    // 辺ごとに (y0, y1, x0, dx/dy) を前計算しておく（水平な辺は交差しないので除く）
+    let edges: Vec<(f32, f32, f32, f32)> = vertices
+        .iter()
+        .zip(vertices.iter().cycle().skip(1))
+        .filter(|(a, b)| a.1 != b.1)
+        .map(|(&(x0, y0), &(x1, y1))| (y0, y1, x0, (x1 - x0) / (y1 - y0)))
+        .collect();
+
+    particles
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| p.x >= min_x && p.x <= max_x && p.y >= min_y && p.y <= max_y)
+        .filter(|(_, p)| {
+            let mut inside = false;
+            for &(y0, y1, x0, slope) in &edges {
+                if (y0 > p.y) != (y1 > p.y) && p.x < x0 + (p.y - y0) * slope {
+                    inside = !inside;
+                }
+            }
+            inside
+        })
+        .map(|(i, _)| i as u32)
+        .collect()
+}
+
+// 選択中のパーティクルを強調表示する色とサイズ倍率
+pub(crate) const HIGHLIGHT_RGB: [f32; 3] = [1.0, 1.0, 1.0];
+pub(crate) const HIGHLIGHT_SCALE: f32 = 2.0;
@@ -0,0 +1,294 @@
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlProgram, WebGlRenderingContext, WebGlShader};
+
+use crate::i18n::{tr, Text};
+
+// リンク済みプログラムのキャッシュ
+// プログラムは作成したコンテキストでしか使えないので、コンテキストとソースのハッシュで引く
+struct CachedProgram {
+    gl: WebGlRenderingContext,
+    source_hash: u64,
+    program: WebGlProgram,
+}
+
+thread_local! {
+    static PROGRAM_CACHE: RefCell<Vec<CachedProgram>> = const { RefCell::new(Vec::new()) };
+    // コンテキストごとの、まだ dispose() していないインスタンスの数
+    static CONTEXT_USERS: RefCell<Vec<(WebGlRenderingContext, u32)>> = const { RefCell::new(Vec::new()) };
+}
+
+// キャッシュにあればそれを返し、なければコンパイル・リンクして登録する
+// 戻り値の bool はキャッシュヒットしたかどうか
+pub(crate) fn get_or_create_program(
+    gl: &WebGlRenderingContext,
+    vertex_source: &str,
+    fragment_source: &str,
+) -> Result<(WebGlProgram, bool), String> {
+    let source_hash = hash_sources(vertex_source, fragment_source);
+
+    let cached = PROGRAM_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        // コンテキストロスト等で無効になったものは捨てる
+        cache.retain(|entry| entry.gl.is_program(Some(&entry.program)));
+        cache
+            .iter()
+            .find(|entry| entry.source_hash == source_hash && entry.gl == *gl)
+            .map(|entry| entry.program.clone())
+    });
+    if let Some(program) = cached {
+        return Ok((program, true));
+    }
+
+    let vert_shader = compile_shader(gl, WebGlRenderingContext::VERTEX_SHADER, vertex_source)?;
+    let frag_shader = compile_shader(gl, WebGlRenderingContext::FRAGMENT_SHADER, fragment_source)?;
+    let program = link_program(gl, &vert_shader, &frag_shader);
+    // リンクした後はシェーダーを持っておく必要がないので、プログラムと一緒に消えるように削除の印を付ける
+    gl.delete_shader(Some(&vert_shader));
+    gl.delete_shader(Some(&frag_shader));
+    let program = program?;
+
+    PROGRAM_CACHE.with(|cache| {
+        cache.borrow_mut().push(CachedProgram {
+            gl: gl.clone(),
+            source_hash,
+            program: program.clone(),
+        })
+    });
+    Ok((program, false))
+}
+
+// キャッシュを空にする（プログラム自体は使用中のインスタンスが持ち続ける）
+#[wasm_bindgen]
+pub fn clear_shader_cache() {
+    PROGRAM_CACHE.with(|cache| cache.borrow_mut().clear());
+}
+
+#[wasm_bindgen]
+pub fn shader_cache_size() -> usize {
+    PROGRAM_CACHE.with(|cache| cache.borrow().len())
+}
+
+// インスタンスが gl を使い始めたことを記録する（破棄したときの Drop の release_context() と対にする）
+pub(crate) fn retain_context(gl: &WebGlRenderingContext) {
+    CONTEXT_USERS.with(|users| {
+        let mut users = users.borrow_mut();
+        match users.iter_mut().find(|(context, _)| context == gl) {
+            Some((_, count)) => *count += 1,
+            None => users.push((gl.clone(), 1)),
+        }
+    });
+}
+
+// インスタンスが gl を使い終えたことを記録する
+// プログラムは同じコンテキストのインスタンスで共有しているので、最後の1つが使い終えたときにだけ消す
+pub(crate) fn release_context(gl: &WebGlRenderingContext) {
+    let last = CONTEXT_USERS.with(|users| {
+        let mut users = users.borrow_mut();
+        let Some(index) = users.iter().position(|(context, _)| context == gl) else {
+            return true;
+        };
+        users[index].1 -= 1;
+        if users[index].1 > 0 {
+            return false;
+        }
+        users.swap_remove(index);
+        true
+    });
+    if !last {
+        return;
+    }
+    PROGRAM_CACHE.with(|cache| {
+        cache.borrow_mut().retain(|entry| {
+            if entry.gl != *gl {
+                return true;
+            }
+            gl.delete_program(Some(&entry.program));
+            false
+        })
+    });
+}
+
+fn hash_sources(vertex_source: &str, fragment_source: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    vertex_source.hash(&mut hasher);
+    fragment_source.hash(&mut hasher);
+    hasher.finish()
+}
+
+// パーティクルのシェーダーの浮動小数点の精度
+// モバイルの GPU では mediump と highp で速さも結果も大きく変わることがある
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum ShaderPrecision {
+    // ソースに書いてあるまま（フラグメントは mediump、頂点は既定の highp）
+    #[default]
+    Default = 0,
+    // 頂点・フラグメントともに mediump
+    Mediump = 1,
+    // 頂点・フラグメントともに highp
+    Highp = 2,
+}
+
+impl ShaderPrecision {
+    pub fn name(self) -> &'static str {
+        match self {
+            ShaderPrecision::Default => "default",
+            ShaderPrecision::Mediump => "mediump",
+            ShaderPrecision::Highp => "highp",
+        }
+    }
+}
+
+// 既存の float の精度指定を外し、precision の指定を先頭（#version の次の行）に入れる
+pub(crate) fn with_precision(source: &str, precision: ShaderPrecision) -> String {
+    let qualifier = match precision {
+        ShaderPrecision::Default => return source.to_string(),
+        ShaderPrecision::Mediump => "mediump",
+        ShaderPrecision::Highp => "highp",
+    };
+    let declaration = format!("precision {} float;", qualifier);
+    let mut lines: Vec<&str> = source
+        .lines()
+        .filter(|line| {
+            let line = line.trim();
+            !(line.starts_with("precision ") && line.ends_with(" float;"))
+        })
+        .collect();
+    let at = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with("#version"))
+        .map_or(0, |index| index + 1);
+    lines.insert(at, &declaration);
+    lines.join("\n")
+}
+
+// 精度を変えて同じフレームを描いた結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct PrecisionComparison {
+    pub frames: u32,
+    // render() から描き終わるまで（gl.finish()）の1フレームあたりの平均(ms)
+    pub mediump_ms: f64,
+    pub highp_ms: f64,
+    // 最後のフレームの描画結果（RGBA）のハッシュ
+    pub mediump_hash: String,
+    pub highp_hash: String,
+    // 色が1でも違ったピクセルの数
+    pub differing_pixels: u32,
+    // highp のシェーダーを作れなかったら false（highp_ms などは 0）
+    pub highp_supported: bool,
+}
+
+// compare_shader_precision() で毎回同じ初期状態から描くためのシード
+pub(crate) const PRECISION_SEED: u64 = 42;
+
+// 1つの精度で計測した結果
+pub(crate) struct PrecisionRun {
+    pub mean_ms: f64,
+    pub pixels: Vec<u8>,
+}
+
+impl PrecisionComparison {
+    pub(crate) fn new(
+        frames: u32,
+        mediump: &PrecisionRun,
+        highp: Option<&PrecisionRun>,
+    ) -> PrecisionComparison {
+        let differing_pixels = highp.map_or(0, |highp| {
+            mediump
+                .pixels
+                .chunks_exact(4)
+                .zip(highp.pixels.chunks_exact(4))
+                .filter(|(a, b)| a != b)
+                .count() as u32
+        });
+        PrecisionComparison {
+            frames,
+            mediump_ms: mediump.mean_ms,
+            highp_ms: highp.map_or(0.0, |highp| highp.mean_ms),
+            mediump_hash: pixel_hash(&mediump.pixels),
+            highp_hash: highp.map_or_else(String::new, |highp| pixel_hash(&highp.pixels)),
+            differing_pixels,
+            highp_supported: highp.is_some(),
+        }
+    }
+}
+
+// 描画バッファ全体を読み戻す（描いた直後に呼ぶ）
+pub(crate) fn read_drawing_buffer(gl: &WebGlRenderingContext) -> Result<Vec<u8>, JsValue> {
+    let width = gl.drawing_buffer_width();
+    let height = gl.drawing_buffer_height();
+    let mut pixels = vec![0u8; width.max(0) as usize * height.max(0) as usize * 4];
+    gl.read_pixels_with_opt_u8_array(
+        0,
+        0,
+        width,
+        height,
+        WebGlRenderingContext::RGBA,
+        WebGlRenderingContext::UNSIGNED_BYTE,
+        Some(&mut pixels),
+    )?;
+    Ok(pixels)
+}
+
+fn pixel_hash(pixels: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    pixels.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+// シェーダーコンパイル
+pub(crate) fn compile_shader(
+    gl: &WebGlRenderingContext,
+    shader_type: u32,
+    source: &str,
+) -> Result<WebGlShader, String> {
+    let shader = gl
+        .create_shader(shader_type)
+        .ok_or_else(|| tr(Text::ShaderCreationFailed, &[]))?;
+    gl.shader_source(&shader, source);
+    gl.compile_shader(&shader);
+
+    if gl
+        .get_shader_parameter(&shader, WebGlRenderingContext::COMPILE_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(shader)
+    } else {
+        Err(gl
+            .get_shader_info_log(&shader)
+            .unwrap_or_else(|| tr(Text::ShaderCompileFailed, &[])))
+    }
+}
+
+// プログラムリンク
+pub(crate) fn link_program(
+    gl: &WebGlRenderingContext,
+    vert_shader: &WebGlShader,
+    frag_shader: &WebGlShader,
+) -> Result<WebGlProgram, String> {
+    let program = gl
+        .create_program()
+        .ok_or_else(|| tr(Text::ShaderCreationFailed, &[]))?;
+
+    gl.attach_shader(&program, vert_shader);
+    gl.attach_shader(&program, frag_shader);
+    gl.link_program(&program);
+
+    if gl
+        .get_program_parameter(&program, WebGlRenderingContext::LINK_STATUS)
+        .as_bool()
+        .unwrap_or(false)
+    {
+        Ok(program)
+    } else {
+        Err(gl
+            .get_program_info_log(&program)
+            .unwrap_or_else(|| tr(Text::ProgramLinkFailed, &[])))
+    }
+}
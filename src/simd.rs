@@ -0,0 +1,282 @@
+// 物理演算と HSL→RGB を4個ずつまとめて計算する（set_simd_enabled(true) のとき、simd フィーチャーが必要）
+// wasm32 で simd128 を有効にしてビルドしたときは v128 の命令を使い、それ以外は同じ計算を4要素の配列で行う
+// 演算の種類と順はスカラー版の integrate / hsl_to_rgb と同じにしてあるので、結果はビット単位で一致する
+// （分岐は両方を計算して選ぶ。% は値の範囲が決まっているので引き算で同じ値になる）
+
+use crate::bounds::BoundaryMode;
+use crate::physics::SimulationConfig;
+#[cfg(any(target_arch = "wasm32", test))]
+use crate::simulation::hsl_to_rgb;
+use crate::simulation::Chunk;
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod lanes {
+    use core::arch::wasm32::*;
+
+    #[derive(Clone, Copy)]
+    pub struct F32x4(v128);
+
+    // 比較の結果（各要素が全ビット 1 か 0）
+    #[derive(Clone, Copy)]
+    pub struct Mask(v128);
+
+    impl F32x4 {
+        pub fn splat(value: f32) -> F32x4 {
+            F32x4(f32x4_splat(value))
+        }
+
+        pub fn load(values: &[f32]) -> F32x4 {
+            let values = &values[..4];
+            // wasm の v128.load は境界をそろえなくてよい
+            F32x4(unsafe { v128_load(values.as_ptr() as *const v128) })
+        }
+
+        pub fn store(self, out: &mut [f32]) {
+            let out = &mut out[..4];
+            unsafe { v128_store(out.as_mut_ptr() as *mut v128, self.0) }
+        }
+
+        pub fn add(self, other: F32x4) -> F32x4 {
+            F32x4(f32x4_add(self.0, other.0))
+        }
+
+        pub fn sub(self, other: F32x4) -> F32x4 {
+            F32x4(f32x4_sub(self.0, other.0))
+        }
+
+        pub fn mul(self, other: F32x4) -> F32x4 {
+            F32x4(f32x4_mul(self.0, other.0))
+        }
+
+        pub fn div(self, other: F32x4) -> F32x4 {
+            F32x4(f32x4_div(self.0, other.0))
+        }
+
+        pub fn abs(self) -> F32x4 {
+            F32x4(f32x4_abs(self.0))
+        }
+
+        pub fn floor(self) -> F32x4 {
+            F32x4(f32x4_floor(self.0))
+        }
+
+        pub fn lt(self, other: F32x4) -> Mask {
+            Mask(f32x4_lt(self.0, other.0))
+        }
+
+        pub fn gt(self, other: F32x4) -> Mask {
+            Mask(f32x4_gt(self.0, other.0))
+        }
+
+        pub fn ge(self, other: F32x4) -> Mask {
+            Mask(f32x4_ge(self.0, other.0))
+        }
+    }
+
+    impl Mask {
+        pub fn or(self, other: Mask) -> Mask {
+            Mask(v128_or(self.0, other.0))
+        }
+
+        // 真の要素は a、偽の要素は b
+        pub fn select(self, a: F32x4, b: F32x4) -> F32x4 {
+            F32x4(v128_bitselect(a.0, b.0, self.0))
+        }
+    }
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_feature = "simd128")))]
+mod lanes {
+    #[derive(Clone, Copy)]
+    pub struct F32x4([f32; 4]);
+
+    #[derive(Clone, Copy)]
+    pub struct Mask([bool; 4]);
+
+    impl F32x4 {
+        pub fn splat(value: f32) -> F32x4 {
+            F32x4([value; 4])
+        }
+
+        pub fn load(values: &[f32]) -> F32x4 {
+            F32x4([values[0], values[1], values[2], values[3]])
+        }
+
+        pub fn store(self, out: &mut [f32]) {
+            out[..4].copy_from_slice(&self.0);
+        }
+
+        fn map(self, other: F32x4, f: impl Fn(f32, f32) -> f32) -> F32x4 {
+            F32x4([0, 1, 2, 3].map(|i| f(self.0[i], other.0[i])))
+        }
+
+        fn compare(self, other: F32x4, f: impl Fn(f32, f32) -> bool) -> Mask {
+            Mask([0, 1, 2, 3].map(|i| f(self.0[i], other.0[i])))
+        }
+
+        pub fn add(self, other: F32x4) -> F32x4 {
+            self.map(other, |a, b| a + b)
+        }
+
+        pub fn sub(self, other: F32x4) -> F32x4 {
+            self.map(other, |a, b| a - b)
+        }
+
+        pub fn mul(self, other: F32x4) -> F32x4 {
+            self.map(other, |a, b| a * b)
+        }
+
+        pub fn div(self, other: F32x4) -> F32x4 {
+            self.map(other, |a, b| a / b)
+        }
+
+        #[cfg(any(target_arch = "wasm32", test))]
+        pub fn abs(self) -> F32x4 {
+            F32x4(self.0.map(f32::abs))
+        }
+
+        pub fn floor(self) -> F32x4 {
+            F32x4(self.0.map(f32::floor))
+        }
+
+        pub fn lt(self, other: F32x4) -> Mask {
+            self.compare(other, |a, b| a < b)
+        }
+
+        pub fn gt(self, other: F32x4) -> Mask {
+            self.compare(other, |a, b| a > b)
+        }
+
+        pub fn ge(self, other: F32x4) -> Mask {
+            self.compare(other, |a, b| a >= b)
+        }
+    }
+
+    impl Mask {
+        pub fn or(self, other: Mask) -> Mask {
+            Mask([0, 1, 2, 3].map(|i| self.0[i] || other.0[i]))
+        }
+
+        // 真の要素は a、偽の要素は b
+        pub fn select(self, a: F32x4, b: F32x4) -> F32x4 {
+            F32x4([0, 1, 2, 3].map(|i| if self.0[i] { a.0[i] } else { b.0[i] }))
+        }
+    }
+}
+
+use lanes::F32x4;
+
+// chunk の先頭から4の倍数個を進め、計算した数を返す（残りは呼び出し側がスカラー版で計算する）
+pub(crate) fn integrate(
+    chunk: &mut Chunk,
+    width: f32,
+    height: f32,
+    hue_speed: f32,
+    config: &SimulationConfig,
+    boundary: BoundaryMode,
+) -> usize {
+    let count = chunk.len() / 4 * 4;
+    let zero = F32x4::splat(0.0);
+    let (width, height) = (F32x4::splat(width), F32x4::splat(height));
+    let gravity = F32x4::splat(config.gravity);
+    let bounce = F32x4::splat(-config.bounce);
+    let friction = F32x4::splat(config.friction);
+    let hue_speed = F32x4::splat(hue_speed);
+    let full_turn = F32x4::splat(360.0);
+
+    for i in (0..count).step_by(4) {
+        let mut x = F32x4::load(&chunk.x[i..]);
+        let mut y = F32x4::load(&chunk.y[i..]);
+        let mut vx = F32x4::load(&chunk.vx[i..]);
+        let mut vy = F32x4::load(&chunk.vy[i..]);
+        let hue = F32x4::load(&chunk.hue[i..]);
+
+        // 重力と位置更新
+        vy = vy.add(gravity);
+        x = x.add(vx);
+        y = y.add(vy);
+
+        match boundary {
+            BoundaryMode::Bounce => {
+                // 左右の壁（clamp も比較と選択で行う）
+                let outside = x.lt(zero).or(x.gt(width));
+                vx = outside.select(vx.mul(bounce), vx);
+                let clamped = x.lt(zero).select(zero, x);
+                let clamped = clamped.gt(width).select(width, clamped);
+                x = outside.select(clamped, x);
+
+                // 天井
+                let top = y.lt(zero);
+                vy = top.select(vy.mul(bounce), vy);
+                y = top.select(zero, y);
+
+                // 床と摩擦
+                let bottom = y.gt(height);
+                vy = bottom.select(vy.mul(bounce), vy);
+                y = bottom.select(height, y);
+                vx = bottom.select(vx.mul(friction), vx);
+            }
+            // スカラー版と同じく floor() で折り返す
+            BoundaryMode::Wrap => {
+                let outside = x.lt(zero).or(x.gt(width));
+                x = outside.select(x.sub(width.mul(x.div(width).floor())), x);
+                let outside = y.lt(zero).or(y.gt(height));
+                y = outside.select(y.sub(height.mul(y.div(height).floor())), y);
+            }
+            BoundaryMode::Respawn | BoundaryMode::None => {}
+        }
+
+        // 色相は 0〜360 に収まっているので、% 360 は 360 以上のときに引くのと同じ
+        let hue = hue.add(hue_speed);
+        let hue = hue.ge(full_turn).select(hue.sub(full_turn), hue);
+
+        x.store(&mut chunk.x[i..]);
+        y.store(&mut chunk.y[i..]);
+        vx.store(&mut chunk.vx[i..]);
+        vy.store(&mut chunk.vy[i..]);
+        hue.store(&mut chunk.hue[i..]);
+    }
+    count
+}
+
+// 色相（度）ごとに彩度 1.0・明度 0.5 の RGB を colors に r, g, b の順で足す
+#[cfg(any(target_arch = "wasm32", test))]
+pub(crate) fn hues_to_rgb(hues: &[f32], colors: &mut Vec<f32>) {
+    let (s, l) = (1.0_f32, 0.5_f32);
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let m = l - c / 2.0;
+    let (zero, one, two) = (F32x4::splat(0.0), F32x4::splat(1.0), F32x4::splat(2.0));
+    let (c_lanes, m_lanes) = (F32x4::splat(c), F32x4::splat(m));
+    let sixty = F32x4::splat(60.0);
+    let bounds = [1.0, 2.0, 3.0, 4.0, 5.0].map(F32x4::splat);
+
+    let count = hues.len() / 4 * 4;
+    colors.reserve(hues.len() * 3);
+    let (mut r, mut g, mut b) = ([0.0; 4], [0.0; 4], [0.0; 4]);
+    for i in (0..count).step_by(4) {
+        let h_prime = F32x4::load(&hues[i..]).div(sixty);
+        // h_prime % 2.0（0 以上なので h - 2 * floor(h / 2) と同じ値になる）
+        let modulo = h_prime.sub(two.mul(h_prime.div(two).floor()));
+        let x = c_lanes.mul(one.sub(modulo.sub(one).abs()));
+
+        // スカラー版の if の連鎖を後ろの区間から順に選ぶ
+        let sector = |values: [F32x4; 6]| {
+            let mut value = values[5];
+            for k in (0..5).rev() {
+                value = h_prime.lt(bounds[k]).select(values[k], value);
+            }
+            value.add(m_lanes)
+        };
+        let c = c_lanes;
+        sector([c, x, zero, zero, x, c]).store(&mut r);
+        sector([x, c, c, x, zero, zero]).store(&mut g);
+        sector([zero, zero, x, c, c, x]).store(&mut b);
+        for ((r, g), b) in r.iter().zip(&g).zip(&b) {
+            colors.extend_from_slice(&[*r, *g, *b]);
+        }
+    }
+    for &hue in &hues[count..] {
+        let (r, g, b) = hsl_to_rgb(hue, s, l);
+        colors.extend_from_slice(&[r, g, b]);
+    }
+}
@@ -0,0 +1,1889 @@
+use rand::Rng;
+use std::f32::consts::PI;
+use wasm_bindgen::prelude::*;
+
+use crate::attractor::{Attractor, Attractors};
+use crate::bounds::{BoundaryMode, OutOfBounds};
+#[cfg(target_arch = "wasm32")]
+use crate::camera::Camera;
+use crate::clustering::KMeans;
+use crate::collision::SpatialHash;
+#[cfg(target_arch = "wasm32")]
+use crate::color_theme::ColorTheme;
+use crate::emitter::{Emitter, Emitters};
+#[cfg(target_arch = "wasm32")]
+use crate::explosion::Charge;
+use crate::explosion::{ChargeQueue, ExplosionConfig};
+use crate::forces::ForceField;
+#[cfg(target_arch = "wasm32")]
+use crate::frame_cap::FrameCap;
+use crate::i18n::{tr, Text};
+use crate::interaction::PointerForces;
+#[cfg(target_arch = "wasm32")]
+use crate::lod::LevelOfDetail;
+use crate::math;
+use crate::memory;
+use crate::obstacle::Obstacles;
+use crate::physics::SimulationConfig;
+use crate::projection::{self, ProjectionMode};
+use crate::particle_style::ParticleStyle;
+use crate::picking::{self, ParticleInfo};
+use crate::scheduler::FrameScheduler;
+use crate::simd;
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+use crate::snapshot::{self, Snapshot};
+use crate::timestep::FixedTimestep;
+
+const HUE_SPEED: f32 = 0.3;
+// stir() でかき混ぜる範囲(px)と、動かした距離のうち速度に加える割合
+#[cfg(target_arch = "wasm32")]
+const STIR_RADIUS: f32 = 80.0;
+#[cfg(target_arch = "wasm32")]
+const STIR_STRENGTH: f32 = 0.3;
+
+// 平均速度がしきい値を下回ったら止まった（落ち着いた）とみなす
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Settle {
+    // 平均速度(px/フレーム)
+    pub threshold: f32,
+    // 検出を始めたフレーム
+    #[cfg(target_arch = "wasm32")]
+    pub started_at: u32,
+    // 最初にしきい値を下回ったフレーム
+    pub settled_at: Option<u32>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Settle {
+    // 検出を始めてから止まるまでのフレーム数
+    pub fn frames_until_settle(&self) -> Option<u32> {
+        // restore() で上限近くから数え始めたときは折り返すので、差も折り返して取る
+        self.settled_at.map(|frame| frame.wrapping_sub(self.started_at))
+    }
+}
+
+// 1パーティクル分の値（保存は ParticleSet の成分ごとの配列で行う）
+#[derive(Clone, Copy)]
+pub(crate) struct Particle {
+    pub x: f32,
+    pub y: f32,
+    pub vx: f32,
+    pub vy: f32,
+    pub hue: f32,
+}
+
+// パーティクルの状態を成分ごとの配列（SoA）で持つ
+// 描画は位置と色相しか読まず、物理演算は同じ成分を続けて読むので、構造体の配列よりキャッシュに載りやすい
+#[derive(Clone, Default)]
+pub(crate) struct ParticleSet {
+    pub x: Vec<f32>,
+    pub y: Vec<f32>,
+    pub vx: Vec<f32>,
+    pub vy: Vec<f32>,
+    pub hue: Vec<f32>,
+    // 残りの寿命（1.0 で生まれ、0 以下で尽きる。寿命のないパーティクルは無限大）
+    // 描画は 1.0 未満のパーティクルを薄くする
+    pub life: Vec<f32>,
+    // 点の大きさに掛ける倍率と不透明度（生まれたときに ParticleStyle で選ぶ。既定は 1.0）
+    // 物理演算は読まないので Chunk には含めない
+    pub size: Vec<f32>,
+    pub alpha: Vec<f32>,
+    // 奥行きの位置と速度（ProjectionMode::Perspective のときだけ動き、Flat では 0 のまま）
+    // 重力も壁以外の力もかからないので Chunk には含めず、ステップの最後にまとめて進める
+    pub z: Vec<f32>,
+    pub vz: Vec<f32>,
+}
+
+impl ParticleSet {
+    fn with_capacity(capacity: usize) -> Result<ParticleSet, String> {
+        Ok(ParticleSet {
+            x: memory::try_vec(capacity)?,
+            y: memory::try_vec(capacity)?,
+            vx: memory::try_vec(capacity)?,
+            vy: memory::try_vec(capacity)?,
+            hue: memory::try_vec(capacity)?,
+            life: memory::try_vec(capacity)?,
+            size: memory::try_vec(capacity)?,
+            alpha: memory::try_vec(capacity)?,
+            z: memory::try_vec(capacity)?,
+            vz: memory::try_vec(capacity)?,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    // 確保済みの要素数（全成分の合計）
+    #[cfg(target_arch = "wasm32")]
+    fn capacity(&self) -> usize {
+        [&self.x, &self.y, &self.vx, &self.vy, &self.hue, &self.life, &self.size, &self.alpha, &self.z, &self.vz]
+            .iter()
+            .map(|column| column.capacity())
+            .sum()
+    }
+
+    pub fn get(&self, i: usize) -> Option<Particle> {
+        Some(Particle {
+            x: *self.x.get(i)?,
+            y: self.y[i],
+            vx: self.vx[i],
+            vy: self.vy[i],
+            hue: self.hue[i],
+        })
+    }
+
+    pub fn iter(&self) -> ParticleIter<'_> {
+        ParticleIter { set: self, next: 0 }
+    }
+
+    fn push(&mut self, p: Particle) {
+        self.x.push(p.x);
+        self.y.push(p.y);
+        self.vx.push(p.vx);
+        self.vy.push(p.vy);
+        self.hue.push(p.hue);
+        self.life.push(f32::INFINITY);
+        self.size.push(1.0);
+        self.alpha.push(1.0);
+        self.z.push(0.0);
+        self.vz.push(0.0);
+    }
+
+    // i 番目を p で置き換える（寿命も指定する。大きさと不透明度と奥行きは呼び出し側で選び直す）
+    fn replace(&mut self, i: usize, p: Particle, life: f32) {
+        self.x[i] = p.x;
+        self.y[i] = p.y;
+        self.vx[i] = p.vx;
+        self.vy[i] = p.vy;
+        self.hue[i] = p.hue;
+        self.life[i] = life;
+    }
+
+    fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.x.truncate(len);
+        self.y.truncate(len);
+        self.vx.truncate(len);
+        self.vy.truncate(len);
+        self.hue.truncate(len);
+        self.life.truncate(len);
+        self.size.truncate(len);
+        self.alpha.truncate(len);
+        self.z.truncate(len);
+        self.vz.truncate(len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.x.reserve(additional);
+        self.y.reserve(additional);
+        self.vx.reserve(additional);
+        self.vy.reserve(additional);
+        self.hue.reserve(additional);
+        self.life.reserve(additional);
+        self.size.reserve(additional);
+        self.alpha.reserve(additional);
+        self.z.reserve(additional);
+        self.vz.reserve(additional);
+    }
+
+    // 各パーティクルを f で書き換える
+    fn update_each(&mut self, f: impl FnMut(&mut Particle)) {
+        self.chunk().update_each(f);
+    }
+
+    fn chunk(&mut self) -> Chunk<'_> {
+        Chunk { x: &mut self.x, y: &mut self.y, vx: &mut self.vx, vy: &mut self.vy, hue: &mut self.hue }
+    }
+
+    // threads 個に分けた範囲を rayon のプールで並列に f に渡す
+    #[cfg(feature = "threads")]
+    fn par_chunks(&mut self, threads: usize, f: impl Fn(Chunk) + Sync) {
+        use rayon::prelude::*;
+
+        let chunk = self.len().div_ceil(threads).max(1);
+        let chunks = self
+            .x
+            .par_chunks_mut(chunk)
+            .zip(self.y.par_chunks_mut(chunk))
+            .zip(self.vx.par_chunks_mut(chunk))
+            .zip(self.vy.par_chunks_mut(chunk))
+            .zip(self.hue.par_chunks_mut(chunk));
+        chunks.for_each(|((((x, y), vx), vy), hue)| f(Chunk { x, y, vx, vy, hue }));
+    }
+
+    // keep が false を返したパーティクルを取り除き、残りを前に詰める
+    fn retain(&mut self, keep: impl Fn(&Particle) -> bool) {
+        let mut kept = 0;
+        for i in 0..self.len() {
+            let Some(p) = self.get(i) else { break };
+            if keep(&p) {
+                self.x[kept] = p.x;
+                self.y[kept] = p.y;
+                self.vx[kept] = p.vx;
+                self.vy[kept] = p.vy;
+                self.hue[kept] = p.hue;
+                self.life[kept] = self.life[i];
+                self.size[kept] = self.size[i];
+                self.alpha[kept] = self.alpha[i];
+                self.z[kept] = self.z[i];
+                self.vz[kept] = self.vz[i];
+                kept += 1;
+            }
+        }
+        self.truncate(kept);
+    }
+
+    // source のうち keep(インデックス) が true を返したものだけを順に写す
+    #[cfg(target_arch = "wasm32")]
+    pub fn copy_filtered(&mut self, source: &ParticleSet, keep: impl Fn(usize) -> bool) {
+        self.clear();
+        for i in (0..source.len()).filter(|&i| keep(i)) {
+            self.x.push(source.x[i]);
+            self.y.push(source.y[i]);
+            self.vx.push(source.vx[i]);
+            self.vy.push(source.vy[i]);
+            self.hue.push(source.hue[i]);
+            self.life.push(source.life[i]);
+            self.size.push(source.size[i]);
+            self.alpha.push(source.alpha[i]);
+            self.z.push(source.z[i]);
+            self.vz.push(source.vz[i]);
+        }
+    }
+}
+
+// ParticleSet の一部の範囲（成分ごとの配列の同じ範囲）
+pub(crate) struct Chunk<'a> {
+    pub x: &'a mut [f32],
+    pub y: &'a mut [f32],
+    pub vx: &'a mut [f32],
+    pub vy: &'a mut [f32],
+    pub hue: &'a mut [f32],
+}
+
+impl Chunk<'_> {
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    // start 以降の各パーティクルを f で書き換える
+    fn update_from(&mut self, start: usize, mut f: impl FnMut(&mut Particle)) {
+        let components = self.x[start..]
+            .iter_mut()
+            .zip(&mut self.y[start..])
+            .zip(&mut self.vx[start..])
+            .zip(&mut self.vy[start..])
+            .zip(&mut self.hue[start..]);
+        for ((((x, y), vx), vy), hue) in components {
+            let mut p = Particle { x: *x, y: *y, vx: *vx, vy: *vy, hue: *hue };
+            f(&mut p);
+            (*x, *y, *vx, *vy, *hue) = (p.x, p.y, p.vx, p.vy, p.hue);
+        }
+    }
+
+    fn update_each(&mut self, f: impl FnMut(&mut Particle)) {
+        self.update_from(0, f);
+    }
+}
+
+pub(crate) struct ParticleIter<'a> {
+    set: &'a ParticleSet,
+    next: usize,
+}
+
+impl Iterator for ParticleIter<'_> {
+    type Item = Particle;
+
+    fn next(&mut self) -> Option<Particle> {
+        let p = self.set.get(self.next)?;
+        self.next += 1;
+        Some(p)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.set.len() - self.next;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for ParticleIter<'_> {}
+
+impl<'a> IntoIterator for &'a ParticleSet {
+    type Item = Particle;
+    type IntoIter = ParticleIter<'a>;
+
+    fn into_iter(self) -> ParticleIter<'a> {
+        self.iter()
+    }
+}
+
+// バックエンド共通のパーティクル状態と物理演算
+//
+// ダブルバッファ有効時は front（描画が読む）と back（update が書く）を分け、
+// 書き終わってから入れ替える。front は入れ替えの瞬間にしか変わらないので、
+// 描画側が読み始めと読み終わりで同じシーケンス番号を見ていれば
+// 1フレームの途中状態を描いていない（tear-free）ことが保証できる。
+pub(crate) struct Simulation {
+    front: ParticleSet,
+    back: ParticleSet,
+    double_buffered: bool,
+    // front に公開済みの状態の通し番号（update ごとに1増える）
+    sequence: u64,
+    pub width: f32,
+    pub height: f32,
+    pub frame_count: u32,
+    // 目標のパーティクル数（set_load で max_particles 以下に増減する）
+    pub particle_count: usize,
+    // 生成時に指定された数（メモリ予算の確認もこの数で行っている）
+    // grow_particle_count() で超えて増やしたときはその数
+    max_particles: usize,
+    // grow_particle_count() で確認するメモリ予算（バイト）
+    #[cfg(any(target_arch = "wasm32", fuzzing))]
+    pub memory_budget: u64,
+    // 選択中のパーティクルのインデックス（昇順）
+    pub selection: Vec<u32>,
+    // 有効なら一定フレームごとにクラスタの色で塗り分ける
+    pub clustering: Option<KMeans>,
+    // クラスタリングのような重い処理を1フレームの予算に収まる分ずつ進める
+    pub scheduler: FrameScheduler,
+    // ステップで全パーティクルに一律に足した色相の合計（度、360 で折り返す）
+    pub hue_shift: f32,
+    // 色相か寿命をステップの一律の変化以外で変えるたびに1増える（GPU で色を計算するときに送り直す目安）
+    pub color_epoch: u64,
+    // 有効ならパーティクル同士も跳ね返る（物理演算の前に格子で近いものだけ調べる）
+    pub collisions: Option<SpatialHash>,
+    // schedule_explosion() で仕掛けた爆発（シーン表示中はバックエンドがシーンに渡す）
+    pub charges: ChargeQueue,
+    // add_attractor() で置いた引力点（update() のたびに速度を加える）
+    pub attractors: Attractors,
+    // set_wind() と set_turbulence() で設定した風と乱流（update() のたびに速度を加える）
+    pub forces: ForceField,
+    // pointer_move() と pointer_drag() で受け取ったポインターの動き（次の update() で勢いを渡す）
+    pub pointer: PointerForces,
+    // add_circle_obstacle() などで置いた障害物（位置を更新した後に外へ押し出す）
+    pub obstacles: Obstacles,
+    // set_particle_style() で設定したパーティクルごとの大きさと不透明度の選び方
+    style: ParticleStyle,
+    // add_emitter() で置いたエミッターと、放出したパーティクルの寿命
+    pub emitters: Emitters,
+    // resize() で領域の外に出たパーティクルの扱い
+    pub out_of_bounds: OutOfBounds,
+    // update() で壁に届いたパーティクルの扱い
+    pub boundary: BoundaryMode,
+    // Perspective なら奥行きも進める（切り替えは set_projection() で行う）
+    projection: ProjectionMode,
+    // set_particle_color_mode() と set_background_color() で設定した塗り方と背景色（描画だけが読む）
+    #[cfg(target_arch = "wasm32")]
+    pub theme: ColorTheme,
+    // false なら色相を回さない（strict_benchmark() で計測を物理演算と描画だけにする）
+    pub cosmetic: bool,
+    // Some なら生成と reset() の乱数をこのシードから作る（同じシードならバックエンドが違っても同じ初期状態）
+    pub seed: Option<u64>,
+    // Some なら毎ステップ平均速度を見て止まったかを調べる
+    pub settle: Option<Settle>,
+    // 重力・跳ね返り・摩擦と点の大きさ
+    pub config: SimulationConfig,
+    // update_with_dt() の経過時間を固定長のステップに分ける
+    pub timestep: FixedTimestep,
+    // tick() の描画の間引き
+    #[cfg(target_arch = "wasm32")]
+    pub frame_cap: FrameCap,
+    // 描くときの視点（attach_input_handlers() のホイールやドラッグで動かす）
+    #[cfg(target_arch = "wasm32")]
+    pub camera: Camera,
+    // true なら update() で何も進めない（pause() から resume() まで。step() では進める）
+    #[cfg(target_arch = "wasm32")]
+    pub paused: bool,
+    // 補間用の直前のステップの位置（timestep が使われているときだけ残す）
+    previous_x: Vec<f32>,
+    previous_y: Vec<f32>,
+    // 補間した描画用の状態と、それを描くか
+    #[cfg(target_arch = "wasm32")]
+    interpolated: ParticleSet,
+    use_interpolated: bool,
+    // 描画が重いときに間引いて描く設定（間引くのは描画だけで、物理演算はすべてに行う）
+    #[cfg(target_arch = "wasm32")]
+    pub lod: LevelOfDetail,
+    // 直前に prepare_visible() したときの間引きの間隔（変わったら色を送り直す）
+    #[cfg(target_arch = "wasm32")]
+    visible_skip: u32,
+    // update() と爆発を分けて計算する数（threads フィーチャーがなければ常に1）
+    threads: usize,
+    // true なら物理演算を4個ずつまとめて計算する（simd フィーチャーがなければ常に false）
+    simd: bool,
+}
+
+impl Simulation {
+    pub fn new(width: f32, height: f32, particle_count: usize) -> Result<Simulation, String> {
+        let mut sim = Simulation::with_capacity(width, height, particle_count)?;
+        sim.spawn_pending(particle_count);
+        Ok(sim)
+    }
+
+    // 領域だけ確保して、パーティクルは spawn_pending で少しずつ生成する
+    pub fn with_capacity(width: f32, height: f32, particle_count: usize) -> Result<Simulation, String> {
+        Ok(Simulation {
+            front: ParticleSet::with_capacity(particle_count)?,
+            back: ParticleSet::default(),
+            double_buffered: false,
+            sequence: 0,
+            width,
+            height,
+            frame_count: 0,
+            particle_count,
+            max_particles: particle_count,
+            #[cfg(any(target_arch = "wasm32", fuzzing))]
+            memory_budget: memory::DEFAULT_MEMORY_BUDGET,
+            selection: Vec::new(),
+            clustering: None,
+            scheduler: FrameScheduler::default(),
+            hue_shift: 0.0,
+            color_epoch: 0,
+            collisions: None,
+            charges: ChargeQueue::default(),
+            attractors: Attractors::default(),
+            forces: ForceField::default(),
+            pointer: PointerForces::default(),
+            obstacles: Obstacles::default(),
+            style: ParticleStyle::default(),
+            emitters: Emitters::default(),
+            out_of_bounds: OutOfBounds::default(),
+            boundary: BoundaryMode::default(),
+            projection: ProjectionMode::default(),
+            #[cfg(target_arch = "wasm32")]
+            theme: ColorTheme::default(),
+            cosmetic: true,
+            seed: None,
+            settle: None,
+            config: SimulationConfig::default(),
+            timestep: FixedTimestep::default(),
+            #[cfg(target_arch = "wasm32")]
+            frame_cap: FrameCap::default(),
+            #[cfg(target_arch = "wasm32")]
+            camera: Camera::default(),
+            #[cfg(target_arch = "wasm32")]
+            paused: false,
+            previous_x: Vec::new(),
+            previous_y: Vec::new(),
+            #[cfg(target_arch = "wasm32")]
+            interpolated: ParticleSet::default(),
+            use_interpolated: false,
+            #[cfg(target_arch = "wasm32")]
+            lod: LevelOfDetail::default(),
+            #[cfg(target_arch = "wasm32")]
+            visible_skip: 1,
+            threads: 1,
+            simd: false,
+        })
+    }
+
+    // 描画側が読むべき安定した状態
+    pub fn particles(&self) -> &ParticleSet {
+        &self.front
+    }
+
+    // 描く状態（update_with_dt() の後は補間した位置、それ以外は particles() と同じ）
+    #[cfg(target_arch = "wasm32")]
+    pub fn rendered(&self) -> &ParticleSet {
+        if self.use_interpolated {
+            &self.interpolated
+        } else {
+            &self.front
+        }
+    }
+
+    // 描く直前に呼ぶ。LOD で間引くときは描くパーティクルを rendered() から集める
+    #[cfg(target_arch = "wasm32")]
+    pub fn prepare_visible(&mut self) {
+        let skip = self.lod.skip();
+        if skip != self.visible_skip {
+            self.visible_skip = skip;
+            self.color_epoch += 1;
+        }
+        if skip > 1 {
+            let source = if self.use_interpolated { &self.interpolated } else { &self.front };
+            self.lod.collect(source);
+        }
+    }
+
+    // 直前に prepare_visible() したときに何個に1個描いたか
+    #[cfg(target_arch = "wasm32")]
+    pub fn visible_skip(&self) -> u32 {
+        self.visible_skip
+    }
+
+    // 描くパーティクル（LOD で間引いているときはその分だけ。prepare_visible() の後に使う）
+    // インデックスは particles() とずれるので、選択の強調などには rendered() を使う
+    #[cfg(target_arch = "wasm32")]
+    pub fn visible(&self) -> &ParticleSet {
+        if self.visible_skip > 1 {
+            self.lod.visible()
+        } else {
+            self.rendered()
+        }
+    }
+
+    // 物理演算を GPU で進めた1ステップ分、フレーム数と色相のずれだけを進め、このステップで起爆する仕掛けを返す
+    #[cfg(target_arch = "wasm32")]
+    pub fn step_external(&mut self) -> Vec<Charge> {
+        let hue_speed = if self.clustering.is_some() || !self.cosmetic { 0.0 } else { HUE_SPEED };
+        self.hue_shift = (self.hue_shift + hue_speed) % 360.0;
+        self.frame_count = self.frame_count.wrapping_add(1);
+        if self.style.is_animated() {
+            self.color_epoch += 1;
+        }
+        self.charges.tick()
+    }
+
+    // GPU で進めた (x, y, vx, vy) の並びを書き戻す（色相は基準の色相に今のずれを足す）
+    // 数が合わなければ何もしない
+    #[cfg(any(target_arch = "wasm32", fuzzing))]
+    pub fn load_state(&mut self, state: &[f32], base_hue: &[f32]) {
+        let count = self.front.x.len();
+        if state.len() != count * 4 || base_hue.len() != count {
+            return;
+        }
+        for (i, (values, &hue)) in state.chunks_exact(4).zip(base_hue).enumerate() {
+            self.front.x[i] = values[0];
+            self.front.y[i] = values[1];
+            self.front.vx[i] = values[2];
+            self.front.vy[i] = values[3];
+            self.front.hue[i] = (hue + self.hue_shift) % 360.0;
+        }
+        self.sequence += 1;
+        self.color_epoch += 1;
+    }
+
+    // 直前のステップと今の位置の間を、持ち越した時間の割合で補間して描画用に残す
+    // 数が変わって直前の位置がないパーティクルは今の位置のまま
+    #[cfg(target_arch = "wasm32")]
+    pub fn interpolate(&mut self) {
+        let alpha = self.timestep.alpha();
+        self.interpolated.clone_from(&self.front);
+        let set = &mut self.interpolated;
+        for (x, &previous) in set.x.iter_mut().zip(&self.previous_x) {
+            *x = previous + (*x - previous) * alpha;
+        }
+        for (y, &previous) in set.y.iter_mut().zip(&self.previous_y) {
+            *y = previous + (*y - previous) * alpha;
+        }
+        self.use_interpolated = true;
+    }
+
+    // 生成済みのパーティクル数
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawned(&self) -> usize {
+        self.front.len()
+    }
+
+    // 未生成のパーティクルを最大 max 個生成し、生成した数を返す
+    // シードがあれば生成済みの数から派生させるので、同じ区切り方で生成すれば同じ状態になる
+    pub fn spawn_pending(&mut self, max: usize) -> usize {
+        match self.seed {
+            Some(seed) => {
+                let seed = seed.wrapping_add(self.front.len() as u64);
+                crate::rng::with_seed(seed, || self.spawn_pending_unseeded(max))
+            }
+            None => self.spawn_pending_unseeded(max),
+        }
+    }
+
+    fn spawn_pending_unseeded(&mut self, max: usize) -> usize {
+        let n = max.min(self.particle_count.saturating_sub(self.front.len()));
+        let mut rng = crate::rng::rng();
+        let (width, height) = (self.width, self.height);
+        self.front.reserve(n);
+        for _ in 0..n {
+            self.front.push(spawn_particle(&mut rng, width, height));
+        }
+        let (start, end) = (self.front.len() - n, self.front.len());
+        self.style.assign(&mut self.front, start..end);
+        let depth = self.depth();
+        self.projection.assign(&mut self.front, start..end, depth);
+        if n > 0 {
+            self.color_epoch += 1;
+        }
+        // エミッターがあれば、増やした分もいずれ尽きてエミッターから出し直されるようにする
+        if !self.emitters.is_empty() {
+            stagger_lives(&mut self.front.life[start..], &mut rng);
+        }
+        self.sequence += 1;
+        n
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    pub fn step(&mut self) {
+        self.step_with(|_| {});
+    }
+
+    // 1ステップ進める。visit は更新後の各パーティクルに対して呼ばれる
+    pub fn step_with(&mut self, mut visit: impl FnMut(&Particle)) {
+        self.use_interpolated = false;
+        if self.boundary == BoundaryMode::Respawn {
+            self.respawn_outside();
+        }
+        if self.timestep.active {
+            self.previous_x.clone_from(&self.front.x);
+            self.previous_y.clone_from(&self.front.y);
+        }
+        let (width, height, config, boundary) = (self.width, self.height, self.config, self.boundary);
+        // クラスタ色分け中と strict_benchmark() 中は色相を固定する
+        let hue_speed = if self.clustering.is_some() || !self.cosmetic { 0.0 } else { HUE_SPEED };
+        self.hue_shift = (self.hue_shift + hue_speed) % 360.0;
+
+        for charge in self.charges.tick() {
+            self.explode(charge.x, charge.y, &charge.explosion);
+        }
+
+        self.scheduler.begin_frame();
+        if let Some(kmeans) = &mut self.clustering {
+            if kmeans.is_due(self.frame_count) {
+                kmeans.start();
+            }
+            if kmeans.is_running() {
+                let front = &mut self.front;
+                if self.scheduler.run(|slice| kmeans.advance(front, slice)) {
+                    self.color_epoch += 1;
+                }
+            }
+        }
+
+        if self.emitters.active {
+            self.emit();
+        }
+
+        if !self.attractors.is_empty() {
+            let attractors = &self.attractors;
+            update_particles(&mut self.front, self.threads, |p| attractors.apply(p));
+        }
+
+        if self.forces.is_active() {
+            let (forces, frame) = (&self.forces, self.frame_count);
+            update_particles(&mut self.front, self.threads, |p| forces.apply(p, frame));
+        }
+
+        if !self.pointer.is_empty() {
+            let impulses = self.pointer.take();
+            update_particles(&mut self.front, self.threads, |p| {
+                for impulse in &impulses {
+                    impulse.apply(p);
+                }
+            });
+        }
+
+        if let Some(collisions) = &mut self.collisions {
+            collisions.resolve(&mut self.front, width, height, config.point_size, config.bounce);
+        }
+
+        // 障害物はパーティクルの半径ぶん手前で跳ね返す
+        let (obstacles, margin) = (&self.obstacles, config.point_size);
+        if self.threads > 1 || self.simd {
+            // まとめて（並列に・4個ずつ）計算してから、visit は1スレッドで順に呼ぶ
+            let front = if self.double_buffered {
+                self.back.clone_from(&self.front);
+                &mut self.back
+            } else {
+                &mut self.front
+            };
+            let simd = self.simd;
+            for_each_chunk(front, self.threads, |mut chunk| {
+                // 4で割り切れない残りはスカラー版で
+                let start = if simd {
+                    simd::integrate(&mut chunk, width, height, hue_speed, &config, boundary)
+                } else {
+                    0
+                };
+                chunk.update_from(start, |p| integrate(p, width, height, hue_speed, &config, boundary));
+            });
+            if !obstacles.is_empty() {
+                update_particles(front, self.threads, |p| obstacles.apply(p, margin, config.bounce));
+            }
+            if self.double_buffered {
+                std::mem::swap(&mut self.front, &mut self.back);
+            }
+            for p in self.front.iter() {
+                visit(&p);
+            }
+        } else if self.double_buffered {
+            // front は読むだけ、次の状態は back に書く
+            self.back.clear();
+            for mut next in self.front.iter() {
+                integrate(&mut next, width, height, hue_speed, &config, boundary);
+                obstacles.apply(&mut next, margin, config.bounce);
+                visit(&next);
+                self.back.push(next);
+            }
+            self.back.life.copy_from_slice(&self.front.life);
+            self.back.size.copy_from_slice(&self.front.size);
+            self.back.alpha.copy_from_slice(&self.front.alpha);
+            self.back.z.copy_from_slice(&self.front.z);
+            self.back.vz.copy_from_slice(&self.front.vz);
+            std::mem::swap(&mut self.front, &mut self.back);
+        } else {
+            // Rustで高速物理演算!
+            self.front.update_each(|p| {
+                integrate(p, width, height, hue_speed, &config, boundary);
+                obstacles.apply(p, margin, config.bounce);
+                visit(p);
+            });
+        }
+
+        if self.projection == ProjectionMode::Perspective {
+            let depth = self.depth();
+            projection::integrate_depth(&mut self.front, depth, config.bounce, boundary);
+        }
+
+        self.sequence += 1;
+        self.frame_count = self.frame_count.wrapping_add(1);
+        // 揺らしているときは描く不透明度が毎ステップ変わるので、色も毎ステップ変わる
+        if self.style.is_animated() {
+            self.color_epoch += 1;
+        }
+
+        if let Some(settle) = self.settle {
+            if settle.settled_at.is_none() && self.mean_speed() < settle.threshold {
+                self.settle = Some(Settle {
+                    settled_at: Some(self.frame_count),
+                    ..settle
+                });
+            }
+        }
+    }
+
+    // 前のステップで領域の外に出たパーティクルを、最初の噴水の位置から寿命はそのままで生成し直す
+    // 外に出た時点では描いても見えないので、乱数を使う生成は並列の積分の後ではなくここで1つずつ行う
+    fn respawn_outside(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let mut rng = None;
+        for i in 0..self.front.len() {
+            if (0.0..=width).contains(&self.front.x[i]) && (0.0..=height).contains(&self.front.y[i]) {
+                continue;
+            }
+            let rng = rng.get_or_insert_with(crate::rng::rng);
+            let life = self.front.life[i];
+            self.front.replace(i, spawn_particle(rng, width, height), life);
+            self.style.assign(&mut self.front, i..i + 1);
+            let depth = self.depth();
+            self.projection.assign(&mut self.front, i..i + 1, depth);
+        }
+        if rng.is_some() {
+            self.color_epoch += 1;
+        }
+    }
+
+    // 止まったかの検出を threshold で始め直す（None で止める）
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        self.settle = threshold.map(|threshold| Settle {
+            threshold,
+            #[cfg(target_arch = "wasm32")]
+            started_at: self.frame_count,
+            settled_at: None,
+        });
+    }
+
+    // パーティクルの平均速度(px/フレーム)
+    pub fn mean_speed(&self) -> f32 {
+        if self.front.is_empty() {
+            return 0.0;
+        }
+        let total: f32 = self
+            .front
+            .vx
+            .iter()
+            .zip(&self.front.vy)
+            .map(|(vx, vy)| math::sqrt(vx * vx + vy * vy))
+            .sum();
+        total / self.front.len() as f32
+    }
+
+    // 負荷 0.0~1.0 を生成時の数に対する割合としてパーティクル数に反映する
+    // 減らすときは末尾から捨て、増やすときは新しく生成する
+    pub fn set_load(&mut self, load: f32) {
+        self.set_particle_count((self.max_particles as f32 * load).round() as usize);
+    }
+
+    // パーティクル数を max_particles 以下で増減し、実際の数を返す
+    // 確保済みの領域の中で増減するので、リセットも作り直しもしない（frame_count もそのまま）
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let count = count.min(self.max_particles);
+        self.particle_count = count;
+        if self.front.len() > count {
+            self.front.truncate(count);
+            self.back.truncate(count);
+            self.selection.retain(|&i| (i as usize) < count);
+            self.sequence += 1;
+            self.color_epoch += 1;
+        } else {
+            self.spawn_pending(count - self.front.len());
+        }
+        count
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_double_buffered(&mut self, enabled: bool) {
+        self.double_buffered = enabled;
+        if enabled {
+            self.back.reserve(self.front.len());
+        } else {
+            self.back = ParticleSet::default();
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn max_particles(&self) -> usize {
+        self.max_particles
+    }
+
+    // 確保済みの領域を超えてパーティクル数を増やし、実際の数を返す（count が少なければ set_particle_count と同じ）
+    // 領域は Vec の伸長に任せるので、途中で取り直しや memory.grow が起きる（それを計測するためのもの）
+    #[cfg(target_arch = "wasm32")]
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, String> {
+        if count > self.max_particles {
+            memory::check_budget(count, self.memory_budget)?;
+            self.max_particles = count;
+        }
+        Ok(self.set_particle_count(count))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn is_double_buffered(&self) -> bool {
+        self.double_buffered
+    }
+
+    // 領域の大きさを変え、外に出たパーティクルを out_of_bounds に従って戻す
+    pub fn resize(&mut self, width: f32, height: f32) {
+        self.width = width;
+        self.height = height;
+        let outside = |p: &Particle| p.x < 0.0 || p.x > width || p.y < 0.0 || p.y > height;
+        match self.out_of_bounds {
+            OutOfBounds::Clamp => {
+                self.front.update_each(|p| {
+                    if outside(p) {
+                        p.x = p.x.clamp(0.0, width);
+                        p.y = p.y.clamp(0.0, height);
+                    }
+                });
+            }
+            OutOfBounds::Kill => {
+                let before = self.front.len();
+                self.front.retain(|p| !outside(p));
+                // 詰めた分インデックスがずれるので選択は解除する
+                let removed = before - self.front.len();
+                if removed > 0 {
+                    self.selection.clear();
+                }
+                // 目標の数も減らす（init_chunk() で生成し直さないように）
+                self.particle_count -= removed;
+                self.color_epoch += 1;
+            }
+            OutOfBounds::Wrap => {
+                self.front.update_each(|p| {
+                    if outside(p) {
+                        p.x = p.x.rem_euclid(width);
+                        p.y = p.y.rem_euclid(height);
+                    }
+                });
+            }
+        }
+        self.back.clear();
+        self.forget_previous();
+        self.sequence += 1;
+    }
+
+    // 位置をステップ以外で変えたときは補間をやめる（次のステップまで今の位置を描く）
+    fn forget_previous(&mut self) {
+        self.previous_x.clear();
+        self.previous_y.clear();
+        self.use_interpolated = false;
+    }
+
+    pub fn reset(&mut self) {
+        match self.seed {
+            Some(seed) => crate::rng::with_seed(seed, || self.reset_unseeded()),
+            None => self.reset_unseeded(),
+        }
+    }
+
+    fn reset_unseeded(&mut self) {
+        create_particles(self.width, self.height, self.particle_count, &mut self.front);
+        self.style.assign(&mut self.front, 0..self.particle_count);
+        let depth = self.depth();
+        self.projection.assign(&mut self.front, 0..self.particle_count, depth);
+        self.timestep.reset();
+        self.forget_previous();
+        if !self.emitters.is_empty() {
+            stagger_lives(&mut self.front.life, &mut crate::rng::rng());
+        }
+        self.back.clear();
+        self.selection.clear();
+        self.charges.clear();
+        self.pointer.clear();
+        self.sequence += 1;
+        self.color_epoch += 1;
+        self.frame_count = 0;
+        self.set_settle_threshold(self.settle.map(|settle| settle.threshold));
+    }
+
+    // 今のパーティクルの状態とフレーム数を snapshot の形式で書き出す
+    #[cfg(any(target_arch = "wasm32", fuzzing))]
+    pub fn snapshot(&self) -> Vec<u8> {
+        snapshot::encode(&self.front, self.width, self.height, self.frame_count, self.hue_shift)
+    }
+
+    // snapshot() で書き出した状態に戻す（パーティクル数も書き出したときの数になる）
+    // 領域の大きさが違えば、外に出たパーティクルは out_of_bounds に従って戻す
+    #[cfg(any(target_arch = "wasm32", fuzzing))]
+    pub fn restore(&mut self, snapshot: Snapshot) -> Result<(), String> {
+        let count = snapshot.particles.len();
+        if count > self.max_particles {
+            memory::check_budget(count, self.memory_budget)?;
+            self.max_particles = count;
+        }
+        self.front = snapshot.particles;
+        // 大きさと不透明度を含まない古い版の状態なら、今の設定で選ぶ
+        if !snapshot.has_style {
+            self.style.assign(&mut self.front, 0..count);
+        }
+        // 奥行きを含まない古い版の状態なら、今の設定でばらまく
+        if !snapshot.has_depth {
+            let depth = projection::depth_extent(snapshot.width, snapshot.height);
+            self.projection.scatter(&mut self.front, depth);
+        }
+        self.particle_count = count;
+        self.frame_count = snapshot.frame_count;
+        self.hue_shift = snapshot.hue_shift;
+        self.timestep.reset();
+        self.forget_previous();
+        self.back.clear();
+        self.selection.clear();
+        self.charges.clear();
+        self.sequence += 1;
+        self.color_epoch += 1;
+        self.set_settle_threshold(self.settle.map(|settle| settle.threshold));
+        if (snapshot.width, snapshot.height) != (self.width, self.height) {
+            self.resize(self.width, self.height);
+        }
+        Ok(())
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn style(&self) -> ParticleStyle {
+        self.style
+    }
+
+    // 大きさと不透明度の選び方を変え、今いるパーティクルにも選び直す
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_style(&mut self, style: ParticleStyle) {
+        self.style = style.sanitized();
+        let count = self.front.len();
+        self.style.assign(&mut self.front, 0..count);
+        self.back.clear();
+        self.sequence += 1;
+        self.color_epoch += 1;
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn projection(&self) -> ProjectionMode {
+        self.projection
+    }
+
+    // 奥行きを持たせるかを切り替える（Perspective にしたときは今いるパーティクルを奥行き全体にばらまく）
+    #[cfg(any(target_arch = "wasm32", test))]
+    pub fn set_projection(&mut self, mode: ProjectionMode) {
+        if mode == self.projection {
+            return;
+        }
+        self.projection = mode;
+        let depth = self.depth();
+        mode.scatter(&mut self.front, depth);
+        self.back.clear();
+        self.sequence += 1;
+    }
+
+    // 奥行きの範囲(px)
+    pub fn depth(&self) -> f32 {
+        projection::depth_extent(self.width, self.height)
+    }
+
+    pub fn particle_info(&self, index: usize) -> Option<ParticleInfo> {
+        picking::particle_info(&self.front, index)
+    }
+
+    // (x, y) から radius 以内のパーティクル数（衝突が有効ならその格子を使い回し、なければ総当たり）
+    pub fn count_within(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        let (width, height, sequence) = (self.width, self.height, self.sequence);
+        match &mut self.collisions {
+            Some(hash) => {
+                hash.index(&self.front, width, height, self.config.point_size, sequence);
+                hash.count_within(&self.front, x, y, radius)
+            }
+            None => picking::count_within(&self.front, x, y, radius),
+        }
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス
+    pub fn nearest(&mut self, x: f32, y: f32) -> Option<usize> {
+        let (width, height, sequence) = (self.width, self.height, self.sequence);
+        match &mut self.collisions {
+            Some(hash) => {
+                hash.index(&self.front, width, height, self.config.point_size, sequence);
+                hash.nearest(&self.front, x, y)
+            }
+            None => picking::nearest(&self.front, x, y),
+        }
+    }
+
+    // クリックで爆発!
+    pub fn explode(&mut self, click_x: f32, click_y: f32, explosion: &ExplosionConfig) {
+        update_particles(&mut self.front, self.threads, |p| {
+            let dx = p.x - click_x;
+            let dy = p.y - click_y;
+            let dist = math::sqrt(dx * dx + dy * dy);
+
+            // 近いパーティクルほど強く吹き飛ぶ
+            if let Some(force) = explosion.force_at(dist) {
+                let angle = math::atan2(dy, dx);
+                p.vx += math::cos(angle) * force;
+                p.vy += math::sin(angle) * force;
+            }
+        });
+        self.sequence += 1;
+    }
+
+    // (x, y) の近くのパーティクルを (dx, dy) の向きに押し流す（ドラッグでかき混ぜる）
+    // 中心ほど強く、STIR_RADIUS で 0 になる
+    #[cfg(target_arch = "wasm32")]
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if !(x.is_finite() && y.is_finite() && dx.is_finite() && dy.is_finite()) {
+            return;
+        }
+        update_particles(&mut self.front, self.threads, |p| {
+            let (ox, oy) = (p.x - x, p.y - y);
+            let dist = math::sqrt(ox * ox + oy * oy);
+            if dist < STIR_RADIUS {
+                let force = STIR_STRENGTH * (1.0 - dist / STIR_RADIUS);
+                p.vx += dx * force;
+                p.vy += dy * force;
+            }
+        });
+        self.sequence += 1;
+    }
+
+    // 計算を分ける数を設定し、実際に使う数を返す
+    // threads フィーチャーがなければ1、あればプールのスレッド数まで（プールを作れないビルドでは1）
+    pub fn set_threads(&mut self, threads: usize) -> usize {
+        self.threads = threads.clamp(1, pool_threads());
+        self.threads
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    // SIMD 版の物理演算を使うか設定し、実際に使うかを返す（simd フィーチャーがなければ false）
+    pub fn set_simd(&mut self, enabled: bool) -> bool {
+        self.simd = enabled && cfg!(feature = "simd");
+        self.simd
+    }
+
+    pub fn simd(&self) -> bool {
+        self.simd
+    }
+
+    pub fn set_collisions(&mut self, enabled: bool) {
+        if enabled != self.collisions.is_some() {
+            self.collisions = enabled.then(SpatialHash::default);
+        }
+    }
+
+    pub fn collisions(&self) -> bool {
+        self.collisions.is_some()
+    }
+
+    // エミッターを置いて番号を返す
+    // 最初の1つを置いたときは、今いる寿命のないパーティクルにばらばらの寿命を与え、
+    // 尽きたものから順にエミッターが出し直すようにする
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        if self.emitters.is_empty() {
+            stagger_lives(&mut self.front.life, &mut crate::rng::rng());
+        }
+        self.sequence += 1;
+        self.color_epoch += 1;
+        self.emitters.add(x, y, rate, spread, speed)
+    }
+
+    // 寿命を減らし、尽きたパーティクルの場所にエミッターから出し直す
+    // 尽きた場所が足りなければ目標の数まで増やし、それでも足りなければそのフレームは出さない
+    // エミッターがなくなった後は、尽きたものを最初の噴水の位置から寿命なしで出し直す
+    fn emit(&mut self) {
+        // 寿命が毎ステップ減るので、色も毎ステップ変わる
+        self.color_epoch += 1;
+        let decay = self.emitters.decay();
+        let (projection, depth) = (self.projection, self.depth());
+        let set = &mut self.front;
+        for life in &mut set.life {
+            *life -= decay;
+        }
+
+        let mut rng = crate::rng::rng();
+        let mut cursor = 0;
+        for emitter in self.emitters.tick() {
+            while cursor < set.len() && set.life[cursor] > 0.0 {
+                cursor += 1;
+            }
+            let p = emitter.particle(&mut rng);
+            if cursor < set.len() {
+                set.replace(cursor, p, 1.0);
+            } else if set.len() < self.particle_count {
+                set.push(p);
+                set.life[cursor] = 1.0;
+            } else {
+                break;
+            }
+            self.style.assign(set, cursor..cursor + 1);
+            projection.assign(set, cursor..cursor + 1, depth);
+            cursor += 1;
+            self.emitters.emitted += 1;
+        }
+
+        if self.emitters.is_empty() {
+            let mut mortal = false;
+            for i in 0..set.len() {
+                if set.life[i] <= 0.0 {
+                    set.replace(i, spawn_particle(&mut rng, self.width, self.height), f32::INFINITY);
+                    self.style.assign(set, i..i + 1);
+                    projection.assign(set, i..i + 1, depth);
+                } else if set.life[i].is_finite() {
+                    mortal = true;
+                }
+            }
+            self.emitters.active = mortal;
+        }
+    }
+
+    // 直近のステップで重なっていた組の数（衝突が無効なら0）
+    pub fn last_contacts(&self) -> usize {
+        self.collisions.as_ref().map_or(0, |collisions| collisions.last_contacts)
+    }
+
+    // パーティクルの状態の配列が確保しているバイト数（ダブルバッファ・補間・間引きの分も含む）
+    #[cfg(target_arch = "wasm32")]
+    pub fn buffer_bytes(&self) -> u64 {
+        let floats = self.front.capacity()
+            + self.back.capacity()
+            + self.interpolated.capacity()
+            + self.lod.visible().capacity()
+            + self.previous_x.capacity()
+            + self.previous_y.capacity();
+        (floats * std::mem::size_of::<f32>()) as u64
+    }
+}
+
+// threads が2以上なら分けて並列に、そうでなければ全体を1つの範囲として f に渡す
+#[cfg(feature = "threads")]
+fn for_each_chunk(set: &mut ParticleSet, threads: usize, f: impl Fn(Chunk) + Sync) {
+    if threads > 1 {
+        set.par_chunks(threads, f);
+    } else {
+        f(set.chunk());
+    }
+}
+
+#[cfg(not(feature = "threads"))]
+fn for_each_chunk(set: &mut ParticleSet, _threads: usize, f: impl Fn(Chunk) + Sync) {
+    f(set.chunk());
+}
+
+// rayon のプールのスレッド数
+// wasm のプールは initThreadPool() が Web Worker で作るもので、atomics なしのビルドでは作れない
+// そのときはグローバルのプールに触らずに1を返す（触るとその場で1スレッドのプールができて固定される）
+#[cfg(feature = "threads")]
+fn pool_threads() -> usize {
+    if cfg!(all(target_arch = "wasm32", not(target_feature = "atomics"))) {
+        return 1;
+    }
+    rayon::current_num_threads()
+}
+
+#[cfg(not(feature = "threads"))]
+fn pool_threads() -> usize {
+    1
+}
+
+// 各パーティクルを f で書き換える（threads が2以上なら並列に）
+fn update_particles(set: &mut ParticleSet, threads: usize, f: impl Fn(&mut Particle) + Sync) {
+    for_each_chunk(set, threads, |mut chunk| chunk.update_each(&f));
+}
+
+// 1パーティクル分の物理演算
+fn integrate(p: &mut Particle, width: f32, height: f32, hue_speed: f32, config: &SimulationConfig, boundary: BoundaryMode) {
+    // 重力
+    p.vy += config.gravity;
+
+    // 位置更新
+    p.x += p.vx;
+    p.y += p.vy;
+
+    match boundary {
+        // 壁で跳ね返る
+        BoundaryMode::Bounce => {
+            if p.x < 0.0 || p.x > width {
+                p.vx *= -config.bounce;
+                p.x = p.x.clamp(0.0, width);
+            }
+
+            if p.y < 0.0 {
+                p.vy *= -config.bounce;
+                p.y = 0.0;
+            }
+
+            if p.y > height {
+                p.vy *= -config.bounce;
+                p.y = height;
+                p.vx *= config.friction; // 摩擦
+            }
+        }
+        // SIMD 版と同じ値になるように rem_euclid() ではなく floor() で折り返す
+        BoundaryMode::Wrap => {
+            if p.x < 0.0 || p.x > width {
+                p.x -= width * (p.x / width).floor();
+            }
+            if p.y < 0.0 || p.y > height {
+                p.y -= height * (p.y / height).floor();
+            }
+        }
+        // 生成し直すのは次のステップの最初（respawn_outside()）
+        BoundaryMode::Respawn | BoundaryMode::None => {}
+    }
+
+    // 色を変化
+    p.hue = (p.hue + hue_speed) % 360.0;
+}
+
+// 寿命のないパーティクルにばらばらの寿命を与える（一斉に尽きないように）
+fn stagger_lives(lives: &mut [f32], rng: &mut impl Rng) {
+    for life in lives.iter_mut().filter(|life| life.is_infinite()) {
+        *life = rng.gen::<f32>();
+    }
+}
+
+// パーティクル生成（out の確保済み領域を再利用する）
+fn create_particles(width: f32, height: f32, particle_count: usize, out: &mut ParticleSet) {
+    let mut rng = crate::rng::rng();
+    out.clear();
+    for _ in 0..particle_count {
+        out.push(spawn_particle(&mut rng, width, height));
+    }
+}
+
+fn spawn_particle(rng: &mut impl Rng, width: f32, height: f32) -> Particle {
+    let angle = rng.gen::<f32>() * 2.0 * PI;
+    let speed = rng.gen::<f32>() * 2.0 + 1.0;
+    Particle {
+        x: width / 2.0,
+        y: height / 4.0,
+        vx: math::cos(angle) * speed,
+        vy: math::sin(angle) * speed - 3.0,
+        hue: rng.gen::<f32>() * 360.0,
+    }
+}
+
+// パーティクルの色相を描画用のRGB（各0.0〜1.0）にする（どのバックエンドも同じ色になるようにここだけで変換する）
+pub(crate) fn hsl_to_rgb(h: f32, s: f32, l: f32) -> (f32, f32, f32) {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h_prime = h / 60.0;
+    let x = c * (1.0 - ((h_prime % 2.0) - 1.0).abs());
+
+    let (r1, g1, b1) = if h_prime < 1.0 {
+        (c, x, 0.0)
+    } else if h_prime < 2.0 {
+        (x, c, 0.0)
+    } else if h_prime < 3.0 {
+        (0.0, c, x)
+    } else if h_prime < 4.0 {
+        (0.0, x, c)
+    } else if h_prime < 5.0 {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+
+    let m = l - c / 2.0;
+    (r1 + m, g1 + m, b1 + m)
+}
+
+// 描画側のシーケンス番号チェック
+// 読み始めと読み終わりで番号が変わっていたら途中状態を描いたことになる
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+pub(crate) struct ReadStamps {
+    pub last_rendered: u64,
+    pub torn_frames: u32,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl ReadStamps {
+    pub fn record(&mut self, begin: u64, end: u64) {
+        if begin != end {
+            self.torn_frames += 1;
+        }
+        self.last_rendered = end;
+    }
+}
+
+// 描画しないシミュレーションだけの公開版
+// JS で描く基準（素の Canvas2D や他のライブラリ）でも、各バックエンドとまったく同じ物理演算を使える
+//
+// x_ptr() などで WASM のメモリ上の成分ごとの配列をコピーせずに読める。無効になる条件:
+// - get_buffer_generation() が変わったら配列が移動しているので、ポインタとビューを取り直す
+// - WASM のメモリが増えると（どこかで大きな確保をすると）JS 側の ArrayBuffer が切り離され、
+//   ビューの length が 0 になる。ポインタはそのまま使えるので get_memory() からビューを作り直す
+// - ビューは読むだけにする（書き換えても次の update() で上書きされる）
+#[wasm_bindgen]
+pub struct ParticleSimulation {
+    sim: Simulation,
+    explosion: ExplosionConfig,
+    // 配列が移動した回数と、最後に見た先頭アドレス（x, y, hue）
+    generation: u32,
+    addresses: [usize; 3],
+}
+
+#[wasm_bindgen]
+impl ParticleSimulation {
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: f32, height: f32, particle_count: usize) -> Result<ParticleSimulation, JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        let sim = Simulation::new(width, height, particle_count)?;
+        let addresses = addresses(sim.particles());
+        Ok(ParticleSimulation {
+            sim,
+            explosion: ExplosionConfig::default(),
+            generation: 0,
+            addresses,
+        })
+    }
+
+    pub fn update(&mut self) {
+        self.sim.step();
+        self.track_reallocation();
+    }
+
+    pub fn reset(&mut self) {
+        self.sim.reset();
+        self.track_reallocation();
+    }
+
+    // 以後の生成と reset() の乱数を seed から作り、パーティクルを作り直す
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.seed = Some(seed);
+        self.reset();
+    }
+
+    pub fn clear_seed(&mut self) {
+        self.sim.seed = None;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.sim.seed
+    }
+
+    pub fn explode(&mut self, x: f32, y: f32) {
+        self.sim.explode(x, y, &self.explosion);
+    }
+
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.sim.pointer.push_move(x, y, dx, dy);
+    }
+
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        self.sim.pointer.push_drag(x, y, strength);
+    }
+
+    // 引力点を置いて番号を返す（strength が負なら押しのける）
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        self.sim.attractors.add(x, y, strength, radius)
+    }
+
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        self.sim.attractors.remove(id)
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        self.sim.attractors.move_to(id, x, y)
+    }
+
+    pub fn clear_attractors(&mut self) {
+        self.sim.attractors.clear();
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        self.sim.attractors.list()
+    }
+
+    // 毎フレーム rate 個ずつパーティクルを出すエミッターを置いて番号を返す
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        self.sim.add_emitter(x, y, rate, spread, speed)
+    }
+
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        self.sim.emitters.remove(id)
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.sim.emitters.clear();
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        self.sim.emitters.list()
+    }
+
+    // エミッターから出したパーティクルの累計
+    pub fn get_emitted_count(&self) -> f64 {
+        self.sim.emitters.emitted as f64
+    }
+
+    // 放出したパーティクルの寿命(フレーム)を設定し、実際に使う値を返す
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        self.sim.emitters.set_lifetime(frames)
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        self.sim.emitters.lifetime()
+    }
+
+    // 残りの寿命（寿命のないパーティクルは無限大）
+    pub fn get_life(&self) -> Vec<f32> {
+        self.sim.particles().life.clone()
+    }
+
+    // (x, y) から radius 以内にあるパーティクルの数
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.sim.count_within(x, y, radius)
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス（パーティクルがなければ undefined）
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.sim.nearest(x, y).map(|index| index as u32)
+    }
+
+    pub fn get_particle(&self, index: u32) -> Option<ParticleInfo> {
+        self.sim.particle_info(index as usize)
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        self.sim.resize(width, height);
+        self.track_reallocation();
+        Ok(())
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        self.sim.config = config.clamped();
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        self.sim.config
+    }
+
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.sim.boundary = mode;
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        self.sim.boundary
+    }
+
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        self.explosion = *config;
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        self.explosion
+    }
+
+    // update() と爆発を分けて計算する数を設定し、実際に使う数を返す（threads フィーチャーが必要）
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        self.sim.set_threads(threads)
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.sim.threads()
+    }
+
+    // 物理演算を SIMD 版にするか切り替え、実際に使うかを返す（simd フィーチャーが必要）
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        self.sim.set_simd(enabled)
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        self.sim.simd()
+    }
+
+    // パーティクル同士の衝突を切り替える（壁だけのときよりずっと重い）
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        self.sim.set_collisions(enabled);
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        self.sim.collisions()
+    }
+
+    pub fn get_contact_count(&self) -> usize {
+        self.sim.last_contacts()
+    }
+
+    // パーティクル数を作成時の数以下でその場で増減し、実際の数を返す（リセットはしない）
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let applied = self.sim.set_particle_count(count);
+        self.track_reallocation();
+        applied
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.sim.particles().len()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        self.sim.frame_count
+    }
+
+    // 成分ごとのコピー（JS では Float32Array になる）
+    pub fn get_x(&self) -> Vec<f32> {
+        self.sim.particles().x.clone()
+    }
+
+    pub fn get_y(&self) -> Vec<f32> {
+        self.sim.particles().y.clone()
+    }
+
+    // 速度(px/フレーム)
+    pub fn get_vx(&self) -> Vec<f32> {
+        self.sim.particles().vx.clone()
+    }
+
+    pub fn get_vy(&self) -> Vec<f32> {
+        self.sim.particles().vy.clone()
+    }
+
+    pub fn get_hue(&self) -> Vec<f32> {
+        self.sim.particles().hue.clone()
+    }
+
+    // 各バックエンドが描く色（パーティクルごとに r, g, b を 0.0〜1.0 で並べる）
+    pub fn get_colors(&self) -> Vec<f32> {
+        let hue = &self.sim.particles().hue;
+        let mut colors = Vec::with_capacity(hue.len() * 3);
+        for &hue in hue {
+            let (r, g, b) = hsl_to_rgb(hue, 1.0, 0.5);
+            colors.extend_from_slice(&[r, g, b]);
+        }
+        colors
+    }
+
+    // 成分ごとの配列の先頭（WASM のメモリ上のバイト位置）。長さは get_particle_count()
+    pub fn x_ptr(&self) -> *const f32 {
+        self.sim.particles().x.as_ptr()
+    }
+
+    pub fn y_ptr(&self) -> *const f32 {
+        self.sim.particles().y.as_ptr()
+    }
+
+    pub fn hue_ptr(&self) -> *const f32 {
+        self.sim.particles().hue.as_ptr()
+    }
+
+    // x_ptr() などから Float32Array を作るための WebAssembly.Memory
+    pub fn get_memory(&self) -> JsValue {
+        wasm_bindgen::memory()
+    }
+
+    // ポインタとビューを取り直す必要があるたびに増える
+    pub fn get_buffer_generation(&self) -> u32 {
+        self.generation
+    }
+
+    // 成分ごとの配列をそのまま見る Float32Array（無効になる条件は構造体のコメントのとおり）
+    pub fn x_view(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.sim.particles().x) }
+    }
+
+    pub fn y_view(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.sim.particles().y) }
+    }
+
+    pub fn hue_view(&self) -> js_sys::Float32Array {
+        unsafe { js_sys::Float32Array::view(&self.sim.particles().hue) }
+    }
+}
+
+impl ParticleSimulation {
+    fn track_reallocation(&mut self) {
+        let addresses = addresses(self.sim.particles());
+        if addresses != self.addresses {
+            self.addresses = addresses;
+            self.generation += 1;
+        }
+    }
+}
+
+fn addresses(particles: &ParticleSet) -> [usize; 3] {
+    [
+        particles.x.as_ptr() as usize,
+        particles.y.as_ptr() as usize,
+        particles.hue.as_ptr() as usize,
+    ]
+}
+
+// 積分の正しさを閉じた形の解と比べて確かめる（ネイティブで cargo test）
+// 積分は半陰的オイラー法（速度を更新してからその速度で位置を進める）で、1ステップ = 1フレーム
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timestep::DEFAULT_STEP_MS;
+    use rand::SeedableRng;
+
+    // 壁に届かない広さ
+    const OPEN: f32 = 1.0e6;
+
+    fn config(gravity: f32, bounce: f32, friction: f32) -> SimulationConfig {
+        SimulationConfig { gravity, bounce, friction, point_size: 2.5 }
+    }
+
+    fn particle(x: f32, y: f32, vx: f32, vy: f32) -> Particle {
+        Particle { x, y, vx, vy, hue: 0.0 }
+    }
+
+    fn run(mut p: Particle, steps: u32, width: f32, height: f32, config: &SimulationConfig) -> Particle {
+        for _ in 0..steps {
+            integrate(&mut p, width, height, 0.0, config, BoundaryMode::Bounce);
+        }
+        p
+    }
+
+    // 下向きを正とした力学的エネルギー（単位質量あたり）
+    fn energy(p: &Particle, gravity: f32) -> f64 {
+        let (vx, vy, y, g) = (p.vx as f64, p.vy as f64, p.y as f64, gravity as f64);
+        (vx * vx + vy * vy) / 2.0 - g * y
+    }
+
+    #[test]
+    fn matches_discrete_projectile() {
+        let (g, vx0, vy0, x0, y0) = (0.05f32, 0.5f32, -2.0f32, 100.0f32, 100.0f32);
+        let config = config(g, 0.85, 0.98);
+        for n in [1u32, 10, 100, 200] {
+            let p = run(particle(x0, y0, vx0, vy0), n, OPEN, OPEN, &config);
+            // vy_n = vy0 + n g、y_n = y0 + n vy0 + g n (n + 1) / 2
+            let nf = n as f64;
+            let expected_vy = vy0 as f64 + nf * g as f64;
+            let expected_y = y0 as f64 + nf * vy0 as f64 + g as f64 * nf * (nf + 1.0) / 2.0;
+            let expected_x = x0 as f64 + nf * vx0 as f64;
+            assert!((p.vy as f64 - expected_vy).abs() < 1e-4, "vy after {} steps: {}", n, p.vy);
+            assert!((p.y as f64 - expected_y).abs() < 1e-2, "y after {} steps: {}", n, p.y);
+            assert!((p.x as f64 - expected_x).abs() < 1e-3, "x after {} steps: {}", n, p.x);
+        }
+    }
+
+    #[test]
+    fn error_against_continuous_projectile_is_first_order() {
+        // 連続の y(t) = y0 + vy0 t + g t² / 2 とのずれは、半陰的オイラー法では g t / 2 になる
+        let (g, vy0, y0) = (0.05f32, -2.0f32, 100.0f32);
+        let config = config(g, 0.85, 0.98);
+        for n in [10u32, 100, 200] {
+            let p = run(particle(0.0, y0, 0.0, vy0), n, OPEN, OPEN, &config);
+            let t = n as f64;
+            let continuous = y0 as f64 + vy0 as f64 * t + g as f64 * t * t / 2.0;
+            let error = p.y as f64 - continuous;
+            assert!((error - g as f64 * t / 2.0).abs() < 1e-2, "error after {} steps: {}", n, error);
+        }
+    }
+
+    #[test]
+    fn modified_energy_is_conserved_in_free_flight() {
+        // 一様な重力では E + g vy / 2 がステップごとに厳密に保たれ、E 自体のずれも g |vy| / 2 以内に収まる
+        let g = 0.02f32;
+        let config = config(g, 0.85, 0.98);
+        let mut p = particle(10.0, 5_000.0, 1.5, -3.0);
+        let initial = energy(&p, g) + g as f64 * p.vy as f64 / 2.0;
+        for _ in 0..1_000 {
+            integrate(&mut p, OPEN, OPEN, 0.0, &config, BoundaryMode::Bounce);
+            let e = energy(&p, g);
+            let modified = e + g as f64 * p.vy as f64 / 2.0;
+            // f32 の丸め誤差の分
+            let tolerance = 1e-4 * initial.abs().max(e.abs()).max(1.0);
+            assert!((modified - initial).abs() < tolerance, "modified energy drifted to {}", modified);
+            assert!((e - initial).abs() <= (g * p.vy.abs()) as f64 / 2.0 + tolerance);
+        }
+    }
+
+    #[test]
+    fn elastic_bounces_do_not_gain_energy() {
+        // 跳ね返りで位置を床に戻すと、はみ出した分の位置エネルギーだけ失う（増えることはない）
+        let (g, height) = (0.05f32, 400.0f32);
+        let config = config(g, 1.0, 1.0);
+        let mut p = particle(50.0, 0.0, 0.0, 0.0);
+        let initial = energy(&p, g) + g as f64 * p.vy as f64 / 2.0;
+        let mut peak_speed = 0.0f32;
+        for _ in 0..20_000 {
+            integrate(&mut p, OPEN, height, 0.0, &config, BoundaryMode::Bounce);
+            peak_speed = peak_speed.max(p.vy.abs());
+            let modified = energy(&p, g) + g as f64 * p.vy as f64 / 2.0;
+            assert!(modified <= initial + (g * peak_speed) as f64, "energy grew to {}", modified);
+        }
+        // 落ちた高さから決まる床での速さ sqrt(2 g h) を大きく超えない
+        assert!(peak_speed as f64 <= (2.0 * g as f64 * height as f64).sqrt() + g as f64);
+    }
+
+    #[test]
+    fn floor_bounce_applies_restitution_and_friction() {
+        let config = config(0.0, 0.5, 0.9);
+        let (width, height) = (200.0, 100.0);
+        let p = run(particle(50.0, height - 1.0, 2.0, 5.0), 1, width, height, &config);
+        assert_eq!(p.y, height);
+        assert_eq!(p.vy, -5.0 * 0.5);
+        assert_eq!(p.vx, 2.0 * 0.9);
+        // 左右の壁は摩擦なしで向きだけ変える
+        let p = run(particle(width - 1.0, 50.0, 3.0, 0.0), 1, width, height, &config);
+        assert_eq!(p.x, width);
+        assert_eq!(p.vx, -3.0 * 0.5);
+    }
+
+    #[test]
+    fn simd_lanes_match_scalar_bit_for_bit() {
+        let config = config(0.03, 0.85, 0.98);
+        let (width, height) = (320.0, 240.0);
+        let mut set = ParticleSet::default();
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..37 {
+            let mut p = spawn_particle(&mut rng, width, height);
+            // 壁の外に出るものも混ぜる
+            p.vx *= 40.0;
+            p.vy *= 40.0;
+            set.push(p);
+        }
+        for boundary in [BoundaryMode::Bounce, BoundaryMode::Wrap, BoundaryMode::None] {
+            let mut set = set.clone();
+            let mut scalar = set.clone();
+            for _ in 0..300 {
+                let count = simd::integrate(&mut set.chunk(), width, height, 0.3, &config, boundary);
+                set.chunk().update_from(count, |p| integrate(p, width, height, 0.3, &config, boundary));
+                scalar.update_each(|p| integrate(p, width, height, 0.3, &config, boundary));
+            }
+            for (a, b) in [(&set.x, &scalar.x), (&set.y, &scalar.y), (&set.vx, &scalar.vx), (&set.vy, &scalar.vy), (&set.hue, &scalar.hue)] {
+                let a: Vec<u32> = a.iter().map(|v| v.to_bits()).collect();
+                let b: Vec<u32> = b.iter().map(|v| v.to_bits()).collect();
+                assert_eq!(a, b, "{:?}", boundary);
+            }
+        }
+    }
+
+    #[test]
+    fn pointer_drag_pushes_along_motion_and_swirls_clockwise() {
+        let mut pointer = PointerForces::default();
+        // 最初の位置は動いた量を求めるためだけに使う
+        pointer.push_drag(100.0, 100.0, 1.0);
+        assert!(pointer.is_empty());
+        pointer.push_drag(110.0, 100.0, 1.0);
+        let impulses = pointer.take();
+        assert_eq!(impulses.len(), 1);
+        let push = |x: f32, y: f32| {
+            let mut p = particle(x, y, 0.0, 0.0);
+            impulses[0].apply(&mut p);
+            p
+        };
+        // 通り道の両側とも動いた向きに流れ、渦で上側（y が小さい方）が速くなる
+        let (above, below) = (push(110.0, 80.0), push(110.0, 120.0));
+        assert!(below.vx > 0.0 && above.vx > below.vx, "{} {}", above.vx, below.vx);
+        // 前にあるものは時計回りに下へ回る
+        assert!(push(130.0, 100.0).vy > 0.0);
+        // 届かない距離のものは動かない
+        assert_eq!(push(400.0, 100.0).vx, 0.0);
+    }
+
+    #[test]
+    fn wrap_and_respawn_keep_particles_in_bounds() {
+        for boundary in [BoundaryMode::Wrap, BoundaryMode::Respawn] {
+            let mut sim = Simulation::new(320.0, 240.0, 500).unwrap();
+            sim.boundary = boundary;
+            sim.config = config(0.2, 0.85, 0.98);
+            for _ in 0..300 {
+                sim.step();
+            }
+            // Respawn は外に出たものを次のステップで戻すので、もう1ステップ進める前の状態を見る
+            if boundary == BoundaryMode::Respawn {
+                sim.respawn_outside();
+            }
+            let particles = sim.particles();
+            assert_eq!(particles.len(), 500);
+            for (&x, &y) in particles.x.iter().zip(&particles.y) {
+                assert!((0.0..=320.0).contains(&x) && (0.0..=240.0).contains(&y), "{:?}: ({}, {})", boundary, x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn perspective_keeps_depth_in_range_without_changing_flat_motion() {
+        // 奥行きは x, y の動きにも乱数の列にも影響しない
+        let mut flat = Simulation::new(320.0, 240.0, 200).unwrap();
+        let mut perspective = Simulation::new(320.0, 240.0, 200).unwrap();
+        for sim in [&mut flat, &mut perspective] {
+            sim.seed = Some(7);
+            sim.reset();
+        }
+        perspective.set_projection(ProjectionMode::Perspective);
+        for sim in [&mut flat, &mut perspective] {
+            sim.config = config(0.2, 0.85, 0.98);
+            for _ in 0..300 {
+                sim.step();
+            }
+        }
+        assert_eq!(flat.particles().x, perspective.particles().x);
+        assert_eq!(flat.particles().y, perspective.particles().y);
+        let depth = perspective.depth();
+        assert!(perspective.particles().z.iter().all(|z| (0.0..=depth).contains(z)));
+        assert!(flat.particles().z.iter().all(|&z| z == 0.0));
+    }
+
+    #[test]
+    fn trajectory_does_not_depend_on_how_dt_is_split() {
+        // 固定長のステップに分けるので、同じ時間をどう刻んで渡しても同じ数だけ進む
+        let config = config(0.05, 0.85, 0.98);
+        let mut results = Vec::new();
+        for chunk_ms in [4.0, 10.0, 16.0, 25.0, 40.0] {
+            let mut timestep = FixedTimestep::default();
+            let mut p = particle(10.0, 10.0, 1.0, 0.0);
+            let mut steps = 0;
+            let mut elapsed = 0.0;
+            while elapsed < 2_000.0 {
+                let n = timestep.advance(chunk_ms);
+                steps += n;
+                p = run(p, n, OPEN, OPEN, &config);
+                elapsed += chunk_ms;
+            }
+            results.push((steps, p.x.to_bits(), p.y.to_bits()));
+        }
+        let expected_steps = (2_000.0 / DEFAULT_STEP_MS) as u32;
+        for &(steps, x, y) in &results {
+            assert!(steps.abs_diff(expected_steps) <= 1, "{} steps", steps);
+            if steps == results[0].0 {
+                assert_eq!((x, y), (results[0].1, results[0].2));
+            }
+        }
+    }
+}
@@ -0,0 +1,184 @@
+// snapshot() が返すパーティクルの状態の形式
+// 数が多いと大きくなるので、値はそのままリトルエンディアンで並べる
+//
+// スキーマ（バージョン 3）:
+//   snapshot = magic:"PWSS" version:u8 width:f32 height:f32 frame_count:u32 hue_shift:f32
+//              count:u32 x:f32*count y:f32*count vx:f32*count vy:f32*count hue:f32*count life:f32*count
+//              size:f32*count alpha:f32*count z:f32*count vz:f32*count
+// バージョン 2 は z と vz が、バージョン 1 はさらに size と alpha がない
+// フィールドを増やすときは version を上げ、古い版も読めるようにする
+// 信頼できないページから渡されることもあるので、壊れた入力でも panic せずエラーを返す（fuzz/ で確かめる）
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+use crate::i18n::{tr, Text};
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+use crate::simulation::ParticleSet;
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const MAGIC: &[u8; 4] = b"PWSS";
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const VERSION: u8 = 3;
+// ヘッダーの大きさと、1パーティクルの値の数（バージョン 2 は z と vz の2つ、バージョン 1 はさらに size と alpha の2つ少ない）
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const HEADER_BYTES: usize = 4 + 1 + 4 * 5;
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const COLUMNS: usize = 10;
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const V2_COLUMNS: usize = 8;
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+const V1_COLUMNS: usize = 6;
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+pub(crate) struct Snapshot {
+    // 保存したときの領域の大きさ（今の大きさと違えば restore() で範囲外の扱いに従って戻す）
+    pub width: f32,
+    pub height: f32,
+    pub frame_count: u32,
+    pub hue_shift: f32,
+    pub particles: ParticleSet,
+    // false なら古い版で、particles の大きさと不透明度はすべて 1.0
+    pub has_style: bool,
+    // false なら古い版で、particles の奥行きの位置と速度はすべて 0
+    pub has_depth: bool,
+}
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+pub(crate) fn encode(
+    particles: &ParticleSet,
+    width: f32,
+    height: f32,
+    frame_count: u32,
+    hue_shift: f32,
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_BYTES + particles.len() * COLUMNS * 4);
+    bytes.extend_from_slice(MAGIC);
+    bytes.push(VERSION);
+    bytes.extend_from_slice(&width.to_le_bytes());
+    bytes.extend_from_slice(&height.to_le_bytes());
+    bytes.extend_from_slice(&frame_count.to_le_bytes());
+    bytes.extend_from_slice(&hue_shift.to_le_bytes());
+    bytes.extend_from_slice(&(particles.len() as u32).to_le_bytes());
+    for values in columns(particles) {
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+pub(crate) fn decode(bytes: &[u8]) -> Result<Snapshot, String> {
+    if bytes.len() < HEADER_BYTES || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(invalid());
+    }
+    let version = bytes[MAGIC.len()];
+    if version == 0 || version > VERSION {
+        return Err(tr(Text::SnapshotVersionUnsupported, &[&version]));
+    }
+    let mut words = bytes[MAGIC.len() + 1..]
+        .chunks_exact(4)
+        .map(|word| [word[0], word[1], word[2], word[3]]);
+    let mut next = || words.next().ok_or_else(invalid);
+    let width = f32::from_le_bytes(next()?);
+    let height = f32::from_le_bytes(next()?);
+    let frame_count = u32::from_le_bytes(next()?);
+    let hue_shift = f32::from_le_bytes(next()?);
+    let count = u32::from_le_bytes(next()?) as usize;
+    let (has_style, has_depth) = (version >= 2, version >= 3);
+    let stored_columns = match (has_style, has_depth) {
+        (_, true) => COLUMNS,
+        (true, false) => V2_COLUMNS,
+        (false, _) => V1_COLUMNS,
+    };
+    let particle_bytes = stored_columns * 4;
+    if bytes.len() - HEADER_BYTES != count.checked_mul(particle_bytes).ok_or_else(invalid)? {
+        return Err(invalid());
+    }
+    if !(width > 0.0 && height > 0.0 && hue_shift.is_finite()) {
+        return Err(invalid());
+    }
+
+    let mut particles = ParticleSet::default();
+    for (index, column) in columns_mut(&mut particles).into_iter().enumerate() {
+        column.reserve_exact(count);
+        if index >= stored_columns {
+            // 大きさと不透明度は 1.0、奥行きは 0
+            column.resize(count, if index < V2_COLUMNS { 1.0 } else { 0.0 });
+            continue;
+        }
+        for _ in 0..count {
+            column.push(f32::from_le_bytes(next()?));
+        }
+    }
+    // 寿命は無限大もある（寿命のないパーティクル）ので、NaN だけを弾く
+    if columns(&particles)
+        .iter()
+        .any(|values| values.iter().any(|v| v.is_nan()))
+    {
+        return Err(invalid());
+    }
+    // 大きさと不透明度は描画にそのまま使うので、有限で負でないものだけ受け付ける
+    if particles
+        .size
+        .iter()
+        .chain(&particles.alpha)
+        .any(|v| !(v.is_finite() && *v >= 0.0))
+    {
+        return Err(invalid());
+    }
+    // 奥行きも写すのに使うので有限なものだけ
+    if particles
+        .z
+        .iter()
+        .chain(&particles.vz)
+        .any(|v| !v.is_finite())
+    {
+        return Err(invalid());
+    }
+    Ok(Snapshot {
+        width,
+        height,
+        frame_count,
+        hue_shift,
+        particles,
+        has_style,
+        has_depth,
+    })
+}
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+fn columns(particles: &ParticleSet) -> [&Vec<f32>; COLUMNS] {
+    [
+        &particles.x,
+        &particles.y,
+        &particles.vx,
+        &particles.vy,
+        &particles.hue,
+        &particles.life,
+        &particles.size,
+        &particles.alpha,
+        &particles.z,
+        &particles.vz,
+    ]
+}
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+fn columns_mut(particles: &mut ParticleSet) -> [&mut Vec<f32>; COLUMNS] {
+    [
+        &mut particles.x,
+        &mut particles.y,
+        &mut particles.vx,
+        &mut particles.vy,
+        &mut particles.hue,
+        &mut particles.life,
+        &mut particles.size,
+        &mut particles.alpha,
+        &mut particles.z,
+        &mut particles.vz,
+    ]
+}
+
+#[cfg(any(target_arch = "wasm32", fuzzing))]
+fn invalid() -> String {
+    tr(Text::SnapshotInvalid, &[])
+}
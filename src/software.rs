@@ -0,0 +1,370 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, OffscreenCanvas};
+
+use crate::camera::Camera;
+use crate::canvas2d::ParticleSystemCanvas2D;
+use crate::color_theme::{ColorMode, ColorTheme};
+use crate::events::BenchmarkEvent;
+use crate::lod::LodMode;
+use crate::metrics::{FrameHistogram, Metrics};
+use crate::picking::ParticleInfo;
+use crate::raster::RasterSurface;
+use crate::render_mode::RenderMode;
+use crate::results::ResultFormat;
+use crate::scene::{ColorPoint, SceneKind};
+use crate::simulation::{Particle, ParticleSet};
+use crate::tuning::TuningProfile;
+
+// Canvas2D の描画APIを使わず、WASM内のRGBAの画素に塗ってから putImageData で1回だけ転送する描き方
+// 塗るのは raster.rs のラスタライザ（export_animation() と同じ）なので、シーンもそのまま描ける
+pub(crate) struct SoftwareRaster {
+    surface: RasterSurface,
+    // surface を作ったときのシミュレーション空間の大きさと倍率
+    size: (f32, f32, f32),
+    rgba: Vec<u8>,
+    points: Vec<ColorPoint>,
+}
+
+impl SoftwareRaster {
+    pub fn new() -> SoftwareRaster {
+        SoftwareRaster {
+            surface: RasterSurface::new(1.0, 1.0, 1.0),
+            size: (0.0, 0.0, 0.0),
+            rgba: Vec::new(),
+            points: Vec::new(),
+        }
+    }
+
+    // width × height の空間を pixel_ratio 倍の画素で塗る描画先（大きさが変わったときだけ作り直す）
+    pub fn surface(&mut self, width: f32, height: f32, pixel_ratio: f32) -> &mut RasterSurface {
+        if self.size != (width, height, pixel_ratio) {
+            self.surface = RasterSurface::new(width, height, 1.0 / pixel_ratio);
+            self.size = (width, height, pixel_ratio);
+        }
+        &mut self.surface
+    }
+
+    // filter を満たすパーティクルを一辺 size の正方形で mode で重ねて描く
+    // 色は theme の塗り方で、寿命が残り少ないものは、他のバックエンドと同じく背景色に近づける
+    pub fn draw_particles(
+        &mut self,
+        particles: &ParticleSet,
+        theme: &ColorTheme,
+        size: f32,
+        camera: Camera,
+        mode: RenderMode,
+        filter: impl Fn(&Particle) -> bool,
+    ) {
+        self.points.clear();
+        let [br, bg, bb] = theme.background();
+        for (p, &life) in particles
+            .iter()
+            .zip(&particles.life)
+            .filter(|(p, _)| filter(p))
+        {
+            let (r, g, b) = theme.rgb(&p);
+            let alpha = life.clamp(0.0, 1.0);
+            let fade = |c: f32, background: f32| background + (c - background) * alpha;
+            let (x, y) = camera.to_screen(p.x, p.y);
+            self.points.push(ColorPoint {
+                x,
+                y,
+                rgb: [fade(r, br), fade(g, bg), fade(b, bb)],
+            });
+        }
+        let size = size * camera.zoom;
+        crate::scene::Surface::draw_points(&mut self.surface, &self.points, size, mode);
+    }
+
+    // 決まった色の点を一辺 size の正方形で上書きする（障害物の輪郭など）
+    pub fn draw_points(&mut self, points: &[ColorPoint], size: f32) {
+        crate::scene::Surface::draw_points(&mut self.surface, points, size, RenderMode::Normal);
+    }
+
+    // 塗った画素をキャンバスの (x, y) に putImageData する（変換とクリップは効かない）
+    pub fn present(
+        &mut self,
+        ctx: &CanvasRenderingContext2d,
+        x: f32,
+        y: f32,
+    ) -> Result<(), JsValue> {
+        self.surface.to_rgba8(&mut self.rgba);
+        let (width, height) = self.surface.resolution();
+        let image =
+            ImageData::new_with_u8_clamped_array_and_sh(Clamped(&self.rgba), width, height)?;
+        ctx.put_image_data(&image, x as f64, y as f64)
+    }
+}
+
+// パーティクルを Rust だけで塗ってキャンバスに転送するバックエンド（Canvas2D と WebGL の間の比較用）
+// 中身は描き方だけを SoftwareRaster に替えた ParticleSystemCanvas2D なので、
+// ここにない操作は Backend（BackendKind::Software）から呼ぶ
+#[wasm_bindgen]
+pub struct ParticleSystemSoftware {
+    system: ParticleSystemCanvas2D,
+}
+
+#[wasm_bindgen]
+impl ParticleSystemSoftware {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: &str, particle_count: usize) -> Result<ParticleSystemSoftware, JsValue> {
+        let mut system = ParticleSystemCanvas2D::new(canvas_id, particle_count)?;
+        system.enable_software();
+        Ok(ParticleSystemSoftware { system })
+    }
+
+    // フレームワークが持っている canvas 要素から生成
+    pub fn from_canvas(
+        canvas: HtmlCanvasElement,
+        particle_count: usize,
+    ) -> Result<ParticleSystemSoftware, JsValue> {
+        let mut system = ParticleSystemCanvas2D::from_canvas(canvas, particle_count)?;
+        system.enable_software();
+        Ok(ParticleSystemSoftware { system })
+    }
+
+    // Worker に渡した OffscreenCanvas から生成
+    pub fn from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+        particle_count: usize,
+    ) -> Result<ParticleSystemSoftware, JsValue> {
+        let mut system = ParticleSystemCanvas2D::from_offscreen_canvas(canvas, particle_count)?;
+        system.enable_software();
+        Ok(ParticleSystemSoftware { system })
+    }
+
+    pub fn update(&mut self) {
+        self.system.update();
+    }
+
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        self.system.update_with_dt(dt_ms)
+    }
+
+    pub fn render(&mut self) {
+        self.system.render();
+    }
+
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        self.system.warmup(frames)
+    }
+
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.system.tick(timestamp)
+    }
+
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.system.set_target_fps(fps)
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        self.system.get_target_fps()
+    }
+
+    pub fn get_refresh_rate(&self) -> f64 {
+        self.system.get_refresh_rate()
+    }
+
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.system.get_rendered_ticks()
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.system.get_skipped_ticks()
+    }
+
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        self.system.set_particle_count(count)
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.system.get_particle_count()
+    }
+
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        self.system.switch_scene(kind)
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        self.system.resize(width, height)
+    }
+
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        self.system.set_pixel_ratio(ratio)
+    }
+
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        self.system.set_render_scale(scale)
+    }
+
+    pub fn explode(&mut self, click_x: f32, click_y: f32) {
+        self.system.explode(click_x, click_y);
+    }
+
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.system.stir(x, y, dx, dy);
+    }
+
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.system.pointer_move(x, y, dx, dy);
+    }
+
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        self.system.pointer_drag(x, y, strength);
+    }
+
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        self.system.attach_input_handlers()
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        self.system.detach_input_handlers();
+    }
+
+    pub fn dispose(self) {
+        self.system.dispose();
+    }
+
+    pub fn reset_camera(&mut self) {
+        self.system.reset_camera();
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        self.system.get_camera_zoom()
+    }
+
+    pub fn set_particle_color_mode(
+        &mut self,
+        mode: ColorMode,
+        colors: Vec<f32>,
+    ) -> Result<(), JsValue> {
+        self.system.set_particle_color_mode(mode, colors)
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        self.system.get_particle_color_mode()
+    }
+
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.system.set_background_color(r, g, b);
+    }
+
+    // [r, g, b]
+    pub fn get_background_color(&self) -> Vec<f32> {
+        self.system.get_background_color()
+    }
+
+    pub fn reset(&mut self) {
+        self.system.reset();
+    }
+
+    pub fn set_seed(&mut self, seed: u64) {
+        self.system.set_seed(seed);
+    }
+
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        self.system.set_lod(mode, skip);
+    }
+
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        self.system.set_adaptive_lod(mode, target_render_ms);
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        self.system.get_lod_mode()
+    }
+
+    pub fn get_lod_skip(&self) -> u32 {
+        self.system.get_lod_skip()
+    }
+
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        self.system.apply_tuning_profile(profile)
+    }
+
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        self.system.get_tuning_profile()
+    }
+
+    pub fn get_visible_particle_count(&self) -> usize {
+        self.system.get_visible_particle_count()
+    }
+
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.system.query_radius(x, y, radius)
+    }
+
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.system.nearest_particle(x, y)
+    }
+
+    pub fn get_particle(&self, index: u32) -> Option<ParticleInfo> {
+        self.system.get_particle(index)
+    }
+
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.system.snapshot()
+    }
+
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        self.system.capture_frame()
+    }
+
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.system.restore(bytes)
+    }
+
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        self.system.strict_benchmark(enabled);
+    }
+
+    pub fn pause(&mut self) {
+        self.system.pause();
+    }
+
+    pub fn resume(&mut self) {
+        self.system.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.system.is_paused()
+    }
+
+    pub fn step(&mut self, n: u32) {
+        self.system.step(n);
+    }
+
+    pub fn get_metrics(&self) -> Metrics {
+        self.system.get_metrics()
+    }
+
+    pub fn get_frame_histogram(&self, bucket_ms: f64) -> FrameHistogram {
+        self.system.get_frame_histogram(bucket_ms)
+    }
+
+    pub fn reset_frame_histogram(&mut self) {
+        self.system.reset_frame_histogram();
+    }
+
+    pub fn export_results(&self, format: ResultFormat, user_agent: &str) -> String {
+        self.system.export_results(format, user_agent)
+    }
+
+    pub fn download_results(
+        &self,
+        format: ResultFormat,
+        user_agent: &str,
+        filename: &str,
+    ) -> Result<(), JsValue> {
+        self.system.download_results(format, user_agent, filename)
+    }
+
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        self.system.poll_events()
+    }
+
+    pub fn get_config_fingerprint(&self) -> String {
+        self.system.get_config_fingerprint()
+    }
+}
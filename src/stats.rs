@@ -0,0 +1,476 @@
+use wasm_bindgen::prelude::*;
+
+// 計測値（フレーム時間など）のまとめ方
+// GCの停止やタブの切り替えで一部のフレームだけ極端に遅くなるので、平均以外も選べるようにする
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Aggregation {
+    // 平均と標準偏差
+    #[default]
+    Mean = 0,
+    // 中央値と MAD
+    Median = 1,
+    // 上下 TRIM_FRACTION ずつを除いた平均と MAD
+    TrimmedMean = 2,
+    // 中央値から OUTLIER_MADS × MAD より離れた値を除いた平均と標準偏差
+    RejectOutliers = 3,
+}
+
+impl Aggregation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Aggregation::Mean => "mean",
+            Aggregation::Median => "median",
+            Aggregation::TrimmedMean => "trimmed-mean",
+            Aggregation::RejectOutliers => "reject-outliers",
+        }
+    }
+}
+
+const TRIM_FRACTION: f64 = 0.1;
+const OUTLIER_MADS: f64 = 3.5;
+// 正規分布なら MAD にこれを掛けると標準偏差と同じ尺度になる
+const MAD_TO_STDDEV: f64 = 1.4826;
+// 平均絶対偏差の場合は √(π/2)
+const MEAN_AD_TO_STDDEV: f64 = 1.2533;
+
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SampleSummary {
+    // 代表値（平均・中央値など）
+    pub center: f64,
+    // ばらつき（標準偏差、または標準偏差の尺度に揃えた MAD）
+    pub spread: f64,
+    // 代表値の計算に使った数と、外れ値として除いた数
+    pub count: u32,
+    pub outliers: u32,
+}
+
+// JSで集めた計測値をまとめる
+#[wasm_bindgen]
+pub fn summarize_samples(samples: &[f64], aggregation: Aggregation) -> SampleSummary {
+    summarize(samples, aggregation)
+}
+
+pub(crate) fn summarize(samples: &[f64], aggregation: Aggregation) -> SampleSummary {
+    let mut sorted: Vec<f64> = samples.iter().copied().filter(|x| x.is_finite()).collect();
+    if sorted.is_empty() {
+        return SampleSummary::default();
+    }
+    sorted.sort_by(f64::total_cmp);
+
+    match aggregation {
+        Aggregation::Mean => SampleSummary {
+            center: mean(&sorted),
+            spread: stddev(&sorted),
+            count: sorted.len() as u32,
+            outliers: 0,
+        },
+        Aggregation::Median => SampleSummary {
+            center: median(&sorted),
+            spread: mad(&sorted),
+            count: sorted.len() as u32,
+            outliers: 0,
+        },
+        Aggregation::TrimmedMean => {
+            let trim = (sorted.len() as f64 * TRIM_FRACTION) as usize;
+            let kept = &sorted[trim..sorted.len() - trim];
+            SampleSummary {
+                center: mean(kept),
+                spread: mad(&sorted),
+                count: kept.len() as u32,
+                outliers: (trim * 2) as u32,
+            }
+        }
+        Aggregation::RejectOutliers => {
+            let center = median(&sorted);
+            // タイマーの分解能が粗いと半分以上が同じ値になり MAD が0になるので、
+            // そのときは平均絶対偏差で代える（それも0ならすべて同じ値）
+            let mut scale = mad(&sorted);
+            if scale == 0.0 {
+                scale = mean_absolute_deviation(&sorted, center) * MEAN_AD_TO_STDDEV;
+            }
+            let limit = OUTLIER_MADS * scale;
+            let kept: Vec<f64> = sorted
+                .iter()
+                .copied()
+                .filter(|x| (x - center).abs() <= limit)
+                .collect();
+            SampleSummary {
+                center: mean(&kept),
+                spread: stddev(&kept),
+                count: kept.len() as u32,
+                outliers: (sorted.len() - kept.len()) as u32,
+            }
+        }
+    }
+}
+
+// 時系列（フレーム時間など）を直線で近似し、最初から最後までに何割変わったか
+// 正なら後半ほど遅くなっている（端末の発熱による性能の低下など）
+#[wasm_bindgen]
+pub fn relative_drift(samples: &[f64]) -> f64 {
+    let n = samples.len();
+    if n < 2 {
+        return 0.0;
+    }
+    // 最小二乗法（x はフレームの番号）
+    let mean_x = (n - 1) as f64 / 2.0;
+    let mean_y = mean(samples);
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (i, y) in samples.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        covariance += dx * (y - mean_y);
+        variance += dx * dx;
+    }
+    let slope = covariance / variance;
+    let start = mean_y - slope * mean_x;
+    let end = mean_y + slope * mean_x;
+    if start <= 0.0 {
+        return 0.0;
+    }
+    (end - start) / start
+}
+
+// p パーセンタイル（0.0〜1.0、隣り合う値の間は線形補間）
+#[wasm_bindgen]
+pub fn percentile(samples: &[f64], p: f64) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let rank = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+// 2組の計測値の Mann-Whitney U 検定（正規近似・両側）
+// フレーム時間は正規分布にならないので、順位だけで差があるかを調べる
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RankTest {
+    // a の U 統計量
+    pub u: f64,
+    pub z: f64,
+    // a と b が同じ分布から取れたとしたときに、これ以上の差が出る確率（どちらかが空なら 1）
+    pub p_value: f64,
+    // b - a の差のすべての組の中央値（Hodges-Lehmann 推定量。正なら b の方が遅い）
+    // 組を並べずに求めるので、サンプルが数千個ずつでもメモリは a と b の分だけで済む
+    pub median_difference: f64,
+}
+
+#[wasm_bindgen]
+pub fn mann_whitney_u(a: &[f64], b: &[f64]) -> RankTest {
+    let (n1, n2) = (a.len() as f64, b.len() as f64);
+    if a.is_empty() || b.is_empty() {
+        return RankTest {
+            p_value: 1.0,
+            ..RankTest::default()
+        };
+    }
+
+    // まとめて並べた順位（同じ値には平均の順位を付ける）
+    let mut all: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&x| (x, true))
+        .chain(b.iter().map(|&x| (x, false)))
+        .collect();
+    all.sort_by(|x, y| x.0.total_cmp(&y.0));
+    let (mut rank_sum_a, mut ties) = (0.0, 0.0);
+    let mut i = 0;
+    while i < all.len() {
+        let mut j = i;
+        while j + 1 < all.len() && all[j + 1].0 == all[i].0 {
+            j += 1;
+        }
+        let rank = (i + j) as f64 / 2.0 + 1.0;
+        rank_sum_a += rank * all[i..=j].iter().filter(|(_, in_a)| *in_a).count() as f64;
+        let t = (j - i + 1) as f64;
+        ties += t * t * t - t;
+        i = j + 1;
+    }
+
+    let u = rank_sum_a - n1 * (n1 + 1.0) / 2.0;
+    let n = n1 + n2;
+    let mean_u = n1 * n2 / 2.0;
+    let variance = n1 * n2 / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)));
+    let z = if variance > 0.0 {
+        // 連続性の補正
+        let diff = u - mean_u;
+        (diff - 0.5 * diff.signum()) / variance.sqrt()
+    } else {
+        0.0
+    };
+
+    RankTest {
+        u,
+        z,
+        p_value: erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0),
+        median_difference: median_difference(a, b),
+    }
+}
+
+// b - a のすべての組の差の中央値（有限でない値は除く）
+fn median_difference(a: &[f64], b: &[f64]) -> f64 {
+    let sorted = |samples: &[f64]| {
+        let mut sorted: Vec<f64> = samples.iter().copied().filter(|x| x.is_finite()).collect();
+        sorted.sort_by(f64::total_cmp);
+        sorted
+    };
+    let (a, b) = (sorted(a), sorted(b));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let pairs = a.len() as u64 * b.len() as u64;
+    if pairs % 2 == 1 {
+        kth_difference(&a, &b, pairs / 2 + 1)
+    } else {
+        (kth_difference(&a, &b, pairs / 2) + kth_difference(&a, &b, pairs / 2 + 1)) / 2.0
+    }
+}
+
+// 昇順の a と b について、b - a の差のうち k 番目（1 始まり）に小さい値
+// 差が t 以下の組の数は尺取りで O(n + m) で数えられるので、それが k 以上になる最小の t を2分探索する
+// t は f64 を大小の順に並ぶ整数に写して探すので、約64回で差そのものの値が正確に求まる
+fn kth_difference(a: &[f64], b: &[f64], k: u64) -> f64 {
+    let count_at_most = |t: f64| {
+        // y が大きくなるほど y - x <= t となる x の下限も大きくなる
+        let mut first = 0;
+        let mut count = 0u64;
+        for &y in b {
+            while first < a.len() && y - a[first] > t {
+                first += 1;
+            }
+            count += (a.len() - first) as u64;
+        }
+        count
+    };
+    let (mut low, mut high) = (
+        order_key(b[0] - a[a.len() - 1]),
+        order_key(b[b.len() - 1] - a[0]),
+    );
+    while low < high {
+        let mid = (low as i128 + high as i128).div_euclid(2) as i64;
+        if count_at_most(from_order_key(mid)) >= k {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    from_order_key(low)
+}
+
+// f64::total_cmp と同じ順に並ぶ整数（自分自身が逆変換）
+fn order_key(x: f64) -> i64 {
+    let bits = x.to_bits() as i64;
+    bits ^ (((bits >> 63) as u64) >> 1) as i64
+}
+
+fn from_order_key(key: i64) -> f64 {
+    f64::from_bits((key ^ (((key >> 63) as u64) >> 1) as i64) as u64)
+}
+
+// 相補誤差関数（Numerical Recipes の近似、相対誤差 1.2e-7 以下）
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+// 標本標準偏差（n - 1 で割る。1個なら0）
+pub(crate) fn stddev(samples: &[f64]) -> f64 {
+    if samples.len() < 2 {
+        return 0.0;
+    }
+    let mean = mean(samples);
+    let squares: f64 = samples.iter().map(|x| (x - mean).powi(2)).sum();
+    (squares / (samples.len() - 1) as f64).sqrt()
+}
+
+// sorted は昇順に並んでいること
+fn median(sorted: &[f64]) -> f64 {
+    let mid = sorted.len() / 2;
+    if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    }
+}
+
+// 中央値からの偏差の中央値（標準偏差の尺度に揃える）
+fn mad(sorted: &[f64]) -> f64 {
+    let center = median(sorted);
+    let mut deviations: Vec<f64> = sorted.iter().map(|x| (x - center).abs()).collect();
+    deviations.sort_by(f64::total_cmp);
+    median(&deviations) * MAD_TO_STDDEV
+}
+
+fn mean_absolute_deviation(samples: &[f64], center: f64) -> f64 {
+    samples.iter().map(|x| (x - center).abs()).sum::<f64>() / samples.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(actual: f64, expected: f64) -> bool {
+        (actual - expected).abs() <= 1e-6 * expected.abs().max(1.0)
+    }
+
+    // すべての組の差を並べた中央値（小さい入力での答え合わせ用）
+    fn naive_median_difference(a: &[f64], b: &[f64]) -> f64 {
+        let mut differences: Vec<f64> = b
+            .iter()
+            .flat_map(|&y| a.iter().map(move |&x| y - x))
+            .collect();
+        differences.sort_by(f64::total_cmp);
+        median(&differences)
+    }
+
+    #[test]
+    fn mann_whitney_u_matches_known_values() {
+        // scipy.stats.mannwhitneyu(a, b, method="asymptotic") と同じ値
+        let test = mann_whitney_u(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]);
+        assert_eq!(test.u, 0.0);
+        assert!(close(test.z, -1.7457431218879391), "{}", test.z);
+        assert!(close(test.p_value, 0.0808555983700523), "{}", test.p_value);
+        assert_eq!(test.median_difference, 3.0);
+
+        let reversed = mann_whitney_u(&[4.0, 5.0, 6.0], &[1.0, 2.0, 3.0]);
+        assert_eq!(reversed.u, 9.0);
+        assert!(close(reversed.p_value, test.p_value));
+        assert_eq!(reversed.median_difference, -3.0);
+    }
+
+    #[test]
+    fn mann_whitney_u_averages_tied_ranks() {
+        let test = mann_whitney_u(&[1.0, 2.0, 2.0, 3.0], &[2.0, 3.0, 3.0, 4.0]);
+        assert_eq!(test.u, 3.0);
+        assert!(close(test.z, -1.365698202000489), "{}", test.z);
+        assert!(close(test.p_value, 0.17203370892182296), "{}", test.p_value);
+        assert_eq!(test.median_difference, 1.0);
+
+        // すべて同じ値なら差はない
+        let same = mann_whitney_u(&[5.0; 4], &[5.0; 6]);
+        assert_eq!(
+            (same.z, same.p_value, same.median_difference),
+            (0.0, 1.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn mann_whitney_u_with_empty_input_finds_no_difference() {
+        for (a, b) in [
+            (&[][..], &[1.0][..]),
+            (&[1.0][..], &[][..]),
+            (&[][..], &[][..]),
+        ] {
+            let test = mann_whitney_u(a, b);
+            assert_eq!(
+                (test.u, test.z, test.p_value, test.median_difference),
+                (0.0, 0.0, 1.0, 0.0)
+            );
+        }
+    }
+
+    #[test]
+    fn median_difference_matches_every_pair() {
+        let a = [3.5, -1.0, 2.0, 2.0, 8.25, 0.1, 7.0];
+        let b = [2.0, 9.5, -4.0, 2.0, 0.3, 6.0];
+        for (a, b) in [
+            (&a[..], &b[..]),
+            (&b[..], &a[..]),
+            (&a[..3], &b[..]),
+            (&a[..], &b[..1]),
+        ] {
+            assert_eq!(
+                median_difference(a, b),
+                naive_median_difference(a, b),
+                "{:?} {:?}",
+                a,
+                b
+            );
+        }
+        // 数千個ずつでも組を並べずに求まる
+        let a: Vec<f64> = (0..4000).map(|i| 10.0 + (i % 97) as f64 * 0.01).collect();
+        let b: Vec<f64> = (0..5000).map(|i| 12.0 + (i % 89) as f64 * 0.01).collect();
+        let difference = median_difference(&a, &b);
+        assert!((1.9..2.1).contains(&difference), "{}", difference);
+    }
+
+    #[test]
+    fn summarize_each_aggregation() {
+        let mean = summarize(&[1.0, 2.0, 3.0, 4.0], Aggregation::Mean);
+        assert_eq!((mean.center, mean.count, mean.outliers), (2.5, 4, 0));
+        assert!(close(mean.spread, 1.2909944487358056));
+
+        let median = summarize(&[100.0, 2.0, 1.0, 4.0, 3.0], Aggregation::Median);
+        assert_eq!((median.center, median.count), (3.0, 5));
+        assert!(close(median.spread, MAD_TO_STDDEV));
+
+        // 10個なら上下1個ずつ除く
+        let samples: Vec<f64> = (1..=9).map(f64::from).chain([1000.0]).collect();
+        let trimmed = summarize(&samples, Aggregation::TrimmedMean);
+        assert_eq!(
+            (trimmed.center, trimmed.count, trimmed.outliers),
+            (5.5, 8, 2)
+        );
+
+        let rejected = summarize(
+            &[10.0, 10.5, 9.5, 10.0, 10.25, 9.75, 60.0],
+            Aggregation::RejectOutliers,
+        );
+        assert_eq!(
+            (rejected.center, rejected.count, rejected.outliers),
+            (10.0, 6, 1)
+        );
+    }
+
+    #[test]
+    fn summarize_skips_non_finite_and_empty_input() {
+        let summary = summarize(&[f64::NAN, 2.0, f64::INFINITY, 4.0], Aggregation::Mean);
+        assert_eq!((summary.center, summary.count), (3.0, 2));
+        for aggregation in [
+            Aggregation::Mean,
+            Aggregation::Median,
+            Aggregation::TrimmedMean,
+            Aggregation::RejectOutliers,
+        ] {
+            let empty = summarize(&[f64::NAN], aggregation);
+            assert_eq!(
+                (empty.center, empty.spread, empty.count, empty.outliers),
+                (0.0, 0.0, 0, 0)
+            );
+        }
+    }
+
+    #[test]
+    fn relative_drift_follows_the_fitted_line() {
+        assert!(close(relative_drift(&[1.0, 2.0, 3.0]), 2.0));
+        assert!(close(relative_drift(&[4.0, 3.0, 2.0]), -0.5));
+        assert_eq!(relative_drift(&[5.0; 10]), 0.0);
+        assert_eq!(relative_drift(&[5.0]), 0.0);
+        assert_eq!(relative_drift(&[]), 0.0);
+        // 直線の始まりが0以下なら割合にできない
+        assert_eq!(relative_drift(&[-1.0, 1.0]), 0.0);
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+
+use crate::backend::Backend;
+use crate::context::{AcquiredContext, CanvasElement, ContextSource};
+use crate::timing;
+
+// 残すサンプル数の既定値と上限
+const DEFAULT_CAPACITY: u32 = 240;
+const MAX_CAPACITY: u32 = 4_096;
+// フレーム時間の縦軸の最小の上端(ms)。これより速いときも 60fps / 30fps の線が見えるようにする
+const MIN_SCALE_MS: f64 = 1000.0 / 30.0;
+const BUDGET_LINES_MS: [f64; 2] = [1000.0 / 60.0, 1000.0 / 30.0];
+
+const BACKGROUND: &str = "#141418";
+const GUIDE: &str = "#3a3a44";
+const FRAME_TIME: &str = "#4fa6ff";
+const PARTICLE_COUNT: &str = "#ffb347";
+const TEXT: &str = "#e6e6e6";
+
+// 別の小さなキャンバスに、直近のフレーム時間の折れ線とパーティクル数の推移を描く
+// JS のグラフライブラリを使わずに、計測ページへ動く統計のグラフを置ける
+// 毎フレーム sample() か push() を呼ぶと描き直す
+#[wasm_bindgen]
+pub struct StatsChart {
+    ctx: CanvasRenderingContext2d,
+    width: f64,
+    height: f64,
+    capacity: usize,
+    // (フレーム時間(ms), パーティクル数)
+    samples: VecDeque<(f64, f64)>,
+    // sample() で間隔を測るための前回の時刻
+    last_sample: Option<f64>,
+}
+
+#[wasm_bindgen]
+impl StatsChart {
+    // capacity は横に並べるサンプル数（0 なら既定値）
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas: HtmlCanvasElement, capacity: u32) -> Result<StatsChart, JsValue> {
+        let AcquiredContext {
+            context: ctx,
+            width,
+            height,
+        } = CanvasElement(&canvas).acquire()?;
+        let capacity = match capacity {
+            0 => DEFAULT_CAPACITY,
+            capacity => capacity.min(MAX_CAPACITY),
+        };
+        Ok(StatsChart {
+            ctx,
+            width: width as f64,
+            height: height as f64,
+            capacity: capacity as usize,
+            samples: VecDeque::new(),
+            last_sample: None,
+        })
+    }
+
+    // backend の render() の後に呼ぶ。前回の sample() からの間隔をフレーム時間として記録して描く
+    pub fn sample(&mut self, backend: &Backend) {
+        let now = timing::now_ms();
+        let Some(last) = self.last_sample.replace(now) else {
+            return;
+        };
+        self.push(now - last, backend.get_particle_count() as f64);
+    }
+
+    // 自分で測った値を1つ足して描く
+    pub fn push(&mut self, frame_ms: f64, particle_count: f64) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples
+            .push_back((frame_ms.max(0.0), particle_count.max(0.0)));
+        self.draw();
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.last_sample = None;
+        self.draw();
+    }
+
+    pub fn draw(&self) {
+        let ctx = &self.ctx;
+        ctx.set_fill_style_str(BACKGROUND);
+        ctx.fill_rect(0.0, 0.0, self.width, self.height);
+
+        let max_ms = self
+            .samples
+            .iter()
+            .map(|&(frame_ms, _)| frame_ms)
+            .fold(MIN_SCALE_MS, f64::max);
+        let max_count = self
+            .samples
+            .iter()
+            .map(|&(_, count)| count)
+            .fold(1.0, f64::max);
+
+        ctx.set_line_width(1.0);
+        ctx.set_stroke_style_str(GUIDE);
+        for budget in BUDGET_LINES_MS {
+            let y = self.y_of(budget / max_ms);
+            ctx.begin_path();
+            ctx.move_to(0.0, y);
+            ctx.line_to(self.width, y);
+            ctx.stroke();
+        }
+
+        self.trace(PARTICLE_COUNT, |(_, count)| count / max_count);
+        self.trace(FRAME_TIME, |(frame_ms, _)| frame_ms / max_ms);
+
+        if let Some(&(frame_ms, count)) = self.samples.back() {
+            ctx.set_font("10px monospace");
+            ctx.set_fill_style_str(TEXT);
+            let _ = ctx.fill_text(&format!("{:.1} ms", frame_ms), 4.0, 12.0);
+            let _ = ctx.fill_text(&format!("{} particles", count as u64), 4.0, 24.0);
+        }
+    }
+}
+
+impl StatsChart {
+    // 0.0〜1.0 の高さをキャンバスの y にする（下端が 0）
+    fn y_of(&self, t: f64) -> f64 {
+        self.height - t.clamp(0.0, 1.0) * (self.height - 1.0)
+    }
+
+    // 古いものから左詰めで、value で 0.0〜1.0 にした折れ線を描く
+    fn trace(&self, color: &str, value: impl Fn((f64, f64)) -> f64) {
+        if self.samples.len() < 2 {
+            return;
+        }
+        let step = self.width / (self.capacity.max(2) - 1) as f64;
+        let ctx = &self.ctx;
+        ctx.set_stroke_style_str(color);
+        ctx.begin_path();
+        for (i, &sample) in self.samples.iter().enumerate() {
+            let (x, y) = (i as f64 * step, self.y_of(value(sample)));
+            if i == 0 {
+                ctx.move_to(x, y);
+            } else {
+                ctx.line_to(x, y);
+            }
+        }
+        ctx.stroke();
+    }
+}
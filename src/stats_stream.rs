@@ -0,0 +1,87 @@
+use wasm_bindgen::prelude::*;
+
+use crate::timing;
+
+// subscribe_stats() のコールバックに渡す、前回の通知からの差分
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug)]
+pub struct StatsDelta {
+    // 前回の通知から描画したフレーム数と経過時間(ms)
+    pub frames: u32,
+    pub elapsed_ms: f64,
+    pub fps: f64,
+    // render() の呼び出し間隔の平均と最大(ms)
+    pub mean_frame_ms: f64,
+    pub max_frame_ms: f64,
+    // 通知時点のフレーム番号
+    pub frame_count: u32,
+}
+
+// 通知間隔の下限(ms)。ダッシュボードの更新で計測を乱さないよう、毎フレームの通知はさせない
+const MIN_INTERVAL_MS: f64 = 50.0;
+
+// render() のたびに数えるだけにして、コールバックは interval_ms ごとに1回だけ呼ぶ
+#[derive(Default)]
+pub(crate) struct StatsStream {
+    callback: Option<js_sys::Function>,
+    interval_ms: f64,
+    // 前回の通知の時刻と、それ以降の集計
+    window_start: f64,
+    last_frame: Option<f64>,
+    frames: u32,
+    frame_ms_sum: f64,
+    max_frame_ms: f64,
+}
+
+impl StatsStream {
+    pub fn subscribe(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        *self = StatsStream {
+            callback: Some(callback),
+            interval_ms: interval_ms.max(MIN_INTERVAL_MS),
+            window_start: timing::now_ms(),
+            ..StatsStream::default()
+        };
+    }
+
+    pub fn unsubscribe(&mut self) {
+        *self = StatsStream::default();
+    }
+
+    // render() の最初に呼ぶ
+    pub fn note_frame(&mut self, frame_count: u32) {
+        if self.callback.is_none() {
+            return;
+        }
+        let now = timing::now_ms();
+        if let Some(last) = self.last_frame.replace(now) {
+            let frame_ms = now - last;
+            self.frames += 1;
+            self.frame_ms_sum += frame_ms;
+            self.max_frame_ms = self.max_frame_ms.max(frame_ms);
+        }
+
+        let elapsed = now - self.window_start;
+        if elapsed < self.interval_ms {
+            return;
+        }
+        let delta = StatsDelta {
+            frames: self.frames,
+            elapsed_ms: elapsed,
+            fps: self.frames as f64 * 1000.0 / elapsed,
+            mean_frame_ms: if self.frames > 0 {
+                self.frame_ms_sum / self.frames as f64
+            } else {
+                0.0
+            },
+            max_frame_ms: self.max_frame_ms,
+            frame_count,
+        };
+        self.window_start = now;
+        self.frames = 0;
+        self.frame_ms_sum = 0.0;
+        self.max_frame_ms = 0.0;
+        if let Some(callback) = &self.callback {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(delta));
+        }
+    }
+}
@@ -0,0 +1,652 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::HtmlCanvasElement;
+
+use crate::backend::{describe_error, Backend, BackendConfig, BackendKind};
+use crate::context::CanvasElement;
+use crate::error::BenchError;
+use crate::fingerprint::ConfigFingerprint;
+use crate::i18n::{tr, Text};
+use crate::jank::{JankClassifier, JankReport};
+use crate::report;
+use crate::rng;
+use crate::scene::{require_backend, SceneKind};
+use crate::stats::{self, Aggregation};
+pub use crate::suite_report::{
+    DrawCallOverhead, OverdrawSensitivity, SuiteReport, SuiteResult, TransformOverhead,
+};
+use crate::timing;
+
+// BenchmarkSuite::run() に渡す設定
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct SuiteConfig {
+    // 各バックエンド用に作るキャンバスの大きさ
+    pub width: u32,
+    pub height: u32,
+    pub particle_count: usize,
+    // 計測前に捨てるフレーム数（JITやキャッシュを温める）
+    pub warmup_frames: u32,
+    pub measure_frames: u32,
+    // 1組を何回計測するか（毎回バックエンドを作り直す）
+    pub trials: u32,
+    // 試行ごとの代表値のばらつきが代表値のこの割合を超えたら信頼できない結果とする
+    pub max_relative_stddev: f64,
+    // フレーム時間と試行ごとの値のまとめ方（GCの停止などの外れ値に強いものも選べる）
+    pub aggregation: Aggregation,
+    // 1回の試行のあいだにフレーム時間がこの割合より増えたら発熱による低下として警告する
+    pub max_drift: f64,
+    // シーンごとにこのシードから始めるので、どのバックエンドでも同じ状態を計測する
+    pub seed: u64,
+    // None ならシーンごとの既定
+    pub load: Option<f32>,
+    // true なら色相の変化など見た目だけの処理を止めて計測する
+    pub strict_benchmark: bool,
+    // 空でなければ一致する組だけを計測する（"webgl:physarum" のような "バックエンド:シーン" か、
+    // バックエンドのすべてのシーンを表す "canvas2d"）
+    pub only: Vec<String>,
+    // Some ならこのキーで localStorage に進捗を保存し、ページを読み込み直しても続きから再開する
+    pub checkpoint_key: Option<String>,
+    // true なら SuiteResult.samples にフレームごとの時間を残す
+    pub keep_samples: bool,
+}
+
+#[wasm_bindgen]
+impl SuiteConfig {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SuiteConfig {
+        SuiteConfig {
+            width: 800,
+            height: 600,
+            particle_count: 10_000,
+            warmup_frames: 30,
+            measure_frames: 120,
+            trials: 1,
+            max_relative_stddev: 0.1,
+            aggregation: Aggregation::Mean,
+            max_drift: 0.1,
+            seed: 42,
+            load: None,
+            strict_benchmark: true,
+            only: Vec::new(),
+            checkpoint_key: None,
+            keep_samples: false,
+        }
+    }
+}
+
+impl Default for SuiteConfig {
+    fn default() -> SuiteConfig {
+        SuiteConfig::new()
+    }
+}
+
+impl SuiteResult {
+    fn skipped(backend: BackendKind, scene: SceneKind, error: String) -> SuiteResult {
+        SuiteResult {
+            backend: backend.name().to_string(),
+            scene: scene.name().to_string(),
+            supported: false,
+            error: Some(error),
+            trials: 0,
+            frames: 0,
+            mean_ms: 0.0,
+            stddev_ms: 0.0,
+            aggregation: String::new(),
+            outliers: 0,
+            min_ms: 0.0,
+            max_ms: 0.0,
+            fps: 0.0,
+            reliable: false,
+            drift: 0.0,
+            warning: None,
+            gc_pauses: 0,
+            render_stalls: 0,
+            host_stalls: 0,
+            fingerprint: String::new(),
+            samples: Vec::new(),
+        }
+    }
+
+    fn from_trials(
+        backend: BackendKind,
+        scene: SceneKind,
+        trials: &[Trial],
+        config: &SuiteConfig,
+    ) -> SuiteResult {
+        let aggregation = config.aggregation;
+        let centers: Vec<f64> = trials.iter().map(|trial| trial.mean_ms).collect();
+        let summary = stats::summarize(&centers, aggregation);
+        let (mean, stddev) = (summary.center, summary.spread);
+        let drift = trials
+            .iter()
+            .map(|trial| trial.drift)
+            .fold(f64::MIN, f64::max);
+        SuiteResult {
+            backend: backend.name().to_string(),
+            scene: scene.name().to_string(),
+            supported: true,
+            error: None,
+            trials: trials.len() as u32,
+            frames: trials.iter().map(|trial| trial.frames).sum(),
+            mean_ms: mean,
+            stddev_ms: stddev,
+            aggregation: aggregation.name().to_string(),
+            outliers: trials.iter().map(|trial| trial.outliers).sum(),
+            min_ms: trials
+                .iter()
+                .map(|trial| trial.min_ms)
+                .fold(f64::MAX, f64::min),
+            max_ms: trials.iter().map(|trial| trial.max_ms).fold(0.0, f64::max),
+            fps: if mean > 0.0 { 1000.0 / mean } else { 0.0 },
+            reliable: mean <= 0.0 || stddev / mean <= config.max_relative_stddev,
+            drift,
+            warning: (drift > config.max_drift)
+                .then(|| tr(Text::SuiteThermalDrift, &[&(drift * 100.0).round()])),
+            gc_pauses: trials.iter().map(|trial| trial.jank.gc_pauses).sum(),
+            render_stalls: trials.iter().map(|trial| trial.jank.render_stalls).sum(),
+            host_stalls: trials.iter().map(|trial| trial.jank.host_stalls).sum(),
+            fingerprint: trials[0].fingerprint.clone(),
+            samples: if config.keep_samples {
+                trials
+                    .iter()
+                    .flat_map(|trial| trial.samples.iter().map(|&sample| sample as f32))
+                    .collect()
+            } else {
+                Vec::new()
+            },
+        }
+    }
+}
+
+// 1回の試行の結果
+struct Trial {
+    frames: u32,
+    // フレーム時間を aggregation でまとめた代表値
+    mean_ms: f64,
+    outliers: u32,
+    drift: f64,
+    jank: JankReport,
+    min_ms: f64,
+    max_ms: f64,
+    fingerprint: String,
+    samples: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl SuiteReport {
+    // バックエンドごとの重ね塗りへの弱さ（Overdraw と OverdrawSpread の両方を計測できたものだけ）
+    pub fn overdraw_sensitivity(&self) -> Vec<OverdrawSensitivity> {
+        BACKENDS
+            .iter()
+            .filter_map(|&kind| {
+                let concentrated_ms = self.mean_ms(kind, SceneKind::Overdraw)?;
+                let spread_ms = self.mean_ms(kind, SceneKind::OverdrawSpread)?;
+                Some(OverdrawSensitivity {
+                    backend: kind.name().to_string(),
+                    concentrated_ms,
+                    spread_ms,
+                    ratio: if spread_ms > 0.0 {
+                        concentrated_ms / spread_ms
+                    } else {
+                        0.0
+                    },
+                })
+            })
+            .collect()
+    }
+
+    // バックエンドごとの描画命令のコスト（DrawCalls と DrawCallsBatched の両方を計測できたものだけ）
+    pub fn draw_call_overhead(&self) -> Vec<DrawCallOverhead> {
+        BACKENDS
+            .iter()
+            .filter_map(|&kind| {
+                let separate_ms = self.mean_ms(kind, SceneKind::DrawCalls)?;
+                let batched_ms = self.mean_ms(kind, SceneKind::DrawCallsBatched)?;
+                Some(DrawCallOverhead {
+                    backend: kind.name().to_string(),
+                    separate_ms,
+                    batched_ms,
+                    ratio: if batched_ms > 0.0 {
+                        separate_ms / batched_ms
+                    } else {
+                        0.0
+                    },
+                })
+            })
+            .collect()
+    }
+
+    // バックエンドごとの変換APIのコスト（Transforms と TransformsPrecomputed の両方を計測できたものだけ）
+    pub fn transform_overhead(&self) -> Vec<TransformOverhead> {
+        BACKENDS
+            .iter()
+            .filter_map(|&kind| {
+                let save_restore_ms = self.mean_ms(kind, SceneKind::Transforms)?;
+                let precomputed_ms = self.mean_ms(kind, SceneKind::TransformsPrecomputed)?;
+                Some(TransformOverhead {
+                    backend: kind.name().to_string(),
+                    save_restore_ms,
+                    precomputed_ms,
+                    ratio: if precomputed_ms > 0.0 {
+                        save_restore_ms / precomputed_ms
+                    } else {
+                        0.0
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+impl SuiteReport {
+    // 計測できた組の代表値
+    fn mean_ms(&self, kind: BackendKind, scene: SceneKind) -> Option<f64> {
+        self.results
+            .iter()
+            .find(|result| {
+                result.supported && result.backend == kind.name() && result.scene == scene.name()
+            })
+            .map(|result| result.mean_ms)
+    }
+}
+
+// すべてのバックエンドとシーンを固定シードで順に計測する
+#[wasm_bindgen]
+pub struct BenchmarkSuite;
+
+#[wasm_bindgen]
+impl BenchmarkSuite {
+    // 計測が終わると SuiteReport で解決する Promise を返す
+    // 1組ごとにブラウザへ制御を返すので、実行中もページは固まらない
+    pub fn run(config: &SuiteConfig) -> js_sys::Promise {
+        let config = config.clone();
+        future_to_promise(async move { run_suite(config).await.map(JsValue::from) })
+    }
+}
+
+pub(crate) const BACKENDS: [BackendKind; 5] = [
+    BackendKind::WebGpu,
+    BackendKind::WebGl2,
+    BackendKind::WebGl,
+    BackendKind::Canvas2D,
+    BackendKind::Software,
+];
+
+async fn run_suite(config: SuiteConfig) -> Result<SuiteReport, JsValue> {
+    let start = timing::now_ms();
+    let selected = select_cases(&config.only)?;
+    let mut checkpoint = match &config.checkpoint_key {
+        Some(key) => Checkpoint::load(key, &config_hash(&config))?,
+        None => Checkpoint::default(),
+    };
+    let mut results = Vec::new();
+
+    for kind in BACKENDS {
+        // 前回までに終わった組は保存した結果を使う
+        let mut pending = Vec::new();
+        for scene in SceneKind::ALL {
+            if !selected(kind, scene) {
+                continue;
+            }
+            match checkpoint.result(kind, scene) {
+                Some(result) => results.push(result),
+                None => pending.push(scene),
+            }
+        }
+        // 残りがなければコンテキストも作らない
+        if pending.is_empty() {
+            continue;
+        }
+
+        // 作れないバックエンドはすべての組を飛ばす。作れたものは最初の試行にそのまま使う
+        let mut spare = match create_backend(kind, &config).await {
+            Ok(backend) => Some(backend),
+            Err(error) => {
+                let error = describe_error(&error);
+                for &scene in &pending {
+                    let result = SuiteResult::skipped(kind, scene, error.clone());
+                    checkpoint.finish(&result);
+                    results.push(result);
+                }
+                continue;
+            }
+        };
+
+        for scene in pending {
+            let result = if checkpoint.was_interrupted(kind, scene) {
+                // 計測中にページが読み込み直された組は、もう一度試さずに記録だけする
+                SuiteResult::skipped(kind, scene, tr(Text::SuiteCaseInterrupted, &[]))
+            } else if let Err(error) = require_backend(scene, kind.name()) {
+                // 代替経路で描いた結果が混ざらないよう、本来の経路がないシーンは飛ばす
+                SuiteResult::skipped(kind, scene, error)
+            } else {
+                checkpoint.start(kind, scene);
+                let mut trials = Vec::new();
+                let mut failure = None;
+                for _ in 0..config.trials.max(1) {
+                    // 前の試行の状態を持ち越さないよう、毎回新しいバックエンドで計測する
+                    let backend = match spare.take() {
+                        Some(backend) => Ok(backend),
+                        None => create_backend(kind, &config).await,
+                    };
+                    let trial = backend.and_then(|mut backend| {
+                        rng::with_seed(config.seed, || measure(&mut backend, scene, &config))
+                    });
+                    match trial {
+                        Ok(trial) => trials.push(trial),
+                        Err(error) => {
+                            failure = Some(error);
+                            break;
+                        }
+                    }
+                    yield_to_browser().await;
+                }
+                match failure {
+                    Some(error) => SuiteResult::skipped(kind, scene, describe_error(&error)),
+                    None => SuiteResult::from_trials(kind, scene, &trials, &config),
+                }
+            };
+            checkpoint.finish(&result);
+            results.push(result);
+        }
+    }
+
+    checkpoint.clear();
+    // 再開したときは保存した結果が先に並ぶので、いつもの順に戻す
+    results.sort_by_key(|result| {
+        (
+            BACKENDS
+                .iter()
+                .position(|kind| kind.name() == result.backend),
+            SceneKind::ALL
+                .iter()
+                .position(|kind| kind.name() == result.scene),
+        )
+    });
+    Ok(SuiteReport {
+        results,
+        seed: config.seed,
+        total_ms: timing::now_ms() - start,
+        metadata: report::metadata(),
+    })
+}
+
+// only の指定から計測する組を選ぶ関数を作る（知らない名前はエラー）
+fn select_cases(only: &[String]) -> Result<impl Fn(BackendKind, SceneKind) -> bool, JsValue> {
+    let mut filters = Vec::new();
+    for entry in only {
+        let (backend, scene) = match entry.split_once(':') {
+            Some((backend, scene)) => (backend, Some(scene)),
+            None => (entry.as_str(), None),
+        };
+        let backend = BACKENDS.into_iter().find(|kind| kind.name() == backend);
+        let scene = scene.map(|scene| SceneKind::ALL.into_iter().find(|kind| kind.name() == scene));
+        match (backend, scene) {
+            (Some(backend), None) => filters.push((backend, None)),
+            (Some(backend), Some(Some(scene))) => filters.push((backend, Some(scene))),
+            _ => return Err(tr(Text::SuiteUnknownCase, &[entry]).into()),
+        }
+    }
+    Ok(move |kind: BackendKind, scene: SceneKind| {
+        filters.is_empty()
+            || filters
+                .iter()
+                .any(|&(backend, only)| backend == kind && only.is_none_or(|only| only == scene))
+    })
+}
+
+// 設定が変わっていたら保存した進捗は使わない
+fn config_hash(config: &SuiteConfig) -> String {
+    let mut fingerprint = ConfigFingerprint::new("suite");
+    fingerprint.field("width", config.width);
+    fingerprint.field("height", config.height);
+    fingerprint.field("particle_count", config.particle_count);
+    fingerprint.field("warmup_frames", config.warmup_frames);
+    fingerprint.field("measure_frames", config.measure_frames);
+    fingerprint.field("trials", config.trials);
+    fingerprint.field("max_relative_stddev", config.max_relative_stddev);
+    fingerprint.field("aggregation", config.aggregation.name());
+    fingerprint.field("max_drift", config.max_drift);
+    fingerprint.field("seed", config.seed);
+    fingerprint.optional("load", config.load);
+    fingerprint.field("strict_benchmark", config.strict_benchmark);
+    fingerprint.field("only", config.only.join(","));
+    fingerprint.field("keep_samples", config.keep_samples);
+    fingerprint.hash()
+}
+
+// localStorage に保存する進捗
+// 1行目が "suite-checkpoint\t設定のハッシュ"、以降は終わった組の結果（done）と
+// 計測を始めたがまだ終わっていない組（started）をタブ区切りで1行ずつ書く
+#[derive(Default)]
+struct Checkpoint {
+    storage: Option<(web_sys::Storage, String)>,
+    header: String,
+    results: Vec<SuiteResult>,
+    started: Option<(String, String)>,
+}
+
+const CHECKPOINT_MAGIC: &str = "suite-checkpoint";
+
+impl Checkpoint {
+    fn load(key: &str, hash: &str) -> Result<Checkpoint, JsValue> {
+        let storage = web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| tr(Text::StorageUnavailable, &[]))?;
+        let header = format!("{}\t{}", CHECKPOINT_MAGIC, hash);
+        let mut checkpoint = Checkpoint {
+            storage: None,
+            header: header.clone(),
+            results: Vec::new(),
+            started: None,
+        };
+        let saved = storage.get_item(key)?.unwrap_or_default();
+        let mut lines = saved.lines();
+        if lines.next() == Some(header.as_str()) {
+            for line in lines {
+                let fields: Vec<&str> = line.split('\t').collect();
+                match fields.as_slice() {
+                    ["started", backend, scene] => {
+                        checkpoint.started = Some((backend.to_string(), scene.to_string()));
+                    }
+                    ["done", rest @ ..] => {
+                        if let Some(result) = SuiteResult::from_fields(rest) {
+                            checkpoint.results.push(result);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        checkpoint.storage = Some((storage, key.to_string()));
+        Ok(checkpoint)
+    }
+
+    fn result(&self, kind: BackendKind, scene: SceneKind) -> Option<SuiteResult> {
+        self.results
+            .iter()
+            .find(|result| result.backend == kind.name() && result.scene == scene.name())
+            .cloned()
+    }
+
+    fn was_interrupted(&self, kind: BackendKind, scene: SceneKind) -> bool {
+        self.started
+            .as_ref()
+            .is_some_and(|(backend, started)| backend == kind.name() && started == scene.name())
+    }
+
+    fn start(&mut self, kind: BackendKind, scene: SceneKind) {
+        self.started = Some((kind.name().to_string(), scene.name().to_string()));
+        self.save();
+    }
+
+    fn finish(&mut self, result: &SuiteResult) {
+        self.started = None;
+        self.results.push(result.clone());
+        self.save();
+    }
+
+    // 保存に失敗しても計測は続ける（再開できなくなるだけ）
+    fn save(&self) {
+        let Some((storage, key)) = &self.storage else {
+            return;
+        };
+        let mut text = self.header.clone();
+        for result in &self.results {
+            text.push_str("\ndone\t");
+            text.push_str(&result.to_fields());
+        }
+        if let Some((backend, scene)) = &self.started {
+            text.push_str(&format!("\nstarted\t{}\t{}", backend, scene));
+        }
+        let _ = storage.set_item(key, &text);
+    }
+
+    fn clear(&self) {
+        if let Some((storage, key)) = &self.storage {
+            let _ = storage.remove_item(key);
+        }
+    }
+}
+
+impl SuiteResult {
+    fn to_fields(&self) -> String {
+        // エラーや警告の文字列にタブや改行が入っていても1行に収める
+        let single_line = |text: &Option<String>| {
+            text.as_deref()
+                .map(|text| text.replace(['\t', '\n', '\r'], " "))
+                .unwrap_or_default()
+        };
+        [
+            self.backend.clone(),
+            self.scene.clone(),
+            self.supported.to_string(),
+            single_line(&self.error),
+            self.trials.to_string(),
+            self.frames.to_string(),
+            self.mean_ms.to_string(),
+            self.stddev_ms.to_string(),
+            self.aggregation.clone(),
+            self.outliers.to_string(),
+            self.min_ms.to_string(),
+            self.max_ms.to_string(),
+            self.fps.to_string(),
+            self.reliable.to_string(),
+            self.drift.to_string(),
+            single_line(&self.warning),
+            self.gc_pauses.to_string(),
+            self.render_stalls.to_string(),
+            self.host_stalls.to_string(),
+            self.fingerprint.clone(),
+        ]
+        .join("\t")
+    }
+
+    fn from_fields(fields: &[&str]) -> Option<SuiteResult> {
+        let [backend, scene, supported, error, trials, frames, mean, stddev, aggregation, outliers, min, max, fps, reliable, drift, warning, gc_pauses, render_stalls, host_stalls, fingerprint] =
+            fields
+        else {
+            return None;
+        };
+        Some(SuiteResult {
+            backend: backend.to_string(),
+            scene: scene.to_string(),
+            supported: supported.parse().ok()?,
+            error: (!error.is_empty()).then(|| error.to_string()),
+            trials: trials.parse().ok()?,
+            frames: frames.parse().ok()?,
+            mean_ms: mean.parse().ok()?,
+            stddev_ms: stddev.parse().ok()?,
+            aggregation: aggregation.to_string(),
+            outliers: outliers.parse().ok()?,
+            min_ms: min.parse().ok()?,
+            max_ms: max.parse().ok()?,
+            fps: fps.parse().ok()?,
+            reliable: reliable.parse().ok()?,
+            drift: drift.parse().ok()?,
+            warning: (!warning.is_empty()).then(|| warning.to_string()),
+            gc_pauses: gc_pauses.parse().ok()?,
+            render_stalls: render_stalls.parse().ok()?,
+            host_stalls: host_stalls.parse().ok()?,
+            fingerprint: fingerprint.to_string(),
+            samples: Vec::new(),
+        })
+    }
+}
+
+// 1つのキャンバスでは1種類のコンテキストしか取れないので、バックエンドごとにキャンバスを作る
+async fn create_backend(kind: BackendKind, config: &SuiteConfig) -> Result<Backend, JsValue> {
+    let canvas = create_canvas(config.width, config.height)?;
+    let backend_config = BackendConfig::new(config.particle_count);
+    let mut backend = Backend::create_async(kind, &CanvasElement(&canvas), &backend_config).await?;
+    backend.strict_benchmark(config.strict_benchmark);
+    Ok(backend)
+}
+
+fn measure(
+    backend: &mut Backend,
+    scene: SceneKind,
+    config: &SuiteConfig,
+) -> Result<Trial, JsValue> {
+    backend.switch_scene(scene)?;
+    if let Some(load) = config.load {
+        backend.set_load(load);
+    }
+    for _ in 0..config.warmup_frames {
+        backend.update();
+        backend.render();
+    }
+    backend.finish_gpu();
+
+    // GPU のバックエンドも描き終わるまでを1フレームの時間にする（Canvas2D と同じ条件で比べる）
+    let frames = config.measure_frames.max(1);
+    let mut samples = Vec::with_capacity(frames as usize);
+    let mut jank = JankClassifier::new();
+    for _ in 0..frames {
+        let start = timing::now_ms();
+        jank.begin_frame();
+        backend.update();
+        backend.render();
+        backend.finish_gpu();
+        jank.end_frame();
+        samples.push(timing::now_ms() - start);
+    }
+    let summary = stats::summarize(&samples, config.aggregation);
+
+    Ok(Trial {
+        frames,
+        mean_ms: summary.center,
+        outliers: summary.outliers,
+        drift: stats::relative_drift(&samples),
+        jank: jank.get_report(),
+        min_ms: samples.iter().copied().fold(f64::MAX, f64::min),
+        max_ms: samples.iter().copied().fold(0.0, f64::max),
+        fingerprint: backend.get_config_fingerprint(),
+        samples,
+    })
+}
+
+pub(crate) fn create_canvas(width: u32, height: u32) -> Result<HtmlCanvasElement, JsValue> {
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or(BenchError::DocumentUnavailable)?;
+    let canvas = document
+        .create_element("canvas")?
+        .dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_width(width);
+    canvas.set_height(height);
+    Ok(canvas)
+}
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &js_sys::Function, delay_ms: i32);
+}
+
+// イベントループに1回制御を返す
+pub(crate) async fn yield_to_browser() {
+    let promise = js_sys::Promise::new(&mut |resolve, _| set_timeout(&resolve, 0));
+    let _ = JsFuture::from(promise).await;
+}
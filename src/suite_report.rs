@@ -0,0 +1,100 @@
+// BenchmarkSuite が返す結果（計測には web-sys が要るが、結果の読み書きと比較はネイティブでも使う）
+
+use wasm_bindgen::prelude::*;
+
+use crate::binary_report;
+
+// バックエンド × シーン 1組の結果
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct SuiteResult {
+    pub backend: String,
+    pub scene: String,
+    // false なら計測していない（理由は error）
+    pub supported: bool,
+    pub error: Option<String>,
+    pub trials: u32,
+    // すべての試行で計測したフレーム数
+    pub frames: u32,
+    // update() + render() の1フレームあたりの時間(ms)
+    // mean_ms は試行ごとの代表値を aggregation でまとめたもの、stddev_ms はそのばらつき
+    // （Mean と RejectOutliers は標準偏差、Median と TrimmedMean は MAD。試行が1回なら0）
+    pub mean_ms: f64,
+    pub stddev_ms: f64,
+    pub aggregation: String,
+    // 代表値の計算から外れ値として除いたフレーム数（すべての試行の合計）
+    pub outliers: u32,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub fps: f64,
+    // 試行間のばらつきが max_relative_stddev 以下なら true
+    pub reliable: bool,
+    // 試行の最初から最後までのフレーム時間の変化の割合（試行のうち最大のもの）
+    pub drift: f64,
+    // drift が max_drift を超えたときの警告
+    pub warning: Option<String>,
+    // フレーム時間が急増したフレームの数を推定した原因ごとに数えたもの（すべての試行の合計）
+    pub gc_pauses: u32,
+    pub render_stalls: u32,
+    pub host_stalls: u32,
+    pub fingerprint: String,
+    // keep_samples のときだけ、すべての試行のフレームごとの時間(ms)を順に並べたもの
+    // （チェックポイントには保存しないので、再開前に終わった組では空になる）
+    pub samples: Vec<f32>,
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct SuiteReport {
+    // バックエンド（高機能な順）× シーン（SceneKind の値の順）
+    pub results: Vec<SuiteResult>,
+    pub seed: u64,
+    pub total_ms: f64,
+    // 計測を終えたときに set_metadata() で付けていた JSON オブジェクト
+    pub metadata: String,
+}
+
+#[wasm_bindgen]
+impl SuiteReport {
+    // アップロード用の詰めたバイナリ形式（スキーマは binary_report.rs）
+    pub fn to_binary(&self) -> Vec<u8> {
+        binary_report::encode(self)
+    }
+
+    pub fn from_binary(bytes: &[u8]) -> Result<SuiteReport, JsValue> {
+        Ok(binary_report::decode(bytes)?)
+    }
+}
+
+// 同じ矩形を1つずつ描いたときとまとめて描いたときのフレーム時間の比
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct DrawCallOverhead {
+    pub backend: String,
+    pub separate_ms: f64,
+    pub batched_ms: f64,
+    // separate_ms / batched_ms（大きいほど1回の描画命令が重い）
+    pub ratio: f64,
+}
+
+// 同じスプライトを save / translate / rotate / restore で描いたときと setTransform で描いたときのフレーム時間の比
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct TransformOverhead {
+    pub backend: String,
+    pub save_restore_ms: f64,
+    pub precomputed_ms: f64,
+    // save_restore_ms / precomputed_ms（大きいほど変換APIの呼び出しが重い）
+    pub ratio: f64,
+}
+
+// 同じ点を集めて描いたときと散らして描いたときのフレーム時間の比
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct OverdrawSensitivity {
+    pub backend: String,
+    pub concentrated_ms: f64,
+    pub spread_ms: f64,
+    // concentrated_ms / spread_ms（1.0 に近いほど重ね塗りに強い）
+    pub ratio: f64,
+}
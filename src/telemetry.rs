@@ -0,0 +1,137 @@
+use web_sys::{BroadcastChannel, WebSocket};
+
+use crate::i18n::{tr, Text};
+use crate::metrics::Metrics;
+use crate::report;
+use crate::timing;
+
+// 送る間隔(ms)
+const INTERVAL_MS: f64 = 1000.0;
+// 送りきれずに溜まっていてよい量(バイト)。遅い回線で溜め続けないよう、超えたら送らずに数える
+const MAX_BUFFERED_BYTES: u32 = 64 * 1024;
+
+// 計測値のまとめを1秒ごとに別のタブや集計サーバーへ送る（telemetry フィーチャー）
+// 送るのはその1秒の FPS とフレーム時間、バックエンド名・パーティクル数・時刻・送れなかった回数の JSON
+// get_metrics() があるバックエンドでは update()・render()・転送の平均時間も付ける
+// 端末の区別は set_metadata() で付けた "metadata" で行う
+#[derive(Default)]
+pub(crate) struct Telemetry {
+    sink: Option<Sink>,
+    last_sent: f64,
+    // 前に送ってからのフレーム数と、render() の呼び出し間隔の合計と最大(ms)
+    last_frame: Option<f64>,
+    frames: u32,
+    frame_ms_sum: f64,
+    max_frame_ms: f64,
+    // 前に送ってから、接続中や溜まりすぎで送れなかった回数
+    dropped: u32,
+}
+
+enum Sink {
+    // 同じオリジンの別のタブ（ダッシュボード）へ
+    Channel(BroadcastChannel),
+    // 離れた集計サーバーへ
+    Socket(WebSocket),
+}
+
+impl Drop for Sink {
+    fn drop(&mut self) {
+        match self {
+            Sink::Channel(channel) => channel.close(),
+            Sink::Socket(socket) => {
+                let _ = socket.close();
+            }
+        }
+    }
+}
+
+impl Telemetry {
+    pub fn start_channel(&mut self, name: &str) -> Result<(), String> {
+        let channel =
+            BroadcastChannel::new(name).map_err(|_| tr(Text::TelemetryUnavailable, &[&name]))?;
+        self.start(Sink::Channel(channel));
+        Ok(())
+    }
+
+    // url は ws:// か wss://（接続できるまでのまとめは捨てる）
+    pub fn start_socket(&mut self, url: &str) -> Result<(), String> {
+        if !(url.starts_with("ws://") || url.starts_with("wss://")) {
+            return Err(tr(Text::TelemetryUnavailable, &[&url]));
+        }
+        let socket = WebSocket::new(url).map_err(|_| tr(Text::TelemetryUnavailable, &[&url]))?;
+        self.start(Sink::Socket(socket));
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        *self = Telemetry::default();
+    }
+
+    // render() の最初に呼ぶ。true なら send() で送る頃合い
+    pub fn note_frame(&mut self) -> bool {
+        if self.sink.is_none() {
+            return false;
+        }
+        let now = timing::now_ms();
+        if let Some(last) = self.last_frame.replace(now) {
+            let frame_ms = now - last;
+            self.frames += 1;
+            self.frame_ms_sum += frame_ms;
+            self.max_frame_ms = self.max_frame_ms.max(frame_ms);
+        }
+        now - self.last_sent >= INTERVAL_MS
+    }
+
+    pub fn send(&mut self, metrics: Option<Metrics>, backend: &str, particles: usize) {
+        let Some(sink) = &self.sink else {
+            return;
+        };
+        let now = timing::now_ms();
+        let elapsed = now - self.last_sent;
+        let mut message = report::versioned()
+            .string("backend", backend)
+            .number("particles", particles as f64)
+            .number("time_ms", now)
+            .number("dropped", self.dropped as f64)
+            .number("frames", self.frames as f64)
+            .number("fps", self.frames as f64 * 1000.0 / elapsed)
+            .number(
+                "mean_frame_ms",
+                self.frame_ms_sum / self.frames.max(1) as f64,
+            )
+            .number("max_frame_ms", self.max_frame_ms);
+        if let Some(metrics) = metrics {
+            message = message
+                .number("update_ms", metrics.update_ms)
+                .number("render_ms", metrics.render_ms)
+                .number("upload_ms", metrics.upload_ms)
+                .number("context_losses", metrics.context_losses as f64);
+        }
+        let message = message.finish();
+        self.last_sent = now;
+        self.frames = 0;
+        self.frame_ms_sum = 0.0;
+        self.max_frame_ms = 0.0;
+        let sent = match sink {
+            Sink::Channel(channel) => channel.post_message(&message.into()).is_ok(),
+            Sink::Socket(socket) => {
+                socket.ready_state() == WebSocket::OPEN
+                    && socket.buffered_amount() <= MAX_BUFFERED_BYTES
+                    && socket.send_with_str(&message).is_ok()
+            }
+        };
+        if sent {
+            self.dropped = 0;
+        } else {
+            self.dropped += 1;
+        }
+    }
+
+    fn start(&mut self, sink: Sink) {
+        *self = Telemetry {
+            sink: Some(sink),
+            last_sent: timing::now_ms(),
+            ..Telemetry::default()
+        };
+    }
+}
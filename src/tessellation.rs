@@ -0,0 +1,124 @@
+// 曲線・多角形をGPUで描ける線分や三角形に分解する
+// Canvas2D は同じ図形をネイティブAPIで描くので、WebGL側だけがこのコストを払う
+
+// 平坦とみなす許容誤差(px)
+const FLATNESS_TOLERANCE: f32 = 0.25;
+// 分割の深さの上限（2^10 = 1024 分割）
+const MAX_DEPTH: u32 = 10;
+
+// 3次ベジェを適応的に折れ線に分解する
+// 始点は out に入れず、各区間の終点だけを順に追加する
+pub(crate) fn flatten_cubic(points: &[(f32, f32); 4], out: &mut Vec<(f32, f32)>) {
+    subdivide(points, 0, out);
+}
+
+fn subdivide(p: &[(f32, f32); 4], depth: u32, out: &mut Vec<(f32, f32)>) {
+    if depth >= MAX_DEPTH || is_flat(p) {
+        out.push(p[3]);
+        return;
+    }
+
+    // de Casteljau で t = 0.5 で2分割する
+    let mid = |a: (f32, f32), b: (f32, f32)| ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5);
+    let p01 = mid(p[0], p[1]);
+    let p12 = mid(p[1], p[2]);
+    let p23 = mid(p[2], p[3]);
+    let p012 = mid(p01, p12);
+    let p123 = mid(p12, p23);
+    let center = mid(p012, p123);
+
+    subdivide(&[p[0], p01, p012, center], depth + 1, out);
+    subdivide(&[center, p123, p23, p[3]], depth + 1, out);
+}
+
+// 制御点が弦から十分近ければ直線で近似できる（Roger Willcocks の判定）
+fn is_flat(p: &[(f32, f32); 4]) -> bool {
+    let ux = 3.0 * p[1].0 - 2.0 * p[0].0 - p[3].0;
+    let uy = 3.0 * p[1].1 - 2.0 * p[0].1 - p[3].1;
+    let vx = 3.0 * p[2].0 - p[0].0 - 2.0 * p[3].0;
+    let vy = 3.0 * p[2].1 - p[0].1 - 2.0 * p[3].1;
+    let ux = (ux * ux).max(vx * vx);
+    let uy = (uy * uy).max(vy * vy);
+    ux + uy <= 16.0 * FLATNESS_TOLERANCE * FLATNESS_TOLERANCE
+}
+
+// 単純多角形（穴なし・自己交差なし）を耳切り法で三角形に分割する
+// 三角形の頂点インデックスを3つずつ out に追加する
+pub(crate) fn earcut(points: &[(f32, f32)], out: &mut Vec<u32>) {
+    let n = points.len();
+    if n < 3 {
+        return;
+    }
+
+    // 向きに関係なく「凸な頂点」を判定できるよう、面積の符号を掛ける
+    let orientation = signed_area(points).signum();
+    let mut prev: Vec<usize> = (0..n).map(|i| (i + n - 1) % n).collect();
+    let mut next: Vec<usize> = (0..n).map(|i| (i + 1) % n).collect();
+
+    let mut remaining = n;
+    let mut current = 0;
+    // 一周しても耳が見つからなければ（退化した入力）打ち切る
+    let mut misses = 0;
+    while remaining > 3 {
+        let (a, b, c) = (prev[current], current, next[current]);
+        if is_ear(points, &next, a, b, c, orientation) {
+            out.extend_from_slice(&[a as u32, b as u32, c as u32]);
+            next[a] = c;
+            prev[c] = a;
+            remaining -= 1;
+            current = c;
+            misses = 0;
+        } else {
+            current = c;
+            misses += 1;
+            if misses > remaining {
+                return;
+            }
+        }
+    }
+    out.extend_from_slice(&[prev[current] as u32, current as u32, next[current] as u32]);
+}
+
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for (i, p) in points.iter().enumerate() {
+        let q = points[(i + 1) % points.len()];
+        area += p.0 * q.1 - q.0 * p.1;
+    }
+    area * 0.5
+}
+
+fn cross(a: (f32, f32), b: (f32, f32), c: (f32, f32)) -> f32 {
+    (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0)
+}
+
+// b が凸で、三角形 abc の中に他の頂点が1つもなければ耳
+fn is_ear(
+    points: &[(f32, f32)],
+    next: &[usize],
+    a: usize,
+    b: usize,
+    c: usize,
+    orientation: f32,
+) -> bool {
+    let (pa, pb, pc) = (points[a], points[b], points[c]);
+    if cross(pa, pb, pc) * orientation <= 0.0 {
+        return false;
+    }
+
+    let mut i = next[c];
+    while i != a {
+        let p = points[i];
+        if p != pa
+            && p != pb
+            && p != pc
+            && cross(pa, pb, p) * orientation >= 0.0
+            && cross(pb, pc, p) * orientation >= 0.0
+            && cross(pc, pa, p) * orientation >= 0.0
+        {
+            return false;
+        }
+        i = next[i];
+    }
+    true
+}
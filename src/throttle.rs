@@ -0,0 +1,188 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::future_to_promise;
+
+use crate::json::{self, JsonObject};
+use crate::report;
+use crate::stats::{self, Aggregation};
+use crate::suite::yield_to_browser;
+use crate::timing;
+
+// ページが見えているか隠れているかごとの、タイマーと requestAnimationFrame が実際に動けた回数
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ThrottleStats {
+    // "visible" か "hidden"
+    pub state: String,
+    // その状態だった時間(ms)
+    pub duration_ms: f64,
+    // setTimeout(0) で回した回数と、1秒あたりの回数・間隔(ms)
+    pub timer_ticks: u32,
+    pub timer_hz: f64,
+    pub timer_median_ms: f64,
+    pub timer_max_ms: f64,
+    // 同じ間に requestAnimationFrame が呼ばれた回数と1秒あたりの回数
+    pub raf_ticks: u32,
+    pub raf_hz: f64,
+}
+
+impl ThrottleStats {
+    fn new(state: &str, ticks: &Ticks) -> ThrottleStats {
+        let per_second = |count: u32| {
+            if ticks.duration_ms > 0.0 {
+                count as f64 * 1_000.0 / ticks.duration_ms
+            } else {
+                0.0
+            }
+        };
+        ThrottleStats {
+            state: state.to_string(),
+            duration_ms: ticks.duration_ms,
+            timer_ticks: ticks.intervals.len() as u32,
+            timer_hz: per_second(ticks.intervals.len() as u32),
+            timer_median_ms: stats::summarize(&ticks.intervals, Aggregation::Median).center,
+            timer_max_ms: ticks.intervals.iter().copied().fold(0.0, f64::max),
+            raf_ticks: ticks.raf,
+            raf_hz: per_second(ticks.raf),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        JsonObject::new()
+            .string("state", &self.state)
+            .number("duration_ms", self.duration_ms)
+            .number("timer_ticks", self.timer_ticks as f64)
+            .number("timer_hz", self.timer_hz)
+            .number("timer_median_ms", self.timer_median_ms)
+            .number("timer_max_ms", self.timer_max_ms)
+            .number("raf_ticks", self.raf_ticks as f64)
+            .number("raf_hz", self.raf_hz)
+            .finish()
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct ThrottleReport {
+    // 見えている間と隠れている間（その状態にならなかったものは duration_ms が 0）
+    pub results: Vec<ThrottleStats>,
+    pub total_ms: f64,
+}
+
+#[wasm_bindgen]
+impl ThrottleReport {
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .number("total_ms", self.total_ms)
+            .raw(
+                "results",
+                &json::array(self.results.iter().map(ThrottleStats::to_json)),
+            )
+            .finish()
+    }
+}
+
+// 1つの状態の間に数えたもの
+#[derive(Default)]
+struct Ticks {
+    intervals: Vec<f64>,
+    raf: u32,
+    duration_ms: f64,
+}
+
+// 次のフレームでも自分自身を登録し直すコールバック
+type FrameCallback = Rc<RefCell<Option<Closure<dyn FnMut()>>>>;
+
+// requestAnimationFrame を止めるまで呼ばれるたびに数える
+// [見えている間, 隠れている間] の回数
+struct RafCounter {
+    counts: Rc<Cell<[u32; 2]>>,
+    running: Rc<Cell<bool>>,
+}
+
+impl RafCounter {
+    fn start() -> RafCounter {
+        let counts = Rc::new(Cell::new([0; 2]));
+        let running = Rc::new(Cell::new(true));
+        let callback: FrameCallback = Rc::new(RefCell::new(None));
+        let next = callback.clone();
+        let (frame_counts, frame_running) = (counts.clone(), running.clone());
+        *callback.borrow_mut() = Some(Closure::new(move || {
+            // 呼ばれている最中のクロージャは解放できないので、止めた後は登録し直さないだけにする
+            // （自分自身を参照したまま残るが、計測1回につき1つだけ）
+            if !frame_running.get() {
+                return;
+            }
+            let mut counts = frame_counts.get();
+            counts[is_hidden() as usize] += 1;
+            frame_counts.set(counts);
+            if let Some(callback) = next.borrow().as_ref() {
+                request_animation_frame(callback);
+            }
+        }));
+        if let Some(callback) = callback.borrow().as_ref() {
+            request_animation_frame(callback);
+        }
+        RafCounter { counts, running }
+    }
+
+    fn stop(self) -> [u32; 2] {
+        self.running.set(false);
+        self.counts.get()
+    }
+}
+
+fn request_animation_frame(callback: &Closure<dyn FnMut()>) {
+    if let Some(window) = web_sys::window() {
+        let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+    }
+}
+
+fn is_hidden() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .is_some_and(|document| document.hidden())
+}
+
+// ページが隠れていても止まらずに動き続け、実際に動けた間隔を記録する（ブラウザごとの間引き方の記録用）
+// requestAnimationFrame は隠れたタブでは止まることが多いので、setTimeout(0) で回し続け、
+// 見えている間と隠れている間に分けてタイマーと requestAnimationFrame の回数を数える
+// 計測中にタブを切り替えると、隠れている間の値が取れる
+#[wasm_bindgen]
+pub struct ThrottleProbe;
+
+#[wasm_bindgen]
+impl ThrottleProbe {
+    // duration_ms の間回し続け、ThrottleReport で解決する Promise を返す
+    pub fn run(duration_ms: f64) -> js_sys::Promise {
+        future_to_promise(async move { Ok(probe(duration_ms).await.into()) })
+    }
+}
+
+async fn probe(duration_ms: f64) -> ThrottleReport {
+    let start = timing::now_ms();
+    let raf = RafCounter::start();
+    let mut ticks = [Ticks::default(), Ticks::default()];
+    let mut last = start;
+    while last - start < duration_ms {
+        yield_to_browser().await;
+        let now = timing::now_ms();
+        let state = &mut ticks[is_hidden() as usize];
+        state.intervals.push(now - last);
+        state.duration_ms += now - last;
+        last = now;
+    }
+    let [visible_raf, hidden_raf] = raf.stop();
+    ticks[0].raf = visible_raf;
+    ticks[1].raf = hidden_raf;
+
+    ThrottleReport {
+        results: vec![
+            ThrottleStats::new("visible", &ticks[0]),
+            ThrottleStats::new("hidden", &ticks[1]),
+        ],
+        total_ms: timing::now_ms() - start,
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::VecDeque;
+
+use crate::json;
+use crate::report;
+use crate::timing;
+
+// 残すフレームの数（60fps で1分）と印の数。古いものから捨てる
+const MAX_FRAMES: usize = 3_600;
+const MAX_ANNOTATIONS: usize = 256;
+// 印の名前の長さの上限（文字数）
+const MAX_LABEL_CHARS: usize = 128;
+
+// annotate() で付けた印
+struct Annotation {
+    label: String,
+    time_ms: f64,
+    frame_count: u32,
+}
+
+// 直近のフレーム時間と、ホストのページが annotate() で付けた印（クリック・データの読み込み・タブへの復帰など）
+// どちらも performance.now() の時刻で残すので、書き出した結果で長いフレームとその原因を突き合わせられる
+#[derive(Default)]
+pub(crate) struct Timeline {
+    // (フレーム番号, render() の時刻, 前の render() からの間隔)
+    frames: VecDeque<(u32, f64, f64)>,
+    last_frame: Option<f64>,
+    annotations: VecDeque<Annotation>,
+}
+
+impl Timeline {
+    // render() の最初に呼ぶ
+    pub fn note_frame(&mut self, frame_count: u32) {
+        let now = timing::now_ms();
+        let frame_ms = self.last_frame.replace(now).map_or(0.0, |last| now - last);
+        if self.frames.len() == MAX_FRAMES {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((frame_count, now, frame_ms));
+    }
+
+    // 今の時刻に印を付ける（frame_count は次に描くフレームの番号）
+    pub fn annotate(&mut self, label: &str, frame_count: u32) {
+        if self.annotations.len() == MAX_ANNOTATIONS {
+            self.annotations.pop_front();
+        }
+        self.annotations.push_back(Annotation {
+            label: label.chars().take(MAX_LABEL_CHARS).collect(),
+            time_ms: timing::now_ms(),
+            frame_count,
+        });
+    }
+
+    pub fn clear(&mut self) {
+        *self = Timeline::default();
+    }
+
+    // [フレーム番号, 時刻(ms), 間隔(ms)] の配列
+    pub fn frames_json(&self) -> String {
+        json::array(self.frames.iter().map(|&(frame_count, time_ms, frame_ms)| {
+            json::array([
+                frame_count.to_string(),
+                json_number(time_ms),
+                json_number(frame_ms),
+            ])
+        }))
+    }
+
+    pub fn annotations_json(&self) -> String {
+        json::array(self.annotations.iter().map(|annotation| {
+            json::JsonObject::new()
+                .string("label", &annotation.label)
+                .number("time_ms", annotation.time_ms)
+                .number("frame_count", annotation.frame_count as f64)
+                .finish()
+        }))
+    }
+
+    // CSV の1列に入れる "名前@フレーム番号" を "; " でつないだもの
+    pub fn annotations_summary(&self) -> String {
+        let items: Vec<String> = self
+            .annotations
+            .iter()
+            .map(|annotation| format!("{}@{}", annotation.label, annotation.frame_count))
+            .collect();
+        items.join("; ")
+    }
+
+    pub fn to_json(&self) -> String {
+        report::versioned()
+            .raw("frames", &self.frames_json())
+            .raw("annotations", &self.annotations_json())
+            .finish()
+    }
+}
+
+fn json_number(value: f64) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
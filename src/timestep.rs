@@ -0,0 +1,78 @@
+// 既定の1ステップの長さ(ms)（update() 1回 = 60Hz の1フレーム分）
+#[cfg(any(target_arch = "wasm32", test))]
+pub(crate) const DEFAULT_STEP_MS: f64 = 1_000.0 / 60.0;
+// 1ステップの長さの範囲(ms)
+#[cfg(target_arch = "wasm32")]
+const MIN_STEP_MS: f64 = 1.0;
+#[cfg(target_arch = "wasm32")]
+const MAX_STEP_MS: f64 = 100.0;
+// 1回の update_with_dt() で進める最大のステップ数
+// タブが裏に回っていた後などの長い dt を全部計算すると、その計算でまた dt が伸びて追いつけなくなるので、
+// 超えた分は捨てる
+#[cfg(any(target_arch = "wasm32", test))]
+const MAX_STEPS_PER_UPDATE: u32 = 5;
+
+// update_with_dt() の経過時間を固定長のステップに分ける
+// 端数は次の呼び出しに持ち越し、描画は直前の2ステップの間を端数の割合で補間する
+// これでリフレッシュレートが違っても（120Hz でも）1秒に進む量が同じになる
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct FixedTimestep {
+    #[cfg(any(target_arch = "wasm32", test))]
+    step_ms: f64,
+    // 持ち越している時間(ms)
+    accumulator: f64,
+    // update_with_dt() が一度でも呼ばれたら true（補間のために直前の位置を残す）
+    pub active: bool,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> FixedTimestep {
+        FixedTimestep {
+            #[cfg(any(target_arch = "wasm32", test))]
+            step_ms: DEFAULT_STEP_MS,
+            accumulator: 0.0,
+            active: false,
+        }
+    }
+}
+
+impl FixedTimestep {
+    #[cfg(target_arch = "wasm32")]
+    pub fn step_ms(&self) -> f64 {
+        self.step_ms
+    }
+
+    // 範囲内に丸めて、実際に使う値を返す
+    #[cfg(target_arch = "wasm32")]
+    pub fn set_step_ms(&mut self, step_ms: f64) -> f64 {
+        self.step_ms = if step_ms.is_finite() {
+            step_ms.clamp(MIN_STEP_MS, MAX_STEP_MS)
+        } else {
+            DEFAULT_STEP_MS
+        };
+        self.accumulator = self.accumulator.min(self.step_ms);
+        self.step_ms
+    }
+
+    // dt_ms 経過したことにして、進めるステップ数を返す（負や NaN の dt は 0 とみなす）
+    #[cfg(any(target_arch = "wasm32", test))]
+    pub fn advance(&mut self, dt_ms: f64) -> u32 {
+        self.active = true;
+        if dt_ms.is_finite() && dt_ms > 0.0 {
+            self.accumulator += dt_ms;
+        }
+        let steps = (self.accumulator / self.step_ms).floor();
+        self.accumulator -= steps * self.step_ms;
+        steps.min(MAX_STEPS_PER_UPDATE as f64) as u32
+    }
+
+    // 最後のステップから次のステップまでのどこにいるか（0〜1）
+    #[cfg(target_arch = "wasm32")]
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step_ms).clamp(0.0, 1.0) as f32
+    }
+
+    pub fn reset(&mut self) {
+        self.accumulator = 0.0;
+    }
+}
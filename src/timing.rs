@@ -0,0 +1,31 @@
+// 計測用の高精度タイマー
+// ブラウザでもNodeでも globalThis.performance.now() を使う（window に依存しない）
+
+#[cfg(all(target_arch = "wasm32", not(target_os = "wasi")))]
+mod imp {
+    use wasm_bindgen::prelude::*;
+
+    #[wasm_bindgen]
+    extern "C" {
+        #[wasm_bindgen(js_namespace = performance, js_name = now)]
+        fn performance_now() -> f64;
+    }
+
+    pub fn now_ms() -> f64 {
+        performance_now()
+    }
+}
+
+// ネイティブビルド（テストやプロファイリング）と WASI では std の単調時計を使う
+#[cfg(any(not(target_arch = "wasm32"), target_os = "wasi"))]
+mod imp {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    pub fn now_ms() -> f64 {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+pub use imp::now_ms;
@@ -0,0 +1,163 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlFramebuffer, WebGlProgram, WebGlRenderingContext, WebGlTexture};
+
+use crate::gl_surface::create_texture;
+use crate::i18n::{tr, Text};
+use crate::render_mode::{RenderMode, TRAIL_FADE};
+use crate::shader;
+
+// WebGL で残像（RenderMode::Trails）を描くための描画先
+// 既定の描画バッファは表示のたびに消える（preserveDrawingBuffer: false）ので、
+// テクスチャに描き溜めておき、毎フレームそれを画面に写す
+// WebGL2 のコンテキストも WebGlRenderingContext として渡せば使える
+pub(crate) struct TrailBuffer {
+    framebuffer: WebGlFramebuffer,
+    texture: WebGlTexture,
+    // テクスチャの大きさ（描画バッファと違ったら作り直す）
+    size: (i32, i32),
+    fade_program: WebGlProgram,
+    copy_program: WebGlProgram,
+    quad_buffer: WebGlBuffer,
+}
+
+impl TrailBuffer {
+    pub fn new(gl: &WebGlRenderingContext) -> Result<TrailBuffer, JsValue> {
+        let (fade_program, _) =
+            shader::get_or_create_program(gl, QUAD_VERTEX_SHADER, FADE_FRAGMENT_SHADER)?;
+        let (copy_program, _) =
+            shader::get_or_create_program(gl, QUAD_VERTEX_SHADER, COPY_FRAGMENT_SHADER)?;
+
+        // 画面全体を覆う四角形（TRIANGLE_STRIP）
+        let quad_buffer = gl
+            .create_buffer()
+            .ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+        let corners: [f32; 8] = [-1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&quad_buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&corners);
+            gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &array,
+                WebGlRenderingContext::STATIC_DRAW,
+            );
+        }
+
+        let texture = create_texture(gl)?;
+        let framebuffer = gl
+            .create_framebuffer()
+            .ok_or_else(|| tr(Text::FramebufferCreationFailed, &[]))?;
+        Ok(TrailBuffer {
+            framebuffer,
+            texture,
+            size: (0, 0),
+            fade_program,
+            copy_program,
+            quad_buffer,
+        })
+    }
+
+    // プログラムはキャッシュのものなので消さない
+    pub fn delete(&self, gl: &WebGlRenderingContext) {
+        gl.delete_framebuffer(Some(&self.framebuffer));
+        gl.delete_texture(Some(&self.texture));
+        gl.delete_buffer(Some(&self.quad_buffer));
+    }
+
+    // 以後の描画をテクスチャに向け、前のフレームを背景色で薄める
+    // 描画バッファの大きさが変わったときはテクスチャを作り直して背景色で塗る
+    pub fn begin(&mut self, gl: &WebGlRenderingContext, background: [f32; 3]) {
+        let size = (gl.drawing_buffer_width(), gl.drawing_buffer_height());
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, Some(&self.framebuffer));
+        if size != self.size {
+            gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.texture));
+            let _ = gl.tex_image_2d_with_i32_and_i32_and_i32_and_format_and_type_and_opt_u8_array(
+                WebGlRenderingContext::TEXTURE_2D,
+                0,
+                WebGlRenderingContext::RGBA as i32,
+                size.0,
+                size.1,
+                0,
+                WebGlRenderingContext::RGBA,
+                WebGlRenderingContext::UNSIGNED_BYTE,
+                None,
+            );
+            gl.framebuffer_texture_2d(
+                WebGlRenderingContext::FRAMEBUFFER,
+                WebGlRenderingContext::COLOR_ATTACHMENT0,
+                WebGlRenderingContext::TEXTURE_2D,
+                Some(&self.texture),
+                0,
+            );
+            gl.clear_color(background[0], background[1], background[2], 1.0);
+            gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+            self.size = size;
+        }
+
+        gl.use_program(Some(&self.fade_program));
+        let color_location = gl.get_uniform_location(&self.fade_program, "u_color");
+        gl.uniform4f(
+            color_location.as_ref(),
+            background[0],
+            background[1],
+            background[2],
+            TRAIL_FADE,
+        );
+        RenderMode::Normal.apply_gl(gl);
+        self.draw_quad(gl, &self.fade_program);
+        gl.disable(WebGlRenderingContext::BLEND);
+    }
+
+    // 描き溜めたテクスチャをそのまま画面に写す（ビューポートの外は触らない）
+    pub fn end(&self, gl: &WebGlRenderingContext) {
+        gl.bind_framebuffer(WebGlRenderingContext::FRAMEBUFFER, None);
+        gl.use_program(Some(&self.copy_program));
+        gl.active_texture(WebGlRenderingContext::TEXTURE0);
+        gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, Some(&self.texture));
+        let texture_location = gl.get_uniform_location(&self.copy_program, "u_texture");
+        gl.uniform1i(texture_location.as_ref(), 0);
+        let resolution_location = gl.get_uniform_location(&self.copy_program, "u_resolution");
+        gl.uniform2f(
+            resolution_location.as_ref(),
+            self.size.0 as f32,
+            self.size.1 as f32,
+        );
+        self.draw_quad(gl, &self.copy_program);
+    }
+
+    fn draw_quad(&self, gl: &WebGlRenderingContext, program: &WebGlProgram) {
+        let location = gl.get_attrib_location(program, "a_position") as u32;
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(&self.quad_buffer));
+        gl.vertex_attrib_pointer_with_i32(location, 2, WebGlRenderingContext::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(location);
+        gl.draw_arrays(WebGlRenderingContext::TRIANGLE_STRIP, 0, 4);
+    }
+}
+
+const QUAD_VERTEX_SHADER: &str = r#"
+    attribute vec2 a_position;
+
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+    }
+"#;
+
+// 背景色を半透明で重ねて前のフレームを薄める
+const FADE_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform vec4 u_color;
+
+    void main() {
+        gl_FragColor = u_color;
+    }
+"#;
+
+// 描画バッファと同じ大きさのテクスチャを画素ごとに写す
+const COPY_FRAGMENT_SHADER: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_texture;
+    uniform vec2 u_resolution;
+
+    void main() {
+        gl_FragColor = texture2D(u_texture, gl_FragCoord.xy / u_resolution);
+    }
+"#;
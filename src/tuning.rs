@@ -0,0 +1,119 @@
+use wasm_bindgen::prelude::*;
+
+use crate::backend::BackendKind;
+use crate::draw_strategy::DrawStrategy;
+use crate::lod::LodMode;
+use crate::render_mode::RenderMode;
+use crate::shader::ShaderPrecision;
+use crate::upload::UploadStrategy;
+
+// 合成方法・点の大きさ・シェーダーの精度・頂点データの送り方・間引きなどをまとめて決める設定の組
+// 1つずつ設定するとバックエンドごとに選べる値が違って揃えにくいので、名前で選んで結果にも残す
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TuningProfile {
+    // 見た目を優先する（高精度・2倍の描画解像度・間引きなし）
+    MaxQuality = 0,
+    // 1フレームに描ける数を優先する（小さい点・ブレンドなし・中精度）
+    MaxThroughput = 1,
+    // 消費電力を抑える（半分の描画解像度・30fps の上限・描画時間に合わせた間引き）
+    LowPower = 2,
+}
+
+// LowPower で間引きを増やし始める render() の時間(ms)と描画の上限(fps)
+const LOW_POWER_RENDER_MS: f64 = 8.0;
+const LOW_POWER_FPS: f64 = 30.0;
+
+impl TuningProfile {
+    pub fn name(self) -> &'static str {
+        match self {
+            TuningProfile::MaxQuality => "max-quality",
+            TuningProfile::MaxThroughput => "max-throughput",
+            TuningProfile::LowPower => "low-power",
+        }
+    }
+
+    // backend で実際に選べる値だけを使った設定
+    pub(crate) fn settings(self, backend: BackendKind) -> Tuning {
+        let webgl = matches!(backend, BackendKind::WebGl | BackendKind::WebGl2);
+        // 合成方法を選べないのは WebGPU、1個ずつの描き方を選べるのは Canvas2D、送り方を選べるのは WebGL1 だけ
+        let blending = backend != BackendKind::WebGpu;
+        let per_particle = backend == BackendKind::Canvas2D;
+        let upload = backend == BackendKind::WebGl;
+        let precision = |precision| {
+            if webgl {
+                precision
+            } else {
+                ShaderPrecision::Default
+            }
+        };
+        match self {
+            TuningProfile::MaxQuality => Tuning {
+                render_mode: if blending {
+                    RenderMode::Additive
+                } else {
+                    RenderMode::Normal
+                },
+                point_size: 3.0,
+                render_scale: 2.0,
+                precision: precision(ShaderPrecision::Highp),
+                draw_strategy: if per_particle {
+                    DrawStrategy::PerParticle
+                } else {
+                    DrawStrategy::Batched
+                },
+                // 直前のフレームが描いているバッファに書かないので、待ちでフレームが揺れにくい
+                upload_strategy: if upload {
+                    UploadStrategy::Ring
+                } else {
+                    UploadStrategy::BufferData
+                },
+                adaptive_lod: None,
+                target_fps: 0.0,
+            },
+            TuningProfile::MaxThroughput => Tuning {
+                render_mode: RenderMode::Normal,
+                point_size: 1.0,
+                render_scale: 1.0,
+                precision: precision(ShaderPrecision::Mediump),
+                draw_strategy: DrawStrategy::Batched,
+                upload_strategy: if upload {
+                    UploadStrategy::SubData
+                } else {
+                    UploadStrategy::BufferData
+                },
+                adaptive_lod: None,
+                target_fps: 0.0,
+            },
+            TuningProfile::LowPower => Tuning {
+                render_mode: RenderMode::Normal,
+                point_size: 2.0,
+                render_scale: 0.5,
+                precision: precision(ShaderPrecision::Mediump),
+                draw_strategy: DrawStrategy::Batched,
+                upload_strategy: if upload {
+                    UploadStrategy::SubData
+                } else {
+                    UploadStrategy::BufferData
+                },
+                adaptive_lod: Some((LodMode::Stochastic, LOW_POWER_RENDER_MS)),
+                target_fps: LOW_POWER_FPS,
+            },
+        }
+    }
+}
+
+// TuningProfile をあるバックエンドに当てはめた値
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Tuning {
+    pub render_mode: RenderMode,
+    pub point_size: f32,
+    pub render_scale: f32,
+    pub precision: ShaderPrecision,
+    pub draw_strategy: DrawStrategy,
+    pub upload_strategy: UploadStrategy,
+    // Some なら (間引き方, render() の目標時間(ms)) で間引きを自動で調整し、None なら間引かない
+    pub adaptive_lod: Option<(LodMode, f64)>,
+    // 描画の上限(fps)。0 なら上限なし
+    pub target_fps: f64,
+}
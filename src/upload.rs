@@ -0,0 +1,308 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{WebGlBuffer, WebGlRenderingContext};
+
+use crate::i18n::{tr, Text};
+
+// 毎フレームの頂点データを GPU に送る方法（同期待ちの違いを比べる）
+// 同じバッファに buffer_data で送り直すと、前のフレームの描画がそのバッファを使い終わるまで
+// CPU が待たされることがあるので、その避け方を選べるようにする
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum UploadStrategy {
+    // 同じバッファに毎フレーム buffer_data で送り直す（既定）
+    BufferData = 0,
+    // 先に中身なしの buffer_data で領域を捨ててから（orphaning）、buffer_sub_data で書く
+    Orphan = 1,
+    // 最大個数分の領域を一度だけ確保し、以後は buffer_sub_data で書き換えるだけにする
+    SubData = 2,
+    // RING_SIZE 組のバッファを順番に使い、直前のフレームが描いているバッファには書かない
+    Ring = 3,
+}
+
+impl UploadStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            UploadStrategy::BufferData => "buffer_data",
+            UploadStrategy::Orphan => "orphan",
+            UploadStrategy::SubData => "sub_data",
+            UploadStrategy::Ring => "ring",
+        }
+    }
+}
+
+// 位置と色の頂点データの並べ方（メモリ帯域の効き方の違いを比べる）
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BufferLayout {
+    // 位置と色を別々のバッファに送る（既定）
+    Planar = 0,
+    // 1個分の位置と色を続けて並べた1つのバッファに送り、stride と offset で読む
+    Interleaved = 1,
+}
+
+impl BufferLayout {
+    pub fn name(self) -> &'static str {
+        match self {
+            BufferLayout::Planar => "planar",
+            BufferLayout::Interleaved => "interleaved",
+        }
+    }
+}
+
+// Ring で順番に使うバッファの組の数
+const RING_SIZE: usize = 3;
+// 1個あたりの位置と色の float の数
+const POSITION_FLOATS: usize = 2;
+const COLOR_FLOATS: usize = 3;
+
+// 位置と色の頂点バッファの組（Interleaved では position だけに両方を入れる）
+struct BufferPair {
+    position: WebGlBuffer,
+    color: WebGlBuffer,
+    // 確保済みの領域の個数（SubData と Ring で、足りないときだけ確保し直す）
+    capacity: usize,
+}
+
+impl BufferPair {
+    fn new(gl: &WebGlRenderingContext) -> Result<BufferPair, JsValue> {
+        let create = || {
+            gl.create_buffer()
+                .ok_or_else(|| JsValue::from(tr(Text::BufferCreationFailed, &[])))
+        };
+        Ok(BufferPair {
+            position: create()?,
+            color: create()?,
+            capacity: 0,
+        })
+    }
+
+    // count 個分の領域を確保する（中身は不定）
+    fn allocate(&mut self, gl: &WebGlRenderingContext, layout: BufferLayout, count: usize) {
+        let sizes = match layout {
+            BufferLayout::Planar => [POSITION_FLOATS, COLOR_FLOATS],
+            BufferLayout::Interleaved => [POSITION_FLOATS + COLOR_FLOATS, 0],
+        };
+        for (buffer, floats) in [(&self.position, sizes[0]), (&self.color, sizes[1])] {
+            gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+            gl.buffer_data_with_i32(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                (count * floats * 4) as i32,
+                WebGlRenderingContext::DYNAMIC_DRAW,
+            );
+        }
+        self.capacity = count;
+    }
+}
+
+// パーティクルの位置と色を送る頂点バッファ
+pub(crate) struct VertexBuffers {
+    strategy: UploadStrategy,
+    layout: BufferLayout,
+    // Interleaved のときに位置と色を並べ直して送るデータ（毎フレーム使い回す）
+    interleaved: Vec<f32>,
+    // Ring のときだけ RING_SIZE 組、それ以外は先頭の1組だけ使う
+    pairs: Vec<BufferPair>,
+    // Ring で次に書く組
+    next: usize,
+}
+
+impl VertexBuffers {
+    pub fn new(gl: &WebGlRenderingContext) -> Result<VertexBuffers, JsValue> {
+        Ok(VertexBuffers {
+            strategy: UploadStrategy::BufferData,
+            layout: BufferLayout::Planar,
+            interleaved: Vec::new(),
+            pairs: vec![BufferPair::new(gl)?],
+            next: 0,
+        })
+    }
+
+    // dispose() で全部の組を消す
+    pub fn delete(&self, gl: &WebGlRenderingContext) {
+        for pair in &self.pairs {
+            gl.delete_buffer(Some(&pair.position));
+            gl.delete_buffer(Some(&pair.color));
+        }
+    }
+
+    pub fn strategy(&self) -> UploadStrategy {
+        self.strategy
+    }
+
+    pub fn layout(&self) -> BufferLayout {
+        self.layout
+    }
+
+    // 並べ方を切り替える（確保済みの領域は大きさが変わるので、今の送り方で確保し直す）
+    pub fn set_layout(
+        &mut self,
+        gl: &WebGlRenderingContext,
+        layout: BufferLayout,
+        reserve: usize,
+    ) -> Result<(), JsValue> {
+        self.layout = layout;
+        for pair in &mut self.pairs {
+            pair.capacity = 0;
+        }
+        self.set_strategy(gl, self.strategy, reserve)
+    }
+
+    // 送り方を切り替える（SubData と Ring はここで reserve 個分を確保しておく）
+    pub fn set_strategy(
+        &mut self,
+        gl: &WebGlRenderingContext,
+        strategy: UploadStrategy,
+        reserve: usize,
+    ) -> Result<(), JsValue> {
+        let pairs = if strategy == UploadStrategy::Ring {
+            RING_SIZE
+        } else {
+            1
+        };
+        while self.pairs.len() < pairs {
+            self.pairs.push(BufferPair::new(gl)?);
+        }
+        for pair in self.pairs.iter_mut().skip(pairs) {
+            gl.delete_buffer(Some(&pair.position));
+            gl.delete_buffer(Some(&pair.color));
+        }
+        self.pairs.truncate(pairs);
+        self.next = 0;
+        for pair in &mut self.pairs {
+            match strategy {
+                UploadStrategy::SubData | UploadStrategy::Ring if pair.capacity < reserve => {
+                    pair.allocate(gl, self.layout, reserve)
+                }
+                // buffer_data で大きさが毎回変わるので、確保済みの大きさは当てにならない
+                UploadStrategy::BufferData | UploadStrategy::Orphan => pair.capacity = 0,
+                _ => {}
+            }
+        }
+        self.strategy = strategy;
+        Ok(())
+    }
+
+    // 位置だけを送り、属性 position_attrib に結び付ける（色をシェーダーで計算するとき）
+    // 色を送らないので、並べ方によらず位置だけを詰めて送る
+    pub fn upload_positions(
+        &mut self,
+        gl: &WebGlRenderingContext,
+        positions: &[f32],
+        position_attrib: u32,
+    ) {
+        let pair = self.next_pair(gl, positions.len() / POSITION_FLOATS);
+        write_buffer(gl, self.strategy, &self.pairs[pair].position, positions);
+        point_attrib(gl, position_attrib, POSITION_FLOATS, 0, 0);
+    }
+
+    // 今回書く組の番号（Ring なら次の組に進め、領域に count 個が収まらなければ確保し直す）
+    fn next_pair(&mut self, gl: &WebGlRenderingContext, count: usize) -> usize {
+        let index = self.next;
+        if self.strategy == UploadStrategy::Ring {
+            self.next = (self.next + 1) % self.pairs.len();
+        }
+        let pair = &mut self.pairs[index];
+        if matches!(
+            self.strategy,
+            UploadStrategy::SubData | UploadStrategy::Ring
+        ) && count > pair.capacity
+        {
+            pair.allocate(gl, self.layout, count);
+        }
+        index
+    }
+
+    // 位置と色を送り、送ったバッファを属性 position_attrib と color_attrib に結び付ける
+    // Interleaved では並べ直しも送る時間に含まれる（pack_vertices() は位置と色を別々に詰めるので）
+    pub fn upload(
+        &mut self,
+        gl: &WebGlRenderingContext,
+        positions: &[f32],
+        colors: &[f32],
+        position_attrib: u32,
+        color_attrib: u32,
+    ) {
+        let (strategy, layout) = (self.strategy, self.layout);
+        let pair = self.next_pair(gl, positions.len() / POSITION_FLOATS);
+        let pair = &self.pairs[pair];
+        match layout {
+            BufferLayout::Planar => {
+                write_buffer(gl, strategy, &pair.position, positions);
+                point_attrib(gl, position_attrib, POSITION_FLOATS, 0, 0);
+                write_buffer(gl, strategy, &pair.color, colors);
+                point_attrib(gl, color_attrib, COLOR_FLOATS, 0, 0);
+            }
+            BufferLayout::Interleaved => {
+                self.interleaved.clear();
+                for (position, color) in positions
+                    .chunks_exact(POSITION_FLOATS)
+                    .zip(colors.chunks_exact(COLOR_FLOATS))
+                {
+                    self.interleaved.extend_from_slice(position);
+                    self.interleaved.extend_from_slice(color);
+                }
+                write_buffer(gl, strategy, &pair.position, &self.interleaved);
+                let stride = POSITION_FLOATS + COLOR_FLOATS;
+                point_attrib(gl, position_attrib, POSITION_FLOATS, stride, 0);
+                point_attrib(gl, color_attrib, COLOR_FLOATS, stride, POSITION_FLOATS);
+            }
+        }
+    }
+}
+
+// 結び付けたバッファに data を strategy で送る
+fn write_buffer(
+    gl: &WebGlRenderingContext,
+    strategy: UploadStrategy,
+    buffer: &WebGlBuffer,
+    data: &[f32],
+) {
+    gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+    unsafe {
+        let array = js_sys::Float32Array::view(data);
+        match strategy {
+            UploadStrategy::BufferData => gl.buffer_data_with_array_buffer_view(
+                WebGlRenderingContext::ARRAY_BUFFER,
+                &array,
+                WebGlRenderingContext::DYNAMIC_DRAW,
+            ),
+            UploadStrategy::Orphan => {
+                gl.buffer_data_with_i32(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    (data.len() * 4) as i32,
+                    WebGlRenderingContext::STREAM_DRAW,
+                );
+                gl.buffer_sub_data_with_i32_and_array_buffer_view(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    0,
+                    &array,
+                );
+            }
+            UploadStrategy::SubData | UploadStrategy::Ring => gl
+                .buffer_sub_data_with_i32_and_array_buffer_view(
+                    WebGlRenderingContext::ARRAY_BUFFER,
+                    0,
+                    &array,
+                ),
+        }
+    }
+}
+
+// 今結び付けているバッファを属性 attrib として読む（stride と offset は float の数）
+fn point_attrib(
+    gl: &WebGlRenderingContext,
+    attrib: u32,
+    size: usize,
+    stride: usize,
+    offset: usize,
+) {
+    gl.vertex_attrib_pointer_with_i32(
+        attrib,
+        size as i32,
+        WebGlRenderingContext::FLOAT,
+        false,
+        (stride * 4) as i32,
+        (offset * 4) as i32,
+    );
+    gl.enable_vertex_attrib_array(attrib);
+}
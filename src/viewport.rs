@@ -0,0 +1,106 @@
+use wasm_bindgen::prelude::*;
+#[cfg(target_arch = "wasm32")]
+use web_sys::{CanvasRenderingContext2d, WebGlRenderingContext};
+
+// 1つのキャンバス（コンテキスト）内の描画領域（ピクセル座標、左上原点）
+// 複数の論理ビューで1つのWebGLコンテキストを共有し、ブラウザの
+// コンテキスト数上限（~16個）に引っかからずに比較グリッドを作るために使う
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[wasm_bindgen]
+impl Viewport {
+    #[wasm_bindgen(constructor)]
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Viewport {
+        Viewport { x, y, width, height }
+    }
+
+    // キャンバスを cols × rows に分割したときの index 番目（左上から行優先）
+    pub fn grid_cell(
+        canvas_width: f32,
+        canvas_height: f32,
+        cols: u32,
+        rows: u32,
+        index: u32,
+    ) -> Viewport {
+        let cols = cols.max(1);
+        let rows = rows.max(1);
+        let width = canvas_width / cols as f32;
+        let height = canvas_height / rows as f32;
+        Viewport {
+            x: (index % cols) as f32 * width,
+            y: (index / cols % rows) as f32 * height,
+            width,
+            height,
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Viewport {
+    // 左右2分割（A/B比較用）
+    pub(crate) fn split_halves(self) -> (Viewport, Viewport) {
+        let half = self.width / 2.0;
+        (
+            Viewport { width: half, ..self },
+            Viewport {
+                x: self.x + half,
+                width: self.width - half,
+                ..self
+            },
+        )
+    }
+}
+
+// WebGLのビューポートとシザーを設定する（None ならキャンバス全体）
+// WebGLは左下原点なのでY座標を反転する
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn apply_gl(gl: &WebGlRenderingContext, viewport: Option<Viewport>) {
+    let buffer_width = gl.drawing_buffer_width();
+    let buffer_height = gl.drawing_buffer_height();
+    match viewport {
+        Some(v) => {
+            let x = v.x as i32;
+            let y = buffer_height - (v.y + v.height) as i32;
+            let (w, h) = (v.width as i32, v.height as i32);
+            gl.enable(WebGlRenderingContext::SCISSOR_TEST);
+            gl.viewport(x, y, w, h);
+            gl.scissor(x, y, w, h);
+        }
+        None => {
+            gl.disable(WebGlRenderingContext::SCISSOR_TEST);
+            gl.viewport(0, 0, buffer_width, buffer_height);
+        }
+    }
+}
+
+// シザー矩形だけを変える（ビューポートの座標変換はそのまま）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn scissor_gl(gl: &WebGlRenderingContext, region: Viewport) {
+    let buffer_height = gl.drawing_buffer_height();
+    gl.enable(WebGlRenderingContext::SCISSOR_TEST);
+    gl.scissor(
+        region.x as i32,
+        buffer_height - (region.y + region.height) as i32,
+        region.width as i32,
+        region.height as i32,
+    );
+}
+
+// Canvas2Dでは領域でクリップして原点を移動する（終わったら restore すること）
+#[cfg(target_arch = "wasm32")]
+pub(crate) fn begin_canvas(ctx: &CanvasRenderingContext2d, viewport: Option<Viewport>) {
+    ctx.save();
+    if let Some(v) = viewport {
+        ctx.begin_path();
+        ctx.rect(v.x as f64, v.y as f64, v.width as f64, v.height as f64);
+        ctx.clip();
+        let _ = ctx.translate(v.x as f64, v.y as f64);
+    }
+}
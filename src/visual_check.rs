@@ -0,0 +1,104 @@
+use wasm_bindgen::prelude::*;
+
+use crate::raster::{RasterRun, RasterSurface};
+use crate::rng;
+use crate::scene::SceneKind;
+
+// 描画最適化でシーンの見た目が変わっていないかを確かめる
+// 固定シードで一定フレーム進めたシーンを縮小した参照画像に描き、知覚ハッシュを期待値と比べる
+// 参照画像はバックエンドを通さずWASM内で描くので、ブラウザに依存せず同じ値になる
+
+const CANVAS_WIDTH: f32 = 288.0;
+const CANVAS_HEIGHT: f32 = 256.0;
+// 4x4px を参照画像の1画素にする
+const SCALE: f32 = 4.0;
+const GRID_WIDTH: usize = 72;
+const GRID_HEIGHT: usize = 64;
+const SEED: u64 = 42;
+const FRAMES: u32 = 30;
+const PARTICLE_COUNT: usize = 2_000;
+// 64ビット中この数までの違いは許す（三角関数の実装差などで境界の画素が変わる程度）
+const MAX_DISTANCE: u32 = 8;
+
+// シーンごとの期待するハッシュ（SceneKind の値の順）
+// 意図して見た目を変えたときは verify_visuals() の actual で更新する
+const GOLDEN: [u64; 14] = [
+    0xece8_7010_0000_0000, // particles
+    0x0000_0000_0000_0000, // clear
+    0xe8f0_7000_0000_0000, // density
+    0x70e8_cc8e_8ecc_e870, // physarum
+    0x170f_0e0e_0e0e_0e17, // reaction-diffusion
+    0x916e_922b_5c26_4d56, // life
+    0x0303_0311_1111_0303, // fractal
+    0x0303_0311_1111_0303, // fractal-gpu
+    0x55aa_55aa_55aa_55aa, // blur
+    0x0000_2060_4321_0000, // raycaster
+    0x9364_3662_acd8_c419, // skeleton
+    0x25d2_b515_9798_e6a9, // tilemap
+    0xfca4_e4b8_e8e4_f0cc, // bezier
+    0x26ab_cef7_9a9c_1d6c, // polygons
+];
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct VisualCheck {
+    pub kind: SceneKind,
+    pub name: String,
+    pub expected: u64,
+    pub actual: u64,
+    // 違っていたビット数
+    pub distance: u32,
+    pub passed: bool,
+}
+
+// すべてのシーンを確認する（SceneKind の値の順）
+#[wasm_bindgen]
+pub fn verify_visuals() -> Vec<VisualCheck> {
+    SceneKind::ALL
+        .iter()
+        .map(|&kind| {
+            let expected = GOLDEN[kind as usize];
+            let actual = scene_hash(kind);
+            let distance = (expected ^ actual).count_ones();
+            VisualCheck {
+                kind,
+                name: kind.name().to_string(),
+                expected,
+                actual,
+                distance,
+                passed: distance <= MAX_DISTANCE,
+            }
+        })
+        .collect()
+}
+
+fn scene_hash(kind: SceneKind) -> u64 {
+    let mut surface = RasterSurface::new(CANVAS_WIDTH, CANVAS_HEIGHT, SCALE);
+    rng::with_seed(SEED, || {
+        let mut run = RasterRun::new(kind, CANVAS_WIDTH, CANVAS_HEIGHT, PARTICLE_COUNT)
+            .expect("reference canvas size is valid for every scene");
+        for _ in 0..FRAMES {
+            run.update();
+        }
+        run.render(&mut surface);
+    });
+    difference_hash(&surface)
+}
+
+// dHash: 9x8 に縮小した輝度の横方向の大小関係を64ビットに詰める
+fn difference_hash(surface: &RasterSurface) -> u64 {
+    let (block_w, block_h) = (GRID_WIDTH / 9, GRID_HEIGHT / 8);
+    let mut luma = [[0.0f32; 9]; 8];
+    for (y, row) in surface.pixels().chunks_exact(GRID_WIDTH).enumerate() {
+        for (x, [r, g, b]) in row.iter().enumerate() {
+            luma[y / block_h][x / block_w] += 0.299 * r + 0.587 * g + 0.114 * b;
+        }
+    }
+    let mut hash = 0u64;
+    for row in &luma {
+        for x in 0..8 {
+            hash = (hash << 1) | (row[x] < row[x + 1]) as u64;
+        }
+    }
+    hash
+}
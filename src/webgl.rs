@@ -0,0 +1,2387 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas, WebGlBuffer, WebGlRenderingContext, WebGlProgram, WebGlTexture};
+
+use crate::attractor::Attractor;
+use crate::obstacle::Obstacle;
+use crate::emitter::Emitter;
+use crate::bounds::{BoundaryMode, OutOfBounds};
+use crate::capture::CapturedBuffer;
+use crate::clustering::KMeans;
+use crate::color_pipeline::{ColorPipeline, GpuColors, GPU_COLOR_VERTEX_SHADER};
+use crate::color_theme::{ColorMode, ColorTheme};
+use crate::gpu_physics::PhysicsMode;
+use crate::projection::ProjectionMode;
+use crate::events::{BenchmarkEvent, EventBus, EventKind};
+use crate::explosion::ExplosionConfig;
+use crate::fingerprint::ConfigFingerprint;
+use crate::i18n::{tr, Text};
+use crate::context::{AcquiredContext, CanvasById, CanvasElement, ContextSource, ExternalContext, OffscreenCanvasSource};
+use crate::context_loss::ContextLossWatch;
+use crate::backend::{describe_error, BackendKind};
+use crate::init::{progress, BuildOptions, InitProgress, InitTimings};
+use crate::camera::Camera;
+use crate::input::{InputAction, InputHandlers};
+use crate::panel::OverlayRect;
+use crate::memory::{check_budget, try_vec};
+use crate::physics::SimulationConfig;
+use crate::metrics::{FrameHistogram, Metrics, MetricsCollector};
+use crate::results::{ResultFormat, RunInfo};
+use crate::scheduler::SchedulerStats;
+use crate::draw_strategy::DrawStrategy;
+use crate::particle_shape::ParticleShape;
+use crate::particle_style::ParticleStyle;
+use crate::image_source::DecodedImage;
+use crate::quirks::Quirks;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::trails::TrailBuffer;
+use crate::upload::{BufferLayout, UploadStrategy, VertexBuffers};
+use crate::resize::CanvasSizing;
+use crate::shader::{PrecisionComparison, PrecisionRun, ShaderPrecision};
+use crate::gl_state::GlState;
+use crate::gl_surface::{GlSurface, SceneGl};
+use crate::scene::{
+    create_blur_scene, create_clip_scene, create_composite_scene, create_life_scene, create_point_size_scene, create_scene, create_readback_scene, create_shadow_scene, create_state_change_scene, create_texture_upload_scene, describe_scene,
+    require_backend, Atlas, BlurMethod, Scene, SceneDescription, SceneKind,
+};
+use crate::lod::LodMode;
+use crate::picking::ParticleInfo;
+use crate::simulation::{Particle, ParticleSet, ReadStamps, Simulation};
+use crate::observers::Observers;
+use crate::timeline::Timeline;
+use crate::tuning::TuningProfile;
+use crate::stats_stream::StatsStream;
+#[cfg(feature = "telemetry")]
+use crate::telemetry::Telemetry;
+use crate::viewport::Viewport;
+use crate::{capture, export, frame_capture, gl_surface, panel, particle_shape, particle_style, projection, quirks, results, rng, selection, shader, snapshot, timing, viewport};
+
+// 頂点データをいつ詰めるか
+// Split: update() で物理演算、render() で改めて全パーティクルを走査して詰める（従来）
+// Interleaved: update() の中で、キャッシュに載っているうちにそのまま詰める
+#[wasm_bindgen]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScheduleMode {
+    Split = 0,
+    Interleaved = 1,
+}
+
+#[wasm_bindgen]
+pub struct ParticleSystem {
+    sim: Simulation,
+    gl: WebGlRenderingContext,
+    // 描く前に既定に戻す GL の状態
+    gl_state: GlState,
+    program: WebGlProgram,
+    // 位置と色の頂点バッファと、その送り方
+    buffers: VertexBuffers,
+    // ColorPipeline::Gpu のときだけ Some（色をシェーダーで計算するプログラムと、色相と寿命のバッファ）
+    gpu_colors: Option<GpuColors>,
+    read_stamps: ReadStamps,
+    init: InitProgress,
+    // Particles 以外のシーンが有効なときだけ Some
+    scene: Option<Box<dyn Scene>>,
+    scene_gl: Option<SceneGl>,
+    viewport: Option<Viewport>,
+    split_compare: Option<(RenderMode, RenderMode)>,
+    schedule: ScheduleMode,
+    // 毎フレーム使い回す頂点データ
+    positions: Vec<f32>,
+    colors: Vec<f32>,
+    // Interleaved モードで update() が詰めたデータが最新か
+    vertices_packed: bool,
+    // set_load() で指定した負荷（None ならシーンごとの既定）
+    load: Option<f32>,
+    events: EventBus,
+    strict: bool,
+    // explode() の強さ・範囲・減衰
+    explosion: ExplosionConfig,
+    // パーティクルのシェーダーの精度
+    precision: ShaderPrecision,
+    // パーティクルの形と、Sprite のときに使うテクスチャ（最初に Sprite にしたときに作る）
+    shape: ParticleShape,
+    sprite_texture: Option<WebGlTexture>,
+    // set_particle_texture() で渡した画像（組み込みの光の玉なら None。コンテキストが戻ったら転送し直す）
+    particle_texture: Option<DecodedImage>,
+    // 点ごとの大きさの倍率（a_size）のバッファと送るデータ（最初に大きさが変わる設定にしたときに作る）
+    size_buffer: Option<WebGlBuffer>,
+    point_sizes: Vec<f32>,
+    // 透視投影のときの奥行き（a_depth、0〜1）のバッファと送るデータ（最初に Perspective にしたときに作る）
+    depth_buffer: Option<WebGlBuffer>,
+    depths: Vec<f32>,
+    // パーティクルの合成方法と、Trails のときに描き溜める先（最初に Trails にしたときに作る）
+    render_mode: RenderMode,
+    trail_buffer: Option<TrailBuffer>,
+    // 検出したブラウザ固有の問題と回避策
+    quirks: Quirks,
+    // subscribe_stats() の購読
+    stats_stream: StatsStream,
+    // start_telemetry_channel() と start_telemetry_socket() の送り先
+    #[cfg(feature = "telemetry")]
+    telemetry: Telemetry,
+    // on_frame() などで登録されたコールバック
+    observers: Observers,
+    // 直近のフレーム時間と annotate() の印
+    timeline: Timeline,
+    // apply_tuning_profile() で当てた設定の組
+    tuning: Option<TuningProfile>,
+    // get_metrics() の計測
+    metrics: MetricsCollector,
+    // 描画バッファの倍率と ResizeObserver
+    sizing: CanvasSizing,
+    // attach_input_handlers() で登録した入力のコールバック
+    input: Option<InputHandlers>,
+    // webglcontextlost / webglcontextrestored のリスナー（キャンバスが無ければ None）
+    context_loss: Option<ContextLossWatch>,
+}
+
+#[wasm_bindgen]
+impl ParticleSystem {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: &str, particle_count: usize) -> Result<ParticleSystem, JsValue> {
+        Self::from_canvas(CanvasById(canvas_id).canvas()?, particle_count)
+    }
+
+    // フレームワークが持っている canvas 要素から生成（id が無くても、ドキュメントに追加前でもよい）
+    pub fn from_canvas(canvas: HtmlCanvasElement, particle_count: usize) -> Result<ParticleSystem, JsValue> {
+        Self::from_source(&CanvasElement(&canvas), particle_count, BuildOptions::default())
+    }
+
+    // メモリ予算を指定して生成（超える場合は確保前にエラーを返す）
+    pub fn with_memory_budget(
+        canvas_id: &str,
+        particle_count: usize,
+        budget_bytes: u64,
+    ) -> Result<ParticleSystem, JsValue> {
+        let options = BuildOptions {
+            memory_budget: budget_bytes,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // 重力・跳ね返り・摩擦・点の大きさを指定して生成する
+    pub fn with_simulation_config(
+        canvas_id: &str,
+        particle_count: usize,
+        config: &SimulationConfig,
+    ) -> Result<ParticleSystem, JsValue> {
+        let options = BuildOptions {
+            simulation: *config,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // 機能が足りないときに代替経路で描かず、エラーを返すモードで生成する
+    pub fn new_strict(canvas_id: &str, particle_count: usize) -> Result<ParticleSystem, JsValue> {
+        let options = BuildOptions {
+            strict: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // パーティクルを生成せずに作り、init_chunk() で少しずつ生成する
+    // 数百万個でもページを固めずに起動できる
+    pub fn new_progressive(canvas_id: &str, particle_count: usize) -> Result<ParticleSystem, JsValue> {
+        let options = BuildOptions {
+            progressive: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // キャンバスの一部の領域だけを使って生成する
+    // 同じキャンバスに複数インスタンスを作ると1つのコンテキストを共有して描画する
+    pub fn with_viewport(
+        canvas_id: &str,
+        particle_count: usize,
+        viewport: &Viewport,
+    ) -> Result<ParticleSystem, JsValue> {
+        let options = BuildOptions {
+            viewport: Some(*viewport),
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // JS側で用意したWebGLコンテキストから生成（Node + headless-gl 用）
+    pub fn with_context(
+        gl: JsValue,
+        width: u32,
+        height: u32,
+        particle_count: usize,
+    ) -> Result<ParticleSystem, JsValue> {
+        let source = ExternalContext {
+            context: gl,
+            width: width as f32,
+            height: height as f32,
+        };
+        Self::from_source(&source, particle_count, BuildOptions::default())
+    }
+
+    // Worker に渡した OffscreenCanvas から生成（メインスレッドと Worker での描画を比べる用）
+    pub fn from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+        particle_count: usize,
+    ) -> Result<ParticleSystem, JsValue> {
+        Self::from_source(&OffscreenCanvasSource(&canvas), particle_count, BuildOptions::default())
+    }
+
+    pub fn update(&mut self) {
+        self.apply_input();
+        if self.sim.paused {
+            return;
+        }
+        let start = timing::now_ms();
+        self.step_frame();
+        self.metrics.record_update(start);
+    }
+
+    // update() で物理演算を止める（描画・入力による視点の操作・爆発の受け付けは続ける）
+    pub fn pause(&mut self) {
+        self.sim.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.sim.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sim.paused
+    }
+
+    // 止めていても n ステップだけ update() と同じように進める（描画はしない）
+    pub fn step(&mut self, n: u32) {
+        let paused = std::mem::replace(&mut self.sim.paused, false);
+        for _ in 0..n {
+            self.update();
+        }
+        self.sim.paused = paused;
+    }
+
+    // dt_ms 経過した分だけ固定長のステップで update() し、進めたステップ数を返す
+    // 物理の進み方がリフレッシュレートに依らなくなる。描画は持ち越した端数の分だけ直前のステップとの間を補間する
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        let steps = self.advance_timestep(dt_ms);
+        for _ in 0..steps {
+            self.update();
+        }
+        self.interpolate_frame();
+        steps
+    }
+
+    // 1ステップの長さ(ms)を設定し、実際に使う値を返す（既定は 60Hz の1フレーム）
+    pub fn set_fixed_timestep(&mut self, step_ms: f64) -> f64 {
+        self.sim.timestep.set_step_ms(step_ms)
+    }
+
+    pub fn get_fixed_timestep(&self) -> f64 {
+        self.sim.timestep.step_ms()
+    }
+
+    // requestAnimationFrame の timestamp(ms) を渡して1回分進める
+    // 物理は毎回 update() で進め、描画は set_target_fps() の上限を超えない回だけ行う。描いたら true
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.update();
+        let render = self.cap_frame(timestamp);
+        if render {
+            self.render();
+        }
+        render
+    }
+
+    // 描画の上限(fps)を設定し、実際に使う値を返す（0 以下なら上限なし。既定は上限なし）
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.sim.frame_cap.set_target_fps(fps)
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        self.sim.frame_cap.target_fps()
+    }
+
+    // tick() の間隔から推定した表示のリフレッシュレート(Hz)（まだ分からなければ 0）
+    pub fn get_refresh_rate(&self) -> f64 {
+        self.sim.frame_cap.refresh_rate()
+    }
+
+    // set_target_fps() してから tick() で描いた回数と、上限のために描かなかった回数
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.sim.frame_cap.rendered
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.sim.frame_cap.skipped
+    }
+
+    // 頂点データを詰めるタイミングを切り替える
+    pub fn set_schedule_mode(&mut self, mode: ScheduleMode) {
+        self.schedule = mode;
+        self.vertices_packed = false;
+    }
+
+    pub fn get_schedule_mode(&self) -> ScheduleMode {
+        self.schedule
+    }
+
+    pub fn render(&mut self) {
+        if let Some((width, height)) = self.sizing.take_pending() {
+            let _ = self.resize(width, height);
+        }
+        let start = timing::now_ms();
+        self.render_frame();
+        self.metrics.record_render(start);
+        self.sim.lod.record_render(timing::now_ms() - start, self.sim.particle_count);
+        let vertex_floats = self.positions.capacity() + self.colors.capacity() + self.point_sizes.capacity() + self.depths.capacity();
+        self.metrics.record_buffer_bytes(self.sim.buffer_bytes() + (vertex_floats * std::mem::size_of::<f32>()) as u64);
+    }
+
+    // 描画せずに n ステップ連続で物理演算し、経過時間(ms)を返す
+    // JS⇔WASMの境界を1回しか跨がないので純粋な物理演算性能を測れる
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..n {
+            self.update();
+        }
+        let elapsed = timing::now_ms() - start;
+        self.observers
+            .note_complete(n, elapsed, false, self.get_frame_count());
+        elapsed
+    }
+
+    // 最初の frames フレームを今すぐ物理演算と描画まで進め、計測から除く（経過時間(ms)を返す）
+    // シェーダーのコンパイルや JIT・バッファの確保を済ませ、get_metrics() が落ち着いた状態の値になる
+    // 呼ばなくても最初の数フレームは除く（warmup(0) なら除かない）
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        self.metrics.begin_warmup(frames);
+        let start = timing::now_ms();
+        for _ in 0..frames {
+            self.update();
+            self.render();
+        }
+        timing::now_ms() - start
+    }
+
+    // 止まるまで（平均速度が threshold px/フレームを下回るまで）最大 max_frames ステップ物理演算し、
+    // 経過時間(ms)を返す。止まるまでのフレーム数は get_frames_until_settle() で取れる
+    pub fn simulate_until_settled(&mut self, threshold: f32, max_frames: u32) -> f64 {
+        self.set_settle_threshold(Some(threshold));
+        let start = timing::now_ms();
+        let mut frames = 0;
+        for _ in 0..max_frames {
+            self.update();
+            frames += 1;
+            if self.is_settled() {
+                break;
+            }
+        }
+        let elapsed = timing::now_ms() - start;
+        let (settled, frame_count) = (self.is_settled(), self.get_frame_count());
+        self.observers
+            .note_complete(frames, elapsed, settled, frame_count);
+        elapsed
+    }
+
+    // 平均速度が threshold を下回ったら止まったとみなす終了条件（None で解除）
+    // 固定フレーム数より、跳ねて止まるまでを測るシーンに向いている
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        self.sim.set_settle_threshold(threshold);
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.get_frames_until_settle().is_some()
+    }
+
+    // 終了条件を設定してから止まるまでのフレーム数（まだ止まっていなければ None）
+    pub fn get_frames_until_settle(&self) -> Option<u32> {
+        self.sim.settle.and_then(|settle| settle.frames_until_settle())
+    }
+
+    pub fn get_mean_speed(&self) -> f32 {
+        self.sim.mean_speed()
+    }
+
+    // 未生成のパーティクルを最大 max_particles 個生成し、進捗(0.0~1.0)を返す
+    pub fn init_chunk(&mut self, max_particles: usize) -> f32 {
+        let start = timing::now_ms();
+        self.sim.spawn_pending(max_particles);
+        self.init.timings.particles_ms += timing::now_ms() - start;
+        let (spawned, total) = (self.sim.spawned(), self.sim.particle_count);
+        self.init.report(spawned, total);
+        progress(spawned, total)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.sim.spawned() == self.sim.particle_count
+    }
+
+    // init_chunk() のたびに (progress, spawned, total) で呼ばれる
+    pub fn set_init_progress_callback(&mut self, callback: js_sys::Function) {
+        self.init.set_callback(callback);
+    }
+
+    // 生成開始から全パーティクルが揃った最初のフレームまでの時間(ms)
+    pub fn get_time_to_first_frame(&self) -> f64 {
+        self.init.time_to_first_frame()
+    }
+
+    // コンストラクタ各段階の所要時間
+    pub fn get_init_timings(&self) -> InitTimings {
+        self.init.timings()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        match &self.scene {
+            Some(scene) => scene.frame_count(),
+            None => self.sim.frame_count,
+        }
+    }
+
+    // コンテキスト・バッファ・プログラムを作り直さずにシーンを切り替える
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        if self.strict {
+            require_backend(kind, "webgl")?;
+        }
+        if kind != SceneKind::Particles {
+            self.ensure_scene_gl()?;
+        }
+        self.events.clear_reported();
+        self.scene = create_scene(kind, self.sim.width, self.sim.height)?;
+        if self.scene.is_none() {
+            self.reset();
+        }
+        self.apply_load();
+        Ok(())
+    }
+
+    // セル数を指定してライフゲームに切り替える（数百万セルまで指定できる）
+    pub fn switch_to_life_grid(&mut self, columns: usize, rows: usize) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_life_scene(
+            columns,
+            rows,
+            self.sim.width,
+            self.sim.height,
+        )?);
+        self.apply_load();
+        Ok(())
+    }
+
+    // 決まった数（負荷 0.5 のとき）の点を size px で描くシーンに切り替える（フィルレートの計測用）
+    // get_max_point_size() より大きい点は上限の大きさで描く
+    pub fn switch_to_point_size(&mut self, count: usize, size: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_point_size_scene(
+            count,
+            size,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // キャンバスの一辺の scale 倍の画像を毎フレームテクスチャとして送り直すシーンに切り替える（転送帯域の計測用）
+    pub fn switch_to_texture_upload(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_texture_upload_scene(scale, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描いた直後にキャンバスの一辺の scale 倍の範囲を readPixels で読み戻すシーンに切り替える（読み戻しの計測用）
+    pub fn switch_to_readback(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_readback_scene(scale, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 領域の数を決めてクリップのシーンに切り替える（1〜64 に丸める、ステンシルバッファでクリップする）
+    pub fn switch_to_clip(&mut self, regions: u32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_clip_scene(regions, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 合成方法を1つに決めて重ねた矩形のシーンに切り替える（ブレンド式で表せない合成方法はエラー）
+    pub fn switch_to_compositing(&mut self, mode: CompositeMode) -> Result<(), JsValue> {
+        if mode.gl_blend().is_none() {
+            return Err(tr(Text::CompositeModeUnavailable, &[&mode.name(), &"webgl"]).into());
+        }
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_composite_scene(mode, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 影のぼかしの大きさ(px)を決めて光る円のシーンに切り替える（0〜64 に丸める、シェーダーで同じ見た目を描く）
+    pub fn switch_to_shadows(&mut self, shadow_blur: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_shadow_scene(shadow_blur, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // change_every 個の矩形ごとに描画状態を切り替えるシーンに切り替える
+    // WebGL では状態を切り替えられないので色だけで描く（FallbackUsed を送る）
+    pub fn switch_to_state_changes(&mut self, change_every: u32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_state_change_scene(change_every, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 点のシーンで描ける一番大きい点(px)
+    pub fn get_max_point_size(&self) -> f32 {
+        quirks::max_point_size(&self.gl).unwrap_or(1.0)
+    }
+
+    // 任意の画像（width × height のRGBA）を毎フレームぼかすシーンに切り替える
+    // 描画先が method に対応していなければWASMでぼかす
+    // radius は 16px までに丸める
+    pub fn switch_to_blur(
+        &mut self,
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+        method: BlurMethod,
+        radius: u32,
+    ) -> Result<(), JsValue> {
+        if self.strict && method == BlurMethod::CanvasFilter {
+            return Err(tr(Text::StrictBlurUnsupported, &[&"Canvas2D filter"]).into());
+        }
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_blur_scene(width, height, rgba, method, radius)?);
+        self.apply_load();
+        Ok(())
+    }
+
+    // シーンの負荷を 0.0~1.0 で指定する（パーティクル数・格子の大きさ・曲線の数など）
+    // 0.5 がおおむね各シーンの既定。シーンを切り替えても引き継ぐ
+    pub fn set_load(&mut self, load: f32) {
+        if !(0.0..=1.0).contains(&load) {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::LoadClamped, &[&load]),
+            );
+        }
+        self.load = Some(load.clamp(0.0, 1.0));
+        self.apply_load();
+    }
+
+    pub fn get_load(&self) -> Option<f32> {
+        self.load
+    }
+
+    // 動いているパーティクルの数をその場で増減する（バッファの作り直しやリセットはしない）
+    // 作成時の数までで、それより多ければ丸めて ParameterClamped を送る。実際の数を返す
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let applied = self.sim.set_particle_count(count);
+        if applied != count {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ParticleCountClamped, &[&count, &applied]),
+            );
+        }
+        self.vertices_packed = false;
+        applied
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.sim.particle_count
+    }
+
+    // 作成時の数を超えてパーティクル数を増やし、実際の数を返す（メモリ予算を超える数はエラー）
+    // 確保済みの領域を超えた分は伸ばしながら確保するので、メモリの増え方による停止の計測に使う
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, JsValue> {
+        let applied = self.sim.grow_particle_count(count)?;
+        self.vertices_packed = false;
+        Ok(applied)
+    }
+
+    // set_particle_count() で増やせる上限（作成時の数か、grow_particle_count() で増やした数）
+    pub fn get_max_particles(&self) -> usize {
+        self.sim.max_particles()
+    }
+
+    // 現在のシーン・キャンバスの大きさ・負荷で固定シードの実行をAPNGに書き出す
+    // 描画はWASM内で行うので、どのバックエンドから書き出しても同じアニメーションになる
+    pub fn export_animation(&self, frames: u32, every_n: u32) -> Result<Vec<u8>, JsValue> {
+        export::export_scene_animation(
+            self.get_scene(),
+            self.sim.width as u32,
+            self.sim.height as u32,
+            self.sim.max_particles(),
+            self.load,
+            frames,
+            every_n,
+        )
+    }
+
+    // 最後に描いたフレームを上の行から並べた RGBA で読み戻す（大きさは描画バッファの画素数）
+    // compare_frames() でバックエンド同士や変更前後の見た目を比べる。render() と同じタスクの中で呼ぶ
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        frame_capture::read_gl_frame(&self.gl)
+    }
+
+    // 今のシーンの途中のバッファを PNG で取り出す（パーティクル描画には途中のバッファがないので空）
+    pub fn capture_debug_buffers(&self) -> Result<Vec<CapturedBuffer>, JsValue> {
+        match &self.scene {
+            Some(scene) => capture::capture(scene.debug_buffers()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    // 実際に効いている設定（シーン・数・モード・機能・ビルド）のハッシュ
+    // 値が違う計測結果同士は比べられない
+    pub fn get_config_fingerprint(&self) -> String {
+        self.config_fingerprint().hash()
+    }
+
+    // ハッシュの元になった設定の一覧（"key=value;" の並び）
+    pub fn get_config_summary(&self) -> String {
+        self.config_fingerprint().into_text()
+    }
+
+    // 溜まっている警告イベントを取り出す（古い順）
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        self.events.drain()
+    }
+
+    // 警告イベントが起きるたびに callback(event) を呼ぶ（登録中は poll_events() には溜まらない）
+    pub fn set_event_callback(&mut self, callback: js_sys::Function) {
+        self.events.set_callback(callback);
+    }
+
+    pub fn clear_event_callback(&mut self) {
+        self.events.clear_callback();
+    }
+
+    // interval_ms（50ms以上）ごとに callback(StatsDelta) を呼ぶ（毎フレームは呼ばない）
+    pub fn subscribe_stats(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        self.stats_stream.subscribe(callback, interval_ms);
+    }
+
+    pub fn unsubscribe_stats(&mut self) {
+        self.stats_stream.unsubscribe();
+    }
+
+    // 1秒ごとに計測値のまとめ（JSON の文字列）を BroadcastChannel(name) に送る（telemetry フィーチャー）
+    // 同じオリジンの別のタブで new BroadcastChannel(name) を開けば、複数の端末やタブの結果を1か所で見られる
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_channel(&mut self, name: &str) -> Result<(), JsValue> {
+        self.telemetry.start_channel(name)?;
+        Ok(())
+    }
+
+    // 同じまとめを WebSocket で集計サーバーに送る（url は ws:// か wss://）
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_socket(&mut self, url: &str) -> Result<(), JsValue> {
+        self.telemetry.start_socket(url)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn stop_telemetry(&mut self) {
+        self.telemetry.stop();
+    }
+
+    // render() のたびに callback(FrameNotice) を呼ぶ
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        self.observers.on_frame(callback);
+    }
+
+    // simulate_frames()・simulate_until_settled() が終わったら callback(BenchmarkComplete) を呼ぶ
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        self.observers.on_benchmark_complete(callback);
+    }
+
+    // 500ms ごとの FPS が threshold を下回ったら callback(FpsDrop) を呼ぶ（上回るまでは1回だけ）
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        self.observers.on_fps_drop(threshold, callback);
+    }
+
+    // on_frame()・on_benchmark_complete()・on_fps_drop() の登録をすべて外す
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // ホストのページの出来事（クリック・データの読み込み・タブへの復帰など）を今の時刻の印として残す
+    // 印は直近のフレーム時間と一緒に get_timeline_json() と export_results() に書き出される
+    pub fn annotate(&mut self, label: &str) {
+        let frame_count = self.get_frame_count();
+        self.timeline.annotate(label, frame_count);
+    }
+
+    // 直近のフレーム時間と annotate() の印（時刻はどちらも performance.now() の ms）
+    pub fn get_timeline_json(&self) -> String {
+        self.timeline.to_json()
+    }
+
+    pub fn clear_timeline(&mut self) {
+        self.timeline.clear();
+    }
+
+    // update()・render() の所要時間とFPS
+    pub fn get_metrics(&self) -> Metrics {
+        self.metrics.snapshot()
+    }
+
+    // WebGL のコンテキストが失われているか（その間は render() が何も描かない）
+    // ブラウザが戻したら次の render() でシェーダーとバッファを作り直して描き始める
+    pub fn context_lost(&self) -> bool {
+        self.gl.is_context_lost()
+    }
+
+    // これまでにコンテキストが失われた回数（get_metrics() の context_losses と同じ）
+    pub fn get_context_losses(&self) -> u32 {
+        self.context_loss.as_ref().map_or(0, ContextLossWatch::losses)
+    }
+
+    pub fn get_metrics_json(&self) -> String {
+        self.metrics.snapshot().to_json()
+    }
+
+    // これまでの render() の間隔を bucket_ms 幅でまとめた分布と、パーセンタイル・長いフレームの回数
+    pub fn get_frame_histogram(&self, bucket_ms: f64) -> FrameHistogram {
+        self.metrics.frame_histogram(bucket_ms)
+    }
+
+    pub fn reset_frame_histogram(&mut self) {
+        self.metrics.reset_frame_histogram();
+    }
+
+    // 計測値と実行の情報（バックエンド・パーティクル数・キャンバスの大きさ・シード・ビルドのフィーチャー）を書き出す
+    // user_agent は JS から navigator.userAgent を渡す（set_metadata() の内容も入る）
+    pub fn export_results(&self, format: ResultFormat, user_agent: &str) -> String {
+        let (width, height) = self.size();
+        let info = RunInfo {
+            backend: "webgl",
+            particle_count: self.sim.particle_count,
+            width,
+            height,
+            seed: self.sim.seed,
+            tuning_profile: self.tuning.map(TuningProfile::name),
+            user_agent,
+        };
+        results::export(
+            format,
+            &info,
+            &self.metrics.snapshot(),
+            &self.metrics.frame_histogram(1.0),
+            &self.timeline,
+        )
+    }
+
+    // export_results() を filename のファイルとしてダウンロードさせる
+    pub fn download_results(
+        &self,
+        format: ResultFormat,
+        user_agent: &str,
+        filename: &str,
+    ) -> Result<(), JsValue> {
+        results::download(&self.export_results(format, user_agent), format, filename)
+    }
+
+    // 左半分を mode_a、右半分を mode_b で描いて見た目と負荷を同時に比較する
+    pub fn set_split_compare(&mut self, mode_a: RenderMode, mode_b: RenderMode) {
+        self.split_compare = Some((mode_a, mode_b));
+    }
+
+    pub fn clear_split_compare(&mut self) {
+        self.split_compare = None;
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        self.scene.as_ref().map_or(SceneKind::Particles, |scene| scene.kind())
+    }
+
+    // 現在のシーンの名前・パラメーター・対応バックエンド
+    pub fn describe_current_scene(&self) -> SceneDescription {
+        match &self.scene {
+            Some(scene) => scene.describe(),
+            None => describe_scene(SceneKind::Particles),
+        }
+    }
+
+    // 状態のダブルバッファリング（描画中に次の状態を書けるようにする）
+    pub fn set_double_buffered(&mut self, enabled: bool) {
+        self.sim.set_double_buffered(enabled);
+    }
+
+    pub fn is_double_buffered(&self) -> bool {
+        self.sim.is_double_buffered()
+    }
+
+    // 公開済み状態のシーケンス番号
+    pub fn get_state_sequence(&self) -> u64 {
+        self.sim.sequence()
+    }
+
+    // 直近の render() が描いた状態のシーケンス番号
+    pub fn get_rendered_sequence(&self) -> u64 {
+        self.read_stamps.last_rendered
+    }
+
+    // 読み取り中に状態が書き換わったフレーム数（ダブルバッファなら常に0）
+    pub fn get_torn_frame_count(&self) -> u32 {
+        self.read_stamps.torn_frames
+    }
+
+    // 矩形内のパーティクルを選択して強調表示する。選択数を返す
+    pub fn select_region(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> usize {
+        self.sim.selection = selection::select_region(self.sim.particles(), x0, y0, x1, y1);
+        self.sim.selection.len()
+    }
+
+    // 投げ縄選択。points は [x0, y0, x1, y1, ...] の多角形の頂点列。選択数を返す
+    pub fn select_polygon(&mut self, points: &[f32]) -> usize {
+        self.sim.selection = selection::select_polygon(self.sim.particles(), points);
+        self.sim.selection.len()
+    }
+
+    // 選択中のパーティクルのインデックス
+    pub fn get_selection(&self) -> Vec<u32> {
+        self.sim.selection.clone()
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.sim.selection.clear();
+    }
+
+    // 位置のk-meansクラスタで色分けする（interval_frames ごとに再計算）
+    pub fn set_cluster_coloring(&mut self, k: u32, interval_frames: u32) {
+        self.sim.clustering = Some(KMeans::new(k, interval_frames));
+    }
+
+    pub fn disable_cluster_coloring(&mut self) {
+        self.sim.clustering = None;
+    }
+
+    // 直近のクラスタリングにかかった時間(ms)
+    pub fn get_last_cluster_ms(&self) -> f64 {
+        self.sim.clustering.as_ref().map_or(0.0, |kmeans| kmeans.last_run_ms)
+    }
+
+    // クラスタリングのような重い処理に1フレームで使ってよい時間(ms)。超える分は次のフレームに持ち越す
+    // 0 なら分けずに一度に計算する（既定。周期的なスパイクをそのまま測る）。実際に使う値を返す
+    pub fn set_frame_budget(&mut self, budget_ms: f64) -> f64 {
+        self.sim.scheduler.set_budget_ms(budget_ms)
+    }
+
+    pub fn get_scheduler_stats(&self) -> SchedulerStats {
+        self.sim.scheduler.stats()
+    }
+
+    pub fn reset_scheduler_stats(&mut self) {
+        self.sim.scheduler.reset_stats();
+    }
+
+    pub fn reset(&mut self) {
+        self.sim.charges.clear();
+        if let Some(scene) = &mut self.scene {
+            if scene.reset() {
+                return;
+            }
+            // 一度作れたシーンなので作り直しに失敗することはまずないが、失敗したら今のまま続ける
+            if let Ok(scene) = create_scene(scene.kind(), self.sim.width, self.sim.height) {
+                self.scene = scene;
+                self.apply_load();
+            }
+            return;
+        }
+
+        self.sim.reset();
+        self.vertices_packed = false;
+    }
+
+    // パーティクルの位置・速度・色相・寿命とフレーム数をバイト列に書き出す
+    // restore() に渡せば、どのバックエンドでも同じ途中の状態から続けられる（爆発の取り消しなどにも使える）
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.sim.snapshot()
+    }
+
+    // snapshot() の状態に戻す（パーティクル数も保存したときの数になる。シーンの状態は含まない）
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.sim.restore(snapshot::decode(bytes)?)?;
+        self.vertices_packed = false;
+        Ok(())
+    }
+
+    // (x, y) から radius 以内にあるパーティクルの数（座標はシミュレーションの px。カメラで動かした画面の位置とは違う）
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.sim.count_within(x, y, radius)
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス（パーティクルがなければ undefined）
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.sim.nearest(x, y).map(|index| index as u32)
+    }
+
+    // index 番目のパーティクルの位置・速度・色相・寿命（範囲外なら undefined）
+    pub fn get_particle(&self, index: u32) -> Option<ParticleInfo> {
+        self.sim.particle_info(index as usize)
+    }
+
+    // 以後の生成と reset() の乱数を seed から作り、パーティクルを作り直す
+    // 同じシードなら実行やバックエンドが違っても同じ初期状態から始まる
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.seed = Some(seed);
+        rng::with_seed(seed, || self.reset());
+    }
+
+    // 乱数を毎回違うものに戻す（今のパーティクルはそのまま）
+    pub fn clear_seed(&mut self) {
+        self.sim.seed = None;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.sim.seed
+    }
+
+    // クリックで爆発!
+    pub fn explode(&mut self, click_x: f32, click_y: f32) {
+        if let Some(scene) = &mut self.scene {
+            scene.explode(click_x, click_y, &self.explosion);
+            return;
+        }
+
+        self.sim.explode(click_x, click_y, &self.explosion);
+    }
+
+    // 同じ範囲・減衰で中心へ吸い寄せる
+    pub fn implode(&mut self, x: f32, y: f32) {
+        let implosion = self.explosion.inverted();
+        if let Some(scene) = &mut self.scene {
+            scene.explode(x, y, &implosion);
+            return;
+        }
+
+        self.sim.explode(x, y, &implosion);
+    }
+
+    // (x, y) の近くのパーティクルを (dx, dy) の向きに押し流す（シーンの描画中は何もしない）
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() {
+            self.sim.stir(x, y, dx, dy);
+        }
+    }
+
+    // (x, y) を (dx, dy)(px) 動いたポインターの勢いを、次の update() で近くのパーティクルに渡す
+    // 押し流しに加えて周りに渦ができる（シーンの描画中は何もしない）
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() {
+            self.sim.pointer.push_move(x, y, dx, dy);
+        }
+    }
+
+    // ドラッグ中のポインターの位置を渡す（動いた量は前に渡した位置から求め、strength 倍にする）
+    // strength が負なら渦の向きが逆になる
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        if self.scene.is_none() {
+            self.sim.pointer.push_drag(x, y, strength);
+        }
+    }
+
+    // delay_frames 回後の update() の最初に explode(x, y) する（0 ならすぐに爆発する）
+    // 仕掛けた時点の設定で爆発し、シーンを切り替えても残る（reset() で取り消す）
+    pub fn schedule_explosion(&mut self, x: f32, y: f32, delay_frames: u32) {
+        if delay_frames == 0 {
+            self.explode(x, y);
+            return;
+        }
+        self.sim.charges.schedule(x, y, delay_frames, self.explosion);
+    }
+
+    // まだ爆発していない schedule_explosion() の数
+    pub fn get_pending_explosions(&self) -> usize {
+        self.sim.charges.len()
+    }
+
+    // 引力点を置いて番号を返す。update() のたびに半径の中のパーティクルを中心へ引き寄せる
+    // （strength が負なら押しのける。マウスで引きずるときは move_attractor() で動かす）
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        self.sim.attractors.add(x, y, strength, radius)
+    }
+
+    // 見つからなければ false
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        self.sim.attractors.remove(id)
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        self.sim.attractors.move_to(id, x, y)
+    }
+
+    pub fn clear_attractors(&mut self) {
+        self.sim.attractors.clear();
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        self.sim.attractors.list()
+    }
+
+    // 中心 (x, y)・半径 radius の円の障害物を置く。update() のたびに当たったパーティクルを跳ね返す
+    // （跳ね返りの割合は壁と同じ bounce。NaN などや半径が 0 以下なら置かずに false）
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        self.sim.obstacles.add_circle(x, y, radius)
+    }
+
+    // (x0, y0)〜(x1, y1) の線分の障害物を置く（1ステップで横切るほど速いパーティクルも跳ね返す）
+    pub fn add_line_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        self.sim.obstacles.add_line(x0, y0, x1, y1)
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.sim.obstacles.clear();
+    }
+
+    pub fn get_obstacles(&self) -> Vec<Obstacle> {
+        self.sim.obstacles.list()
+    }
+
+    // 毎フレーム rate 個ずつパーティクルを出すエミッターを置いて番号を返す
+    // 出したパーティクルは寿命が減るにつれて薄くなり、尽きると同じ場所を使って出し直される
+    // （最初の1つを置くと、今いるパーティクルも順に尽きてエミッターのものに入れ替わる）
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        self.sim.add_emitter(x, y, rate, spread, speed)
+    }
+
+    // 見つからなければ false
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        self.sim.emitters.remove(id)
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.sim.emitters.clear();
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        self.sim.emitters.list()
+    }
+
+    // エミッターから出したパーティクルの累計
+    pub fn get_emitted_count(&self) -> f64 {
+        self.sim.emitters.emitted as f64
+    }
+
+    // 放出したパーティクルの寿命(フレーム)を設定し、実際に使う値を返す（1フレーム以上）
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        self.sim.emitters.set_lifetime(frames)
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        self.sim.emitters.lifetime()
+    }
+
+    // シミュレーションの領域の大きさを変える（シーン表示中はシーンを新しい大きさで作り直す）
+    // 縮めたときに外に出たパーティクルは set_out_of_bounds() の方法で扱う
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        if let Some(scene) = &self.scene {
+            self.scene = create_scene(scene.kind(), width, height)?;
+        }
+        self.sim.resize(width, height);
+        self.apply_backing_size();
+        self.vertices_packed = false;
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描画バッファを表示サイズの ratio 倍にする（HiDPI で滲まないように devicePixelRatio を渡す）
+    // シミュレーションの座標は変わらない。範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        let ratio = self.sizing.set_pixel_ratio(ratio);
+        self.apply_backing_size();
+        ratio
+    }
+
+    pub fn get_pixel_ratio(&self) -> f32 {
+        self.sizing.pixel_ratio()
+    }
+
+    // 描画バッファを pixel_ratio のさらに scale 倍にする（2 なら 4 倍の画素を描くスーパーサンプリング、
+    // 0.5 なら 1/4 の画素を描いて拡大表示）。表示の大きさとシミュレーションの座標は変わらない
+    // 範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        let scale = self.sizing.set_render_scale(scale);
+        self.apply_backing_size();
+        scale
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.sizing.render_scale()
+    }
+
+    // ResizeObserver でキャンバスの表示サイズと devicePixelRatio を監視し、変わったら次の render() で resize() する
+    // ページ上の canvas 要素に描いているときだけ使える
+    pub fn observe_resize(&mut self) -> Result<(), JsValue> {
+        if self.viewport.is_some() {
+            return Err(tr(Text::ResizeObserverUnavailable, &[]).into());
+        }
+        let canvas = self.gl.canvas().map(JsValue::from).ok_or_else(|| tr(Text::ResizeObserverUnavailable, &[]))?;
+        self.sizing.observe(&canvas)
+    }
+
+    pub fn unobserve_resize(&mut self) {
+        self.sizing.unobserve();
+    }
+
+    // キャンバスのポインター・ホイール・タッチのイベントを受け取り、次の update() で
+    // クリック（タップ）は explode()、ドラッグは stir()、ホイール・ピンチ・シフト＋ドラッグは視点の移動にする
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        let canvas = self.gl.canvas().map(JsValue::from).ok_or_else(|| tr(Text::InputUnavailable, &[]))?;
+        self.input = Some(InputHandlers::attach(&canvas)?);
+        Ok(())
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        self.input = None;
+    }
+
+    // バッファ・テクスチャ・フレームバッファを消して破棄する（以後このインスタンスは使えない）
+    // JS のガベージコレクションを待たずに GPU のメモリを返すので、インスタンスを作り直すときに呼ぶ
+    // 消す処理は Drop にあるので、free() で解放したときも同じように消える
+    pub fn dispose(self) {
+        drop(self);
+    }
+
+    // 視点を元に戻す（拡大率 1、平行移動なし）
+    pub fn reset_camera(&mut self) {
+        self.sim.camera = Camera::default();
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        self.sim.camera.zoom
+    }
+
+    // 色相の変化や選択範囲の重ね描きなど見た目だけの毎フレームの処理を止め、
+    // 物理演算と描画だけを計測する（設定のハッシュにも含まれるので通常の計測とは混ざらない）
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        self.sim.cosmetic = !enabled;
+    }
+
+    pub fn is_strict_benchmark(&self) -> bool {
+        !self.sim.cosmetic
+    }
+
+    // 検出して回避策を使った（または記録した）ブラウザ固有の問題の名前
+    pub fn get_quirks(&self) -> Vec<String> {
+        self.quirks.applied().iter().map(|quirk| quirk.name().to_string()).collect()
+    }
+
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.sim.out_of_bounds = policy;
+    }
+
+    pub fn get_out_of_bounds(&self) -> OutOfBounds {
+        self.sim.out_of_bounds
+    }
+
+    // 壁に届いたパーティクルを跳ね返すか・折り返すか・生成し直すか・そのまま出すか
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.sim.boundary = mode;
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        self.sim.boundary
+    }
+
+    // パーティクルに奥行きを持たせて透視投影で描くかを切り替えてシェーダーを作り直す（既定は Flat）
+    // 奥行きは頂点ごとの a_depth で送り、位置と点の大きさは頂点シェーダーで u_projection から求める
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) -> Result<(), JsValue> {
+        let perspective = mode == ProjectionMode::Perspective;
+        if perspective && self.depth_buffer.is_none() {
+            self.depth_buffer = Some(self.gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?);
+        }
+        let sized = self.sim.style().varies_size();
+        let (program, _) = particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Cpu, sized, perspective)?;
+        if let Some(gpu_colors) = &mut self.gpu_colors {
+            gpu_colors.set_program(particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Gpu, sized, perspective)?.0);
+        }
+        self.program = program;
+        self.sim.set_projection(mode);
+        Ok(())
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        self.sim.projection()
+    }
+
+    // explode() の半径・強さ・減衰曲線（シーンを切り替えても引き継ぐ）
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        self.explosion = *config;
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        self.explosion
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        self.sim.config = config.clamped();
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        self.sim.config
+    }
+
+    // デモのスライダー用に1項目ずつ変える
+    pub fn set_gravity(&mut self, gravity: f32) {
+        self.set_simulation_config(&SimulationConfig { gravity, ..self.sim.config });
+    }
+
+    pub fn set_bounce(&mut self, bounce: f32) {
+        self.set_simulation_config(&SimulationConfig { bounce, ..self.sim.config });
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        self.set_simulation_config(&SimulationConfig { friction, ..self.sim.config });
+    }
+
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.set_simulation_config(&SimulationConfig { point_size, ..self.sim.config });
+    }
+
+    // パーティクルの形を切り替えてシェーダーを作り直す（既定は四角形）
+    // Canvas2D と同じ見た目で比べるときは、両方に Circle か Sprite を指定する
+    pub fn set_particle_shape(&mut self, shape: ParticleShape) -> Result<(), JsValue> {
+        if shape == ParticleShape::Sprite && self.sprite_texture.is_none() {
+            self.sprite_texture = Some(gl_surface::create_sprite_texture(&self.gl)?);
+        }
+        let sized = self.sim.style().varies_size();
+        let perspective = self.perspective();
+        let (program, _) = particle_program(&self.gl, self.precision, shape, ColorPipeline::Cpu, sized, perspective)?;
+        if let Some(gpu_colors) = &mut self.gpu_colors {
+            gpu_colors.set_program(particle_program(&self.gl, self.precision, shape, ColorPipeline::Gpu, sized, perspective)?.0);
+        }
+        self.program = program;
+        self.shape = shape;
+        Ok(())
+    }
+
+    pub fn get_particle_shape(&self) -> ParticleShape {
+        self.shape
+    }
+
+    // パーティクルを image（HTMLImageElement か ImageBitmap）のスプライトで描く（形は Sprite になる）
+    // 画像はそのままの大きさでテクスチャに転送し、シェーダーで頂点の色を掛ける
+    pub fn set_particle_texture(&mut self, image: &JsValue) -> Result<(), JsValue> {
+        let image = DecodedImage::from_js(image).ok_or_else(|| tr(Text::ParticleTextureInvalid, &[]))?;
+        let texture = gl_surface::create_image_texture(&self.gl, &image)?;
+        if let Some(old) = self.sprite_texture.replace(texture) {
+            self.gl.delete_texture(Some(&old));
+        }
+        self.particle_texture = Some(image);
+        self.set_particle_shape(ParticleShape::Sprite)
+    }
+
+    // パーティクルごとの大きさと不透明度（既定はすべて 1）
+    // 大きさが変わる設定では点の大きさを頂点ごとに送るシェーダーに替え、不透明度は頂点の色に混ぜる
+    pub fn set_particle_style(&mut self, style: &ParticleStyle) -> Result<(), JsValue> {
+        let sized = style.sanitized().varies_size();
+        let perspective = self.perspective();
+        if sized && self.size_buffer.is_none() {
+            self.size_buffer = Some(self.gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?);
+        }
+        let (program, _) = particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Cpu, sized, perspective)?;
+        if let Some(gpu_colors) = &mut self.gpu_colors {
+            gpu_colors.set_program(particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Gpu, sized, perspective)?.0);
+        }
+        self.program = program;
+        self.sim.set_style(*style);
+        self.vertices_packed = false;
+        Ok(())
+    }
+
+    pub fn get_particle_style(&self) -> ParticleStyle {
+        self.sim.style()
+    }
+
+    // 組み込みの光の玉のスプライトに戻す（形は変えない）
+    pub fn clear_particle_texture(&mut self) -> Result<(), JsValue> {
+        if self.particle_texture.take().is_none() {
+            return Ok(());
+        }
+        if let Some(old) = self.sprite_texture.take() {
+            self.gl.delete_texture(Some(&old));
+        }
+        if self.shape == ParticleShape::Sprite {
+            self.sprite_texture = Some(gl_surface::create_sprite_texture(&self.gl)?);
+        }
+        Ok(())
+    }
+
+    // パーティクルの合成方法を切り替える（Particles シーンだけに効く）
+    // 既定の Normal では四角形は従来どおりブレンドせずに描く
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> Result<(), JsValue> {
+        if mode == RenderMode::Trails && self.trail_buffer.is_none() {
+            self.trail_buffer = Some(TrailBuffer::new(&self.gl)?);
+        }
+        self.render_mode = mode;
+        Ok(())
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    // 描くパーティクルを skip 個に1個に間引く（物理演算はすべてに行う。mode が Off か skip が 1 なら間引かない）
+    // シーンの表示中は間引かない
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        self.sim.lod.set_fixed(mode, skip);
+    }
+
+    // render() の時間が target_render_ms を超えないように、間引く間隔を AutoScaler と同じ探し方で決める
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        let particle_count = self.sim.particle_count;
+        self.sim.lod.set_adaptive(mode, target_render_ms, particle_count);
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        self.sim.lod.mode()
+    }
+
+    // 直前のフレームで何個に1個描いたか（間引いていなければ 1）
+    pub fn get_lod_skip(&self) -> u32 {
+        self.sim.visible_skip()
+    }
+
+    // 直前のフレームで描いたパーティクル数
+    pub fn get_visible_particle_count(&self) -> usize {
+        self.sim.visible().len()
+    }
+
+    // WebGL は元から全部を1回の描画命令で描くので、描き方は Batched だけ
+    pub fn set_draw_strategy(&mut self, strategy: DrawStrategy) -> Result<(), JsValue> {
+        if strategy != DrawStrategy::Batched {
+            return Err(tr(Text::DrawStrategyUnavailable, &[&strategy.name(), &"webgl"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_draw_strategy(&self) -> DrawStrategy {
+        DrawStrategy::Batched
+    }
+
+    // 頂点データの送り方を切り替える（既定は BufferData）
+    // 送るのにかかった時間は get_metrics() の upload_ms に出るので、切り替えて比べる
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) -> Result<(), JsValue> {
+        self.buffers.set_strategy(&self.gl, strategy, self.sim.max_particles())
+    }
+
+    pub fn get_upload_strategy(&self) -> UploadStrategy {
+        self.buffers.strategy()
+    }
+
+    // 名前の付いた設定の組（合成方法・点の大きさ・描画解像度・精度・送り方・間引き・fps の上限）をまとめて当てる
+    // 組の名前は get_config_fingerprint() に入るので、同じ組で測った結果同士を比べられる
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        let tuning = profile.settings(BackendKind::WebGl);
+        self.set_shader_precision(tuning.precision)?;
+        self.set_upload_strategy(tuning.upload_strategy)?;
+        self.set_draw_strategy(tuning.draw_strategy)?;
+        self.set_render_mode(tuning.render_mode)?;
+        self.set_point_size(tuning.point_size);
+        self.set_render_scale(tuning.render_scale);
+        match tuning.adaptive_lod {
+            Some((mode, target_render_ms)) => self.set_adaptive_lod(mode, target_render_ms),
+            None => self.set_lod(LodMode::Off, 1),
+        }
+        self.set_target_fps(tuning.target_fps);
+        self.tuning = Some(profile);
+        Ok(())
+    }
+
+    // 最後に apply_tuning_profile() で当てた組（当てていなければ undefined）
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        self.tuning
+    }
+
+    // 位置と色を別々のバッファに送るか、1つのバッファに交互に並べて送るかを切り替える（既定は Planar）
+    pub fn set_buffer_layout(&mut self, layout: BufferLayout) -> Result<(), JsValue> {
+        self.buffers.set_layout(&self.gl, layout, self.sim.max_particles())
+    }
+
+    pub fn get_buffer_layout(&self) -> BufferLayout {
+        self.buffers.layout()
+    }
+
+    // パーティクルの色を CPU と頂点シェーダーのどちらで計算するかを切り替える（既定は Cpu）
+    // Gpu では毎フレームの色相から RGB への変換と色の送信がなくなるので、その分が CPU の色の計算のコストになる
+    // シェーダーは虹色しか描けないので、set_particle_color_mode() が RainbowCycle のときだけ Gpu にできる
+    pub fn set_color_mode(&mut self, mode: ColorPipeline) -> Result<(), JsValue> {
+        self.gpu_colors = match mode {
+            ColorPipeline::Cpu => None,
+            ColorPipeline::Gpu if !self.sim.theme.is_rainbow() => {
+                return Err(tr(Text::ColorModeConflict, &[&self.sim.theme.mode().name(), &"the gpu color pipeline"]).into());
+            }
+            ColorPipeline::Gpu => {
+                let (program, _) = particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Gpu, self.sim.style().varies_size(), self.perspective())?;
+                Some(GpuColors::new(&self.gl, program)?)
+            }
+        };
+        Ok(())
+    }
+
+    // transform feedback は WebGL2 にしかないので Cpu だけ
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> Result<(), JsValue> {
+        if mode != PhysicsMode::Cpu {
+            return Err(tr(Text::PhysicsModeUnavailable, &[&mode.name(), &"webgl"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_physics_mode(&self) -> PhysicsMode {
+        PhysicsMode::Cpu
+    }
+
+    pub fn get_color_mode(&self) -> ColorPipeline {
+        match self.gpu_colors {
+            Some(_) => ColorPipeline::Gpu,
+            None => ColorPipeline::Cpu,
+        }
+    }
+
+    // パーティクルの塗り方を切り替える（既定は RainbowCycle）
+    // colors は Palette と Monochrome の色を 0〜1 の r, g, b の順に並べたもの（ほかのモードでは空でよい）
+    // set_color_mode(Gpu) の間は RainbowCycle だけ
+    pub fn set_particle_color_mode(&mut self, mode: ColorMode, colors: Vec<f32>) -> Result<(), JsValue> {
+        if self.gpu_colors.is_some() && mode != ColorMode::RainbowCycle {
+            return Err(tr(Text::ColorModeConflict, &[&mode.name(), &"the gpu color pipeline"]).into());
+        }
+        self.sim.theme.set_mode(mode, &colors)?;
+        self.vertices_packed = false;
+        Ok(())
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        self.sim.theme.mode()
+    }
+
+    // 背景色を 0〜1 の RGB で設定する（有限でない値を渡すと既定の灰色に戻す）
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.sim.theme.set_background(r, g, b);
+        self.vertices_packed = false;
+    }
+
+    // [r, g, b]
+    pub fn get_background_color(&self) -> Vec<f32> {
+        self.sim.theme.background().to_vec()
+    }
+
+    // update() と爆発の計算を threads 個に分けて並列に行う（threads フィーチャーが必要）
+    // 実際に使う数を返す。フィーチャーなしでは1、ありでもプールのスレッド数までに丸める
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        let applied = self.sim.set_threads(threads);
+        if applied != threads {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ThreadCountClamped, &[&threads, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.sim.threads()
+    }
+
+    // 物理演算と色の変換を SIMD 版（4個ずつ）にするか切り替える。実際に使うかを返す
+    // simd フィーチャーなしでビルドしたときはスカラー版のまま
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        let applied = self.sim.set_simd(enabled);
+        if applied != enabled {
+            self.events
+                .emit(EventKind::FallbackUsed, &tr(Text::SimdUnavailable, &[]));
+        }
+        applied
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        self.sim.simd()
+    }
+
+    // パーティクル同士の衝突を切り替える（格子で近いものだけ調べるが、壁だけのときよりずっと重い）
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        self.sim.set_collisions(enabled);
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        self.sim.collisions()
+    }
+
+    // 全体に一様に吹く風（毎フレーム速度に足す px/フレーム²。重力と同じ単位）。(0, 0) で止める
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        self.sim.forces.set_wind(x, y);
+    }
+
+    // [x, y]
+    pub fn get_wind(&self) -> Vec<f32> {
+        let (x, y) = self.sim.forces.wind();
+        vec![x, y]
+    }
+
+    // パーティクルごとに curl ノイズの流れを加える（strength が 0 なら止める）
+    // scale は渦のおおよその大きさ(px)。場はゆっくり流れるので同じ場所でも向きが変わっていく
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        self.sim.forces.set_turbulence(strength, scale);
+    }
+
+    // [strength, scale]（止めていれば None）
+    pub fn get_turbulence(&self) -> Option<Vec<f32>> {
+        self.sim
+            .forces
+            .turbulence()
+            .map(|(strength, scale)| vec![strength, scale])
+    }
+
+    // 直近の update() で重なっていたパーティクルの組の数
+    pub fn get_contact_count(&self) -> usize {
+        self.sim.last_contacts()
+    }
+
+    // パーティクルのシェーダーの精度を切り替えて作り直す（作れなければ今のまま）
+    pub fn set_shader_precision(&mut self, precision: ShaderPrecision) -> Result<(), JsValue> {
+        let sized = self.sim.style().varies_size();
+        let perspective = self.perspective();
+        let (program, _) = particle_program(&self.gl, precision, self.shape, ColorPipeline::Cpu, sized, perspective)?;
+        if let Some(gpu_colors) = &mut self.gpu_colors {
+            gpu_colors.set_program(particle_program(&self.gl, precision, self.shape, ColorPipeline::Gpu, sized, perspective)?.0);
+        }
+        self.program = program;
+        self.precision = precision;
+        Ok(())
+    }
+
+    pub fn get_shader_precision(&self) -> ShaderPrecision {
+        self.precision
+    }
+
+    // mediump と highp で同じ状態から frames フレーム描き、速さと最後のフレームの違いを比べる
+    // Particles シーンで使う（終わると元の精度に戻り、パーティクルは初期状態からやり直しになる）
+    pub fn compare_shader_precision(&mut self, frames: u32) -> Result<PrecisionComparison, JsValue> {
+        let original = self.precision;
+        let frames = frames.max(1);
+        let mediump = self.run_precision(ShaderPrecision::Mediump, frames);
+        let highp = self.run_precision(ShaderPrecision::Highp, frames).ok();
+        self.set_shader_precision(original)?;
+        Ok(PrecisionComparison::new(frames, &mediump?, highp.as_ref()))
+    }
+}
+
+impl ParticleSystem {
+    fn run_precision(&mut self, precision: ShaderPrecision, frames: u32) -> Result<PrecisionRun, JsValue> {
+        self.set_shader_precision(precision)?;
+        rng::with_seed(shader::PRECISION_SEED, || self.reset());
+        let mut total_ms = 0.0;
+        for _ in 0..frames {
+            self.update();
+            let start = timing::now_ms();
+            self.render();
+            self.gl.finish();
+            total_ms += timing::now_ms() - start;
+        }
+        Ok(PrecisionRun {
+            mean_ms: total_ms / frames as f64,
+            pixels: shader::read_drawing_buffer(&self.gl)?,
+        })
+    }
+
+    pub(crate) fn emit_event(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    // 出した描画命令が終わるまで待つ（待てたら true）
+    pub(crate) fn finish_gpu(&self) -> bool {
+        self.gl.finish();
+        true
+    }
+
+    // attach_input_handlers() で積まれた操作を取り出す（視点の操作はここで反映する）
+    pub(crate) fn take_input(&mut self) -> Vec<InputAction> {
+        match &self.input {
+            Some(input) => input.take_actions(&mut self.sim.camera),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn input_handlers(&self) -> Option<&InputHandlers> {
+        self.input.as_ref()
+    }
+
+    // 描いたフレームの上に rects を重ねる（領域分割しているときは描かない）
+    pub(crate) fn draw_overlay(&mut self, rects: &[OverlayRect]) -> bool {
+        if self.viewport.is_some() {
+            return false;
+        }
+        panel::draw_gl(&self.gl, rects, self.sizing.backing_ratio());
+        viewport::apply_gl(&self.gl, self.viewport);
+        true
+    }
+
+    fn apply_input(&mut self) {
+        for action in self.take_input() {
+            match action {
+                InputAction::Explode { x, y } => self.explode(x, y),
+                InputAction::Stir { x, y, dx, dy } => self.stir(x, y, dx, dy),
+            }
+        }
+    }
+
+    pub(crate) fn advance_timestep(&mut self, dt_ms: f64) -> u32 {
+        self.sim.timestep.advance(dt_ms)
+    }
+
+    pub(crate) fn cap_frame(&mut self, timestamp: f64) -> bool {
+        self.sim.frame_cap.tick(timestamp)
+    }
+
+    // update_with_dt() のステップの後で、描く位置を補間する（シーン表示中は何もしない）
+    pub(crate) fn interpolate_frame(&mut self) {
+        if self.scene.is_none() {
+            self.sim.interpolate();
+            // Interleaved で update() 中に詰めた頂点は補間前の位置なので詰め直す
+            self.vertices_packed = false;
+        }
+    }
+
+    // 今のシーンがスプライトを描くアトラス（画像の渡し方を切り替えるのに使う）
+    pub(crate) fn scene_atlas_mut(&mut self) -> Option<&mut Atlas> {
+        self.scene.as_mut()?.atlas_mut()
+    }
+
+    // シミュレーション空間の大きさ（記録する座標を割合に直すのに使う）
+    pub(crate) fn size(&self) -> (f32, f32) {
+        (self.sim.width, self.sim.height)
+    }
+
+    fn step_frame(&mut self) {
+        if let Some(scene) = &mut self.scene {
+            // パーティクルのシミュレーションは止まっているので、仕掛けた爆発はここで数える
+            for charge in self.sim.charges.tick() {
+                scene.explode(charge.x, charge.y, &charge.explosion);
+            }
+            scene.update();
+            return;
+        }
+
+        // 間引いて描くときは描くものだけを render() で詰める
+        let interleaved = self.schedule == ScheduleMode::Interleaved && self.sim.lod.skip() == 1;
+        if interleaved {
+            self.positions.clear();
+            self.colors.clear();
+        }
+
+        let (width, height, camera) = (self.sim.width, self.sim.height, self.sim.camera);
+        // ステップの間は sim を借りられないので、詰めるときだけ塗り方を写しておく
+        let theme = interleaved.then(|| self.sim.theme.clone());
+        let positions = &mut self.positions;
+        let colors = &mut self.colors;
+        self.sim.step_with(|p| {
+            if let Some(theme) = &theme {
+                pack_vertex(p, width, height, camera, theme, positions, colors);
+            }
+        });
+        if interleaved {
+            fade_colors(self.sim.particles(), &self.sim.style(), self.sim.frame_count, self.sim.theme.background(), &mut self.colors);
+        }
+
+        self.vertices_packed = interleaved;
+    }
+
+    // 領域分割なしのときだけ、キャンバスの描画バッファをシミュレーションの大きさに合わせる
+    fn apply_backing_size(&self) {
+        if let (None, Some(canvas)) = (self.viewport, self.gl.canvas()) {
+            self.sizing.apply(&canvas, self.sim.width, self.sim.height);
+        }
+    }
+
+    fn render_frame(&mut self) {
+        if let Some(watch) = &self.context_loss {
+            self.metrics.record_context_losses(watch.losses());
+            if watch.take_restored() {
+                self.restore_context();
+            }
+        }
+        if self.gl.is_context_lost() {
+            self.events.emit(EventKind::ContextLost, &tr(Text::ContextLost, &[]));
+            return;
+        }
+
+        if let Some(message) = self.quirks.note_frame() {
+            self.events.emit(EventKind::QuirkApplied, &message);
+        }
+        let frame_count = self.get_frame_count();
+        self.stats_stream.note_frame(frame_count);
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.note_frame() {
+            self.telemetry.send(Some(self.metrics.snapshot()), "webgl", self.get_particle_count());
+        }
+        self.observers.note_frame(frame_count);
+        self.timeline.note_frame(frame_count);
+
+        // コンテキストを共有している他のインスタンスに状態を変えられていても描けるようにする
+        self.gl_state.reset(&self.gl);
+        self.gl.use_program(Some(&self.program));
+        viewport::apply_gl(&self.gl, self.viewport);
+
+        if let (Some(scene), Some(resources)) = (&mut self.scene, &mut self.scene_gl) {
+            let mut surface = GlSurface {
+                gl: &self.gl,
+                width: self.sim.width,
+                height: self.sim.height,
+                resources,
+                events: &mut self.events,
+                saved_viewport: None,
+            };
+            scene.render(&mut surface);
+            return;
+        }
+
+        let gl = &self.gl;
+
+        // 画面クリア（Trails は描き溜めたテクスチャに向けて、前のフレームを薄めるだけ）
+        let trails = self.render_mode == RenderMode::Trails;
+        let background = self.sim.theme.background();
+        match self.trail_buffer.as_mut().filter(|_| trails) {
+            Some(buffer) => {
+                buffer.begin(gl, background);
+                gl.use_program(Some(&self.program));
+            }
+            None => {
+                gl.clear_color(background[0], background[1], background[2], 1.0);
+                gl.clear(WebGlRenderingContext::COLOR_BUFFER_BIT);
+            }
+        }
+
+        // 位置データを準備 (100,000個分!)
+        // Interleaved モードでは update() で詰め終わっているのでスキップ
+        let sequence_begin = self.sim.sequence();
+        self.sim.prepare_visible();
+        if !self.vertices_packed {
+            match self.gpu_colors {
+                // 色はシェーダーで計算するので位置だけ詰める
+                Some(_) => pack_positions(self.sim.visible(), self.sim.width, self.sim.height, self.sim.camera, &mut self.positions),
+                None => {
+                    pack_vertices(&self.sim, &mut self.positions, &mut self.colors);
+                    fade_colors(self.sim.visible(), &self.sim.style(), self.sim.frame_count, background, &mut self.colors);
+                }
+            }
+        }
+        self.read_stamps.record(sequence_begin, self.sim.sequence());
+        self.init.mark_frame(self.is_initialized());
+        let positions = &self.positions;
+        let colors = &self.colors;
+
+        // 位置と色のバッファにデータを送る（送り方は set_upload_strategy() で選ぶ）
+        let program = match &self.gpu_colors {
+            Some(gpu_colors) => {
+                gl.use_program(Some(&gpu_colors.program));
+                gpu_colors.program.clone()
+            }
+            None => self.program.clone(),
+        };
+        let position_attrib = gl.get_attrib_location(&program, "a_position") as u32;
+        let upload_start = timing::now_ms();
+        match &mut self.gpu_colors {
+            Some(gpu_colors) => {
+                self.buffers.upload_positions(gl, positions, position_attrib);
+                gpu_colors.bind(gl, &self.sim);
+            }
+            None => {
+                let color_attrib = gl.get_attrib_location(&program, "a_color") as u32;
+                self.buffers.upload(gl, positions, colors, position_attrib, color_attrib);
+            }
+        }
+        self.metrics.record_upload(upload_start);
+
+        // ポイントサイズを設定（WebGLは直径、Canvas2Dは半径なので2倍）
+        let point_size_location = gl.get_uniform_location(&program, "u_pointSize");
+        gl.uniform1f(point_size_location.as_ref(), self.quirks.point_size(self.sim.config.point_diameter() * self.sim.camera.zoom * self.sizing.backing_ratio()));
+        if self.shape == ParticleShape::Sprite {
+            gl.active_texture(WebGlRenderingContext::TEXTURE0);
+            gl.bind_texture(WebGlRenderingContext::TEXTURE_2D, self.sprite_texture.as_ref());
+        }
+        // 点ごとの大きさの倍率（大きさが変わる設定のときだけ a_size がある）
+        let size_attrib = gl.get_attrib_location(&program, "a_size");
+        if let (Ok(size_attrib), Some(buffer)) = (u32::try_from(size_attrib), &self.size_buffer) {
+            self.sim.style().pack_sizes(self.sim.visible(), self.sim.frame_count, &mut self.point_sizes);
+            gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+            unsafe {
+                let array = js_sys::Float32Array::view(&self.point_sizes);
+                gl.buffer_data_with_array_buffer_view(WebGlRenderingContext::ARRAY_BUFFER, &array, WebGlRenderingContext::STREAM_DRAW);
+            }
+            gl.vertex_attrib_pointer_with_i32(size_attrib, 1, WebGlRenderingContext::FLOAT, false, 0, 0);
+            gl.enable_vertex_attrib_array(size_attrib);
+        }
+        if self.perspective() {
+            let depth = self.sim.depth();
+            self.depths.clear();
+            self.depths.extend(self.sim.visible().z.iter().map(|&z| z / depth));
+            self.upload_depths(&program);
+        }
+
+        // 描画! (GPUが一瞬で10万個を描画)
+        let count = (self.positions.len() / 2) as i32;
+        match self.split_compare {
+            None if self.shape == ParticleShape::Square && self.render_mode == RenderMode::Normal => {
+                gl.draw_arrays(WebGlRenderingContext::POINTS, 0, count)
+            }
+            None => {
+                // 円とスプライトは縁が半透明なので、Normal でも Canvas2D と同じくアルファブレンドで重ねる
+                self.render_mode.apply_gl(gl);
+                gl.draw_arrays(WebGlRenderingContext::POINTS, 0, count);
+                gl.disable(WebGlRenderingContext::BLEND);
+            }
+            Some((mode_a, mode_b)) => {
+                // 同じ頂点データを左右で別の合成方法で描く
+                let region = self.viewport.unwrap_or(Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: gl.drawing_buffer_width() as f32,
+                    height: gl.drawing_buffer_height() as f32,
+                });
+                let (left, right) = region.split_halves();
+                for (mode, half) in [(mode_a, left), (mode_b, right)] {
+                    viewport::scissor_gl(gl, half);
+                    mode.apply_gl(gl);
+                    gl.draw_arrays(WebGlRenderingContext::POINTS, 0, count);
+                }
+                gl.disable(WebGlRenderingContext::BLEND);
+            }
+        }
+        // 選択の強調と障害物は同じプログラムで倍率 1 の点として描く
+        if let Ok(size_attrib) = u32::try_from(size_attrib) {
+            gl.disable_vertex_attrib_array(size_attrib);
+            gl.vertex_attrib1f(size_attrib, 1.0);
+        }
+        // 選択の強調は残像に残さないよう、画面に写してから重ねる
+        if let Some(buffer) = self.trail_buffer.as_ref().filter(|_| trails) {
+            buffer.end(gl);
+            gl.use_program(Some(&self.program));
+        }
+
+        if !self.sim.selection.is_empty() && self.sim.cosmetic {
+            self.render_selection();
+        }
+        if !self.sim.obstacles.is_empty() {
+            self.render_obstacles();
+        }
+    }
+
+    // 戻ったコンテキストにシェーダー・バッファ・テクスチャを作り直す（失われる前の設定のまま続ける）
+    fn restore_context(&mut self) {
+        match self.restore_gl() {
+            Ok(()) => {
+                // 拡張機能の有無なども作り直したコンテキストについて改めて知らせる
+                self.events.clear_reported();
+                self.events.emit(EventKind::ContextRestored, &tr(Text::ContextRestored, &[]));
+            }
+            Err(error) => self.events.emit(
+                EventKind::ContextLost,
+                &tr(Text::ContextRestoreFailed, &[&describe_error(&error)]),
+            ),
+        }
+    }
+
+    fn restore_gl(&mut self) -> Result<(), JsValue> {
+        let sized = self.sim.style().varies_size();
+        let perspective = self.perspective();
+        self.program = particle_program(&self.gl, self.precision, self.shape, ColorPipeline::Cpu, sized, perspective)?.0;
+        let (strategy, layout) = (self.buffers.strategy(), self.buffers.layout());
+        self.buffers = VertexBuffers::new(&self.gl)?;
+        self.buffers.set_strategy(&self.gl, strategy, self.sim.max_particles())?;
+        self.buffers.set_layout(&self.gl, layout, self.sim.max_particles())?;
+        if self.gpu_colors.take().is_some() {
+            self.set_color_mode(ColorPipeline::Gpu)?;
+        }
+        self.sprite_texture = match (&self.particle_texture, self.shape) {
+            (Some(image), _) => Some(gl_surface::create_image_texture(&self.gl, image)?),
+            (None, ParticleShape::Sprite) => Some(gl_surface::create_sprite_texture(&self.gl)?),
+            (None, _) => None,
+        };
+        self.size_buffer = match sized {
+            true => Some(self.gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?),
+            false => None,
+        };
+        self.depth_buffer = match perspective {
+            true => Some(self.gl.create_buffer().ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?),
+            false => None,
+        };
+        self.trail_buffer = match self.render_mode {
+            RenderMode::Trails => Some(TrailBuffer::new(&self.gl)?),
+            _ => None,
+        };
+        self.scene_gl = None;
+        if self.scene.is_some() {
+            self.ensure_scene_gl()?;
+        }
+        self.vertices_packed = false;
+        Ok(())
+    }
+
+    // シーン用のGLリソースは最初に必要になったときに作る
+    fn ensure_scene_gl(&mut self) -> Result<(), JsValue> {
+        if self.scene_gl.is_some() {
+            return Ok(());
+        }
+        let resources = SceneGl::new(&self.gl)?;
+        if !resources.has_instancing() {
+            self.events.emit(
+                EventKind::ExtensionMissing,
+                &tr(Text::InstancingUnavailable, &[]),
+            );
+        }
+        self.scene_gl = Some(resources);
+        Ok(())
+    }
+
+    fn config_fingerprint(&self) -> ConfigFingerprint {
+        let mut config = ConfigFingerprint::new("webgl");
+        config.field("strict", self.strict);
+        config.field("width", self.sim.width);
+        config.field("height", self.sim.height);
+        config.field("render_scale", self.sizing.render_scale());
+        config.field("out_of_bounds", self.sim.out_of_bounds.name());
+        config.field("boundary_mode", self.sim.boundary.name());
+        config.field("particle_color_mode", self.sim.theme.mode().name());
+        config.field("projection_mode", self.sim.projection().name());
+        config.field("gravity", self.sim.config.gravity);
+        config.field("bounce", self.sim.config.bounce);
+        config.field("friction", self.sim.config.friction);
+        config.field("point_size", self.sim.config.point_size);
+        config.field("threads", self.sim.threads());
+        config.field("simd", self.sim.simd());
+        config.field("collisions", self.sim.collisions());
+        config.field("attractors", self.sim.attractors.len());
+        config.field("forces", self.sim.forces.describe());
+        config.field("obstacles", self.sim.obstacles.len());
+        config.field("emitters", self.sim.emitters.len());
+        config.field("particle_lifetime", self.sim.emitters.lifetime());
+        config.field("strict_benchmark", !self.sim.cosmetic);
+        config.field("shader_precision", self.precision.name());
+        config.field("particle_shape", self.shape.name());
+        config.field("particle_texture", particle_shape::describe_texture(self.particle_texture.as_ref().map(DecodedImage::size)));
+        config.field("particle_style", self.sim.style().describe());
+        config.field("render_mode", self.render_mode.name());
+        config.optional("tuning_profile", self.tuning.map(TuningProfile::name));
+        config.field("upload_strategy", self.buffers.strategy().name());
+        config.field("buffer_layout", self.buffers.layout().name());
+        config.field("color_mode", self.get_color_mode().name());
+        config.field("quirks", self.quirks.fingerprint());
+        config.optional("settle_threshold", self.sim.settle.map(|settle| settle.threshold));
+        config.optional("seed", self.sim.seed);
+        config.optional(
+            "viewport",
+            self.viewport
+                .map(|v| format!("{},{},{},{}", v.x, v.y, v.width, v.height)),
+        );
+        config.field("scene", self.get_scene().name());
+        config.optional("load", self.load);
+        config.field("max_particles", self.sim.max_particles());
+        config.field("lod", self.sim.lod.describe());
+        config.field("particles", self.sim.particle_count);
+        config.field("double_buffered", self.sim.is_double_buffered());
+        config.optional(
+            "split_compare",
+            self.split_compare
+                .map(|(a, b)| format!("{}|{}", a.name(), b.name())),
+        );
+        config.optional(
+            "clustering",
+            self.sim.clustering.as_ref().map(|kmeans| {
+                let (k, interval) = kmeans.settings();
+                format!("{}/{}", k, interval)
+            }),
+        );
+        config.optional("frame_budget", self.sim.scheduler.budget_ms());
+        config.field("schedule", format!("{:?}", self.schedule));
+        config.optional(
+            "instancing",
+            self.scene_gl.as_ref().map(|resources| resources.has_instancing()),
+        );
+        config
+    }
+
+    fn apply_load(&mut self) {
+        let Some(load) = self.load else {
+            return;
+        };
+        match &mut self.scene {
+            Some(scene) => scene.set_load(load),
+            None => {
+                self.sim.set_load(load);
+                self.vertices_packed = false;
+            }
+        }
+    }
+
+    // 選択中のパーティクルを大きめの白い点で重ね描きする
+    // 頂点データは描画済みなので同じバッファを上書きして使う
+    fn render_selection(&mut self) {
+        let gl = &self.gl;
+        // 色をシェーダーで計算しているときも、強調は決まった色で描く
+        gl.use_program(Some(&self.program));
+        // A/B比較でシザーが半分に絞られている場合があるので戻す
+        viewport::apply_gl(gl, self.viewport);
+        let particles = self.sim.rendered();
+        let depth = self.sim.depth();
+        self.positions.clear();
+        self.colors.clear();
+        self.depths.clear();
+        for &i in &self.sim.selection {
+            if let Some(p) = particles.get(i as usize) {
+                let (x, y) = self.sim.camera.to_screen(p.x, p.y);
+                self.positions.push((x / self.sim.width) * 2.0 - 1.0);
+                self.positions.push(1.0 - (y / self.sim.height) * 2.0);
+                self.colors.extend_from_slice(&selection::HIGHLIGHT_RGB);
+                self.depths.push(particles.z[i as usize] / depth);
+            }
+        }
+        // 次の render() で通常の頂点データを詰め直す
+        self.vertices_packed = false;
+
+        let position_attrib = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_attrib = gl.get_attrib_location(&self.program, "a_color") as u32;
+        self.buffers.upload(gl, &self.positions, &self.colors, position_attrib, color_attrib);
+        self.upload_depths(&self.program);
+
+        let point_size_location = gl.get_uniform_location(&self.program, "u_pointSize");
+        let highlight_size =
+            self.sim.config.point_diameter() * selection::HIGHLIGHT_SCALE * self.sim.camera.zoom * self.sizing.backing_ratio();
+        gl.uniform1f(point_size_location.as_ref(), self.quirks.point_size(highlight_size));
+        gl.draw_arrays(WebGlRenderingContext::POINTS, 0, (self.positions.len() / 2) as i32);
+    }
+
+    // 透視投影のとき、self.depths を a_depth に送り、u_projection を設定する（Flat では何もしない）
+    fn upload_depths(&self, program: &WebGlProgram) {
+        let gl = &self.gl;
+        let depth_attrib = gl.get_attrib_location(program, "a_depth");
+        let (Ok(depth_attrib), Some(buffer)) = (u32::try_from(depth_attrib), &self.depth_buffer) else {
+            return;
+        };
+        gl.bind_buffer(WebGlRenderingContext::ARRAY_BUFFER, Some(buffer));
+        unsafe {
+            let array = js_sys::Float32Array::view(&self.depths);
+            gl.buffer_data_with_array_buffer_view(WebGlRenderingContext::ARRAY_BUFFER, &array, WebGlRenderingContext::STREAM_DRAW);
+        }
+        gl.vertex_attrib_pointer_with_i32(depth_attrib, 1, WebGlRenderingContext::FLOAT, false, 0, 0);
+        gl.enable_vertex_attrib_array(depth_attrib);
+        let projection_location = gl.get_uniform_location(program, "u_projection");
+        gl.uniform_matrix4fv_with_f32_array(projection_location.as_ref(), false, &projection::perspective_matrix());
+    }
+
+    fn perspective(&self) -> bool {
+        self.sim.projection() == ProjectionMode::Perspective
+    }
+
+    // 障害物の輪郭をパーティクルと同じ大きさの点を並べて描く
+    fn render_obstacles(&mut self) {
+        let gl = &self.gl;
+        gl.use_program(Some(&self.program));
+        viewport::apply_gl(gl, self.viewport);
+        self.positions.clear();
+        self.colors.clear();
+        let diameter = self.sim.config.point_diameter();
+        self.sim.obstacles.pack_outline(diameter, (self.sim.width, self.sim.height), self.sim.camera, &mut self.positions, &mut self.colors);
+        self.vertices_packed = false;
+        // 障害物は奥行きによらないので、輪郭は手前の面に描く
+        self.depths.clear();
+        self.depths.resize(self.positions.len() / 2, 0.0);
+
+        let position_attrib = gl.get_attrib_location(&self.program, "a_position") as u32;
+        let color_attrib = gl.get_attrib_location(&self.program, "a_color") as u32;
+        self.buffers.upload(gl, &self.positions, &self.colors, position_attrib, color_attrib);
+        self.upload_depths(&self.program);
+
+        let point_size_location = gl.get_uniform_location(&self.program, "u_pointSize");
+        gl.uniform1f(point_size_location.as_ref(), self.quirks.point_size(diameter * self.sim.camera.zoom * self.sizing.backing_ratio()));
+        gl.draw_arrays(WebGlRenderingContext::POINTS, 0, (self.positions.len() / 2) as i32);
+    }
+
+    pub(crate) fn from_source(
+        source: &impl ContextSource<WebGlRenderingContext>,
+        particle_count: usize,
+        options: BuildOptions,
+    ) -> Result<ParticleSystem, JsValue> {
+        check_budget(particle_count, options.memory_budget)?;
+        let mut init = InitProgress::start();
+
+        let AcquiredContext {
+            context: gl,
+            width,
+            height,
+        } = source.acquire()?;
+        if options.strict {
+            require_extensions(&gl)?;
+        }
+        let simulation = options.simulation.clamped();
+        let quirks = Quirks::detect_webgl(&gl, simulation.point_diameter() * selection::HIGHLIGHT_SCALE);
+        let context_loss = gl.canvas().and_then(|canvas| ContextLossWatch::attach(&canvas).ok());
+        init.timings.context_ms = init.lap();
+
+        // シェーダーをコンパイル（同じコンテキスト・同じソースならキャッシュを再利用）
+        let (program, cache_hit) = particle_program(&gl, ShaderPrecision::default(), ParticleShape::Square, ColorPipeline::Cpu, false, false)?;
+        init.timings.shader_cache_hits += cache_hit as u32;
+        gl.use_program(Some(&program));
+        init.timings.shader_ms = init.lap();
+
+        // バッファを作成
+        let buffers = VertexBuffers::new(&gl)?;
+        let positions = try_vec(particle_count * 2)?;
+        let colors = try_vec(particle_count * 3)?;
+        init.timings.buffers_ms = init.lap();
+
+        // パーティクルを生成
+        // ビューポート指定時はその領域がシミュレーション空間になる
+        let (width, height) = match options.viewport {
+            Some(v) => (v.width, v.height),
+            None => (width, height),
+        };
+        let mut sim = Simulation::with_capacity(width, height, particle_count)?;
+        sim.seed = options.seed;
+        if !options.progressive {
+            sim.spawn_pending(particle_count);
+        }
+        sim.config = simulation;
+        sim.memory_budget = options.memory_budget;
+        init.timings.particles_ms = init.lap();
+        init.finish_constructor();
+
+        shader::retain_context(&gl);
+        let mut system = ParticleSystem {
+            sim,
+            gl_state: GlState::new(&gl),
+            gl,
+            program,
+            buffers,
+            gpu_colors: None,
+            read_stamps: ReadStamps::default(),
+            init,
+            scene: None,
+            scene_gl: None,
+            viewport: options.viewport,
+            split_compare: None,
+            schedule: ScheduleMode::Split,
+            positions,
+            colors,
+            vertices_packed: false,
+            load: None,
+            events: EventBus::default(),
+            strict: options.strict,
+            explosion: ExplosionConfig::default(),
+            precision: ShaderPrecision::default(),
+            shape: ParticleShape::Square,
+            sprite_texture: None,
+            particle_texture: None,
+            size_buffer: None,
+            point_sizes: Vec::new(),
+            depth_buffer: None,
+            depths: Vec::new(),
+            render_mode: RenderMode::Normal,
+            trail_buffer: None,
+            quirks,
+            stats_stream: StatsStream::default(),
+            #[cfg(feature = "telemetry")]
+            telemetry: Telemetry::default(),
+            observers: Observers::default(),
+            timeline: Timeline::default(),
+            tuning: None,
+            metrics: MetricsCollector::default(),
+            sizing: CanvasSizing::default(),
+            input: None,
+            context_loss,
+        };
+        for message in system.quirks.messages() {
+            system.events.emit(EventKind::QuirkApplied, &message);
+        }
+        Ok(system)
+    }
+}
+
+// プログラムは同じコンテキストの他のインスタンスと共有しているので、最後の1つを破棄したときに消す
+impl Drop for ParticleSystem {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        self.buffers.delete(gl);
+        if let Some(gpu_colors) = &self.gpu_colors {
+            gpu_colors.delete(gl);
+        }
+        if let Some(resources) = &self.scene_gl {
+            resources.delete(gl);
+        }
+        if let Some(trail_buffer) = &self.trail_buffer {
+            trail_buffer.delete(gl);
+        }
+        gl.delete_buffer(self.size_buffer.as_ref());
+        gl.delete_buffer(self.depth_buffer.as_ref());
+        gl.delete_texture(self.sprite_texture.as_ref());
+        shader::release_context(gl);
+    }
+}
+
+// strict モードで必須にする拡張機能と、ないときに使われる代替経路
+const STRICT_EXTENSIONS: [(&str, &str); 1] = [(
+    "ANGLE_instanced_arrays",
+    "sprites would be drawn without instancing",
+)];
+
+fn require_extensions(gl: &WebGlRenderingContext) -> Result<(), JsValue> {
+    let missing: Vec<String> = STRICT_EXTENSIONS
+        .iter()
+        .filter(|(name, _)| gl.get_extension(name).ok().flatten().is_none())
+        .map(|(name, fallback)| format!("{} ({})", name, fallback))
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    Err(tr(Text::StrictMissingExtensions, &[&missing.join(", ")]).into())
+}
+
+// 描くパーティクル（sim.visible()）の頂点データを詰め直す（positions と colors の確保済み領域を使い回す）
+// 成分ごとの配列から位置と色を別々のループで書くので、位置の変換はベクトル化されやすい
+// 色は sim.theme の塗り方で、simd なら虹色の変換を4個ずつまとめて行う
+pub(crate) fn pack_vertices(sim: &Simulation, positions: &mut Vec<f32>, colors: &mut Vec<f32>) {
+    pack_positions(sim.visible(), sim.width, sim.height, sim.camera, positions);
+    sim.theme.write_colors(sim.visible(), sim.simd(), colors);
+}
+
+// 位置だけを camera から見た正規化座標にして詰める
+fn pack_positions(particles: &ParticleSet, width: f32, height: f32, camera: Camera, positions: &mut Vec<f32>) {
+    positions.clear();
+    for (&x, &y) in particles.x.iter().zip(&particles.y) {
+        let (x, y) = camera.to_screen(x, y);
+        positions.push((x / width) * 2.0 - 1.0);
+        positions.push(1.0 - (y / height) * 2.0);
+    }
+}
+
+// 寿命が残り少ないパーティクルと不透明度が 1 未満のパーティクルの色を背景色に近づける
+// 頂点の色はRGBだけなので、背景の上に寿命の割合と不透明度を掛けたアルファで重ねたのと同じ色にする
+pub(crate) fn fade_colors(particles: &ParticleSet, style: &ParticleStyle, frame: u32, background: [f32; 3], colors: &mut [f32]) {
+    for (i, (rgb, &life)) in colors.chunks_exact_mut(3).zip(&particles.life).enumerate() {
+        let (_, alpha) = style.at(particles.size[i], particles.alpha[i], particles.hue[i], frame);
+        let alpha = life.clamp(0.0, 1.0) * alpha;
+        if alpha < 1.0 {
+            for (c, &bg) in rgb.iter_mut().zip(&background) {
+                *c = bg + (*c - bg) * alpha;
+            }
+        }
+    }
+}
+
+// 1パーティクル分の頂点データ（正規化座標とRGB）を追加
+fn pack_vertex(p: &Particle, width: f32, height: f32, camera: Camera, theme: &ColorTheme, positions: &mut Vec<f32>, colors: &mut Vec<f32>) {
+    // 正規化座標に変換 (-1.0 ~ 1.0)
+    let (x, y) = camera.to_screen(p.x, p.y);
+    positions.push((x / width) * 2.0 - 1.0);
+    positions.push(1.0 - (y / height) * 2.0);
+
+    // 塗り方に合わせてRGBにする
+    let rgb = theme.rgb(p);
+    colors.push(rgb.0);
+    colors.push(rgb.1);
+    colors.push(rgb.2);
+}
+
+// 頂点シェーダー
+const VERTEX_SHADER_SOURCE: &str = r#"
+    attribute vec2 a_position;
+    attribute vec3 a_color;
+    uniform float u_pointSize;
+    varying vec3 v_color;
+    varying float v_edge;
+
+    void main() {
+        gl_Position = vec4(a_position, 0.0, 1.0);
+        gl_PointSize = u_pointSize;
+        v_color = a_color;
+        // 円の縁をぼかす幅（gl_PointCoord での1ピクセル分）
+        v_edge = 1.0 / max(u_pointSize, 1.0);
+    }
+"#;
+
+// フラグメントシェーダー
+const FRAGMENT_SHADER_SOURCE: &str = r#"
+    precision mediump float;
+    varying vec3 v_color;
+
+    void main() {
+        gl_FragColor = vec4(v_color, 0.8);
+    }
+"#;
+
+// 円のフラグメントシェーダー（点の内接円の外を捨て、縁の1ピクセルを smoothstep でぼかす）
+const CIRCLE_FRAGMENT_SHADER_SOURCE: &str = r#"
+    precision mediump float;
+    varying vec3 v_color;
+    varying float v_edge;
+
+    void main() {
+        float distance = length(gl_PointCoord - vec2(0.5));
+        float coverage = 1.0 - smoothstep(0.5 - v_edge, 0.5, distance);
+        if (coverage <= 0.0) {
+            discard;
+        }
+        gl_FragColor = vec4(v_color, 0.8 * coverage);
+    }
+"#;
+
+// スプライトのフラグメントシェーダー（白い光の玉のテクスチャに頂点の色を掛ける）
+const SPRITE_FRAGMENT_SHADER_SOURCE: &str = r#"
+    precision mediump float;
+    uniform sampler2D u_sprite;
+    varying vec3 v_color;
+
+    void main() {
+        vec4 texel = texture2D(u_sprite, gl_PointCoord);
+        gl_FragColor = vec4(v_color * texel.rgb, 0.8 * texel.a);
+    }
+"#;
+
+// 形と精度に合わせたパーティクルのプログラム（同じ組み合わせならキャッシュを再利用）
+// sized なら点の大きさに頂点ごとの倍率 a_size を掛け、perspective なら a_depth と u_projection で透視投影する
+fn particle_program(
+    gl: &WebGlRenderingContext,
+    precision: ShaderPrecision,
+    shape: ParticleShape,
+    colors: ColorPipeline,
+    sized: bool,
+    perspective: bool,
+) -> Result<(WebGlProgram, bool), String> {
+    let vertex_source = match colors {
+        ColorPipeline::Cpu => VERTEX_SHADER_SOURCE,
+        ColorPipeline::Gpu => GPU_COLOR_VERTEX_SHADER,
+    };
+    let vertex_source = match sized {
+        true => particle_style::with_point_sizes(vertex_source),
+        false => String::from(vertex_source),
+    };
+    let vertex_source = match perspective {
+        true => projection::with_perspective(&vertex_source),
+        false => vertex_source,
+    };
+    let fragment_source = match shape {
+        ParticleShape::Square => FRAGMENT_SHADER_SOURCE,
+        ParticleShape::Circle => CIRCLE_FRAGMENT_SHADER_SOURCE,
+        ParticleShape::Sprite => SPRITE_FRAGMENT_SHADER_SOURCE,
+    };
+    shader::get_or_create_program(
+        gl,
+        &shader::with_precision(&vertex_source, precision),
+        &shader::with_precision(fragment_source, precision),
+    )
+}
@@ -0,0 +1,1990 @@
+use wasm_bindgen::prelude::*;
+use web_sys::{
+    HtmlCanvasElement, OffscreenCanvas, WebGl2RenderingContext, WebGlBuffer, WebGlProgram,
+    WebGlRenderingContext, WebGlVertexArrayObject,
+};
+
+use crate::attractor::Attractor;
+use crate::backend::{describe_error, BackendKind};
+use crate::bounds::{BoundaryMode, OutOfBounds};
+use crate::camera::Camera;
+use crate::capture::{self, CapturedBuffer};
+use crate::color_pipeline::ColorPipeline;
+use crate::color_theme::ColorMode;
+use crate::context::{
+    AcquiredContext, CanvasById, CanvasElement, ContextSource, ExternalContext,
+    OffscreenCanvasSource,
+};
+use crate::context_loss::ContextLossWatch;
+use crate::draw_strategy::DrawStrategy;
+use crate::emitter::Emitter;
+use crate::events::{BenchmarkEvent, EventBus, EventKind};
+use crate::explosion::ExplosionConfig;
+use crate::export;
+use crate::fingerprint::ConfigFingerprint;
+use crate::frame_capture;
+use crate::gl_state::GlState;
+use crate::gl_surface::{GlSurface, SceneGl};
+use crate::gpu_physics::{GpuPhysics, PhysicsMode};
+use crate::i18n::{tr, Text};
+use crate::init::{progress, BuildOptions, InitProgress, InitTimings};
+use crate::input::{InputAction, InputHandlers};
+use crate::lod::LodMode;
+use crate::memory::{check_budget, try_vec};
+use crate::observers::Observers;
+use crate::obstacle::Obstacle;
+use crate::panel::{self, OverlayRect};
+use crate::particle_shape::ParticleShape;
+use crate::particle_style::ParticleStyle;
+use crate::physics::SimulationConfig;
+use crate::picking::ParticleInfo;
+use crate::projection::ProjectionMode;
+use crate::quirks::{self, Quirks};
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::resize::CanvasSizing;
+use crate::rng;
+use crate::scene::{
+    create_clip_scene, create_composite_scene, create_point_size_scene, create_readback_scene,
+    create_scene, create_shadow_scene, create_state_change_scene, create_texture_upload_scene,
+    describe_scene, require_backend, Atlas, Scene, SceneDescription, SceneKind,
+};
+use crate::shader::{self, PrecisionComparison, PrecisionRun, ShaderPrecision};
+use crate::simulation::Simulation;
+use crate::snapshot;
+use crate::stats_stream::StatsStream;
+#[cfg(feature = "telemetry")]
+use crate::telemetry::Telemetry;
+use crate::timeline::Timeline;
+use crate::timing;
+use crate::trails::TrailBuffer;
+use crate::tuning::TuningProfile;
+use crate::upload::{BufferLayout, UploadStrategy};
+use crate::viewport;
+use crate::webgl::{fade_colors, pack_vertices};
+
+// WebGL2 の VAO とインスタンス描画でパーティクルを描くバックエンド
+// 1パーティクルを点スプライトではなく四角形1つのインスタンスとして描くので、
+// WebGL1 の点スプライト（ParticleSystem）と描画経路だけを比べられる
+#[wasm_bindgen]
+pub struct ParticleSystemWebGl2 {
+    sim: Simulation,
+    gl: WebGl2RenderingContext,
+    // 描く前に既定に戻す GL の状態
+    gl_state: GlState,
+    program: WebGlProgram,
+    // 四角形の頂点とインスタンスごとの属性をまとめた VAO
+    vao: WebGlVertexArrayObject,
+    corner_buffer: WebGlBuffer,
+    position_buffer: WebGlBuffer,
+    color_buffer: WebGlBuffer,
+    init: InitProgress,
+    // Particles 以外のシーンが有効なときだけ Some
+    scene: Option<Box<dyn Scene>>,
+    scene_gl: Option<SceneGl>,
+    // 毎フレーム使い回すインスタンスデータ
+    positions: Vec<f32>,
+    colors: Vec<f32>,
+    // set_load() で指定した負荷（None ならシーンごとの既定）
+    load: Option<f32>,
+    events: EventBus,
+    strict: bool,
+    // explode() の強さ・範囲・減衰
+    explosion: ExplosionConfig,
+    // パーティクルのシェーダーの精度
+    precision: ShaderPrecision,
+    // パーティクルの合成方法と、Trails のときに描き溜める先（最初に Trails にしたときに作る）
+    render_mode: RenderMode,
+    trail_buffer: Option<TrailBuffer>,
+    // 検出したブラウザ固有の問題と回避策
+    quirks: Quirks,
+    // subscribe_stats() の購読
+    stats_stream: StatsStream,
+    // start_telemetry_channel() と start_telemetry_socket() の送り先
+    #[cfg(feature = "telemetry")]
+    telemetry: Telemetry,
+    // on_frame() などで登録されたコールバック
+    observers: Observers,
+    // 直近のフレーム時間と annotate() の印
+    timeline: Timeline,
+    // apply_tuning_profile() で当てた設定の組
+    tuning: Option<TuningProfile>,
+    // 描画バッファの倍率と ResizeObserver
+    sizing: CanvasSizing,
+    // attach_input_handlers() で登録した入力のコールバック
+    input: Option<InputHandlers>,
+    // set_physics_mode(Gpu) の間だけ Some
+    physics: Option<GpuPhysics>,
+    // webglcontextlost / webglcontextrestored のリスナー（キャンバスが無ければ None）
+    context_loss: Option<ContextLossWatch>,
+}
+
+#[wasm_bindgen]
+impl ParticleSystemWebGl2 {
+    #[wasm_bindgen(constructor)]
+    pub fn new(canvas_id: &str, particle_count: usize) -> Result<ParticleSystemWebGl2, JsValue> {
+        Self::from_canvas(CanvasById(canvas_id).canvas()?, particle_count)
+    }
+
+    // フレームワークが持っている canvas 要素から生成（id が無くても、ドキュメントに追加前でもよい）
+    pub fn from_canvas(
+        canvas: HtmlCanvasElement,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        Self::from_source(
+            &CanvasElement(&canvas),
+            particle_count,
+            BuildOptions::default(),
+        )
+    }
+
+    // メモリ予算を指定して生成（超える場合は確保前にエラーを返す）
+    pub fn with_memory_budget(
+        canvas_id: &str,
+        particle_count: usize,
+        budget_bytes: u64,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        let options = BuildOptions {
+            memory_budget: budget_bytes,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // 重力・跳ね返り・摩擦・点の大きさを指定して生成する
+    pub fn with_simulation_config(
+        canvas_id: &str,
+        particle_count: usize,
+        config: &SimulationConfig,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        let options = BuildOptions {
+            simulation: *config,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // 本来の経路で描けないシーンをエラーにするモードで生成する
+    pub fn new_strict(
+        canvas_id: &str,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        let options = BuildOptions {
+            strict: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // パーティクルを生成せずに作り、init_chunk() で少しずつ生成する
+    pub fn new_progressive(
+        canvas_id: &str,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        let options = BuildOptions {
+            progressive: true,
+            ..BuildOptions::default()
+        };
+        Self::from_source(&CanvasById(canvas_id), particle_count, options)
+    }
+
+    // JS側で用意したWebGL2コンテキストから生成
+    pub fn with_context(
+        gl: JsValue,
+        width: u32,
+        height: u32,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        let source = ExternalContext {
+            context: gl,
+            width: width as f32,
+            height: height as f32,
+        };
+        Self::from_source(&source, particle_count, BuildOptions::default())
+    }
+
+    // Worker に渡した OffscreenCanvas から生成（メインスレッドと Worker での描画を比べる用）
+    pub fn from_offscreen_canvas(
+        canvas: OffscreenCanvas,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        Self::from_source(
+            &OffscreenCanvasSource(&canvas),
+            particle_count,
+            BuildOptions::default(),
+        )
+    }
+
+    pub fn update(&mut self) {
+        self.apply_input();
+        if self.sim.paused {
+            return;
+        }
+        if let Some(scene) = &mut self.scene {
+            // パーティクルのシミュレーションは止まっているので、仕掛けた爆発はここで数える
+            for charge in self.sim.charges.tick() {
+                scene.explode(charge.x, charge.y, &charge.explosion);
+            }
+            scene.update();
+            return;
+        }
+
+        if let Some(physics) = &mut self.physics {
+            let sim = &mut self.sim;
+            physics.sync(&self.gl, sim.particles(), sim.sequence(), sim.hue_shift);
+            for charge in sim.step_external() {
+                physics.explode(charge.x, charge.y, &charge.explosion);
+            }
+            physics.step(&self.gl, sim.width, sim.height, &sim.config, sim.boundary);
+            return;
+        }
+
+        self.sim.step_with(|_| {});
+    }
+
+    // update() で物理演算を止める（描画・入力による視点の操作・爆発の受け付けは続ける）
+    pub fn pause(&mut self) {
+        self.sim.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.sim.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sim.paused
+    }
+
+    // 止めていても n ステップだけ update() と同じように進める（描画はしない）
+    pub fn step(&mut self, n: u32) {
+        let paused = std::mem::replace(&mut self.sim.paused, false);
+        for _ in 0..n {
+            self.update();
+        }
+        self.sim.paused = paused;
+    }
+
+    // dt_ms 経過した分だけ固定長のステップで update() し、進めたステップ数を返す
+    // 物理の進み方がリフレッシュレートに依らなくなる。描画は持ち越した端数の分だけ直前のステップとの間を補間する
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        let steps = self.advance_timestep(dt_ms);
+        for _ in 0..steps {
+            self.update();
+        }
+        self.interpolate_frame();
+        steps
+    }
+
+    // 1ステップの長さ(ms)を設定し、実際に使う値を返す（既定は 60Hz の1フレーム）
+    pub fn set_fixed_timestep(&mut self, step_ms: f64) -> f64 {
+        self.sim.timestep.set_step_ms(step_ms)
+    }
+
+    pub fn get_fixed_timestep(&self) -> f64 {
+        self.sim.timestep.step_ms()
+    }
+
+    // requestAnimationFrame の timestamp(ms) を渡して1回分進める
+    // 物理は毎回 update() で進め、描画は set_target_fps() の上限を超えない回だけ行う。描いたら true
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.update();
+        let render = self.cap_frame(timestamp);
+        if render {
+            self.render();
+        }
+        render
+    }
+
+    // 描画の上限(fps)を設定し、実際に使う値を返す（0 以下なら上限なし。既定は上限なし）
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.sim.frame_cap.set_target_fps(fps)
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        self.sim.frame_cap.target_fps()
+    }
+
+    // tick() の間隔から推定した表示のリフレッシュレート(Hz)（まだ分からなければ 0）
+    pub fn get_refresh_rate(&self) -> f64 {
+        self.sim.frame_cap.refresh_rate()
+    }
+
+    // set_target_fps() してから tick() で描いた回数と、上限のために描かなかった回数
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.sim.frame_cap.rendered
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.sim.frame_cap.skipped
+    }
+
+    pub fn render(&mut self) {
+        if let Some((width, height)) = self.sizing.take_pending() {
+            let _ = self.resize(width, height);
+        }
+        if self
+            .context_loss
+            .as_ref()
+            .is_some_and(ContextLossWatch::take_restored)
+        {
+            self.restore_context();
+        }
+        if self.gl.is_context_lost() {
+            self.events
+                .emit(EventKind::ContextLost, &tr(Text::ContextLost, &[]));
+            return;
+        }
+
+        if let Some(message) = self.quirks.note_frame() {
+            self.events.emit(EventKind::QuirkApplied, &message);
+        }
+        let frame_count = self.get_frame_count();
+        self.stats_stream.note_frame(frame_count);
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.note_frame() {
+            self.telemetry
+                .send(None, "webgl2", self.get_particle_count());
+        }
+        self.observers.note_frame(frame_count);
+        self.timeline.note_frame(frame_count);
+
+        let gl = &self.gl;
+        // コンテキストを共有している他のインスタンスやページに状態を変えられていても描けるようにする
+        // 頂点属性を無効にするのは既定の VAO に対してなので、ホストの VAO を書き換えないように先に外す
+        gl.bind_vertex_array(None);
+        self.gl_state.reset(gl.unchecked_ref());
+        gl.use_program(Some(&self.program));
+        viewport::apply_gl(self.gl.unchecked_ref(), None);
+
+        if let (Some(scene), Some(resources)) = (&mut self.scene, &mut self.scene_gl) {
+            // シーンの属性の設定でこちらの VAO を書き換えないように外しておく
+            gl.bind_vertex_array(None);
+            let mut surface = GlSurface {
+                gl: self.gl.unchecked_ref(),
+                width: self.sim.width,
+                height: self.sim.height,
+                resources,
+                events: &mut self.events,
+                saved_viewport: None,
+            };
+            scene.render(&mut surface);
+            return;
+        }
+
+        // 画面クリア（Trails は描き溜めたテクスチャに向けて、前のフレームを薄めるだけ）
+        let trails = self.render_mode == RenderMode::Trails;
+        let background = self.sim.theme.background();
+        match self.trail_buffer.as_mut().filter(|_| trails) {
+            Some(buffer) => {
+                buffer.begin(self.gl.unchecked_ref(), background);
+                gl.use_program(Some(&self.program));
+            }
+            None => {
+                gl.clear_color(background[0], background[1], background[2], 1.0);
+                gl.clear(WebGl2RenderingContext::COLOR_BUFFER_BIT);
+            }
+        }
+
+        self.init
+            .mark_frame(self.sim.spawned() == self.sim.particle_count);
+        // 四角形の大きさ（1px をクリップ座標に直した値 × 点の直径）
+        let point_size =
+            self.sim.config.point_diameter() * self.sim.camera.zoom * self.sizing.backing_ratio();
+        let size = (
+            2.0 * point_size / gl.drawing_buffer_width() as f32,
+            2.0 * point_size / gl.drawing_buffer_height() as f32,
+        );
+        if self.render_mode != RenderMode::Normal {
+            self.render_mode.apply_gl(self.gl.unchecked_ref());
+        }
+
+        match &self.physics {
+            Some(physics) => {
+                // 位置は GPU のバッファにあるので送らずにそのまま描く
+                let sim = &self.sim;
+                physics.render(gl, sim.width, sim.height, sim.camera, size, sim.hue_shift);
+            }
+            None => self.draw_instances(size),
+        }
+        self.gl.disable(WebGl2RenderingContext::BLEND);
+        if let Some(buffer) = self.trail_buffer.as_ref().filter(|_| trails) {
+            buffer.end(self.gl.unchecked_ref());
+        }
+    }
+
+    // CPU で計算した位置と色を詰めて送り、インスタンスとして描く
+    // GPU で物理演算しているときは通らないので、間引いて描くのもこちらだけ
+    fn draw_instances(&mut self, size: (f32, f32)) {
+        let start = timing::now_ms();
+        self.sim.prepare_visible();
+        pack_vertices(&self.sim, &mut self.positions, &mut self.colors);
+        fade_colors(
+            self.sim.visible(),
+            &self.sim.style(),
+            self.sim.frame_count,
+            self.sim.theme.background(),
+            &mut self.colors,
+        );
+        // 障害物の輪郭はパーティクルと同じ大きさの点として後ろに足す
+        self.sim.obstacles.pack_outline(
+            self.sim.config.point_diameter(),
+            (self.sim.width, self.sim.height),
+            self.sim.camera,
+            &mut self.positions,
+            &mut self.colors,
+        );
+
+        // インスタンスごとの位置と色を送る（属性の設定は VAO に記録済み）
+        let gl = &self.gl;
+        gl.bind_vertex_array(Some(&self.vao));
+        for (buffer, data) in [
+            (&self.position_buffer, &self.positions),
+            (&self.color_buffer, &self.colors),
+        ] {
+            gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(buffer));
+            unsafe {
+                let array = js_sys::Float32Array::view(data);
+                gl.buffer_data_with_array_buffer_view(
+                    WebGl2RenderingContext::ARRAY_BUFFER,
+                    &array,
+                    WebGl2RenderingContext::DYNAMIC_DRAW,
+                );
+            }
+        }
+
+        let size_location = gl.get_uniform_location(&self.program, "u_size");
+        gl.uniform2f(size_location.as_ref(), size.0, size.1);
+
+        let count = (self.positions.len() / 2) as i32;
+        gl.draw_arrays_instanced(WebGl2RenderingContext::TRIANGLE_STRIP, 0, 4, count);
+        gl.bind_vertex_array(None);
+        let particle_count = self.sim.particle_count;
+        self.sim
+            .lod
+            .record_render(timing::now_ms() - start, particle_count);
+    }
+
+    // 描画せずに n ステップ連続で物理演算し、経過時間(ms)を返す
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..n {
+            self.update();
+        }
+        let elapsed = timing::now_ms() - start;
+        self.observers
+            .note_complete(n, elapsed, false, self.get_frame_count());
+        elapsed
+    }
+
+    // 最初の frames フレームを今すぐ物理演算と描画まで進める（経過時間(ms)を返す）
+    // シェーダーのコンパイルや JIT・バッファの確保を計測の前に済ませる
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..frames {
+            self.update();
+            self.render();
+        }
+        timing::now_ms() - start
+    }
+
+    // 止まるまで（平均速度が threshold px/フレームを下回るまで）最大 max_frames ステップ物理演算し、
+    // 経過時間(ms)を返す
+    pub fn simulate_until_settled(&mut self, threshold: f32, max_frames: u32) -> f64 {
+        self.set_settle_threshold(Some(threshold));
+        let start = timing::now_ms();
+        let mut frames = 0;
+        for _ in 0..max_frames {
+            self.update();
+            frames += 1;
+            if self.is_settled() {
+                break;
+            }
+        }
+        let elapsed = timing::now_ms() - start;
+        let (settled, frame_count) = (self.is_settled(), self.get_frame_count());
+        self.observers
+            .note_complete(frames, elapsed, settled, frame_count);
+        elapsed
+    }
+
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        self.sim.set_settle_threshold(threshold);
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.get_frames_until_settle().is_some()
+    }
+
+    pub fn get_frames_until_settle(&self) -> Option<u32> {
+        self.sim
+            .settle
+            .and_then(|settle| settle.frames_until_settle())
+    }
+
+    pub fn get_mean_speed(&self) -> f32 {
+        self.sim.mean_speed()
+    }
+
+    // 未生成のパーティクルを最大 max_particles 個生成し、進捗(0.0~1.0)を返す
+    pub fn init_chunk(&mut self, max_particles: usize) -> f32 {
+        let start = timing::now_ms();
+        self.sim.spawn_pending(max_particles);
+        self.init.timings.particles_ms += timing::now_ms() - start;
+        let (spawned, total) = (self.sim.spawned(), self.sim.particle_count);
+        self.init.report(spawned, total);
+        progress(spawned, total)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.sim.spawned() == self.sim.particle_count
+    }
+
+    // init_chunk() のたびに (progress, spawned, total) で呼ばれる
+    pub fn set_init_progress_callback(&mut self, callback: js_sys::Function) {
+        self.init.set_callback(callback);
+    }
+
+    // 生成開始から全パーティクルが揃った最初のフレームまでの時間(ms)
+    pub fn get_time_to_first_frame(&self) -> f64 {
+        self.init.time_to_first_frame()
+    }
+
+    pub fn get_init_timings(&self) -> InitTimings {
+        self.init.timings()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        match &self.scene {
+            Some(scene) => scene.frame_count(),
+            None => self.sim.frame_count,
+        }
+    }
+
+    // シーンは WebGL1 と同じ経路（WebGL2 コンテキストの WebGL1 互換の API）で描く
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        if self.strict {
+            require_backend(kind, "webgl2")?;
+        }
+        if kind != SceneKind::Particles {
+            self.ensure_scene_gl()?;
+        }
+        self.events.clear_reported();
+        self.scene = create_scene(kind, self.sim.width, self.sim.height)?;
+        if self.scene.is_none() {
+            self.reset();
+        }
+        self.apply_load();
+        Ok(())
+    }
+
+    // 決まった数（負荷 0.5 のとき）の点を size px で描くシーンに切り替える（フィルレートの計測用）
+    // get_max_point_size() より大きい点は上限の大きさで描く
+    pub fn switch_to_point_size(&mut self, count: usize, size: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_point_size_scene(
+            count,
+            size,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // キャンバスの一辺の scale 倍の画像を毎フレームテクスチャとして送り直すシーンに切り替える（転送帯域の計測用）
+    pub fn switch_to_texture_upload(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_texture_upload_scene(
+            scale,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描いた直後にキャンバスの一辺の scale 倍の範囲を readPixels で読み戻すシーンに切り替える（読み戻しの計測用）
+    pub fn switch_to_readback(&mut self, scale: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_readback_scene(
+            scale,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 領域の数を決めてクリップのシーンに切り替える（1〜64 に丸める、ステンシルバッファでクリップする）
+    pub fn switch_to_clip(&mut self, regions: u32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_clip_scene(regions, self.sim.width, self.sim.height));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 合成方法を1つに決めて重ねた矩形のシーンに切り替える（ブレンド式で表せない合成方法はエラー）
+    pub fn switch_to_compositing(&mut self, mode: CompositeMode) -> Result<(), JsValue> {
+        if mode.gl_blend().is_none() {
+            return Err(tr(Text::CompositeModeUnavailable, &[&mode.name(), &"webgl2"]).into());
+        }
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_composite_scene(
+            mode,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 影のぼかしの大きさ(px)を決めて光る円のシーンに切り替える（0〜64 に丸める、シェーダーで同じ見た目を描く）
+    pub fn switch_to_shadows(&mut self, shadow_blur: f32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_shadow_scene(
+            shadow_blur,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // change_every 個の矩形ごとに描画状態を切り替えるシーンに切り替える
+    // WebGL では状態を切り替えられないので色だけで描く（FallbackUsed を送る）
+    pub fn switch_to_state_changes(&mut self, change_every: u32) -> Result<(), JsValue> {
+        self.ensure_scene_gl()?;
+        self.events.clear_reported();
+        self.scene = Some(create_state_change_scene(
+            change_every,
+            self.sim.width,
+            self.sim.height,
+        ));
+        self.apply_load();
+        Ok(())
+    }
+
+    // 点のシーンで描ける一番大きい点(px)
+    pub fn get_max_point_size(&self) -> f32 {
+        quirks::max_point_size(self.gl.unchecked_ref()).unwrap_or(1.0)
+    }
+
+    // シーンの負荷を 0.0~1.0 で指定する（シーンを切り替えても引き継ぐ）
+    pub fn set_load(&mut self, load: f32) {
+        if !(0.0..=1.0).contains(&load) {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::LoadClamped, &[&load]),
+            );
+        }
+        self.load = Some(load.clamp(0.0, 1.0));
+        self.apply_load();
+    }
+
+    pub fn get_load(&self) -> Option<f32> {
+        self.load
+    }
+
+    // 動いているパーティクルの数をその場で増減する（バッファの作り直しやリセットはしない）
+    // 作成時の数までで、それより多ければ丸めて ParameterClamped を送る。実際の数を返す
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let applied = self.sim.set_particle_count(count);
+        if applied != count {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ParticleCountClamped, &[&count, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.sim.particle_count
+    }
+
+    // 作成時の数を超えてパーティクル数を増やし、実際の数を返す（メモリ予算を超える数はエラー）
+    // 確保済みの領域を超えた分は伸ばしながら確保するので、メモリの増え方による停止の計測に使う
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, JsValue> {
+        let applied = self.sim.grow_particle_count(count)?;
+        Ok(applied)
+    }
+
+    // set_particle_count() で増やせる上限（作成時の数か、grow_particle_count() で増やした数）
+    pub fn get_max_particles(&self) -> usize {
+        self.sim.max_particles()
+    }
+
+    // 現在のシーン・キャンバスの大きさ・負荷で固定シードの実行をAPNGに書き出す
+    pub fn export_animation(&self, frames: u32, every_n: u32) -> Result<Vec<u8>, JsValue> {
+        export::export_scene_animation(
+            self.get_scene(),
+            self.sim.width as u32,
+            self.sim.height as u32,
+            self.sim.max_particles(),
+            self.load,
+            frames,
+            every_n,
+        )
+    }
+
+    // 最後に描いたフレームを上の行から並べた RGBA で読み戻す（大きさは描画バッファの画素数）
+    // compare_frames() でバックエンド同士や変更前後の見た目を比べる。render() と同じタスクの中で呼ぶ
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        frame_capture::read_gl_frame(self.gl.unchecked_ref())
+    }
+
+    // 今のシーンの途中のバッファを PNG で取り出す（パーティクル描画には途中のバッファがないので空）
+    pub fn capture_debug_buffers(&self) -> Result<Vec<CapturedBuffer>, JsValue> {
+        match &self.scene {
+            Some(scene) => capture::capture(scene.debug_buffers()),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn get_config_fingerprint(&self) -> String {
+        self.config_fingerprint().hash()
+    }
+
+    pub fn get_config_summary(&self) -> String {
+        self.config_fingerprint().into_text()
+    }
+
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        self.events.drain()
+    }
+
+    pub fn set_event_callback(&mut self, callback: js_sys::Function) {
+        self.events.set_callback(callback);
+    }
+
+    pub fn clear_event_callback(&mut self) {
+        self.events.clear_callback();
+    }
+
+    // interval_ms（50ms以上）ごとに callback(StatsDelta) を呼ぶ（毎フレームは呼ばない）
+    pub fn subscribe_stats(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        self.stats_stream.subscribe(callback, interval_ms);
+    }
+
+    pub fn unsubscribe_stats(&mut self) {
+        self.stats_stream.unsubscribe();
+    }
+
+    // 1秒ごとに計測値のまとめ（JSON の文字列）を BroadcastChannel(name) に送る（telemetry フィーチャー）
+    // 同じオリジンの別のタブで new BroadcastChannel(name) を開けば、複数の端末やタブの結果を1か所で見られる
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_channel(&mut self, name: &str) -> Result<(), JsValue> {
+        self.telemetry.start_channel(name)?;
+        Ok(())
+    }
+
+    // 同じまとめを WebSocket で集計サーバーに送る（url は ws:// か wss://）
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_socket(&mut self, url: &str) -> Result<(), JsValue> {
+        self.telemetry.start_socket(url)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn stop_telemetry(&mut self) {
+        self.telemetry.stop();
+    }
+
+    // render() のたびに callback(FrameNotice) を呼ぶ
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        self.observers.on_frame(callback);
+    }
+
+    // simulate_frames()・simulate_until_settled() が終わったら callback(BenchmarkComplete) を呼ぶ
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        self.observers.on_benchmark_complete(callback);
+    }
+
+    // 500ms ごとの FPS が threshold を下回ったら callback(FpsDrop) を呼ぶ（上回るまでは1回だけ）
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        self.observers.on_fps_drop(threshold, callback);
+    }
+
+    // on_frame()・on_benchmark_complete()・on_fps_drop() の登録をすべて外す
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // ホストのページの出来事（クリック・データの読み込み・タブへの復帰など）を今の時刻の印として残す
+    // 印は直近のフレーム時間と一緒に get_timeline_json() と export_results() に書き出される
+    pub fn annotate(&mut self, label: &str) {
+        let frame_count = self.get_frame_count();
+        self.timeline.annotate(label, frame_count);
+    }
+
+    // 直近のフレーム時間と annotate() の印（時刻はどちらも performance.now() の ms）
+    pub fn get_timeline_json(&self) -> String {
+        self.timeline.to_json()
+    }
+
+    pub fn clear_timeline(&mut self) {
+        self.timeline.clear();
+    }
+
+    // WebGL のコンテキストが失われているか（その間は render() が何も描かない）
+    // ブラウザが戻したら次の render() でシェーダーとバッファを作り直して描き始める
+    pub fn context_lost(&self) -> bool {
+        self.gl.is_context_lost()
+    }
+
+    // これまでにコンテキストが失われた回数
+    pub fn get_context_losses(&self) -> u32 {
+        self.context_loss
+            .as_ref()
+            .map_or(0, ContextLossWatch::losses)
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        self.scene
+            .as_ref()
+            .map_or(SceneKind::Particles, |scene| scene.kind())
+    }
+
+    pub fn describe_current_scene(&self) -> SceneDescription {
+        match &self.scene {
+            Some(scene) => scene.describe(),
+            None => describe_scene(SceneKind::Particles),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sim.charges.clear();
+        if let Some(scene) = &mut self.scene {
+            if scene.reset() {
+                return;
+            }
+            if let Ok(scene) = create_scene(scene.kind(), self.sim.width, self.sim.height) {
+                self.scene = scene;
+                self.apply_load();
+            }
+            return;
+        }
+
+        self.sim.reset();
+    }
+
+    // パーティクルの位置・速度・色相・寿命とフレーム数をバイト列に書き出す
+    // restore() に渡せば、どのバックエンドでも同じ途中の状態から続けられる（爆発の取り消しなどにも使える）
+    pub fn snapshot(&mut self) -> Vec<u8> {
+        self.read_back_physics();
+        self.sim.snapshot()
+    }
+
+    // snapshot() の状態に戻す（パーティクル数も保存したときの数になる。シーンの状態は含まない）
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.sim.restore(snapshot::decode(bytes)?)?;
+        Ok(())
+    }
+
+    // (x, y) から radius 以内にあるパーティクルの数（座標はシミュレーションの px。カメラで動かした画面の位置とは違う）
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.read_back_physics();
+        self.sim.count_within(x, y, radius)
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス（パーティクルがなければ undefined）
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.read_back_physics();
+        self.sim.nearest(x, y).map(|index| index as u32)
+    }
+
+    // index 番目のパーティクルの位置・速度・色相・寿命（範囲外なら undefined）
+    pub fn get_particle(&mut self, index: u32) -> Option<ParticleInfo> {
+        self.read_back_physics();
+        self.sim.particle_info(index as usize)
+    }
+
+    // 以後の生成と reset() の乱数を seed から作り、パーティクルを作り直す
+    // 同じシードなら実行やバックエンドが違っても同じ初期状態から始まる
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.seed = Some(seed);
+        rng::with_seed(seed, || self.reset());
+    }
+
+    // 乱数を毎回違うものに戻す（今のパーティクルはそのまま）
+    pub fn clear_seed(&mut self) {
+        self.sim.seed = None;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.sim.seed
+    }
+
+    pub fn explode(&mut self, click_x: f32, click_y: f32) {
+        if let Some(scene) = &mut self.scene {
+            scene.explode(click_x, click_y, &self.explosion);
+            return;
+        }
+
+        if let Some(physics) = &mut self.physics {
+            physics.explode(click_x, click_y, &self.explosion);
+            return;
+        }
+
+        self.sim.explode(click_x, click_y, &self.explosion);
+    }
+
+    // 同じ範囲・減衰で中心へ吸い寄せる
+    pub fn implode(&mut self, x: f32, y: f32) {
+        let implosion = self.explosion.inverted();
+        if let Some(scene) = &mut self.scene {
+            scene.explode(x, y, &implosion);
+            return;
+        }
+
+        if let Some(physics) = &mut self.physics {
+            physics.explode(x, y, &implosion);
+            return;
+        }
+
+        self.sim.explode(x, y, &implosion);
+    }
+
+    // (x, y) の近くのパーティクルを (dx, dy) の向きに押し流す（シーンの描画中と GPU の物理演算中は何もしない）
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() && self.physics.is_none() {
+            self.sim.stir(x, y, dx, dy);
+        }
+    }
+
+    // (x, y) を (dx, dy)(px) 動いたポインターの勢いを、次の update() で近くのパーティクルに渡す
+    // 押し流しに加えて周りに渦ができる（シーンの描画中と GPU の物理演算中は何もしない）
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        if self.scene.is_none() && self.physics.is_none() {
+            self.sim.pointer.push_move(x, y, dx, dy);
+        }
+    }
+
+    // ドラッグ中のポインターの位置を渡す（動いた量は前に渡した位置から求め、strength 倍にする）
+    // strength が負なら渦の向きが逆になる
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        if self.scene.is_none() && self.physics.is_none() {
+            self.sim.pointer.push_drag(x, y, strength);
+        }
+    }
+
+    // delay_frames 回後の update() の最初に explode(x, y) する（0 ならすぐに爆発する）
+    pub fn schedule_explosion(&mut self, x: f32, y: f32, delay_frames: u32) {
+        if delay_frames == 0 {
+            self.explode(x, y);
+            return;
+        }
+        self.sim
+            .charges
+            .schedule(x, y, delay_frames, self.explosion);
+    }
+
+    pub fn get_pending_explosions(&self) -> usize {
+        self.sim.charges.len()
+    }
+
+    // 引力点を置いて番号を返す。update() のたびに半径の中のパーティクルを中心へ引き寄せる
+    // （strength が負なら押しのける。マウスで引きずるときは move_attractor() で動かす）
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        self.sim.attractors.add(x, y, strength, radius)
+    }
+
+    // 見つからなければ false
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        self.sim.attractors.remove(id)
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        self.sim.attractors.move_to(id, x, y)
+    }
+
+    pub fn clear_attractors(&mut self) {
+        self.sim.attractors.clear();
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        self.sim.attractors.list()
+    }
+
+    // 中心 (x, y)・半径 radius の円の障害物を置く。update() のたびに当たったパーティクルを跳ね返す
+    // （跳ね返りの割合は壁と同じ bounce。NaN などや半径が 0 以下なら置かずに false）
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        self.sim.obstacles.add_circle(x, y, radius)
+    }
+
+    // (x0, y0)〜(x1, y1) の線分の障害物を置く（1ステップで横切るほど速いパーティクルも跳ね返す）
+    pub fn add_line_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        self.sim.obstacles.add_line(x0, y0, x1, y1)
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.sim.obstacles.clear();
+    }
+
+    pub fn get_obstacles(&self) -> Vec<Obstacle> {
+        self.sim.obstacles.list()
+    }
+
+    // 毎フレーム rate 個ずつパーティクルを出すエミッターを置いて番号を返す
+    // 出したパーティクルは寿命が減るにつれて薄くなり、尽きると同じ場所を使って出し直される
+    // （最初の1つを置くと、今いるパーティクルも順に尽きてエミッターのものに入れ替わる）
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        self.sim.add_emitter(x, y, rate, spread, speed)
+    }
+
+    // 見つからなければ false
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        self.sim.emitters.remove(id)
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.sim.emitters.clear();
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        self.sim.emitters.list()
+    }
+
+    // エミッターから出したパーティクルの累計
+    pub fn get_emitted_count(&self) -> f64 {
+        self.sim.emitters.emitted as f64
+    }
+
+    // 放出したパーティクルの寿命(フレーム)を設定し、実際に使う値を返す（1フレーム以上）
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        self.sim.emitters.set_lifetime(frames)
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        self.sim.emitters.lifetime()
+    }
+
+    // シミュレーションの領域の大きさを変える（シーン表示中はシーンを新しい大きさで作り直す）
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        if let Some(scene) = &self.scene {
+            self.scene = create_scene(scene.kind(), width, height)?;
+        }
+        self.sim.resize(width, height);
+        self.apply_backing_size();
+        self.apply_load();
+        Ok(())
+    }
+
+    // 描画バッファを表示サイズの ratio 倍にする（HiDPI で滲まないように devicePixelRatio を渡す）
+    // シミュレーションの座標は変わらない。範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        let ratio = self.sizing.set_pixel_ratio(ratio);
+        self.apply_backing_size();
+        ratio
+    }
+
+    pub fn get_pixel_ratio(&self) -> f32 {
+        self.sizing.pixel_ratio()
+    }
+
+    // 描画バッファを pixel_ratio のさらに scale 倍にする（2 なら 4 倍の画素を描くスーパーサンプリング、
+    // 0.5 なら 1/4 の画素を描いて拡大表示）。表示の大きさとシミュレーションの座標は変わらない
+    // 範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        let scale = self.sizing.set_render_scale(scale);
+        self.apply_backing_size();
+        scale
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.sizing.render_scale()
+    }
+
+    // ResizeObserver でキャンバスの表示サイズと devicePixelRatio を監視し、変わったら次の render() で resize() する
+    // ページ上の canvas 要素に描いているときだけ使える
+    pub fn observe_resize(&mut self) -> Result<(), JsValue> {
+        let canvas = self
+            .gl
+            .canvas()
+            .map(JsValue::from)
+            .ok_or_else(|| tr(Text::ResizeObserverUnavailable, &[]))?;
+        self.sizing.observe(&canvas)
+    }
+
+    pub fn unobserve_resize(&mut self) {
+        self.sizing.unobserve();
+    }
+
+    // キャンバスのポインター・ホイール・タッチのイベントを受け取り、次の update() で
+    // クリック（タップ）は explode()、ドラッグは stir()、ホイール・ピンチ・シフト＋ドラッグは視点の移動にする
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        let canvas = self
+            .gl
+            .canvas()
+            .map(JsValue::from)
+            .ok_or_else(|| tr(Text::InputUnavailable, &[]))?;
+        self.input = Some(InputHandlers::attach(&canvas)?);
+        Ok(())
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        self.input = None;
+    }
+
+    // VAO・バッファ・テクスチャを消して破棄する（以後このインスタンスは使えない）
+    // 消す処理は Drop にあるので、free() で解放したときも同じように消える
+    pub fn dispose(self) {
+        drop(self);
+    }
+
+    // 視点を元に戻す（拡大率 1、平行移動なし）
+    pub fn reset_camera(&mut self) {
+        self.sim.camera = Camera::default();
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        self.sim.camera.zoom
+    }
+
+    // 見た目だけの毎フレームの処理を止め、物理演算と描画だけを計測する
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        self.sim.cosmetic = !enabled;
+    }
+
+    pub fn is_strict_benchmark(&self) -> bool {
+        !self.sim.cosmetic
+    }
+
+    pub fn get_quirks(&self) -> Vec<String> {
+        self.quirks
+            .applied()
+            .iter()
+            .map(|quirk| quirk.name().to_string())
+            .collect()
+    }
+
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.sim.out_of_bounds = policy;
+    }
+
+    pub fn get_out_of_bounds(&self) -> OutOfBounds {
+        self.sim.out_of_bounds
+    }
+
+    // 壁に届いたパーティクルを跳ね返すか・折り返すか・生成し直すか・そのまま出すか
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.sim.boundary = mode;
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        self.sim.boundary
+    }
+
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        self.explosion = *config;
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        self.explosion
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        self.sim.config = config.clamped();
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        self.sim.config
+    }
+
+    // デモのスライダー用に1項目ずつ変える
+    pub fn set_gravity(&mut self, gravity: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            gravity,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_bounce(&mut self, bounce: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            bounce,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            friction,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            point_size,
+            ..self.sim.config
+        });
+    }
+
+    // 四角形のインスタンスで描くのでパーティクルの形は Square だけ
+    pub fn set_particle_shape(&mut self, shape: ParticleShape) -> Result<(), JsValue> {
+        if shape != ParticleShape::Square {
+            return Err(tr(Text::ParticleShapeUnavailable, &[&shape.name(), &"webgl2"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_particle_shape(&self) -> ParticleShape {
+        ParticleShape::Square
+    }
+
+    // スプライトを描けないので画像は受け取らない
+    pub fn set_particle_texture(&mut self, _image: &JsValue) -> Result<(), JsValue> {
+        self.set_particle_shape(ParticleShape::Sprite)
+    }
+
+    pub fn clear_particle_texture(&mut self) -> Result<(), JsValue> {
+        Ok(())
+    }
+
+    // インスタンスの大きさはそろっているので、不透明度だけを変えられる（色に混ぜる）
+    pub fn set_particle_style(&mut self, style: &ParticleStyle) -> Result<(), JsValue> {
+        if style.sanitized().varies_size() {
+            return Err(tr(
+                Text::ParticleStyleUnavailable,
+                &[&style.describe(), &"webgl2"],
+            )
+            .into());
+        }
+        self.sim.set_style(*style);
+        Ok(())
+    }
+
+    pub fn get_particle_style(&self) -> ParticleStyle {
+        self.sim.style()
+    }
+
+    // パーティクルの合成方法を切り替える（Particles シーンだけに効く）
+    // 既定の Normal では従来どおりブレンドせずに描く
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> Result<(), JsValue> {
+        if mode == RenderMode::Trails && self.trail_buffer.is_none() {
+            self.trail_buffer = Some(TrailBuffer::new(self.gl.unchecked_ref())?);
+        }
+        self.render_mode = mode;
+        Ok(())
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        self.render_mode
+    }
+
+    // 描くパーティクルを skip 個に1個に間引く（物理演算はすべてに行う。mode が Off か skip が 1 なら間引かない）
+    // シーンの表示中は間引かない
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        self.sim.lod.set_fixed(mode, skip);
+    }
+
+    // render() の時間が target_render_ms を超えないように、間引く間隔を AutoScaler と同じ探し方で決める
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        let particle_count = self.sim.particle_count;
+        self.sim
+            .lod
+            .set_adaptive(mode, target_render_ms, particle_count);
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        self.sim.lod.mode()
+    }
+
+    // 直前のフレームで何個に1個描いたか（間引いていなければ 1）
+    pub fn get_lod_skip(&self) -> u32 {
+        // GPU で物理演算しているときは間引かない
+        match self.physics {
+            Some(_) => 1,
+            None => self.sim.visible_skip(),
+        }
+    }
+
+    // 直前のフレームで描いたパーティクル数
+    pub fn get_visible_particle_count(&self) -> usize {
+        match self.physics {
+            Some(_) => self.sim.spawned(),
+            None => self.sim.visible().len(),
+        }
+    }
+
+    // WebGL は元から全部を1回の描画命令で描くので、描き方は Batched だけ
+    pub fn set_draw_strategy(&mut self, strategy: DrawStrategy) -> Result<(), JsValue> {
+        if strategy != DrawStrategy::Batched {
+            return Err(tr(
+                Text::DrawStrategyUnavailable,
+                &[&strategy.name(), &"webgl2"],
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn get_draw_strategy(&self) -> DrawStrategy {
+        DrawStrategy::Batched
+    }
+
+    // 頂点データの送り方を選べるのは今のところ WebGL だけなので、BufferData だけ
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) -> Result<(), JsValue> {
+        if strategy != UploadStrategy::BufferData {
+            return Err(tr(
+                Text::UploadStrategyUnavailable,
+                &[&strategy.name(), &"webgl2"],
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn get_upload_strategy(&self) -> UploadStrategy {
+        UploadStrategy::BufferData
+    }
+
+    // 名前の付いた設定の組（合成方法・点の大きさ・描画解像度・精度・送り方・間引き・fps の上限）をまとめて当てる
+    // 組の名前は get_config_fingerprint() に入るので、同じ組で測った結果同士を比べられる
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        let tuning = profile.settings(BackendKind::WebGl2);
+        self.set_shader_precision(tuning.precision)?;
+        self.set_upload_strategy(tuning.upload_strategy)?;
+        self.set_draw_strategy(tuning.draw_strategy)?;
+        self.set_render_mode(tuning.render_mode)?;
+        self.set_point_size(tuning.point_size);
+        self.set_render_scale(tuning.render_scale);
+        match tuning.adaptive_lod {
+            Some((mode, target_render_ms)) => self.set_adaptive_lod(mode, target_render_ms),
+            None => self.set_lod(LodMode::Off, 1),
+        }
+        self.set_target_fps(tuning.target_fps);
+        self.tuning = Some(profile);
+        Ok(())
+    }
+
+    // 最後に apply_tuning_profile() で当てた組（当てていなければ undefined）
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        self.tuning
+    }
+
+    // 並べ方を選べるのも今のところ WebGL だけなので、Planar だけ
+    pub fn set_buffer_layout(&mut self, layout: BufferLayout) -> Result<(), JsValue> {
+        if layout != BufferLayout::Planar {
+            return Err(tr(Text::BufferLayoutUnavailable, &[&layout.name(), &"webgl2"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_buffer_layout(&self) -> BufferLayout {
+        BufferLayout::Planar
+    }
+
+    // 色をシェーダーで計算できるのは今のところ WebGL だけなので、Cpu だけ
+    pub fn set_color_mode(&mut self, mode: ColorPipeline) -> Result<(), JsValue> {
+        if mode != ColorPipeline::Cpu {
+            return Err(tr(Text::ColorModeUnavailable, &[&mode.name(), &"webgl2"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_color_mode(&self) -> ColorPipeline {
+        ColorPipeline::Cpu
+    }
+
+    // パーティクルの塗り方を切り替える（既定は RainbowCycle）
+    // colors は Palette と Monochrome の色を 0〜1 の r, g, b の順に並べたもの（ほかのモードでは空でよい）
+    // GPU で物理演算している間は RainbowCycle だけ
+    pub fn set_particle_color_mode(
+        &mut self,
+        mode: ColorMode,
+        colors: Vec<f32>,
+    ) -> Result<(), JsValue> {
+        if self.physics.is_some() && mode != ColorMode::RainbowCycle {
+            return Err(tr(Text::ColorModeConflict, &[&mode.name(), &"gpu physics"]).into());
+        }
+        self.sim.theme.set_mode(mode, &colors)?;
+        Ok(())
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        self.sim.theme.mode()
+    }
+
+    // 背景色を 0〜1 の RGB で設定する（有限でない値を渡すと既定の灰色に戻す）
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.sim.theme.set_background(r, g, b);
+    }
+
+    // [r, g, b]
+    pub fn get_background_color(&self) -> Vec<f32> {
+        self.sim.theme.background().to_vec()
+    }
+
+    // 透視投影できるのは今のところ WebGL と Canvas2D だけなので、Flat だけ
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) -> Result<(), JsValue> {
+        if mode != ProjectionMode::Flat {
+            return Err(tr(Text::ProjectionModeUnavailable, &[&mode.name(), &"webgl2"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        ProjectionMode::Flat
+    }
+
+    // パーティクルの物理演算を CPU と GPU（transform feedback）のどちらで行うかを切り替える（既定は Cpu）
+    // Gpu では毎フレームの CPU の計算と頂点データの送信がなくなる。引力点・風と乱流・障害物・エミッター・衝突と、虹色以外の塗り方は使えない
+    // Cpu に戻すと GPU で進めた位置と速度を読み戻して続きから計算する
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> Result<(), JsValue> {
+        match mode {
+            PhysicsMode::Gpu if self.physics.is_none() => {
+                let conflicts: Vec<&str> = [
+                    ("attractors", !self.sim.attractors.is_empty()),
+                    ("wind/turbulence", self.sim.forces.is_active()),
+                    ("obstacles", !self.sim.obstacles.is_empty()),
+                    ("emitters", !self.sim.emitters.is_empty()),
+                    ("collisions", self.sim.collisions()),
+                    ("particle color modes", !self.sim.theme.is_rainbow()),
+                ]
+                .into_iter()
+                .filter_map(|(name, enabled)| enabled.then_some(name))
+                .collect();
+                if !conflicts.is_empty() {
+                    return Err(tr(Text::GpuPhysicsConflict, &[&conflicts.join(", ")]).into());
+                }
+                let fragment = shader::with_precision(FRAGMENT_SHADER_SOURCE, self.precision);
+                self.physics = Some(GpuPhysics::new(&self.gl, &fragment)?);
+            }
+            PhysicsMode::Cpu => {
+                if let Some(physics) = self.physics.take() {
+                    let (state, base_hue) = physics.read_back(&self.gl);
+                    self.sim.load_state(&state, base_hue);
+                    physics.delete(&self.gl);
+                }
+            }
+            PhysicsMode::Gpu => {}
+        }
+        Ok(())
+    }
+
+    pub fn get_physics_mode(&self) -> PhysicsMode {
+        match self.physics {
+            Some(_) => PhysicsMode::Gpu,
+            None => PhysicsMode::Cpu,
+        }
+    }
+
+    // update() と爆発の計算を threads 個に分けて並列に行う（threads フィーチャーが必要）
+    // 実際に使う数を返す。フィーチャーなしでは1、ありでもプールのスレッド数までに丸める
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        let applied = self.sim.set_threads(threads);
+        if applied != threads {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ThreadCountClamped, &[&threads, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.sim.threads()
+    }
+
+    // 物理演算と色の変換を SIMD 版（4個ずつ）にするか切り替える。実際に使うかを返す
+    // simd フィーチャーなしでビルドしたときはスカラー版のまま
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        let applied = self.sim.set_simd(enabled);
+        if applied != enabled {
+            self.events
+                .emit(EventKind::FallbackUsed, &tr(Text::SimdUnavailable, &[]));
+        }
+        applied
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        self.sim.simd()
+    }
+
+    // パーティクル同士の衝突を切り替える（格子で近いものだけ調べるが、壁だけのときよりずっと重い）
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        self.sim.set_collisions(enabled);
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        self.sim.collisions()
+    }
+
+    // 全体に一様に吹く風（毎フレーム速度に足す px/フレーム²。重力と同じ単位）。(0, 0) で止める
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        self.sim.forces.set_wind(x, y);
+    }
+
+    // [x, y]
+    pub fn get_wind(&self) -> Vec<f32> {
+        let (x, y) = self.sim.forces.wind();
+        vec![x, y]
+    }
+
+    // パーティクルごとに curl ノイズの流れを加える（strength が 0 なら止める）
+    // scale は渦のおおよその大きさ(px)。場はゆっくり流れるので同じ場所でも向きが変わっていく
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        self.sim.forces.set_turbulence(strength, scale);
+    }
+
+    // [strength, scale]（止めていれば None）
+    pub fn get_turbulence(&self) -> Option<Vec<f32>> {
+        self.sim
+            .forces
+            .turbulence()
+            .map(|(strength, scale)| vec![strength, scale])
+    }
+
+    // 直近の update() で重なっていたパーティクルの組の数
+    pub fn get_contact_count(&self) -> usize {
+        self.sim.last_contacts()
+    }
+
+    // パーティクルのシェーダーの精度を切り替えて作り直す（作れなければ今のまま）
+    pub fn set_shader_precision(&mut self, precision: ShaderPrecision) -> Result<(), JsValue> {
+        let (program, _) = shader::get_or_create_program(
+            self.gl.unchecked_ref(),
+            &shader::with_precision(VERTEX_SHADER_SOURCE, precision),
+            &shader::with_precision(FRAGMENT_SHADER_SOURCE, precision),
+        )?;
+        self.program = program;
+        self.precision = precision;
+        Ok(())
+    }
+
+    pub fn get_shader_precision(&self) -> ShaderPrecision {
+        self.precision
+    }
+
+    // mediump と highp で同じ状態から frames フレーム描き、速さと最後のフレームの違いを比べる
+    // Particles シーンで使う（終わると元の精度に戻り、パーティクルは初期状態からやり直しになる）
+    pub fn compare_shader_precision(
+        &mut self,
+        frames: u32,
+    ) -> Result<PrecisionComparison, JsValue> {
+        let original = self.precision;
+        let frames = frames.max(1);
+        let mediump = self.run_precision(ShaderPrecision::Mediump, frames);
+        let highp = self.run_precision(ShaderPrecision::Highp, frames).ok();
+        self.set_shader_precision(original)?;
+        Ok(PrecisionComparison::new(frames, &mediump?, highp.as_ref()))
+    }
+}
+
+impl ParticleSystemWebGl2 {
+    fn run_precision(
+        &mut self,
+        precision: ShaderPrecision,
+        frames: u32,
+    ) -> Result<PrecisionRun, JsValue> {
+        self.set_shader_precision(precision)?;
+        rng::with_seed(shader::PRECISION_SEED, || self.reset());
+        let mut total_ms = 0.0;
+        for _ in 0..frames {
+            self.update();
+            let start = timing::now_ms();
+            self.render();
+            self.gl.finish();
+            total_ms += timing::now_ms() - start;
+        }
+        Ok(PrecisionRun {
+            mean_ms: total_ms / frames as f64,
+            pixels: shader::read_drawing_buffer(self.gl.unchecked_ref())?,
+        })
+    }
+
+    pub(crate) fn emit_event(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    // 出した描画命令が終わるまで待つ（待てたら true）
+    pub(crate) fn finish_gpu(&self) -> bool {
+        self.gl.finish();
+        true
+    }
+
+    // attach_input_handlers() で積まれた操作を取り出す（視点の操作はここで反映する）
+    pub(crate) fn take_input(&mut self) -> Vec<InputAction> {
+        match &self.input {
+            Some(input) => input.take_actions(&mut self.sim.camera),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn input_handlers(&self) -> Option<&InputHandlers> {
+        self.input.as_ref()
+    }
+
+    // 描いたフレームの上に rects を重ねる
+    pub(crate) fn draw_overlay(&mut self, rects: &[OverlayRect]) -> bool {
+        panel::draw_gl(self.gl.unchecked_ref(), rects, self.sizing.backing_ratio());
+        true
+    }
+
+    fn apply_input(&mut self) {
+        for action in self.take_input() {
+            match action {
+                InputAction::Explode { x, y } => self.explode(x, y),
+                InputAction::Stir { x, y, dx, dy } => self.stir(x, y, dx, dy),
+            }
+        }
+    }
+
+    pub(crate) fn advance_timestep(&mut self, dt_ms: f64) -> u32 {
+        self.sim.timestep.advance(dt_ms)
+    }
+
+    pub(crate) fn cap_frame(&mut self, timestamp: f64) -> bool {
+        self.sim.frame_cap.tick(timestamp)
+    }
+
+    // update_with_dt() のステップの後で、描く位置を補間する（シーン表示中は何もしない）
+    pub(crate) fn interpolate_frame(&mut self) {
+        if self.scene.is_none() && self.physics.is_none() {
+            self.sim.interpolate();
+        }
+    }
+
+    // 今のシーンがスプライトを描くアトラス（画像の渡し方を切り替えるのに使う）
+    pub(crate) fn scene_atlas_mut(&mut self) -> Option<&mut Atlas> {
+        self.scene.as_mut()?.atlas_mut()
+    }
+
+    // シミュレーション空間の大きさ（記録する座標を割合に直すのに使う）
+    pub(crate) fn size(&self) -> (f32, f32) {
+        (self.sim.width, self.sim.height)
+    }
+
+    // シーン用のGLリソースは最初に必要になったときに作る
+    // ANGLE_instanced_arrays は WebGL2 では取得できないので、シーンのスプライトはインスタンス描画しない
+    // GPU で物理演算を進めているときは CPU 側の状態を読み戻して揃える（GPU の処理を待つので重い）
+    fn read_back_physics(&mut self) {
+        if let Some(physics) = &self.physics {
+            let (state, base_hue) = physics.read_back(&self.gl);
+            self.sim.load_state(&state, base_hue);
+        }
+    }
+
+    // 戻ったコンテキストにシェーダー・バッファを作り直す（失われる前の設定のまま続ける）
+    // GPU の物理演算は最後に CPU 側へ読み戻した状態からやり直す
+    fn restore_context(&mut self) {
+        match self.restore_gl() {
+            Ok(()) => {
+                self.events.clear_reported();
+                self.events
+                    .emit(EventKind::ContextRestored, &tr(Text::ContextRestored, &[]));
+            }
+            Err(error) => self.events.emit(
+                EventKind::ContextLost,
+                &tr(Text::ContextRestoreFailed, &[&describe_error(&error)]),
+            ),
+        }
+    }
+
+    fn restore_gl(&mut self) -> Result<(), JsValue> {
+        self.program = shader::get_or_create_program(
+            self.gl.unchecked_ref(),
+            &shader::with_precision(VERTEX_SHADER_SOURCE, self.precision),
+            &shader::with_precision(FRAGMENT_SHADER_SOURCE, self.precision),
+        )?
+        .0;
+        (
+            self.vao,
+            self.corner_buffer,
+            self.position_buffer,
+            self.color_buffer,
+        ) = create_vertex_array(&self.gl)?;
+        self.trail_buffer = match self.render_mode {
+            RenderMode::Trails => Some(TrailBuffer::new(self.gl.unchecked_ref())?),
+            _ => None,
+        };
+        if self.physics.is_some() {
+            let fragment = shader::with_precision(FRAGMENT_SHADER_SOURCE, self.precision);
+            self.physics = Some(GpuPhysics::new(&self.gl, &fragment)?);
+        }
+        self.scene_gl = None;
+        if self.scene.is_some() {
+            self.ensure_scene_gl()?;
+        }
+        Ok(())
+    }
+
+    fn ensure_scene_gl(&mut self) -> Result<(), JsValue> {
+        if self.scene_gl.is_some() {
+            return Ok(());
+        }
+        let resources = SceneGl::new(self.gl.unchecked_ref())?;
+        if !resources.has_instancing() {
+            self.events.emit(
+                EventKind::ExtensionMissing,
+                &tr(Text::InstancingUnavailable, &[]),
+            );
+        }
+        self.scene_gl = Some(resources);
+        Ok(())
+    }
+
+    fn config_fingerprint(&self) -> ConfigFingerprint {
+        let mut config = ConfigFingerprint::new("webgl2");
+        config.field("strict", self.strict);
+        config.field("width", self.sim.width);
+        config.field("height", self.sim.height);
+        config.field("render_scale", self.sizing.render_scale());
+        config.field("out_of_bounds", self.sim.out_of_bounds.name());
+        config.field("boundary_mode", self.sim.boundary.name());
+        config.field("particle_color_mode", self.sim.theme.mode().name());
+        config.field("projection_mode", self.sim.projection().name());
+        config.field("gravity", self.sim.config.gravity);
+        config.field("bounce", self.sim.config.bounce);
+        config.field("friction", self.sim.config.friction);
+        config.field("point_size", self.sim.config.point_size);
+        config.field("threads", self.sim.threads());
+        config.field("simd", self.sim.simd());
+        config.field("collisions", self.sim.collisions());
+        config.field("attractors", self.sim.attractors.len());
+        config.field("forces", self.sim.forces.describe());
+        config.field("obstacles", self.sim.obstacles.len());
+        config.field("emitters", self.sim.emitters.len());
+        config.field("particle_lifetime", self.sim.emitters.lifetime());
+        config.field("strict_benchmark", !self.sim.cosmetic);
+        config.field("particle_style", self.sim.style().describe());
+        config.field("shader_precision", self.precision.name());
+        config.field("render_mode", self.render_mode.name());
+        config.optional("tuning_profile", self.tuning.map(TuningProfile::name));
+        config.field("physics_mode", self.get_physics_mode().name());
+        config.field("quirks", self.quirks.fingerprint());
+        config.optional(
+            "settle_threshold",
+            self.sim.settle.map(|settle| settle.threshold),
+        );
+        config.optional("seed", self.sim.seed);
+        config.field("scene", self.get_scene().name());
+        config.optional("load", self.load);
+        config.field("max_particles", self.sim.max_particles());
+        config.field("lod", self.sim.lod.describe());
+        config.field("particles", self.sim.particle_count);
+        config.optional(
+            "instancing",
+            self.scene_gl
+                .as_ref()
+                .map(|resources| resources.has_instancing()),
+        );
+        config
+    }
+
+    fn apply_backing_size(&self) {
+        if let Some(canvas) = self.gl.canvas() {
+            self.sizing.apply(&canvas, self.sim.width, self.sim.height);
+        }
+    }
+
+    fn apply_load(&mut self) {
+        let Some(load) = self.load else {
+            return;
+        };
+        match &mut self.scene {
+            Some(scene) => scene.set_load(load),
+            None => self.sim.set_load(load),
+        }
+    }
+
+    pub(crate) fn from_source(
+        source: &impl ContextSource<WebGl2RenderingContext>,
+        particle_count: usize,
+        options: BuildOptions,
+    ) -> Result<ParticleSystemWebGl2, JsValue> {
+        check_budget(particle_count, options.memory_budget)?;
+        let mut init = InitProgress::start();
+
+        let AcquiredContext {
+            context: gl,
+            width,
+            height,
+        } = source.acquire()?;
+        let context_loss = gl
+            .canvas()
+            .and_then(|canvas| ContextLossWatch::attach(&canvas).ok());
+        init.timings.context_ms = init.lap();
+
+        // シェーダーのコンパイル・リンクは WebGL1 と同じ API なので共通のキャッシュを使う
+        let (program, cache_hit) = shader::get_or_create_program(
+            gl.unchecked_ref::<WebGlRenderingContext>(),
+            VERTEX_SHADER_SOURCE,
+            FRAGMENT_SHADER_SOURCE,
+        )?;
+        init.timings.shader_cache_hits += cache_hit as u32;
+        gl.use_program(Some(&program));
+        init.timings.shader_ms = init.lap();
+
+        let (vao, corner_buffer, position_buffer, color_buffer) = create_vertex_array(&gl)?;
+        let positions = try_vec(particle_count * 2)?;
+        let colors = try_vec(particle_count * 3)?;
+        init.timings.buffers_ms = init.lap();
+
+        let mut sim = Simulation::with_capacity(width, height, particle_count)?;
+        sim.seed = options.seed;
+        if !options.progressive {
+            sim.spawn_pending(particle_count);
+        }
+        sim.config = options.simulation.clamped();
+        sim.memory_budget = options.memory_budget;
+        init.timings.particles_ms = init.lap();
+        init.finish_constructor();
+
+        shader::retain_context(gl.unchecked_ref());
+        Ok(ParticleSystemWebGl2 {
+            sim,
+            gl_state: GlState::new(gl.unchecked_ref()),
+            gl,
+            program,
+            vao,
+            corner_buffer,
+            position_buffer,
+            color_buffer,
+            init,
+            scene: None,
+            scene_gl: None,
+            positions,
+            colors,
+            load: None,
+            events: EventBus::default(),
+            strict: options.strict,
+            explosion: ExplosionConfig::default(),
+            precision: ShaderPrecision::default(),
+            render_mode: RenderMode::Normal,
+            trail_buffer: None,
+            // 点スプライトを使わないので gl_PointSize の上限の問題は起きない
+            quirks: Quirks::default(),
+            stats_stream: StatsStream::default(),
+            #[cfg(feature = "telemetry")]
+            telemetry: Telemetry::default(),
+            observers: Observers::default(),
+            timeline: Timeline::default(),
+            tuning: None,
+            sizing: CanvasSizing::default(),
+            input: None,
+            physics: None,
+            context_loss,
+        })
+    }
+}
+
+// プログラムは同じコンテキストの他のインスタンスと共有しているので、最後の1つを破棄したときに消す
+impl Drop for ParticleSystemWebGl2 {
+    fn drop(&mut self) {
+        let gl = &self.gl;
+        gl.delete_vertex_array(Some(&self.vao));
+        for buffer in [
+            &self.corner_buffer,
+            &self.position_buffer,
+            &self.color_buffer,
+        ] {
+            gl.delete_buffer(Some(buffer));
+        }
+        if let Some(resources) = &self.scene_gl {
+            resources.delete(gl.unchecked_ref());
+        }
+        if let Some(trail_buffer) = &self.trail_buffer {
+            trail_buffer.delete(gl.unchecked_ref());
+        }
+        if let Some(physics) = &self.physics {
+            physics.delete(gl);
+        }
+        shader::release_context(gl.unchecked_ref());
+    }
+}
+
+// 四角形の頂点（位置 0）と、インスタンスごとの位置（1）・色（2）を VAO に設定する
+// 戻り値は (VAO, 四角形の頂点, 位置, 色)
+fn create_vertex_array(
+    gl: &WebGl2RenderingContext,
+) -> Result<
+    (
+        WebGlVertexArrayObject,
+        WebGlBuffer,
+        WebGlBuffer,
+        WebGlBuffer,
+    ),
+    JsValue,
+> {
+    let vao = gl
+        .create_vertex_array()
+        .ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?;
+    let mut buffers = Vec::with_capacity(3);
+    for _ in 0..3 {
+        buffers.push(
+            gl.create_buffer()
+                .ok_or_else(|| tr(Text::BufferCreationFailed, &[]))?,
+        );
+    }
+    gl.bind_vertex_array(Some(&vao));
+
+    // 中心を原点とした一辺1の四角形（TRIANGLE_STRIP）
+    let corners: [f32; 8] = [-0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, 0.5];
+    gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffers[0]));
+    unsafe {
+        let array = js_sys::Float32Array::view(&corners);
+        gl.buffer_data_with_array_buffer_view(
+            WebGl2RenderingContext::ARRAY_BUFFER,
+            &array,
+            WebGl2RenderingContext::STATIC_DRAW,
+        );
+    }
+
+    // (location, 要素数, divisor)
+    for (index, (location, size, divisor)) in
+        [(0, 2, 0), (1, 2, 1), (2, 3, 1)].into_iter().enumerate()
+    {
+        gl.bind_buffer(WebGl2RenderingContext::ARRAY_BUFFER, Some(&buffers[index]));
+        gl.enable_vertex_attrib_array(location);
+        gl.vertex_attrib_pointer_with_i32(
+            location,
+            size,
+            WebGl2RenderingContext::FLOAT,
+            false,
+            0,
+            0,
+        );
+        gl.vertex_attrib_divisor(location, divisor);
+    }
+    gl.bind_vertex_array(None);
+
+    let color_buffer = buffers.pop().unwrap();
+    let position_buffer = buffers.pop().unwrap();
+    let corner_buffer = buffers.pop().unwrap();
+    Ok((vao, corner_buffer, position_buffer, color_buffer))
+}
+
+// 頂点シェーダー（GLSL ES 3.00、属性の位置は create_vertex_array() と合わせる）
+const VERTEX_SHADER_SOURCE: &str = r#"#version 300 es
+    layout(location = 0) in vec2 a_corner;
+    layout(location = 1) in vec2 a_position;
+    layout(location = 2) in vec3 a_color;
+    uniform vec2 u_size;
+    out vec3 v_color;
+
+    void main() {
+        gl_Position = vec4(a_position + a_corner * u_size, 0.0, 1.0);
+        v_color = a_color;
+    }
+"#;
+
+// フラグメントシェーダー
+const FRAGMENT_SHADER_SOURCE: &str = r#"#version 300 es
+    precision mediump float;
+    in vec3 v_color;
+    out vec4 out_color;
+
+    void main() {
+        out_color = vec4(v_color, 0.8);
+    }
+"#;
@@ -0,0 +1,1521 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::attractor::Attractor;
+use crate::backend::BackendKind;
+use crate::bounds::{BoundaryMode, OutOfBounds};
+use crate::camera::Camera;
+use crate::capture::CapturedBuffer;
+use crate::color_pipeline::ColorPipeline;
+use crate::color_theme::ColorMode;
+use crate::context::{AcquiredContext, CanvasById, CanvasElement, ContextSource};
+use crate::draw_strategy::DrawStrategy;
+use crate::emitter::Emitter;
+use crate::error::BenchError;
+use crate::events::{BenchmarkEvent, EventBus, EventKind};
+use crate::explosion::ExplosionConfig;
+use crate::export;
+use crate::fingerprint::ConfigFingerprint;
+use crate::gpu::{
+    self, array, object, GpuAdapter, GpuBuffer, GpuCanvasContext, GpuDevice, GpuRenderPipeline,
+};
+use crate::gpu_physics::PhysicsMode;
+use crate::i18n::{tr, Text};
+use crate::init::{progress, BuildOptions, InitProgress, InitTimings};
+use crate::input::{InputAction, InputHandlers};
+use crate::lod::LodMode;
+use crate::memory::{check_budget, try_vec};
+use crate::observers::Observers;
+use crate::obstacle::Obstacle;
+use crate::panel::OverlayRect;
+use crate::particle_shape::ParticleShape;
+use crate::particle_style::ParticleStyle;
+use crate::physics::SimulationConfig;
+use crate::picking::ParticleInfo;
+use crate::projection::ProjectionMode;
+use crate::quirks::Quirks;
+use crate::render_mode::{CompositeMode, RenderMode};
+use crate::resize::CanvasSizing;
+use crate::scene::{describe_scene, Atlas, SceneDescription, SceneKind};
+use crate::simulation::Simulation;
+use crate::snapshot;
+use crate::stats_stream::StatsStream;
+#[cfg(feature = "telemetry")]
+use crate::telemetry::Telemetry;
+use crate::timeline::Timeline;
+use crate::timing;
+use crate::tuning::TuningProfile;
+use crate::upload::{BufferLayout, UploadStrategy};
+use crate::webgl::{fade_colors, pack_vertices};
+
+// WebGPU のレンダーパイプラインでパーティクルを描くバックエンド
+// 物理演算は他のバックエンドと同じくWASMで行い、WebGL2 と同じインスタンス描画の四角形で描くので、
+// 同じ負荷で API ごとの差だけを比べられる（パーティクル以外のシーンには対応していない）
+// アダプターとデバイスの取得が非同期なので、生成は create() の Promise で行う
+#[wasm_bindgen]
+pub struct ParticleSystemWebGpu {
+    sim: Simulation,
+    device: GpuDevice,
+    context: GpuCanvasContext,
+    pipeline: GpuRenderPipeline,
+    // 四角形の頂点と、インスタンスごとの位置・色
+    corner_buffer: GpuBuffer,
+    position_buffer: GpuBuffer,
+    color_buffer: GpuBuffer,
+    // 位置と色のバッファに入るインスタンスの数（障害物の輪郭の点が足りなくなったら作り直す）
+    instance_capacity: usize,
+    init: InitProgress,
+    // 毎フレーム使い回すインスタンスデータ
+    positions: Vec<f32>,
+    colors: Vec<f32>,
+    // set_load() で指定した負荷
+    load: Option<f32>,
+    events: EventBus,
+    strict: bool,
+    // explode() の強さ・範囲・減衰
+    explosion: ExplosionConfig,
+    // 検出したブラウザ固有の問題と回避策
+    quirks: Quirks,
+    // subscribe_stats() の購読
+    stats_stream: StatsStream,
+    // start_telemetry_channel() と start_telemetry_socket() の送り先
+    #[cfg(feature = "telemetry")]
+    telemetry: Telemetry,
+    // on_frame() などで登録されたコールバック
+    observers: Observers,
+    // 直近のフレーム時間と annotate() の印
+    timeline: Timeline,
+    // apply_tuning_profile() で当てた設定の組
+    tuning: Option<TuningProfile>,
+    // 描画バッファの倍率と ResizeObserver
+    sizing: CanvasSizing,
+    // attach_input_handlers() で登録した入力のコールバック
+    input: Option<InputHandlers>,
+}
+
+#[wasm_bindgen]
+impl ParticleSystemWebGpu {
+    // WebGPU に対応していなければ reject する
+    pub async fn create(
+        canvas_id: String,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGpu, JsValue> {
+        Self::create_from_canvas(CanvasById(&canvas_id).canvas()?, particle_count).await
+    }
+
+    // フレームワークが持っている canvas 要素から生成（id が無くても、ドキュメントに追加前でもよい）
+    pub async fn create_from_canvas(
+        canvas: web_sys::HtmlCanvasElement,
+        particle_count: usize,
+    ) -> Result<ParticleSystemWebGpu, JsValue> {
+        Self::from_source(
+            &CanvasElement(&canvas),
+            particle_count,
+            BuildOptions::default(),
+        )
+        .await
+    }
+
+    pub fn update(&mut self) {
+        self.apply_input();
+        if self.sim.paused {
+            return;
+        }
+        self.sim.step_with(|_| {});
+    }
+
+    // update() で物理演算を止める（描画・入力による視点の操作・爆発の受け付けは続ける）
+    pub fn pause(&mut self) {
+        self.sim.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.sim.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.sim.paused
+    }
+
+    // 止めていても n ステップだけ update() と同じように進める（描画はしない）
+    pub fn step(&mut self, n: u32) {
+        let paused = std::mem::replace(&mut self.sim.paused, false);
+        for _ in 0..n {
+            self.update();
+        }
+        self.sim.paused = paused;
+    }
+
+    // dt_ms 経過した分だけ固定長のステップで update() し、進めたステップ数を返す
+    // 物理の進み方がリフレッシュレートに依らなくなる。描画は持ち越した端数の分だけ直前のステップとの間を補間する
+    pub fn update_with_dt(&mut self, dt_ms: f64) -> u32 {
+        let steps = self.advance_timestep(dt_ms);
+        for _ in 0..steps {
+            self.update();
+        }
+        self.interpolate_frame();
+        steps
+    }
+
+    // 1ステップの長さ(ms)を設定し、実際に使う値を返す（既定は 60Hz の1フレーム）
+    pub fn set_fixed_timestep(&mut self, step_ms: f64) -> f64 {
+        self.sim.timestep.set_step_ms(step_ms)
+    }
+
+    pub fn get_fixed_timestep(&self) -> f64 {
+        self.sim.timestep.step_ms()
+    }
+
+    // requestAnimationFrame の timestamp(ms) を渡して1回分進める
+    // 物理は毎回 update() で進め、描画は set_target_fps() の上限を超えない回だけ行う。描いたら true
+    pub fn tick(&mut self, timestamp: f64) -> bool {
+        self.update();
+        let render = self.cap_frame(timestamp);
+        if render {
+            self.render();
+        }
+        render
+    }
+
+    // 描画の上限(fps)を設定し、実際に使う値を返す（0 以下なら上限なし。既定は上限なし）
+    pub fn set_target_fps(&mut self, fps: f64) -> f64 {
+        self.sim.frame_cap.set_target_fps(fps)
+    }
+
+    pub fn get_target_fps(&self) -> f64 {
+        self.sim.frame_cap.target_fps()
+    }
+
+    // tick() の間隔から推定した表示のリフレッシュレート(Hz)（まだ分からなければ 0）
+    pub fn get_refresh_rate(&self) -> f64 {
+        self.sim.frame_cap.refresh_rate()
+    }
+
+    // set_target_fps() してから tick() で描いた回数と、上限のために描かなかった回数
+    pub fn get_rendered_ticks(&self) -> u32 {
+        self.sim.frame_cap.rendered
+    }
+
+    pub fn get_skipped_ticks(&self) -> u32 {
+        self.sim.frame_cap.skipped
+    }
+
+    pub fn render(&mut self) {
+        if let Some((width, height)) = self.sizing.take_pending() {
+            let _ = self.resize(width, height);
+        }
+        if let Some(message) = self.quirks.note_frame() {
+            self.events.emit(EventKind::QuirkApplied, &message);
+        }
+        let frame_count = self.get_frame_count();
+        self.stats_stream.note_frame(frame_count);
+        #[cfg(feature = "telemetry")]
+        if self.telemetry.note_frame() {
+            self.telemetry
+                .send(None, "webgpu", self.get_particle_count());
+        }
+        self.observers.note_frame(frame_count);
+        self.timeline.note_frame(frame_count);
+
+        let start = timing::now_ms();
+        self.sim.prepare_visible();
+        pack_vertices(&self.sim, &mut self.positions, &mut self.colors);
+        fade_colors(
+            self.sim.visible(),
+            &self.sim.style(),
+            self.sim.frame_count,
+            self.sim.theme.background(),
+            &mut self.colors,
+        );
+        // 障害物の輪郭はパーティクルと同じ大きさの点として後ろに足す
+        self.sim.obstacles.pack_outline(
+            self.sim.config.point_diameter(),
+            (self.sim.width, self.sim.height),
+            self.sim.camera,
+            &mut self.positions,
+            &mut self.colors,
+        );
+        self.init
+            .mark_frame(self.sim.spawned() == self.sim.particle_count);
+
+        // デバイスを失ったなどで描けないフレームは飛ばす（次の render() でまた試す）
+        if self
+            .reserve_instances()
+            .and_then(|_| self.encode_frame())
+            .is_err()
+        {
+            self.events
+                .emit(EventKind::ContextLost, &tr(Text::ContextLost, &[]));
+        }
+        let particle_count = self.sim.particle_count;
+        self.sim
+            .lod
+            .record_render(timing::now_ms() - start, particle_count);
+    }
+
+    pub fn simulate_frames(&mut self, n: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..n {
+            self.update();
+        }
+        let elapsed = timing::now_ms() - start;
+        self.observers
+            .note_complete(n, elapsed, false, self.get_frame_count());
+        elapsed
+    }
+
+    // 最初の frames フレームを今すぐ物理演算と描画まで進める（経過時間(ms)を返す）
+    // シェーダーのコンパイルや JIT・バッファの確保を計測の前に済ませる
+    pub fn warmup(&mut self, frames: u32) -> f64 {
+        let start = timing::now_ms();
+        for _ in 0..frames {
+            self.update();
+            self.render();
+        }
+        timing::now_ms() - start
+    }
+
+    pub fn simulate_until_settled(&mut self, threshold: f32, max_frames: u32) -> f64 {
+        self.set_settle_threshold(Some(threshold));
+        let start = timing::now_ms();
+        let mut frames = 0;
+        for _ in 0..max_frames {
+            self.update();
+            frames += 1;
+            if self.is_settled() {
+                break;
+            }
+        }
+        let elapsed = timing::now_ms() - start;
+        let (settled, frame_count) = (self.is_settled(), self.get_frame_count());
+        self.observers
+            .note_complete(frames, elapsed, settled, frame_count);
+        elapsed
+    }
+
+    pub fn set_settle_threshold(&mut self, threshold: Option<f32>) {
+        self.sim.set_settle_threshold(threshold);
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.get_frames_until_settle().is_some()
+    }
+
+    pub fn get_frames_until_settle(&self) -> Option<u32> {
+        self.sim
+            .settle
+            .and_then(|settle| settle.frames_until_settle())
+    }
+
+    pub fn get_mean_speed(&self) -> f32 {
+        self.sim.mean_speed()
+    }
+
+    pub fn init_chunk(&mut self, max_particles: usize) -> f32 {
+        let start = timing::now_ms();
+        self.sim.spawn_pending(max_particles);
+        self.init.timings.particles_ms += timing::now_ms() - start;
+        let (spawned, total) = (self.sim.spawned(), self.sim.particle_count);
+        self.init.report(spawned, total);
+        progress(spawned, total)
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.sim.spawned() == self.sim.particle_count
+    }
+
+    pub fn get_time_to_first_frame(&self) -> f64 {
+        self.init.time_to_first_frame()
+    }
+
+    pub fn get_init_timings(&self) -> InitTimings {
+        self.init.timings()
+    }
+
+    pub fn get_frame_count(&self) -> u32 {
+        self.sim.frame_count
+    }
+
+    // パーティクル以外のシーンはこのバックエンドでは描けない
+    pub fn switch_scene(&mut self, kind: SceneKind) -> Result<(), JsValue> {
+        if kind != SceneKind::Particles {
+            return Err(tr(Text::SceneUnavailable, &[&kind.name(), &"webgpu"]).into());
+        }
+        self.events.clear_reported();
+        self.reset();
+        self.apply_load();
+        Ok(())
+    }
+
+    pub fn switch_to_point_size(&mut self, _count: usize, _size: f32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::PointSize.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_texture_upload(&mut self, _scale: f32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::TextureUpload.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_readback(&mut self, _scale: f32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::Readback.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_clip(&mut self, _regions: u32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::Clip.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_compositing(&mut self, _mode: CompositeMode) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::Compositing.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_shadows(&mut self, _shadow_blur: f32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::Shadows.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    pub fn switch_to_state_changes(&mut self, _change_every: u32) -> Result<(), JsValue> {
+        Err(tr(
+            Text::SceneUnavailable,
+            &[&SceneKind::StateChanges.name(), &"webgpu"],
+        )
+        .into())
+    }
+
+    // 点のシーンは描けないが、パーティクルの四角形の大きさに上限はない
+    pub fn get_max_point_size(&self) -> f32 {
+        f32::INFINITY
+    }
+
+    pub fn set_load(&mut self, load: f32) {
+        if !(0.0..=1.0).contains(&load) {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::LoadClamped, &[&load]),
+            );
+        }
+        self.load = Some(load.clamp(0.0, 1.0));
+        self.apply_load();
+    }
+
+    pub fn get_load(&self) -> Option<f32> {
+        self.load
+    }
+
+    // 動いているパーティクルの数をその場で増減する（バッファの作り直しやリセットはしない）
+    // 作成時の数までで、それより多ければ丸めて ParameterClamped を送る。実際の数を返す
+    pub fn set_particle_count(&mut self, count: usize) -> usize {
+        let applied = self.sim.set_particle_count(count);
+        if applied != count {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ParticleCountClamped, &[&count, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.sim.particle_count
+    }
+
+    // 頂点バッファは作成時の数で作ってあるので、それを超えては増やせない
+    pub fn grow_particle_count(&mut self, count: usize) -> Result<usize, JsValue> {
+        if count > self.sim.max_particles() {
+            return Err(tr(Text::ParticleGrowthUnavailable, &[&"webgpu"]).into());
+        }
+        Ok(self.set_particle_count(count))
+    }
+
+    // set_particle_count() で増やせる上限（作成時の数）
+    pub fn get_max_particles(&self) -> usize {
+        self.sim.max_particles()
+    }
+
+    pub fn export_animation(&self, frames: u32, every_n: u32) -> Result<Vec<u8>, JsValue> {
+        export::export_scene_animation(
+            SceneKind::Particles,
+            self.sim.width as u32,
+            self.sim.height as u32,
+            self.sim.max_particles(),
+            self.load,
+            frames,
+            every_n,
+        )
+    }
+
+    // 描いたテクスチャを読むには非同期のバッファのマップが要るので、同期では読み戻せない
+    pub fn capture_frame(&self) -> Result<Vec<u8>, JsValue> {
+        Err(tr(Text::FrameCaptureUnavailable, &[&"webgpu"]).into())
+    }
+
+    // WebGPU はパーティクル描画だけなので、取り出せる途中のバッファはない
+    pub fn capture_debug_buffers(&self) -> Result<Vec<CapturedBuffer>, JsValue> {
+        Ok(Vec::new())
+    }
+
+    pub fn get_config_fingerprint(&self) -> String {
+        self.config_fingerprint().hash()
+    }
+
+    pub fn get_config_summary(&self) -> String {
+        self.config_fingerprint().into_text()
+    }
+
+    pub fn poll_events(&mut self) -> Vec<BenchmarkEvent> {
+        self.events.drain()
+    }
+
+    pub fn set_event_callback(&mut self, callback: js_sys::Function) {
+        self.events.set_callback(callback);
+    }
+
+    pub fn clear_event_callback(&mut self) {
+        self.events.clear_callback();
+    }
+
+    pub fn subscribe_stats(&mut self, callback: js_sys::Function, interval_ms: f64) {
+        self.stats_stream.subscribe(callback, interval_ms);
+    }
+
+    pub fn unsubscribe_stats(&mut self) {
+        self.stats_stream.unsubscribe();
+    }
+
+    // 1秒ごとに計測値のまとめ（JSON の文字列）を BroadcastChannel(name) に送る（telemetry フィーチャー）
+    // 同じオリジンの別のタブで new BroadcastChannel(name) を開けば、複数の端末やタブの結果を1か所で見られる
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_channel(&mut self, name: &str) -> Result<(), JsValue> {
+        self.telemetry.start_channel(name)?;
+        Ok(())
+    }
+
+    // 同じまとめを WebSocket で集計サーバーに送る（url は ws:// か wss://）
+    #[cfg(feature = "telemetry")]
+    pub fn start_telemetry_socket(&mut self, url: &str) -> Result<(), JsValue> {
+        self.telemetry.start_socket(url)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "telemetry")]
+    pub fn stop_telemetry(&mut self) {
+        self.telemetry.stop();
+    }
+
+    // render() のたびに callback(FrameNotice) を呼ぶ
+    pub fn on_frame(&mut self, callback: js_sys::Function) {
+        self.observers.on_frame(callback);
+    }
+
+    // simulate_frames()・simulate_until_settled() が終わったら callback(BenchmarkComplete) を呼ぶ
+    pub fn on_benchmark_complete(&mut self, callback: js_sys::Function) {
+        self.observers.on_benchmark_complete(callback);
+    }
+
+    // 500ms ごとの FPS が threshold を下回ったら callback(FpsDrop) を呼ぶ（上回るまでは1回だけ）
+    pub fn on_fps_drop(&mut self, threshold: f64, callback: js_sys::Function) {
+        self.observers.on_fps_drop(threshold, callback);
+    }
+
+    // on_frame()・on_benchmark_complete()・on_fps_drop() の登録をすべて外す
+    pub fn clear_observers(&mut self) {
+        self.observers.clear();
+    }
+
+    // ホストのページの出来事（クリック・データの読み込み・タブへの復帰など）を今の時刻の印として残す
+    // 印は直近のフレーム時間と一緒に get_timeline_json() と export_results() に書き出される
+    pub fn annotate(&mut self, label: &str) {
+        let frame_count = self.get_frame_count();
+        self.timeline.annotate(label, frame_count);
+    }
+
+    // 直近のフレーム時間と annotate() の印（時刻はどちらも performance.now() の ms）
+    pub fn get_timeline_json(&self) -> String {
+        self.timeline.to_json()
+    }
+
+    pub fn clear_timeline(&mut self) {
+        self.timeline.clear();
+    }
+
+    // WebGL のコンテキストの喪失だけを数える（WebGPU のデバイスの喪失は見ていないので、常に false）
+    pub fn context_lost(&self) -> bool {
+        false
+    }
+
+    pub fn get_context_losses(&self) -> u32 {
+        0
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        SceneKind::Particles
+    }
+
+    pub fn describe_current_scene(&self) -> SceneDescription {
+        describe_scene(SceneKind::Particles)
+    }
+
+    pub fn reset(&mut self) {
+        self.sim.charges.clear();
+        self.sim.reset();
+    }
+
+    // パーティクルの位置・速度・色相・寿命とフレーム数をバイト列に書き出す
+    // restore() に渡せば、どのバックエンドでも同じ途中の状態から続けられる（爆発の取り消しなどにも使える）
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.sim.snapshot()
+    }
+
+    // snapshot() の状態に戻す（パーティクル数も保存したときの数になる。シーンの状態は含まない）
+    pub fn restore(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        self.sim.restore(snapshot::decode(bytes)?)?;
+        Ok(())
+    }
+
+    // (x, y) から radius 以内にあるパーティクルの数（座標はシミュレーションの px。カメラで動かした画面の位置とは違う）
+    pub fn query_radius(&mut self, x: f32, y: f32, radius: f32) -> usize {
+        self.sim.count_within(x, y, radius)
+    }
+
+    // (x, y) に一番近いパーティクルのインデックス（パーティクルがなければ undefined）
+    pub fn nearest_particle(&mut self, x: f32, y: f32) -> Option<u32> {
+        self.sim.nearest(x, y).map(|index| index as u32)
+    }
+
+    // index 番目のパーティクルの位置・速度・色相・寿命（範囲外なら undefined）
+    pub fn get_particle(&self, index: u32) -> Option<ParticleInfo> {
+        self.sim.particle_info(index as usize)
+    }
+
+    // 以後の生成と reset() の乱数を seed から作り、パーティクルを作り直す
+    // 同じシードなら実行やバックエンドが違っても同じ初期状態から始まる
+    pub fn set_seed(&mut self, seed: u64) {
+        self.sim.seed = Some(seed);
+        self.reset();
+    }
+
+    // 乱数を毎回違うものに戻す（今のパーティクルはそのまま）
+    pub fn clear_seed(&mut self) {
+        self.sim.seed = None;
+    }
+
+    pub fn get_seed(&self) -> Option<u64> {
+        self.sim.seed
+    }
+
+    pub fn explode(&mut self, click_x: f32, click_y: f32) {
+        self.sim.explode(click_x, click_y, &self.explosion);
+    }
+
+    pub fn implode(&mut self, x: f32, y: f32) {
+        self.sim.explode(x, y, &self.explosion.inverted());
+    }
+
+    // (x, y) の近くのパーティクルを (dx, dy) の向きに押し流す
+    pub fn stir(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.sim.stir(x, y, dx, dy);
+    }
+
+    pub fn pointer_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        self.sim.pointer.push_move(x, y, dx, dy);
+    }
+
+    pub fn pointer_drag(&mut self, x: f32, y: f32, strength: f32) {
+        self.sim.pointer.push_drag(x, y, strength);
+    }
+
+    pub fn schedule_explosion(&mut self, x: f32, y: f32, delay_frames: u32) {
+        if delay_frames == 0 {
+            self.explode(x, y);
+            return;
+        }
+        self.sim
+            .charges
+            .schedule(x, y, delay_frames, self.explosion);
+    }
+
+    pub fn get_pending_explosions(&self) -> usize {
+        self.sim.charges.len()
+    }
+
+    // 引力点を置いて番号を返す。update() のたびに半径の中のパーティクルを中心へ引き寄せる
+    // （strength が負なら押しのける。マウスで引きずるときは move_attractor() で動かす）
+    pub fn add_attractor(&mut self, x: f32, y: f32, strength: f32, radius: f32) -> u32 {
+        self.sim.attractors.add(x, y, strength, radius)
+    }
+
+    // 見つからなければ false
+    pub fn remove_attractor(&mut self, id: u32) -> bool {
+        self.sim.attractors.remove(id)
+    }
+
+    pub fn move_attractor(&mut self, id: u32, x: f32, y: f32) -> bool {
+        self.sim.attractors.move_to(id, x, y)
+    }
+
+    pub fn clear_attractors(&mut self) {
+        self.sim.attractors.clear();
+    }
+
+    pub fn get_attractors(&self) -> Vec<Attractor> {
+        self.sim.attractors.list()
+    }
+
+    // 中心 (x, y)・半径 radius の円の障害物を置く。update() のたびに当たったパーティクルを跳ね返す
+    // （跳ね返りの割合は壁と同じ bounce。NaN などや半径が 0 以下なら置かずに false）
+    pub fn add_circle_obstacle(&mut self, x: f32, y: f32, radius: f32) -> bool {
+        self.sim.obstacles.add_circle(x, y, radius)
+    }
+
+    // (x0, y0)〜(x1, y1) の線分の障害物を置く（1ステップで横切るほど速いパーティクルも跳ね返す）
+    pub fn add_line_obstacle(&mut self, x0: f32, y0: f32, x1: f32, y1: f32) -> bool {
+        self.sim.obstacles.add_line(x0, y0, x1, y1)
+    }
+
+    pub fn clear_obstacles(&mut self) {
+        self.sim.obstacles.clear();
+    }
+
+    pub fn get_obstacles(&self) -> Vec<Obstacle> {
+        self.sim.obstacles.list()
+    }
+
+    // 毎フレーム rate 個ずつパーティクルを出すエミッターを置いて番号を返す
+    // 出したパーティクルは寿命が減るにつれて薄くなり、尽きると同じ場所を使って出し直される
+    // （最初の1つを置くと、今いるパーティクルも順に尽きてエミッターのものに入れ替わる）
+    pub fn add_emitter(&mut self, x: f32, y: f32, rate: f32, spread: f32, speed: f32) -> u32 {
+        self.sim.add_emitter(x, y, rate, spread, speed)
+    }
+
+    // 見つからなければ false
+    pub fn remove_emitter(&mut self, id: u32) -> bool {
+        self.sim.emitters.remove(id)
+    }
+
+    pub fn clear_emitters(&mut self) {
+        self.sim.emitters.clear();
+    }
+
+    pub fn get_emitters(&self) -> Vec<Emitter> {
+        self.sim.emitters.list()
+    }
+
+    // エミッターから出したパーティクルの累計
+    pub fn get_emitted_count(&self) -> f64 {
+        self.sim.emitters.emitted as f64
+    }
+
+    // 放出したパーティクルの寿命(フレーム)を設定し、実際に使う値を返す（1フレーム以上）
+    pub fn set_particle_lifetime(&mut self, frames: f32) -> f32 {
+        self.sim.emitters.set_lifetime(frames)
+    }
+
+    pub fn get_particle_lifetime(&self) -> f32 {
+        self.sim.emitters.lifetime()
+    }
+
+    pub fn resize(&mut self, width: f32, height: f32) -> Result<(), JsValue> {
+        if !(width >= 1.0 && height >= 1.0 && width.is_finite() && height.is_finite()) {
+            return Err(tr(Text::InvalidSize, &[&width, &height]).into());
+        }
+        self.sim.resize(width, height);
+        self.apply_backing_size();
+        self.apply_load();
+        Ok(())
+    }
+
+    pub fn set_pixel_ratio(&mut self, ratio: f32) -> f32 {
+        let ratio = self.sizing.set_pixel_ratio(ratio);
+        self.apply_backing_size();
+        ratio
+    }
+
+    pub fn get_pixel_ratio(&self) -> f32 {
+        self.sizing.pixel_ratio()
+    }
+
+    // 描画バッファを pixel_ratio のさらに scale 倍にする（2 なら 4 倍の画素を描くスーパーサンプリング、
+    // 0.5 なら 1/4 の画素を描いて拡大表示）。表示の大きさとシミュレーションの座標は変わらない
+    // 範囲外の値は 0.25〜4 に丸め、実際に使う値を返す
+    pub fn set_render_scale(&mut self, scale: f32) -> f32 {
+        let scale = self.sizing.set_render_scale(scale);
+        self.apply_backing_size();
+        scale
+    }
+
+    pub fn get_render_scale(&self) -> f32 {
+        self.sizing.render_scale()
+    }
+
+    pub fn observe_resize(&mut self) -> Result<(), JsValue> {
+        let canvas = Some(JsValue::from(self.context.canvas()))
+            .ok_or_else(|| tr(Text::ResizeObserverUnavailable, &[]))?;
+        self.sizing.observe(&canvas)
+    }
+
+    pub fn unobserve_resize(&mut self) {
+        self.sizing.unobserve();
+    }
+
+    // キャンバスのポインター・ホイール・タッチのイベントを受け取り、次の update() で
+    // クリック（タップ）は explode()、ドラッグは stir()、ホイール・ピンチ・シフト＋ドラッグは視点の移動にする
+    pub fn attach_input_handlers(&mut self) -> Result<(), JsValue> {
+        self.input = Some(InputHandlers::attach(&self.context.canvas().into())?);
+        Ok(())
+    }
+
+    pub fn detach_input_handlers(&mut self) {
+        self.input = None;
+    }
+
+    // 頂点バッファを解放して破棄する（以後このインスタンスは使えない）
+    pub fn dispose(self) {
+        for buffer in [
+            &self.corner_buffer,
+            &self.position_buffer,
+            &self.color_buffer,
+        ] {
+            buffer.destroy();
+        }
+    }
+
+    // 視点を元に戻す（拡大率 1、平行移動なし）
+    pub fn reset_camera(&mut self) {
+        self.sim.camera = Camera::default();
+    }
+
+    pub fn get_camera_zoom(&self) -> f32 {
+        self.sim.camera.zoom
+    }
+
+    pub fn strict_benchmark(&mut self, enabled: bool) {
+        self.sim.cosmetic = !enabled;
+    }
+
+    pub fn is_strict_benchmark(&self) -> bool {
+        !self.sim.cosmetic
+    }
+
+    pub fn get_quirks(&self) -> Vec<String> {
+        self.quirks
+            .applied()
+            .iter()
+            .map(|quirk| quirk.name().to_string())
+            .collect()
+    }
+
+    pub fn set_out_of_bounds(&mut self, policy: OutOfBounds) {
+        self.sim.out_of_bounds = policy;
+    }
+
+    pub fn get_out_of_bounds(&self) -> OutOfBounds {
+        self.sim.out_of_bounds
+    }
+
+    // 壁に届いたパーティクルを跳ね返すか・折り返すか・生成し直すか・そのまま出すか
+    pub fn set_boundary_mode(&mut self, mode: BoundaryMode) {
+        self.sim.boundary = mode;
+    }
+
+    pub fn get_boundary_mode(&self) -> BoundaryMode {
+        self.sim.boundary
+    }
+
+    pub fn set_explosion_config(&mut self, config: &ExplosionConfig) {
+        self.explosion = *config;
+    }
+
+    pub fn get_explosion_config(&self) -> ExplosionConfig {
+        self.explosion
+    }
+
+    pub fn set_simulation_config(&mut self, config: &SimulationConfig) {
+        self.sim.config = config.clamped();
+    }
+
+    pub fn get_simulation_config(&self) -> SimulationConfig {
+        self.sim.config
+    }
+
+    // デモのスライダー用に1項目ずつ変える
+    pub fn set_gravity(&mut self, gravity: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            gravity,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_bounce(&mut self, bounce: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            bounce,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_friction(&mut self, friction: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            friction,
+            ..self.sim.config
+        });
+    }
+
+    pub fn set_point_size(&mut self, point_size: f32) {
+        self.set_simulation_config(&SimulationConfig {
+            point_size,
+            ..self.sim.config
+        });
+    }
+
+    // 四角形のインスタンスで描くのでパーティクルの形は Square だけ
+    pub fn set_particle_shape(&mut self, shape: ParticleShape) -> Result<(), JsValue> {
+        if shape != ParticleShape::Square {
+            return Err(tr(Text::ParticleShapeUnavailable, &[&shape.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_particle_shape(&self) -> ParticleShape {
+        ParticleShape::Square
+    }
+
+    // スプライトを描けないので画像は受け取らない
+    pub fn set_particle_texture(&mut self, _image: &JsValue) -> Result<(), JsValue> {
+        self.set_particle_shape(ParticleShape::Sprite)
+    }
+
+    pub fn clear_particle_texture(&mut self) -> Result<(), JsValue> {
+        Ok(())
+    }
+
+    // インスタンスの大きさはそろっているので、不透明度だけを変えられる（色に混ぜる）
+    pub fn set_particle_style(&mut self, style: &ParticleStyle) -> Result<(), JsValue> {
+        if style.sanitized().varies_size() {
+            return Err(tr(
+                Text::ParticleStyleUnavailable,
+                &[&style.describe(), &"webgpu"],
+            )
+            .into());
+        }
+        self.sim.set_style(*style);
+        Ok(())
+    }
+
+    pub fn get_particle_style(&self) -> ParticleStyle {
+        self.sim.style()
+    }
+
+    // パイプラインのブレンドは作るときに決まるので、合成方法は Normal だけ
+    pub fn set_render_mode(&mut self, mode: RenderMode) -> Result<(), JsValue> {
+        if mode != RenderMode::Normal {
+            return Err(tr(Text::RenderModeUnavailable, &[&mode.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_render_mode(&self) -> RenderMode {
+        RenderMode::Normal
+    }
+
+    // 描くパーティクルを skip 個に1個に間引く（物理演算はすべてに行う。mode が Off か skip が 1 なら間引かない）
+    // シーンの表示中は間引かない
+    pub fn set_lod(&mut self, mode: LodMode, skip: u32) {
+        self.sim.lod.set_fixed(mode, skip);
+    }
+
+    // render() の時間が target_render_ms を超えないように、間引く間隔を AutoScaler と同じ探し方で決める
+    pub fn set_adaptive_lod(&mut self, mode: LodMode, target_render_ms: f64) {
+        let particle_count = self.sim.particle_count;
+        self.sim
+            .lod
+            .set_adaptive(mode, target_render_ms, particle_count);
+    }
+
+    pub fn get_lod_mode(&self) -> LodMode {
+        self.sim.lod.mode()
+    }
+
+    // 直前のフレームで何個に1個描いたか（間引いていなければ 1）
+    pub fn get_lod_skip(&self) -> u32 {
+        self.sim.visible_skip()
+    }
+
+    // 直前のフレームで描いたパーティクル数
+    pub fn get_visible_particle_count(&self) -> usize {
+        self.sim.visible().len()
+    }
+
+    // 全部を1回の描画命令で描くので、描き方は Batched だけ
+    pub fn set_draw_strategy(&mut self, strategy: DrawStrategy) -> Result<(), JsValue> {
+        if strategy != DrawStrategy::Batched {
+            return Err(tr(
+                Text::DrawStrategyUnavailable,
+                &[&strategy.name(), &"webgpu"],
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn get_draw_strategy(&self) -> DrawStrategy {
+        DrawStrategy::Batched
+    }
+
+    // 頂点データは毎フレーム write_buffer で送るので、送り方は BufferData だけ
+    pub fn set_upload_strategy(&mut self, strategy: UploadStrategy) -> Result<(), JsValue> {
+        if strategy != UploadStrategy::BufferData {
+            return Err(tr(
+                Text::UploadStrategyUnavailable,
+                &[&strategy.name(), &"webgpu"],
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    pub fn get_upload_strategy(&self) -> UploadStrategy {
+        UploadStrategy::BufferData
+    }
+
+    // 名前の付いた設定の組（合成方法・点の大きさ・描画解像度・精度・送り方・間引き・fps の上限）をまとめて当てる
+    // 組の名前は get_config_fingerprint() に入るので、同じ組で測った結果同士を比べられる
+    pub fn apply_tuning_profile(&mut self, profile: TuningProfile) -> Result<(), JsValue> {
+        let tuning = profile.settings(BackendKind::WebGpu);
+        self.set_upload_strategy(tuning.upload_strategy)?;
+        self.set_draw_strategy(tuning.draw_strategy)?;
+        self.set_render_mode(tuning.render_mode)?;
+        self.set_point_size(tuning.point_size);
+        self.set_render_scale(tuning.render_scale);
+        match tuning.adaptive_lod {
+            Some((mode, target_render_ms)) => self.set_adaptive_lod(mode, target_render_ms),
+            None => self.set_lod(LodMode::Off, 1),
+        }
+        self.set_target_fps(tuning.target_fps);
+        self.tuning = Some(profile);
+        Ok(())
+    }
+
+    // 最後に apply_tuning_profile() で当てた組（当てていなければ undefined）
+    pub fn get_tuning_profile(&self) -> Option<TuningProfile> {
+        self.tuning
+    }
+
+    // 位置と色は別々のバッファで送るので、並べ方は Planar だけ
+    pub fn set_buffer_layout(&mut self, layout: BufferLayout) -> Result<(), JsValue> {
+        if layout != BufferLayout::Planar {
+            return Err(tr(Text::BufferLayoutUnavailable, &[&layout.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_buffer_layout(&self) -> BufferLayout {
+        BufferLayout::Planar
+    }
+
+    // 色をシェーダーで計算できるのは今のところ WebGL だけなので、Cpu だけ
+    pub fn set_color_mode(&mut self, mode: ColorPipeline) -> Result<(), JsValue> {
+        if mode != ColorPipeline::Cpu {
+            return Err(tr(Text::ColorModeUnavailable, &[&mode.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_color_mode(&self) -> ColorPipeline {
+        ColorPipeline::Cpu
+    }
+
+    // パーティクルの塗り方を切り替える（既定は RainbowCycle）
+    // colors は Palette と Monochrome の色を 0〜1 の r, g, b の順に並べたもの（ほかのモードでは空でよい）
+    pub fn set_particle_color_mode(
+        &mut self,
+        mode: ColorMode,
+        colors: Vec<f32>,
+    ) -> Result<(), JsValue> {
+        self.sim.theme.set_mode(mode, &colors)?;
+        Ok(())
+    }
+
+    pub fn get_particle_color_mode(&self) -> ColorMode {
+        self.sim.theme.mode()
+    }
+
+    // 背景色を 0〜1 の RGB で設定する（有限でない値を渡すと既定の灰色に戻す）
+    pub fn set_background_color(&mut self, r: f32, g: f32, b: f32) {
+        self.sim.theme.set_background(r, g, b);
+    }
+
+    // [r, g, b]
+    pub fn get_background_color(&self) -> Vec<f32> {
+        self.sim.theme.background().to_vec()
+    }
+
+    // 透視投影できるのは今のところ WebGL と Canvas2D だけなので、Flat だけ
+    pub fn set_projection_mode(&mut self, mode: ProjectionMode) -> Result<(), JsValue> {
+        if mode != ProjectionMode::Flat {
+            return Err(tr(Text::ProjectionModeUnavailable, &[&mode.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_projection_mode(&self) -> ProjectionMode {
+        ProjectionMode::Flat
+    }
+
+    // GPU で物理演算できるのは今のところ WebGL2 の transform feedback だけなので、Cpu だけ
+    pub fn set_physics_mode(&mut self, mode: PhysicsMode) -> Result<(), JsValue> {
+        if mode != PhysicsMode::Cpu {
+            return Err(tr(Text::PhysicsModeUnavailable, &[&mode.name(), &"webgpu"]).into());
+        }
+        Ok(())
+    }
+
+    pub fn get_physics_mode(&self) -> PhysicsMode {
+        PhysicsMode::Cpu
+    }
+
+    // update() と爆発の計算を threads 個に分けて並列に行う（threads フィーチャーが必要）
+    // 実際に使う数を返す。フィーチャーなしでは1、ありでもプールのスレッド数までに丸める
+    pub fn set_thread_count(&mut self, threads: usize) -> usize {
+        let applied = self.sim.set_threads(threads);
+        if applied != threads {
+            self.events.emit(
+                EventKind::ParameterClamped,
+                &tr(Text::ThreadCountClamped, &[&threads, &applied]),
+            );
+        }
+        applied
+    }
+
+    pub fn get_thread_count(&self) -> usize {
+        self.sim.threads()
+    }
+
+    // 物理演算と色の変換を SIMD 版（4個ずつ）にするか切り替える。実際に使うかを返す
+    // simd フィーチャーなしでビルドしたときはスカラー版のまま
+    pub fn set_simd_enabled(&mut self, enabled: bool) -> bool {
+        let applied = self.sim.set_simd(enabled);
+        if applied != enabled {
+            self.events
+                .emit(EventKind::FallbackUsed, &tr(Text::SimdUnavailable, &[]));
+        }
+        applied
+    }
+
+    pub fn get_simd_enabled(&self) -> bool {
+        self.sim.simd()
+    }
+
+    // パーティクル同士の衝突を切り替える（格子で近いものだけ調べるが、壁だけのときよりずっと重い）
+    pub fn set_collisions_enabled(&mut self, enabled: bool) {
+        self.sim.set_collisions(enabled);
+    }
+
+    pub fn get_collisions_enabled(&self) -> bool {
+        self.sim.collisions()
+    }
+
+    // 全体に一様に吹く風（毎フレーム速度に足す px/フレーム²。重力と同じ単位）。(0, 0) で止める
+    pub fn set_wind(&mut self, x: f32, y: f32) {
+        self.sim.forces.set_wind(x, y);
+    }
+
+    // [x, y]
+    pub fn get_wind(&self) -> Vec<f32> {
+        let (x, y) = self.sim.forces.wind();
+        vec![x, y]
+    }
+
+    // パーティクルごとに curl ノイズの流れを加える（strength が 0 なら止める）
+    // scale は渦のおおよその大きさ(px)。場はゆっくり流れるので同じ場所でも向きが変わっていく
+    pub fn set_turbulence(&mut self, strength: f32, scale: f32) {
+        self.sim.forces.set_turbulence(strength, scale);
+    }
+
+    // [strength, scale]（止めていれば None）
+    pub fn get_turbulence(&self) -> Option<Vec<f32>> {
+        self.sim
+            .forces
+            .turbulence()
+            .map(|(strength, scale)| vec![strength, scale])
+    }
+
+    // 直近の update() で重なっていたパーティクルの組の数
+    pub fn get_contact_count(&self) -> usize {
+        self.sim.last_contacts()
+    }
+}
+
+impl ParticleSystemWebGpu {
+    pub(crate) fn emit_event(&mut self, kind: EventKind, message: &str) {
+        self.events.emit(kind, message);
+    }
+
+    // 送った描画が終わるのを同期して待つ方法がない（onSubmittedWorkDone() は Promise）ので false
+    pub(crate) fn finish_gpu(&self) -> bool {
+        false
+    }
+
+    // attach_input_handlers() で積まれた操作を取り出す（視点の操作はここで反映する）
+    pub(crate) fn take_input(&mut self) -> Vec<InputAction> {
+        match &self.input {
+            Some(input) => input.take_actions(&mut self.sim.camera),
+            None => Vec::new(),
+        }
+    }
+
+    pub(crate) fn input_handlers(&self) -> Option<&InputHandlers> {
+        self.input.as_ref()
+    }
+
+    // 重ねて描く方法がまだない（Kiosk は WebGPU を使わない）
+    pub(crate) fn draw_overlay(&mut self, _rects: &[OverlayRect]) -> bool {
+        false
+    }
+
+    fn apply_input(&mut self) {
+        for action in self.take_input() {
+            match action {
+                InputAction::Explode { x, y } => self.explode(x, y),
+                InputAction::Stir { x, y, dx, dy } => self.stir(x, y, dx, dy),
+            }
+        }
+    }
+
+    pub(crate) fn advance_timestep(&mut self, dt_ms: f64) -> u32 {
+        self.sim.timestep.advance(dt_ms)
+    }
+
+    pub(crate) fn cap_frame(&mut self, timestamp: f64) -> bool {
+        self.sim.frame_cap.tick(timestamp)
+    }
+
+    // update_with_dt() のステップの後で、描く位置を補間する
+    pub(crate) fn interpolate_frame(&mut self) {
+        self.sim.interpolate();
+    }
+
+    // シーンを描かないのでアトラスもない
+    pub(crate) fn scene_atlas_mut(&mut self) -> Option<&mut Atlas> {
+        None
+    }
+
+    // シミュレーション空間の大きさ（記録する座標を割合に直すのに使う）
+    pub(crate) fn size(&self) -> (f32, f32) {
+        (self.sim.width, self.sim.height)
+    }
+
+    fn config_fingerprint(&self) -> ConfigFingerprint {
+        let mut config = ConfigFingerprint::new("webgpu");
+        config.field("strict", self.strict);
+        config.field("width", self.sim.width);
+        config.field("height", self.sim.height);
+        config.field("render_scale", self.sizing.render_scale());
+        config.field("out_of_bounds", self.sim.out_of_bounds.name());
+        config.field("boundary_mode", self.sim.boundary.name());
+        config.field("particle_color_mode", self.sim.theme.mode().name());
+        config.field("projection_mode", self.sim.projection().name());
+        config.field("gravity", self.sim.config.gravity);
+        config.field("bounce", self.sim.config.bounce);
+        config.field("friction", self.sim.config.friction);
+        config.field("point_size", self.sim.config.point_size);
+        config.field("threads", self.sim.threads());
+        config.field("simd", self.sim.simd());
+        config.field("collisions", self.sim.collisions());
+        config.field("attractors", self.sim.attractors.len());
+        config.field("forces", self.sim.forces.describe());
+        config.field("obstacles", self.sim.obstacles.len());
+        config.field("emitters", self.sim.emitters.len());
+        config.field("particle_lifetime", self.sim.emitters.lifetime());
+        config.field("strict_benchmark", !self.sim.cosmetic);
+        config.field("particle_style", self.sim.style().describe());
+        config.field("quirks", self.quirks.fingerprint());
+        config.optional("tuning_profile", self.tuning.map(TuningProfile::name));
+        config.optional(
+            "settle_threshold",
+            self.sim.settle.map(|settle| settle.threshold),
+        );
+        config.optional("seed", self.sim.seed);
+        config.field("scene", SceneKind::Particles.name());
+        config.optional("load", self.load);
+        config.field("max_particles", self.sim.max_particles());
+        config.field("lod", self.sim.lod.describe());
+        config.field("particles", self.sim.particle_count);
+        config
+    }
+
+    // 描画バッファの大きさは毎フレーム getCurrentTexture() で追従する
+    fn apply_backing_size(&self) {
+        self.sizing
+            .apply(&self.context.canvas(), self.sim.width, self.sim.height);
+    }
+
+    fn apply_load(&mut self) {
+        if let Some(load) = self.load {
+            self.sim.set_load(load);
+        }
+    }
+
+    // 詰めたインスタンスがバッファに入りきらなければ、入る大きさで作り直す
+    fn reserve_instances(&mut self) -> Result<(), JsValue> {
+        let instances = self.positions.len() / 2;
+        if instances > self.instance_capacity {
+            self.position_buffer = create_vertex_buffer(&self.device, instances * 2)?;
+            self.color_buffer = create_vertex_buffer(&self.device, instances * 3)?;
+            self.instance_capacity = instances;
+        }
+        Ok(())
+    }
+
+    // インスタンスデータを送り、1つのレンダーパスで描いて送信する
+    fn encode_frame(&self) -> Result<(), JsValue> {
+        let queue = self.device.queue();
+        // 四角形の大きさはキャンバスの大きさで変わるので毎フレーム書く（8個の f32 だけ）
+        let canvas = self.context.canvas();
+        let (width, height) = canvas_size(&canvas);
+        let point_size =
+            self.sim.config.point_diameter() * self.sim.camera.zoom * self.sizing.backing_ratio();
+        let (sx, sy) = (point_size / width, point_size / height);
+        let corners = [-sx, -sy, sx, -sy, -sx, sy, sx, sy];
+        queue.write_buffer(&self.corner_buffer, 0.0, as_bytes(&corners))?;
+        queue.write_buffer(&self.position_buffer, 0.0, as_bytes(&self.positions))?;
+        queue.write_buffer(&self.color_buffer, 0.0, as_bytes(&self.colors))?;
+
+        let view = self.context.get_current_texture()?.create_view()?;
+        let [r, g, b] = self.sim.theme.background().map(|c| c as f64);
+        let clear = object(&[
+            ("r", r.into()),
+            ("g", g.into()),
+            ("b", b.into()),
+            ("a", 1.0.into()),
+        ]);
+        let attachment = object(&[
+            ("view", view),
+            ("loadOp", "clear".into()),
+            ("storeOp", "store".into()),
+            ("clearValue", clear.into()),
+        ]);
+        let pass_descriptor = object(&[("colorAttachments", array([attachment.into()]).into())]);
+
+        let encoder = self.device.create_command_encoder();
+        let pass = encoder.begin_render_pass(&pass_descriptor)?;
+        pass.set_pipeline(&self.pipeline);
+        for (slot, buffer) in [
+            &self.corner_buffer,
+            &self.position_buffer,
+            &self.color_buffer,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            pass.set_vertex_buffer(slot as u32, buffer);
+        }
+        pass.draw(4, (self.positions.len() / 2) as u32);
+        pass.end();
+        queue.submit(&array([encoder.finish()]));
+        Ok(())
+    }
+
+    pub(crate) async fn from_source(
+        source: &impl ContextSource<GpuCanvasContext>,
+        particle_count: usize,
+        options: BuildOptions,
+    ) -> Result<ParticleSystemWebGpu, JsValue> {
+        check_budget(particle_count, options.memory_budget)?;
+        let mut init = InitProgress::start();
+
+        // キャンバスから webgpu のコンテキストを取ると同じキャンバスで WebGL を使えなくなるので、
+        // 先にデバイスまで取得できることを確かめる
+        let gpu = gpu::gpu().ok_or(BenchError::WebGpuUnsupported)?;
+        // 使えるアダプターがなければ null で resolve される
+        let adapter = JsFuture::from(gpu.request_adapter()).await?;
+        if adapter.is_null() {
+            return Err(BenchError::WebGpuUnsupported.into());
+        }
+        let adapter: GpuAdapter = adapter.unchecked_into();
+        let device: GpuDevice = JsFuture::from(adapter.request_device())
+            .await?
+            .unchecked_into();
+
+        let AcquiredContext {
+            context,
+            width,
+            height,
+        } = source.acquire()?;
+        let format = gpu.get_preferred_canvas_format();
+        let configuration = object(&[
+            ("device", device.clone().into()),
+            ("format", format.as_str().into()),
+        ]);
+        context.configure(&configuration)?;
+        init.timings.context_ms = init.lap();
+
+        let pipeline = create_pipeline(&device, &format)?;
+        init.timings.shader_ms = init.lap();
+
+        let corner_buffer = create_vertex_buffer(&device, 8)?;
+        let position_buffer = create_vertex_buffer(&device, particle_count * 2)?;
+        let color_buffer = create_vertex_buffer(&device, particle_count * 3)?;
+        let positions = try_vec(particle_count * 2)?;
+        let colors = try_vec(particle_count * 3)?;
+        init.timings.buffers_ms = init.lap();
+
+        let mut sim = Simulation::with_capacity(width, height, particle_count)?;
+        sim.seed = options.seed;
+        if !options.progressive {
+            sim.spawn_pending(particle_count);
+        }
+        sim.config = options.simulation.clamped();
+        sim.memory_budget = options.memory_budget;
+        init.timings.particles_ms = init.lap();
+        init.finish_constructor();
+
+        Ok(ParticleSystemWebGpu {
+            sim,
+            device,
+            context,
+            pipeline,
+            corner_buffer,
+            position_buffer,
+            color_buffer,
+            instance_capacity: particle_count,
+            init,
+            positions,
+            colors,
+            load: None,
+            events: EventBus::default(),
+            strict: options.strict,
+            explosion: ExplosionConfig::default(),
+            quirks: Quirks::default(),
+            stats_stream: StatsStream::default(),
+            #[cfg(feature = "telemetry")]
+            telemetry: Telemetry::default(),
+            observers: Observers::default(),
+            timeline: Timeline::default(),
+            tuning: None,
+            sizing: CanvasSizing::default(),
+            input: None,
+        })
+    }
+}
+
+// floats 個の f32 を入れられる頂点バッファ（大きさ0のバッファは作れないので最低1個分）
+fn create_vertex_buffer(device: &GpuDevice, floats: usize) -> Result<GpuBuffer, JsValue> {
+    let size = floats.max(1) * std::mem::size_of::<f32>();
+    device.create_buffer(&object(&[
+        ("size", (size as f64).into()),
+        (
+            "usage",
+            (gpu::BUFFER_USAGE_VERTEX | gpu::BUFFER_USAGE_COPY_DST).into(),
+        ),
+    ]))
+}
+
+// 頂点バッファ 0: 四角形の頂点、1: インスタンスの位置、2: インスタンスの色
+fn create_pipeline(device: &GpuDevice, format: &str) -> Result<GpuRenderPipeline, JsValue> {
+    let module = device.create_shader_module(&object(&[("code", SHADER_SOURCE.into())]));
+
+    let layouts = [
+        ("float32x2", 2, "vertex"),
+        ("float32x2", 2, "instance"),
+        ("float32x3", 3, "instance"),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(location, (format, floats, step_mode))| {
+        let attribute = object(&[
+            ("format", format.into()),
+            ("offset", 0.into()),
+            ("shaderLocation", (location as u32).into()),
+        ]);
+        let stride = (floats * std::mem::size_of::<f32>()) as u32;
+        object(&[
+            ("arrayStride", stride.into()),
+            ("stepMode", step_mode.into()),
+            ("attributes", array([attribute.into()]).into()),
+        ])
+        .into()
+    });
+    let vertex = object(&[
+        ("module", module.clone().into()),
+        ("entryPoint", "vs_main".into()),
+        ("buffers", array(layouts).into()),
+    ]);
+
+    let target = object(&[("format", format.into())]);
+    let fragment = object(&[
+        ("module", module.into()),
+        ("entryPoint", "fs_main".into()),
+        ("targets", array([target.into()]).into()),
+    ]);
+
+    let primitive = object(&[("topology", "triangle-strip".into())]);
+
+    device.create_render_pipeline(&object(&[
+        ("layout", "auto".into()),
+        ("vertex", vertex.into()),
+        ("fragment", fragment.into()),
+        ("primitive", primitive.into()),
+    ]))
+}
+
+fn canvas_size(canvas: &js_sys::Object) -> (f32, f32) {
+    // HtmlCanvasElement と OffscreenCanvas のどちらでも width/height で取れる
+    let get = |key: &str| {
+        js_sys::Reflect::get(canvas, &key.into())
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(1.0)
+            .max(1.0) as f32
+    };
+    (get("width"), get("height"))
+}
+
+fn as_bytes(data: &[f32]) -> &[u8] {
+    // f32 の配列はそのままバイト列として読める（アラインメントは u8 の方が緩い）
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, std::mem::size_of_val(data)) }
+}
+
+// 頂点・フラグメントシェーダー（WGSL）
+const SHADER_SOURCE: &str = r#"
+    struct VertexOut {
+        @builtin(position) position: vec4<f32>,
+        @location(0) color: vec3<f32>,
+    };
+
+    @vertex
+    fn vs_main(
+        @location(0) corner: vec2<f32>,
+        @location(1) position: vec2<f32>,
+        @location(2) color: vec3<f32>,
+    ) -> VertexOut {
+        var out: VertexOut;
+        out.position = vec4<f32>(position + corner, 0.0, 1.0);
+        out.color = color;
+        return out;
+    }
+
+    @fragment
+    fn fs_main(@location(0) color: vec3<f32>) -> @location(0) vec4<f32> {
+        return vec4<f32>(color, 0.8);
+    }
+"#;
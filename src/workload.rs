@@ -0,0 +1,482 @@
+// 実際の操作（クリックでの爆発・シーン切り替え・負荷の変更など）をフレーム番号つきで記録し、
+// 別の端末で同じ順・同じタイミングで再生する
+// 合成した一定の負荷ではなく、同じ実際の使われ方で端末どうしを比べるために使う
+//
+// 座標はキャンバスの大きさに対する割合（0.0〜1.0）で持つので、画面の大きさが違っても同じ位置に当たる
+// resize() だけは大きさ(px)をそのまま残し、再生でも同じ大きさにする
+//
+// JSON の形式（WORKLOAD_VERSION）
+// 1: {"version", "frames", "particle_count", "scene", "steps": [{"frame", "action", ...}]}
+// 2: 1 に引力点の操作（add/remove/move/clear_attractor(s)）と resize を足したもの（1 もそのまま読める）
+// 3: 2 に障害物の操作（add_circle_obstacle, add_line_obstacle, clear_obstacles）を足したもの
+// 4: 3 にポインターの動き（pointer_move, pointer_drag）を足したもの
+
+use wasm_bindgen::prelude::*;
+
+use crate::i18n::{tr, Text};
+use crate::json::{self, JsonObject, Value};
+use crate::scene::SceneKind;
+
+pub const WORKLOAD_VERSION: u32 = 4;
+
+// 記録する操作
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Action {
+    Explode {
+        x: f32,
+        y: f32,
+    },
+    Implode {
+        x: f32,
+        y: f32,
+    },
+    // dx, dy も x, y と同じく大きさで割った値
+    Stir {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+    ScheduleExplosion {
+        x: f32,
+        y: f32,
+        delay_frames: u32,
+    },
+    // dx, dy も x, y と同じく大きさで割った値
+    PointerMove {
+        x: f32,
+        y: f32,
+        dx: f32,
+        dy: f32,
+    },
+    // 動いた量は再生でも前の位置から求める
+    PointerDrag {
+        x: f32,
+        y: f32,
+        strength: f32,
+    },
+    SwitchScene(SceneKind),
+    SetLoad(f32),
+    Reset,
+    // id は記録したときに振られた番号（再生では実際に振られた番号に読み替える）
+    AddAttractor {
+        x: f32,
+        y: f32,
+        strength: f32,
+        radius: f32,
+        id: u32,
+    },
+    RemoveAttractor {
+        id: u32,
+    },
+    MoveAttractor {
+        id: u32,
+        x: f32,
+        y: f32,
+    },
+    ClearAttractors,
+    // 障害物の位置はキャンバスの外にはみ出してもよい（負や 1.0 より大きい値もある）
+    AddCircleObstacle {
+        x: f32,
+        y: f32,
+        radius: f32,
+    },
+    AddLineObstacle {
+        x0: f32,
+        y0: f32,
+        x1: f32,
+        y1: f32,
+    },
+    ClearObstacles,
+    Resize {
+        width: f32,
+        height: f32,
+    },
+}
+
+impl Action {
+    fn name(&self) -> &'static str {
+        match self {
+            Action::Explode { .. } => "explode",
+            Action::Implode { .. } => "implode",
+            Action::Stir { .. } => "stir",
+            Action::ScheduleExplosion { .. } => "schedule_explosion",
+            Action::PointerMove { .. } => "pointer_move",
+            Action::PointerDrag { .. } => "pointer_drag",
+            Action::SwitchScene(_) => "switch_scene",
+            Action::SetLoad(_) => "set_load",
+            Action::Reset => "reset",
+            Action::AddAttractor { .. } => "add_attractor",
+            Action::RemoveAttractor { .. } => "remove_attractor",
+            Action::MoveAttractor { .. } => "move_attractor",
+            Action::ClearAttractors => "clear_attractors",
+            Action::AddCircleObstacle { .. } => "add_circle_obstacle",
+            Action::AddLineObstacle { .. } => "add_line_obstacle",
+            Action::ClearObstacles => "clear_obstacles",
+            Action::Resize { .. } => "resize",
+        }
+    }
+}
+
+// frame 回目の update() の直前に action を行う
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Step {
+    pub frame: u32,
+    pub action: Action,
+}
+
+impl Step {
+    fn to_json(&self) -> String {
+        let object = JsonObject::new()
+            .number("frame", self.frame as f64)
+            .string("action", self.action.name());
+        match self.action {
+            Action::Explode { x, y } | Action::Implode { x, y } => {
+                object.number("x", x as f64).number("y", y as f64)
+            }
+            Action::Stir { x, y, dx, dy } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("dx", dx as f64)
+                .number("dy", dy as f64),
+            Action::ScheduleExplosion { x, y, delay_frames } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("delay_frames", delay_frames as f64),
+            Action::PointerMove { x, y, dx, dy } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("dx", dx as f64)
+                .number("dy", dy as f64),
+            Action::PointerDrag { x, y, strength } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("strength", strength as f64),
+            Action::SwitchScene(kind) => object.string("scene", kind.name()),
+            Action::SetLoad(load) => object.number("load", load as f64),
+            Action::AddAttractor {
+                x,
+                y,
+                strength,
+                radius,
+                id,
+            } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("strength", strength as f64)
+                .number("radius", radius as f64)
+                .number("id", id as f64),
+            Action::RemoveAttractor { id } => object.number("id", id as f64),
+            Action::MoveAttractor { id, x, y } => object
+                .number("id", id as f64)
+                .number("x", x as f64)
+                .number("y", y as f64),
+            Action::AddCircleObstacle { x, y, radius } => object
+                .number("x", x as f64)
+                .number("y", y as f64)
+                .number("radius", radius as f64),
+            Action::AddLineObstacle { x0, y0, x1, y1 } => object
+                .number("x0", x0 as f64)
+                .number("y0", y0 as f64)
+                .number("x1", x1 as f64)
+                .number("y1", y1 as f64),
+            Action::Resize { width, height } => object
+                .number("width", width as f64)
+                .number("height", height as f64),
+            Action::Reset | Action::ClearAttractors | Action::ClearObstacles => object,
+        }
+        .finish()
+    }
+}
+
+// 記録した操作の列
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Workload {
+    // 記録していた間の update() の回数（再生はこのフレーム数で終わる）
+    frames: u32,
+    // 記録したときのパーティクル数とシーン（再生前にこれに揃える）
+    particle_count: usize,
+    scene: SceneKind,
+    steps: Vec<Step>,
+}
+
+#[wasm_bindgen]
+impl Workload {
+    pub fn from_json(text: &str) -> Result<Workload, JsValue> {
+        Ok(Workload::parse(text)?)
+    }
+
+    pub fn to_json(&self) -> String {
+        JsonObject::new()
+            .number("version", WORKLOAD_VERSION as f64)
+            .number("frames", self.frames as f64)
+            .number("particle_count", self.particle_count as f64)
+            .string("scene", self.scene.name())
+            .raw("steps", &json::array(self.steps.iter().map(Step::to_json)))
+            .finish()
+    }
+
+    pub fn get_frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn get_particle_count(&self) -> usize {
+        self.particle_count
+    }
+
+    pub fn get_scene(&self) -> SceneKind {
+        self.scene
+    }
+
+    // 記録した操作の数
+    pub fn get_step_count(&self) -> usize {
+        self.steps.len()
+    }
+}
+
+impl Workload {
+    // from_json() の中身（JsValue を使わないので、ネイティブの fuzz からも呼べる）
+    pub(crate) fn parse(text: &str) -> Result<Workload, String> {
+        let value = json::parse(text)
+            .filter(|value| matches!(value, Value::Object(_)))
+            .ok_or_else(|| tr(Text::WorkloadInvalid, &[&"JSON"]))?;
+        let version = number(&value, "version")?;
+        if !(1.0..=WORKLOAD_VERSION as f64).contains(&version) || version.fract() != 0.0 {
+            return Err(tr(Text::WorkloadVersionUnsupported, &[&version]));
+        }
+        let scene = string(&value, "scene")?;
+        let scene =
+            SceneKind::from_name(&scene).ok_or_else(|| tr(Text::WorkloadInvalid, &[&scene]))?;
+        let mut steps = value
+            .get("steps")
+            .and_then(Value::as_array)
+            .ok_or_else(|| tr(Text::WorkloadInvalid, &[&"steps"]))?
+            .iter()
+            .map(parse_step)
+            .collect::<Result<Vec<_>, _>>()?;
+        // 手で編集した記録でも再生できるように、同じフレームの中の順は保ったまま並べ直す
+        steps.sort_by_key(|step| step.frame);
+        let frames = number(&value, "frames")? as u32;
+        if steps.iter().any(|step| step.frame > frames) {
+            return Err(tr(Text::WorkloadInvalid, &[&"frames"]));
+        }
+        Ok(Workload {
+            frames,
+            particle_count: number(&value, "particle_count")? as usize,
+            scene,
+            steps,
+        })
+    }
+}
+
+fn number(object: &Value, key: &str) -> Result<f64, String> {
+    object
+        .get(key)
+        .and_then(Value::as_f64)
+        .filter(|value| value.is_finite() && *value >= 0.0)
+        .ok_or_else(|| tr(Text::WorkloadInvalid, &[&key]))
+}
+
+// 負の値も許す数（引力点の強さは負なら斥力、障害物は画面の外にはみ出してもよい）
+fn signed(object: &Value, key: &str) -> Result<f64, String> {
+    object
+        .get(key)
+        .and_then(Value::as_f64)
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| tr(Text::WorkloadInvalid, &[&key]))
+}
+
+fn string(object: &Value, key: &str) -> Result<String, String> {
+    object
+        .get(key)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| tr(Text::WorkloadInvalid, &[&key]))
+}
+
+fn parse_step(step: &Value) -> Result<Step, String> {
+    let frame = number(step, "frame")? as u32;
+    let position = || -> Result<(f32, f32), String> {
+        Ok((number(step, "x")? as f32, number(step, "y")? as f32))
+    };
+    let action = match string(step, "action")?.as_str() {
+        "explode" => {
+            let (x, y) = position()?;
+            Action::Explode { x, y }
+        }
+        "implode" => {
+            let (x, y) = position()?;
+            Action::Implode { x, y }
+        }
+        "stir" => {
+            let (x, y) = position()?;
+            Action::Stir {
+                x,
+                y,
+                dx: signed(step, "dx")? as f32,
+                dy: signed(step, "dy")? as f32,
+            }
+        }
+        "schedule_explosion" => {
+            let (x, y) = position()?;
+            let delay_frames = number(step, "delay_frames")? as u32;
+            Action::ScheduleExplosion { x, y, delay_frames }
+        }
+        "pointer_move" => {
+            let (x, y) = position()?;
+            Action::PointerMove {
+                x,
+                y,
+                dx: signed(step, "dx")? as f32,
+                dy: signed(step, "dy")? as f32,
+            }
+        }
+        "pointer_drag" => {
+            let (x, y) = position()?;
+            Action::PointerDrag {
+                x,
+                y,
+                strength: signed(step, "strength")? as f32,
+            }
+        }
+        "switch_scene" => {
+            let scene = string(step, "scene")?;
+            Action::SwitchScene(
+                SceneKind::from_name(&scene).ok_or_else(|| tr(Text::WorkloadInvalid, &[&scene]))?,
+            )
+        }
+        "set_load" => Action::SetLoad(number(step, "load")? as f32),
+        "reset" => Action::Reset,
+        "add_attractor" => {
+            let (x, y) = position()?;
+            Action::AddAttractor {
+                x,
+                y,
+                strength: signed(step, "strength")? as f32,
+                radius: number(step, "radius")? as f32,
+                id: number(step, "id")? as u32,
+            }
+        }
+        "remove_attractor" => Action::RemoveAttractor {
+            id: number(step, "id")? as u32,
+        },
+        "move_attractor" => {
+            let (x, y) = position()?;
+            Action::MoveAttractor {
+                id: number(step, "id")? as u32,
+                x,
+                y,
+            }
+        }
+        "clear_attractors" => Action::ClearAttractors,
+        "add_circle_obstacle" => Action::AddCircleObstacle {
+            x: signed(step, "x")? as f32,
+            y: signed(step, "y")? as f32,
+            radius: number(step, "radius")? as f32,
+        },
+        "add_line_obstacle" => Action::AddLineObstacle {
+            x0: signed(step, "x0")? as f32,
+            y0: signed(step, "y0")? as f32,
+            x1: signed(step, "x1")? as f32,
+            y1: signed(step, "y1")? as f32,
+        },
+        "clear_obstacles" => Action::ClearObstacles,
+        "resize" => Action::Resize {
+            width: number(step, "width")? as f32,
+            height: number(step, "height")? as f32,
+        },
+        other => return Err(tr(Text::WorkloadInvalid, &[&other])),
+    };
+    Ok(Step { frame, action })
+}
+
+// Backend に操作が来るたびに、何フレーム目だったかと合わせて残す
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Recorder {
+    frames: u32,
+    particle_count: usize,
+    scene: SceneKind,
+    steps: Vec<Step>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Recorder {
+    pub fn new(particle_count: usize, scene: SceneKind) -> Recorder {
+        Recorder {
+            frames: 0,
+            particle_count,
+            scene,
+            steps: Vec::new(),
+        }
+    }
+
+    pub fn record(&mut self, action: Action) {
+        self.steps.push(Step {
+            frame: self.frames,
+            action,
+        });
+    }
+
+    pub fn advance(&mut self) {
+        self.frames += 1;
+    }
+
+    pub fn finish(self) -> Workload {
+        Workload {
+            frames: self.frames,
+            particle_count: self.particle_count,
+            scene: self.scene,
+            steps: self.steps,
+        }
+    }
+}
+
+// 記録の操作を update() のたびに順に取り出す
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Player {
+    workload: Workload,
+    frame: u32,
+    next: usize,
+    // (記録したときの引力点の番号, 再生で振られた番号)
+    attractors: Vec<(u32, u32)>,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Player {
+    pub fn new(workload: Workload) -> Player {
+        Player {
+            workload,
+            frame: 0,
+            next: 0,
+            attractors: Vec::new(),
+        }
+    }
+
+    pub fn map_attractor(&mut self, recorded: u32, actual: u32) {
+        self.attractors.push((recorded, actual));
+    }
+
+    // 記録したときの引力点の番号を再生での番号に読み替える（再生中に置かれていなければ None）
+    pub fn attractor(&self, recorded: u32) -> Option<u32> {
+        self.attractors
+            .iter()
+            .find(|(id, _)| *id == recorded)
+            .map(|&(_, actual)| actual)
+    }
+
+    // 今のフレームで行う操作を返し、フレームを1つ進める
+    pub fn advance(&mut self) -> &[Step] {
+        let start = self.next;
+        let steps = &self.workload.steps;
+        while self.next < steps.len() && steps[self.next].frame <= self.frame {
+            self.next += 1;
+        }
+        self.frame += 1;
+        &steps[start..self.next]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.workload.frames
+    }
+}
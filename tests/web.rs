@@ -0,0 +1,159 @@
+// ブラウザの中で動かす結合テスト（wasm-pack test --headless --chrome / --firefox）
+// 物理演算の性質（エネルギーが増えない・領域から出ない・同じシードならバックエンドが違っても同じ状態）と、
+// 画面に出していないキャンバスへの WebGL の描画を確かめる
+// WebGL が使えない環境（GPU のない CI など）では WebGL のテストは何もせずに終わる
+#![cfg(target_arch = "wasm32")]
+
+use particle_webgl::canvas2d::ParticleSystemCanvas2D;
+use particle_webgl::frame_capture::compare_frames;
+use particle_webgl::physics::SimulationConfig;
+use particle_webgl::simulation::ParticleSimulation;
+use particle_webgl::webgl2::ParticleSystemWebGl2;
+use particle_webgl::ParticleSystem;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_test::*;
+use web_sys::{HtmlCanvasElement, OffscreenCanvas};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+const WIDTH: u32 = 320;
+const HEIGHT: u32 = 240;
+const PARTICLES: usize = 2_000;
+const SEED: u64 = 42;
+const FRAMES: u32 = 300;
+
+// 既定の重力は小さすぎて位置エネルギーがほとんど変わらないので、強めにして床で跳ねさせる
+fn config() -> SimulationConfig {
+    SimulationConfig {
+        gravity: 0.05,
+        bounce: 0.85,
+        friction: 0.98,
+        point_size: 2.5,
+    }
+}
+
+fn simulation() -> ParticleSimulation {
+    let mut sim = ParticleSimulation::new(WIDTH as f32, HEIGHT as f32, PARTICLES).unwrap();
+    sim.set_simulation_config(&config());
+    sim.set_seed(SEED);
+    sim
+}
+
+// 単位質量あたりの力学的エネルギーの合計（y は下向きなので、床からの高さは HEIGHT - y）
+fn energy(sim: &ParticleSimulation) -> f64 {
+    let gravity = sim.get_simulation_config().gravity as f64;
+    let (vx, vy, y) = (sim.get_vx(), sim.get_vy(), sim.get_y());
+    vx.iter()
+        .zip(&vy)
+        .zip(&y)
+        .map(|((&vx, &vy), &y)| {
+            let (vx, vy, y) = (vx as f64, vy as f64, y as f64);
+            (vx * vx + vy * vy) / 2.0 + gravity * (HEIGHT as f64 - y)
+        })
+        .sum()
+}
+
+fn canvas() -> HtmlCanvasElement {
+    let canvas: HtmlCanvasElement = web_sys::window()
+        .unwrap()
+        .document()
+        .unwrap()
+        .create_element("canvas")
+        .unwrap()
+        .dyn_into()
+        .unwrap();
+    canvas.set_width(WIDTH);
+    canvas.set_height(HEIGHT);
+    canvas
+}
+
+#[wasm_bindgen_test]
+fn energy_never_increases_without_explosions() {
+    let mut sim = simulation();
+    let mut previous = energy(&sim);
+    for frame in 0..FRAMES {
+        sim.update();
+        let current = energy(&sim);
+        // 半陰的オイラー法では1ステップごとに g²/2 ずつ減り、壁では跳ね返りの係数の分だけ減る
+        // f32 の丸めの分だけ許す
+        assert!(
+            current <= previous * (1.0 + 1e-5) + 1e-3,
+            "energy grew at frame {}: {} -> {}",
+            frame,
+            previous,
+            current
+        );
+        previous = current;
+    }
+}
+
+#[wasm_bindgen_test]
+fn particles_stay_in_bounds() {
+    let mut sim = simulation();
+    for frame in 0..FRAMES {
+        // 爆発で速度が大きくなっても、壁で跳ね返って領域の中に戻る
+        if frame % 60 == 0 {
+            sim.explode(WIDTH as f32 / 2.0, HEIGHT as f32 / 2.0);
+        }
+        sim.update();
+        for (&x, &y) in sim.get_x().iter().zip(&sim.get_y()) {
+            assert!(
+                (0.0..=WIDTH as f32).contains(&x) && (0.0..=HEIGHT as f32).contains(&y),
+                "particle out of bounds at frame {}: ({}, {})",
+                frame,
+                x,
+                y
+            );
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn seeded_backends_match() {
+    let mut canvas2d = ParticleSystemCanvas2D::from_canvas(canvas(), PARTICLES).unwrap();
+    canvas2d.set_seed(SEED);
+    for _ in 0..FRAMES {
+        canvas2d.update();
+    }
+    let expected = canvas2d.snapshot();
+
+    if let Ok(mut webgl) = ParticleSystem::from_canvas(canvas(), PARTICLES) {
+        webgl.set_seed(SEED);
+        for _ in 0..FRAMES {
+            webgl.update();
+        }
+        assert!(webgl.snapshot() == expected, "webgl diverged from canvas2d");
+    }
+    if let Ok(mut webgl2) = ParticleSystemWebGl2::from_canvas(canvas(), PARTICLES) {
+        webgl2.set_seed(SEED);
+        for _ in 0..FRAMES {
+            webgl2.update();
+        }
+        assert!(webgl2.snapshot() == expected, "webgl2 diverged from canvas2d");
+    }
+}
+
+// 同じシードで同じフレームまで進めて描いた2つは、ピクセルまで同じになる
+#[wasm_bindgen_test]
+fn gl_renderer_draws_to_offscreen_canvas() {
+    let draw = || -> Option<Vec<u8>> {
+        let offscreen = OffscreenCanvas::new(WIDTH, HEIGHT).ok()?;
+        let mut system = ParticleSystem::from_offscreen_canvas(offscreen, PARTICLES).ok()?;
+        system.set_seed(SEED);
+        for _ in 0..30 {
+            system.update();
+        }
+        system.render();
+        Some(system.capture_frame().unwrap())
+    };
+    let Some(frame) = draw() else {
+        return;
+    };
+    // 背景だけでなくパーティクルが描かれている
+    let background = &frame[..4];
+    assert!(frame.chunks_exact(4).any(|pixel| pixel != background));
+
+    let again = draw().unwrap();
+    let diff = compare_frames(&frame, &again).unwrap();
+    assert_eq!(diff.differing_pixels, 0, "same seed drew different pixels");
+}